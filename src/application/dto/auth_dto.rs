@@ -11,6 +11,8 @@ pub enum TokenSource {
     Keyring,
     /// Token entered by user.
     UserInput,
+    /// Token from scanning a QR code with the mobile app.
+    QrCode,
 }
 
 impl TokenSource {
@@ -21,6 +23,7 @@ impl TokenSource {
             Self::Environment => "environment variable",
             Self::Keyring => "system keyring",
             Self::UserInput => "user input",
+            Self::QrCode => "QR code login",
         }
     }
 }
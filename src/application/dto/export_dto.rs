@@ -0,0 +1,41 @@
+//! History export DTOs.
+
+/// File format for exported messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// A JSON array of messages.
+    #[default]
+    Json,
+    /// One human-readable line per message.
+    Txt,
+}
+
+/// Which part of a channel's history to export.
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    /// Channel to read.
+    pub channel_id: u64,
+    /// Output format.
+    pub format: ExportFormat,
+    /// Only messages newer than this message id.
+    pub after: Option<u64>,
+    /// Only messages older than this message id.
+    pub before: Option<u64>,
+    /// Stop after this many messages, counted back from `before` when it is
+    /// the only bound.
+    pub limit: Option<usize>,
+}
+
+impl ExportRequest {
+    /// Creates a request for a channel's whole history as JSON.
+    #[must_use]
+    pub const fn new(channel_id: u64) -> Self {
+        Self {
+            channel_id,
+            format: ExportFormat::Json,
+            after: None,
+            before: None,
+            limit: None,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Data transfer objects for the application layer.
 
 mod auth_dto;
+mod export_dto;
 
 pub use auth_dto::{LoginRequest, LoginResponse, TokenSource};
+pub use export_dto::{ExportFormat, ExportRequest};
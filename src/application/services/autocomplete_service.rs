@@ -1,17 +1,84 @@
-use crate::domain::entities::CachedUser;
+use crate::application::services::identity_resolver::IdentityResolver;
+use crate::domain::entities::{CachedUser, Channel, Role};
+
+/// What the typed trigger asks to mention: `@` users, `@&` roles, `#` channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MentionKind {
+    #[default]
+    User,
+    Role,
+    Channel,
+}
+
+impl MentionKind {
+    /// The characters that start a mention of this kind.
+    #[must_use]
+    pub const fn trigger(self) -> &'static str {
+        match self {
+            Self::User => "@",
+            Self::Role => "@&",
+            Self::Channel => "#",
+        }
+    }
+}
+
+/// Something the mention popup can suggest.
+#[derive(Debug, Clone)]
+pub enum MentionCandidate {
+    User(CachedUser),
+    Role(Role),
+    Channel(Channel),
+}
+
+impl MentionCandidate {
+    /// Discord markup the mention is sent as.
+    #[must_use]
+    pub fn markup(&self) -> String {
+        match self {
+            Self::User(user) => format!("<@{}>", user.id()),
+            Self::Role(role) => format!("<@&{}>", role.id),
+            Self::Channel(channel) => format!("<#{}>", channel.id()),
+        }
+    }
+
+    /// Name the mention popup shows for this candidate.
+    #[must_use]
+    pub fn label(&self, resolver: &IdentityResolver) -> String {
+        match self {
+            Self::User(user) => resolver.resolve(user),
+            Self::Role(role) => role.name.clone(),
+            Self::Channel(channel) => channel.name().to_string(),
+        }
+    }
+
+    fn matches(&self, lower_query: &str) -> bool {
+        match self {
+            Self::User(user) => {
+                user.username().to_lowercase().contains(lower_query)
+                    || user.display_name().to_lowercase().contains(lower_query)
+            }
+            Self::Role(role) => role.name.to_lowercase().contains(lower_query),
+            Self::Channel(channel) => channel.name().to_lowercase().contains(lower_query),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct AutocompleteState {
     pub active: bool,
+    pub kind: MentionKind,
     pub query: String,
     pub trigger_index: usize,
-    pub results: Vec<CachedUser>,
+    pub results: Vec<MentionCandidate>,
+    /// Character positions the query matched in each result's label, like
+    /// `SearchResult::match_indices`.
+    pub match_indices: Vec<Vec<usize>>,
     pub selected_index: usize,
 }
 
 impl AutocompleteState {
     #[must_use]
-    pub fn selected_user(&self) -> Option<&CachedUser> {
+    pub fn selected(&self) -> Option<&MentionCandidate> {
         self.results.get(self.selected_index)
     }
 }
@@ -35,8 +102,10 @@ impl AutocompleteService {
 
     pub fn reset(&mut self) {
         self.state.active = false;
+        self.state.kind = MentionKind::User;
         self.state.query.clear();
         self.state.results.clear();
+        self.state.match_indices.clear();
         self.state.selected_index = 0;
     }
 
@@ -69,7 +138,7 @@ impl AutocompleteService {
 
         let slice_up_to_cursor = &text[..safe_cursor_idx];
 
-        if let Some(last_at_index) = slice_up_to_cursor.rfind('@') {
+        if let Some(last_at_index) = slice_up_to_cursor.rfind(['@', '#']) {
             let valid_trigger = if last_at_index == 0 {
                 true
             } else {
@@ -81,9 +150,18 @@ impl AutocompleteService {
             };
 
             if valid_trigger {
-                let query = &slice_up_to_cursor[last_at_index + 1..];
+                let after = &slice_up_to_cursor[last_at_index..];
+                let kind = if after.starts_with('#') {
+                    MentionKind::Channel
+                } else if after.starts_with("@&") {
+                    MentionKind::Role
+                } else {
+                    MentionKind::User
+                };
+                let query = &after[kind.trigger().len()..];
 
-                if query.contains('\n') {
+                // Channel names never hold spaces, so `# ` is a heading.
+                if query.contains('\n') || (kind == MentionKind::Channel && query.contains(' ')) {
                     if self.state.active {
                         self.reset();
                         return true;
@@ -92,8 +170,9 @@ impl AutocompleteService {
                 }
 
                 let new_query = query.to_string();
-                if !self.state.active || self.state.query != new_query {
+                if !self.state.active || self.state.query != new_query || self.state.kind != kind {
                     self.state.active = true;
+                    self.state.kind = kind;
                     self.state.query = new_query;
                     self.state.trigger_index = last_at_index;
                     return true;
@@ -109,15 +188,20 @@ impl AutocompleteService {
         false
     }
 
-    pub fn update_results(&mut self, candidates: Vec<CachedUser>) {
+    pub fn update_results(
+        &mut self,
+        candidates: Vec<MentionCandidate>,
+        resolver: &IdentityResolver,
+    ) {
         let lower_query = self.state.query.to_lowercase();
-        self.state.results = candidates
+        (self.state.results, self.state.match_indices) = candidates
             .into_iter()
-            .filter(|user| {
-                user.username().to_lowercase().contains(&lower_query)
-                    || user.display_name().to_lowercase().contains(&lower_query)
+            .filter(|candidate| candidate.matches(&lower_query))
+            .map(|candidate| {
+                let indices = match_indices(&candidate.label(resolver), &lower_query);
+                (candidate, indices)
             })
-            .collect();
+            .unzip();
 
         if self.state.selected_index >= self.state.results.len() {
             self.state.selected_index = 0;
@@ -145,8 +229,95 @@ impl AutocompleteService {
     }
 }
 
+/// Character positions where `lower_query` first occurs in `label`,
+/// ignoring case. Empty if a user matched on their other name.
+fn match_indices(label: &str, lower_query: &str) -> Vec<usize> {
+    if lower_query.is_empty() {
+        return Vec::new();
+    }
+    let lower_label = label.to_lowercase();
+    lower_label
+        .find(lower_query)
+        .map_or_else(Vec::new, |start| {
+            let first = lower_label[..start].chars().count();
+            (first..first + lower_query.chars().count()).collect()
+        })
+}
+
 impl Default for AutocompleteService {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{ChannelId, ChannelKind, Permissions, RoleId};
+
+    fn typed(text: &str) -> AutocompleteService {
+        let mut service = AutocompleteService::new();
+        service.process_input(text, text.len());
+        service
+    }
+
+    #[test]
+    fn test_channel_trigger_detected() {
+        let service = typed("see #gen");
+        let state = service.state();
+        assert!(state.active);
+        assert_eq!(state.kind, MentionKind::Channel);
+        assert_eq!(state.query, "gen");
+        assert_eq!(state.trigger_index, 4);
+
+        assert!(!typed("# heading").state().active);
+        assert!(!typed("C#").state().active);
+
+        let mut service = typed("#gen");
+        service.update_results(
+            vec![
+                MentionCandidate::Channel(Channel::new(ChannelId(7), "general", ChannelKind::Text)),
+                MentionCandidate::Channel(Channel::new(ChannelId(8), "memes", ChannelKind::Text)),
+            ],
+            &IdentityResolver::with_preference(false),
+        );
+        assert_eq!(service.state().results.len(), 1);
+        assert_eq!(service.state().selected().unwrap().markup(), "<#7>");
+        assert_eq!(service.state().match_indices, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_role_trigger_detected() {
+        let service = typed("ping @&mod");
+        let state = service.state();
+        assert_eq!(state.kind, MentionKind::Role);
+        assert_eq!(state.query, "mod");
+        assert_eq!(state.trigger_index, 5);
+
+        let mut service = typed("@mo");
+        assert_eq!(service.state().kind, MentionKind::User);
+        assert!(service.process_input("@&mo", 4));
+        assert_eq!(service.state().kind, MentionKind::Role);
+
+        let role = |id, name: &str| {
+            MentionCandidate::Role(Role {
+                id: RoleId(id),
+                name: name.to_string(),
+                color: 0,
+                hoist: false,
+                icon: None,
+                unicode_emoji: None,
+                position: 0,
+                permissions: Permissions::empty(),
+                managed: false,
+                mentionable: true,
+            })
+        };
+        service.update_results(
+            vec![role(1, "Moderators"), role(2, "Members")],
+            &IdentityResolver::with_preference(false),
+        );
+        assert_eq!(service.state().results.len(), 1);
+        assert_eq!(service.state().selected().unwrap().markup(), "<@&1>");
+    }
+}
@@ -1,16 +1,27 @@
 use std::iter::Peekable;
 use std::str::CharIndices;
 
+use crate::domain::ports::MentionType;
+
 /// Mention resolver trait for parsing logic if needed (though usually resolution happens at render time)
 /// Keeping it here just in case, but likely not needed for AST generation unless we want to validate IDs.
 pub trait MentionResolver: Send + Sync {
     fn resolve(&self, user_id: &str) -> Option<String>;
     fn resolve_channel(&self, channel_id: &str) -> Option<String>;
+
+    /// Role name and color (`0` when uncolored) for a `<@&id>` mention.
+    fn resolve_role(&self, _role_id: &str) -> Option<(String, u32)> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum MdBlock {
-    Header(u8, Vec<MdInline>),
+    /// `#`, `##` or `###` heading.
+    Heading {
+        level: u8,
+        inlines: Vec<MdInline>,
+    },
     List {
         indent: u8,
         content: Vec<MdInline>,
@@ -36,7 +47,46 @@ pub enum MdInline {
     Spoiler(Vec<MdInline>),
     Code(String),
     Mention(String),
+    RoleMention(String),
+    /// `@everyone` or `@here`, holding the target without the `@`.
+    MassMention(String),
     Channel(String),
+    /// `[text](url)` masked link.
+    Link(Link),
+}
+
+/// A link with the text it is displayed as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+impl Link {
+    /// A bare URL, displayed as itself.
+    #[must_use]
+    pub fn plain(url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self {
+            text: url.clone(),
+            url,
+        }
+    }
+
+    /// Whether the displayed text differs from where the link actually goes.
+    #[must_use]
+    pub fn hides_destination(&self) -> bool {
+        fn normalize(s: &str) -> String {
+            let s = s.trim();
+            let s = s
+                .strip_prefix("https://")
+                .or_else(|| s.strip_prefix("http://"))
+                .unwrap_or(s);
+            s.trim_end_matches('/').to_lowercase()
+        }
+
+        normalize(&self.text) != normalize(&self.url)
+    }
 }
 
 #[must_use]
@@ -44,6 +94,98 @@ pub fn parse_markdown(content: &str) -> Vec<MdBlock> {
     Parser::parse(content)
 }
 
+/// Whether `content` pings `@everyone` or `@here` outside of code.
+#[must_use]
+pub fn mentions_everyone(content: &str) -> bool {
+    find_inline(&parse_markdown(content), &|inline| {
+        matches!(inline, MdInline::MassMention(_)).then_some(())
+    })
+    .is_some()
+}
+
+/// Kinds of mention in `content` that can ping, outside of code.
+#[must_use]
+pub fn mention_types(content: &str) -> Vec<MentionType> {
+    let blocks = parse_markdown(content);
+    [
+        MentionType::Users,
+        MentionType::Roles,
+        MentionType::Everyone,
+    ]
+    .into_iter()
+    .filter(|&kind| {
+        find_inline(&blocks, &|inline| {
+            let found = match inline {
+                MdInline::Mention(_) => MentionType::Users,
+                MdInline::RoleMention(_) => MentionType::Roles,
+                MdInline::MassMention(_) => MentionType::Everyone,
+                _ => return None,
+            };
+            (found == kind).then_some(())
+        })
+        .is_some()
+    })
+    .collect()
+}
+
+/// First `[text](url)` link in `content`, outside of code.
+#[must_use]
+pub fn first_masked_link(content: &str) -> Option<Link> {
+    find_inline(&parse_markdown(content), &|inline| match inline {
+        MdInline::Link(link) => Some(link.clone()),
+        _ => None,
+    })
+}
+
+/// A fenced code block's language tag and raw contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeBlockRef<'a> {
+    pub lang: Option<&'a str>,
+    pub code: &'a str,
+}
+
+/// Fenced code blocks in document order, including those inside quotes.
+#[must_use]
+pub fn code_blocks(blocks: &[MdBlock]) -> Vec<CodeBlockRef<'_>> {
+    let mut found = Vec::new();
+    for block in blocks {
+        match block {
+            MdBlock::CodeBlock { lang, code } => found.push(CodeBlockRef {
+                lang: lang.as_deref(),
+                code,
+            }),
+            MdBlock::BlockQuote(children) => found.extend(code_blocks(children)),
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Depth-first search for the first inline `f` maps to a value.
+fn find_inline<T>(blocks: &[MdBlock], f: &impl Fn(&MdInline) -> Option<T>) -> Option<T> {
+    fn in_inlines<T>(inlines: &[MdInline], f: &impl Fn(&MdInline) -> Option<T>) -> Option<T> {
+        inlines.iter().find_map(|inline| match inline {
+            MdInline::Bold(c)
+            | MdInline::Italic(c)
+            | MdInline::Underline(c)
+            | MdInline::Strike(c)
+            | MdInline::Spoiler(c) => in_inlines(c, f),
+            _ => f(inline),
+        })
+    }
+
+    blocks.iter().find_map(|block| match block {
+        MdBlock::Heading { inlines, .. }
+        | MdBlock::List {
+            content: inlines, ..
+        }
+        | MdBlock::Subtext(inlines)
+        | MdBlock::Paragraph(inlines) => in_inlines(inlines, f),
+        MdBlock::BlockQuote(children) => find_inline(children, f),
+        MdBlock::CodeBlock { .. } | MdBlock::Empty => None,
+    })
+}
+
 struct Parser;
 
 impl Parser {
@@ -81,24 +223,16 @@ impl Parser {
                 continue;
             }
 
-            if let Some(content) = line.strip_prefix("-# ") {
+            if let Some(content) = subtext(line) {
                 blocks.push(MdBlock::Subtext(parse_inline(content)));
                 continue;
-            } else if line == "-#" {
-                blocks.push(MdBlock::Subtext(Vec::new()));
-                continue;
             }
 
-            if let Some(content) = line.strip_prefix("### ") {
-                blocks.push(MdBlock::Header(3, parse_inline(content)));
-                continue;
-            }
-            if let Some(content) = line.strip_prefix("## ") {
-                blocks.push(MdBlock::Header(2, parse_inline(content)));
-                continue;
-            }
-            if let Some(content) = line.strip_prefix("# ") {
-                blocks.push(MdBlock::Header(1, parse_inline(content)));
+            if let Some((level, content)) = heading(line) {
+                blocks.push(MdBlock::Heading {
+                    level,
+                    inlines: parse_inline(content),
+                });
                 continue;
             }
 
@@ -116,48 +250,86 @@ impl Parser {
                 continue;
             }
 
-            if let Some(content) = line.strip_prefix("> ") {
-                let mut inner_blocks = vec![MdBlock::Paragraph(parse_inline(content))];
+            if let Some(content) = quote_line(line) {
+                let mut quote_content = String::from(content);
 
-                while let Some(next_line) = lines.peek() {
-                    if next_line.starts_with("> ") && !next_line.starts_with(">>> ") {
-                        let next_content = &lines.next().unwrap()[2..];
-                        inner_blocks.push(MdBlock::Paragraph(parse_inline(next_content)));
-                    } else {
-                        break;
-                    }
+                while let Some(next_content) = lines.peek().and_then(|l| quote_line(l)) {
+                    quote_content.push('\n');
+                    quote_content.push_str(next_content);
+                    lines.next();
                 }
+
+                // Quoted lines are full markdown, so `> > ` nests and
+                // fenced code works inside a quote.
+                let inner_blocks = Parser::parse(&quote_content);
                 blocks.push(MdBlock::BlockQuote(inner_blocks));
                 continue;
             }
 
-            let trimmed = line.trim_start();
-            let indent_len = line.len() - trimmed.len();
-
-            if let Some(content) = trimmed.strip_prefix("- ") {
+            if let Some((indent, bullet, content)) = list_item(line) {
                 blocks.push(MdBlock::List {
-                    indent: u8::try_from(indent_len / 2).unwrap_or(0),
+                    indent,
                     content: parse_inline(content),
-                    bullet: '-',
-                });
-                continue;
-            }
-            if let Some(content) = trimmed.strip_prefix("* ") {
-                blocks.push(MdBlock::List {
-                    indent: u8::try_from(indent_len / 2).unwrap_or(0),
-                    content: parse_inline(content),
-                    bullet: '*',
+                    bullet,
                 });
                 continue;
             }
 
-            blocks.push(MdBlock::Paragraph(parse_inline(line)));
+            // Discord keeps single newlines as line breaks, and formatting
+            // may span them, so consecutive plain lines share one paragraph.
+            let mut paragraph = String::from(line);
+            while let Some(next_line) = lines.next_if(|l| !starts_block(l)) {
+                paragraph.push('\n');
+                paragraph.push_str(next_line);
+            }
+            blocks.push(MdBlock::Paragraph(parse_inline(&paragraph)));
         }
 
         blocks
     }
 }
 
+/// Text of a `-# ` subtext line, including a bare `-#`.
+fn subtext(line: &str) -> Option<&str> {
+    line.strip_prefix("-# ").or((line == "-#").then_some(""))
+}
+
+/// Level and text of a `#`, `##` or `###` heading.
+fn heading(line: &str) -> Option<(u8, &str)> {
+    [("### ", 3), ("## ", 2), ("# ", 1)]
+        .into_iter()
+        .find_map(|(prefix, level)| line.strip_prefix(prefix).map(|content| (level, content)))
+}
+
+/// Contents of a single-line `> ` quote, including a bare `>`.
+fn quote_line(line: &str) -> Option<&str> {
+    if line.starts_with(">>> ") {
+        return None;
+    }
+    line.strip_prefix("> ").or((line == ">").then_some(""))
+}
+
+/// Nesting depth, bullet and text of a `- ` or `* ` list item.
+fn list_item(line: &str) -> Option<(u8, char, &str)> {
+    let trimmed = line.trim_start();
+    let indent = u8::try_from((line.len() - trimmed.len()) / 2).unwrap_or(0);
+    ['-', '*'].into_iter().find_map(|bullet| {
+        let content = trimmed.strip_prefix(bullet)?.strip_prefix(' ')?;
+        Some((indent, bullet, content))
+    })
+}
+
+/// Whether `line` opens a block of its own rather than continuing a paragraph.
+fn starts_block(line: &str) -> bool {
+    line.trim_end().is_empty()
+        || line.starts_with("```")
+        || line.starts_with(">>> ")
+        || subtext(line).is_some()
+        || heading(line).is_some()
+        || quote_line(line).is_some()
+        || list_item(line).is_some()
+}
+
 fn parse_inline(input: &str) -> Vec<MdInline> {
     let mut inlines = Vec::new();
     let mut chars = input.char_indices().peekable();
@@ -227,6 +399,14 @@ fn handle_special_chars(
             handle_mention(input, idx, start, inlines, chars);
             true
         }
+        '@' => {
+            handle_mass_mention(input, idx, start, inlines, chars);
+            true
+        }
+        '[' => {
+            handle_masked_link(input, idx, start, inlines, chars);
+            true
+        }
         'h' => {
             handle_discord_url(input, idx, start, inlines, chars);
             true
@@ -332,10 +512,17 @@ fn handle_mention(
             }
             let content = &remaining[..=end];
             let id_content = &content[2..end];
-            let id = id_content.trim_start_matches('!');
+            let (is_role, id) = match id_content.strip_prefix('&') {
+                Some(role_id) => (true, role_id),
+                None => (false, id_content.trim_start_matches('!')),
+            };
 
             if id.chars().all(char::is_numeric) && !id.is_empty() {
-                inlines.push(MdInline::Mention(id.to_string()));
+                inlines.push(if is_role {
+                    MdInline::RoleMention(id.to_string())
+                } else {
+                    MdInline::Mention(id.to_string())
+                });
 
                 let end_pos = idx + end;
                 while let Some((curr, _)) = chars.peek() {
@@ -373,6 +560,91 @@ fn handle_mention(
     }
 }
 
+fn handle_mass_mention(
+    input: &str,
+    idx: usize,
+    start: &mut usize,
+    inlines: &mut Vec<MdInline>,
+    chars: &mut Peekable<CharIndices>,
+) {
+    let remaining = &input[idx + 1..];
+    let Some(target) = ["everyone", "here"]
+        .into_iter()
+        .find(|target| remaining.starts_with(target))
+    else {
+        return;
+    };
+    let end_pos = idx + 1 + target.len();
+
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    if input[..idx].chars().next_back().is_some_and(is_word_char)
+        || input[end_pos..].chars().next().is_some_and(is_word_char)
+    {
+        return;
+    }
+
+    if idx > *start {
+        inlines.push(MdInline::Text(input[*start..idx].to_string()));
+    }
+    inlines.push(MdInline::MassMention(target.to_string()));
+
+    while let Some((curr, _)) = chars.peek() {
+        if *curr < end_pos {
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    *start = end_pos;
+}
+
+fn handle_masked_link(
+    input: &str,
+    idx: usize,
+    start: &mut usize,
+    inlines: &mut Vec<MdInline>,
+    chars: &mut Peekable<CharIndices>,
+) {
+    if input[..idx].ends_with('!') {
+        return;
+    }
+    let remaining = &input[idx + 1..];
+    let Some(text_end) = remaining.find("](") else {
+        return;
+    };
+    let text = &remaining[..text_end];
+    let target = &remaining[text_end + 2..];
+    let Some(url_end) = target.find(')') else {
+        return;
+    };
+    let url = target[..url_end].trim_matches(|c| c == '<' || c == '>');
+    if text.is_empty()
+        || text.contains(['[', ']'])
+        || !(url.starts_with("https://") || url.starts_with("http://"))
+        || url.contains(char::is_whitespace)
+    {
+        return;
+    }
+
+    if idx > *start {
+        inlines.push(MdInline::Text(input[*start..idx].to_string()));
+    }
+    inlines.push(MdInline::Link(Link {
+        text: text.to_string(),
+        url: url.to_string(),
+    }));
+
+    let end_pos = idx + 1 + text_end + 2 + url_end + 1;
+    while let Some((curr, _)) = chars.peek() {
+        if *curr < end_pos {
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    *start = end_pos;
+}
+
 fn handle_escape(
     input: &str,
     idx: usize,
@@ -468,7 +740,7 @@ mod tests {
         let blocks = parse_markdown(content);
         assert_eq!(blocks.len(), 2);
 
-        if let MdBlock::Header(level, inlines) = &blocks[0] {
+        if let MdBlock::Heading { level, inlines } = &blocks[0] {
             assert_eq!(*level, 3);
             if let MdInline::Text(t) = &inlines[0] {
                 assert_eq!(t, "Header 3");
@@ -565,4 +837,134 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_role_and_mass_mentions() {
+        let blocks = parse_markdown("<@&42> ping @everyone and @here, not me@here");
+        let MdBlock::Paragraph(inlines) = &blocks[0] else {
+            panic!("Expected paragraph");
+        };
+
+        assert!(matches!(&inlines[0], MdInline::RoleMention(id) if id == "42"));
+        assert!(matches!(&inlines[2], MdInline::MassMention(t) if t == "everyone"));
+        assert!(matches!(&inlines[4], MdInline::MassMention(t) if t == "here"));
+        assert!(matches!(&inlines[5], MdInline::Text(t) if t == ", not me@here"));
+    }
+
+    #[test]
+    fn test_parse_masked_link() {
+        let blocks =
+            parse_markdown("see [docs](https://example.com/a) and ![img](https://x.io/i.png)");
+        let MdBlock::Paragraph(inlines) = &blocks[0] else {
+            panic!("Expected paragraph");
+        };
+        let links: Vec<_> = inlines
+            .iter()
+            .filter_map(|i| match i {
+                MdInline::Link(link) => Some(link.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![Link {
+                text: "docs".to_string(),
+                url: "https://example.com/a".to_string(),
+            }]
+        );
+        assert!(matches!(inlines.last(), Some(MdInline::Text(t)) if t.contains("![img]")));
+        assert_eq!(first_masked_link("`[x](https://a.io)`"), None);
+    }
+
+    #[test]
+    fn test_link_hides_destination() {
+        let link = |text: &str, url: &str| Link {
+            text: text.to_string(),
+            url: url.to_string(),
+        };
+        assert!(link("click me", "https://evil.example").hides_destination());
+        assert!(link("https://bank.example", "https://evil.example").hides_destination());
+        assert!(!link("example.com/", "https://example.com").hides_destination());
+        assert!(!Link::plain("https://example.com").hides_destination());
+    }
+
+    #[test]
+    fn test_mention_types_found_outside_code() {
+        assert_eq!(
+            mention_types("hi <@1> and <@&2> `@everyone`"),
+            vec![MentionType::Users, MentionType::Roles]
+        );
+        assert_eq!(mention_types("*@here*"), vec![MentionType::Everyone]);
+        assert!(mention_types("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_mentions_everyone_ignores_code() {
+        assert!(mentions_everyone("hey **@here**"));
+        assert!(!mentions_everyone("run `@everyone` later"));
+        assert!(!mentions_everyone("```\n@everyone\n```"));
+        assert!(!mentions_everyone("@everyones"));
+    }
+
+    #[test]
+    fn test_formatting_spans_single_newlines() {
+        let blocks = parse_markdown("*one\ntwo*\n- item\nthree");
+        assert_eq!(blocks.len(), 3);
+        let MdBlock::Paragraph(inlines) = &blocks[0] else {
+            panic!("expected paragraph");
+        };
+        assert!(matches!(&inlines[..], [MdInline::Italic(c)]
+            if matches!(&c[..], [MdInline::Text(t)] if t == "one\ntwo")));
+        assert!(matches!(blocks[1], MdBlock::List { .. }));
+        assert!(matches!(&blocks[2], MdBlock::Paragraph(_)));
+    }
+
+    #[test]
+    fn test_parse_nested_quote() {
+        let blocks = parse_markdown("> outer\n> > inner\n> back out\nafter");
+        assert_eq!(blocks.len(), 2);
+        let MdBlock::BlockQuote(outer) = &blocks[0] else {
+            panic!("expected quote");
+        };
+        assert!(matches!(&outer[..], [
+            MdBlock::Paragraph(_),
+            MdBlock::BlockQuote(inner),
+            MdBlock::Paragraph(_),
+        ] if matches!(&inner[..], [MdBlock::Paragraph(_)])));
+        assert!(matches!(&blocks[1], MdBlock::Paragraph(_)));
+    }
+
+    #[test]
+    fn test_parse_quote_with_code_block() {
+        let blocks = parse_markdown("> look:\n> ```rust\n> let x = 1;\n>\n> ```");
+        let MdBlock::BlockQuote(inner) = &blocks[0] else {
+            panic!("expected quote");
+        };
+        assert_eq!(
+            code_blocks(&blocks),
+            vec![CodeBlockRef {
+                lang: Some("rust"),
+                code: "let x = 1;\n",
+            }]
+        );
+        assert_eq!(inner.len(), 2);
+    }
+
+    #[test]
+    fn test_code_blocks_strip_fences() {
+        let blocks = parse_markdown("see\n```rust\nfn main() {}\n```\n>>> ```\nls -la\n```");
+        assert_eq!(
+            code_blocks(&blocks),
+            vec![
+                CodeBlockRef {
+                    lang: Some("rust"),
+                    code: "fn main() {}",
+                },
+                CodeBlockRef {
+                    lang: None,
+                    code: "ls -la",
+                },
+            ]
+        );
+    }
 }
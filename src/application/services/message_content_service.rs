@@ -1,10 +1,11 @@
+use crate::application::services::markdown_parser::{Link, first_masked_link};
 use crate::application::services::url_extractor::UrlExtractor;
 use crate::domain::entities::Message;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum MessageContentAction {
     OpenImages,
-    OpenLink(String),
+    OpenLink(Link),
     None,
 }
 
@@ -37,8 +38,18 @@ impl MessageContentService {
             return MessageContentAction::OpenImages;
         }
 
-        if let Some(url) = UrlExtractor::extract_first_url(message.content()) {
-            return MessageContentAction::OpenLink(url);
+        let content = message.content();
+        if let Some(url) = UrlExtractor::extract_first_url(content) {
+            // A masked link before the first bare URL wins, keeping its display text.
+            let url_pos = content.find(&url).unwrap_or(0);
+            let link = first_masked_link(content)
+                .filter(|masked| {
+                    content
+                        .find(&format!("[{}](", masked.text))
+                        .is_some_and(|pos| pos <= url_pos)
+                })
+                .unwrap_or_else(|| Link::plain(url));
+            return MessageContentAction::OpenLink(link);
         }
 
         MessageContentAction::None
@@ -98,11 +109,34 @@ mod tests {
         let message = create_dummy_message("Check this https://google.com", vec![]);
         let action = MessageContentService::resolve(&message);
         match action {
-            MessageContentAction::OpenLink(url) => assert_eq!(url, "https://google.com"),
+            MessageContentAction::OpenLink(link) => {
+                assert_eq!(link, Link::plain("https://google.com"));
+            }
             _ => panic!("Expected OpenLink"),
         }
     }
 
+    #[test]
+    fn test_resolve_masked_link_keeps_display_text() {
+        let message = create_dummy_message("Log in [here](https://evil.example/login)", vec![]);
+        let MessageContentAction::OpenLink(link) = MessageContentService::resolve(&message) else {
+            panic!("Expected OpenLink");
+        };
+        assert_eq!(link.text, "here");
+        assert_eq!(link.url, "https://evil.example/login");
+        assert!(link.hides_destination());
+    }
+
+    #[test]
+    fn test_resolve_masked_link_with_url_as_text() {
+        let message = create_dummy_message("[https://bank.example](https://evil.example)", vec![]);
+        let MessageContentAction::OpenLink(link) = MessageContentService::resolve(&message) else {
+            panic!("Expected OpenLink");
+        };
+        assert_eq!(link.url, "https://evil.example");
+        assert!(link.hides_destination());
+    }
+
     #[test]
     fn test_resolve_none() {
         let message = create_dummy_message("Hello world", vec![]);
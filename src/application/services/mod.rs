@@ -4,4 +4,6 @@ pub mod markdown_parser;
 pub mod message_content_service;
 pub mod notification_manager;
 pub mod notification_service;
+pub mod outbound_queue;
+pub mod typing_throttle;
 pub mod url_extractor;
@@ -1,12 +1,19 @@
 use std::collections::VecDeque;
 use std::time::Duration;
 
+use crate::domain::entities::ChannelId;
 use crate::domain::{Notification, NotificationLevel};
 
+/// How many past notifications the history panel keeps.
+pub const HISTORY_LIMIT: usize = 50;
+
 #[derive(Debug)]
 pub struct NotificationManager {
     queue: VecDeque<Notification>,
     default_duration: Duration,
+    max_queued: usize,
+    history: VecDeque<Notification>,
+    unseen: usize,
 }
 
 impl Default for NotificationManager {
@@ -21,9 +28,20 @@ impl NotificationManager {
         Self {
             queue: VecDeque::new(),
             default_duration,
+            max_queued: usize::MAX,
+            history: VecDeque::with_capacity(HISTORY_LIMIT),
+            unseen: 0,
         }
     }
 
+    /// Caps how many notifications wait in line; the oldest are dropped
+    /// to make room so the popup stays current.
+    #[must_use]
+    pub fn with_max_queued(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued.max(1);
+        self
+    }
+
     pub fn notify(
         &mut self,
         level: NotificationLevel,
@@ -32,7 +50,39 @@ impl NotificationManager {
     ) {
         let notification =
             Notification::new(level, title, message).with_duration(self.default_duration);
+        self.push(notification);
+    }
+
+    /// Like [`Self::info`], remembering the channel the notification is about
+    /// and its `#channel` or `@user` name.
+    pub fn info_from(
+        &mut self,
+        channel_id: ChannelId,
+        channel_name: impl Into<String>,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        let notification = Notification::new(NotificationLevel::Info, title, message)
+            .with_duration(self.default_duration)
+            .with_source(channel_id, channel_name);
+        self.push(notification);
+    }
+
+    fn push(&mut self, notification: Notification) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification.clone());
+        self.unseen = (self.unseen + 1).min(self.history.len());
+
         self.queue.push_back(notification);
+        if self.queue.len() > self.max_queued {
+            let excess = self.queue.len() - self.max_queued;
+            self.queue.drain(..excess);
+            if let Some(front) = self.queue.front_mut() {
+                front.mark_displayed();
+            }
+        }
     }
 
     pub fn info(&mut self, title: impl Into<String>, message: impl Into<String>) {
@@ -68,6 +118,28 @@ impl NotificationManager {
     pub fn has_notifications(&self) -> bool {
         !self.queue.is_empty()
     }
+
+    /// Past notifications, oldest first.
+    #[must_use]
+    pub const fn history(&self) -> &VecDeque<Notification> {
+        &self.history
+    }
+
+    /// Notifications added since the history was last looked at.
+    #[must_use]
+    pub const fn unseen_count(&self) -> usize {
+        self.unseen
+    }
+
+    pub const fn mark_seen(&mut self) {
+        self.unseen = 0;
+    }
+
+    /// Empties the history, marking everything seen.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.mark_seen();
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +178,31 @@ mod tests {
         let second = manager.current_notification().unwrap();
         assert!(second.displayed_at.unwrap().elapsed() < Duration::from_secs(1));
     }
+
+    #[test]
+    fn test_history_is_a_ring_buffer() {
+        let mut manager = NotificationManager::default();
+        for i in 0..=HISTORY_LIMIT {
+            manager.info(i.to_string(), "message");
+        }
+
+        assert_eq!(manager.history().len(), HISTORY_LIMIT);
+        assert_eq!(manager.history().front().unwrap().title, "1");
+        assert_eq!(manager.unseen_count(), HISTORY_LIMIT);
+
+        manager.clear_history();
+        assert!(manager.history().is_empty());
+        assert_eq!(manager.unseen_count(), 0);
+    }
+
+    #[test]
+    fn test_max_queued_drops_oldest() {
+        let mut manager = NotificationManager::default().with_max_queued(2);
+        manager.info("1", "First");
+        manager.info("2", "Second");
+        manager.info("3", "Third");
+
+        assert_eq!(manager.queue.len(), 2);
+        assert_eq!(manager.current_notification().unwrap().title, "2");
+    }
 }
@@ -1,19 +1,148 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::domain::entities::ChannelId;
 use crate::domain::ports::NotificationPort;
 
+/// Message notifications held back while their channel's window is open.
+#[derive(Debug, Clone)]
+struct Batch {
+    opened_at: Instant,
+    channel_name: String,
+    title: String,
+    body: String,
+    count: usize,
+}
+
+impl Batch {
+    /// The message itself when alone, otherwise a count for the channel.
+    fn summary(&self) -> (String, String) {
+        if self.count == 1 {
+            (self.title.clone(), self.body.clone())
+        } else {
+            (
+                "Oxicord".to_string(),
+                format!("{} new messages in {}", self.count, self.channel_name),
+            )
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct NotificationService {
     port: Arc<dyn NotificationPort>,
+    batch_window: Duration,
+    batches: HashMap<ChannelId, Batch>,
 }
 
 impl NotificationService {
     #[must_use]
     pub fn new(port: Arc<dyn NotificationPort>) -> Self {
-        Self { port }
+        Self {
+            port,
+            batch_window: Duration::ZERO,
+            batches: HashMap::new(),
+        }
+    }
+
+    /// Merges messages from one channel arriving within `window` into a
+    /// single notification. A zero window sends every message at once.
+    #[must_use]
+    pub fn with_batch_window(mut self, window: Duration) -> Self {
+        self.batch_window = window;
+        self
     }
 
     pub fn send(&self, title: &str, body: &str) {
         self.port.send(title, body);
     }
+
+    /// Queues a notification for a message in `channel_id`. It is sent by
+    /// [`Self::flush`] once the channel's batch window closes.
+    pub fn notify_message(
+        &mut self,
+        channel_id: ChannelId,
+        channel_name: &str,
+        title: &str,
+        body: &str,
+        now: Instant,
+    ) {
+        if self.batch_window.is_zero() {
+            self.send(title, body);
+            return;
+        }
+        self.batches
+            .entry(channel_id)
+            .and_modify(|batch| batch.count += 1)
+            .or_insert_with(|| Batch {
+                opened_at: now,
+                channel_name: channel_name.to_string(),
+                title: title.to_string(),
+                body: body.to_string(),
+                count: 1,
+            });
+    }
+
+    /// Sends every batch whose window has closed by `now`.
+    pub fn flush(&mut self, now: Instant) {
+        let window = self.batch_window;
+        let port = &self.port;
+        self.batches.retain(|_, batch| {
+            if now.duration_since(batch.opened_at) < window {
+                return true;
+            }
+            let (title, body) = batch.summary();
+            port.send(&title, &body);
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::mocks::MockNotificationPort;
+
+    #[test]
+    fn test_rapid_messages_are_batched() {
+        let port = Arc::new(MockNotificationPort::new());
+        let sent = port.notifications.clone();
+        let mut service =
+            NotificationService::new(port).with_batch_window(Duration::from_millis(500));
+
+        let start = Instant::now();
+        for i in 0..3 {
+            let now = start + Duration::from_millis(i * 100);
+            service.notify_message(ChannelId(1), "#general", "@ferris", "hi", now);
+        }
+        service.notify_message(ChannelId(2), "@crab", "@crab", "hello", start);
+
+        service.flush(start + Duration::from_millis(300));
+        assert!(sent.lock().unwrap().is_empty());
+
+        service.flush(start + Duration::from_millis(500));
+        let mut sent = sent.lock().unwrap().clone();
+        sent.sort();
+        assert_eq!(
+            sent,
+            vec![
+                ("@crab".to_string(), "hello".to_string()),
+                (
+                    "Oxicord".to_string(),
+                    "3 new messages in #general".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zero_window_sends_immediately() {
+        let port = Arc::new(MockNotificationPort::new());
+        let sent = port.notifications.clone();
+        let mut service = NotificationService::new(port);
+
+        service.notify_message(ChannelId(1), "#general", "@ferris", "hi", Instant::now());
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
 }
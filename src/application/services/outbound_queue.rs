@@ -0,0 +1,101 @@
+//! Queue for messages composed while the connection is down.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::domain::entities::ChannelId;
+use crate::domain::ports::SendMessageRequest;
+
+/// Holds pending sends per channel until the connection comes back.
+///
+/// Messages keep their composition order within a channel, and channels are
+/// flushed in the order they first received a queued message.
+#[derive(Debug, Default)]
+pub struct OutboundQueue {
+    pending: HashMap<ChannelId, VecDeque<SendMessageRequest>>,
+    channel_order: Vec<ChannelId>,
+}
+
+impl OutboundQueue {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, request: SendMessageRequest) {
+        let channel_id = request.channel_id;
+        if !self.channel_order.contains(&channel_id) {
+            self.channel_order.push(channel_id);
+        }
+        self.pending
+            .entry(channel_id)
+            .or_default()
+            .push_back(request);
+    }
+
+    /// Takes every queued message, grouped by channel and in send order.
+    pub fn drain(&mut self) -> Vec<SendMessageRequest> {
+        let mut requests = Vec::with_capacity(self.len());
+        for channel_id in self.channel_order.drain(..) {
+            if let Some(queue) = self.pending.remove(&channel_id) {
+                requests.extend(queue);
+            }
+        }
+        requests
+    }
+
+    #[must_use]
+    pub fn pending_in(&self, channel_id: ChannelId) -> usize {
+        self.pending.get(&channel_id).map_or(0, VecDeque::len)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.values().map(VecDeque::len).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.values().all(VecDeque::is_empty)
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.channel_order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_keeps_order_per_channel() {
+        let mut queue = OutboundQueue::new();
+        queue.push(SendMessageRequest::new(ChannelId(1), "a1"));
+        queue.push(SendMessageRequest::new(ChannelId(2), "b1"));
+        queue.push(SendMessageRequest::new(ChannelId(1), "a2"));
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.pending_in(ChannelId(1)), 2);
+
+        let contents: Vec<_> = queue.drain().into_iter().map(|r| r.content).collect();
+        assert_eq!(contents, vec!["a1", "a2", "b1"]);
+        assert!(queue.is_empty());
+        assert_eq!(queue.pending_in(ChannelId(1)), 0);
+    }
+
+    #[test]
+    fn test_requeue_after_drain_starts_fresh_order() {
+        let mut queue = OutboundQueue::new();
+        queue.push(SendMessageRequest::new(ChannelId(1), "first"));
+        let drained = queue.drain();
+
+        for request in drained {
+            queue.push(request);
+        }
+        queue.push(SendMessageRequest::new(ChannelId(1), "second"));
+
+        let contents: Vec<_> = queue.drain().into_iter().map(|r| r.content).collect();
+        assert_eq!(contents, vec!["first", "second"]);
+    }
+}
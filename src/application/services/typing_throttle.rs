@@ -0,0 +1,71 @@
+//! Rate limit for the typing indicators we send.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::domain::entities::ChannelId;
+
+/// Decides when a typing indicator should be sent for a channel.
+///
+/// Discord shows an indicator for about ten seconds, so one per interval
+/// keeps it lit while typing without a request on every keystroke.
+#[derive(Debug)]
+pub struct TypingThrottle {
+    interval: Duration,
+    last_sent: HashMap<ChannelId, Instant>,
+}
+
+impl TypingThrottle {
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Whether an indicator for `channel_id` is due at `now`, recording it
+    /// as sent when it is.
+    pub fn should_send(&mut self, channel_id: ChannelId, now: Instant) -> bool {
+        let interval = self.interval;
+        self.last_sent
+            .retain(|_, sent| now.duration_since(*sent) < interval);
+        if self.last_sent.contains_key(&channel_id) {
+            return false;
+        }
+        self.last_sent.insert(channel_id, now);
+        true
+    }
+
+    /// Forgets the last indicator for `channel_id`, after a send or when the
+    /// input is cleared, so typing again shows right away.
+    pub fn reset(&mut self, channel_id: ChannelId) {
+        self.last_sent.remove(&channel_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sends_once_per_interval_per_channel() {
+        let mut throttle = TypingThrottle::new(Duration::from_secs(8));
+        let start = Instant::now();
+
+        assert!(throttle.should_send(ChannelId(1), start));
+        assert!(!throttle.should_send(ChannelId(1), start + Duration::from_secs(7)));
+        assert!(throttle.should_send(ChannelId(2), start + Duration::from_secs(7)));
+        assert!(throttle.should_send(ChannelId(1), start + Duration::from_secs(8)));
+    }
+
+    #[test]
+    fn test_reset_allows_immediate_send() {
+        let mut throttle = TypingThrottle::new(Duration::from_secs(8));
+        let start = Instant::now();
+
+        assert!(throttle.should_send(ChannelId(1), start));
+        throttle.reset(ChannelId(1));
+        assert!(throttle.should_send(ChannelId(1), start + Duration::from_secs(1)));
+    }
+}
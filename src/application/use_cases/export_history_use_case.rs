@@ -0,0 +1,430 @@
+//! Channel history export use case.
+
+use std::future::Future;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::application::dto::{ExportFormat, ExportRequest};
+use crate::domain::entities::{AuthToken, Message};
+use crate::domain::errors::AuthError;
+use crate::domain::ports::{DiscordDataPort, FetchMessagesOptions};
+
+/// Most messages Discord returns per request.
+const PAGE_SIZE: usize = 100;
+
+/// Export failure variants.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("failed to fetch messages: {0}")]
+    Fetch(#[from] AuthError),
+
+    #[error("failed to write export: {0}")]
+    Write(#[from] io::Error),
+
+    #[error("failed to encode message: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+/// Pages through a channel's history and streams it to a writer.
+pub struct ExportHistoryUseCase {
+    data_port: Arc<dyn DiscordDataPort>,
+}
+
+impl ExportHistoryUseCase {
+    /// Creates new use case.
+    #[must_use]
+    pub const fn new(data_port: Arc<dyn DiscordDataPort>) -> Self {
+        Self { data_port }
+    }
+
+    /// Writes the requested messages to `out`, oldest first.
+    ///
+    /// Walking forward, each page is written as soon as it arrives, so memory
+    /// use does not grow with the size of the channel. With only `before`
+    /// and a limit set, the last `limit` messages before it are read
+    /// backwards and held until they can be written oldest first. Returns
+    /// the number of messages written.
+    ///
+    /// # Errors
+    /// Returns error if a page cannot be fetched or the output written.
+    pub async fn execute<W: Write>(
+        &self,
+        token: &AuthToken,
+        request: &ExportRequest,
+        out: W,
+    ) -> Result<usize, ExportError> {
+        export_pages(request, out, |options| {
+            self.data_port
+                .fetch_messages(token, request.channel_id, options)
+        })
+        .await
+    }
+}
+
+/// Pages through the requested range until the channel, the bounds or the
+/// limit run out.
+///
+/// Paging stops on an empty page rather than a short one, since messages
+/// that fail to parse are dropped from the page.
+async fn export_pages<W, F, Fut>(
+    request: &ExportRequest,
+    out: W,
+    fetch: F,
+) -> Result<usize, ExportError>
+where
+    W: Write,
+    F: FnMut(FetchMessagesOptions) -> Fut,
+    Fut: Future<Output = Result<Vec<Message>, AuthError>>,
+{
+    let writer = HistoryWriter::new(out, request.format)?;
+    match (request.after, request.before, request.limit) {
+        (None, Some(before), Some(limit)) => export_backward(before, limit, writer, fetch).await,
+        (after, before, limit) => export_forward(after, before, limit, writer, fetch).await,
+    }
+}
+
+/// Walks forward from `after` (or the start of the channel), writing each
+/// page as it arrives.
+async fn export_forward<W, F, Fut>(
+    after: Option<u64>,
+    before: Option<u64>,
+    limit: Option<usize>,
+    mut writer: HistoryWriter<W>,
+    mut fetch: F,
+) -> Result<usize, ExportError>
+where
+    W: Write,
+    F: FnMut(FetchMessagesOptions) -> Fut,
+    Fut: Future<Output = Result<Vec<Message>, AuthError>>,
+{
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut cursor = after.unwrap_or(0);
+
+    while writer.written < limit {
+        let options = FetchMessagesOptions::default()
+            .with_limit(page_limit(limit - writer.written))
+            .after_message(cursor);
+        let mut page = fetch(options).await?;
+        debug!(count = page.len(), after = cursor, "Fetched export page");
+        page.retain(|message| message.id().as_u64() > cursor);
+        if page.is_empty() {
+            break;
+        }
+
+        page.sort_by_key(|message| message.id().as_u64());
+        for message in &page {
+            let id = message.id().as_u64();
+            if before.is_some_and(|before| id >= before) {
+                return writer.finish();
+            }
+            writer.write(message)?;
+            cursor = id;
+        }
+    }
+
+    writer.finish()
+}
+
+/// Walks backward from `before` until the channel or the limit runs out,
+/// then writes what was read oldest first.
+async fn export_backward<W, F, Fut>(
+    before: u64,
+    limit: usize,
+    mut writer: HistoryWriter<W>,
+    mut fetch: F,
+) -> Result<usize, ExportError>
+where
+    W: Write,
+    F: FnMut(FetchMessagesOptions) -> Fut,
+    Fut: Future<Output = Result<Vec<Message>, AuthError>>,
+{
+    let mut cursor = before;
+    let mut messages = Vec::new();
+
+    while messages.len() < limit {
+        let options = FetchMessagesOptions::default()
+            .with_limit(page_limit(limit - messages.len()))
+            .before_message(cursor);
+        let mut page = fetch(options).await?;
+        debug!(count = page.len(), before = cursor, "Fetched export page");
+        page.retain(|message| message.id().as_u64() < cursor);
+        let Some(oldest) = page.iter().map(|message| message.id().as_u64()).min() else {
+            break;
+        };
+        cursor = oldest;
+        messages.append(&mut page);
+    }
+
+    messages.sort_by_key(|message| message.id().as_u64());
+    let skip = messages.len().saturating_sub(limit);
+    for message in &messages[skip..] {
+        writer.write(message)?;
+    }
+    writer.finish()
+}
+
+/// Request size for a page when `remaining` messages are still wanted.
+fn page_limit(remaining: usize) -> u8 {
+    u8::try_from(PAGE_SIZE.min(remaining)).unwrap_or(u8::MAX)
+}
+
+/// Streams messages in the chosen format.
+struct HistoryWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    written: usize,
+}
+
+impl<W: Write> HistoryWriter<W> {
+    fn new(mut out: W, format: ExportFormat) -> io::Result<Self> {
+        if format == ExportFormat::Json {
+            out.write_all(b"[")?;
+        }
+        Ok(Self {
+            out,
+            format,
+            written: 0,
+        })
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), ExportError> {
+        match self.format {
+            ExportFormat::Json => {
+                let separator: &[u8] = if self.written == 0 { b"\n  " } else { b",\n  " };
+                self.out.write_all(separator)?;
+                serde_json::to_writer(&mut self.out, message)?;
+            }
+            ExportFormat::Txt => writeln!(self.out, "{}", text_line(message))?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<usize, ExportError> {
+        if self.format == ExportFormat::Json {
+            let close: &[u8] = if self.written == 0 { b"]\n" } else { b"\n]\n" };
+            self.out.write_all(close)?;
+        }
+        self.out.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// `[2024-01-01 10:00:00] name: content`, with continuation lines and
+/// attachments indented below.
+fn text_line(message: &Message) -> String {
+    let mut line = format!(
+        "[{}] {}: {}",
+        message.timestamp().format("%Y-%m-%d %H:%M:%S"),
+        message.author().raw_display_name(),
+        message.content().replace('\n', "\n    ")
+    );
+    if message.is_edited() {
+        line.push_str(" (edited)");
+    }
+    line.extend(
+        message
+            .attachments()
+            .iter()
+            .map(|attachment| format!("\n    {} <{}>", attachment.filename, attachment.url)),
+    );
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{ChannelId, MessageAuthor, MessageId, MessageKind};
+    use chrono::{Local, TimeZone};
+
+    fn message(id: u64) -> Message {
+        Message::new(
+            MessageId(id),
+            ChannelId(10),
+            MessageAuthor {
+                id: "1".to_string(),
+                username: "user".to_string(),
+                discriminator: "0".to_string(),
+                avatar: None,
+                bot: false,
+                global_name: None,
+            },
+            format!("message {id}"),
+            Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap(),
+            MessageKind::Default,
+        )
+    }
+
+    /// Serves `history` the way the API does, newest first, for `after` and
+    /// `before` queries.
+    fn fetch_from(
+        history: &[u64],
+        requests: &mut Vec<FetchMessagesOptions>,
+        options: FetchMessagesOptions,
+    ) -> std::future::Ready<Result<Vec<Message>, AuthError>> {
+        let limit = usize::from(options.limit.unwrap_or(50));
+        let mut page: Vec<u64> = if let Some(before) = options.before {
+            history
+                .iter()
+                .rev()
+                .copied()
+                .filter(|id| *id < before)
+                .take(limit)
+                .collect()
+        } else {
+            let after = options.after.unwrap_or(0);
+            history
+                .iter()
+                .copied()
+                .filter(|id| *id > after)
+                .take(limit)
+                .collect()
+        };
+        page.sort_unstable_by(|a, b| b.cmp(a));
+        requests.push(options);
+        std::future::ready(Ok(page.into_iter().map(message).collect()))
+    }
+
+    #[tokio::test]
+    async fn test_export_pages_forward_within_bounds() {
+        let history: Vec<u64> = (1..=250).collect();
+        let mut request = ExportRequest::new(10);
+        request.after = Some(20);
+        request.before = Some(240);
+
+        let mut requests = Vec::new();
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |options| {
+            fetch_from(&history, &mut requests, options)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written, 219);
+        assert_eq!(
+            requests.iter().map(|o| o.after).collect::<Vec<_>>(),
+            vec![Some(20), Some(120), Some(220)]
+        );
+        let exported: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(exported.len(), 219);
+        assert_eq!(exported[0]["content"], "message 21");
+        assert_eq!(exported[218]["content"], "message 239");
+    }
+
+    #[tokio::test]
+    async fn test_export_limit_and_text_format() {
+        let history: Vec<u64> = (1..=5).collect();
+        let mut request = ExportRequest::new(10);
+        request.format = ExportFormat::Txt;
+        request.limit = Some(2);
+
+        let mut requests = Vec::new();
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |options| {
+            fetch_from(&history, &mut requests, options)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written, 2);
+        assert_eq!(requests[0].limit, Some(2));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[2024-01-01 10:00:00] user: message 1\n[2024-01-01 10:00:00] user: message 2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_pages_do_not_end_export() {
+        let history: Vec<u64> = (1..=250).collect();
+        let request = ExportRequest::new(10);
+
+        let mut requests = Vec::new();
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |options| {
+            let mut page = fetch_from(&history, &mut requests, options).into_inner();
+            // Stands in for messages that fail to parse.
+            if let Ok(messages) = &mut page {
+                messages.retain(|m| m.id().as_u64() % 10 != 0);
+            }
+            std::future::ready(page)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written, 225);
+        assert_eq!(
+            requests.iter().map(|o| o.after).collect::<Vec<_>>(),
+            vec![Some(0), Some(99), Some(199), Some(249)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_pages_backward_from_before() {
+        let history: Vec<u64> = (1..=250).collect();
+        let mut request = ExportRequest::new(10);
+        request.before = Some(240);
+        request.limit = Some(150);
+
+        let mut requests = Vec::new();
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |options| {
+            fetch_from(&history, &mut requests, options)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written, 150);
+        assert_eq!(
+            requests
+                .iter()
+                .map(|o| (o.before, o.limit))
+                .collect::<Vec<_>>(),
+            vec![(Some(240), Some(100)), (Some(140), Some(50))]
+        );
+        let exported: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(exported[0]["content"], "message 90");
+        assert_eq!(exported[149]["content"], "message 239");
+    }
+
+    #[tokio::test]
+    async fn test_export_before_without_limit_walks_forward() {
+        let history: Vec<u64> = (1..=250).collect();
+        let mut request = ExportRequest::new(10);
+        request.before = Some(150);
+
+        let mut requests = Vec::new();
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |options| {
+            fetch_from(&history, &mut requests, options)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written, 149);
+        assert!(requests.iter().all(|o| o.before.is_none()));
+        assert_eq!(
+            requests.iter().map(|o| o.after).collect::<Vec<_>>(),
+            vec![Some(0), Some(100)]
+        );
+        let exported: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(exported[0]["content"], "message 1");
+        assert_eq!(exported[148]["content"], "message 149");
+    }
+
+    #[tokio::test]
+    async fn test_empty_export_is_valid_json() {
+        let request = ExportRequest::new(10);
+        let mut out = Vec::new();
+        let written = export_pages(&request, &mut out, |_| std::future::ready(Ok(Vec::new())))
+            .await
+            .unwrap();
+
+        assert_eq!(written, 0);
+        let exported: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+        assert!(exported.is_empty());
+    }
+}
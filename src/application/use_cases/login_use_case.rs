@@ -5,10 +5,17 @@ use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 use crate::application::dto::{LoginRequest, LoginResponse};
-use crate::domain::entities::AuthToken;
+use crate::domain::entities::{AuthToken, User};
 use crate::domain::errors::AuthError;
 use crate::domain::ports::{AuthPort, TokenStoragePort};
 
+/// A token Discord accepted, and the account it belongs to.
+#[derive(Debug, Clone)]
+pub struct ValidatedToken {
+    pub token: AuthToken,
+    pub user: User,
+}
+
 /// Handles user authentication workflow.
 #[derive(Clone)]
 pub struct LoginUseCase {
@@ -36,17 +43,7 @@ impl LoginUseCase {
     pub async fn execute(&self, request: LoginRequest) -> Result<LoginResponse, AuthError> {
         debug!(source = %request.source, "Attempting login");
 
-        let token = AuthToken::new(&request.token).ok_or_else(|| {
-            warn!("Invalid token format provided");
-            AuthError::invalid_format("token does not match expected Discord token format")
-        })?;
-
-        debug!("Token format validated, checking with Discord API");
-
-        let user = self.auth_port.validate_token(&token).await.map_err(|e| {
-            warn!(error = %e, "Token validation failed");
-            e
-        })?;
+        let ValidatedToken { token, user } = self.validate(&request.token).await?;
 
         info!(
             user_id = %user.id(),
@@ -73,6 +70,27 @@ impl LoginUseCase {
         Ok(LoginResponse::new(user, request.source, token_persisted))
     }
 
+    /// Checks the token's format, then asks Discord who it belongs to.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidTokenFormat`] before any request is made,
+    /// otherwise the API's answer: rejected, rate limited or a network error.
+    pub async fn validate(&self, raw_token: &str) -> Result<ValidatedToken, AuthError> {
+        let token = AuthToken::new(raw_token).ok_or_else(|| {
+            warn!("Invalid token format provided");
+            AuthError::invalid_format("token does not match expected Discord token format")
+        })?;
+
+        debug!("Token format validated, checking with Discord API");
+
+        let user = self.auth_port.validate_token(&token).await.map_err(|e| {
+            warn!(error = %e, "Token validation failed");
+            e
+        })?;
+
+        Ok(ValidatedToken { token, user })
+    }
+
     /// Deletes the stored token.
     ///
     /// # Errors
@@ -146,6 +164,19 @@ mod tests {
         assert!(matches!(result, Err(AuthError::TokenRejected { .. })));
     }
 
+    #[tokio::test]
+    async fn test_validate_does_not_store_token() {
+        let auth_port = Arc::new(MockAuthPort::new(true));
+        let storage_port = Arc::new(MockTokenStorage::new());
+
+        let use_case = LoginUseCase::new(auth_port, storage_port.clone());
+        let validated = use_case.validate(&make_valid_token()).await.unwrap();
+
+        assert_eq!(validated.user.username(), "testuser");
+        assert_eq!(validated.token.as_str(), make_valid_token());
+        assert!(!storage_port.has_token().await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_login_without_persistence() {
         let auth_port = Arc::new(MockAuthPort::new(true));
@@ -1,7 +1,9 @@
 //! Use case implementations.
 
+mod export_history_use_case;
 mod login_use_case;
 mod resolve_token_use_case;
 
-pub use login_use_case::LoginUseCase;
+pub use export_history_use_case::{ExportError, ExportHistoryUseCase};
+pub use login_use_case::{LoginUseCase, ValidatedToken};
 pub use resolve_token_use_case::ResolveTokenUseCase;
@@ -111,6 +111,7 @@ impl ChannelKind {
             Self::Dm | Self::GroupDm | Self::LfgGroupDm | Self::EphemeralDm => "@ ",
             Self::Category => "",
             Self::Forum | Self::Media => "󰭹 ",
+            Self::Announcement => "󰎟 ",
             _ => "#",
         }
     }
@@ -528,6 +529,12 @@ mod tests {
         assert_eq!(channel.display_name(), "󰕾 Voice");
     }
 
+    #[test]
+    fn test_announcement_channel_display() {
+        let channel = Channel::new(123_u64, "news", ChannelKind::Announcement);
+        assert_eq!(channel.display_name(), "󰎟 news");
+    }
+
     #[test]
     fn test_channel_kind_is_text_based() {
         assert!(ChannelKind::Text.is_text_based());
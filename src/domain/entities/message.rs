@@ -173,6 +173,8 @@ pub struct Embed {
     pub video: Option<EmbedVideo>,
     #[serde(default)]
     pub fields: Vec<EmbedField>,
+    #[serde(default)]
+    pub kind: EmbedKind,
 }
 
 #[allow(missing_docs)]
@@ -181,6 +183,51 @@ impl Embed {
     pub fn new() -> Self {
         Self::default()
     }
+
+    #[must_use]
+    pub const fn with_kind(mut self, kind: EmbedKind) -> Self {
+        self.kind = kind;
+        self
+    }
+}
+
+/// Embed `type` as reported by Discord.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedKind {
+    /// Built by a bot or webhook.
+    #[default]
+    Rich,
+    Image,
+    Video,
+    Gifv,
+    Article,
+    Link,
+    /// Types this client does not know about.
+    #[serde(other)]
+    Other,
+}
+
+impl EmbedKind {
+    /// Parses the embed `type` string, treating a missing one as rich.
+    #[must_use]
+    pub fn from_type(kind: Option<&str>) -> Self {
+        match kind {
+            None | Some("rich") => Self::Rich,
+            Some("image") => Self::Image,
+            Some("video") => Self::Video,
+            Some("gifv") => Self::Gifv,
+            Some("article") => Self::Article,
+            Some("link") => Self::Link,
+            Some(_) => Self::Other,
+        }
+    }
+
+    /// Whether Discord generated this embed from a URL in the message.
+    #[must_use]
+    pub const fn is_link_preview(self) -> bool {
+        matches!(self, Self::Article | Self::Link)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -251,6 +298,9 @@ pub struct Attachment {
 }
 
 impl Attachment {
+    /// Filename prefix Discord uses to mark an attachment as a spoiler.
+    pub const SPOILER_PREFIX: &'static str = "SPOILER_";
+
     #[must_use]
     pub fn new(
         id: impl Into<String>,
@@ -276,6 +326,12 @@ impl Attachment {
         self
     }
 
+    /// Whether the attachment is hidden behind a spoiler.
+    #[must_use]
+    pub fn is_spoiler(&self) -> bool {
+        self.spoiler || self.filename.starts_with(Self::SPOILER_PREFIX)
+    }
+
     #[must_use]
     pub fn is_image(&self) -> bool {
         if let Some(ct) = &self.content_type {
@@ -368,6 +424,16 @@ pub struct Message {
     message_reference: Option<MessageReference>,
     referenced_message: Option<Box<Message>>,
     guild_id: Option<GuildId>,
+    #[serde(default)]
+    forwarded: Option<Box<ForwardedMessage>>,
+    /// Client-chosen token echoed back by Discord, used to match a sent
+    /// message to its local echo.
+    #[serde(default)]
+    nonce: Option<String>,
+    /// Whether Discord reports this message as pinging `@everyone`/`@here`,
+    /// which only happens when the author was allowed to.
+    #[serde(default)]
+    mention_everyone: bool,
 }
 
 impl Message {
@@ -426,6 +492,16 @@ impl Message {
         self.flags
     }
 
+    #[must_use]
+    pub fn nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+
+    #[must_use]
+    pub const fn mentions_everyone(&self) -> bool {
+        self.mention_everyone
+    }
+
     #[must_use]
     pub const fn message_reference(&self) -> Option<&MessageReference> {
         self.message_reference.as_ref()
@@ -446,9 +522,48 @@ impl Message {
         self.edited_timestamp.is_some()
     }
 
+    /// Whether this message could still be published to channel followers.
+    #[must_use]
+    pub const fn is_publishable(&self) -> bool {
+        self.kind.is_regular()
+            && !self
+                .flags
+                .intersects(MessageFlags::CROSSPOSTED.union(MessageFlags::IS_CROSSPOST))
+    }
+
     #[must_use]
     pub fn is_reply(&self) -> bool {
-        self.kind == MessageKind::Reply || self.message_reference.is_some()
+        self.kind == MessageKind::Reply
+            || self
+                .message_reference
+                .as_ref()
+                .is_some_and(|r| r.kind != MessageReferenceKind::Forward)
+    }
+
+    /// Snapshot of the original message if this message is a forward.
+    #[must_use]
+    pub fn forwarded(&self) -> Option<&ForwardedMessage> {
+        self.forwarded.as_deref()
+    }
+
+    /// Link to the original message of a forward.
+    #[must_use]
+    pub fn forward_link(&self) -> Option<String> {
+        let reference = self
+            .message_reference
+            .as_ref()
+            .filter(|r| r.kind == MessageReferenceKind::Forward)?;
+        Some(message_link(
+            reference.guild_id,
+            reference.channel_id?,
+            reference.message_id?,
+        ))
+    }
+
+    /// Link to this message in the Discord client. `guild_id` is `None` for DMs.
+    #[must_use]
+    pub fn link(&self, guild_id: Option<GuildId>) -> String {
+        message_link(guild_id.or(self.guild_id), self.channel_id, self.id)
     }
 
     #[must_use]
@@ -467,11 +582,39 @@ impl Message {
     }
 }
 
+/// Whether a message reference is a reply or a forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MessageReferenceKind {
+    #[default]
+    Default,
+    Forward,
+}
+
+impl From<u8> for MessageReferenceKind {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Forward,
+            _ => Self::Default,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MessageReference {
     pub message_id: Option<MessageId>,
     pub channel_id: Option<ChannelId>,
     pub guild_id: Option<GuildId>,
+    #[serde(default)]
+    pub kind: MessageReferenceKind,
+}
+
+/// Content of a forwarded message as captured when it was forwarded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ForwardedMessage {
+    /// Original author, when Discord includes it in the snapshot.
+    pub author: Option<MessageAuthor>,
+    pub content: String,
+    pub attachments: Vec<Attachment>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -498,8 +641,15 @@ impl MessageReference {
             message_id,
             channel_id,
             guild_id,
+            kind: MessageReferenceKind::Default,
         }
     }
+
+    #[must_use]
+    pub const fn with_kind(mut self, kind: MessageReferenceKind) -> Self {
+        self.kind = kind;
+        self
+    }
 }
 
 #[allow(missing_docs)]
@@ -530,6 +680,9 @@ impl Message {
             message_reference: None,
             referenced_message: None,
             guild_id: None,
+            forwarded: None,
+            nonce: None,
+            mention_everyone: false,
         }
     }
 
@@ -592,12 +745,30 @@ impl Message {
         self
     }
 
+    #[must_use]
+    pub fn with_forwarded(mut self, forwarded: Option<ForwardedMessage>) -> Self {
+        self.forwarded = forwarded.map(Box::new);
+        self
+    }
+
     #[must_use]
     pub fn with_flags(mut self, flags: MessageFlags) -> Self {
         self.flags = flags;
         self
     }
 
+    #[must_use]
+    pub fn with_nonce(mut self, nonce: Option<String>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_mention_everyone(mut self, mention_everyone: bool) -> Self {
+        self.mention_everyone = mention_everyone;
+        self
+    }
+
     /// Checks if the message can be edited by the given user.
     ///
     /// Currently, a user can only edit their own messages.
@@ -606,3 +777,68 @@ impl Message {
         self.author.id == user.id_str()
     }
 }
+
+fn message_link(guild_id: Option<GuildId>, channel_id: ChannelId, message_id: MessageId) -> String {
+    let guild = guild_id.map_or_else(|| "@me".to_string(), |g| g.as_u64().to_string());
+    format!("https://discord.com/channels/{guild}/{channel_id}/{message_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> Message {
+        let author = MessageAuthor {
+            id: "1".to_string(),
+            username: "user".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        Message::new(
+            MessageId(30),
+            ChannelId(20),
+            author,
+            "hi".to_string(),
+            Local::now(),
+            MessageKind::Default,
+        )
+    }
+
+    #[test]
+    fn test_message_link() {
+        assert_eq!(
+            message().link(Some(GuildId(10))),
+            "https://discord.com/channels/10/20/30"
+        );
+        assert_eq!(
+            message().with_guild_id(Some(GuildId(10))).link(None),
+            "https://discord.com/channels/10/20/30"
+        );
+        assert_eq!(
+            message().link(None),
+            "https://discord.com/channels/@me/20/30"
+        );
+    }
+
+    #[test]
+    fn test_is_publishable() {
+        assert!(message().is_publishable());
+        assert!(
+            !message()
+                .with_flags(MessageFlags::CROSSPOSTED)
+                .is_publishable()
+        );
+        assert!(
+            !message()
+                .with_flags(MessageFlags::IS_CROSSPOST)
+                .is_publishable()
+        );
+        assert!(
+            message()
+                .with_flags(MessageFlags::SUPPRESS_EMBEDS)
+                .is_publishable()
+        );
+    }
+}
@@ -17,6 +17,7 @@ mod role;
 mod token;
 mod user;
 mod user_cache;
+mod voice_state;
 
 pub use channel::{
     Channel, ChannelFlags, ChannelId, ChannelKind, OverwriteType, PermissionOverwrite,
@@ -32,9 +33,9 @@ pub use image_stub::{ImageId, ImageMetadata, ImageSource, ImageStatus, LoadedIma
 
 pub use member::Member;
 pub use message::{
-    Attachment, Embed, EmbedAuthor, EmbedField, EmbedFooter, EmbedImage, EmbedProvider,
-    EmbedThumbnail, EmbedVideo, Message, MessageAuthor, MessageFlags, MessageId, MessageKind,
-    MessageReference, Reaction, ReactionEmoji,
+    Attachment, Embed, EmbedAuthor, EmbedField, EmbedFooter, EmbedImage, EmbedKind, EmbedProvider,
+    EmbedThumbnail, EmbedVideo, ForwardedMessage, Message, MessageAuthor, MessageFlags, MessageId,
+    MessageKind, MessageReference, MessageReferenceKind, Reaction, ReactionEmoji,
 };
 pub use permissions::Permissions;
 pub use read_state::ReadState;
@@ -43,3 +44,4 @@ pub use role::{Role, RoleId};
 pub use token::AuthToken;
 pub use user::{PremiumType, User, UserFlags, UserId};
 pub use user_cache::{CachedUser, UserCache};
+pub use voice_state::VoiceState;
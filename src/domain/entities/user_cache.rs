@@ -146,6 +146,16 @@ impl UserCache {
             .and_then(|inner| inner.users.get(user_id).cloned())
     }
 
+    /// Every cached user, in no particular order.
+    #[must_use]
+    pub fn users(&self) -> Vec<CachedUser> {
+        self.inner
+            .read()
+            .ok()
+            .map(|inner| inner.users.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
     #[must_use]
     pub fn get_display_name(&self, user_id: &str) -> Option<String> {
         self.get(user_id).map(|u| u.display_name())
@@ -0,0 +1,47 @@
+//! Voice state entity.
+
+use super::ChannelId;
+
+/// A user connected to a voice channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceState {
+    /// Connected user's ID.
+    pub user_id: String,
+    /// Voice channel the user is in.
+    pub channel_id: ChannelId,
+    /// Guild nickname, when the event carried the member.
+    pub nick: Option<String>,
+    /// Muted by themselves or by a moderator.
+    pub muted: bool,
+    /// Deafened by themselves or by a moderator.
+    pub deafened: bool,
+}
+
+impl VoiceState {
+    /// Creates a new voice state.
+    #[must_use]
+    pub fn new(user_id: impl Into<String>, channel_id: ChannelId) -> Self {
+        Self {
+            user_id: user_id.into(),
+            channel_id,
+            nick: None,
+            muted: false,
+            deafened: false,
+        }
+    }
+
+    /// Sets the mute and deafen flags.
+    #[must_use]
+    pub const fn with_flags(mut self, muted: bool, deafened: bool) -> Self {
+        self.muted = muted;
+        self.deafened = deafened;
+        self
+    }
+
+    /// Sets the guild nickname.
+    #[must_use]
+    pub fn with_nick(mut self, nick: Option<String>) -> Self {
+        self.nick = nick;
+        self
+    }
+}
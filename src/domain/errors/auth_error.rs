@@ -95,9 +95,49 @@ impl AuthError {
         )
     }
 
+    /// Short explanation for the login screen, leading with the kind of
+    /// failure so the user knows whether to fix the token or retry.
+    #[must_use]
+    pub fn user_message(&self) -> String {
+        match self {
+            Self::InvalidTokenFormat { reason } => format!("Invalid token: {reason}"),
+            Self::TokenRejected { message } => format!("Invalid token: {message}"),
+            Self::NetworkError { message } => format!("Network error: {message}"),
+            Self::RateLimited { retry_after_ms } => format!(
+                "Rate limited: try again in {}s",
+                retry_after_ms.div_ceil(1000).max(1)
+            ),
+            other => other.to_string(),
+        }
+    }
+
     /// Returns whether error is network related.
     #[must_use]
     pub const fn is_network_error(&self) -> bool {
         matches!(self, Self::NetworkError { .. } | Self::RateLimited { .. })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_message_names_the_failure() {
+        assert_eq!(
+            AuthError::rejected("invalid or expired token").user_message(),
+            "Invalid token: invalid or expired token"
+        );
+        assert_eq!(
+            AuthError::network("request timed out").user_message(),
+            "Network error: request timed out"
+        );
+        assert_eq!(
+            AuthError::RateLimited {
+                retry_after_ms: 1500
+            }
+            .user_message(),
+            "Rate limited: try again in 2s"
+        );
+    }
+}
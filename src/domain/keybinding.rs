@@ -10,6 +10,7 @@ pub enum Action {
     ToggleGuildsTree,
     ToggleFileExplorer,
     ToggleHiddenFiles,
+    ToggleAttachmentSpoiler,
 
     FocusGuilds,
     FocusMessages,
@@ -42,17 +43,33 @@ pub enum Action {
     CopyContent,
     CopyImage,
     YankId,
+    CopyMessageLink,
+    CopyCodeBlock,
     YankUrl,
     OpenAttachments,
     JumpToReply,
+    ToggleThreadList,
+    RetrySend,
+    ToggleMessageExpand,
+    PublishMessage,
+    PurgeOwnMessages,
+    NextMention,
+    PreviousMention,
+    JumpToLatest,
 
     OpenEditor,
     ClearInput,
+    ToggleReplyMention,
     Cancel,
     Paste,
     SecureLogout,
     ToggleDisplayName,
+    ToggleImagePreview,
+    ChangeNickname,
     ToggleQuickSwitcher,
+    CopyUnreadChannels,
+    ToggleNotificationHistory,
+    ClearRecents,
     None,
 }
 
@@ -17,7 +17,7 @@ pub mod search;
 pub mod serde_utils;
 pub mod services;
 
-pub use notification::{Notification, NotificationLevel};
+pub use notification::{Notification, NotificationLevel, NotificationSource};
 
 pub use connection::ConnectionStatus;
 pub use entities::{AuthToken, User};
@@ -1,5 +1,9 @@
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Local};
+
+use crate::domain::entities::ChannelId;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NotificationLevel {
     Info,
@@ -7,14 +11,24 @@ pub enum NotificationLevel {
     Error,
 }
 
+/// Channel a notification was raised for, so it can be jumped to later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationSource {
+    pub channel_id: ChannelId,
+    /// `#channel` or `@user`, as shown in the history panel.
+    pub name: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Notification {
     pub level: NotificationLevel,
     pub title: String,
     pub message: String,
     pub created_at: Instant,
+    pub timestamp: DateTime<Local>,
     pub displayed_at: Option<Instant>,
     pub duration: Duration,
+    pub source: Option<NotificationSource>,
 }
 
 impl Notification {
@@ -29,11 +43,22 @@ impl Notification {
             title: title.into(),
             message: message.into(),
             created_at: Instant::now(),
+            timestamp: Local::now(),
             displayed_at: None,
             duration: Duration::from_secs(5),
+            source: None,
         }
     }
 
+    #[must_use]
+    pub fn with_source(mut self, channel_id: ChannelId, name: impl Into<String>) -> Self {
+        self.source = Some(NotificationSource {
+            channel_id,
+            name: name.into(),
+        });
+        self
+    }
+
     #[must_use]
     pub fn with_duration(mut self, duration: Duration) -> Self {
         self.duration = duration;
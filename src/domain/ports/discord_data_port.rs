@@ -1,12 +1,19 @@
 //! Discord data port for fetching guilds and channels.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 
 use crate::domain::entities::{
-    AuthToken, Channel, ChannelId, ForumThread, Guild, GuildId, Message, MessageId, ReadState,
+    Attachment, AuthToken, Channel, ChannelId, ForumThread, Guild, GuildId, Member, Message,
+    MessageId, ReadState,
 };
 use crate::domain::errors::AuthError;
 
+/// Called with the expected wait when requests are held back by a rate limit.
+pub type RateLimitListener = Arc<dyn Fn(Duration) + Send + Sync>;
+
 /// Represents a direct message channel with a recipient.
 #[derive(Debug, Clone)]
 pub struct DirectMessageChannel {
@@ -26,6 +33,74 @@ pub struct DirectMessageChannel {
     pub has_unread: bool,
     /// The number of mentions in this channel.
     pub mention_count: u32,
+    /// One-to-one or group conversation.
+    pub kind: DmKind,
+}
+
+impl DirectMessageChannel {
+    #[must_use]
+    pub const fn is_group(&self) -> bool {
+        matches!(self.kind, DmKind::Group { .. })
+    }
+
+    /// Name shown for the conversation: the recipient for a one-to-one DM,
+    /// and the group's name or its participants for a group DM.
+    #[must_use]
+    pub fn name(&self, use_display_name: bool) -> String {
+        match &self.kind {
+            DmKind::Group {
+                name: Some(name), ..
+            } if !name.is_empty() => name.clone(),
+            DmKind::Group { recipients, .. } => {
+                let names: Vec<&str> = recipients
+                    .iter()
+                    .map(|recipient| recipient.name(use_display_name))
+                    .collect();
+                if names.is_empty() {
+                    "Empty group".to_string()
+                } else {
+                    names.join(", ")
+                }
+            }
+            DmKind::Direct => self
+                .recipient_global_name
+                .as_deref()
+                .filter(|_| use_display_name)
+                .unwrap_or(&self.recipient_username)
+                .to_string(),
+        }
+    }
+}
+
+/// Whether a DM is with one user or a group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DmKind {
+    #[default]
+    Direct,
+    /// Everyone in the group except the current user.
+    Group {
+        name: Option<String>,
+        recipients: Vec<DmRecipient>,
+    },
+}
+
+/// A member of a group DM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmRecipient {
+    pub id: String,
+    pub username: String,
+    pub discriminator: String,
+    pub global_name: Option<String>,
+}
+
+impl DmRecipient {
+    #[must_use]
+    pub fn name(&self, use_display_name: bool) -> &str {
+        self.global_name
+            .as_deref()
+            .filter(|_| use_display_name)
+            .unwrap_or(&self.username)
+    }
 }
 
 /// Options for fetching messages from a channel.
@@ -57,12 +132,42 @@ impl FetchMessagesOptions {
     }
 }
 
+/// Kinds of mention Discord can be told to ping for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MentionType {
+    Users,
+    Roles,
+    /// `@everyone` and `@here`.
+    Everyone,
+}
+
+/// Which mentions in a sent message actually ping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedMentions {
+    pub parse: Vec<MentionType>,
+}
+
+impl AllowedMentions {
+    /// Drops `@everyone`, `@here` and role pings, keeping user mentions.
+    #[must_use]
+    pub fn without_mass_mentions(mut self) -> Self {
+        self.parse.retain(|kind| *kind == MentionType::Users);
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SendMessageRequest {
     pub channel_id: ChannelId,
     pub content: String,
     pub reply_to: Option<MessageId>,
-    pub attachments: Vec<std::path::PathBuf>,
+    /// Ping the author of the message being replied to.
+    pub mention_reply: bool,
+    /// `None` leaves it to Discord, which pings everything mentioned.
+    pub allowed_mentions: Option<AllowedMentions>,
+    pub attachments: Vec<OutgoingAttachment>,
+    /// Echoed back on the created message so it can replace its local echo.
+    pub nonce: Option<String>,
 }
 
 impl SendMessageRequest {
@@ -72,7 +177,10 @@ impl SendMessageRequest {
             channel_id,
             content: content.into(),
             reply_to: None,
+            mention_reply: true,
+            allowed_mentions: None,
             attachments: Vec::new(),
+            nonce: None,
         }
     }
 
@@ -83,12 +191,67 @@ impl SendMessageRequest {
     }
 
     #[must_use]
-    pub fn with_attachments(mut self, attachments: Vec<std::path::PathBuf>) -> Self {
+    pub const fn with_reply_mention(mut self, mention: bool) -> Self {
+        self.mention_reply = mention;
+        self
+    }
+
+    #[must_use]
+    pub fn with_allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    #[must_use]
+    pub fn with_attachments(mut self, attachments: Vec<OutgoingAttachment>) -> Self {
         self.attachments = attachments;
         self
     }
 }
 
+/// Local file uploaded with a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingAttachment {
+    pub path: std::path::PathBuf,
+    /// Upload behind a spoiler.
+    pub spoiler: bool,
+}
+
+impl OutgoingAttachment {
+    /// Most files Discord accepts on one message.
+    pub const MAX_PER_MESSAGE: usize = 10;
+
+    #[must_use]
+    pub const fn new(path: std::path::PathBuf) -> Self {
+        Self {
+            path,
+            spoiler: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_spoiler(mut self, spoiler: bool) -> Self {
+        self.spoiler = spoiler;
+        self
+    }
+
+    /// Filename sent to Discord, which marks spoilers by a filename prefix.
+    #[must_use]
+    pub fn upload_filename(&self) -> String {
+        let filename = self
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if self.spoiler && !filename.starts_with(Attachment::SPOILER_PREFIX) {
+            format!("{}{filename}", Attachment::SPOILER_PREFIX)
+        } else {
+            filename
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EditMessageRequest {
     pub channel_id: ChannelId,
@@ -168,6 +331,14 @@ pub trait DiscordDataPort: Send + Sync {
         message_id: MessageId,
     ) -> Result<(), AuthError>;
 
+    /// Publishes a message in an announcement channel to its followers.
+    async fn crosspost_message(
+        &self,
+        token: &AuthToken,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AuthError>;
+
     /// Sends a typing indicator to a channel.
     async fn send_typing_indicator(
         &self,
@@ -199,4 +370,323 @@ pub trait DiscordDataPort: Send + Sync {
         token: &AuthToken,
         channel_id: ChannelId,
     ) -> Result<Channel, AuthError>;
+
+    /// Sets the current user's nickname in a guild; `None` resets it to the username.
+    async fn set_nickname(
+        &self,
+        token: &AuthToken,
+        guild_id: GuildId,
+        nick: Option<String>,
+    ) -> Result<Member, AuthError>;
+
+    /// Creates a group DM with the given users.
+    async fn create_group_dm(
+        &self,
+        token: &AuthToken,
+        user_ids: &[String],
+    ) -> Result<DirectMessageChannel, AuthError>;
+
+    /// Adds a user to an existing group DM.
+    async fn add_group_recipient(
+        &self,
+        token: &AuthToken,
+        channel_id: ChannelId,
+        user_id: &str,
+    ) -> Result<(), AuthError>;
+
+    /// Registers a callback for when requests wait out a rate limit.
+    fn set_rate_limit_listener(&self, _listener: RateLimitListener) {}
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use crate::domain::entities::{MessageAuthor, MessageKind};
+    use std::sync::Mutex;
+
+    /// Mock data port that keeps DM channels in memory, records group DM
+    /// calls and echoes sent messages back. Everything else returns nothing.
+    #[derive(Default)]
+    pub struct MockDiscordData {
+        pub dms: Mutex<Vec<DirectMessageChannel>>,
+        pub created_groups: Mutex<Vec<Vec<String>>>,
+        pub added_recipients: Mutex<Vec<(ChannelId, String)>>,
+    }
+
+    impl MockDiscordData {
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        fn unsupported<T>() -> Result<T, AuthError> {
+            Err(AuthError::unexpected("not supported by mock"))
+        }
+    }
+
+    fn recipient(user_id: &str) -> DmRecipient {
+        DmRecipient {
+            id: user_id.to_string(),
+            username: format!("user{user_id}"),
+            discriminator: "0".to_string(),
+            global_name: None,
+        }
+    }
+
+    #[async_trait]
+    impl DiscordDataPort for MockDiscordData {
+        async fn fetch_guilds(&self, _token: &AuthToken) -> Result<Vec<Guild>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn fetch_channels(
+            &self,
+            _token: &AuthToken,
+            _guild_id: u64,
+        ) -> Result<Vec<Channel>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn fetch_dm_channels(
+            &self,
+            _token: &AuthToken,
+        ) -> Result<Vec<DirectMessageChannel>, AuthError> {
+            Ok(self.dms.lock().unwrap().clone())
+        }
+
+        async fn fetch_read_states(&self, _token: &AuthToken) -> Result<Vec<ReadState>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn fetch_messages(
+            &self,
+            _token: &AuthToken,
+            _channel_id: u64,
+            _options: FetchMessagesOptions,
+        ) -> Result<Vec<Message>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn load_more_before_id(
+            &self,
+            _token: &AuthToken,
+            _channel_id: u64,
+            _message_id: u64,
+            _limit: u8,
+        ) -> Result<Vec<Message>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn send_message(
+            &self,
+            _token: &AuthToken,
+            request: SendMessageRequest,
+        ) -> Result<Message, AuthError> {
+            let author = MessageAuthor {
+                id: "1".to_string(),
+                username: "mock".to_string(),
+                discriminator: "0".to_string(),
+                avatar: None,
+                bot: false,
+                global_name: None,
+            };
+            Ok(Message::new(
+                1_u64.into(),
+                request.channel_id,
+                author,
+                request.content,
+                chrono::Local::now(),
+                MessageKind::Default,
+            ))
+        }
+
+        async fn edit_message(
+            &self,
+            _token: &AuthToken,
+            _request: EditMessageRequest,
+        ) -> Result<Message, AuthError> {
+            Self::unsupported()
+        }
+
+        async fn delete_message(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+            _message_id: MessageId,
+        ) -> Result<(), AuthError> {
+            Ok(())
+        }
+
+        async fn crosspost_message(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+            _message_id: MessageId,
+        ) -> Result<Message, AuthError> {
+            Self::unsupported()
+        }
+
+        async fn send_typing_indicator(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+        ) -> Result<(), AuthError> {
+            Ok(())
+        }
+
+        async fn acknowledge_message(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+            _message_id: MessageId,
+        ) -> Result<(), AuthError> {
+            Ok(())
+        }
+
+        async fn fetch_forum_threads(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+            _guild_id: Option<GuildId>,
+            _offset: u32,
+            _limit: Option<u8>,
+        ) -> Result<Vec<ForumThread>, AuthError> {
+            Ok(vec![])
+        }
+
+        async fn fetch_channel(
+            &self,
+            _token: &AuthToken,
+            _channel_id: ChannelId,
+        ) -> Result<Channel, AuthError> {
+            Self::unsupported()
+        }
+
+        async fn set_nickname(
+            &self,
+            _token: &AuthToken,
+            _guild_id: GuildId,
+            _nick: Option<String>,
+        ) -> Result<Member, AuthError> {
+            Self::unsupported()
+        }
+
+        async fn create_group_dm(
+            &self,
+            _token: &AuthToken,
+            user_ids: &[String],
+        ) -> Result<DirectMessageChannel, AuthError> {
+            let mut dms = self.dms.lock().unwrap();
+            let dm = DirectMessageChannel {
+                channel_id: (900 + dms.len()).to_string(),
+                recipient_id: user_ids.first().cloned().unwrap_or_default(),
+                recipient_username: String::new(),
+                recipient_discriminator: "0".to_string(),
+                recipient_global_name: None,
+                last_message_id: None,
+                has_unread: false,
+                mention_count: 0,
+                kind: DmKind::Group {
+                    name: None,
+                    recipients: user_ids.iter().map(|id| recipient(id)).collect(),
+                },
+            };
+            dms.push(dm.clone());
+            self.created_groups.lock().unwrap().push(user_ids.to_vec());
+            Ok(dm)
+        }
+
+        async fn add_group_recipient(
+            &self,
+            _token: &AuthToken,
+            channel_id: ChannelId,
+            user_id: &str,
+        ) -> Result<(), AuthError> {
+            let mut dms = self.dms.lock().unwrap();
+            let Some(DmKind::Group { recipients, .. }) = dms
+                .iter_mut()
+                .find(|dm| dm.channel_id == channel_id.to_string())
+                .map(|dm| &mut dm.kind)
+            else {
+                return Err(AuthError::unexpected("unknown group DM"));
+            };
+            recipients.push(recipient(user_id));
+            self.added_recipients
+                .lock()
+                .unwrap()
+                .push((channel_id, user_id.to_string()));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn recipient(username: &str, global_name: Option<&str>) -> DmRecipient {
+        DmRecipient {
+            id: username.to_string(),
+            username: username.to_string(),
+            discriminator: "0".to_string(),
+            global_name: global_name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_suppressed_mentions_keep_users() {
+        let allowed = AllowedMentions {
+            parse: vec![
+                MentionType::Users,
+                MentionType::Roles,
+                MentionType::Everyone,
+            ],
+        }
+        .without_mass_mentions();
+        assert_eq!(allowed.parse, vec![MentionType::Users]);
+    }
+
+    #[test]
+    fn test_group_dm_name() {
+        let mut dm = DirectMessageChannel {
+            channel_id: "1".to_string(),
+            recipient_id: "alice".to_string(),
+            recipient_username: "alice".to_string(),
+            recipient_discriminator: "0".to_string(),
+            recipient_global_name: Some("Alice".to_string()),
+            last_message_id: None,
+            has_unread: false,
+            mention_count: 0,
+            kind: DmKind::Direct,
+        };
+        assert_eq!(dm.name(true), "Alice");
+        assert!(!dm.is_group());
+
+        dm.kind = DmKind::Group {
+            name: None,
+            recipients: vec![recipient("alice", Some("Alice")), recipient("bob", None)],
+        };
+        assert!(dm.is_group());
+        assert_eq!(dm.name(true), "Alice, bob");
+        assert_eq!(dm.name(false), "alice, bob");
+
+        dm.kind = DmKind::Group {
+            name: Some("Book club".to_string()),
+            recipients: vec![recipient("alice", None)],
+        };
+        assert_eq!(dm.name(true), "Book club");
+    }
+
+    #[test]
+    fn test_spoiler_upload_filename() {
+        let attachment = OutgoingAttachment::new(PathBuf::from("/tmp/cat.png"));
+        assert_eq!(attachment.upload_filename(), "cat.png");
+        assert_eq!(
+            attachment.clone().with_spoiler(true).upload_filename(),
+            "SPOILER_cat.png"
+        );
+        let named = OutgoingAttachment::new(PathBuf::from("SPOILER_cat.png")).with_spoiler(true);
+        assert_eq!(named.upload_filename(), "SPOILER_cat.png");
+    }
 }
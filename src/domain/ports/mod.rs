@@ -9,7 +9,8 @@ mod token_storage_port;
 
 pub use auth_port::AuthPort;
 pub use discord_data_port::{
-    DirectMessageChannel, DiscordDataPort, EditMessageRequest, FetchMessagesOptions,
+    AllowedMentions, DirectMessageChannel, DiscordDataPort, DmKind, DmRecipient,
+    EditMessageRequest, FetchMessagesOptions, MentionType, OutgoingAttachment, RateLimitListener,
     SendMessageRequest,
 };
 pub use gateway_port::{GatewayEvent, GatewayPort};
@@ -22,5 +23,7 @@ pub use token_storage_port::TokenStoragePort;
 #[cfg(test)]
 pub mod mocks {
     pub use super::auth_port::mock::MockAuthPort;
+    pub use super::discord_data_port::mock::MockDiscordData;
+    pub use super::notification_port::mock::MockNotificationPort;
     pub use super::token_storage_port::mock::MockTokenStorage;
 }
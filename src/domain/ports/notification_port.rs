@@ -19,6 +19,7 @@ pub mod mock {
     }
 
     impl MockNotificationPort {
+        #[must_use]
         pub fn new() -> Self {
             Self::default()
         }
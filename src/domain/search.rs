@@ -32,7 +32,13 @@ pub struct SearchResult {
     pub guild_id: Option<String>,
     pub guild_name: Option<String>,
     pub parent_name: Option<String>,
+    /// Context drawn dimmed below the name.
+    #[serde(default)]
+    pub subtitle: Option<String>,
     pub score: i64,
+    /// Character positions in `name` the query matched, for highlighting.
+    #[serde(default)]
+    pub match_indices: Vec<usize>,
 }
 
 impl SearchResult {
@@ -44,7 +50,9 @@ impl SearchResult {
             guild_id: None,
             guild_name: None,
             parent_name: None,
+            subtitle: None,
             score: 0,
+            match_indices: Vec::new(),
         }
     }
 
@@ -61,11 +69,23 @@ impl SearchResult {
         self
     }
 
+    #[must_use]
+    pub fn with_subtitle(mut self, subtitle: Option<String>) -> Self {
+        self.subtitle = subtitle;
+        self
+    }
+
     #[must_use]
     pub fn with_score(mut self, score: i64) -> Self {
         self.score = score;
         self
     }
+
+    #[must_use]
+    pub fn with_match_indices(mut self, indices: Vec<usize>) -> Self {
+        self.match_indices = indices;
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -1,6 +1,7 @@
 //! Clipboard service using xclip/wl-copy shell commands (static-friendly).
 
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{debug, warn};
@@ -244,7 +245,7 @@ impl ClipboardService {
     }
 
     fn try_wl_paste_binary() -> Result<(Vec<u8>, String), ()> {
-        // First try to get MIME type
+        // First try to get MIME type, preferring an image over its text form
         let type_output = Command::new("wl-paste")
             .args(&["--list-types"])
             .output()
@@ -254,11 +255,18 @@ impl ClipboardService {
             return Err(());
         }
 
-        let mime = String::from_utf8_lossy(&type_output.stdout);
-        let mime = mime.lines().next().unwrap_or("application/octet-stream");
+        let types = String::from_utf8_lossy(&type_output.stdout);
+        let mime = types
+            .lines()
+            .find(|mime| mime.starts_with("image/"))
+            .or_else(|| types.lines().next())
+            .unwrap_or("application/octet-stream");
 
         // Get the actual data
-        let output = Command::new("wl-paste").output().map_err(|_| ())?;
+        let output = Command::new("wl-paste")
+            .args(["--type", mime])
+            .output()
+            .map_err(|_| ())?;
 
         if output.status.success() {
             Ok((output.stdout, mime.to_string()))
@@ -266,6 +274,83 @@ impl ClipboardService {
             Err(())
         }
     }
+
+    /// Saves an image on the clipboard to a temp file so it can be attached.
+    ///
+    /// Uses xclip or wl-paste on Linux, `pngpaste` on macOS and PowerShell
+    /// on Windows. Returns `None` when the clipboard holds no image, so the
+    /// caller can fall back to pasting text.
+    #[must_use]
+    pub fn save_image(&self) -> Option<PathBuf> {
+        let dir = super::temp_dir();
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!(error = %e, "Failed to create temp dir for pasted image");
+            return None;
+        }
+        let stem = dir.join(format!("paste_{}", uuid::Uuid::new_v4()));
+
+        if cfg!(target_os = "macos") {
+            let path = stem.with_extension("png");
+            return Self::try_pngpaste(&path).ok().map(|()| path);
+        }
+        if cfg!(windows) {
+            let path = stem.with_extension("png");
+            return Self::try_powershell_image(&path).ok().map(|()| path);
+        }
+
+        let (data, mime) = self.get_binary()?;
+        let path = stem.with_extension(image_extension(&mime)?);
+        if let Err(e) = std::fs::write(&path, data) {
+            warn!(error = %e, path = %path.display(), "Failed to save pasted image");
+            return None;
+        }
+        Some(path)
+    }
+
+    fn try_pngpaste(path: &Path) -> Result<(), ()> {
+        let status = Command::new("pngpaste")
+            .arg(path)
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|_| ())?;
+
+        if status.success() && path.exists() {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn try_powershell_image(path: &Path) -> Result<(), ()> {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $img = [System.Windows.Forms.Clipboard]::GetImage(); \
+             if ($img -eq $null) {{ exit 1 }}; \
+             $img.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+            path.display().to_string().replace('\'', "''")
+        );
+        let status = Command::new("powershell")
+            .args(["-NoProfile", "-STA", "-Command", &script])
+            .status()
+            .map_err(|_| ())?;
+
+        if status.success() && path.exists() {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// File extension for an image MIME type Discord can display.
+fn image_extension(mime: &str) -> Option<&'static str> {
+    match mime {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
 }
 
 /// Stub ImageData type for compatibility with existing code.
@@ -276,3 +361,16 @@ pub mod stub_image {
         pub bytes: std::borrow::Cow<'static, [u8]>,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_extension() {
+        assert_eq!(image_extension("image/png"), Some("png"));
+        assert_eq!(image_extension("image/jpeg"), Some("jpg"));
+        assert_eq!(image_extension("text/uri-list"), None);
+        assert_eq!(image_extension("text/plain"), None);
+    }
+}
@@ -80,10 +80,21 @@ pub struct AppConfig {
     #[serde(default)]
     pub disable_user_colors: bool,
 
-    /// Editor command to use for file viewing/editing.
-    /// Overrides $EDITOR environment variable.
+    /// External editor settings. A plain string sets just the command.
     #[serde(default)]
-    pub editor: Option<String>,
+    pub editor: EditorConfig,
+
+    /// Attachment file explorer settings.
+    #[serde(default)]
+    pub file_explorer: FileExplorerConfig,
+
+    /// Keybinding preset applied before custom keybindings.
+    #[serde(default)]
+    pub keymap: KeymapPreset,
+
+    /// How long a multi-key sequence such as `gg` waits for its next key.
+    #[serde(default = "default_sequence_timeout_ms")]
+    pub sequence_timeout_ms: u64,
 
     /// Custom keybindings.
     #[serde(default)]
@@ -97,10 +108,23 @@ pub struct AppConfig {
     #[serde(default)]
     pub notifications: NotificationsConfig,
 
+    /// Gateway event configuration.
+    #[serde(default)]
+    pub gateway: GatewayConfig,
+
+    /// Image download configuration.
+    #[serde(default)]
+    pub image: ImageConfig,
+
     /// Quick Switcher sort mode (Recents, Mixed).
     #[serde(default)]
     pub quick_switcher_order: QuickSwitcherSortMode,
 
+    /// Sections shown by the Quick Switcher before anything is typed, in
+    /// display order. Only used in mixed mode.
+    #[serde(default = "default_quick_switcher_sections")]
+    pub quick_switcher_sections: Vec<QuickSwitcherSection>,
+
     /// Theme configuration.
     #[serde(default)]
     pub theme: ThemeConfig,
@@ -122,7 +146,8 @@ pub struct UiConfig {
     #[serde(default = "default_true")]
     pub image_preview: bool,
 
-    /// Timestamp format string (chrono format).
+    /// Timestamp format string (chrono format), or `"relative"` for
+    /// relative timestamps.
     #[serde(default = "default_timestamp_format")]
     pub timestamp_format: String,
 
@@ -141,6 +166,127 @@ pub struct UiConfig {
     /// If true, hide messages from blocked users completely instead of showing a placeholder.
     #[serde(default)]
     pub hide_blocked_completely: bool,
+
+    /// Ask for confirmation before logging out while unsent drafts exist.
+    #[serde(default = "default_true")]
+    pub confirm_logout_with_drafts: bool,
+
+    /// Show the only channel of a category at the category's level.
+    #[serde(default)]
+    pub flatten_single_channel_categories: bool,
+
+    /// Number of top guilds whose visible channels are computed up front.
+    ///
+    /// Other guilds are prepared when first selected or expanded; 0 prepares
+    /// every guild eagerly.
+    #[serde(default)]
+    pub eager_guild_count: usize,
+
+    /// Content lines above which a message is shown collapsed; 0 never collapses.
+    #[serde(default)]
+    pub collapse_message_lines: usize,
+
+    /// Group consecutive replies from one author to the same message or author.
+    #[serde(default)]
+    pub group_replies: bool,
+
+    /// Require Esc twice to close overlays such as help or the quick switcher.
+    #[serde(default)]
+    pub double_esc_to_close: bool,
+
+    /// Return focus to the previously focused pane after closing the quick switcher.
+    #[serde(default)]
+    pub restore_focus_after_overlay: bool,
+
+    /// Show top-level guilds tree nodes by their initials, expanding the selected one.
+    #[serde(default)]
+    pub compact_guilds_tree: bool,
+
+    /// Mark the open channel read when the terminal regains focus.
+    #[serde(default)]
+    pub mark_read_on_focus: bool,
+
+    /// Keep `@everyone`/`@here` from counting as mentions.
+    #[serde(default)]
+    pub suppress_everyone_highlight: bool,
+
+    /// Pane focused when the chat screen opens.
+    #[serde(default)]
+    pub startup_focus: StartupFocus,
+
+    /// Ask before opening a masked link whose text hides its destination.
+    #[serde(default = "default_true")]
+    pub confirm_masked_links: bool,
+
+    /// Extra columns to indent wrapped continuation lines of a message.
+    #[serde(default)]
+    pub wrap_indent: u16,
+
+    /// Show the total unread mention count in the header.
+    #[serde(default = "default_true")]
+    pub show_mention_count: bool,
+
+    /// Ask before sending a message that pings @everyone or @here.
+    #[serde(default = "default_true")]
+    pub confirm_everyone_send: bool,
+
+    /// Show timestamps only on the first message of a group.
+    #[serde(default = "default_true")]
+    pub group_start_timestamps_only: bool,
+
+    /// Maximum number of your own messages removed by one purge.
+    #[serde(default = "default_purge_message_limit")]
+    pub purge_message_limit: usize,
+
+    /// Marker appended to text cut short to fit its space.
+    #[serde(default = "default_ellipsis")]
+    pub ellipsis: String,
+
+    /// Show link preview embeds generated from URLs; rich bot embeds always show.
+    #[serde(default = "default_true")]
+    pub show_embeds: bool,
+
+    /// Where message timestamps are drawn.
+    #[serde(default)]
+    pub timestamp_position: TimestampPosition,
+
+    /// Require a second Esc shortly after the first to quit.
+    #[serde(default)]
+    pub require_double_escape_to_quit: bool,
+
+    /// Show the animated splash screen while loading.
+    #[serde(default = "default_true")]
+    pub show_splash: bool,
+
+    /// Keep the splash screen up at least this many milliseconds.
+    #[serde(default)]
+    pub splash_min_duration_ms: u64,
+
+    /// Most channels remembered in the quick switcher's recents.
+    #[serde(default = "default_max_recents")]
+    pub max_recents: usize,
+
+    /// Whether opened channels are added to the quick switcher's recents.
+    #[serde(default = "default_true")]
+    pub record_recents: bool,
+
+    /// Vertical spacing of the message list.
+    #[serde(default)]
+    pub message_density: MessageDensity,
+
+    /// Pastes longer than this many characters offer to be sent as a file
+    /// instead; 0 disables the prompt.
+    #[serde(default = "default_large_paste_threshold")]
+    pub large_paste_threshold: usize,
+
+    /// Longest message that may be sent; raise to 4000 with Nitro. 0 removes
+    /// the limit.
+    #[serde(default = "default_max_message_length")]
+    pub max_message_length: usize,
+
+    /// Keep `@everyone`, `@here` and role mentions from pinging.
+    #[serde(default)]
+    pub default_suppress_mentions: bool,
 }
 
 impl Default for UiConfig {
@@ -154,6 +300,35 @@ impl Default for UiConfig {
             enable_animations: true,
             notification_duration: 5,
             hide_blocked_completely: false,
+            confirm_logout_with_drafts: true,
+            flatten_single_channel_categories: false,
+            eager_guild_count: 0,
+            collapse_message_lines: 0,
+            group_replies: false,
+            double_esc_to_close: false,
+            restore_focus_after_overlay: false,
+            compact_guilds_tree: false,
+            mark_read_on_focus: false,
+            suppress_everyone_highlight: false,
+            startup_focus: StartupFocus::default(),
+            confirm_masked_links: true,
+            wrap_indent: 0,
+            show_mention_count: true,
+            confirm_everyone_send: true,
+            group_start_timestamps_only: true,
+            purge_message_limit: 25,
+            ellipsis: default_ellipsis(),
+            show_embeds: true,
+            timestamp_position: TimestampPosition::Left,
+            require_double_escape_to_quit: false,
+            show_splash: true,
+            splash_min_duration_ms: 0,
+            max_recents: default_max_recents(),
+            record_recents: true,
+            message_density: MessageDensity::Cozy,
+            large_paste_threshold: 2000,
+            max_message_length: 2000,
+            default_suppress_mentions: false,
         }
     }
 }
@@ -168,6 +343,15 @@ pub struct NotificationsConfig {
     /// Enable internal TUI notifications.
     #[serde(default = "default_true")]
     pub internal_notifications: bool,
+
+    /// Desktop notifications for one channel within this many milliseconds
+    /// are merged into one (0 sends each message separately).
+    #[serde(default = "default_batch_window_ms")]
+    pub batch_window_ms: u64,
+
+    /// Most internal notifications queued at once; older ones are dropped.
+    #[serde(default = "default_max_internal_notifications")]
+    pub max_internal_notifications: usize,
 }
 
 impl Default for NotificationsConfig {
@@ -175,10 +359,154 @@ impl Default for NotificationsConfig {
         Self {
             enabled: true,
             internal_notifications: true,
+            batch_window_ms: default_batch_window_ms(),
+            max_internal_notifications: default_max_internal_notifications(),
+        }
+    }
+}
+
+/// Gateway event configuration.
+///
+/// Turning these off removes the matching intent bits on identify and drops
+/// any such events the gateway still sends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    /// Receive typing events (`GuildMessageTyping`, `DirectMessageTyping`).
+    /// Also hides the typing indicator when disabled.
+    #[serde(default = "default_true")]
+    pub enable_typing: bool,
+
+    /// Receive presence updates (`GuildPresences`, `UserPresence`).
+    #[serde(default = "default_true")]
+    pub enable_presence: bool,
+
+    /// Seconds without input before our status is set to Idle (0 disables).
+    #[serde(default)]
+    pub idle_after_secs: u64,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            enable_typing: true,
+            enable_presence: true,
+            idle_after_secs: 0,
+        }
+    }
+}
+
+/// Image download configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageConfig {
+    /// Width requested from the Discord CDN; the height follows at 4:3.
+    #[serde(default = "default_cdn_max_dimension")]
+    pub cdn_max_dimension: u32,
+
+    /// Request WebP images instead of PNG. GIFs stay GIFs while
+    /// `ui.enable_animations` is on.
+    #[serde(default = "default_true")]
+    pub prefer_webp: bool,
+
+    /// Most megabytes of images kept on disk; the least recently used are
+    /// evicted past this.
+    #[serde(default = "default_disk_cache_max_mb")]
+    pub disk_cache_max_mb: u64,
+
+    /// Images of messages up to this far beyond the visible ones are
+    /// fetched ahead of time, behind visible images.
+    #[serde(default = "default_image_prefetch_distance")]
+    pub prefetch_distance: usize,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            cdn_max_dimension: default_cdn_max_dimension(),
+            prefer_webp: true,
+            disk_cache_max_mb: default_disk_cache_max_mb(),
+            prefetch_distance: default_image_prefetch_distance(),
+        }
+    }
+}
+
+/// Attachment file explorer configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileExplorerConfig {
+    /// Directory the explorer first opens in; defaults to the home directory.
+    /// Later opens return to wherever it was last left.
+    #[serde(default)]
+    pub start_dir: Option<PathBuf>,
+
+    /// Directories opened with the number keys 1-9, in order. Missing ones
+    /// keep their number.
+    #[serde(default)]
+    pub bookmarks: Vec<PathBuf>,
+}
+
+/// External editor configuration.
+///
+/// Accepts either `editor = "nvim"` or an `[editor]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "EditorSetting")]
+pub struct EditorConfig {
+    /// Editor command, overriding $EDITOR. A `{file}` argument is replaced
+    /// with the draft's path; without one the path is appended.
+    pub command: Option<String>,
+
+    /// Extension given to the draft file (e.g. "md"), so the editor picks
+    /// matching syntax highlighting.
+    pub extension: Option<String>,
+}
+
+impl EditorConfig {
+    /// Draft file suffix, with its leading dot.
+    #[must_use]
+    pub fn suffix(&self) -> Option<String> {
+        self.extension
+            .as_deref()
+            .map(|ext| ext.trim_start_matches('.'))
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!(".{ext}"))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EditorSetting {
+    Command(String),
+    Table {
+        #[serde(default)]
+        command: Option<String>,
+        #[serde(default)]
+        extension: Option<String>,
+    },
+}
+
+impl From<EditorSetting> for EditorConfig {
+    fn from(setting: EditorSetting) -> Self {
+        match setting {
+            EditorSetting::Command(command) => Self {
+                command: Some(command),
+                extension: None,
+            },
+            EditorSetting::Table { command, extension } => Self { command, extension },
         }
     }
 }
 
+/// Named keybinding preset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapPreset {
+    /// Built-in bindings.
+    #[default]
+    Default,
+    /// Vim-style bindings (`gg`, `dd`, `/`).
+    Vim,
+    /// Emacs-style bindings (`Ctrl+n`, `Ctrl+p`, `Ctrl+s`).
+    Emacs,
+}
+
 /// Quick Switcher sorting strategy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -197,6 +525,63 @@ impl std::fmt::Display for QuickSwitcherSortMode {
     }
 }
 
+/// Group of entries listed by an empty Quick Switcher query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuickSwitcherSection {
+    Recents,
+    Dms,
+    Guilds,
+    Channels,
+}
+
+impl QuickSwitcherSection {
+    /// Every section, in the default display order.
+    pub const ALL: [Self; 4] = [Self::Recents, Self::Dms, Self::Guilds, Self::Channels];
+}
+
+fn default_quick_switcher_sections() -> Vec<QuickSwitcherSection> {
+    QuickSwitcherSection::ALL.to_vec()
+}
+
+/// Which pane has focus when the chat screen first opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupFocus {
+    /// Guild and channel tree (default).
+    #[default]
+    Guilds,
+    /// Message list.
+    Messages,
+    /// Message input.
+    Input,
+}
+
+/// Where message timestamps are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampPosition {
+    /// Before the author name (default).
+    #[default]
+    Left,
+    /// Against the right edge of the message pane.
+    Right,
+    /// Not shown at all.
+    Hidden,
+}
+
+/// Vertical spacing of the message list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDensity {
+    /// Padded embeds and one row per file attachment (default).
+    #[default]
+    Cozy,
+    /// Unpadded embeds and file attachments on one row, fitting more
+    /// messages on screen.
+    Compact,
+}
+
 /// Theme mode configuration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -224,6 +609,53 @@ pub struct ThemeConfig {
     /// Theme mode (Dark, Light, Auto).
     #[serde(default)]
     pub mode: ThemeMode,
+
+    /// Accent color overrides keyed by guild or channel id.
+    #[serde(default)]
+    pub accent_overrides: HashMap<String, String>,
+
+    /// Seconds between terminal background re-checks (0 disables).
+    #[serde(default)]
+    pub background_poll_interval: u64,
+
+    /// How authors without a role color get their name color.
+    #[serde(default)]
+    pub author_color_mode: AuthorColorMode,
+}
+
+/// How author name colors are derived when no role color applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorColorMode {
+    /// What a color is derived from.
+    #[serde(default)]
+    pub seed: AuthorColorSeed,
+    /// Which set of colors is picked from.
+    #[serde(default)]
+    pub palette: AuthorPalette,
+}
+
+/// What an author's color is derived from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorColorSeed {
+    /// The user id (default).
+    #[default]
+    Id,
+    /// The username, so a user keeps their color across accounts with the same name.
+    Username,
+}
+
+/// Colors author names are picked from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorPalette {
+    /// Bright terminal colors (default).
+    #[default]
+    Vibrant,
+    /// Soft pastel tones.
+    Pastel,
+    /// Shades of the accent color.
+    Accent,
 }
 
 fn default_accent_color() -> String {
@@ -242,12 +674,59 @@ fn default_notification_duration() -> u64 {
     5
 }
 
+fn default_cdn_max_dimension() -> u32 {
+    400
+}
+
+fn default_disk_cache_max_mb() -> u64 {
+    200
+}
+
+fn default_image_prefetch_distance() -> usize {
+    10
+}
+
+fn default_batch_window_ms() -> u64 {
+    2000
+}
+
+fn default_sequence_timeout_ms() -> u64 {
+    1500
+}
+
+fn default_max_internal_notifications() -> usize {
+    3
+}
+
+fn default_ellipsis() -> String {
+    "…".to_string()
+}
+
+fn default_purge_message_limit() -> usize {
+    25
+}
+
+fn default_max_recents() -> usize {
+    50
+}
+
+fn default_large_paste_threshold() -> usize {
+    2000
+}
+
+fn default_max_message_length() -> usize {
+    2000
+}
+
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
             accent_color: default_accent_color(),
             mention_color: None,
             mode: ThemeMode::default(),
+            accent_overrides: HashMap::new(),
+            background_poll_interval: 0,
+            author_color_mode: AuthorColorMode::default(),
         }
     }
 }
@@ -266,6 +745,9 @@ impl AppConfig {
         if let Some(log_level) = args.log_level {
             self.log_level = log_level;
         }
+        if let Some(keymap) = args.keymap {
+            self.keymap = keymap;
+        }
         if let Some(mouse) = args.mouse {
             self.mouse = mouse;
         }
@@ -340,11 +822,17 @@ impl Default for AppConfig {
             mouse: true,
             enable_desktop_notifications: true,
             disable_user_colors: false,
-            editor: None,
+            editor: EditorConfig::default(),
+            file_explorer: FileExplorerConfig::default(),
+            keymap: KeymapPreset::default(),
+            sequence_timeout_ms: default_sequence_timeout_ms(),
             keybindings: HashMap::new(),
             ui: UiConfig::default(),
             notifications: NotificationsConfig::default(),
+            gateway: GatewayConfig::default(),
+            image: ImageConfig::default(),
             quick_switcher_order: QuickSwitcherSortMode::default(),
+            quick_switcher_sections: default_quick_switcher_sections(),
             theme: ThemeConfig::default(),
         }
     }
@@ -365,21 +853,41 @@ mod tests {
 
             [notifications]
             internal_notifications = false
+            batch_window_ms = 500
+
+            [gateway]
+            enable_presence = false
+
+            [image]
+            cdn_max_dimension = 800
 
             [keybindings]
             "Ctrl+q" = "Quit"
             "Alt+Enter" = "SendMessage"
+
+            [theme.accent_overrides]
+            "123456789" = "Blue"
         "#;
 
         let config: AppConfig = toml::from_str(toml_content).expect("Failed to parse config");
 
-        assert_eq!(config.editor, Some("nvim".to_string()));
+        assert_eq!(config.editor.command, Some("nvim".to_string()));
         assert!(!config.ui.enable_animations);
         assert!(!config.notifications.internal_notifications);
+        assert_eq!(config.notifications.batch_window_ms, 500);
+        assert_eq!(config.notifications.max_internal_notifications, 3);
+        assert!(!config.gateway.enable_presence);
+        assert!(config.gateway.enable_typing);
+        assert_eq!(config.image.cdn_max_dimension, 800);
+        assert!(config.image.prefer_webp);
         assert_eq!(
             config.quick_switcher_order,
             QuickSwitcherSortMode::default()
         );
+        assert_eq!(
+            config.quick_switcher_sections,
+            default_quick_switcher_sections()
+        );
 
         assert_eq!(config.keybindings.len(), 2);
         assert_eq!(config.keybindings.get("Ctrl+q"), Some(&Action::Quit));
@@ -387,15 +895,98 @@ mod tests {
             config.keybindings.get("Alt+Enter"),
             Some(&Action::SendMessage)
         );
+        assert_eq!(
+            config
+                .theme
+                .accent_overrides
+                .get("123456789")
+                .map(String::as_str),
+            Some("Blue")
+        );
     }
 
     #[test]
     fn test_default_config() {
         let config = AppConfig::default();
 
-        assert_eq!(config.editor, None);
+        assert_eq!(config.editor, EditorConfig::default());
+        assert_eq!(config.file_explorer, FileExplorerConfig::default());
         assert!(config.keybindings.is_empty());
+        assert_eq!(config.keymap, KeymapPreset::Default);
+        assert_eq!(config.sequence_timeout_ms, 1500);
         assert!(config.ui.enable_animations); // default_true
+        assert!(config.ui.confirm_logout_with_drafts); // default_true
+        assert!(!config.ui.flatten_single_channel_categories);
+        assert_eq!(config.ui.eager_guild_count, 0);
+        assert_eq!(config.ui.collapse_message_lines, 0);
+        assert!(!config.ui.group_replies);
+        assert!(!config.ui.double_esc_to_close);
+        assert!(!config.ui.restore_focus_after_overlay);
+        assert!(!config.ui.compact_guilds_tree);
+        assert!(!config.ui.mark_read_on_focus);
+        assert!(!config.ui.suppress_everyone_highlight);
+        assert_eq!(config.ui.startup_focus, StartupFocus::default());
+        assert!(config.ui.confirm_masked_links); // default_true
+        assert_eq!(config.ui.wrap_indent, 0);
+        assert!(config.ui.show_mention_count); // default_true
+        assert!(config.ui.confirm_everyone_send); // default_true
+        assert!(config.ui.group_start_timestamps_only); // default_true
+        assert_eq!(config.ui.purge_message_limit, 25);
+        assert_eq!(config.ui.ellipsis, "…");
+        assert!(config.ui.show_embeds); // default_true
+        assert_eq!(config.ui.timestamp_position, TimestampPosition::Left);
+        assert!(!config.ui.require_double_escape_to_quit);
+        assert!(config.ui.show_splash); // default_true
+        assert_eq!(config.ui.splash_min_duration_ms, 0);
+        assert_eq!(config.ui.max_recents, 50);
+        assert!(config.ui.record_recents); // default_true
+        assert_eq!(config.ui.message_density, MessageDensity::Cozy);
+        assert_eq!(config.ui.large_paste_threshold, 2000);
+        assert_eq!(config.ui.max_message_length, 2000);
+        assert!(!config.ui.default_suppress_mentions);
         assert!(config.notifications.internal_notifications); // default_true
+        assert!(config.gateway.enable_typing); // default_true
+        assert!(config.gateway.enable_presence); // default_true
+        assert_eq!(config.gateway.idle_after_secs, 0);
+        assert_eq!(config.image.cdn_max_dimension, 400);
+        assert!(config.image.prefer_webp); // default_true
+        assert_eq!(config.image.disk_cache_max_mb, 200);
+        assert_eq!(config.image.prefetch_distance, 10);
+        assert!(config.theme.accent_overrides.is_empty());
+        assert_eq!(config.theme.background_poll_interval, 0);
+        assert_eq!(config.theme.author_color_mode, AuthorColorMode::default());
+    }
+
+    #[test]
+    fn test_parse_editor_table() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [editor]
+            command = "code --wait {file}"
+            extension = ".md"
+        "#,
+        )
+        .expect("Failed to parse config");
+
+        assert_eq!(config.editor.command.as_deref(), Some("code --wait {file}"));
+        assert_eq!(config.editor.suffix().as_deref(), Some(".md"));
+        assert_eq!(EditorConfig::default().suffix(), None);
+    }
+
+    #[test]
+    fn test_partial_ui_table_keeps_defaults() {
+        let config: AppConfig = toml::from_str(
+            r"
+            [ui]
+            group_guilds = true
+        ",
+        )
+        .expect("Failed to parse config");
+
+        assert!(config.ui.group_guilds);
+        assert_eq!(config.ui.max_recents, 50);
+        assert_eq!(config.ui.purge_message_limit, 25);
+        assert_eq!(config.ui.max_message_length, 2000);
+        assert!(config.ui.record_recents);
     }
 }
@@ -1,5 +1,6 @@
-use super::app_config::LogLevel;
-use clap::Parser;
+use super::app_config::{KeymapPreset, LogLevel};
+use crate::application::dto::ExportFormat;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -22,6 +23,10 @@ pub struct CliArgs {
     #[arg(long, value_enum)]
     pub log_level: Option<LogLevel>,
 
+    /// Keybinding preset (default, vim, emacs).
+    #[arg(long, value_enum)]
+    pub keymap: Option<KeymapPreset>,
+
     /// Enable mouse support.
     #[arg(long)]
     pub mouse: Option<bool>,
@@ -61,4 +66,46 @@ pub struct CliArgs {
     /// Accent color (name or hex code).
     #[arg(long)]
     pub accent_color: Option<String>,
+
+    /// Run the image pipeline self-test and exit.
+    #[arg(long, hide = true)]
+    pub image_self_test: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Headless commands that run without opening the TUI.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write a channel's message history to a file.
+    Export(ExportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Channel to export.
+    #[arg(long, value_name = "ID")]
+    pub channel: u64,
+
+    /// File to write.
+    #[arg(long, value_name = "PATH")]
+    pub out: PathBuf,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    pub format: ExportFormat,
+
+    /// Only export messages after this message id.
+    #[arg(long, value_name = "ID")]
+    pub after: Option<u64>,
+
+    /// Only export messages before this message id.
+    #[arg(long, value_name = "ID")]
+    pub before: Option<u64>,
+
+    /// Export at most this many messages: the oldest ones, or the newest
+    /// before `--before` when it is given without `--after`.
+    #[arg(long)]
+    pub limit: Option<usize>,
 }
@@ -4,34 +4,43 @@ use async_trait::async_trait;
 use chrono::{DateTime, Local, Utc};
 use reqwest::{Client, Method, StatusCode, header};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, warn};
 
 use super::dto::{
-    AttachmentResponse, ChannelResponse, DmChannelResponse, EditMessagePayload, EmbedDto,
-    ErrorResponse, GuildResponse, MessageReferencePayload, MessageResponse, SendMessagePayload,
-    UserResponse,
+    AllowedMentionsPayload, AttachmentResponse, ChannelResponse, CreateGroupDmPayload,
+    DmChannelResponse, EditMessagePayload, EmbedDto, ErrorResponse, GuildResponse,
+    MessageAuthorResponse, MessageReferencePayload, MessageResponse, SendMessagePayload,
+    SetNicknamePayload, SnapshotMessageResponse, UserResponse,
 };
 use super::identity::ClientIdentity;
+use super::rate_limit::{RateLimitResponse, RateLimiter, retry_after_header, route_key};
 use super::scraper;
 use crate::domain::entities::{
-    Attachment, AuthToken, Channel, ChannelId, ChannelKind, Embed, EmbedProvider, EmbedThumbnail,
-    ForumThread, Guild, GuildId, Message, MessageAuthor, MessageId, ReadState, User,
+    Attachment, AuthToken, Channel, ChannelId, ChannelKind, Embed, EmbedKind, EmbedProvider,
+    EmbedThumbnail, ForumThread, ForwardedMessage, Guild, GuildId, Member, Message, MessageAuthor,
+    MessageId, ReadState, User,
 };
 use crate::domain::errors::AuthError;
 use crate::domain::ports::{
-    AuthPort, DirectMessageChannel, DiscordDataPort, EditMessageRequest, FetchMessagesOptions,
-    SendMessageRequest,
+    AuthPort, DirectMessageChannel, DiscordDataPort, DmKind, DmRecipient, EditMessageRequest,
+    FetchMessagesOptions, RateLimitListener, SendMessageRequest,
 };
 
 const DISCORD_API_BASE: &str = "https://discord.com/api/v10";
 const MAX_IDLE_CONNECTIONS: usize = 10;
 const DEFAULT_MESSAGE_LIMIT: u8 = 50;
+/// Times a request is retried after `429 Too Many Requests` before failing.
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Wait used when a 429 gives no usable `retry_after`.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
 
 /// Discord API client for authentication and data fetching.
 pub struct DiscordClient {
     client: Client,
     base_url: String,
     pub identity: Arc<ClientIdentity>,
+    rate_limiter: RateLimiter,
 }
 
 impl DiscordClient {
@@ -61,7 +70,7 @@ impl DiscordClient {
         identity: Arc<ClientIdentity>,
     ) -> Result<Self, AuthError> {
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+            .timeout(Duration::from_secs(30))
             .pool_max_idle_per_host(MAX_IDLE_CONNECTIONS)
             .build()
             .map_err(|e| AuthError::unexpected(format!("failed to create HTTP client: {e}")))?;
@@ -70,6 +79,7 @@ impl DiscordClient {
             client,
             base_url: base_url.into(),
             identity,
+            rate_limiter: RateLimiter::new(),
         })
     }
 
@@ -86,27 +96,54 @@ impl DiscordClient {
             .header("X-Super-Properties", self.identity.get_header_value())
     }
 
+    /// Sends a request, waiting out known rate limits and retrying 429s.
+    ///
+    /// Requests whose body cannot be replayed (attachment uploads) are sent
+    /// once; a final 429 is returned for [`Self::handle_error_response`].
+    async fn send(&self, request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+        let mut request = request.build()?;
+        let route = route_key(request.method(), request.url().path());
+        let mut retries = 0;
+
+        loop {
+            self.rate_limiter.acquire(&route).await;
+            let replay = request.try_clone();
+            let response = self.client.execute(request).await?;
+            self.rate_limiter.update(&route, response.headers());
+
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            let Some(next) = replay.filter(|_| retries < MAX_RATE_LIMIT_RETRIES) else {
+                return Ok(response);
+            };
+
+            let header_wait = retry_after_header(response.headers());
+            let (wait, global) = match response.json::<RateLimitResponse>().await {
+                Ok(limit) => (
+                    Duration::from_secs_f64(limit.retry_after.max(0.0)),
+                    limit.global,
+                ),
+                Err(_) => (header_wait.unwrap_or(DEFAULT_RETRY_AFTER), false),
+            };
+            warn!(route = %route, wait_ms = wait.as_millis(), global, "Rate limited, retrying");
+            self.rate_limiter.block(&route, wait, global);
+            retries += 1;
+            request = next;
+        }
+    }
+
     async fn handle_error_response(
         &self,
         status: StatusCode,
         response: reqwest::Response,
     ) -> AuthError {
+        let retry_after = retry_after_header(response.headers()).unwrap_or(DEFAULT_RETRY_AFTER);
         let error_message = match response.json::<ErrorResponse>().await {
             Ok(error) => error.message,
             Err(_) => format!("HTTP {status}"),
         };
-
-        match status {
-            StatusCode::UNAUTHORIZED => AuthError::rejected("invalid or expired token"),
-            StatusCode::FORBIDDEN => AuthError::rejected(format!("access denied: {error_message}")),
-            StatusCode::TOO_MANY_REQUESTS => AuthError::RateLimited {
-                retry_after_ms: 5000,
-            },
-            StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
-                AuthError::network("Discord API is temporarily unavailable")
-            }
-            _ => AuthError::unexpected(format!("unexpected response: {status} - {error_message}")),
-        }
+        status_error(status, &error_message, retry_after)
     }
 
     fn parse_channels(channel_responses: Vec<ChannelResponse>, guild_id: u64) -> Vec<Channel> {
@@ -237,6 +274,7 @@ impl DiscordClient {
                     inline: f.inline,
                 })
                 .collect(),
+            kind: EmbedKind::from_type(embed.kind.as_deref()),
         }
     }
 
@@ -274,6 +312,29 @@ impl DiscordClient {
             .collect()
     }
 
+    fn parse_author(author: MessageAuthorResponse) -> MessageAuthor {
+        MessageAuthor {
+            id: author.id,
+            username: author.username,
+            discriminator: author.discriminator,
+            avatar: author.avatar,
+            bot: author.bot,
+            global_name: author.global_name,
+        }
+    }
+
+    fn parse_snapshot(snapshot: SnapshotMessageResponse) -> ForwardedMessage {
+        ForwardedMessage {
+            author: snapshot.author.map(Self::parse_author),
+            content: snapshot.content,
+            attachments: snapshot
+                .attachments
+                .into_iter()
+                .map(Self::parse_attachment)
+                .collect(),
+        }
+    }
+
     fn parse_message_response(response: MessageResponse, channel_id: u64) -> Option<Message> {
         let MessageResponse {
             id,
@@ -286,6 +347,7 @@ impl DiscordClient {
             embeds,
             message_reference,
             referenced_message,
+            message_snapshots,
             pinned,
             mentions,
             member: _,
@@ -296,14 +358,7 @@ impl DiscordClient {
         } = response;
 
         let id: u64 = id.parse().ok()?;
-        let message_author = MessageAuthor {
-            id: author.id,
-            username: author.username,
-            discriminator: author.discriminator,
-            avatar: author.avatar,
-            bot: author.bot,
-            global_name: author.global_name,
-        };
+        let message_author = Self::parse_author(author);
 
         let timestamp: DateTime<Utc> = timestamp.parse().ok()?;
 
@@ -330,10 +385,15 @@ impl DiscordClient {
                     .guild_id
                     .and_then(|id| id.parse::<u64>().ok())
                     .map(GuildId),
+                kind: r.kind.into(),
             };
             message = message.with_reference(mr);
         }
 
+        if let Some(snapshot) = message_snapshots.into_iter().next() {
+            message = message.with_forwarded(Some(Self::parse_snapshot(snapshot.message)));
+        }
+
         if let Some(ref_msg) = referenced_message {
             let ref_cid = ref_msg.channel_id.parse().unwrap_or(channel_id);
             if let Some(parsed_ref) = Self::parse_message_response(*ref_msg, ref_cid) {
@@ -386,9 +446,10 @@ impl AuthPort for DiscordClient {
         debug!("Validating token against Discord API");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to connect to Discord API");
@@ -434,8 +495,7 @@ impl AuthPort for DiscordClient {
         debug!("Performing Discord API health check");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .send()
+            .send(self.build_request(Method::GET, &url))
             .await
             .map_err(|e| {
                 if e.is_timeout() {
@@ -466,9 +526,10 @@ impl DiscordDataPort for DiscordClient {
         debug!("Fetching user guilds from Discord API");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch guilds");
@@ -525,9 +586,10 @@ impl DiscordDataPort for DiscordClient {
         debug!(guild_id = guild_id, "Fetching channels from Discord API");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch channels");
@@ -570,9 +632,10 @@ impl DiscordDataPort for DiscordClient {
         debug!("Fetching DM channels from Discord API");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch DM channels");
@@ -597,21 +660,7 @@ impl DiscordDataPort for DiscordClient {
 
         let dm_channels = dm_responses
             .into_iter()
-            .filter_map(|dm| {
-                let recipient = dm.recipients.first()?;
-                Some(DirectMessageChannel {
-                    channel_id: dm.id,
-                    recipient_id: recipient.id.clone(),
-                    recipient_username: recipient.username.clone(),
-                    recipient_discriminator: recipient.discriminator.clone(),
-                    recipient_global_name: recipient.global_name.clone(),
-                    last_message_id: dm
-                        .last_message_id
-                        .and_then(|id| id.parse::<u64>().ok().map(Into::into)),
-                    has_unread: false,
-                    mention_count: 0,
-                })
-            })
+            .filter_map(Self::parse_dm_channel)
             .collect();
 
         Ok(dm_channels)
@@ -653,9 +702,10 @@ impl DiscordDataPort for DiscordClient {
         );
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch messages");
@@ -730,6 +780,14 @@ impl DiscordDataPort for DiscordClient {
             message_reference: request.reply_to.map(|id| MessageReferencePayload {
                 message_id: id.as_u64().to_string(),
             }),
+            allowed_mentions: match (&request.allowed_mentions, request.reply_to) {
+                (Some(allowed), _) => Some(
+                    AllowedMentionsPayload::reply(request.mention_reply).with_parse(&allowed.parse),
+                ),
+                (None, Some(_)) => Some(AllowedMentionsPayload::reply(request.mention_reply)),
+                (None, None) => None,
+            },
+            nonce: request.nonce,
         };
 
         let mut request_builder = self
@@ -753,17 +811,13 @@ impl DiscordDataPort for DiscordClient {
                     .map_err(|e| AuthError::unexpected(format!("failed to set mime type: {e}")))?,
             );
 
-            for (index, path) in request.attachments.iter().enumerate() {
-                let filename = path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
+            for (index, attachment) in request.attachments.iter().enumerate() {
+                let filename = attachment.upload_filename();
 
-                let content = tokio::fs::read(path).await.map_err(|e| {
+                let content = tokio::fs::read(&attachment.path).await.map_err(|e| {
                     AuthError::unexpected(format!(
                         "failed to read attachment {}: {}",
-                        path.display(),
+                        attachment.path.display(),
                         e
                     ))
                 })?;
@@ -775,7 +829,7 @@ impl DiscordDataPort for DiscordClient {
             request_builder = request_builder.multipart(form);
         }
 
-        let response = request_builder.send().await.map_err(|e| {
+        let response = self.send(request_builder).await.map_err(|e| {
             warn!(error = %e, "Failed to send message");
             AuthError::network(e.to_string())
         })?;
@@ -820,11 +874,12 @@ impl DiscordDataPort for DiscordClient {
         };
 
         let response = self
-            .build_request(Method::PATCH, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .header(header::CONTENT_TYPE, "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.build_request(Method::PATCH, &url)
+                    .header(header::AUTHORIZATION, token.as_str())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .json(&payload),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to edit message");
@@ -868,9 +923,10 @@ impl DiscordDataPort for DiscordClient {
         );
 
         let response = self
-            .build_request(Method::DELETE, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::DELETE, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to delete message");
@@ -888,6 +944,53 @@ impl DiscordDataPort for DiscordClient {
         Ok(())
     }
 
+    async fn crosspost_message(
+        &self,
+        token: &AuthToken,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AuthError> {
+        let url = format!(
+            "{}/channels/{}/messages/{}/crosspost",
+            self.base_url,
+            channel_id.as_u64(),
+            message_id.as_u64()
+        );
+
+        debug!(
+            channel_id = %channel_id,
+            message_id = %message_id,
+            "Crossposting message via Discord API"
+        );
+
+        let response = self
+            .send(
+                self.build_request(Method::POST, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "Failed to crosspost message");
+                AuthError::network(e.to_string())
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(self.handle_error_response(status, response).await);
+        }
+
+        let message_response: MessageResponse = response.json().await.map_err(|e| {
+            warn!(error = %e, "Failed to parse message response");
+            AuthError::unexpected(format!("failed to parse message response: {e}"))
+        })?;
+
+        debug!(message_id = %message_response.id, "Message crossposted successfully");
+
+        Self::parse_message_response(message_response, channel_id.as_u64())
+            .ok_or_else(|| AuthError::unexpected("failed to parse crossposted message"))
+    }
+
     async fn send_typing_indicator(
         &self,
         token: &AuthToken,
@@ -898,9 +1001,10 @@ impl DiscordDataPort for DiscordClient {
         debug!(channel_id = %channel_id, "Sending typing indicator");
 
         let response = self
-            .build_request(Method::POST, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::POST, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to send typing indicator");
@@ -938,11 +1042,12 @@ impl DiscordDataPort for DiscordClient {
         let payload = serde_json::json!({ "token": null });
 
         let response = self
-            .build_request(Method::POST, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .header(header::CONTENT_TYPE, "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.build_request(Method::POST, &url)
+                    .header(header::AUTHORIZATION, token.as_str())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .json(&payload),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to acknowledge message");
@@ -979,9 +1084,10 @@ impl DiscordDataPort for DiscordClient {
         debug!("Fetching forum threads from URL: {}", url);
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch forum threads");
@@ -1020,9 +1126,10 @@ impl DiscordDataPort for DiscordClient {
         debug!(channel_id = %channel_id, "Fetching single channel from Discord API");
 
         let response = self
-            .build_request(Method::GET, &url)
-            .header(header::AUTHORIZATION, token.as_str())
-            .send()
+            .send(
+                self.build_request(Method::GET, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
             .await
             .map_err(|e| {
                 warn!(error = %e, "Failed to fetch channel");
@@ -1052,9 +1159,155 @@ impl DiscordDataPort for DiscordClient {
             .next()
             .ok_or_else(|| AuthError::unexpected("failed to parse fetched channel"))
     }
+
+    async fn set_nickname(
+        &self,
+        token: &AuthToken,
+        guild_id: GuildId,
+        nick: Option<String>,
+    ) -> Result<Member, AuthError> {
+        let url = format!("{}/guilds/{}/members/@me", self.base_url, guild_id.as_u64());
+
+        debug!(guild_id = %guild_id, "Setting nickname via Discord API");
+
+        let response = self
+            .send(
+                self.build_request(Method::PATCH, &url)
+                    .header(header::AUTHORIZATION, token.as_str())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .json(&SetNicknamePayload { nick }),
+            )
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "Failed to set nickname");
+                AuthError::network(e.to_string())
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(self.handle_error_response(status, response).await);
+        }
+
+        response.json().await.map_err(|e| {
+            warn!(error = %e, "Failed to parse member response");
+            AuthError::unexpected(format!("failed to parse member: {e}"))
+        })
+    }
+
+    async fn create_group_dm(
+        &self,
+        token: &AuthToken,
+        user_ids: &[String],
+    ) -> Result<DirectMessageChannel, AuthError> {
+        let url = format!("{}/users/@me/channels", self.base_url);
+
+        debug!(count = user_ids.len(), "Creating group DM via Discord API");
+
+        let response = self
+            .send(
+                self.build_request(Method::POST, &url)
+                    .header(header::AUTHORIZATION, token.as_str())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .json(&CreateGroupDmPayload {
+                        recipients: user_ids.to_vec(),
+                    }),
+            )
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "Failed to create group DM");
+                AuthError::network(e.to_string())
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(self.handle_error_response(status, response).await);
+        }
+
+        let dm: DmChannelResponse = response.json().await.map_err(|e| {
+            warn!(error = %e, "Failed to parse group DM response");
+            AuthError::unexpected(format!("failed to parse group DM: {e}"))
+        })?;
+
+        Self::parse_dm_channel(dm)
+            .ok_or_else(|| AuthError::unexpected("group DM has no recipients"))
+    }
+
+    async fn add_group_recipient(
+        &self,
+        token: &AuthToken,
+        channel_id: ChannelId,
+        user_id: &str,
+    ) -> Result<(), AuthError> {
+        let url = format!(
+            "{}/channels/{}/recipients/{}",
+            self.base_url,
+            channel_id.as_u64(),
+            user_id
+        );
+
+        debug!(channel_id = %channel_id, user_id, "Adding group DM recipient via Discord API");
+
+        let response = self
+            .send(
+                self.build_request(Method::PUT, &url)
+                    .header(header::AUTHORIZATION, token.as_str()),
+            )
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "Failed to add group DM recipient");
+                AuthError::network(e.to_string())
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(self.handle_error_response(status, response).await);
+        }
+
+        Ok(())
+    }
+
+    fn set_rate_limit_listener(&self, listener: RateLimitListener) {
+        self.rate_limiter.set_listener(listener);
+    }
 }
 
 impl DiscordClient {
+    fn parse_dm_channel(dm: DmChannelResponse) -> Option<DirectMessageChannel> {
+        let recipient = dm.recipients.first()?;
+        let mut channel = DirectMessageChannel {
+            channel_id: dm.id,
+            recipient_id: recipient.id.clone(),
+            recipient_username: recipient.username.clone(),
+            recipient_discriminator: recipient.discriminator.clone(),
+            recipient_global_name: recipient.global_name.clone(),
+            last_message_id: dm
+                .last_message_id
+                .and_then(|id| id.parse::<u64>().ok().map(Into::into)),
+            has_unread: false,
+            mention_count: 0,
+            kind: DmKind::Direct,
+        };
+        if ChannelKind::from(dm.kind) == ChannelKind::GroupDm {
+            channel.kind = DmKind::Group {
+                name: dm.name,
+                recipients: dm
+                    .recipients
+                    .into_iter()
+                    .map(|recipient| DmRecipient {
+                        id: recipient.id,
+                        username: recipient.username,
+                        discriminator: recipient.discriminator,
+                        global_name: recipient.global_name,
+                    })
+                    .collect(),
+            };
+        }
+        Some(channel)
+    }
+
     fn process_threads_response(
         response: super::dto::ThreadsResponse,
         parent_id: Option<ChannelId>,
@@ -1136,6 +1389,21 @@ impl Default for DiscordClient {
     }
 }
 
+/// Maps an unsuccessful response to the [`AuthError`] shown to the user.
+fn status_error(status: StatusCode, error_message: &str, retry_after: Duration) -> AuthError {
+    match status {
+        StatusCode::UNAUTHORIZED => AuthError::rejected("invalid or expired token"),
+        StatusCode::FORBIDDEN => AuthError::rejected(format!("access denied: {error_message}")),
+        StatusCode::TOO_MANY_REQUESTS => AuthError::RateLimited {
+            retry_after_ms: u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX),
+        },
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            AuthError::network("Discord API is temporarily unavailable")
+        }
+        _ => AuthError::unexpected(format!("unexpected response: {status} - {error_message}")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1147,6 +1415,76 @@ mod tests {
         assert!(client.is_ok());
     }
 
+    #[test]
+    fn test_status_error_maps_to_auth_errors() {
+        let wait = Duration::from_secs(3);
+        assert!(matches!(
+            status_error(StatusCode::UNAUTHORIZED, "401: Unauthorized", wait),
+            AuthError::TokenRejected { .. }
+        ));
+        assert!(matches!(
+            status_error(StatusCode::FORBIDDEN, "Missing Access", wait),
+            AuthError::TokenRejected { message } if message == "access denied: Missing Access"
+        ));
+        assert!(matches!(
+            status_error(StatusCode::TOO_MANY_REQUESTS, "", wait),
+            AuthError::RateLimited {
+                retry_after_ms: 3000
+            }
+        ));
+        assert!(matches!(
+            status_error(StatusCode::BAD_GATEWAY, "", wait),
+            AuthError::NetworkError { .. }
+        ));
+        assert!(matches!(
+            status_error(StatusCode::IM_A_TEAPOT, "", wait),
+            AuthError::Unexpected { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_request_waits_and_retries() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let body =
+            r#"{"message": "You are being rate limited.", "retry_after": 0.2, "global": false}"#;
+        let responses = [
+            format!(
+                "HTTP/1.1 429 Too Many Requests\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            ),
+            "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n".to_string(),
+        ];
+        let server = tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = socket.read(&mut request).await.unwrap();
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let client =
+            DiscordClient::with_base_url(format!("http://{addr}"), Arc::new(ClientIdentity::new()))
+                .unwrap();
+        let started = std::time::Instant::now();
+        let result = client
+            .delete_message(
+                &AuthToken::new_unchecked("token"),
+                ChannelId(1),
+                MessageId(2),
+            )
+            .await;
+
+        assert!(result.is_ok(), "expected retry to succeed, got {result:?}");
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        server.await.unwrap();
+    }
+
     #[test]
     fn test_guild_response_parsing() {
         let json = r#"[
@@ -1255,4 +1593,62 @@ mod tests {
         assert_eq!(r2.emoji.id.as_deref(), Some("999"));
         assert_eq!(r2.emoji.name.as_deref(), Some("custom"));
     }
+
+    #[test]
+    fn test_forwarded_message_parsing() {
+        use super::MessageResponse;
+
+        let json = r#"{
+            "id": "123456789",
+            "channel_id": "987654321",
+            "author": {
+                "id": "111222",
+                "username": "User",
+                "discriminator": "0",
+                "avatar": null
+            },
+            "content": "",
+            "timestamp": "2023-01-01T12:00:00Z",
+            "type": 0,
+            "message_reference": {
+                "type": 1,
+                "message_id": "555",
+                "channel_id": "444",
+                "guild_id": "333"
+            },
+            "message_snapshots": [
+                {
+                    "message": {
+                        "content": "original text",
+                        "timestamp": "2022-12-31T12:00:00Z",
+                        "attachments": [
+                            {
+                                "id": "1",
+                                "filename": "cat.png",
+                                "size": 10,
+                                "url": "https://cdn.discordapp.com/cat.png",
+                                "content_type": "image/png"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#;
+
+        let response: MessageResponse =
+            serde_json::from_str(json).expect("Should parse forwarded message JSON");
+        let message = DiscordClient::parse_message_response(response, 987_654_321)
+            .expect("Should convert to Message");
+
+        assert!(!message.is_reply());
+        let forwarded = message.forwarded().expect("Should carry the snapshot");
+        assert_eq!(forwarded.content, "original text");
+        assert!(forwarded.author.is_none());
+        assert_eq!(forwarded.attachments.len(), 1);
+        assert!(forwarded.attachments[0].is_image());
+        assert_eq!(
+            message.forward_link().as_deref(),
+            Some("https://discord.com/channels/333/444/555")
+        );
+    }
 }
@@ -2,6 +2,8 @@
 
 use serde::Deserialize;
 
+use crate::domain::ports::MentionType;
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct UserResponse {
@@ -143,8 +145,10 @@ pub struct DmRecipient {
 pub struct DmChannelResponse {
     pub id: String,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     pub kind: u8,
+    /// Set for named group DMs.
+    #[serde(default)]
+    pub name: Option<String>,
     #[serde(default)]
     pub recipients: Vec<DmRecipient>,
     pub last_message_id: Option<String>,
@@ -243,6 +247,8 @@ pub struct EmbedDto {
     pub video: Option<EmbedVideoDto>,
     #[serde(default)]
     pub fields: Vec<EmbedFieldDto>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -251,6 +257,23 @@ pub struct MessageReferenceResponse {
     pub message_id: Option<String>,
     pub channel_id: Option<String>,
     pub guild_id: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageSnapshotResponse {
+    pub message: SnapshotMessageResponse,
+}
+
+/// Partial message captured in a forward.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotMessageResponse {
+    #[serde(default)]
+    pub content: String,
+    pub author: Option<MessageAuthorResponse>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentResponse>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -273,6 +296,8 @@ pub struct MessageResponse {
     pub message_reference: Option<MessageReferenceResponse>,
     pub referenced_message: Option<Box<Self>>,
     #[serde(default)]
+    pub message_snapshots: Vec<MessageSnapshotResponse>,
+    #[serde(default)]
     pub pinned: bool,
     #[serde(default)]
     pub mentions: Vec<MentionUserResponse>,
@@ -332,6 +357,42 @@ pub struct SendMessagePayload {
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message_reference: Option<MessageReferencePayload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentionsPayload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AllowedMentionsPayload {
+    pub parse: Vec<&'static str>,
+    pub replied_user: bool,
+}
+
+impl AllowedMentionsPayload {
+    /// Leaves mentions in the content alone and only sets whether the
+    /// author of the replied-to message is pinged.
+    #[must_use]
+    pub fn reply(mention: bool) -> Self {
+        Self {
+            parse: vec!["users", "roles", "everyone"],
+            replied_user: mention,
+        }
+    }
+
+    /// Pings only the given kinds of mention found in the content.
+    #[must_use]
+    pub fn with_parse(mut self, parse: &[MentionType]) -> Self {
+        self.parse = parse
+            .iter()
+            .map(|kind| match kind {
+                MentionType::Users => "users",
+                MentionType::Roles => "roles",
+                MentionType::Everyone => "everyone",
+            })
+            .collect();
+        self
+    }
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -344,10 +405,48 @@ pub struct EditMessagePayload {
     pub content: String,
 }
 
+#[derive(Debug, serde::Serialize)]
+pub struct SetNicknamePayload {
+    pub nick: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct CreateGroupDmPayload {
+    pub recipients: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_reply_without_ping_serializes_allowed_mentions() {
+        let payload = SendMessagePayload {
+            content: Some("hi".to_string()),
+            message_reference: Some(MessageReferencePayload {
+                message_id: "5".to_string(),
+            }),
+            allowed_mentions: Some(AllowedMentionsPayload::reply(false)),
+            nonce: None,
+        };
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["allowed_mentions"]["replied_user"], false);
+        assert_eq!(
+            json["allowed_mentions"]["parse"],
+            serde_json::json!(["users", "roles", "everyone"])
+        );
+    }
+
+    #[test]
+    fn test_allowed_mentions_parse_narrows_pings() {
+        let payload = AllowedMentionsPayload::reply(true).with_parse(&[MentionType::Users]);
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["replied_user"], true);
+        assert_eq!(json["parse"], serde_json::json!(["users"]));
+    }
+
     #[test]
     fn test_channel_decoding_with_special_chars() {
         let json = r#"{
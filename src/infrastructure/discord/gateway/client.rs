@@ -9,11 +9,11 @@ use tracing::{debug, error, info, warn};
 
 use super::connection::{GatewayConnectionHandler, WebSocketConnection};
 use super::constants::{
-    GatewayIntents, MAX_RECONNECT_ATTEMPTS, RECONNECT_DELAY_BASE, RECONNECT_DELAY_MAX,
-    RECONNECT_JITTER_MAX,
+    GatewayIntent, GatewayIntents, MAX_RECONNECT_ATTEMPTS, RECONNECT_DELAY_BASE,
+    RECONNECT_DELAY_MAX, RECONNECT_JITTER_MAX,
 };
 use super::error::{GatewayCloseCode, GatewayError, GatewayResult};
-use super::events::{GatewayCommand, GatewayEventKind};
+use super::events::{GatewayCommand, GatewayEventKind, PresenceStatus};
 use super::heartbeat::HeartbeatManager;
 use super::payloads::GatewayPayload;
 use super::session::SessionInfo;
@@ -23,6 +23,12 @@ pub struct GatewayClientConfig {
     pub intents: GatewayIntents,
     pub auto_reconnect: bool,
     pub max_reconnect_attempts: u32,
+    /// Receive `TYPING_START` events. Disabling clears the
+    /// `GuildMessageTyping` and `DirectMessageTyping` intents.
+    pub enable_typing: bool,
+    /// Receive `PRESENCE_UPDATE` events. Disabling clears the
+    /// `GuildPresences` and `UserPresence` intents.
+    pub enable_presence: bool,
 }
 
 impl Default for GatewayClientConfig {
@@ -31,6 +37,8 @@ impl Default for GatewayClientConfig {
             intents: GatewayIntents::default_client(),
             auto_reconnect: true,
             max_reconnect_attempts: MAX_RECONNECT_ATTEMPTS,
+            enable_typing: true,
+            enable_presence: true,
         }
     }
 }
@@ -58,6 +66,35 @@ impl GatewayClientConfig {
         self.max_reconnect_attempts = attempts;
         self
     }
+
+    #[must_use]
+    pub const fn with_typing(mut self, enabled: bool) -> Self {
+        self.enable_typing = enabled;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_presence(mut self, enabled: bool) -> Self {
+        self.enable_presence = enabled;
+        self
+    }
+
+    /// Intents sent on identify, with the bits for disabled event kinds removed.
+    #[must_use]
+    pub const fn effective_intents(&self) -> GatewayIntents {
+        let mut intents = self.intents;
+        if !self.enable_typing {
+            intents = intents
+                .without(GatewayIntent::GuildMessageTyping)
+                .without(GatewayIntent::DirectMessageTyping);
+        }
+        if !self.enable_presence {
+            intents = intents
+                .without(GatewayIntent::GuildPresences)
+                .without(GatewayIntent::UserPresence);
+        }
+        intents
+    }
 }
 
 pub struct GatewayClient {
@@ -101,7 +138,7 @@ impl GatewayClient {
 
         let config = GatewayLoopConfig {
             token: token.to_string(),
-            intents: self.config.intents,
+            intents: self.config.effective_intents(),
             auto_reconnect: self.config.auto_reconnect,
             max_attempts: self.config.max_reconnect_attempts,
         };
@@ -338,6 +375,7 @@ async fn run_connection_loop(
             }
             Some(()) = heartbeat_error_rx.recv() => {
                 warn!("Heartbeat failure detected, closing connection");
+                handler.close(4000).await;
                 return Err(GatewayError::ConnectionClosed {
                     code: 4000,
                     reason: "Heartbeat ACK missing".to_string(),
@@ -361,6 +399,18 @@ async fn process_gateway_command(command: GatewayCommand, payload_tx: &mpsc::Sen
                 let _ = payload_tx.send(json).await;
             }
         }
+        GatewayCommand::UpdatePresence { status } => {
+            let since = (status == PresenceStatus::Idle).then(|| {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let now = chrono::Utc::now().timestamp_millis() as u64;
+                now
+            });
+            let payload = GatewayPayload::presence_update(status, since);
+            if let Ok(json) = serde_json::to_string(&payload) {
+                debug!(status = status.as_str(), "Sending presence update");
+                let _ = payload_tx.send(json).await;
+            }
+        }
     }
 }
 
@@ -437,6 +487,31 @@ mod tests {
         assert_eq!(config.max_reconnect_attempts, 5);
     }
 
+    #[test]
+    fn test_effective_intents_toggles() {
+        let intents = GatewayIntents::default_client().with_presence();
+
+        let config = GatewayClientConfig::new().with_intents(intents);
+        assert_eq!(config.effective_intents(), intents);
+
+        let no_presence = GatewayClientConfig::new()
+            .with_intents(intents)
+            .with_presence(false)
+            .effective_intents();
+        assert!(!no_presence.has(GatewayIntent::GuildPresences));
+        assert!(no_presence.has(GatewayIntent::GuildMessageTyping));
+        assert!(no_presence.has(GatewayIntent::DirectMessageTyping));
+
+        let no_typing = GatewayClientConfig::new()
+            .with_intents(intents)
+            .with_typing(false)
+            .effective_intents();
+        assert!(!no_typing.has(GatewayIntent::GuildMessageTyping));
+        assert!(!no_typing.has(GatewayIntent::DirectMessageTyping));
+        assert!(no_typing.has(GatewayIntent::GuildPresences));
+        assert!(no_typing.has(GatewayIntent::GuildMessages));
+    }
+
     #[test]
     fn test_backoff_delay() {
         let delay0 = calculate_backoff_delay(0);
@@ -7,15 +7,16 @@ use super::events::{
     Activity, ActivityKind, DispatchEvent, PresenceStatus, ReactionEmoji, UnavailableGuild,
 };
 use super::payloads::{
-    ActivityPayload, ChannelPayload, GatewayMessage, GuildCreatePayload, GuildDeletePayload,
-    HelloPayload, MessageDeleteBulkPayload, MessageDeletePayload, MessagePayload,
-    PresenceUpdatePayload, ReactionPayload, ReactionRemoveAllPayload, ReadyPayload,
+    ActivityPayload, AttachmentPayload, AuthorPayload, ChannelPayload, GatewayMessage,
+    GuildCreatePayload, GuildDeletePayload, HelloPayload, MessageDeleteBulkPayload,
+    MessageDeletePayload, MessagePayload, PresenceUpdatePayload, ReactionPayload,
+    ReactionRemoveAllPayload, ReadyPayload, ReadyVoiceStatePayload, SnapshotMessagePayload,
     TypingStartPayload, UserUpdatePayload,
 };
 
 use crate::domain::entities::{
-    Attachment, ChannelId, GuildId, Member, Message, MessageAuthor, MessageId, MessageKind,
-    MessageReference, Permissions, Role, RoleId, User,
+    Attachment, ChannelId, ForwardedMessage, GuildId, Member, Message, MessageAuthor, MessageFlags,
+    MessageId, MessageKind, MessageReference, Permissions, Role, RoleId, User, VoiceState,
 };
 
 const INITIAL_BUFFER_SIZE: usize = 32 * 1024;
@@ -331,12 +332,19 @@ impl EventParser {
             }
         }
 
+        let voice_states = ready
+            .guilds
+            .iter()
+            .flat_map(|g| Self::voice_states(&g.voice_states))
+            .collect();
+
         let guilds = ready
             .guilds
             .iter()
             .filter_map(|g| {
                 g.id.parse::<u64>().ok().map(|id| UnavailableGuild {
                     id: GuildId(id),
+                    name: g.name.clone(),
                     unavailable: g.unavailable,
                 })
             })
@@ -359,6 +367,12 @@ impl EventParser {
             })
             .collect();
 
+        let status = ready
+            .user_settings
+            .as_ref()
+            .and_then(|s| s.status.as_deref())
+            .map(PresenceStatus::parse);
+
         let guild_folders = ready
             .user_settings
             .map(|s| s.guild_folders)
@@ -408,6 +422,8 @@ impl EventParser {
             read_states,
             guild_folders,
             relationships,
+            status,
+            voice_states,
         })
     }
 
@@ -839,6 +855,8 @@ impl EventParser {
             })
             .collect();
 
+        let voice_states = Self::voice_states(&payload.voice_states).collect();
+
         Ok(DispatchEvent::GuildCreate {
             guild_id: GuildId(guild_id),
             name: payload.name,
@@ -847,6 +865,19 @@ impl EventParser {
             threads,
             roles,
             members,
+            voice_states,
+        })
+    }
+
+    /// Voice states of users connected to a channel; entries without one
+    /// are skipped.
+    fn voice_states(payloads: &[ReadyVoiceStatePayload]) -> impl Iterator<Item = VoiceState> + '_ {
+        payloads.iter().filter_map(|vs| {
+            let channel_id = vs.channel_id.as_ref()?.parse::<u64>().ok()?;
+            Some(
+                VoiceState::new(vs.user_id.clone(), ChannelId(channel_id))
+                    .with_flags(vs.mute || vs.self_mute, vs.deaf || vs.self_deaf),
+            )
         })
     }
 
@@ -900,6 +931,7 @@ impl EventParser {
                 GatewayError::serialization(format!("Failed to parse UserSettingsUpdate: {e}"))
             })?;
 
+        let status = payload.status.as_deref().map(PresenceStatus::parse);
         let guild_folders = payload
             .guild_folders
             .into_iter()
@@ -923,7 +955,10 @@ impl EventParser {
             })
             .collect();
 
-        Ok(DispatchEvent::UserSettingsUpdate { guild_folders })
+        Ok(DispatchEvent::UserSettingsUpdate {
+            guild_folders,
+            status,
+        })
     }
 
     fn parse_voice_state_update(data: serde_json::Value) -> GatewayResult<DispatchEvent> {
@@ -941,10 +976,20 @@ impl EventParser {
             .and_then(|id| id.parse::<u64>().ok())
             .map(ChannelId);
 
+        let (user, nick) = payload.member.map_or((None, None), |member| {
+            let user = member.user.map(|u| {
+                User::new(u.id, u.username, u.discriminator, u.avatar, u.bot, None)
+                    .with_global_name(u.global_name.unwrap_or_default())
+            });
+            (user, member.nick)
+        });
+
         Ok(DispatchEvent::VoiceStateUpdate {
             guild_id,
             channel_id,
             user_id: payload.user_id,
+            user,
+            nick,
             session_id: payload.session_id,
             deaf: payload.deaf,
             mute: payload.mute,
@@ -1027,14 +1072,7 @@ impl EventParser {
             .parse()
             .map_err(|_| GatewayError::protocol("Invalid timestamp"))?;
 
-        let author = MessageAuthor {
-            id: payload.author.id,
-            username: payload.author.username,
-            discriminator: payload.author.discriminator,
-            avatar: payload.author.avatar,
-            bot: payload.author.bot,
-            global_name: payload.author.global_name,
-        };
+        let author = Self::convert_author(payload.author);
 
         let mut message = Message::new(
             MessageId(id),
@@ -1044,19 +1082,20 @@ impl EventParser {
             timestamp.with_timezone(&Local),
             MessageKind::from(payload.kind),
         )
-        .with_pinned(payload.pinned);
+        .with_pinned(payload.pinned)
+        .with_flags(MessageFlags::from_bits_truncate(payload.flags))
+        .with_mention_everyone(payload.mention_everyone)
+        .with_nonce(payload.nonce.and_then(|nonce| match nonce {
+            serde_json::Value::String(s) => Some(s),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }));
 
         if !payload.attachments.is_empty() {
             let attachments: Vec<Attachment> = payload
                 .attachments
                 .into_iter()
-                .map(|a| {
-                    let mut att = Attachment::new(a.id, a.filename, a.size, a.url);
-                    if let Some(ct) = a.content_type {
-                        att = att.with_content_type(ct);
-                    }
-                    att
-                })
+                .map(Self::convert_attachment)
                 .collect();
             message = message.with_attachments(attachments);
         }
@@ -1070,12 +1109,20 @@ impl EventParser {
         if let Some(reference) = payload.message_reference {
             let ref_msg_id = reference.message_id.and_then(|id| id.parse::<u64>().ok());
             let ref_channel_id = reference.channel_id.and_then(|id| id.parse::<u64>().ok());
+            let ref_guild_id = reference.guild_id.and_then(|id| id.parse::<u64>().ok());
 
-            message = message.with_reference(MessageReference::new(
-                ref_msg_id.map(Into::into),
-                ref_channel_id.map(Into::into),
-                None,
-            ));
+            message = message.with_reference(
+                MessageReference::new(
+                    ref_msg_id.map(Into::into),
+                    ref_channel_id.map(Into::into),
+                    ref_guild_id.map(GuildId),
+                )
+                .with_kind(reference.kind.into()),
+            );
+        }
+
+        if let Some(snapshot) = payload.message_snapshots.into_iter().next() {
+            message = message.with_forwarded(Some(Self::convert_snapshot(snapshot.message)));
         }
 
         if let Some(referenced) = payload.referenced_message
@@ -1105,6 +1152,38 @@ impl EventParser {
         Ok(message)
     }
 
+    fn convert_author(payload: AuthorPayload) -> MessageAuthor {
+        MessageAuthor {
+            id: payload.id,
+            username: payload.username,
+            discriminator: payload.discriminator,
+            avatar: payload.avatar,
+            bot: payload.bot,
+            global_name: payload.global_name,
+        }
+    }
+
+    fn convert_attachment(payload: AttachmentPayload) -> Attachment {
+        let mut attachment =
+            Attachment::new(payload.id, payload.filename, payload.size, payload.url);
+        if let Some(ct) = payload.content_type {
+            attachment = attachment.with_content_type(ct);
+        }
+        attachment
+    }
+
+    fn convert_snapshot(payload: SnapshotMessagePayload) -> ForwardedMessage {
+        ForwardedMessage {
+            author: payload.author.map(Self::convert_author),
+            content: payload.content,
+            attachments: payload
+                .attachments
+                .into_iter()
+                .map(Self::convert_attachment)
+                .collect(),
+        }
+    }
+
     fn convert_activity(payload: ActivityPayload) -> Activity {
         Activity {
             name: payload.name,
@@ -1314,4 +1393,136 @@ mod tests {
             panic!("Expected Ready event");
         }
     }
+
+    #[test]
+    fn test_parse_ready_user_settings_status() {
+        let data = serde_json::json!({
+            "session_id": "session123",
+            "user": {
+                "id": "12345",
+                "username": "test",
+                "discriminator": "0000",
+                "avatar": null
+            },
+            "user_settings": { "status": "dnd" }
+        });
+
+        let Ok(DispatchEvent::Ready { status, .. }) = EventParser::parse_ready(data) else {
+            panic!("Expected Ready event");
+        };
+        assert_eq!(status, Some(PresenceStatus::DoNotDisturb));
+    }
+
+    #[test]
+    fn test_parse_message_create_nonce() {
+        for (nonce, expected) in [
+            (serde_json::json!("1234"), Some("1234")),
+            (serde_json::json!(5678), Some("5678")),
+            (serde_json::Value::Null, None),
+        ] {
+            let data = serde_json::json!({
+                "id": "10",
+                "channel_id": "20",
+                "author": { "id": "30", "username": "user" },
+                "content": "hi",
+                "timestamp": "2024-01-01T00:00:00+00:00",
+                "edited_timestamp": null,
+                "nonce": nonce,
+            });
+            match EventParser::parse_dispatch("MESSAGE_CREATE", Some(data)).unwrap() {
+                DispatchEvent::MessageCreate { message } => {
+                    assert_eq!(message.nonce(), expected);
+                }
+                _ => panic!("Expected MessageCreate event"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_message_create_mention_everyone_flag() {
+        for (flag, expected) in [
+            (serde_json::json!(true), true),
+            (serde_json::Value::Null, false),
+        ] {
+            let mut data = serde_json::json!({
+                "id": "10",
+                "channel_id": "20",
+                "author": { "id": "30", "username": "user" },
+                "content": "@everyone hi",
+                "timestamp": "2024-01-01T00:00:00+00:00",
+                "edited_timestamp": null,
+            });
+            if !flag.is_null() {
+                data["mention_everyone"] = flag;
+            }
+            match EventParser::parse_dispatch("MESSAGE_CREATE", Some(data)).unwrap() {
+                DispatchEvent::MessageCreate { message } => {
+                    assert_eq!(message.mentions_everyone(), expected);
+                }
+                _ => panic!("Expected MessageCreate event"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_voice_state_update_member() {
+        let data = serde_json::json!({
+            "guild_id": "1",
+            "channel_id": "2",
+            "user_id": "3",
+            "session_id": "abc",
+            "deaf": false,
+            "mute": false,
+            "self_deaf": false,
+            "self_mute": true,
+            "self_video": false,
+            "suppress": false,
+            "member": {
+                "nick": "Ferris",
+                "user": {
+                    "id": "3",
+                    "username": "ferris",
+                    "discriminator": "0",
+                    "global_name": "Ferris the Crab",
+                    "avatar": null
+                }
+            }
+        });
+        match EventParser::parse_dispatch("VOICE_STATE_UPDATE", Some(data)).unwrap() {
+            DispatchEvent::VoiceStateUpdate {
+                channel_id,
+                user,
+                nick,
+                self_mute,
+                ..
+            } => {
+                assert_eq!(channel_id, Some(ChannelId(2)));
+                assert_eq!(user.unwrap().username(), "ferris");
+                assert_eq!(nick.as_deref(), Some("Ferris"));
+                assert!(self_mute);
+            }
+            _ => panic!("Expected VoiceStateUpdate event"),
+        }
+    }
+
+    #[test]
+    fn test_parse_guild_create_voice_states() {
+        let data = serde_json::json!({
+            "id": "1",
+            "name": "Guild",
+            "voice_states": [
+                { "user_id": "3", "channel_id": "2", "self_deaf": true },
+                { "user_id": "4", "channel_id": null }
+            ]
+        });
+        match EventParser::parse_dispatch("GUILD_CREATE", Some(data)).unwrap() {
+            DispatchEvent::GuildCreate { voice_states, .. } => {
+                assert_eq!(
+                    voice_states,
+                    vec![VoiceState::new("3", ChannelId(2)).with_flags(false, true)]
+                );
+            }
+            _ => panic!("Expected GuildCreate event"),
+        }
+    }
 }
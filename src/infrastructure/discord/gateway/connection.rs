@@ -9,12 +9,14 @@ use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio::time::timeout;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
 use tracing::{debug, error, info, trace, warn};
 
 use super::codec::{EventParser, GatewayCodec};
 use super::constants::{
-    CONNECTION_TIMEOUT, GATEWAY_URL, GatewayIntents, GatewayOpcode, IDENTIFY_TIMEOUT,
+    CONNECTION_TIMEOUT, GATEWAY_URL, GatewayIntent, GatewayIntents, GatewayOpcode, IDENTIFY_TIMEOUT,
 };
 use super::error::{GatewayError, GatewayResult};
 use super::events::{DispatchEvent, GatewayEventKind};
@@ -31,6 +33,9 @@ type WsReader = SplitStream<WsStream>;
 pub trait GatewayConnection: Send + Sync {
     async fn connect(&mut self, gateway_url: Option<&str>) -> GatewayResult<()>;
     async fn disconnect(&mut self) -> GatewayResult<()>;
+    /// Closes with a specific close code. Codes other than 1000/1001 keep
+    /// the session resumable.
+    async fn close(&mut self, code: u16) -> GatewayResult<()>;
     async fn send(&mut self, payload: &GatewayPayload) -> GatewayResult<()>;
     async fn receive(&mut self) -> GatewayResult<Option<GatewayMessage>>;
     fn is_connected(&self) -> bool;
@@ -95,6 +100,17 @@ impl GatewayConnection for WebSocketConnection {
         Ok(())
     }
 
+    async fn close(&mut self, code: u16) -> GatewayResult<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            let frame = CloseFrame {
+                code: CloseCode::from(code),
+                reason: "".into(),
+            };
+            let _ = writer.send(WsMessage::Close(Some(frame))).await;
+        }
+        self.disconnect().await
+    }
+
     async fn send(&mut self, payload: &GatewayPayload) -> GatewayResult<()> {
         let writer = self.writer.as_mut().ok_or(GatewayError::NotConnected)?;
 
@@ -392,6 +408,9 @@ impl GatewayConnectionHandler {
                     match serde_json::from_str::<GatewayPayload>(&payload) {
                         Ok(gateway_payload) => {
                             trace!(op = gateway_payload.op, "Sending payload to gateway");
+                            if gateway_payload.op == GatewayOpcode::Heartbeat.as_u8() {
+                                self.state.record_heartbeat_sent();
+                            }
                             if let Err(e) = self.connection.send(&gateway_payload).await {
                                 warn!(error = %e, "Failed to send payload");
                             }
@@ -457,8 +476,30 @@ impl GatewayConnectionHandler {
         Ok(())
     }
 
+    /// User accounts may still receive events whose intents were not
+    /// requested, so those are filtered here as well.
+    const fn is_subscribed(&self, event: &DispatchEvent) -> bool {
+        match event {
+            DispatchEvent::TypingStart { guild_id, .. } => {
+                if guild_id.is_some() {
+                    self.intents.has(GatewayIntent::GuildMessageTyping)
+                } else {
+                    self.intents.has(GatewayIntent::DirectMessageTyping)
+                }
+            }
+            DispatchEvent::PresenceUpdate { .. } => {
+                self.intents.has(GatewayIntent::GuildPresences)
+                    || self.intents.has(GatewayIntent::UserPresence)
+            }
+            _ => true,
+        }
+    }
+
     fn handle_dispatch(&self, event_type: &str, data: Option<serde_json::Value>) {
         match EventParser::parse_dispatch(event_type, data) {
+            Ok(event) if !self.is_subscribed(&event) => {
+                debug!(event = event_type, "Dropping event for disabled intent");
+            }
             Ok(event) => {
                 debug!(event = event_type, "Dispatching event");
                 let _ = self.event_tx.send(GatewayEventKind::Dispatch(event));
@@ -469,6 +510,13 @@ impl GatewayConnectionHandler {
         }
     }
 
+    /// Closes the connection with `code`, e.g. 4000 for a zombied connection.
+    pub async fn close(&mut self, code: u16) {
+        if let Err(e) = self.connection.close(code).await {
+            debug!(error = %e, "Failed to close gateway connection");
+        }
+    }
+
     #[must_use]
     pub const fn session(&self) -> &SessionInfo {
         &self.session
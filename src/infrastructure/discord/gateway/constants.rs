@@ -124,6 +124,12 @@ impl GatewayIntents {
         self
     }
 
+    #[must_use]
+    pub const fn without(mut self, intent: GatewayIntent) -> Self {
+        self.0 &= !intent.as_u32();
+        self
+    }
+
     #[must_use]
     pub const fn has(self, intent: GatewayIntent) -> bool {
         (self.0 & intent.as_u32()) != 0
@@ -138,6 +144,7 @@ impl GatewayIntents {
     pub const fn default_client() -> Self {
         Self::new()
             .with(GatewayIntent::Guilds)
+            .with(GatewayIntent::GuildVoiceStates)
             .with(GatewayIntent::GuildMessages)
             .with(GatewayIntent::GuildMessageTyping)
             .with(GatewayIntent::DirectMessages)
@@ -187,6 +194,7 @@ mod tests {
         let intents = GatewayIntents::default_client();
         assert!(intents.has(GatewayIntent::Guilds));
         assert!(intents.has(GatewayIntent::GuildMessages));
+        assert!(intents.has(GatewayIntent::GuildVoiceStates));
         assert!(!intents.has(GatewayIntent::GuildPresences));
 
         let with_presence = intents.with_presence();
@@ -4,7 +4,7 @@ use chrono::{DateTime, Utc};
 
 use crate::domain::entities::{
     Channel, ChannelId, GuildFolder, GuildId, Member, Message, MessageId, ReadState, Relationship,
-    RelationshipType, Role, UserId,
+    RelationshipType, Role, User, UserId, VoiceState,
 };
 
 /// Commands that can be sent to the gateway.
@@ -16,6 +16,8 @@ pub enum GatewayCommand {
         guild_id: String,
         channel_id: String,
     },
+    /// Set our own presence status (e.g. going idle after inactivity).
+    UpdatePresence { status: PresenceStatus },
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +82,10 @@ pub enum DispatchEvent {
         read_states: Vec<ReadState>,
         guild_folders: Vec<GuildFolder>,
         relationships: Vec<Relationship>,
+        /// Status the user picked in their settings, if known.
+        status: Option<PresenceStatus>,
+        /// Users already connected to voice channels.
+        voice_states: Vec<VoiceState>,
     },
 
     MessageCreate {
@@ -159,6 +165,7 @@ pub enum DispatchEvent {
         threads: Vec<Channel>,
         roles: Vec<Role>,
         members: Vec<Member>,
+        voice_states: Vec<VoiceState>,
     },
     GuildUpdate {
         guild_id: GuildId,
@@ -178,12 +185,17 @@ pub enum DispatchEvent {
 
     UserSettingsUpdate {
         guild_folders: Vec<GuildFolder>,
+        status: Option<PresenceStatus>,
     },
 
     VoiceStateUpdate {
         guild_id: Option<GuildId>,
+        /// `None` when the user left voice.
         channel_id: Option<ChannelId>,
         user_id: String,
+        /// The connected user, when the event carried the member.
+        user: Option<User>,
+        nick: Option<String>,
         session_id: String,
         deaf: bool,
         mute: bool,
@@ -290,6 +302,8 @@ impl DispatchEvent {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UnavailableGuild {
     pub id: GuildId,
+    /// Sent for guilds whose channels are streamed in later.
+    pub name: Option<String>,
     pub unavailable: bool,
 }
 
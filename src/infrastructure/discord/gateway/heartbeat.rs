@@ -99,3 +99,33 @@ impl Drop for HeartbeatManager {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    #[tokio::test]
+    async fn test_missed_ack_signals_reconnect() {
+        let mut heartbeat = HeartbeatManager::new(20);
+        let ack_received = Arc::new(AtomicBool::new(true));
+        heartbeat.set_ack_received(ack_received.clone());
+        let (payload_tx, mut payload_rx) = mpsc::channel(4);
+        let (error_tx, mut error_rx) = mpsc::channel(1);
+        let handle = heartbeat.start(payload_tx, error_tx);
+
+        let first = tokio::time::timeout(TIMEOUT, payload_rx.recv()).await;
+        assert!(first.is_ok_and(|payload| payload.is_some()));
+        assert!(!ack_received.load(Ordering::SeqCst));
+
+        ack_received.store(true, Ordering::SeqCst);
+        let second = tokio::time::timeout(TIMEOUT, payload_rx.recv()).await;
+        assert!(second.is_ok_and(|payload| payload.is_some()));
+
+        let signal = tokio::time::timeout(TIMEOUT, error_rx.recv()).await;
+        assert_eq!(signal.ok().flatten(), Some(()));
+        assert!(tokio::time::timeout(TIMEOUT, handle).await.is_ok());
+        assert!(payload_rx.try_recv().is_err());
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::constants::LARGE_THRESHOLD;
+use super::events::PresenceStatus;
 use crate::infrastructure::discord::identity::SuperProperties;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +87,26 @@ impl GatewayPayload {
         }
     }
 
+    /// Creates a `PresenceUpdate` (Opcode 3) payload setting our own status.
+    #[must_use]
+    pub fn presence_update(status: PresenceStatus, since_ms: Option<u64>) -> Self {
+        use serde_json::json;
+
+        let data = json!({
+            "status": status.as_str(),
+            "since": since_ms.unwrap_or(0),
+            "activities": [],
+            "afk": status == PresenceStatus::Idle,
+        });
+
+        Self {
+            op: 3,
+            d: data,
+            s: None,
+            t: None,
+        }
+    }
+
     /// Creates a `LazyRequest` (Opcode 14) payload to subscribe to a guild channel.
     /// This is required for user accounts to receive `TYPING_START` events.
     #[must_use]
@@ -218,6 +239,8 @@ pub struct ReadyPayload {
 pub struct UserSettingsPayload {
     #[serde(default)]
     pub guild_folders: Vec<GuildFolderPayload>,
+    #[serde(default)]
+    pub status: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -248,7 +271,6 @@ pub struct ReadyUser {
 pub struct ReadyGuild {
     pub id: String,
     #[serde(default)]
-    #[allow(dead_code)]
     pub name: Option<String>,
     #[serde(default)]
     pub unavailable: bool,
@@ -260,6 +282,8 @@ pub struct ReadyGuild {
     pub roles: Vec<RolePayload>,
     #[serde(default)]
     pub members: Vec<GuildMemberPayload>,
+    #[serde(default)]
+    pub voice_states: Vec<ReadyVoiceStatePayload>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -278,10 +302,19 @@ pub struct MessagePayload {
     pub message_reference: Option<MessageReferencePayload>,
     pub referenced_message: Option<Box<Self>>,
     #[serde(default)]
+    pub message_snapshots: Vec<MessageSnapshotPayload>,
+    #[serde(default)]
     pub pinned: bool,
     #[serde(default)]
     pub mentions: Vec<MentionUserPayload>,
+    #[serde(default)]
+    pub mention_everyone: bool,
     pub member: Option<MemberPayload>,
+    #[serde(default)]
+    pub flags: u64,
+    /// Sent back as a string or an integer, whichever the client used.
+    #[serde(default)]
+    pub nonce: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -328,6 +361,24 @@ pub struct AttachmentPayload {
 pub struct MessageReferencePayload {
     pub message_id: Option<String>,
     pub channel_id: Option<String>,
+    pub guild_id: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageSnapshotPayload {
+    pub message: SnapshotMessagePayload,
+}
+
+/// Partial message captured in a forward.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotMessagePayload {
+    #[serde(default)]
+    pub content: String,
+    pub author: Option<AuthorPayload>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentPayload>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -461,6 +512,8 @@ pub struct GuildCreatePayload {
     pub roles: Vec<RolePayload>,
     #[serde(default)]
     pub members: Vec<GuildMemberPayload>,
+    #[serde(default)]
+    pub voice_states: Vec<ReadyVoiceStatePayload>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -529,7 +582,7 @@ pub struct VoiceStateUpdatePayload {
     pub guild_id: Option<String>,
     pub channel_id: Option<String>,
     pub user_id: String,
-    pub member: Option<MemberPayload>,
+    pub member: Option<VoiceMemberPayload>,
     pub session_id: String,
     pub deaf: bool,
     pub mute: bool,
@@ -540,6 +593,29 @@ pub struct VoiceStateUpdatePayload {
     pub request_to_speak_timestamp: Option<String>,
 }
 
+/// Member attached to a voice state, used for the participant's name.
+#[derive(Debug, Deserialize)]
+pub struct VoiceMemberPayload {
+    pub user: Option<UserPayload>,
+    pub nick: Option<String>,
+}
+
+/// Voice state listed per guild in READY and `GUILD_CREATE`.
+#[derive(Debug, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReadyVoiceStatePayload {
+    pub user_id: String,
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub deaf: bool,
+    #[serde(default)]
+    pub mute: bool,
+    #[serde(default)]
+    pub self_deaf: bool,
+    #[serde(default)]
+    pub self_mute: bool,
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct VoiceServerUpdatePayload {
@@ -687,6 +763,22 @@ mod tests {
         assert_eq!(obj.get("seq").unwrap(), 100);
     }
 
+    #[test]
+    fn test_presence_update_payload() {
+        let payload = GatewayPayload::presence_update(PresenceStatus::Idle, Some(1000));
+        assert_eq!(payload.op, 3);
+
+        let obj = payload.d.as_object().unwrap();
+        assert_eq!(obj.get("status").unwrap(), "idle");
+        assert_eq!(obj.get("since").unwrap(), 1000);
+        assert_eq!(obj.get("afk").unwrap(), true);
+
+        let payload = GatewayPayload::presence_update(PresenceStatus::Online, None);
+        let obj = payload.d.as_object().unwrap();
+        assert_eq!(obj.get("status").unwrap(), "online");
+        assert_eq!(obj.get("afk").unwrap(), false);
+    }
+
     #[test]
     fn test_guild_folder_payload_with_negative_string_id() {
         let json = r#"{
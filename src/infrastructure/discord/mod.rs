@@ -4,6 +4,8 @@ mod client;
 mod dto;
 pub mod gateway;
 pub mod identity;
+mod rate_limit;
+pub mod remote_auth;
 pub mod scraper;
 
 pub use client::DiscordClient;
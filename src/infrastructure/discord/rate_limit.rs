@@ -0,0 +1,181 @@
+//! Per-route REST rate limiting.
+//!
+//! Discord reports each route's remaining budget in `X-RateLimit-*` headers
+//! and answers bursts with `429 Too Many Requests`. The limiter remembers
+//! when each route (and the global bucket) frees up, so the next request on
+//! that route waits instead of failing.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+use reqwest::Method;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::domain::ports::RateLimitListener;
+
+const GLOBAL_ROUTE: &str = "global";
+
+/// Waits shorter than this pass silently instead of notifying the listener.
+const NOTIFY_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Body of a `429 Too Many Requests` response.
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResponse {
+    /// Seconds to wait before retrying.
+    pub retry_after: f64,
+    /// Whether the limit applies to every route.
+    #[serde(default)]
+    pub global: bool,
+}
+
+/// Tracks when each route may be called again.
+#[derive(Default)]
+pub struct RateLimiter {
+    blocked_until: Mutex<HashMap<String, Instant>>,
+    listener: RwLock<Option<RateLimitListener>>,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback told about waits long enough to notice.
+    pub fn set_listener(&self, listener: RateLimitListener) {
+        *self
+            .listener
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(listener);
+    }
+
+    /// Sleeps until neither `route` nor the global bucket is blocked.
+    pub async fn acquire(&self, route: &str) {
+        let Some(wait) = self.wait_for(route, Instant::now()) else {
+            return;
+        };
+
+        debug!(route, wait_ms = wait.as_millis(), "Waiting for rate limit");
+        if wait >= NOTIFY_THRESHOLD
+            && let Some(listener) = self
+                .listener
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .as_ref()
+        {
+            listener(wait);
+        }
+        tokio::time::sleep(wait).await;
+    }
+
+    /// Records the budget reported by a response's headers.
+    pub fn update(&self, route: &str, headers: &HeaderMap) {
+        let remaining = header_value::<u64>(headers, "x-ratelimit-remaining");
+        let reset_after = header_value::<f64>(headers, "x-ratelimit-reset-after");
+        if remaining == Some(0)
+            && let Some(reset_after) = reset_after
+        {
+            self.block(route, Duration::from_secs_f64(reset_after.max(0.0)), false);
+        }
+    }
+
+    /// Holds back `route`, or every route when `global`, for `wait`.
+    pub fn block(&self, route: &str, wait: Duration, global: bool) {
+        let key = if global { GLOBAL_ROUTE } else { route };
+        let until = Instant::now() + wait;
+        let mut blocked = self
+            .blocked_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let entry = blocked.entry(key.to_string()).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+
+    fn wait_for(&self, route: &str, now: Instant) -> Option<Duration> {
+        let mut blocked = self
+            .blocked_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        blocked.retain(|_, until| *until > now);
+        [route, GLOBAL_ROUTE]
+            .iter()
+            .filter_map(|key| blocked.get(*key))
+            .max()
+            .map(|until| *until - now)
+    }
+}
+
+/// Reads the wait from a 429's `Retry-After` header, in seconds.
+#[must_use]
+pub fn retry_after_header(headers: &HeaderMap) -> Option<Duration> {
+    header_value::<f64>(headers, "retry-after")
+        .map(|seconds| Duration::from_secs_f64(seconds.max(0.0)))
+}
+
+/// Groups a request with others sharing its rate limit bucket.
+///
+/// Discord keys limits on the route plus its major parameter (channel,
+/// guild or webhook id), so other ids in the path are collapsed.
+#[must_use]
+pub fn route_key(method: &Method, path: &str) -> String {
+    let mut route = String::new();
+    let mut previous = "";
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let major = matches!(previous, "channels" | "guilds" | "webhooks");
+        let collapsed = if !major && segment.bytes().all(|b| b.is_ascii_digit()) {
+            ":id"
+        } else {
+            segment
+        };
+        route.push('/');
+        route.push_str(collapsed);
+        previous = segment;
+    }
+    format!("{method} {route}")
+}
+
+fn header_value<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_route_key_keeps_major_parameter() {
+        assert_eq!(
+            route_key(&Method::DELETE, "/api/v10/channels/10/messages/1"),
+            route_key(&Method::DELETE, "/api/v10/channels/10/messages/2")
+        );
+        assert_ne!(
+            route_key(&Method::DELETE, "/api/v10/channels/10/messages/1"),
+            route_key(&Method::DELETE, "/api/v10/channels/11/messages/1")
+        );
+    }
+
+    #[test]
+    fn test_exhausted_bucket_blocks_route() {
+        let limiter = RateLimiter::new();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-after", HeaderValue::from_static("2.5"));
+        limiter.update("DELETE /channels/10", &headers);
+
+        let now = Instant::now();
+        assert!(
+            limiter
+                .wait_for("DELETE /channels/10", now)
+                .is_some_and(|wait| wait > Duration::from_secs(2))
+        );
+        assert_eq!(limiter.wait_for("DELETE /channels/11", now), None);
+
+        limiter.block("GET /users/@me", Duration::from_secs(1), true);
+        assert!(limiter.wait_for("DELETE /channels/11", now).is_some());
+    }
+}
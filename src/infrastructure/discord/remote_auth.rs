@@ -0,0 +1,464 @@
+//! Discord remote auth: logging in by scanning a QR code with the mobile app.
+//!
+//! The handshake runs over its own websocket:
+//! 1. the server says `hello` and we send `init` with a fresh RSA public key,
+//! 2. it sends a nonce encrypted to that key and we prove we could read it,
+//! 3. it sends a fingerprint, shown as `https://discord.com/ra/<fingerprint>`,
+//! 4. once scanned it sends the user, and once approved a ticket that the
+//!    REST API trades for the token, both encrypted to our key.
+//!
+//! Codes expire after the server's timeout, so [`RemoteAuthClient::run`]
+//! starts a fresh handshake each time one expires. Dropped connections are
+//! retried a few times with a growing delay before the login fails.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::header;
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::time::{Interval, timeout};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tracing::{debug, info, warn};
+
+use super::identity::ClientIdentity;
+
+const REMOTE_AUTH_URL: &str = "wss://remote-auth-gateway.discord.gg/?v=2";
+const LOGIN_URL: &str = "https://discord.com/api/v9/users/@me/remote-auth/login";
+const QR_URL_PREFIX: &str = "https://discord.com/ra/";
+const KEY_BITS: usize = 2048;
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+/// Close code the gateway sends when a QR code times out.
+const TIMEOUT_CLOSE_CODE: u16 = 4003;
+/// Reconnects after a lost connection before the login is reported failed.
+const MAX_RECONNECTS: u32 = 3;
+/// Delay before the first reconnect, doubled for each one after.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Remote auth failure variants.
+#[derive(Debug, Error)]
+pub enum RemoteAuthError {
+    #[error("connection failed: {0}")]
+    Connection(String),
+
+    #[error("unexpected message: {0}")]
+    Protocol(String),
+
+    #[error("could not decrypt the server's reply")]
+    Decrypt,
+
+    #[error("login request failed: {0}")]
+    Http(String),
+}
+
+/// Progress of a QR code login.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAuthEvent {
+    /// Show this URL as a QR code. Sent again whenever the code is renewed.
+    Code(String),
+    /// The code was scanned; waiting for approval on the phone.
+    Scanned { username: String },
+    /// Approved; the account's token.
+    Token(String),
+    /// The login was declined on the phone.
+    Cancelled,
+    /// The handshake failed.
+    Failed(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ServerMessage {
+    Hello {
+        heartbeat_interval: u64,
+    },
+    NonceProof {
+        encrypted_nonce: String,
+    },
+    PendingRemoteInit {
+        fingerprint: String,
+    },
+    PendingTicket {
+        encrypted_user_payload: String,
+    },
+    PendingLogin {
+        ticket: String,
+    },
+    Cancel,
+    HeartbeatAck,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+    Init { encoded_public_key: String },
+    NonceProof { proof: String },
+    Heartbeat,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoginResponse {
+    encrypted_token: String,
+}
+
+/// How one handshake ended.
+enum SessionEnd {
+    Token(String),
+    Cancelled,
+    Expired,
+}
+
+/// The key pair the server encrypts its replies to.
+struct RemoteAuthKeys {
+    private_key: RsaPrivateKey,
+}
+
+impl RemoteAuthKeys {
+    fn generate(bits: usize) -> Result<Self, RemoteAuthError> {
+        let private_key = RsaPrivateKey::new(&mut rsa::rand_core::OsRng, bits)
+            .map_err(|e| RemoteAuthError::Protocol(format!("key generation failed: {e}")))?;
+        Ok(Self { private_key })
+    }
+
+    /// Base64 of the public key's DER `SubjectPublicKeyInfo`.
+    fn encoded_public_key(&self) -> Result<String, RemoteAuthError> {
+        let der = RsaPublicKey::from(&self.private_key)
+            .to_public_key_der()
+            .map_err(|e| RemoteAuthError::Protocol(format!("key encoding failed: {e}")))?;
+        Ok(STANDARD.encode(der.as_bytes()))
+    }
+
+    fn decrypt(&self, encoded: &str) -> Result<Vec<u8>, RemoteAuthError> {
+        let data = STANDARD
+            .decode(encoded)
+            .map_err(|_| RemoteAuthError::Decrypt)?;
+        self.private_key
+            .decrypt(Oaep::new::<Sha256>(), &data)
+            .map_err(|_| RemoteAuthError::Decrypt)
+    }
+}
+
+/// How a session ends when the server closes it: only the timeout close
+/// means the code expired.
+fn close_end(frame: Option<&CloseFrame>) -> Result<SessionEnd, RemoteAuthError> {
+    match frame {
+        Some(frame) if u16::from(frame.code) == TIMEOUT_CLOSE_CODE => Ok(SessionEnd::Expired),
+        Some(frame) => Err(RemoteAuthError::Connection(format!(
+            "closed by server ({}): {}",
+            u16::from(frame.code),
+            frame.reason
+        ))),
+        None => Err(RemoteAuthError::Connection("closed by server".to_string())),
+    }
+}
+
+/// Answer to the server's nonce challenge.
+fn nonce_proof(nonce: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(nonce))
+}
+
+/// Username from `id:discriminator:avatar:username`.
+fn payload_username(payload: &str) -> Option<&str> {
+    payload
+        .splitn(4, ':')
+        .nth(3)
+        .filter(|name| !name.is_empty())
+}
+
+fn encode(message: &ClientMessage) -> Result<WsMessage, RemoteAuthError> {
+    let json =
+        serde_json::to_string(message).map_err(|e| RemoteAuthError::Protocol(e.to_string()))?;
+    Ok(WsMessage::Text(json.into()))
+}
+
+async fn tick(heartbeat: Option<&mut Interval>) {
+    match heartbeat {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Runs QR code logins against Discord's remote auth gateway.
+pub struct RemoteAuthClient {
+    http: reqwest::Client,
+    identity: Arc<ClientIdentity>,
+}
+
+impl RemoteAuthClient {
+    #[must_use]
+    pub fn new(identity: Arc<ClientIdentity>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            identity,
+        }
+    }
+
+    /// Reports progress to `events` until a token is obtained, the login is
+    /// declined or it fails. Expired codes are replaced with new ones.
+    pub async fn run(&self, events: mpsc::UnboundedSender<RemoteAuthEvent>) {
+        let mut reconnects = 0;
+        loop {
+            let event = match self.session(&events).await {
+                Ok(SessionEnd::Expired) => {
+                    if events.is_closed() {
+                        return;
+                    }
+                    debug!("QR code expired, starting a new one");
+                    reconnects = 0;
+                    continue;
+                }
+                Err(RemoteAuthError::Connection(e)) if reconnects < MAX_RECONNECTS => {
+                    let delay = RECONNECT_DELAY * 2_u32.pow(reconnects);
+                    reconnects += 1;
+                    warn!(error = %e, attempt = reconnects, "QR code login connection lost, retrying");
+                    tokio::time::sleep(delay).await;
+                    if events.is_closed() {
+                        return;
+                    }
+                    continue;
+                }
+                Ok(SessionEnd::Token(token)) => {
+                    info!("QR code login approved");
+                    RemoteAuthEvent::Token(token)
+                }
+                Ok(SessionEnd::Cancelled) => RemoteAuthEvent::Cancelled,
+                Err(e) => {
+                    warn!(error = %e, "QR code login failed");
+                    RemoteAuthEvent::Failed(e.to_string())
+                }
+            };
+            let _ = events.send(event);
+            return;
+        }
+    }
+
+    async fn session(
+        &self,
+        events: &mpsc::UnboundedSender<RemoteAuthEvent>,
+    ) -> Result<SessionEnd, RemoteAuthError> {
+        let keys = tokio::task::spawn_blocking(|| RemoteAuthKeys::generate(KEY_BITS))
+            .await
+            .map_err(|e| RemoteAuthError::Protocol(e.to_string()))??;
+
+        let mut request = REMOTE_AUTH_URL
+            .into_client_request()
+            .map_err(|e| RemoteAuthError::Connection(e.to_string()))?;
+        request.headers_mut().insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://discord.com"),
+        );
+        let (stream, _) = timeout(CONNECTION_TIMEOUT, connect_async(request))
+            .await
+            .map_err(|_| RemoteAuthError::Connection("timed out".to_string()))?
+            .map_err(|e| RemoteAuthError::Connection(e.to_string()))?;
+        let (mut writer, mut reader) = stream.split();
+        let mut heartbeat: Option<Interval> = None;
+
+        loop {
+            let message = tokio::select! {
+                message = reader.next() => message,
+                () = tick(heartbeat.as_mut()) => {
+                    writer
+                        .send(encode(&ClientMessage::Heartbeat)?)
+                        .await
+                        .map_err(|e| RemoteAuthError::Connection(e.to_string()))?;
+                    continue;
+                }
+            };
+            let text = match message {
+                None => return Err(RemoteAuthError::Connection("connection closed".to_string())),
+                Some(Ok(WsMessage::Close(frame))) => return close_end(frame.as_ref()),
+                Some(Ok(WsMessage::Text(text))) => text,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(RemoteAuthError::Connection(e.to_string())),
+            };
+            let message: ServerMessage = serde_json::from_str(text.as_str())
+                .map_err(|e| RemoteAuthError::Protocol(e.to_string()))?;
+
+            let reply = match message {
+                ServerMessage::Hello { heartbeat_interval } => {
+                    let period = Duration::from_millis(heartbeat_interval.max(1000));
+                    heartbeat = Some(tokio::time::interval_at(
+                        tokio::time::Instant::now() + period,
+                        period,
+                    ));
+                    Some(ClientMessage::Init {
+                        encoded_public_key: keys.encoded_public_key()?,
+                    })
+                }
+                ServerMessage::NonceProof { encrypted_nonce } => Some(ClientMessage::NonceProof {
+                    proof: nonce_proof(&keys.decrypt(&encrypted_nonce)?),
+                }),
+                ServerMessage::PendingRemoteInit { fingerprint } => {
+                    let _ = events.send(RemoteAuthEvent::Code(format!(
+                        "{QR_URL_PREFIX}{fingerprint}"
+                    )));
+                    None
+                }
+                ServerMessage::PendingTicket {
+                    encrypted_user_payload,
+                } => {
+                    let payload = keys.decrypt(&encrypted_user_payload)?;
+                    let payload = String::from_utf8_lossy(&payload);
+                    let _ = events.send(RemoteAuthEvent::Scanned {
+                        username: payload_username(&payload).unwrap_or("unknown").to_string(),
+                    });
+                    None
+                }
+                ServerMessage::PendingLogin { ticket } => {
+                    let token = self.redeem_ticket(&keys, &ticket).await?;
+                    let _ = writer.close().await;
+                    return Ok(SessionEnd::Token(token));
+                }
+                ServerMessage::Cancel => return Ok(SessionEnd::Cancelled),
+                ServerMessage::HeartbeatAck | ServerMessage::Unknown => None,
+            };
+            if let Some(reply) = reply {
+                writer
+                    .send(encode(&reply)?)
+                    .await
+                    .map_err(|e| RemoteAuthError::Connection(e.to_string()))?;
+            }
+        }
+    }
+
+    /// Trades an approved login's ticket for the account token.
+    async fn redeem_ticket(
+        &self,
+        keys: &RemoteAuthKeys,
+        ticket: &str,
+    ) -> Result<String, RemoteAuthError> {
+        let response = self
+            .http
+            .post(LOGIN_URL)
+            .header(
+                header::USER_AGENT,
+                &self.identity.get_props().browser_user_agent,
+            )
+            .header("X-Super-Properties", self.identity.get_header_value())
+            .json(&serde_json::json!({ "ticket": ticket }))
+            .send()
+            .await
+            .map_err(|e| RemoteAuthError::Http(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RemoteAuthError::Http(format!("HTTP {status}")));
+        }
+        let body: LoginResponse = response
+            .json()
+            .await
+            .map_err(|e| RemoteAuthError::Http(e.to_string()))?;
+        String::from_utf8(keys.decrypt(&body.encrypted_token)?)
+            .map_err(|_| RemoteAuthError::Decrypt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs8::DecodePublicKey;
+
+    #[test]
+    fn test_keys_decrypt_what_the_server_encrypts() {
+        let keys = RemoteAuthKeys::generate(1024).unwrap();
+        let der = STANDARD.decode(keys.encoded_public_key().unwrap()).unwrap();
+        let public_key = RsaPublicKey::from_public_key_der(&der).unwrap();
+
+        let encrypted = public_key
+            .encrypt(
+                &mut rsa::rand_core::OsRng,
+                Oaep::new::<Sha256>(),
+                b"nonce bytes",
+            )
+            .unwrap();
+        assert_eq!(
+            keys.decrypt(&STANDARD.encode(encrypted)).unwrap(),
+            b"nonce bytes"
+        );
+        assert!(matches!(
+            keys.decrypt("bm90IGVuY3J5cHRlZA=="),
+            Err(RemoteAuthError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn test_nonce_proof_is_unpadded_url_safe_sha256() {
+        assert_eq!(
+            nonce_proof(b""),
+            "47DEQpj8HBSa-_TImW-5JCeuQeRkm5NMpJWZG3hSuFU"
+        );
+    }
+
+    #[test]
+    fn test_only_timeout_close_expires_the_code() {
+        use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+        let close = |code: u16| CloseFrame {
+            code: CloseCode::from(code),
+            reason: "reason".into(),
+        };
+        assert!(matches!(
+            close_end(Some(&close(TIMEOUT_CLOSE_CODE))),
+            Ok(SessionEnd::Expired)
+        ));
+        assert!(matches!(
+            close_end(Some(&close(4002))),
+            Err(RemoteAuthError::Connection(reason)) if reason.contains("4002")
+        ));
+        assert!(matches!(
+            close_end(None),
+            Err(RemoteAuthError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_server_messages() {
+        let hello: ServerMessage = serde_json::from_str(
+            r#"{"op":"hello","heartbeat_interval":41250,"timeout_ms":120000}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            hello,
+            ServerMessage::Hello {
+                heartbeat_interval: 41250
+            }
+        ));
+        let init: ServerMessage =
+            serde_json::from_str(r#"{"op":"pending_remote_init","fingerprint":"abc"}"#).unwrap();
+        assert!(matches!(
+            init,
+            ServerMessage::PendingRemoteInit { fingerprint } if fingerprint == "abc"
+        ));
+        let unknown: ServerMessage = serde_json::from_str(r#"{"op":"something_new"}"#).unwrap();
+        assert!(matches!(unknown, ServerMessage::Unknown));
+
+        assert_eq!(
+            serde_json::to_string(&ClientMessage::NonceProof {
+                proof: "p".to_string()
+            })
+            .unwrap(),
+            r#"{"op":"nonce_proof","proof":"p"}"#
+        );
+        assert_eq!(
+            payload_username("123:0:a_hash:ferris:with:colons"),
+            Some("ferris:with:colons")
+        );
+        assert_eq!(payload_username("123:0"), None);
+    }
+}
@@ -6,18 +6,72 @@ pub const DEFAULT_WIDTH: u32 = 400;
 /// Default target height for optimized images.
 pub const DEFAULT_HEIGHT: u32 = 300;
 
+/// Largest width or height the Discord CDN will resize to.
+pub const MAX_DIMENSION: u32 = 4096;
+
+/// How images are requested from the Discord CDN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CdnImageOptions {
+    /// Requested width; the height keeps the default 4:3 box. Clamped to
+    /// [`MAX_DIMENSION`].
+    pub max_dimension: u32,
+    /// Ask for low quality WebP instead of PNG.
+    pub prefer_webp: bool,
+    /// Leave GIFs in their own format so they keep animating.
+    pub animate: bool,
+}
+
+impl Default for CdnImageOptions {
+    fn default() -> Self {
+        Self {
+            max_dimension: DEFAULT_WIDTH,
+            prefer_webp: true,
+            animate: false,
+        }
+    }
+}
+
+impl CdnImageOptions {
+    /// Requested `(width, height)`.
+    #[must_use]
+    pub const fn dimensions(self) -> (u32, u32) {
+        let width = if self.max_dimension > MAX_DIMENSION {
+            MAX_DIMENSION
+        } else {
+            self.max_dimension
+        };
+        (width, width * DEFAULT_HEIGHT / DEFAULT_WIDTH)
+    }
+
+    /// Format to convert `url` to, or `None` to keep the original.
+    fn format_for(self, url: &str) -> Option<&'static str> {
+        if self.animate && is_gif_url(url) {
+            None
+        } else if self.prefer_webp {
+            Some("webp")
+        } else {
+            Some("png")
+        }
+    }
+}
+
+/// Checks if a URL points at a GIF, ignoring its query.
+fn is_gif_url(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url);
+    path.to_ascii_lowercase().ends_with(".gif")
+}
+
 /// Optimizes a Discord CDN URL by adding format and size parameters.
 /// This significantly reduces bandwidth usage and RAM consumption.
 ///
 /// # Arguments
 /// * `url` - The original image URL
-/// * `width` - Target width (default: 800)
-/// * `height` - Target height (default: 600)
+/// * `options` - Target size and format
 ///
 /// # Returns
 /// The optimized URL with query parameters, or the original URL if not a Discord CDN URL.
 #[must_use]
-pub fn optimize_cdn_url(url: &str, width: u32, height: u32) -> String {
+pub fn optimize_cdn_url(url: &str, options: &CdnImageOptions) -> String {
     if !url.contains("cdn.discordapp.com") && !url.contains("media.discordapp.net") {
         return url.to_string();
     }
@@ -28,12 +82,14 @@ pub fn optimize_cdn_url(url: &str, width: u32, height: u32) -> String {
         (url, None)
     };
 
-    let mut params = vec![
-        format!("format=webp"),
-        format!("width={width}"),
-        format!("height={height}"),
-        format!("quality=low"),
-    ];
+    let (width, height) = options.dimensions();
+    let format = options.format_for(url);
+    let mut params: Vec<String> = format.map(|f| format!("format={f}")).into_iter().collect();
+    params.push(format!("width={width}"));
+    params.push(format!("height={height}"));
+    if format == Some("webp") {
+        params.push("quality=low".to_string());
+    }
 
     if let Some(existing) = existing_params {
         for param in existing.split('&') {
@@ -50,7 +106,7 @@ pub fn optimize_cdn_url(url: &str, width: u32, height: u32) -> String {
 /// Optimizes a URL with default dimensions.
 #[must_use]
 pub fn optimize_cdn_url_default(url: &str) -> String {
-    optimize_cdn_url(url, DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    optimize_cdn_url(url, &CdnImageOptions::default())
 }
 
 /// Extracts the attachment ID from a Discord CDN URL.
@@ -76,11 +132,52 @@ pub fn is_discord_cdn_url(url: &str) -> bool {
     url.contains("cdn.discordapp.com") || url.contains("media.discordapp.net")
 }
 
+/// Badge drawn over an animated avatar shown as a still frame.
+pub const ANIMATED_AVATAR_INDICATOR: &str = "GIF";
+
+/// An avatar to load, and whether it is a still of an animated one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvatarImage {
+    pub url: String,
+    /// `url` is the first frame of an animated avatar; show
+    /// [`ANIMATED_AVATAR_INDICATOR`] beside it.
+    pub animated: bool,
+}
+
+/// Checks if an asset hash names an animated image (`a_` prefix).
+#[must_use]
+pub fn is_animated_hash(hash: &str) -> bool {
+    hash.starts_with("a_")
+}
+
+/// Picks the avatar image for a user.
+///
+/// With `animate` off, animated avatars load as a static PNG of their first
+/// frame and are flagged so the caller can draw the indicator.
+#[must_use]
+pub fn avatar_image(
+    user_id: &str,
+    avatar_hash: Option<&str>,
+    discriminator: &str,
+    animate: bool,
+) -> AvatarImage {
+    match avatar_hash {
+        Some(hash) if is_animated_hash(hash) && !animate => AvatarImage {
+            url: format!("https://cdn.discordapp.com/avatars/{user_id}/{hash}.png"),
+            animated: true,
+        },
+        _ => AvatarImage {
+            url: avatar_url(user_id, avatar_hash, discriminator),
+            animated: false,
+        },
+    }
+}
+
 /// Generates an avatar URL for a user.
 #[must_use]
 pub fn avatar_url(user_id: &str, avatar_hash: Option<&str>, discriminator: &str) -> String {
     if let Some(hash) = avatar_hash {
-        let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+        let ext = if is_animated_hash(hash) { "gif" } else { "png" };
         format!("https://cdn.discordapp.com/avatars/{user_id}/{hash}.{ext}")
     } else {
         default_avatar_url(user_id, discriminator)
@@ -106,7 +203,7 @@ pub fn default_avatar_url(user_id: &str, discriminator: &str) -> String {
 #[must_use]
 pub fn guild_icon_url(guild_id: &str, icon_hash: Option<&str>) -> Option<String> {
     icon_hash.map(|hash| {
-        let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+        let ext = if is_animated_hash(hash) { "gif" } else { "png" };
         format!("https://cdn.discordapp.com/icons/{guild_id}/{hash}.{ext}")
     })
 }
@@ -115,7 +212,7 @@ pub fn guild_icon_url(guild_id: &str, icon_hash: Option<&str>) -> Option<String>
 #[must_use]
 pub fn guild_banner_url(guild_id: &str, banner_hash: Option<&str>) -> Option<String> {
     banner_hash.map(|hash| {
-        let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+        let ext = if is_animated_hash(hash) { "gif" } else { "png" };
         format!("https://cdn.discordapp.com/banners/{guild_id}/{hash}.{ext}")
     })
 }
@@ -188,10 +285,53 @@ mod tests {
     #[test]
     fn test_custom_dimensions() {
         let url = "https://cdn.discordapp.com/attachments/123/456/image.png";
-        let optimized = optimize_cdn_url(url, 400, 300);
+        let options = CdnImageOptions {
+            max_dimension: 800,
+            ..CdnImageOptions::default()
+        };
+        let optimized = optimize_cdn_url(url, &options);
+
+        assert!(optimized.contains("width=800"));
+        assert!(optimized.contains("height=600"));
+    }
 
-        assert!(optimized.contains("width=400"));
-        assert!(optimized.contains("height=300"));
+    #[test]
+    fn test_dimensions_clamped_to_cdn_limit() {
+        let options = CdnImageOptions {
+            max_dimension: u32::MAX,
+            ..CdnImageOptions::default()
+        };
+        assert_eq!(options.dimensions(), (4096, 3072));
+    }
+
+    #[test]
+    fn test_optimize_respects_format_preferences() {
+        let url = "https://media.discordapp.net/attachments/1/2/cat.gif?ex=abc&format=webp&size=64";
+        let png = CdnImageOptions {
+            max_dimension: 200,
+            prefer_webp: false,
+            animate: false,
+        };
+        assert_eq!(
+            optimize_cdn_url(url, &png),
+            "https://media.discordapp.net/attachments/1/2/cat.gif?format=png&width=200&height=150&ex=abc"
+        );
+
+        let animated = CdnImageOptions {
+            animate: true,
+            ..CdnImageOptions::default()
+        };
+        assert_eq!(
+            optimize_cdn_url(url, &animated),
+            "https://media.discordapp.net/attachments/1/2/cat.gif?width=400&height=300&ex=abc"
+        );
+        assert!(
+            optimize_cdn_url(
+                "https://cdn.discordapp.com/attachments/1/2/a.png",
+                &animated
+            )
+            .starts_with("https://cdn.discordapp.com/attachments/1/2/a.png?format=webp&")
+        );
     }
 
     #[test]
@@ -211,6 +351,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_animated_avatar_falls_back_to_static_frame() {
+        assert!(is_animated_hash("a_abcdef"));
+        assert!(!is_animated_hash("abcdef"));
+
+        assert_eq!(
+            avatar_image("123456", Some("a_abcdef"), "0", false),
+            AvatarImage {
+                url: "https://cdn.discordapp.com/avatars/123456/a_abcdef.png".to_string(),
+                animated: true,
+            }
+        );
+        assert_eq!(
+            avatar_image("123456", Some("a_abcdef"), "0", true),
+            AvatarImage {
+                url: "https://cdn.discordapp.com/avatars/123456/a_abcdef.gif".to_string(),
+                animated: false,
+            }
+        );
+        assert!(!avatar_image("123456", Some("abcdef"), "0", false).animated);
+        assert!(!avatar_image("123456", None, "0", false).animated);
+    }
+
     #[test]
     fn test_default_avatar_url() {
         // Legacy: 1234 % 5 = 4
@@ -1,8 +1,9 @@
 //! Disk-based image cache for persistence across sessions.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::SystemTime;
 
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
@@ -20,6 +21,8 @@ pub struct DiskImageCache {
     max_size: u64,
     current_size: AtomicU64,
     item_count: AtomicUsize,
+    /// Set while an eviction pass runs, so passes never overlap.
+    cleaning: AtomicBool,
 }
 
 impl DiskImageCache {
@@ -40,7 +43,12 @@ impl DiskImageCache {
 
         while let Ok(Some(entry)) = entries.next_entry().await {
             let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "img")
+            if is_temp_file(&path) {
+                debug!(path = %path.display(), "Removing unfinished cache write");
+                if let Err(e) = fs::remove_file(&path).await {
+                    warn!(path = %path.display(), error = %e, "Failed to remove temp cache file");
+                }
+            } else if path.extension().is_some_and(|ext| ext == "img")
                 && let Ok(meta) = entry.metadata().await
             {
                 total_size += meta.len();
@@ -53,6 +61,7 @@ impl DiskImageCache {
             max_size,
             current_size: AtomicU64::new(total_size),
             item_count: AtomicUsize::new(count),
+            cleaning: AtomicBool::new(false),
         };
 
         cache.cleanup_if_needed().await;
@@ -65,8 +74,15 @@ impl DiskImageCache {
     /// # Errors
     /// Returns error if cache directory cannot be created.
     pub async fn default_location() -> CacheResult<Self> {
-        let cache_dir = dirs_cache_path();
-        Self::new(cache_dir, DEFAULT_MAX_CACHE_SIZE).await
+        Self::default_location_with_max_size(DEFAULT_MAX_CACHE_SIZE).await
+    }
+
+    /// Creates a cache in the default location holding at most `max_size` bytes.
+    ///
+    /// # Errors
+    /// Returns error if cache directory cannot be created.
+    pub async fn default_location_with_max_size(max_size: u64) -> CacheResult<Self> {
+        Self::new(dirs_cache_path(), max_size).await
     }
 
     /// Returns the path for a cached image.
@@ -79,7 +95,7 @@ impl DiskImageCache {
         let path = self.cache_path(id);
         if let Ok(bytes) = fs::read(&path).await {
             trace!(id = %id, path = %path.display(), "Disk cache hit");
-            let _ = fs::File::open(&path).await;
+            touch(&path).await;
             Some(bytes)
         } else {
             trace!(id = %id, "Disk cache miss");
@@ -111,24 +127,27 @@ impl DiskImageCache {
 
     /// Stores raw bytes in the disk cache.
     ///
+    /// The bytes are written to a temporary file and renamed into place, so
+    /// concurrent readers never see a partial image. The temporary file is
+    /// removed if the write fails; any left behind by a crash are removed on
+    /// the next startup.
+    ///
     /// # Errors
     /// Returns error if file cannot be created or written.
     pub async fn put_bytes(&self, id: &ImageId, bytes: &[u8]) -> CacheResult<()> {
         let path = self.cache_path(id);
+        let temp_path = path.with_extension("img.tmp");
 
         let old_size = fs::metadata(&path).await.map(|m| m.len()).ok();
 
-        let mut file = fs::File::create(&path)
-            .await
-            .map_err(|e| CacheError::IoError(format!("Failed to create cache file: {e}")))?;
-
-        file.write_all(bytes)
-            .await
-            .map_err(|e| CacheError::IoError(format!("Failed to write cache file: {e}")))?;
-
-        file.flush()
-            .await
-            .map_err(|e| CacheError::IoError(format!("Failed to flush cache file: {e}")))?;
+        if let Err(e) = write_then_rename(&temp_path, &path, bytes).await {
+            if let Err(e) = fs::remove_file(&temp_path).await
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                warn!(path = %temp_path.display(), error = %e, "Failed to remove temp cache file");
+            }
+            return Err(e);
+        }
         let new_size = bytes.len() as u64;
         if let Some(old) = old_size {
             if new_size > old {
@@ -209,13 +228,37 @@ impl DiskImageCache {
         self.len().await == 0
     }
 
-    /// Cleans up old cache entries if over size limit.
-    async fn cleanup_if_needed(&self) {
+    /// Returns disk cache statistics.
+    #[must_use]
+    pub fn stats(&self) -> DiskCacheStats {
+        DiskCacheStats {
+            size_bytes: self.current_size.load(Ordering::Relaxed),
+            max_size_bytes: self.max_size,
+            entries: self.item_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Evicts the least recently used entries if over the size limit.
+    ///
+    /// Runs on startup, after every write and periodically from the loader.
+    /// Overlapping calls return at once while a pass is in progress.
+    pub async fn cleanup_if_needed(&self) {
         let current_size = self.current_size().await;
         if current_size <= self.max_size {
             return;
         }
+        if self
+            .cleaning
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        let _guard = CleaningGuard(&self.cleaning);
+        self.evict_least_recently_used(current_size).await;
+    }
 
+    async fn evict_least_recently_used(&self, current_size: u64) {
         debug!(
             current_size = current_size,
             max_size = self.max_size,
@@ -226,7 +269,7 @@ impl DiskImageCache {
             return;
         };
 
-        let mut files: Vec<(PathBuf, std::time::SystemTime, u64)> = Vec::new();
+        let mut files: Vec<(PathBuf, SystemTime, u64)> = Vec::new();
 
         while let Ok(Some(entry)) = entries.next_entry().await {
             let path = entry.path();
@@ -235,8 +278,7 @@ impl DiskImageCache {
             }
 
             if let Ok(meta) = entry.metadata().await {
-                let accessed = meta.accessed().unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-                files.push((path, accessed, meta.len()));
+                files.push((path, last_used(&meta), meta.len()));
             }
         }
 
@@ -276,6 +318,89 @@ impl DiskImageCache {
     }
 }
 
+/// Disk cache usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskCacheStats {
+    /// Bytes stored.
+    pub size_bytes: u64,
+    /// Bytes allowed before eviction.
+    pub max_size_bytes: u64,
+    /// Number of cached files.
+    pub entries: usize,
+}
+
+impl std::fmt::Display for DiskCacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const MB: u64 = 1024 * 1024;
+        write!(
+            f,
+            "Disk cache: {} images, {} / {} MB",
+            self.entries,
+            self.size_bytes.div_ceil(MB),
+            self.max_size_bytes / MB
+        )
+    }
+}
+
+/// Clears the `cleaning` flag when an eviction pass ends, even if the pass
+/// is cancelled or panics.
+struct CleaningGuard<'a>(&'a AtomicBool);
+
+impl Drop for CleaningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Writes `bytes` to `temp_path` and moves the file to `path`.
+async fn write_then_rename(temp_path: &Path, path: &Path, bytes: &[u8]) -> CacheResult<()> {
+    let mut file = fs::File::create(temp_path)
+        .await
+        .map_err(|e| CacheError::IoError(format!("Failed to create cache file: {e}")))?;
+
+    file.write_all(bytes)
+        .await
+        .map_err(|e| CacheError::IoError(format!("Failed to write cache file: {e}")))?;
+
+    file.flush()
+        .await
+        .map_err(|e| CacheError::IoError(format!("Failed to flush cache file: {e}")))?;
+    drop(file);
+    fs::rename(temp_path, path)
+        .await
+        .map_err(|e| CacheError::IoError(format!("Failed to move cache file: {e}")))
+}
+
+/// Whether `path` is a cache write that never finished.
+fn is_temp_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".img.tmp"))
+}
+
+/// When a cache file was last read or written.
+fn last_used(meta: &std::fs::Metadata) -> SystemTime {
+    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let accessed = meta.accessed().unwrap_or(SystemTime::UNIX_EPOCH);
+    modified.max(accessed)
+}
+
+/// Marks a cache file as just used. Access times are often not kept
+/// (`noatime`, `relatime`), so the modification time is bumped instead.
+async fn touch(path: &Path) {
+    let path = path.to_path_buf();
+    let result = tokio::task::spawn_blocking(move || {
+        std::fs::File::options()
+            .append(true)
+            .open(&path)?
+            .set_modified(SystemTime::now())
+    })
+    .await;
+    if let Ok(Err(e)) = result {
+        trace!(error = %e, "Failed to touch cache file");
+    }
+}
+
 /// Returns the default cache directory path.
 fn dirs_cache_path() -> PathBuf {
     directories::ProjectDirs::from("com", "linuxmobile", "oxicord").map_or_else(
@@ -411,4 +536,71 @@ mod tests {
         assert_eq!(cache.len().await, 1);
         assert_eq!(cache.current_size().await, 6);
     }
+
+    #[tokio::test]
+    async fn test_writing_past_cap_evicts_least_recently_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = DiskImageCache::new(temp_dir.path().to_path_buf(), 14)
+            .await
+            .unwrap();
+        let hour_ago = SystemTime::now() - std::time::Duration::from_hours(1);
+        let set_last_used = |name: &str, time: SystemTime| {
+            let times = std::fs::FileTimes::new()
+                .set_accessed(time)
+                .set_modified(time);
+            std::fs::File::options()
+                .append(true)
+                .open(cache.cache_path(&ImageId::new(name)))
+                .unwrap()
+                .set_times(times)
+                .unwrap();
+        };
+
+        for name in ["a", "b"] {
+            cache.put_bytes(&ImageId::new(name), b"1234").await.unwrap();
+        }
+        set_last_used("a", hour_ago);
+        set_last_used("b", hour_ago + std::time::Duration::from_mins(1));
+        assert!(cache.get_bytes(&ImageId::new("a")).await.is_some());
+        for name in ["c", "d"] {
+            cache.put_bytes(&ImageId::new(name), b"1234").await.unwrap();
+        }
+
+        assert!(!cache.contains(&ImageId::new("b")).await);
+        for name in ["a", "c", "d"] {
+            assert!(cache.contains(&ImageId::new(name)).await, "{name} evicted");
+        }
+        assert_eq!(
+            cache.stats(),
+            DiskCacheStats {
+                size_bytes: 12,
+                max_size_bytes: 14,
+                entries: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_startup_removes_unfinished_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_file = temp_dir.path().join("partial.img.tmp");
+        std::fs::write(&temp_file, b"1234").unwrap();
+
+        let cache = DiskImageCache::new(temp_dir.path().to_path_buf(), 1024)
+            .await
+            .unwrap();
+
+        assert!(!temp_file.exists());
+        assert!(cache.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_removes_temp_file() {
+        let (cache, _temp) = create_test_cache().await;
+        let id = ImageId::new("blocked");
+        std::fs::create_dir(cache.cache_path(&id)).unwrap();
+
+        assert!(cache.put_bytes(&id, b"1234").await.is_err());
+        assert!(!cache.cache_path(&id).with_extension("img.tmp").exists());
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! Implements a three-tier cache: Memory -> Disk -> Network
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -12,10 +12,13 @@ use tracing::{debug, error, info, trace, warn};
 use crate::domain::entities::{ImageId, ImageSource, LoadedImage};
 use crate::domain::ports::{CacheError, CacheResult, ImageCachePort};
 
-use super::discord_cdn::optimize_cdn_url_default;
-use super::disk_cache::DiskImageCache;
+use super::discord_cdn::{CdnImageOptions, optimize_cdn_url};
+use super::disk_cache::{DiskCacheStats, DiskImageCache};
 use super::memory_cache::MemoryImageCache;
 
+/// How often the disk cache is trimmed back under its size limit.
+const DISK_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_mins(10);
+
 /// Message sent when an image finishes loading.
 #[derive(Debug, Clone)]
 pub struct ImageLoadedEvent {
@@ -36,6 +39,8 @@ pub struct ImageLoaderConfig {
     pub max_concurrent_downloads: usize,
     /// Request timeout in seconds.
     pub timeout_secs: u64,
+    /// Size and format requested from the Discord CDN.
+    pub cdn: CdnImageOptions,
 }
 
 impl Default for ImageLoaderConfig {
@@ -45,6 +50,7 @@ impl Default for ImageLoaderConfig {
             disk_cache_size: 200 * 1024 * 1024,
             max_concurrent_downloads: 4,
             timeout_secs: 30,
+            cdn: CdnImageOptions::default(),
         }
     }
 }
@@ -53,7 +59,7 @@ impl Default for ImageLoaderConfig {
 pub struct ImageLoader {
     memory_cache: Arc<MemoryImageCache>,
     disk_cache: Arc<DiskImageCache>,
-    pending_loads: Arc<RwLock<HashSet<ImageId>>>,
+    pending_loads: Arc<RwLock<HashMap<ImageId, bool>>>,
     request_tx: mpsc::UnboundedSender<LoaderCommand>,
     config: ImageLoaderConfig,
     http_client: reqwest::Client,
@@ -61,8 +67,17 @@ pub struct ImageLoader {
 
 #[derive(Debug)]
 enum LoaderCommand {
-    Load { id: ImageId, url: String },
-    Cancel { id: ImageId },
+    Load {
+        id: ImageId,
+        url: String,
+    },
+    /// Replaces the prefetch queue; served only when no load is waiting.
+    Prefetch {
+        images: Vec<(ImageId, String)>,
+    },
+    Cancel {
+        id: ImageId,
+    },
     CancelAll,
 }
 
@@ -78,9 +93,10 @@ impl std::fmt::Debug for ImageLoader {
 struct WorkerState {
     memory_cache: Arc<MemoryImageCache>,
     disk_cache: Arc<DiskImageCache>,
-    pending_loads: Arc<RwLock<HashSet<ImageId>>>,
+    pending_loads: Arc<RwLock<HashMap<ImageId, bool>>>,
     event_tx: mpsc::UnboundedSender<ImageLoadedEvent>,
     http_client: reqwest::Client,
+    cdn: CdnImageOptions,
     semaphore: Arc<Semaphore>,
     request_rx: mpsc::UnboundedReceiver<LoaderCommand>,
 }
@@ -104,7 +120,7 @@ impl ImageLoader {
 
         let (request_tx, request_rx) = mpsc::unbounded_channel();
         let semaphore = Arc::new(Semaphore::new(config.max_concurrent_downloads));
-        let pending_loads = Arc::new(RwLock::new(HashSet::new()));
+        let pending_loads = Arc::new(RwLock::new(HashMap::new()));
 
         let worker_state = WorkerState {
             memory_cache: memory_cache.clone(),
@@ -112,6 +128,7 @@ impl ImageLoader {
             pending_loads: pending_loads.clone(),
             event_tx: event_tx.clone(),
             http_client: http_client.clone(),
+            cdn: config.cdn,
             semaphore,
             request_rx,
         };
@@ -132,58 +149,51 @@ impl ImageLoader {
     async fn run_worker_loop(mut state: WorkerState) {
         let mut queue: std::collections::VecDeque<(ImageId, String)> =
             std::collections::VecDeque::new();
+        let mut prefetch: std::collections::VecDeque<(ImageId, String)> =
+            std::collections::VecDeque::new();
+        let mut disk_cleanup = tokio::time::interval_at(
+            tokio::time::Instant::now() + DISK_CLEANUP_INTERVAL,
+            DISK_CLEANUP_INTERVAL,
+        );
 
         loop {
             tokio::select! {
                 cmd = state.request_rx.recv() => {
                     match cmd {
                         Some(LoaderCommand::Load { id, url }) => {
+                            prefetch.retain(|(qid, _)| *qid != id);
                             if !queue.iter().any(|(qid, _)| *qid == id) {
                                 queue.push_front((id, url));
                             }
                         }
+                        Some(LoaderCommand::Prefetch { images }) => {
+                            prefetch = images
+                                .into_iter()
+                                .filter(|(id, _)| !queue.iter().any(|(qid, _)| qid == id))
+                                .collect();
+                        }
                         Some(LoaderCommand::Cancel { id }) => {
                             queue.retain(|(qid, _)| *qid != id);
+                            prefetch.retain(|(qid, _)| *qid != id);
                         }
                         Some(LoaderCommand::CancelAll) => {
                             queue.clear();
+                            prefetch.clear();
                         }
                         None => break,
                     }
                 }
-                Ok(permit) = state.semaphore.clone().acquire_owned(), if !queue.is_empty() => {
+                _ = disk_cleanup.tick() => {
+                    let disk_cache = state.disk_cache.clone();
+                    tokio::spawn(async move { disk_cache.cleanup_if_needed().await });
+                }
+                Ok(permit) = state.semaphore.clone().acquire_owned(),
+                    if !queue.is_empty() || !prefetch.is_empty() =>
+                {
                     if let Some((id, url)) = queue.pop_front() {
-                        let handle = ImageLoaderHandle {
-                            memory_cache: state.memory_cache.clone(),
-                            disk_cache: state.disk_cache.clone(),
-                            pending_loads: state.pending_loads.clone(),
-                            event_tx: state.event_tx.clone(),
-                            http_client: state.http_client.clone(),
-                        };
-
-                        tokio::spawn(async move {
-                            {
-                                let mut pending = handle.pending_loads.write().await;
-                                if pending.contains(&id) {
-                                    return;
-                                }
-                                pending.insert(id.clone());
-                            }
-
-                            let result = handle.load_image(&id, &url).await;
-
-                            {
-                                let mut pending = handle.pending_loads.write().await;
-                                pending.remove(&id);
-                            }
-
-                            let event = ImageLoadedEvent {
-                                id: id.clone(),
-                                result,
-                            };
-                            let _ = handle.event_tx.send(event);
-                            drop(permit);
-                        });
+                        state.spawn_load(permit, id, url, true);
+                    } else if let Some((id, url)) = prefetch.pop_front() {
+                        state.spawn_load(permit, id, url, false);
                     }
                 }
             }
@@ -197,8 +207,20 @@ impl ImageLoader {
     pub async fn with_defaults(
         event_tx: mpsc::UnboundedSender<ImageLoadedEvent>,
     ) -> CacheResult<Self> {
-        let disk_cache = Arc::new(DiskImageCache::default_location().await?);
-        Self::new(ImageLoaderConfig::default(), &event_tx, disk_cache)
+        Self::with_config(ImageLoaderConfig::default(), event_tx).await
+    }
+
+    /// Creates a loader with the given configuration and the default disk cache.
+    ///
+    /// # Errors
+    /// Returns error if disk cache or HTTP client cannot be created.
+    pub async fn with_config(
+        config: ImageLoaderConfig,
+        event_tx: mpsc::UnboundedSender<ImageLoadedEvent>,
+    ) -> CacheResult<Self> {
+        let disk_cache =
+            Arc::new(DiskImageCache::default_location_with_max_size(config.disk_cache_size).await?);
+        Self::new(config, &event_tx, disk_cache)
     }
 
     /// Checks memory cache synchronously (non-blocking peek).
@@ -228,7 +250,7 @@ impl ImageLoader {
             });
         }
 
-        let optimized_url = optimize_cdn_url_default(url);
+        let optimized_url = optimize_cdn_url(url, &self.config.cdn);
         debug!(id = %id, url = %optimized_url, "Downloading image from network");
 
         let (bytes, _content_type) = self.download(&optimized_url).await?;
@@ -267,10 +289,13 @@ impl ImageLoader {
         }
     }
 
-    /// Prefetches multiple images into cache.
+    /// Prefetches images into cache behind any visible loads.
+    ///
+    /// Replaces the previous batch, so images no longer near the viewport
+    /// are dropped from the queue. Nothing is reported when they finish.
     pub fn prefetch_batch(&self, images: Vec<(ImageId, String)>) {
-        for (id, url) in images {
-            self.load_async(id, url);
+        if let Err(e) = self.request_tx.send(LoaderCommand::Prefetch { images }) {
+            error!("Failed to send prefetch request: {}", e);
         }
     }
 
@@ -303,7 +328,7 @@ impl ImageLoader {
     /// Returns true if an image is currently loading.
     pub async fn is_loading(&self, id: &ImageId) -> bool {
         let pending = self.pending_loads.read().await;
-        pending.contains(id)
+        pending.contains_key(id)
     }
 
     /// Returns the number of pending loads.
@@ -360,7 +385,7 @@ impl ImageLoader {
         {
             (Bytes::from(cached_bytes), None)
         } else {
-            let optimized_url = optimize_cdn_url_default(url);
+            let optimized_url = optimize_cdn_url(url, &self.config.cdn);
             let (bytes, ctype) = self.download(&optimized_url).await?;
             let _ = self.disk_cache.put_bytes(id, &bytes).await;
             (bytes, ctype)
@@ -385,7 +410,7 @@ impl ImageLoader {
             "png"
         };
 
-        let temp_dir = std::env::temp_dir().join("oxicord").join("view");
+        let temp_dir = crate::infrastructure::temp_dir().join("view");
         tokio::fs::create_dir_all(&temp_dir)
             .await
             .map_err(|e| CacheError::IoError(format!("Failed to create temp view dir: {e}")))?;
@@ -417,6 +442,12 @@ impl ImageLoader {
         self.memory_cache.stats()
     }
 
+    /// Returns disk cache statistics.
+    #[must_use]
+    pub fn disk_cache_stats(&self) -> DiskCacheStats {
+        self.disk_cache.stats()
+    }
+
     /// Clears all caches.
     pub async fn clear_all(&self) {
         self.memory_cache.clear().await;
@@ -427,13 +458,73 @@ impl ImageLoader {
     }
 }
 
+impl WorkerState {
+    /// Loads one image on its own task, holding `permit` until done.
+    ///
+    /// Prefetches (`notify` off) only warm the caches: no event is sent, so
+    /// the image stays in the memory cache's LRU until it is shown or
+    /// evicted. They are tracked as pending like any other load; a visible
+    /// load for an image that is still prefetching marks the entry instead,
+    /// and the prefetch reports the result when it finishes.
+    fn spawn_load(
+        &self,
+        permit: tokio::sync::OwnedSemaphorePermit,
+        id: ImageId,
+        url: String,
+        notify: bool,
+    ) {
+        let handle = ImageLoaderHandle {
+            memory_cache: self.memory_cache.clone(),
+            disk_cache: self.disk_cache.clone(),
+            pending_loads: self.pending_loads.clone(),
+            event_tx: self.event_tx.clone(),
+            http_client: self.http_client.clone(),
+            cdn: self.cdn,
+        };
+
+        tokio::spawn(async move {
+            {
+                let mut pending = handle.pending_loads.write().await;
+                if let Some(wanted) = pending.get_mut(&id) {
+                    *wanted |= notify;
+                    return;
+                }
+                pending.insert(id.clone(), notify);
+            }
+
+            let result = handle.load_image(&id, &url).await;
+
+            let wanted = {
+                let mut pending = handle.pending_loads.write().await;
+                pending.remove(&id).unwrap_or(false)
+            };
+
+            if !notify && !wanted {
+                if let Err(e) = result {
+                    trace!(id = %id, error = %e, "Image prefetch failed");
+                }
+                drop(permit);
+                return;
+            }
+
+            let event = ImageLoadedEvent {
+                id: id.clone(),
+                result,
+            };
+            let _ = handle.event_tx.send(event);
+            drop(permit);
+        });
+    }
+}
+
 /// Internal handle for async loading tasks.
 struct ImageLoaderHandle {
     memory_cache: Arc<MemoryImageCache>,
     disk_cache: Arc<DiskImageCache>,
-    pending_loads: Arc<RwLock<HashSet<ImageId>>>,
+    pending_loads: Arc<RwLock<HashMap<ImageId, bool>>>,
     event_tx: mpsc::UnboundedSender<ImageLoadedEvent>,
     http_client: reqwest::Client,
+    cdn: CdnImageOptions,
 }
 
 impl ImageLoaderHandle {
@@ -455,7 +546,7 @@ impl ImageLoaderHandle {
             });
         }
 
-        let optimized_url = optimize_cdn_url_default(url);
+        let optimized_url = optimize_cdn_url(url, &self.cdn);
         debug!(id = %id, "Downloading image: {}", optimized_url);
 
         let response = self
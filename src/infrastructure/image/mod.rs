@@ -12,8 +12,9 @@ pub mod loader;
 pub mod memory_cache;
 
 pub use discord_cdn::{
-    extract_attachment_id, is_discord_cdn_url, optimize_cdn_url, optimize_cdn_url_default,
+    ANIMATED_AVATAR_INDICATOR, AvatarImage, CdnImageOptions, avatar_image, extract_attachment_id,
+    is_animated_hash, is_discord_cdn_url, optimize_cdn_url, optimize_cdn_url_default,
 };
-pub use disk_cache::DiskImageCache;
+pub use disk_cache::{DiskCacheStats, DiskImageCache};
 pub use loader::{ImageLoadedEvent, ImageLoader, ImageLoaderConfig};
 pub use memory_cache::{CacheStats, MemoryImageCache};
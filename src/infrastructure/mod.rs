@@ -25,8 +25,15 @@ pub use discord::{
 };
 #[cfg(feature = "image")]
 pub use image::{
-    CacheStats, DiskImageCache, ImageLoadedEvent, ImageLoader, ImageLoaderConfig, MemoryImageCache,
-    extract_attachment_id, is_discord_cdn_url, optimize_cdn_url, optimize_cdn_url_default,
+    CacheStats, CdnImageOptions, DiskCacheStats, DiskImageCache, ImageLoadedEvent, ImageLoader,
+    ImageLoaderConfig, MemoryImageCache, extract_attachment_id, is_discord_cdn_url,
+    optimize_cdn_url, optimize_cdn_url_default,
 };
 pub use state_store::StateStore;
 pub use storage::KeyringTokenStorage;
+
+/// Directory for temporary files such as pasted and exported images.
+#[must_use]
+pub fn temp_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("oxicord")
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use fuzzy_matcher::FuzzyMatcher;
@@ -31,6 +32,25 @@ impl FuzzySearcher {
     pub fn score(&self, choice: &str, pattern: &str) -> Option<i64> {
         self.matcher.fuzzy_match(choice, pattern)
     }
+
+    /// Like [`Self::score`], also returning the character positions in
+    /// `choice` that matched.
+    #[must_use]
+    pub fn indices(&self, choice: &str, pattern: &str) -> Option<(i64, Vec<usize>)> {
+        self.matcher.fuzzy_indices(choice, pattern)
+    }
+}
+
+/// `Guild › category` line shown under a channel, or the parent channel
+/// for a thread.
+#[must_use]
+pub fn channel_subtitle(guild_name: &str, parent_name: Option<&str>) -> Option<String> {
+    match (guild_name.is_empty(), parent_name) {
+        (false, Some(parent)) => Some(format!("{guild_name} › {parent}")),
+        (false, None) => Some(guild_name.to_string()),
+        (true, Some(parent)) => Some(parent.to_string()),
+        (true, None) => None,
+    }
 }
 
 /// Search provider for Guild Channels.
@@ -72,7 +92,12 @@ impl ChannelSearchProvider {
                 format!("{} {} {}", guild_name, channel.name(), guild_name)
             };
 
-            let name_score = self.searcher.score(channel.name(), query);
+            let (name_score, match_indices) = self
+                .searcher
+                .indices(channel.name(), query)
+                .map_or((None, Vec::new()), |(score, indices)| {
+                    (Some(score), indices)
+                });
             let context_score = self.searcher.score(&search_text, query);
 
             let mut score = match (name_score, context_score) {
@@ -119,8 +144,9 @@ impl ChannelSearchProvider {
                 SearchKind::Channel
             };
 
-            let mut result =
-                SearchResult::new(channel.id().to_string(), channel.name(), kind).with_score(score);
+            let mut result = SearchResult::new(channel.id().to_string(), channel.name(), kind)
+                .with_score(score)
+                .with_match_indices(match_indices);
 
             if !guild_name.is_empty()
                 && let Some(gid) = channel.guild_id()
@@ -132,7 +158,8 @@ impl ChannelSearchProvider {
                 result = result.with_parent_name(p_name);
             }
 
-            results.push(result);
+            results
+                .push(result.with_subtitle(channel_subtitle(guild_name, parent_name.as_deref())));
         }
 
         results.sort_by(|a, b| b.score.cmp(&a.score));
@@ -154,6 +181,8 @@ pub struct DmSearchProvider {
     dms: Vec<DirectMessageChannel>,
     searcher: FuzzySearcher,
     use_display_name: bool,
+    /// Last message seen per DM channel id.
+    snippets: HashMap<String, String>,
 }
 
 impl DmSearchProvider {
@@ -163,24 +192,26 @@ impl DmSearchProvider {
             dms,
             searcher: FuzzySearcher::new(),
             use_display_name,
+            snippets: HashMap::new(),
         }
     }
 
+    /// Shows the given last messages, keyed by DM channel id, under results.
+    #[must_use]
+    pub fn with_snippets(mut self, snippets: HashMap<String, String>) -> Self {
+        self.snippets = snippets;
+        self
+    }
+
     #[must_use]
     pub fn search_sync(&self, query: &str) -> Vec<SearchResult> {
         let mut results = Vec::new();
         let query_lower = query.to_lowercase();
 
         for dm in &self.dms {
-            let name = if self.use_display_name {
-                dm.recipient_global_name
-                    .as_ref()
-                    .unwrap_or(&dm.recipient_username)
-            } else {
-                &dm.recipient_username
-            };
+            let name = &dm.name(self.use_display_name);
 
-            if let Some(mut score) = self.searcher.score(name, query) {
+            if let Some((mut score, indices)) = self.searcher.indices(name, query) {
                 if name.eq_ignore_ascii_case(query) {
                     score += 100;
                 } else if name.to_lowercase().starts_with(&query_lower) {
@@ -189,7 +220,9 @@ impl DmSearchProvider {
 
                 results.push(
                     SearchResult::new(dm.channel_id.clone(), name, SearchKind::DM)
-                        .with_score(score),
+                        .with_subtitle(self.snippets.get(&dm.channel_id).cloned())
+                        .with_score(score)
+                        .with_match_indices(indices),
                 );
             }
         }
@@ -225,10 +258,11 @@ impl GuildSearchProvider {
         let mut results = Vec::new();
 
         for guild in &self.guilds {
-            if let Some(score) = self.searcher.score(guild.name(), query) {
+            if let Some((score, indices)) = self.searcher.indices(guild.name(), query) {
                 results.push(
                     SearchResult::new(guild.id().to_string(), guild.name(), SearchKind::Guild)
-                        .with_score(score),
+                        .with_score(score)
+                        .with_match_indices(indices),
                 );
             }
         }
@@ -262,11 +296,46 @@ mod tests {
         assert_eq!(results[0].name, "cool-thread");
         assert_eq!(results[0].parent_name.as_deref(), Some("General"));
         assert_eq!(results[0].kind, SearchKind::Thread);
+        assert_eq!(results[0].subtitle.as_deref(), Some("Guild A › General"));
 
         let results = provider.search("General").await;
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "cool-thread");
     }
+
+    #[test]
+    fn test_fuzzy_indices_mark_matched_characters() {
+        let searcher = FuzzySearcher::new();
+        let (score, indices) = searcher.indices("crash-course", "crs").unwrap();
+        assert_eq!(Some(score), searcher.score("crash-course", "crs"));
+        assert_eq!(indices, vec![0, 1, 3]);
+        assert!(searcher.indices("crash-course", "xyz").is_none());
+
+        let crash = Channel::new(ChannelId(1), "crash-course", ChannelKind::Text);
+        let results =
+            ChannelSearchProvider::new(vec![(String::new(), crash, None)]).search_sync("crs");
+        assert_eq!(results[0].match_indices, vec![0, 1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_dm_result_shows_last_message() {
+        let dm = DirectMessageChannel {
+            channel_id: "5".to_string(),
+            recipient_id: "7".to_string(),
+            recipient_username: "ferris".to_string(),
+            recipient_discriminator: "0".to_string(),
+            recipient_global_name: None,
+            last_message_id: None,
+            has_unread: false,
+            mention_count: 0,
+            kind: crate::domain::ports::DmKind::Direct,
+        };
+        let provider = DmSearchProvider::new(vec![dm], true)
+            .with_snippets(HashMap::from([("5".to_string(), "see you".to_string())]));
+
+        let results = provider.search("ferris").await;
+        assert_eq!(results[0].subtitle.as_deref(), Some("see you"));
+    }
 }
 
 #[cfg(test)]
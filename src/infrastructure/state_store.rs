@@ -14,6 +14,12 @@ pub struct AppState {
     pub recents: Vec<RecentItem>,
     #[serde(default)]
     pub quick_switcher_order: QuickSwitcherSortMode,
+    /// Persist keys of expanded guild tree folders and guilds.
+    #[serde(default)]
+    pub expanded_nodes: Vec<String>,
+    /// Channels pinned to the top of the quick switcher.
+    #[serde(default)]
+    pub pinned: Vec<RecentItem>,
 }
 
 #[derive(Clone)]
@@ -46,6 +52,14 @@ impl StateStore {
         }
     }
 
+    /// Creates a state store persisting to `path`.
+    #[must_use]
+    pub const fn with_path(path: PathBuf) -> Self {
+        Self {
+            config_path: Some(path),
+        }
+    }
+
     /// Loads the persisted state from disk.
     ///
     /// # Errors
@@ -82,6 +96,8 @@ impl StateStore {
         channel_id: Option<String>,
         recents: &[RecentItem],
         sort_mode: QuickSwitcherSortMode,
+        expanded_nodes: &[String],
+        pinned: &[RecentItem],
     ) -> Result<()> {
         let Some(path) = &self.config_path else {
             return Ok(());
@@ -92,6 +108,8 @@ impl StateStore {
             last_channel_id: channel_id,
             recents: recents.to_vec(),
             quick_switcher_order: sort_mode,
+            expanded_nodes: expanded_nodes.to_vec(),
+            pinned: pinned.to_vec(),
         };
 
         if let Some(parent) = path.parent() {
@@ -108,4 +126,65 @@ impl StateStore {
 
         Ok(())
     }
+    /// Deletes everything persisted: last channel, recents and the guild
+    /// tree's expanded folders.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state file exists but cannot be removed.
+    pub async fn clear_all(&self) -> Result<()> {
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+
+        match fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).wrap_err("Failed to remove state file")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::search::SearchKind;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_clear_all_removes_persisted_state() {
+        let dir = tempdir().unwrap();
+        let store = StateStore::with_path(dir.path().join("state.toml"));
+        let recents = [RecentItem {
+            id: "1".to_string(),
+            name: "general".to_string(),
+            kind: SearchKind::Channel,
+            guild_id: Some("10".to_string()),
+            timestamp: 0,
+        }];
+        store
+            .save(
+                Some("10".to_string()),
+                Some("1".to_string()),
+                &recents,
+                QuickSwitcherSortMode::default(),
+                &["guild:10".to_string()],
+                &recents,
+            )
+            .await
+            .unwrap();
+        let saved = store.load().await.unwrap();
+        assert_eq!(saved.recents.len(), 1);
+        assert_eq!(saved.pinned[0].name, "general");
+
+        store.clear_all().await.unwrap();
+        let state = store.load().await.unwrap();
+        assert!(state.recents.is_empty());
+        assert!(state.last_channel_id.is_none());
+        assert!(state.expanded_nodes.is_empty());
+        assert!(state.pinned.is_empty());
+
+        store.clear_all().await.unwrap();
+    }
 }
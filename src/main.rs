@@ -1,14 +1,19 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use clap::Parser;
-use color_eyre::eyre::Result;
-use tracing::info;
+use color_eyre::eyre::{Result, eyre};
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-use oxicord::application::dto::TokenSource;
+use oxicord::application::dto::{ExportRequest, TokenSource};
+use oxicord::application::use_cases::{ExportHistoryUseCase, ResolveTokenUseCase};
+use oxicord::infrastructure::config::{Command, ExportArgs};
 use oxicord::infrastructure::{
     AppConfig, CliArgs, DiscordClient, KeyringTokenStorage, StorageManager,
 };
+use oxicord::presentation::theme::detection::detect_light_mode;
+use oxicord::presentation::widgets::{ImageManager, SELF_TEST_IMAGE};
 use oxicord::presentation::{App, Theme};
 
 fn init_logging(config: &AppConfig) -> Result<()> {
@@ -44,27 +49,59 @@ fn init_logging(config: &AppConfig) -> Result<()> {
     Ok(())
 }
 
-fn detect_light_mode() -> bool {
-    let timeout = std::time::Duration::from_millis(100);
-    if let Ok(theme) = termbg::theme(timeout) {
-        matches!(theme, termbg::Theme::Light)
-    } else {
-        false
+/// Reports graphics protocol detection and a decode/render of the bundled
+/// test image, for debugging images that don't show.
+fn run_image_self_test() -> Result<()> {
+    let report = ImageManager::new().self_test(SELF_TEST_IMAGE);
+    println!("{report}");
+    if !report.passed() {
+        std::process::exit(1);
     }
+    Ok(())
 }
 
-fn create_app() -> Result<(App, Option<(String, TokenSource)>)> {
-    let args = CliArgs::parse();
+fn env_token() -> Option<(String, TokenSource)> {
+    std::env::var("OXICORD_TOKEN")
+        .ok()
+        .map(|token| (token, TokenSource::Environment))
+}
 
+/// Writes a channel's history to a file without starting the TUI.
+async fn run_export(args: ExportArgs, config_path: Option<&Path>) -> Result<()> {
+    let storage = StorageManager::new()?;
+    let config = storage.load_config(config_path)?;
+    init_logging(&config)?;
+
+    let token = ResolveTokenUseCase::new(Arc::new(KeyringTokenStorage::new()))
+        .execute(env_token())
+        .await?
+        .ok_or_else(|| eyre!("no token found: log in once or set OXICORD_TOKEN"))?
+        .token;
+
+    let request = ExportRequest {
+        channel_id: args.channel,
+        format: args.format,
+        after: args.after,
+        before: args.before,
+        limit: args.limit,
+    };
+    let out = std::io::BufWriter::new(std::fs::File::create(&args.out)?);
+    let count = ExportHistoryUseCase::new(Arc::new(DiscordClient::new()?))
+        .execute(&token, &request, out)
+        .await?;
+
+    println!("Exported {count} messages to {}", args.out.display());
+    Ok(())
+}
+
+fn create_app(args: CliArgs) -> Result<(App, Option<(String, TokenSource)>)> {
     let storage = StorageManager::new()?;
 
     let mut config = storage.load_config(args.config.as_deref())?;
 
     config.merge_with_args(args);
 
-    let external_token: Option<(String, TokenSource)> = std::env::var("OXICORD_TOKEN")
-        .ok()
-        .map(|token| (token, TokenSource::Environment));
+    let external_token = env_token();
 
     init_logging(&config)?;
 
@@ -73,12 +110,27 @@ fn create_app() -> Result<(App, Option<(String, TokenSource)>)> {
     let discord_client = Arc::new(DiscordClient::new()?);
     let identity = discord_client.identity.clone();
     let token_storage = Arc::new(KeyringTokenStorage::new());
-    let is_light_mode = detect_light_mode();
+    let is_light_mode = detect_light_mode().unwrap_or(false);
     let theme = Theme::new(
         &config.theme.accent_color,
         config.theme.mention_color.as_deref(),
         is_light_mode,
-    );
+    )
+    .with_author_color_mode(config.theme.author_color_mode);
+    let accent_overrides = config
+        .theme
+        .accent_overrides
+        .iter()
+        .filter_map(|(id, color)| {
+            let Ok(id) = id.parse::<u64>() else {
+                warn!(id, "Ignoring accent override with invalid id");
+                return None;
+            };
+            let theme = Theme::new(color, config.theme.mention_color.as_deref(), is_light_mode)
+                .with_author_color_mode(config.theme.author_color_mode);
+            Some((id, theme))
+        })
+        .collect();
 
     let app_config = oxicord::presentation::AppConfig {
         disable_user_colors: config.disable_user_colors,
@@ -89,14 +141,62 @@ fn create_app() -> Result<(App, Option<(String, TokenSource)>)> {
         image_preview: config.ui.image_preview,
         timestamp_format: config.ui.timestamp_format.clone(),
         show_typing: config.ui.show_typing,
+        enable_typing_events: config.gateway.enable_typing,
+        enable_presence_updates: config.gateway.enable_presence,
+        idle_after: (config.gateway.idle_after_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.gateway.idle_after_secs)),
         internal_notifications: config.notifications.internal_notifications,
+        notification_batch_window: std::time::Duration::from_millis(
+            config.notifications.batch_window_ms,
+        ),
+        max_internal_notifications: config.notifications.max_internal_notifications,
         enable_animations: config.ui.enable_animations,
+        cdn_max_dimension: config.image.cdn_max_dimension,
+        prefer_webp: config.image.prefer_webp,
+        disk_cache_max_mb: config.image.disk_cache_max_mb,
+        image_prefetch_distance: config.image.prefetch_distance,
         editor: config.editor.clone(),
+        file_explorer: config.file_explorer.clone(),
+        keymap: config.keymap,
+        sequence_timeout: std::time::Duration::from_millis(config.sequence_timeout_ms),
         keybindings: config.keybindings.clone(),
         notification_duration: config.ui.notification_duration,
         theme,
         hide_blocked_completely: config.ui.hide_blocked_completely,
         quick_switcher_order: config.quick_switcher_order,
+        quick_switcher_sections: config.quick_switcher_sections,
+        confirm_logout_with_drafts: config.ui.confirm_logout_with_drafts,
+        default_suppress_mentions: config.ui.default_suppress_mentions,
+        max_message_length: config.ui.max_message_length,
+        large_paste_threshold: config.ui.large_paste_threshold,
+        message_density: config.ui.message_density,
+        record_recents: config.ui.record_recents,
+        max_recents: config.ui.max_recents,
+        splash_min_duration_ms: config.ui.splash_min_duration_ms,
+        show_splash: config.ui.show_splash,
+        require_double_escape_to_quit: config.ui.require_double_escape_to_quit,
+        timestamp_position: config.ui.timestamp_position,
+        show_embeds: config.ui.show_embeds,
+        ellipsis: config.ui.ellipsis.clone(),
+        purge_message_limit: config.ui.purge_message_limit,
+        group_start_timestamps_only: config.ui.group_start_timestamps_only,
+        confirm_everyone_send: config.ui.confirm_everyone_send,
+        show_mention_count: config.ui.show_mention_count,
+        wrap_indent: config.ui.wrap_indent,
+        confirm_masked_links: config.ui.confirm_masked_links,
+        startup_focus: config.ui.startup_focus,
+        suppress_everyone_highlight: config.ui.suppress_everyone_highlight,
+        mark_read_on_focus: config.ui.mark_read_on_focus,
+        compact_guilds_tree: config.ui.compact_guilds_tree,
+        restore_focus_after_overlay: config.ui.restore_focus_after_overlay,
+        double_esc_to_close: config.ui.double_esc_to_close,
+        group_replies: config.ui.group_replies,
+        collapse_message_lines: config.ui.collapse_message_lines,
+        eager_guild_count: config.ui.eager_guild_count,
+        flatten_single_channel_categories: config.ui.flatten_single_channel_categories,
+        accent_overrides,
+        background_poll_interval: (config.theme.background_poll_interval > 0)
+            .then(|| std::time::Duration::from_secs(config.theme.background_poll_interval)),
     };
 
     let app = App::new(
@@ -114,15 +214,31 @@ fn create_app() -> Result<(App, Option<(String, TokenSource)>)> {
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let (app, external_token) = create_app()?;
+    let mut args = CliArgs::parse();
+    if args.image_self_test {
+        return run_image_self_test();
+    }
+    if let Some(Command::Export(export)) = args.command.take() {
+        return run_export(export, args.config.as_deref()).await;
+    }
+
+    let (app, external_token) = create_app(args)?;
 
     let mut terminal = ratatui::init();
 
-    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::EnableBracketedPaste);
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::event::EnableBracketedPaste,
+        crossterm::event::EnableFocusChange
+    );
 
     let result = app.run(&mut terminal, external_token).await;
 
-    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableFocusChange
+    );
     ratatui::restore();
 
     result
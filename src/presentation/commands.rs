@@ -1,12 +1,34 @@
 use crate::domain::keybinding::{Action, Keybind};
+use crate::infrastructure::config::KeymapPreset;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tracing::warn;
 
+/// How long a sequence prefix waits for its next key unless configured.
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Result of feeding a key into the multi-key sequence matcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceMatch {
+    /// The key neither starts nor continues a known sequence.
+    None,
+    /// The key extends a known prefix; more keys are needed.
+    Pending,
+    /// The key completed the sequence bound to this action.
+    Complete(Action),
+}
+
 #[derive(Clone)]
 pub struct CommandRegistry {
     display_bindings: HashMap<Action, Vec<KeyEvent>>,
     input_bindings: Vec<(KeyEvent, Action)>,
+    sequence_bindings: Vec<(Vec<KeyEvent>, Action)>,
+    pending_sequence: Vec<KeyEvent>,
+    sequence_started: Option<Instant>,
+    sequence_timeout: Duration,
+    /// Action that `find_action` reports while a completed sequence is dispatched.
+    sequence_action: Option<Action>,
 }
 
 #[allow(clippy::too_many_lines)]
@@ -118,6 +140,11 @@ impl Default for CommandRegistry {
             KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL),
             false,
         );
+        register(
+            Action::ToggleAttachmentSpoiler,
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            true,
+        );
         register(
             Action::NextTab,
             KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
@@ -262,6 +289,16 @@ impl Default for CommandRegistry {
             ),
             true,
         );
+        register(
+            Action::CopyMessageLink,
+            KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT),
+            true,
+        );
+        register(
+            Action::CopyCodeBlock,
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE),
+            true,
+        );
         register(
             Action::OpenAttachments,
             KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
@@ -272,6 +309,46 @@ impl Default for CommandRegistry {
             KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE),
             true,
         );
+        register(
+            Action::ToggleThreadList,
+            KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE),
+            true,
+        );
+        register(
+            Action::RetrySend,
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            true,
+        );
+        register(
+            Action::ToggleMessageExpand,
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+            true,
+        );
+        register(
+            Action::PublishMessage,
+            KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT),
+            true,
+        );
+        register(
+            Action::PurgeOwnMessages,
+            KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT),
+            true,
+        );
+        register(
+            Action::NextMention,
+            KeyEvent::new(KeyCode::Char(']'), KeyModifiers::NONE),
+            true,
+        );
+        register(
+            Action::PreviousMention,
+            KeyEvent::new(KeyCode::Char('['), KeyModifiers::NONE),
+            true,
+        );
+        register(
+            Action::JumpToLatest,
+            KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE),
+            true,
+        );
 
         register(
             Action::SendMessage,
@@ -288,6 +365,11 @@ impl Default for CommandRegistry {
             KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
             true,
         );
+        register(
+            Action::ToggleReplyMention,
+            KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT),
+            true,
+        );
         register(
             Action::Cancel,
             KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
@@ -330,16 +412,46 @@ impl Default for CommandRegistry {
             KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
             true,
         );
+        register(
+            Action::ToggleImagePreview,
+            KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT),
+            true,
+        );
+        register(
+            Action::ChangeNickname,
+            KeyEvent::new(KeyCode::Char('k'), KeyModifiers::ALT),
+            true,
+        );
 
         register(
             Action::ToggleQuickSwitcher,
             KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL),
             true,
         );
+        register(
+            Action::CopyUnreadChannels,
+            KeyEvent::new(KeyCode::Char('u'), KeyModifiers::ALT),
+            true,
+        );
+        register(
+            Action::ToggleNotificationHistory,
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT),
+            true,
+        );
+        register(
+            Action::ClearRecents,
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT),
+            false,
+        );
 
         Self {
             display_bindings,
             input_bindings,
+            sequence_bindings: Vec::new(),
+            pending_sequence: Vec::new(),
+            sequence_started: None,
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
+            sequence_action: None,
         }
     }
 }
@@ -362,8 +474,19 @@ impl CommandRegistry {
             .and_then(|v| v.first().copied())
     }
 
+    #[must_use]
+    pub fn with_preset(preset: KeymapPreset) -> Self {
+        let mut registry = Self::default();
+        registry.apply_preset(preset);
+        registry
+    }
+
     #[must_use]
     pub fn find_action(&self, key: KeyEvent) -> Option<Action> {
+        if let Some(action) = self.sequence_action {
+            return Some(action);
+        }
+
         self.input_bindings
             .iter()
             .find(|(k, _)| k.code == key.code && k.modifiers == key.modifiers)
@@ -372,21 +495,174 @@ impl CommandRegistry {
 
     pub fn apply_overrides(&mut self, overrides: &HashMap<String, Action>) {
         for (key_str, action) in overrides {
-            if let Some(key_event) = parse_key_event(key_str) {
-                self.input_bindings.retain(|(k, _)| *k != key_event);
-
-                self.input_bindings.insert(0, (key_event, *action));
-
-                // Update display bindings
-                self.display_bindings
-                    .entry(*action)
-                    .or_default()
-                    .insert(0, key_event);
+            if key_str.contains(' ') {
+                let keys: Option<Vec<KeyEvent>> =
+                    key_str.split_whitespace().map(parse_key_event).collect();
+                match keys {
+                    Some(keys) if keys.len() > 1 => self.register_sequence(&keys, *action),
+                    _ => warn!("Failed to parse key sequence: {}", key_str),
+                }
+            } else if let Some(key_event) = parse_key_event(key_str) {
+                self.bind(key_event, *action);
             } else {
                 warn!("Failed to parse keybinding: {}", key_str);
             }
         }
     }
+
+    fn apply_preset(&mut self, preset: KeymapPreset) {
+        let key = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+
+        match preset {
+            KeymapPreset::Default => {}
+            KeymapPreset::Vim => {
+                let g = key(KeyCode::Char('g'), KeyModifiers::NONE);
+                let d = key(KeyCode::Char('d'), KeyModifiers::NONE);
+                self.unbind(g);
+                self.unbind(d);
+                self.register_sequence(&[g, g], Action::SelectFirst);
+                self.register_sequence(&[d, d], Action::DeleteMessage);
+                self.bind(
+                    key(KeyCode::Char('/'), KeyModifiers::NONE),
+                    Action::ToggleQuickSwitcher,
+                );
+            }
+            KeymapPreset::Emacs => {
+                let ctrl = KeyModifiers::CONTROL;
+                let alt = KeyModifiers::ALT;
+                self.bind(key(KeyCode::Char('n'), ctrl), Action::NavigateDown);
+                self.bind(key(KeyCode::Char('p'), ctrl), Action::NavigateUp);
+                self.bind(key(KeyCode::Char('f'), ctrl), Action::NavigateRight);
+                self.bind(key(KeyCode::Char('b'), ctrl), Action::NavigateLeft);
+                self.bind(key(KeyCode::Char('<'), alt), Action::SelectFirst);
+                self.bind(key(KeyCode::Char('>'), alt), Action::SelectLast);
+                self.bind(key(KeyCode::Char('s'), ctrl), Action::ToggleQuickSwitcher);
+                // Keep the actions displaced from Ctrl+b, Ctrl+n and Ctrl+s reachable.
+                self.bind(key(KeyCode::Char('b'), alt), Action::ToggleGuildsTree);
+                self.bind(key(KeyCode::Char('n'), alt), Action::ToggleDisplayName);
+                self.bind(
+                    key(KeyCode::Char('s'), alt),
+                    Action::ToggleAttachmentSpoiler,
+                );
+            }
+        }
+    }
+
+    /// Binds `key` to `action`, taking precedence over any existing binding.
+    fn bind(&mut self, key: KeyEvent, action: Action) {
+        self.unbind(key);
+        self.input_bindings.insert(0, (key, action));
+        self.display_bindings
+            .entry(action)
+            .or_default()
+            .insert(0, key);
+    }
+
+    fn unbind(&mut self, key: KeyEvent) {
+        self.input_bindings.retain(|(k, _)| *k != key);
+        for keys in self.display_bindings.values_mut() {
+            keys.retain(|k| *k != key);
+        }
+    }
+
+    /// Binds a multi-key sequence such as `gg` to `action`.
+    pub fn register_sequence(&mut self, keys: &[KeyEvent], action: Action) {
+        self.sequence_bindings.retain(|(k, _)| k.as_slice() != keys);
+        self.sequence_bindings.push((keys.to_vec(), action));
+    }
+
+    /// Feeds a key into the sequence matcher.
+    ///
+    /// Keys that are not sequence prefixes return [`SequenceMatch::None`] right away
+    /// so their single-key binding still applies. An unknown continuation
+    /// drops the buffered prefix and the key is matched again on its own, so
+    /// it can still start a new sequence.
+    pub fn feed_sequence(&mut self, key: KeyEvent) -> SequenceMatch {
+        self.feed_sequence_at(key, Instant::now())
+    }
+
+    fn feed_sequence_at(&mut self, key: KeyEvent, now: Instant) -> SequenceMatch {
+        if self.sequence_bindings.is_empty() {
+            return SequenceMatch::None;
+        }
+
+        self.expire_sequence_at(now);
+        self.pending_sequence.push(key);
+        let had_prefix = self.pending_sequence.len() > 1;
+
+        if let Some(action) = self
+            .sequence_bindings
+            .iter()
+            .find(|(keys, _)| keys_match(keys, &self.pending_sequence))
+            .map(|(_, action)| *action)
+        {
+            self.clear_sequence();
+            return SequenceMatch::Complete(action);
+        }
+
+        if self.sequence_bindings.iter().any(|(keys, _)| {
+            keys.len() > self.pending_sequence.len()
+                && keys_match(&keys[..self.pending_sequence.len()], &self.pending_sequence)
+        }) {
+            self.sequence_started.get_or_insert(now);
+            return SequenceMatch::Pending;
+        }
+
+        self.clear_sequence();
+        if had_prefix {
+            self.feed_sequence_at(key, now)
+        } else {
+            SequenceMatch::None
+        }
+    }
+
+    /// Sets how long a sequence prefix waits for its next key.
+    pub const fn set_sequence_timeout(&mut self, timeout: Duration) {
+        self.sequence_timeout = timeout;
+    }
+
+    /// Keys buffered while waiting for the rest of a sequence.
+    #[must_use]
+    pub fn pending_prefix(&self) -> Option<&[KeyEvent]> {
+        (!self.pending_sequence.is_empty()).then_some(self.pending_sequence.as_slice())
+    }
+
+    /// Drops a sequence prefix that has waited longer than the timeout.
+    ///
+    /// Returns `true` if a prefix was discarded.
+    pub fn expire_sequence(&mut self) -> bool {
+        self.expire_sequence_at(Instant::now())
+    }
+
+    fn expire_sequence_at(&mut self, now: Instant) -> bool {
+        let expired = self
+            .sequence_started
+            .is_some_and(|started| now.duration_since(started) >= self.sequence_timeout);
+        if expired {
+            self.clear_sequence();
+        }
+        expired
+    }
+
+    fn clear_sequence(&mut self) {
+        self.pending_sequence.clear();
+        self.sequence_started = None;
+    }
+
+    /// Makes `find_action` report `action` until cleared with `None`.
+    ///
+    /// Used to route a completed sequence through the regular key handlers.
+    pub const fn set_sequence_action(&mut self, action: Option<Action>) {
+        self.sequence_action = action;
+    }
+}
+
+fn keys_match(bound: &[KeyEvent], pressed: &[KeyEvent]) -> bool {
+    bound.len() == pressed.len()
+        && bound
+            .iter()
+            .zip(pressed)
+            .all(|(b, p)| b.code == p.code && b.modifiers == p.modifiers)
 }
 
 fn parse_key_event(s: &str) -> Option<KeyEvent> {
@@ -497,4 +773,107 @@ mod tests {
             Some(KeyEvent::new(expected_code, expected_modifiers))
         );
     }
+
+    #[test]
+    fn test_vim_preset_gg_maps_to_select_first() {
+        let mut registry = CommandRegistry::with_preset(KeymapPreset::Vim);
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+
+        assert_eq!(registry.find_action(g), None);
+        assert_eq!(registry.feed_sequence(g), SequenceMatch::Pending);
+        assert_eq!(
+            registry.feed_sequence(g),
+            SequenceMatch::Complete(Action::SelectFirst)
+        );
+    }
+
+    #[test]
+    fn test_unknown_continuation_restarts_sequence() {
+        let mut registry = CommandRegistry::with_preset(KeymapPreset::Vim);
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE);
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+
+        assert_eq!(registry.feed_sequence(g), SequenceMatch::Pending);
+        assert_eq!(registry.feed_sequence(j), SequenceMatch::None);
+        assert_eq!(registry.feed_sequence(g), SequenceMatch::Pending);
+        assert_eq!(registry.feed_sequence(d), SequenceMatch::Pending);
+        assert_eq!(
+            registry.feed_sequence(d),
+            SequenceMatch::Complete(Action::DeleteMessage)
+        );
+    }
+
+    #[test]
+    fn test_register_sequence_resolves_and_exposes_prefix() {
+        let mut registry = CommandRegistry::default();
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        registry.register_sequence(&[g, u], Action::ScrollToTop);
+
+        assert_eq!(registry.feed_sequence(j), SequenceMatch::None);
+        assert_eq!(registry.find_action(j), Some(Action::NavigateDown));
+
+        assert_eq!(registry.feed_sequence(g), SequenceMatch::Pending);
+        assert_eq!(registry.pending_prefix(), Some([g].as_slice()));
+        assert_eq!(
+            registry.feed_sequence(u),
+            SequenceMatch::Complete(Action::ScrollToTop)
+        );
+        assert_eq!(registry.pending_prefix(), None);
+    }
+
+    #[test]
+    fn test_sequence_prefix_expires_after_timeout() {
+        let mut registry = CommandRegistry::default();
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        registry.register_sequence(&[g, u], Action::ScrollToTop);
+
+        let start = Instant::now();
+        assert_eq!(registry.feed_sequence_at(g, start), SequenceMatch::Pending);
+        assert!(!registry.expire_sequence_at(start + DEFAULT_SEQUENCE_TIMEOUT / 2));
+        assert!(registry.pending_prefix().is_some());
+
+        let late = start + DEFAULT_SEQUENCE_TIMEOUT;
+        assert_eq!(registry.feed_sequence_at(u, late), SequenceMatch::None);
+        assert_eq!(registry.pending_prefix(), None);
+
+        registry.set_sequence_timeout(Duration::from_secs(5));
+        assert_eq!(registry.feed_sequence_at(g, start), SequenceMatch::Pending);
+        assert_eq!(
+            registry.feed_sequence_at(u, late),
+            SequenceMatch::Complete(Action::ScrollToTop),
+            "a longer configured timeout keeps the prefix"
+        );
+    }
+
+    #[test]
+    fn test_sequence_overrides_are_parsed() {
+        let mut registry = CommandRegistry::default();
+        registry.apply_overrides(&HashMap::from([(
+            "g Shift+u".to_string(),
+            Action::ScrollToBottom,
+        )]));
+
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let shift_u = KeyEvent::new(KeyCode::Char('U'), KeyModifiers::SHIFT);
+        assert_eq!(registry.feed_sequence(g), SequenceMatch::Pending);
+        assert_eq!(
+            registry.feed_sequence(shift_u),
+            SequenceMatch::Complete(Action::ScrollToBottom)
+        );
+    }
+
+    #[test]
+    fn test_emacs_preset_overrides_conflicting_keys() {
+        let registry = CommandRegistry::with_preset(KeymapPreset::Emacs);
+        let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        let alt_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::ALT);
+
+        assert_eq!(registry.find_action(ctrl_n), Some(Action::NavigateDown));
+        assert_eq!(registry.find_action(alt_n), Some(Action::ToggleDisplayName));
+        assert_eq!(registry.get_first(Action::ToggleDisplayName), Some(alt_n));
+    }
 }
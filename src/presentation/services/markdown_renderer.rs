@@ -5,21 +5,33 @@ use std::sync::Arc;
 use super::syntax_highlighting::{SyntaxHighlighter, SyntectHighlighter};
 use crate::application::services::markdown_parser::{MdBlock, MdInline, MentionResolver};
 
+/// Left border drawn before every line of a block quote, once per level.
+pub const QUOTE_BORDER: &str = "▎ ";
+
 pub struct MarkdownRenderer {
     highlighter: Arc<dyn SyntaxHighlighter>,
+    accent: Color,
 }
 
 impl MarkdownRenderer {
     #[must_use]
     pub fn new() -> Self {
+        Self::with_highlighter(Arc::new(SyntectHighlighter::new()))
+    }
+
+    #[must_use]
+    pub fn with_highlighter(highlighter: Arc<dyn SyntaxHighlighter>) -> Self {
         Self {
-            highlighter: Arc::new(SyntectHighlighter::new()),
+            highlighter,
+            accent: Color::Magenta,
         }
     }
 
+    /// Color used for top-level headings.
     #[must_use]
-    pub fn with_highlighter(highlighter: Arc<dyn SyntaxHighlighter>) -> Self {
-        Self { highlighter }
+    pub fn with_accent(mut self, accent: Color) -> Self {
+        self.accent = accent;
+        self
     }
 
     #[must_use]
@@ -29,7 +41,8 @@ impl MarkdownRenderer {
         resolver: Option<&dyn MentionResolver>,
         show_spoilers: bool,
     ) -> Text<'static> {
-        let mut renderer = InternalRenderer::new(resolver, &self.highlighter, show_spoilers);
+        let mut renderer =
+            InternalRenderer::new(resolver, &self.highlighter, self.accent, show_spoilers);
         renderer.render(blocks)
     }
 
@@ -54,6 +67,7 @@ impl Default for MarkdownRenderer {
 struct InternalRenderer<'a> {
     resolver: Option<&'a dyn MentionResolver>,
     highlighter: &'a Arc<dyn SyntaxHighlighter>,
+    accent: Color,
     show_spoilers: bool,
 }
 
@@ -61,11 +75,13 @@ impl<'a> InternalRenderer<'a> {
     fn new(
         resolver: Option<&'a dyn MentionResolver>,
         highlighter: &'a Arc<dyn SyntaxHighlighter>,
+        accent: Color,
         show_spoilers: bool,
     ) -> Self {
         Self {
             resolver,
             highlighter,
+            accent,
             show_spoilers,
         }
     }
@@ -83,22 +99,20 @@ impl<'a> InternalRenderer<'a> {
             MdBlock::Empty => lines.push(Line::raw("")),
             MdBlock::Paragraph(inlines) => {
                 let spans = self.render_inlines(inlines, parent_style);
-                lines.push(Line::from(spans));
+                lines.extend(split_lines(spans));
             }
-            MdBlock::Header(level, inlines) => {
+            MdBlock::Heading { level, inlines } => {
+                // Like Discord, headings get a gap above but none below.
+                if lines.last().is_some_and(|line| line.width() > 0) {
+                    lines.push(Line::raw(""));
+                }
                 let style = parent_style.add_modifier(Modifier::BOLD);
                 let style = match level {
-                    1 => style.fg(Color::Magenta),
-                    2 => style.fg(Color::Cyan),
-                    _ => style,
+                    1 => style.fg(self.accent),
+                    2 => style,
+                    _ => style.add_modifier(Modifier::DIM),
                 };
-
-                let mut spans = Vec::new();
-                let prefix = "#".repeat(level as usize);
-                spans.push(Span::styled(format!("{prefix} "), style));
-                spans.extend(self.render_inlines(inlines, style));
-                lines.push(Line::from(spans));
-                lines.push(Line::raw(""));
+                lines.push(Line::from(self.render_inlines(inlines, style)));
             }
             MdBlock::Subtext(inlines) => {
                 let style = parent_style.fg(Color::DarkGray).add_modifier(Modifier::DIM);
@@ -167,7 +181,8 @@ impl<'a> InternalRenderer<'a> {
                 }
 
                 for line in inner_lines {
-                    let mut spans = vec![Span::styled("┃ ", Style::default().fg(Color::DarkGray))];
+                    let mut spans =
+                        vec![Span::styled(QUOTE_BORDER, Style::default().fg(Color::Gray))];
                     spans.extend(line.spans);
                     lines.push(Line::from(spans));
                 }
@@ -221,6 +236,23 @@ impl<'a> InternalRenderer<'a> {
                         style.fg(Color::Blue).add_modifier(Modifier::BOLD),
                     ));
                 }
+                MdInline::RoleMention(id) => {
+                    let (name, color) =
+                        self.resolver.and_then(|r| r.resolve_role(&id)).map_or_else(
+                            || (format!("@&{id}"), Color::Blue),
+                            |(name, color)| (format!("@{name}"), role_color(color)),
+                        );
+                    spans.push(Span::styled(
+                        name,
+                        style.fg(color).add_modifier(Modifier::BOLD),
+                    ));
+                }
+                MdInline::MassMention(target) => {
+                    spans.push(Span::styled(
+                        format!("@{target}"),
+                        style.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                    ));
+                }
                 MdInline::Channel(id) => {
                     let name = self
                         .resolver
@@ -231,12 +263,50 @@ impl<'a> InternalRenderer<'a> {
                         style.fg(Color::Blue).add_modifier(Modifier::BOLD),
                     ));
                 }
+                MdInline::Link(link) => {
+                    spans.push(Span::styled(
+                        link.text,
+                        style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+                    ));
+                }
             }
         }
         spans
     }
 }
 
+/// Breaks `spans` into one line per `\n`, keeping each piece's style.
+fn split_lines(spans: Vec<Span<'static>>) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut current = Vec::new();
+    for span in spans {
+        let mut pieces = span.content.split('\n');
+        if let Some(first) = pieces.next()
+            && !first.is_empty()
+        {
+            current.push(Span::styled(first.to_string(), span.style));
+        }
+        for piece in pieces {
+            lines.push(Line::from(std::mem::take(&mut current)));
+            if !piece.is_empty() {
+                current.push(Span::styled(piece.to_string(), span.style));
+            }
+        }
+    }
+    lines.push(Line::from(current));
+    lines
+}
+
+/// Discord role color, falling back to the mention blue for uncolored roles.
+fn role_color(color: u32) -> Color {
+    if color == 0 {
+        Color::Blue
+    } else {
+        let [_, r, g, b] = color.to_be_bytes();
+        Color::Rgb(r, g, b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +339,131 @@ mod tests {
         assert_eq!(span.style.bg, Some(Color::Rgb(50, 50, 50)));
         assert_ne!(span.style.fg, Some(Color::Rgb(50, 50, 50)));
     }
+
+    struct RoleMap(std::collections::HashMap<String, (String, u32)>);
+
+    impl MentionResolver for RoleMap {
+        fn resolve(&self, _user_id: &str) -> Option<String> {
+            None
+        }
+
+        fn resolve_channel(&self, _channel_id: &str) -> Option<String> {
+            None
+        }
+
+        fn resolve_role(&self, role_id: &str) -> Option<(String, u32)> {
+            self.0.get(role_id).cloned()
+        }
+    }
+
+    #[test]
+    fn test_role_mentions_resolve_with_role_color() {
+        let roles = RoleMap(
+            [
+                ("1".to_string(), ("Mods".to_string(), 0x00FF_8800)),
+                ("2".to_string(), ("Plain".to_string(), 0)),
+            ]
+            .into(),
+        );
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("<@&1> <@&2> <@&3>", Some(&roles), false);
+        let spans = &text.lines[0].spans;
+
+        assert_eq!(spans[0].content, "@Mods");
+        assert_eq!(spans[0].style.fg, Some(Color::Rgb(0xFF, 0x88, 0x00)));
+        assert_eq!(spans[2].content, "@Plain");
+        assert_eq!(spans[2].style.fg, Some(Color::Blue));
+        assert_eq!(spans[4].content, "@&3");
+    }
+
+    #[test]
+    fn test_mass_mention_styled_distinctly() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("@everyone", None, false);
+        let span = &text.lines[0].spans[0];
+
+        assert_eq!(span.content, "@everyone");
+        assert_eq!(span.style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn test_single_newlines_are_line_breaks() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("first\nsecond\nthird", None, false);
+        assert_eq!(text.lines.len(), 3);
+        assert_eq!(text.lines[1].to_string(), "second");
+
+        let text = renderer.render_markdown("**bold\nstill bold** plain\n\nafter", None, false);
+        assert_eq!(text.lines.len(), 4);
+        assert_eq!(text.lines[0].to_string(), "bold");
+        assert_eq!(text.lines[1].to_string(), "still bold plain");
+        assert!(
+            text.lines[1].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+        assert!(
+            !text.lines[1].spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+        assert_eq!(text.lines[2].to_string(), "");
+    }
+
+    #[test]
+    fn test_code_block_keeps_internal_newlines() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("run:\n```\nls\n\ncd /tmp\n```\ndone", None, false);
+        let lines: Vec<String> = text.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, ["run:", "ls", "", "cd /tmp", "done"]);
+    }
+
+    #[test]
+    fn test_nested_quote_repeats_border() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("> outer\n> > inner\nafter", None, false);
+        let lines: Vec<String> = text.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, ["▎ outer", "▎ ▎ inner", "after"]);
+        assert_eq!(text.lines[1].spans[0].style.fg, Some(Color::Gray));
+    }
+
+    #[test]
+    fn test_quote_containing_code_block() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("> ```\n> a\n>\n> b\n> ```", None, false);
+        let lines: Vec<String> = text.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, ["▎ a", "▎ ", "▎ b"]);
+    }
+
+    #[test]
+    fn test_headings_styled_by_level() {
+        let renderer = MarkdownRenderer::new().with_accent(Color::Green);
+        let text = renderer.render_markdown("# Title", None, false);
+        assert_eq!(text.lines.len(), 1);
+        let span = &text.lines[0].spans[0];
+        assert_eq!(span.content, "Title");
+        assert_eq!(span.style.fg, Some(Color::Green));
+        assert!(span.style.add_modifier.contains(Modifier::BOLD));
+
+        let text = renderer.render_markdown("intro\n## Part\nbody\n\n### Note", None, false);
+        let lines: Vec<String> = text.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, ["intro", "", "Part", "body", "", "Note"]);
+        let part = text.lines[2].spans[0].style;
+        assert!(part.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(part.fg, None);
+        let note = text.lines[5].spans[0].style;
+        assert!(note.add_modifier.contains(Modifier::BOLD | Modifier::DIM));
+    }
+
+    #[test]
+    fn test_masked_link_renders_display_text() {
+        let renderer = MarkdownRenderer::new();
+        let text = renderer.render_markdown("[docs](https://example.com)", None, false);
+        let span = &text.lines[0].spans[0];
+
+        assert_eq!(span.content, "docs");
+        assert!(span.style.add_modifier.contains(Modifier::UNDERLINED));
+    }
 }
@@ -0,0 +1,86 @@
+//! Terminal background detection.
+
+use std::time::Duration;
+
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Queries the terminal for its background color.
+///
+/// Returns `None` when the terminal does not answer within the timeout.
+#[must_use]
+pub fn detect_light_mode() -> Option<bool> {
+    termbg::theme(QUERY_TIMEOUT)
+        .ok()
+        .map(|theme| matches!(theme, termbg::Theme::Light))
+}
+
+/// Debounces periodic background readings.
+///
+/// A flip is only reported once two consecutive readings agree, so a single
+/// garbled reply cannot toggle the theme back and forth.
+#[derive(Debug, Clone, Copy)]
+pub struct BackgroundWatcher {
+    is_light_mode: bool,
+    candidate: Option<bool>,
+}
+
+impl BackgroundWatcher {
+    #[must_use]
+    pub const fn new(is_light_mode: bool) -> Self {
+        Self {
+            is_light_mode,
+            candidate: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_light_mode(&self) -> bool {
+        self.is_light_mode
+    }
+
+    /// Records a reading and returns the new mode once the flip is confirmed.
+    pub fn observe(&mut self, reading: Option<bool>) -> Option<bool> {
+        let reading = reading?;
+
+        if reading == self.is_light_mode {
+            self.candidate = None;
+            return None;
+        }
+
+        if self.candidate == Some(reading) {
+            self.is_light_mode = reading;
+            self.candidate = None;
+            Some(reading)
+        } else {
+            self.candidate = Some(reading);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flip_requires_two_matching_readings() {
+        let mut watcher = BackgroundWatcher::new(false);
+
+        assert_eq!(watcher.observe(Some(true)), None);
+        assert_eq!(watcher.observe(Some(true)), Some(true));
+        assert!(watcher.is_light_mode());
+
+        assert_eq!(watcher.observe(Some(true)), None);
+    }
+
+    #[test]
+    fn test_flicker_and_failed_queries_are_ignored() {
+        let mut watcher = BackgroundWatcher::new(false);
+
+        assert_eq!(watcher.observe(Some(true)), None);
+        assert_eq!(watcher.observe(Some(false)), None);
+        assert_eq!(watcher.observe(Some(true)), None);
+        assert_eq!(watcher.observe(None), None);
+        assert!(!watcher.is_light_mode());
+    }
+}
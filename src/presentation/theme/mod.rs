@@ -1,4 +1,5 @@
 pub mod adapter;
+pub mod detection;
 pub mod palette;
 pub mod service;
 
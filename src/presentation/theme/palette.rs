@@ -2,6 +2,7 @@ use super::adapter::ColorConverter;
 use ratatui::style::{Color, Modifier, Style};
 
 pub trait Palette {
+    fn is_light(&self) -> bool;
     fn accent(&self, base: Color) -> Color;
     fn mention_style(&self, base: Color) -> Style;
     fn selection_style(&self, base: Color) -> Style;
@@ -25,6 +26,9 @@ pub trait Palette {
 pub struct DarkPalette;
 
 impl Palette for DarkPalette {
+    fn is_light(&self) -> bool {
+        false
+    }
     fn accent(&self, base: Color) -> Color {
         base
     }
@@ -108,6 +112,9 @@ impl Palette for DarkPalette {
 pub struct LightPalette;
 
 impl Palette for LightPalette {
+    fn is_light(&self) -> bool {
+        true
+    }
     fn accent(&self, base: Color) -> Color {
         base
     }
@@ -1,4 +1,5 @@
 use super::palette::{DarkPalette, LightPalette, Palette};
+use crate::infrastructure::config::app_config::AuthorColorMode;
 use ratatui::style::{Color, Style};
 use std::str::FromStr;
 
@@ -21,6 +22,9 @@ pub struct Theme {
     pub tab_style: Style,
     pub tab_selected_style: Style,
     pub statusbar_style: Style,
+    pub mention_color: Option<Color>,
+    pub is_light: bool,
+    pub author_color_mode: AuthorColorMode,
 }
 
 impl Default for Theme {
@@ -70,9 +74,29 @@ impl Theme {
             info_style: palette.info_style(),
             border_style: palette.border_style(),
             timestamp_style: palette.timestamp_style(),
+            mention_color,
+            is_light: palette.is_light(),
+            author_color_mode: AuthorColorMode::default(),
         }
     }
 
+    #[must_use]
+    pub const fn with_author_color_mode(mut self, mode: AuthorColorMode) -> Self {
+        self.author_color_mode = mode;
+        self
+    }
+
+    /// Rebuilds this theme with the same colors on the light or dark palette.
+    #[must_use]
+    pub fn with_light_mode(self, is_light_mode: bool) -> Self {
+        let theme = if is_light_mode {
+            Self::from_palette(&LightPalette, self.accent, self.mention_color)
+        } else {
+            Self::from_palette(&DarkPalette, self.accent, self.mention_color)
+        };
+        theme.with_author_color_mode(self.author_color_mode)
+    }
+
     #[must_use]
     pub fn from_color(accent: Color, mention_color: Option<Color>) -> Self {
         Self::from_palette(&DarkPalette, accent, mention_color)
@@ -131,4 +155,19 @@ mod tests {
         assert_eq!(parse_color("Orange"), Color::Indexed(208));
         assert_eq!(parse_color("Invalid"), Color::Yellow);
     }
+
+    #[test]
+    fn test_with_light_mode_keeps_colors() {
+        let dark = Theme::new("#FF0000", Some("Green"), false);
+        let light = dark.with_light_mode(true);
+
+        assert!(light.is_light);
+        assert_eq!(light.accent, dark.accent);
+        assert_eq!(light.mention_color, Some(Color::Green));
+        assert_eq!(
+            light.base_style,
+            Theme::new("#FF0000", Some("Green"), true).base_style
+        );
+        assert!(!light.with_light_mode(false).is_light);
+    }
 }
@@ -15,38 +15,58 @@ use tracing::{debug, error, info, warn};
 use zeroize::Zeroize;
 
 use crate::application::dto::{LoginRequest, TokenSource};
+use crate::application::services::markdown_parser::mention_types;
 use crate::application::services::notification_manager::NotificationManager;
 use crate::application::services::notification_service::NotificationService;
+use crate::application::services::outbound_queue::OutboundQueue;
+use crate::application::services::typing_throttle::TypingThrottle;
 use crate::application::use_cases::{LoginUseCase, ResolveTokenUseCase};
 use crate::domain::ConnectionStatus;
 use crate::domain::entities::{
-    AuthToken, Channel, ChannelId, GuildFolder, GuildId, MessageId, RelationshipState, UserCache,
+    AuthToken, Channel, ChannelId, Guild, GuildFolder, GuildId, MessageId, RelationshipState,
+    UserCache, VoiceState,
 };
 use crate::domain::errors::AuthError;
 use crate::domain::ports::{
-    AuthPort, DiscordDataPort, EditMessageRequest, SendMessageRequest, TokenStoragePort,
+    AllowedMentions, AuthPort, DiscordDataPort, DmKind, EditMessageRequest, OutgoingAttachment,
+    SendMessageRequest, TokenStoragePort,
+};
+use crate::infrastructure::config::app_config::{
+    EditorConfig, FileExplorerConfig, KeymapPreset, MessageDensity, QuickSwitcherSection,
+    QuickSwitcherSortMode, StartupFocus, TimestampPosition,
 };
-use crate::infrastructure::config::app_config::QuickSwitcherSortMode;
 use crate::infrastructure::discord::{
     DispatchEvent, GatewayClient, GatewayClientConfig, GatewayCommand, GatewayEventKind,
-    GatewayIntents, TypingIndicatorManager, identity::ClientIdentity,
+    GatewayIntents, PresenceStatus, TypingIndicatorManager,
+    identity::ClientIdentity,
+    remote_auth::{RemoteAuthClient, RemoteAuthEvent},
 };
 #[cfg(feature = "image")]
-use crate::infrastructure::image::{ImageLoadedEvent, ImageLoader};
+use crate::infrastructure::image::{
+    CdnImageOptions, DiskImageCache, ImageLoadedEvent, ImageLoader, ImageLoaderConfig,
+};
 use crate::infrastructure::notifications::DesktopNotificationService;
 use crate::infrastructure::{ClipboardService, StateStore};
 use crate::presentation::events::EventResult;
 use crate::presentation::services::markdown_renderer::MarkdownRenderer;
 use crate::presentation::theme::Theme;
+use crate::presentation::theme::detection::{BackgroundWatcher, detect_light_mode};
 use crate::presentation::ui::{
-    ChatKeyResult, ChatScreen, ChatScreenState, LoginAction, LoginScreen, SplashScreen,
+    ChatKeyResult, ChatScreen, ChatScreenState, LoginAction, LoginScreen, QrLoginStatus,
+    SplashScreen,
     backend::{Action, Backend, BackendCommand},
 };
+use crate::presentation::widgets::SendState;
 
 const TYPING_CLEANUP_INTERVAL: Duration = Duration::from_secs(2);
 const TYPING_THROTTLE_DURATION: Duration = Duration::from_secs(8);
 const ANIMATION_TICK_RATE: Duration = Duration::from_millis(33);
 const IMAGE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a first Esc waits for the second when quitting needs two.
+const QUIT_ESCAPE_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Input must be quiet this long before the terminal background is
+/// re-queried, since keys typed during the query are lost.
+const BACKGROUND_QUERY_QUIET: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppState {
@@ -56,6 +76,37 @@ enum AppState {
     Exiting,
 }
 
+/// Guild, channel, recents, sort mode, expanded tree nodes and pinned
+/// channels to persist.
+type SavedState = (
+    Option<GuildId>,
+    Option<ChannelId>,
+    Vec<crate::domain::search::RecentItem>,
+    QuickSwitcherSortMode,
+    Vec<String>,
+    Vec<crate::domain::search::RecentItem>,
+);
+
+/// Requests for the task that writes the [`StateStore`].
+enum StateStoreCommand {
+    /// Saves after a short debounce, replacing any pending save.
+    Save(SavedState),
+    /// Drops any pending save and deletes what was saved.
+    Clear,
+}
+
+/// A message sent with a local echo that Discord has not confirmed yet.
+struct PendingSend {
+    request: SendMessageRequest,
+    /// The send errored and waits for a retry.
+    failed: bool,
+}
+
+/// The local echo a request was sent under, recovered from its nonce.
+fn local_echo_id(request: &SendMessageRequest) -> Option<MessageId> {
+    request.nonce.as_deref()?.parse().ok().map(MessageId)
+}
+
 enum CurrentScreen {
     Login(Box<LoginScreen>),
     Splash(SplashScreen),
@@ -72,14 +123,64 @@ pub struct AppConfig {
     pub image_preview: bool,
     pub timestamp_format: String,
     pub show_typing: bool,
+    pub enable_typing_events: bool,
+    pub enable_presence_updates: bool,
+    /// Inactivity after which our status is set to Idle, if enabled.
+    pub idle_after: Option<Duration>,
     pub internal_notifications: bool,
+    /// Window within which one channel's desktop notifications are merged.
+    pub notification_batch_window: Duration,
+    pub max_internal_notifications: usize,
     pub notification_duration: u64,
     pub enable_animations: bool,
-    pub editor: Option<String>,
+    /// Width requested for Discord CDN images.
+    pub cdn_max_dimension: u32,
+    /// Request WebP rather than PNG from the Discord CDN.
+    pub prefer_webp: bool,
+    /// Disk image cache limit in megabytes.
+    pub disk_cache_max_mb: u64,
+    /// Messages beyond the visible ones whose images are prefetched.
+    pub image_prefetch_distance: usize,
+    pub editor: EditorConfig,
+    pub file_explorer: FileExplorerConfig,
+    pub keymap: KeymapPreset,
+    pub sequence_timeout: Duration,
     pub keybindings: HashMap<String, KeyAction>,
     pub theme: Theme,
     pub hide_blocked_completely: bool,
     pub quick_switcher_order: QuickSwitcherSortMode,
+    pub quick_switcher_sections: Vec<QuickSwitcherSection>,
+    pub confirm_logout_with_drafts: bool,
+    pub accent_overrides: HashMap<u64, Theme>,
+    pub background_poll_interval: Option<Duration>,
+    pub flatten_single_channel_categories: bool,
+    pub eager_guild_count: usize,
+    pub collapse_message_lines: usize,
+    pub group_replies: bool,
+    pub double_esc_to_close: bool,
+    pub restore_focus_after_overlay: bool,
+    pub compact_guilds_tree: bool,
+    pub mark_read_on_focus: bool,
+    pub suppress_everyone_highlight: bool,
+    pub startup_focus: StartupFocus,
+    pub confirm_masked_links: bool,
+    pub wrap_indent: u16,
+    pub show_mention_count: bool,
+    pub confirm_everyone_send: bool,
+    pub group_start_timestamps_only: bool,
+    pub purge_message_limit: usize,
+    pub ellipsis: String,
+    pub show_embeds: bool,
+    pub timestamp_position: TimestampPosition,
+    pub require_double_escape_to_quit: bool,
+    pub show_splash: bool,
+    pub splash_min_duration_ms: u64,
+    pub max_recents: usize,
+    pub record_recents: bool,
+    pub message_density: MessageDensity,
+    pub large_paste_threshold: usize,
+    pub max_message_length: usize,
+    pub default_suppress_mentions: bool,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -98,13 +199,15 @@ pub struct App {
     action_rx: mpsc::UnboundedReceiver<Action>,
     typing_manager: TypingIndicatorManager,
     last_typing_cleanup: Instant,
-    last_typing_sent: Option<(ChannelId, Instant)>,
+    typing_throttle: TypingThrottle,
     markdown_service: Arc<MarkdownRenderer>,
     user_cache: UserCache,
     current_user_id: Option<String>,
     pending_chat_state: Option<Box<ChatScreenState>>,
     pending_read_states: Option<Vec<crate::domain::entities::ReadState>>,
     pending_guild_folders: Option<Vec<GuildFolder>>,
+    pending_voice_states: Option<Vec<VoiceState>>,
+    pending_guilds: Vec<Guild>,
     pending_channels: std::collections::HashMap<GuildId, Vec<Channel>>,
     pending_roles: std::collections::HashMap<GuildId, Vec<crate::domain::entities::Role>>,
     pending_members: std::collections::HashMap<GuildId, Vec<crate::domain::entities::Member>>,
@@ -126,27 +229,81 @@ pub struct App {
     image_preview: bool,
     timestamp_format: String,
     show_typing: bool,
+    enable_typing_events: bool,
+    enable_presence_updates: bool,
+    idle_after: Option<Duration>,
+    last_input: Instant,
+    /// Status picked in the user's settings, restored after auto-idle.
+    chosen_status: PresenceStatus,
+    /// Set while we have switched the status to Idle ourselves.
+    auto_idle: bool,
     internal_notifications: bool,
     enable_animations: bool,
-    editor: Option<String>,
+    cdn_max_dimension: u32,
+    prefer_webp: bool,
+    disk_cache_max_mb: u64,
+    image_prefetch_distance: usize,
+    /// Images last handed to the loader for prefetching.
+    #[cfg(feature = "image")]
+    last_image_prefetch: Vec<crate::domain::entities::ImageId>,
+    editor: EditorConfig,
+    file_explorer: FileExplorerConfig,
     command_registry: CommandRegistry,
     theme: Theme,
     identity: Arc<ClientIdentity>,
     state_store: StateStore,
-    #[allow(clippy::type_complexity)]
-    state_save_tx: mpsc::UnboundedSender<(
-        Option<GuildId>,
-        Option<ChannelId>,
-        Vec<crate::domain::search::RecentItem>,
-        QuickSwitcherSortMode,
-    )>,
+    state_save_tx: mpsc::UnboundedSender<StateStoreCommand>,
     clipboard_service: ClipboardService,
     notification_manager: NotificationManager,
     notification_service: NotificationService,
-    last_desktop_notification: Option<Instant>,
+    /// Selected row in the notification history panel, when it is open.
+    notification_history: Option<usize>,
     relationship_state: RelationshipState,
     hide_blocked_completely: bool,
     pub quick_switcher_order: QuickSwitcherSortMode,
+    quick_switcher_sections: Vec<QuickSwitcherSection>,
+    confirm_logout_with_drafts: bool,
+    accent_overrides: HashMap<u64, Theme>,
+    outbound_queue: OutboundQueue,
+    /// Sends awaiting confirmation, keyed by their local echo id, kept for retry.
+    pending_sends: HashMap<MessageId, PendingSend>,
+    next_local_id: u64,
+    /// How often to re-check the terminal background, if at all.
+    background_poll_interval: Option<Duration>,
+    last_background_check: Instant,
+    background_watcher: BackgroundWatcher,
+    flatten_single_channel_categories: bool,
+    eager_guild_count: usize,
+    collapse_message_lines: usize,
+    group_replies: bool,
+    double_esc_to_close: bool,
+    restore_focus_after_overlay: bool,
+    compact_guilds_tree: bool,
+    mark_read_on_focus: bool,
+    suppress_everyone_highlight: bool,
+    startup_focus: StartupFocus,
+    confirm_masked_links: bool,
+    wrap_indent: u16,
+    show_mention_count: bool,
+    confirm_everyone_send: bool,
+    group_start_timestamps_only: bool,
+    purge_message_limit: usize,
+    ellipsis: String,
+    show_embeds: bool,
+    timestamp_position: TimestampPosition,
+    require_double_escape_to_quit: bool,
+    /// When the first of two quitting Escs was pressed.
+    quit_escape_at: Option<Instant>,
+    /// Running QR code login, aborted when the user backs out.
+    remote_auth_task: Option<tokio::task::JoinHandle<()>>,
+    show_splash: bool,
+    splash_min_duration_ms: u64,
+    max_recents: usize,
+    record_recents: bool,
+    message_density: MessageDensity,
+    large_paste_threshold: usize,
+    max_message_length: usize,
+    default_suppress_mentions: bool,
 }
 
 impl App {
@@ -166,49 +323,53 @@ impl App {
         let resolve_token_use_case = ResolveTokenUseCase::new(storage_port);
         let (action_tx, action_rx) = mpsc::unbounded_channel();
         let (command_tx, command_rx) = mpsc::unbounded_channel();
-        let markdown_service = Arc::new(MarkdownRenderer::new());
+        let markdown_service = Arc::new(MarkdownRenderer::new().with_accent(config.theme.accent));
         let notification_port = Arc::new(DesktopNotificationService::new(
             config.enable_desktop_notifications,
         ));
-        let notification_service = NotificationService::new(notification_port);
+        let notification_service = NotificationService::new(notification_port)
+            .with_batch_window(config.notification_batch_window);
 
+        let rate_limit_tx = action_tx.clone();
+        discord_data.set_rate_limit_listener(Arc::new(move |wait| {
+            let _ = rate_limit_tx.send(Action::RateLimited(wait));
+        }));
         let backend = Backend::new(discord_data, command_rx, action_tx.clone());
         tokio::spawn(backend.run());
 
         let state_store = StateStore::new();
-        let (state_save_tx, mut state_save_rx) = mpsc::unbounded_channel::<(
-            Option<GuildId>,
-            Option<ChannelId>,
-            Vec<crate::domain::search::RecentItem>,
-            QuickSwitcherSortMode,
-        )>();
+        let (state_save_tx, mut state_save_rx) = mpsc::unbounded_channel::<StateStoreCommand>();
         let store = state_store.clone();
 
-        let mut command_registry = CommandRegistry::new();
+        let mut command_registry = CommandRegistry::with_preset(config.keymap);
+        command_registry.set_sequence_timeout(config.sequence_timeout);
         command_registry.apply_overrides(&config.keybindings);
 
         tokio::spawn(async move {
             const DEBOUNCE_DURATION: Duration = Duration::from_secs(1);
-            type PendingState = (
-                Option<GuildId>,
-                Option<ChannelId>,
-                Vec<crate::domain::search::RecentItem>,
-                QuickSwitcherSortMode,
-            );
-            let mut pending_state: Option<PendingState> = None;
+            let mut pending_state: Option<SavedState> = None;
             let mut timer = Box::pin(tokio::time::sleep(Duration::MAX));
 
             loop {
                 tokio::select! {
-                    Some(state) = state_save_rx.recv() => {
-                        pending_state = Some(state);
-                        timer = Box::pin(tokio::time::sleep(DEBOUNCE_DURATION));
-                    }
+                    Some(command) = state_save_rx.recv() => match command {
+                        StateStoreCommand::Save(state) => {
+                            pending_state = Some(state);
+                            timer = Box::pin(tokio::time::sleep(DEBOUNCE_DURATION));
+                        }
+                        StateStoreCommand::Clear => {
+                            pending_state = None;
+                            timer = Box::pin(tokio::time::sleep(Duration::MAX));
+                            if let Err(e) = store.clear_all().await {
+                                tracing::warn!("Failed to clear state: {e}");
+                            }
+                        }
+                    },
                     () = &mut timer, if pending_state.is_some() => {
-                        if let Some((guild_id, channel_id, recents, sort_mode)) = pending_state.take() {
+                        if let Some((guild_id, channel_id, recents, sort_mode, expanded, pinned)) = pending_state.take() {
                             let gid = guild_id.map(|g| g.as_u64().to_string());
                             let cid = channel_id.map(|c| c.as_u64().to_string());
-                            if let Err(e) = store.save(gid, cid, &recents, sort_mode).await {
+                            if let Err(e) = store.save(gid, cid, &recents, sort_mode, &expanded, &pinned).await {
                                 tracing::warn!("Failed to save state: {e}");
                             }
                         }
@@ -234,13 +395,15 @@ impl App {
             action_rx,
             typing_manager: TypingIndicatorManager::new(),
             last_typing_cleanup: Instant::now(),
-            last_typing_sent: None,
+            typing_throttle: TypingThrottle::new(TYPING_THROTTLE_DURATION),
             markdown_service,
             user_cache: UserCache::new(),
             current_user_id: None,
             pending_chat_state: None,
             pending_read_states: None,
             pending_guild_folders: None,
+            pending_voice_states: None,
+            pending_guilds: Vec::new(),
             pending_channels: std::collections::HashMap::new(),
             pending_roles: std::collections::HashMap::new(),
             pending_members: std::collections::HashMap::new(),
@@ -258,10 +421,23 @@ impl App {
             use_display_name: config.use_display_name,
             image_preview: config.image_preview,
             timestamp_format: config.timestamp_format,
-            show_typing: config.show_typing,
+            show_typing: config.show_typing && config.enable_typing_events,
+            enable_typing_events: config.enable_typing_events,
+            enable_presence_updates: config.enable_presence_updates,
+            idle_after: config.idle_after,
+            last_input: Instant::now(),
+            chosen_status: PresenceStatus::Online,
+            auto_idle: false,
             internal_notifications: config.internal_notifications,
             enable_animations: config.enable_animations,
+            cdn_max_dimension: config.cdn_max_dimension,
+            prefer_webp: config.prefer_webp,
+            disk_cache_max_mb: config.disk_cache_max_mb,
+            image_prefetch_distance: config.image_prefetch_distance,
+            #[cfg(feature = "image")]
+            last_image_prefetch: Vec::new(),
             editor: config.editor,
+            file_explorer: config.file_explorer,
             command_registry,
             theme: config.theme,
             identity,
@@ -270,12 +446,52 @@ impl App {
             clipboard_service: ClipboardService::new(),
             notification_manager: NotificationManager::new(Duration::from_secs(
                 config.notification_duration,
-            )),
+            ))
+            .with_max_queued(config.max_internal_notifications),
             notification_service,
-            last_desktop_notification: None,
+            notification_history: None,
             relationship_state: RelationshipState::new(),
             hide_blocked_completely: config.hide_blocked_completely,
             quick_switcher_order: config.quick_switcher_order,
+            quick_switcher_sections: config.quick_switcher_sections,
+            confirm_logout_with_drafts: config.confirm_logout_with_drafts,
+            default_suppress_mentions: config.default_suppress_mentions,
+            max_message_length: config.max_message_length,
+            large_paste_threshold: config.large_paste_threshold,
+            message_density: config.message_density,
+            record_recents: config.record_recents,
+            max_recents: config.max_recents,
+            splash_min_duration_ms: config.splash_min_duration_ms,
+            show_splash: config.show_splash,
+            require_double_escape_to_quit: config.require_double_escape_to_quit,
+            quit_escape_at: None,
+            remote_auth_task: None,
+            timestamp_position: config.timestamp_position,
+            show_embeds: config.show_embeds,
+            ellipsis: config.ellipsis,
+            purge_message_limit: config.purge_message_limit,
+            group_start_timestamps_only: config.group_start_timestamps_only,
+            confirm_everyone_send: config.confirm_everyone_send,
+            show_mention_count: config.show_mention_count,
+            wrap_indent: config.wrap_indent,
+            confirm_masked_links: config.confirm_masked_links,
+            startup_focus: config.startup_focus,
+            suppress_everyone_highlight: config.suppress_everyone_highlight,
+            mark_read_on_focus: config.mark_read_on_focus,
+            compact_guilds_tree: config.compact_guilds_tree,
+            restore_focus_after_overlay: config.restore_focus_after_overlay,
+            double_esc_to_close: config.double_esc_to_close,
+            group_replies: config.group_replies,
+            collapse_message_lines: config.collapse_message_lines,
+            eager_guild_count: config.eager_guild_count,
+            flatten_single_channel_categories: config.flatten_single_channel_categories,
+            background_watcher: BackgroundWatcher::new(config.theme.is_light),
+            accent_overrides: config.accent_overrides,
+            outbound_queue: OutboundQueue::new(),
+            pending_sends: HashMap::new(),
+            next_local_id: 0,
+            background_poll_interval: config.background_poll_interval,
+            last_background_check: Instant::now(),
         }
     }
 
@@ -307,12 +523,13 @@ impl App {
             Some(TokenSource::Keyring) => {
                 matches!(self.resolve_token_use_case.execute(None).await, Ok(Some(_)))
             }
-            Some(TokenSource::Environment | TokenSource::UserInput) => true,
+            Some(TokenSource::Environment | TokenSource::UserInput | TokenSource::QrCode) => true,
             None => false,
         }
     }
 
     #[cfg(feature = "image")]
+    #[allow(clippy::too_many_lines)]
     async fn run_event_loop(&mut self, terminal: &mut DefaultTerminal) -> color_eyre::Result<()> {
         let mut terminal_events = EventStream::new();
         let mut typing_cleanup_interval = interval(TYPING_CLEANUP_INTERVAL);
@@ -328,6 +545,7 @@ impl App {
                 None => futures_util::future::Either::Right(std::future::pending()),
             };
             let terminal_event = terminal_events.next();
+            let mut requery_background = false;
 
             let image_load_future = match &mut self.image_load_rx {
                 Some(rx) => futures_util::future::Either::Left(rx.recv()),
@@ -353,6 +571,8 @@ impl App {
                 }
 
                 _ = animation_interval.tick() => {
+                    self.notification_service.flush(Instant::now());
+                    self.expire_quit_escape(Instant::now());
                      if let CurrentScreen::Splash(splash) = &mut self.screen {
                         splash.tick(ANIMATION_TICK_RATE);
 
@@ -369,18 +589,21 @@ impl App {
                         } else if !splash.state.intro_finished || (splash.state.data_ready && !splash.state.animation_complete) {
                              self.should_render = true;
                         }
-                    } else if let CurrentScreen::Chat(state) = &mut self.screen {
-                        state.tick(ANIMATION_TICK_RATE);
-                        if !state.has_entered() {
-                            self.should_render = true;
-                        }
+                    } else if let CurrentScreen::Chat(state) = &mut self.screen
+                        && state.tick(ANIMATION_TICK_RATE)
+                    {
+                        self.should_render = true;
                     }
+                    self.send_due_acks();
 
                     if self.last_image_check.elapsed() > IMAGE_CHECK_INTERVAL {
                         self.trigger_image_loads();
                         self.last_image_check = Instant::now();
                     }
 
+                    requery_background = self.background_check_due();
+                    self.poll_idle_presence();
+
                     if self.notification_manager.has_notifications() {
                         self.should_render = true;
                     }
@@ -419,6 +642,11 @@ impl App {
                 }
             }
 
+            if requery_background {
+                terminal_events = self.requery_terminal_background(terminal_events).await;
+                self.should_render = true;
+            }
+
             if self.should_render {
                 terminal.draw(|frame| self.render(frame))?;
             }
@@ -443,6 +671,7 @@ impl App {
                 None => futures_util::future::Either::Right(std::future::pending()),
             };
             let terminal_event = terminal_events.next();
+            let mut requery_background = false;
 
             tokio::select! {
                 biased;
@@ -458,6 +687,8 @@ impl App {
                 }
 
                 _ = animation_interval.tick() => {
+                    self.notification_service.flush(Instant::now());
+                    self.expire_quit_escape(Instant::now());
                      if let CurrentScreen::Splash(splash) = &mut self.screen {
                         splash.tick(ANIMATION_TICK_RATE);
 
@@ -474,12 +705,15 @@ impl App {
                         } else if !splash.state.intro_finished || (splash.state.data_ready && !splash.state.animation_complete) {
                              self.should_render = true;
                         }
-                    } else if let CurrentScreen::Chat(state) = &mut self.screen {
-                        state.tick(ANIMATION_TICK_RATE);
-                        if !state.has_entered() {
-                            self.should_render = true;
-                        }
+                    } else if let CurrentScreen::Chat(state) = &mut self.screen
+                        && state.tick(ANIMATION_TICK_RATE)
+                    {
+                        self.should_render = true;
                     }
+                    self.send_due_acks();
+
+                    requery_background = self.background_check_due();
+                    self.poll_idle_presence();
 
                     if self.notification_manager.has_notifications() {
                         self.should_render = true;
@@ -519,6 +753,11 @@ impl App {
                 }
             }
 
+            if requery_background {
+                terminal_events = self.requery_terminal_background(terminal_events).await;
+                self.should_render = true;
+            }
+
             if self.should_render {
                 terminal.draw(|frame| self.render(frame))?;
             }
@@ -527,14 +766,125 @@ impl App {
         Ok(())
     }
 
+    /// Whether the configured interval for re-checking the terminal
+    /// background has elapsed and input has been quiet long enough. A check
+    /// held back by typing is made once the typing stops.
+    fn background_check_due(&mut self) -> bool {
+        let Some(period) = self.background_poll_interval else {
+            return false;
+        };
+        if self.last_background_check.elapsed() < period
+            || self.last_input.elapsed() < BACKGROUND_QUERY_QUIET
+        {
+            return false;
+        }
+        self.last_background_check = Instant::now();
+        true
+    }
+
+    /// Re-queries the terminal background with the input reader paused.
+    ///
+    /// The query is not free: termbg reads the OSC 11 reply through the
+    /// same crossterm input as the app, consuming any keys typed while it
+    /// waits, and waits up to its timeout for a reply. The crossterm stream
+    /// is dropped for the query so the two do not race, the wait happens off
+    /// the runtime threads, and [`Self::background_check_due`] only allows a
+    /// query once input has gone quiet so typing is rarely lost.
+    async fn requery_terminal_background(&mut self, terminal_events: EventStream) -> EventStream {
+        drop(terminal_events);
+        let is_light_mode = tokio::task::spawn_blocking(detect_light_mode)
+            .await
+            .ok()
+            .flatten();
+        self.handle_action(Action::TerminalBackgroundDetected(is_light_mode));
+        EventStream::new()
+    }
+
+    fn apply_light_mode(&mut self, is_light_mode: bool) {
+        info!(
+            is_light_mode,
+            "Terminal background changed, switching theme"
+        );
+        self.theme = self.theme.with_light_mode(is_light_mode);
+        for theme in self.accent_overrides.values_mut() {
+            *theme = theme.with_light_mode(is_light_mode);
+        }
+
+        match &mut self.screen {
+            CurrentScreen::Login(screen) => screen.set_theme(self.theme),
+            CurrentScreen::Chat(state) => state.set_light_mode(is_light_mode),
+            CurrentScreen::Splash(_) => {}
+        }
+        if let Some(state) = &mut self.pending_chat_state {
+            state.set_light_mode(is_light_mode);
+        }
+    }
+
     fn handle_terminal_event(&mut self, event: &Event) -> EventResult {
+        if matches!(event, Event::Key(_) | Event::Paste(_) | Event::Mouse(_)) {
+            self.note_user_activity();
+        }
         match event {
             Event::Key(key) => self.handle_key(*key),
             Event::Paste(text) => self.handle_paste(text),
+            Event::FocusGained => self.handle_focus_gained(),
             _ => EventResult::Continue,
         }
     }
 
+    /// Records input and restores the chosen status if we went idle.
+    fn note_user_activity(&mut self) {
+        self.last_input = Instant::now();
+        if self.auto_idle {
+            self.auto_idle = false;
+            self.send_presence(self.chosen_status);
+        }
+    }
+
+    /// Sends the read acks the chat screen held back while messages arrived.
+    fn send_due_acks(&mut self) {
+        let CurrentScreen::Chat(state) = &mut self.screen else {
+            return;
+        };
+        for ack in state.take_due_acks() {
+            let _ = self.process_chat_key_result(ack);
+        }
+    }
+
+    /// Switches to Idle once input has been quiet for `idle_after`.
+    ///
+    /// Only an Online status is touched, so a manual DND or Invisible stays.
+    fn poll_idle_presence(&mut self) {
+        let Some(idle_after) = self.idle_after else {
+            return;
+        };
+        if self.auto_idle
+            || !self.gateway_ready
+            || self.chosen_status != PresenceStatus::Online
+            || self.last_input.elapsed() < idle_after
+        {
+            return;
+        }
+        debug!("No input for {idle_after:?}, going idle");
+        self.auto_idle = true;
+        self.send_presence(PresenceStatus::Idle);
+    }
+
+    fn send_presence(&self, status: PresenceStatus) {
+        if let Some(ref gateway_client) = self.gateway_client {
+            gateway_client.send_command(GatewayCommand::UpdatePresence { status });
+        }
+    }
+
+    fn handle_focus_gained(&mut self) -> EventResult {
+        if let CurrentScreen::Chat(state) = &mut self.screen
+            && let Some(ack) = state.on_focus_gained()
+        {
+            return self.process_chat_key_result(ack);
+        }
+        EventResult::Continue
+    }
+
     fn handle_paste(&mut self, text: &str) -> EventResult {
         let clean_text = text.trim_end_matches(['\n', '\r']);
 
@@ -544,7 +894,7 @@ impl App {
                 EventResult::Continue
             }
             CurrentScreen::Chat(state) => {
-                state.insert_text(clean_text);
+                state.paste_text(clean_text);
                 EventResult::Continue
             }
             CurrentScreen::Splash(_) => EventResult::Continue,
@@ -552,14 +902,33 @@ impl App {
     }
 
     fn save_state(&self, guild_id: Option<GuildId>, channel_id: Option<ChannelId>) {
-        let (recents, sort_mode) = if let CurrentScreen::Chat(state) = &self.screen {
-            (state.recents.clone(), state.quick_switcher_sort_mode())
-        } else {
-            (Vec::new(), QuickSwitcherSortMode::default())
-        };
-        let _ = self
-            .state_save_tx
-            .send((guild_id, channel_id, recents, sort_mode));
+        let (recents, sort_mode, expanded, pinned) =
+            if let CurrentScreen::Chat(state) = &self.screen {
+                (
+                    state.recents.clone(),
+                    state.quick_switcher_sort_mode(),
+                    state.tree_expansion(),
+                    state.pinned.clone(),
+                )
+            } else {
+                (
+                    Vec::new(),
+                    QuickSwitcherSortMode::default(),
+                    Vec::new(),
+                    Vec::new(),
+                )
+            };
+        let _ = self.state_save_tx.send(StateStoreCommand::Save((
+            guild_id, channel_id, recents, sort_mode, expanded, pinned,
+        )));
+    }
+
+    /// Saves state for the currently open guild and channel.
+    fn save_current_state(&self) {
+        if let CurrentScreen::Chat(state) = &self.screen {
+            let channel_id = state.selected_channel().map(Channel::id);
+            self.save_state(state.active_guild_id(), channel_id);
+        }
     }
 
     async fn attempt_auto_login(&mut self, token: String, source: TokenSource) {
@@ -596,6 +965,59 @@ impl App {
         self.notification_manager.info("Info", message);
     }
 
+    fn toggle_notification_history(&mut self) {
+        if self.notification_history.take().is_some() {
+            self.notification_manager.mark_seen();
+        } else {
+            self.notification_history = Some(0);
+        }
+    }
+
+    fn handle_notification_history_key(&mut self, key: KeyEvent) -> EventResult {
+        let Some(selected) = self.notification_history else {
+            return EventResult::Continue;
+        };
+        let count = self.notification_manager.history().len();
+        match (key.code, self.command_registry.find_action(key)) {
+            (KeyCode::Esc | KeyCode::Char('q'), _)
+            | (_, Some(KeyAction::ToggleNotificationHistory)) => {
+                self.toggle_notification_history();
+            }
+            (KeyCode::Char('c'), _) => {
+                self.notification_manager.clear_history();
+                self.notification_history = Some(0);
+            }
+            (KeyCode::Enter, _) => {
+                let source = self
+                    .notification_manager
+                    .history()
+                    .iter()
+                    .rev()
+                    .nth(selected)
+                    .and_then(|notification| notification.source.clone());
+                let Some(source) = source else {
+                    return EventResult::Continue;
+                };
+                self.toggle_notification_history();
+                let opened = match &mut self.screen {
+                    CurrentScreen::Chat(state) => state.open_channel(source.channel_id),
+                    _ => None,
+                };
+                if let Some(result) = opened {
+                    return self.process_chat_key_result(result);
+                }
+            }
+            (_, Some(KeyAction::NavigateDown)) => {
+                self.notification_history = Some((selected + 1).min(count.saturating_sub(1)));
+            }
+            (_, Some(KeyAction::NavigateUp)) => {
+                self.notification_history = Some(selected.saturating_sub(1));
+            }
+            _ => {}
+        }
+        EventResult::Continue
+    }
+
     fn render(&mut self, frame: &mut Frame) {
         match &mut self.screen {
             CurrentScreen::Login(screen) => {
@@ -614,6 +1036,19 @@ impl App {
             }
         }
 
+        if let Some(selected) = self.notification_history {
+            use crate::presentation::ui::notification_history::NotificationHistory;
+            frame.render_widget(
+                NotificationHistory::new(
+                    self.notification_manager.history(),
+                    selected,
+                    self.notification_manager.unseen_count(),
+                    &self.theme,
+                ),
+                frame.area(),
+            );
+        }
+
         self.notification_manager.tick();
         if let Some(notification) = self.notification_manager.current_notification() {
             use crate::presentation::ui::notification_popup::NotificationPopup;
@@ -624,24 +1059,70 @@ impl App {
         }
     }
 
+    /// Decides whether a top-level Esc quits. With
+    /// `require_double_escape_to_quit` only a second Esc within
+    /// [`QUIT_ESCAPE_TIMEOUT`] does; the first shows a reminder.
+    fn confirm_quit_escape(&mut self, now: Instant) -> bool {
+        if !self.require_double_escape_to_quit {
+            return true;
+        }
+        if self
+            .quit_escape_at
+            .take()
+            .is_some_and(|at| now.duration_since(at) < QUIT_ESCAPE_TIMEOUT)
+        {
+            return true;
+        }
+        self.quit_escape_at = Some(now);
+        self.set_quit_pending(true);
+        false
+    }
+
+    /// Drops a first quitting Esc whose timeout has run out.
+    fn expire_quit_escape(&mut self, now: Instant) {
+        if self
+            .quit_escape_at
+            .is_some_and(|at| now.duration_since(at) >= QUIT_ESCAPE_TIMEOUT)
+        {
+            self.quit_escape_at = None;
+            self.set_quit_pending(false);
+        }
+    }
+
+    fn set_quit_pending(&mut self, pending: bool) {
+        if let CurrentScreen::Login(screen) = &mut self.screen {
+            screen.set_quit_pending(pending);
+            self.should_render = true;
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn handle_key(&mut self, key: KeyEvent) -> EventResult {
         if self.state == AppState::Login {
-            let is_force_quit = matches!(
+            if matches!(
                 key,
                 KeyEvent {
-                    code: KeyCode::Esc,
-                    ..
-                } | KeyEvent {
                     code: KeyCode::Char('c'),
                     modifiers: crossterm::event::KeyModifiers::CONTROL,
                     ..
                 }
-            );
-
-            if is_force_quit {
+            ) {
                 return EventResult::Exit;
             }
+            if key.code == KeyCode::Esc && self.remote_auth_task.is_some() {
+                self.cancel_qr_login();
+                return EventResult::Continue;
+            }
+            if key.code == KeyCode::Esc {
+                return if self.confirm_quit_escape(Instant::now()) {
+                    EventResult::Exit
+                } else {
+                    EventResult::Continue
+                };
+            }
+            if self.quit_escape_at.take().is_some() {
+                self.set_quit_pending(false);
+            }
         } else if let KeyEvent {
             code: KeyCode::Char('c'),
             modifiers: crossterm::event::KeyModifiers::CONTROL,
@@ -651,20 +1132,31 @@ impl App {
             return EventResult::Exit;
         }
 
-        let result = match &mut self.screen {
+        if self.notification_history.is_some() {
+            return self.handle_notification_history_key(key);
+        }
+
+        let (result, expansion_changed) = match &mut self.screen {
             CurrentScreen::Login(screen) => {
                 match screen.handle_key(key) {
                     LoginAction::Submit => self.handle_login_submit(),
                     LoginAction::DeleteToken => self.handle_delete_token(),
                     LoginAction::Paste => self.handle_login_paste(),
+                    LoginAction::QrLogin => self.handle_qr_login(),
                     LoginAction::None => {}
                 }
                 return EventResult::Continue;
             }
             CurrentScreen::Splash(_) => return EventResult::Continue,
-            CurrentScreen::Chat(state) => state.handle_key(key),
+            CurrentScreen::Chat(state) => {
+                let result = state.handle_key(key);
+                (result, state.take_tree_expansion_changed())
+            }
         };
 
+        if expansion_changed {
+            self.save_current_state();
+        }
         self.process_chat_key_result(result)
     }
 
@@ -677,6 +1169,7 @@ impl App {
             }
             ChatKeyResult::SecureLogout => {
                 self.handle_delete_token();
+                self.clear_local_data();
                 self.transition_to_login();
             }
             ChatKeyResult::CopyToClipboard(text) => {
@@ -684,6 +1177,10 @@ impl App {
                 self.clipboard_service.set_text(text);
                 self.show_notification("Copied to clipboard".to_string());
             }
+            ChatKeyResult::CopyCodeBlock(code) => {
+                self.clipboard_service.set_text(code);
+                self.show_notification("Copied code block".to_string());
+            }
             ChatKeyResult::CopyImageToClipboard(image_id) => {
                 #[cfg(feature = "image")]
                 {
@@ -756,6 +1253,16 @@ impl App {
                 debug!(channel_id = %channel_id, recipient = %recipient_name, "Loading DM messages");
                 self.load_channel_messages(channel_id);
             }
+            ChatKeyResult::BackfillChannel { channel_id, after } => {
+                if let Some(ref token) = self.current_token {
+                    debug!(channel_id = %channel_id, after = %after, "Backfilling after reconnect");
+                    let _ = self.command_tx.send(BackendCommand::BackfillMessages {
+                        channel_id,
+                        after,
+                        token: token.clone(),
+                    });
+                }
+            }
             ChatKeyResult::LoadHistory {
                 channel_id,
                 before_message_id,
@@ -783,6 +1290,14 @@ impl App {
                 debug!(message_id = %message_id, "Delete message requested");
                 self.handle_delete_message(message_id);
             }
+            ChatKeyResult::PurgeMessages(message_ids) => {
+                debug!(count = message_ids.len(), "Purge own messages requested");
+                self.handle_purge_messages(message_ids);
+            }
+            ChatKeyResult::PublishMessage(message_id) => {
+                debug!(message_id = %message_id, "Publish message requested");
+                self.handle_publish_message(message_id);
+            }
             ChatKeyResult::OpenAttachments(message_id) => {
                 debug!(message_id = %message_id, "Open attachments requested");
                 #[cfg(feature = "image")]
@@ -837,16 +1352,29 @@ impl App {
             }
             ChatKeyResult::JumpToMessage(message_id) => {
                 debug!(message_id = %message_id, "Jump to message requested");
-                if let CurrentScreen::Chat(state) = &mut self.screen {
-                    state.jump_to_message(message_id);
+                if let CurrentScreen::Chat(state) = &mut self.screen
+                    && let Some(fetch) = state.scroll_to_message_id(message_id)
+                {
+                    return self.process_chat_key_result(fetch);
                 }
             }
+            ChatKeyResult::RetrySend(local_id) => {
+                self.retry_send(local_id);
+            }
             ChatKeyResult::SendMessage {
                 content,
                 reply_to,
+                mention_reply,
+                mass_mention_confirmed,
                 attachments,
             } => {
-                self.handle_send_message(content, reply_to, attachments);
+                self.handle_send_message(
+                    content,
+                    reply_to,
+                    mention_reply,
+                    mass_mention_confirmed,
+                    attachments,
+                );
             }
             ChatKeyResult::StartTyping => {
                 self.handle_start_typing();
@@ -865,24 +1393,9 @@ impl App {
                 let tx = self.action_tx.clone();
 
                 tokio::task::spawn_blocking(move || {
-                    #[cfg(feature = "image")]
-                    {
-                        if let Some((data, mime_type)) = clipboard.get_binary() {
-                            if mime_type.starts_with("image/") {
-                                let temp_dir = std::env::temp_dir();
-                                let filename = format!("paste_{}.png", uuid::Uuid::new_v4());
-                                let path = temp_dir.join(filename);
-
-                                // Try to load image and save it
-                                if let Ok(img) = image::load_from_memory(&data) {
-                                    let rgba = img.to_rgba8();
-                                    if rgba.save(&path).is_ok() {
-                                        let _ = tx.send(Action::PasteImageLoaded(path));
-                                        return;
-                                    }
-                                }
-                            }
-                        }
+                    if let Some(path) = clipboard.save_image() {
+                        let _ = tx.send(Action::PasteImageLoaded(path));
+                        return;
                     }
 
                     if let Some(text) = clipboard.get_text() {
@@ -891,6 +1404,12 @@ impl App {
                 });
             }
             ChatKeyResult::ToggleHelp | ChatKeyResult::Consumed | ChatKeyResult::Ignored => {}
+            ChatKeyResult::ToggleNotificationHistory => self.toggle_notification_history(),
+            ChatKeyResult::PinsChanged => self.save_current_state(),
+            ChatKeyResult::RecentsCleared => {
+                self.save_current_state();
+                self.show_notification("Cleared recents".to_string());
+            }
             ChatKeyResult::ToggleDisplayName => {
                 self.use_display_name = !self.use_display_name;
                 info!(
@@ -915,6 +1434,38 @@ impl App {
                 };
                 self.show_notification(format!("Display names {status}"));
             }
+            ChatKeyResult::PasteAsFile(text) => match Self::write_paste_file(&text) {
+                Ok(path) => {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    if let CurrentScreen::Chat(ref mut state) = self.screen {
+                        state.add_attachment(path);
+                    }
+                    self.show_notification(format!("Paste attached as file: {name}"));
+                }
+                Err(e) => {
+                    warn!(error = %e, "Failed to save paste as file");
+                    self.show_notification(format!("Could not save paste as file: {e}"));
+                }
+            },
+            ChatKeyResult::ToggleImagePreview => {
+                self.image_preview = !self.image_preview;
+                info!(enabled = self.image_preview, "Toggled image previews");
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.set_image_preview(self.image_preview);
+                } else if let Some(ref mut state) = self.pending_chat_state {
+                    state.set_image_preview(self.image_preview);
+                }
+
+                let status = if self.image_preview {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                self.show_notification(format!("Image previews {status}"));
+            }
             ChatKeyResult::JumpToChannel(channel_id) => {
                 debug!(channel_id = %channel_id, "Jump to channel requested");
                 self.save_state(None, Some(channel_id));
@@ -943,6 +1494,47 @@ impl App {
                     }
                 }
             }
+            ChatKeyResult::SetNickname { guild_id, nick } => {
+                if let Some(ref token) = self.current_token {
+                    let _ = self.command_tx.send(BackendCommand::SetNickname {
+                        token: token.clone(),
+                        guild_id,
+                        nick,
+                    });
+                }
+            }
+            ChatKeyResult::CreateGroupDm { user_ids } => {
+                if let Some(ref token) = self.current_token {
+                    let _ = self.command_tx.send(BackendCommand::CreateGroupDm {
+                        token: token.clone(),
+                        user_ids,
+                    });
+                }
+            }
+            ChatKeyResult::AddGroupRecipients {
+                channel_id,
+                user_ids,
+            } => {
+                if let Some(ref token) = self.current_token {
+                    let _ = self.command_tx.send(BackendCommand::AddGroupRecipients {
+                        token: token.clone(),
+                        channel_id,
+                        user_ids,
+                    });
+                }
+            }
+            ChatKeyResult::AcknowledgeMessage {
+                channel_id,
+                message_id,
+            } => {
+                if let Some(ref token) = self.current_token {
+                    let _ = self.command_tx.send(BackendCommand::AcknowledgeMessage {
+                        channel_id,
+                        message_id,
+                        token: token.clone(),
+                    });
+                }
+            }
             ChatKeyResult::ShowNotification(message) => {
                 self.show_notification(message);
             }
@@ -961,11 +1553,16 @@ impl App {
             return;
         };
 
+        self.spawn_login(token, persist, TokenSource::UserInput);
+    }
+
+    /// Validates `token` and, if `persist`, saves it to the token storage.
+    fn spawn_login(&mut self, token: String, persist: bool, source: TokenSource) {
         if let CurrentScreen::Login(ref mut screen) = self.screen {
             screen.set_validating();
         }
 
-        let mut request = LoginRequest::new(token.clone(), TokenSource::UserInput);
+        let mut request = LoginRequest::new(token.clone(), source);
         if !persist {
             request = request.without_persistence();
         }
@@ -979,7 +1576,7 @@ impl App {
                     let _ = tx.send(Action::LoginSuccess {
                         user: response.user,
                         token,
-                        source: TokenSource::UserInput,
+                        source,
                     });
                 }
                 Err(e) => {
@@ -989,6 +1586,82 @@ impl App {
         });
     }
 
+    fn handle_qr_login(&mut self) {
+        if let CurrentScreen::Login(screen) = &mut self.screen {
+            screen.start_qr();
+        }
+        self.cancel_qr_task();
+
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let client = RemoteAuthClient::new(self.identity.clone());
+        self.remote_auth_task = Some(tokio::spawn(async move {
+            client.run(events_tx).await;
+        }));
+
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events_rx.recv().await {
+                if tx.send(Action::RemoteAuth(event)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    fn cancel_qr_task(&mut self) {
+        if let Some(task) = self.remote_auth_task.take() {
+            task.abort();
+        }
+    }
+
+    fn cancel_qr_login(&mut self) {
+        self.cancel_qr_task();
+        if let CurrentScreen::Login(screen) = &mut self.screen {
+            screen.cancel_qr();
+        }
+    }
+
+    fn handle_remote_auth_event(&mut self, event: RemoteAuthEvent) {
+        let CurrentScreen::Login(screen) = &mut self.screen else {
+            return;
+        };
+        if !screen.is_qr_active() {
+            return;
+        }
+        match event {
+            RemoteAuthEvent::Code(url) => screen.set_qr_status(QrLoginStatus::Code(url)),
+            RemoteAuthEvent::Scanned { username } => {
+                screen.set_qr_status(QrLoginStatus::Scanned(username));
+            }
+            RemoteAuthEvent::Token(token) => {
+                let persist = screen.should_persist();
+                screen.cancel_qr();
+                self.remote_auth_task = None;
+                self.spawn_login(token, persist, TokenSource::QrCode);
+            }
+            RemoteAuthEvent::Cancelled => {
+                screen.cancel_qr();
+                screen.set_error("QR code login was declined");
+                self.remote_auth_task = None;
+            }
+            RemoteAuthEvent::Failed(message) => {
+                screen.cancel_qr();
+                screen.set_error(format!("QR code login failed: {message}"));
+                self.remote_auth_task = None;
+            }
+        }
+    }
+
+    /// Writes pasted text to a `.txt` file in the temp dir so it can be
+    /// attached instead of sent inline.
+    fn write_paste_file(text: &str) -> std::io::Result<std::path::PathBuf> {
+        let dir = crate::infrastructure::temp_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("paste_{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, text)?;
+        Ok(path)
+    }
+
     fn handle_login_paste(&mut self) {
         let clipboard = self.clipboard_service.clone();
         let tx = self.action_tx.clone();
@@ -1020,15 +1693,28 @@ impl App {
 
     fn start_app_loading(&mut self, user: crate::domain::entities::User) {
         self.state = AppState::Initializing;
-        self.screen = CurrentScreen::Splash(SplashScreen::new());
+        self.screen = CurrentScreen::Splash(
+            SplashScreen::new()
+                .with_visible(self.show_splash)
+                .with_min_duration(Duration::from_millis(self.splash_min_duration_ms)),
+        );
         self.gateway_ready = false;
 
         #[cfg(feature = "image")]
         {
             let (img_tx, img_rx) = mpsc::unbounded_channel();
             let action_tx = self.action_tx.clone();
+            let config = ImageLoaderConfig {
+                disk_cache_size: self.disk_cache_max_mb.saturating_mul(1024 * 1024),
+                cdn: CdnImageOptions {
+                    max_dimension: self.cdn_max_dimension,
+                    prefer_webp: self.prefer_webp,
+                    animate: self.enable_animations,
+                },
+                ..ImageLoaderConfig::default()
+            };
             tokio::spawn(async move {
-                match ImageLoader::with_defaults(img_tx).await {
+                match ImageLoader::with_config(config, img_tx).await {
                     Ok(loader) => {
                         let _ = action_tx.send(Action::ImageLoaderReady(Arc::new(loader)));
                     }
@@ -1072,17 +1758,51 @@ impl App {
                     .map(ChannelId),
                 recents: state.recents,
                 sort_mode: state.quick_switcher_order,
+                expanded_nodes: state.expanded_nodes,
+                pinned: state.pinned,
             });
         });
     }
 
     fn set_connection_status(&mut self, status: ConnectionStatus) {
+        let was_connected = self.connection_status == ConnectionStatus::Connected;
         self.connection_status = status;
-        if let CurrentScreen::Chat(ref mut state) = self.screen {
-            state.set_connection_status(status);
-        }
+        let results = if let CurrentScreen::Chat(ref mut state) = self.screen {
+            state.set_connection_status(status)
+        } else {
+            Vec::new()
+        };
         if let Some(ref mut state) = self.pending_chat_state {
-            state.set_connection_status(status);
+            let _ = state.set_connection_status(status);
+        }
+        if status == ConnectionStatus::Connected && !was_connected {
+            self.flush_outbound_queue();
+        }
+        for result in results {
+            self.process_chat_key_result(result);
+        }
+    }
+
+    fn flush_outbound_queue(&mut self) {
+        let Some(ref token) = self.current_token else {
+            return;
+        };
+        if self.outbound_queue.is_empty() {
+            return;
+        }
+
+        let requests = self.outbound_queue.drain();
+        info!(
+            count = requests.len(),
+            "Flushing messages queued while offline"
+        );
+        for request in requests {
+            let local_id = local_echo_id(&request);
+            let _ = self.command_tx.send(BackendCommand::SendQueuedMessage {
+                token: token.clone(),
+                request,
+                local_id,
+            });
         }
     }
 
@@ -1093,6 +1813,8 @@ impl App {
                     .with_presence()
                     .with_reactions(),
             )
+            .with_typing(self.enable_typing_events)
+            .with_presence(self.enable_presence_updates)
             .with_auto_reconnect(true)
             .with_max_reconnect_attempts(10);
 
@@ -1139,6 +1861,9 @@ impl App {
             }
             GatewayEventKind::HeartbeatAck { latency_ms } => {
                 debug!(latency_ms = latency_ms, "Heartbeat acknowledged");
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.set_latency_ms(latency_ms);
+                }
             }
             GatewayEventKind::Dispatch(dispatch) => {
                 self.handle_dispatch_event(dispatch);
@@ -1156,6 +1881,32 @@ impl App {
         }
     }
 
+    /// Seeds the users connected to voice channels when the session starts.
+    fn set_voice_states(&mut self, voice_states: Vec<VoiceState>) {
+        if let CurrentScreen::Chat(ref mut state) = self.screen {
+            state.set_voice_states(voice_states);
+        } else if let Some(ref mut state) = self.pending_chat_state {
+            state.set_voice_states(voice_states);
+        } else {
+            self.pending_voice_states = Some(voice_states);
+        }
+    }
+
+    /// Puts guilds into the tree as soon as the gateway names them, so they
+    /// show up while their channels are still streaming in.
+    fn add_gateway_guilds(&mut self, guilds: Vec<Guild>) {
+        if guilds.is_empty() {
+            return;
+        }
+        if let CurrentScreen::Chat(ref mut state) = self.screen {
+            state.add_guilds(guilds);
+        } else if let Some(ref mut state) = self.pending_chat_state {
+            state.add_guilds(guilds);
+        } else {
+            self.pending_guilds.extend(guilds);
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn handle_dispatch_event(&mut self, event: DispatchEvent) {
         match event {
@@ -1221,22 +1972,29 @@ impl App {
                 mut threads,
                 roles,
                 members,
+                voice_states,
             } => {
                 if !unavailable {
                     info!(guild_id = %guild_id, name = %name, channel_count = channels.len(), thread_count = threads.len(), "Guild available");
+                    self.add_gateway_guilds(vec![Guild::new(guild_id, name)]);
                     let mut all_channels = channels;
                     all_channels.append(&mut threads);
 
                     if let CurrentScreen::Chat(ref mut state) = self.screen {
+                        state.set_guild_voice_states(guild_id, voice_states);
                         state.set_guild_data(guild_id, roles, members);
                         state.set_channels(guild_id, all_channels);
                     } else if let Some(ref mut state) = self.pending_chat_state {
+                        state.set_guild_voice_states(guild_id, voice_states);
                         state.set_guild_data(guild_id, roles, members);
                         state.set_channels(guild_id, all_channels);
                     } else {
                         self.pending_roles.insert(guild_id, roles);
                         self.pending_members.insert(guild_id, members);
                         self.pending_channels.insert(guild_id, all_channels);
+                        self.pending_voice_states
+                            .get_or_insert_with(Vec::new)
+                            .extend(voice_states);
                     }
                 }
             }
@@ -1258,6 +2016,9 @@ impl App {
             } => {
                 debug!(user_id = %user_id, username = %username, "User updated");
                 self.user_cache.update_username(&user_id, &username);
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.refresh_voice_participants();
+                }
             }
             DispatchEvent::Ready {
                 user_id,
@@ -1268,13 +2029,25 @@ impl App {
                 read_states,
                 guild_folders,
                 relationships,
+                status,
+                voice_states,
                 ..
             } => {
                 info!(user_id = %user_id, guild_count = guilds.len(), read_states_count = read_states.len(), relationship_count = relationships.len(), "Gateway ready");
                 self.gateway_ready = true;
+                // A fresh session starts from the settings status again.
+                self.chosen_status = status.unwrap_or(PresenceStatus::Online);
+                self.auto_idle = false;
 
                 self.relationship_state
                     .initialize_from_relationships(&relationships);
+                self.add_gateway_guilds(
+                    guilds
+                        .into_iter()
+                        .filter_map(|g| g.name.map(|name| Guild::new(g.id, name)))
+                        .collect(),
+                );
+                self.set_voice_states(voice_states);
                 debug!(
                     blocked_count = self.relationship_state.blocked_count(),
                     "Initialized relationship state"
@@ -1327,7 +2100,38 @@ impl App {
                     }
                 }
             }
-            DispatchEvent::UserSettingsUpdate { guild_folders } => {
+            DispatchEvent::VoiceStateUpdate {
+                channel_id,
+                user_id,
+                user,
+                nick,
+                deaf,
+                mute,
+                self_deaf,
+                self_mute,
+                ..
+            } => {
+                if let Some(user) = &user {
+                    self.user_cache.insert_from_user(user);
+                }
+                let state = channel_id.map(|channel_id| {
+                    VoiceState::new(user_id.clone(), channel_id)
+                        .with_flags(mute || self_mute, deaf || self_deaf)
+                        .with_nick(nick)
+                });
+                if let CurrentScreen::Chat(ref mut chat) = self.screen {
+                    chat.update_voice_state(&user_id, state);
+                } else if let Some(ref mut chat) = self.pending_chat_state {
+                    chat.update_voice_state(&user_id, state);
+                }
+            }
+            DispatchEvent::UserSettingsUpdate {
+                guild_folders,
+                status,
+            } => {
+                if let Some(status) = status {
+                    self.chosen_status = status;
+                }
                 debug!("Received guild folders update");
                 if let CurrentScreen::Chat(ref mut state) = self.screen {
                     state.set_guild_folders(guild_folders);
@@ -1357,6 +2161,35 @@ impl App {
         }
     }
 
+    /// `#channel` or `@user` for a batched desktop notification.
+    fn notification_channel_name(&self, channel_id: ChannelId) -> String {
+        let state = match &self.screen {
+            CurrentScreen::Chat(state) => Some(&**state),
+            _ => self.pending_chat_state.as_deref(),
+        };
+        let Some(state) = state else {
+            return "a channel".to_string();
+        };
+        let id = channel_id.to_string();
+        if let Some(dm) = state
+            .guilds_tree_data()
+            .dm_users()
+            .iter()
+            .find(|dm| dm.channel_id == id)
+        {
+            let name = dm.name(self.use_display_name);
+            return if dm.is_group() {
+                name
+            } else {
+                format!("@{name}")
+            };
+        }
+        state.get_channel(channel_id).map_or_else(
+            || "a channel".to_string(),
+            |channel| format!("#{}", channel.name()),
+        )
+    }
+
     #[allow(clippy::too_many_lines)]
     fn handle_message_create(&mut self, message: crate::domain::entities::Message) {
         let channel_id = message.channel_id();
@@ -1390,13 +2223,19 @@ impl App {
             is_mentioned = true;
         }
 
-        if is_mentioned {
+        let everyone_only = !is_mentioned
+            && Some(&user_id) != self.current_user_id.as_ref()
+            && message.mentions_everyone();
+        if is_mentioned || everyone_only {
             if let CurrentScreen::Chat(ref mut state) = self.screen {
-                state.increment_mention_count(channel_id);
+                state.increment_mention_count(channel_id, everyone_only);
             } else if let Some(ref mut state) = self.pending_chat_state {
-                state.increment_mention_count(channel_id);
+                state.increment_mention_count(channel_id, everyone_only);
             }
         }
+        if everyone_only && !self.suppress_everyone_highlight {
+            is_mentioned = true;
+        }
 
         let is_focused = if let CurrentScreen::Chat(ref state) = self.screen {
             state.message_pane_data().channel_id() == Some(channel_id)
@@ -1438,6 +2277,7 @@ impl App {
                 body.push_str("...");
             }
 
+            let channel_name = self.notification_channel_name(channel_id);
             if self.internal_notifications {
                 let title = if self.use_display_name {
                     message.author().raw_display_name()
@@ -1445,29 +2285,32 @@ impl App {
                     message.author().username().to_string()
                 };
 
-                self.notification_manager.info(format!("@{title}"), &body);
+                self.notification_manager.info_from(
+                    channel_id,
+                    channel_name.clone(),
+                    format!("@{title}"),
+                    &body,
+                );
             }
 
-            let now = Instant::now();
-            let should_notify = match self.last_desktop_notification {
-                Some(last) => now.duration_since(last) > Duration::from_secs(2),
-                None => true,
-            };
-
-            if should_notify {
-                let title = format!("Oxicord - @{}", message.author().username());
-                self.notification_service.send(&title, &body);
-                self.last_desktop_notification = Some(now);
-            }
+            let title = format!("Oxicord - @{}", message.author().username());
+            self.notification_service.notify_message(
+                channel_id,
+                &channel_name,
+                &title,
+                &body,
+                Instant::now(),
+            );
         }
 
         if let CurrentScreen::Chat(ref mut state) = self.screen {
-            state.on_message_received(&message);
-            if let Some(result) = state.add_message(message) {
+            let ack = state.on_message_received(&message);
+            let added = state.add_message(message);
+            for result in ack.into_iter().chain(added) {
                 let _ = self.process_chat_key_result(result);
             }
         } else if let Some(ref mut state) = self.pending_chat_state {
-            state.on_message_received(&message);
+            let _ = state.on_message_received(&message);
             if let Some(result) = state.add_message(message) {
                 let _ = result;
             }
@@ -1590,6 +2433,9 @@ impl App {
 
     fn load_channel_messages(&mut self, channel_id: ChannelId) {
         self.typing_manager.clear_channel(channel_id);
+        // Loading replaces the pane, taking failed echoes and their retry
+        // affordance with it.
+        self.pending_sends.retain(|_, pending| !pending.failed);
 
         if let Some(ref token) = self.current_token {
             let _ = self.command_tx.send(BackendCommand::LoadChannelMessages {
@@ -1614,12 +2460,18 @@ impl App {
                 token: token.clone(),
                 offset,
             });
+            if let CurrentScreen::Chat(state) = &mut self.screen {
+                state.message_pane_data_mut().set_loading_more(true);
+            }
         }
     }
 
     /// Send a subscription to the gateway to receive typing events for a channel.
     /// This is required for user accounts to receive `TYPING_START` events.
     fn subscribe_to_channel(&mut self, guild_id: GuildId, channel_id: ChannelId) {
+        if !self.enable_typing_events {
+            return;
+        }
         if let Some(ref gateway_client) = self.gateway_client {
             debug!(
                 guild_id = %guild_id,
@@ -1643,6 +2495,9 @@ impl App {
             before_message_id,
             token: token.clone(),
         });
+        if let CurrentScreen::Chat(state) = &mut self.screen {
+            state.message_pane_data_mut().set_loading_more(true);
+        }
     }
 
     #[allow(clippy::too_many_lines)]
@@ -1653,12 +2508,40 @@ impl App {
                     self.cache_users_from_message(message);
                 }
 
-                if let CurrentScreen::Chat(ref mut state) = self.screen {
-                    state.prepend_messages(messages);
+                let results = if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.prepend_messages(messages)
+                } else {
+                    Vec::new()
+                };
+                for result in results {
+                    self.process_chat_key_result(result);
+                }
+            }
+            Action::MessagesBackfilled {
+                channel_id,
+                messages,
+            } => {
+                for message in &messages {
+                    self.cache_users_from_message(message);
+                }
+                let newest = messages.iter().map(|m| m.id().as_u64()).max();
+                if let CurrentScreen::Chat(state) = &mut self.screen
+                    && state.message_pane_data().channel_id() == Some(channel_id)
+                {
+                    let fetch = state.merge_backfilled_messages(channel_id, messages);
+                    let ack =
+                        newest.and_then(|id| state.mark_channel_read(channel_id, MessageId(id)));
+                    for result in fetch.into_iter().chain(ack) {
+                        self.process_chat_key_result(result);
+                    }
                 }
             }
             Action::LoadError(e) => {
                 warn!(error = %e, "Failed to load history");
+                if let CurrentScreen::Chat(state) = &mut self.screen {
+                    state.message_pane_data_mut().set_loading_more(false);
+                    state.cancel_pending_jump();
+                }
             }
             Action::ChannelLoaded(channel) => {
                 let id = channel.id().to_string();
@@ -1690,6 +2573,8 @@ impl App {
                 initial_messages,
                 recents,
                 sort_mode,
+                expanded_nodes,
+                pinned,
             } => {
                 info!("Data loaded, preparing chat state");
                 self.current_user_id = Some(user.id().to_string());
@@ -1708,7 +2593,8 @@ impl App {
                     self.hide_blocked_completely,
                     sort_mode,
                     recents,
-                );
+                )
+                .with_startup_focus(self.startup_focus);
 
                 chat_state.set_connection_status(self.connection_status);
 
@@ -1722,15 +2608,64 @@ impl App {
                             dm.recipient_global_name.clone(),
                             false,
                         ));
+                    if let DmKind::Group { recipients, .. } = &dm.kind {
+                        for recipient in recipients {
+                            self.user_cache
+                                .insert(crate::domain::entities::CachedUser::new(
+                                    &recipient.id,
+                                    &recipient.username,
+                                    &recipient.discriminator,
+                                    None,
+                                    recipient.global_name.clone(),
+                                    false,
+                                ));
+                        }
+                    }
                 }
 
                 chat_state.set_dm_users(dms);
                 chat_state.set_guilds(guilds);
+                chat_state.add_guilds(std::mem::take(&mut self.pending_guilds));
 
                 if let Some(folders) = self.pending_guild_folders.take() {
                     chat_state.set_guild_folders(folders);
                 }
+                if let Some(voice_states) = self.pending_voice_states.take() {
+                    chat_state.set_voice_states(voice_states);
+                }
+                chat_state.restore_tree_expansion(&expanded_nodes);
+                chat_state.set_pinned(pinned);
                 chat_state.set_group_guilds(self.group_guilds);
+                chat_state.set_confirm_logout_with_drafts(self.confirm_logout_with_drafts);
+                chat_state.set_max_message_length(self.max_message_length);
+                chat_state.set_large_paste_threshold(self.large_paste_threshold);
+                chat_state.set_message_density(self.message_density);
+                chat_state.set_file_explorer_config(self.file_explorer.clone());
+                chat_state.set_record_recents(self.record_recents);
+                chat_state.set_max_recents(self.max_recents);
+                chat_state.set_timestamp_position(self.timestamp_position);
+                chat_state.set_show_embeds(self.show_embeds);
+                chat_state.set_ellipsis(self.ellipsis.clone());
+                chat_state.set_purge_message_limit(self.purge_message_limit);
+                chat_state.set_internal_notifications(self.internal_notifications);
+                chat_state.set_image_prefetch_distance(self.image_prefetch_distance);
+                chat_state.set_group_start_timestamps_only(self.group_start_timestamps_only);
+                chat_state.set_confirm_everyone_send(self.confirm_everyone_send);
+                chat_state.set_quick_switcher_sections(self.quick_switcher_sections.clone());
+                chat_state.set_show_mention_count(self.show_mention_count);
+                chat_state.set_wrap_indent(self.wrap_indent);
+                chat_state.set_confirm_masked_links(self.confirm_masked_links);
+                chat_state.set_suppress_everyone_highlight(self.suppress_everyone_highlight);
+                chat_state.set_mark_read_on_focus(self.mark_read_on_focus);
+                chat_state.set_compact_guilds_tree(self.compact_guilds_tree);
+                chat_state.set_restore_focus_after_overlay(self.restore_focus_after_overlay);
+                chat_state.set_double_esc_to_close(self.double_esc_to_close);
+                chat_state.set_group_replies(self.group_replies);
+                chat_state.set_collapse_message_lines(self.collapse_message_lines);
+                chat_state.set_eager_guild_count(self.eager_guild_count);
+                chat_state
+                    .set_flatten_single_channel_categories(self.flatten_single_channel_categories);
+                chat_state.set_accent_overrides(self.accent_overrides.clone());
 
                 for (guild_id, channels) in self.pending_channels.drain() {
                     chat_state.set_channels(guild_id, channels);
@@ -1810,21 +2745,14 @@ impl App {
                     state.set_messages(messages);
                     state.set_typing_indicator(None);
 
-                    if let Some(last_msg) = state
+                    if let Some(message_id) = state
                         .message_pane_data()
                         .messages()
                         .back()
-                        .map(|m| &m.message)
+                        .map(|m| m.message.id())
+                        && let Some(ack) = state.mark_channel_read(channel_id, message_id)
                     {
-                        let message_id = last_msg.id();
-                        if let Some(ref token) = self.current_token {
-                            let _ = self.command_tx.send(BackendCommand::AcknowledgeMessage {
-                                channel_id,
-                                message_id,
-                                token: token.clone(),
-                            });
-                        }
-                        state.mark_channel_read(channel_id, message_id);
+                        self.process_chat_key_result(ack);
                     }
                 }
             }
@@ -1856,16 +2784,41 @@ impl App {
                     state.set_message_error(error);
                 }
             }
-            Action::MessageSent(message) => {
+            Action::MessageSent { message, local_id } => {
                 info!(message_id = %message.id(), "Message sent successfully");
+                if let Some(local_id) = local_id {
+                    self.pending_sends.remove(&local_id);
+                }
                 if let CurrentScreen::Chat(ref mut state) = self.screen {
-                    state.add_message(message);
+                    match local_id {
+                        Some(local_id) => state.confirm_local_echo(local_id, message),
+                        None => state.add_message(message),
+                    };
                 }
             }
-            Action::MessageSendError(error) => {
+            Action::MessageSendError { local_id, error } => {
                 error!(error = %error, "Failed to send message");
-                if let CurrentScreen::Chat(ref mut state) = self.screen {
-                    state.set_message_error(format!("Failed to send: {error}"));
+                self.fail_pending_send(local_id);
+                self.show_notification(format!("Failed to send: {error}"));
+            }
+            Action::QueuedMessageFailed {
+                request,
+                local_id,
+                error,
+            } => {
+                if self.connection_status == ConnectionStatus::Connected {
+                    error!(channel_id = %request.channel_id, error = %error, "Queued message failed");
+                    if let Some(local_id) = local_id {
+                        self.fail_pending_send(local_id);
+                    }
+                    self.show_notification(format!("Failed to send queued message: {error}"));
+                } else {
+                    self.outbound_queue.push(request);
+                }
+            }
+            Action::TerminalBackgroundDetected(reading) => {
+                if let Some(is_light_mode) = self.background_watcher.observe(reading) {
+                    self.apply_light_mode(is_light_mode);
                 }
             }
             Action::MessageEdited(message) => {
@@ -1892,6 +2845,67 @@ impl App {
                     state.set_message_error(format!("Failed to delete: {error}"));
                 }
             }
+            Action::MessagePublished(message) => {
+                info!(message_id = %message.id(), "Message published to followers");
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.update_message(message);
+                }
+                self.show_notification("Message published".to_string());
+            }
+            Action::MessagePublishError(error) => {
+                error!(error = %error, "Failed to publish message");
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.set_message_error(format!("Failed to publish: {error}"));
+                }
+            }
+            Action::RateLimited(wait) => {
+                warn!(
+                    wait_ms = wait.as_millis(),
+                    "Requests throttled by rate limit"
+                );
+                self.show_notification(format!(
+                    "Rate limited, retrying in {}s",
+                    wait.as_secs_f64().ceil()
+                ));
+            }
+            Action::NicknameChanged { guild_id, member } => {
+                let message = match &member.nick {
+                    Some(nick) => format!("Nickname set to {nick}"),
+                    None => "Nickname reset".to_string(),
+                };
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.set_own_member(guild_id, member);
+                }
+                self.show_notification(message);
+            }
+            Action::NicknameError(error) => {
+                error!(error = %error, "Failed to set nickname");
+                self.show_notification(format!("Failed to change nickname: {error}"));
+            }
+            Action::GroupDmCreated(dm) => {
+                let channel_id = dm.channel_id.clone();
+                let name = dm.name(self.use_display_name);
+                let mut opened = None;
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.upsert_dm(dm);
+                    opened = state.open_dm(&channel_id);
+                }
+                if let Some(result) = opened {
+                    self.process_chat_key_result(result);
+                }
+                self.show_notification(format!("Created group DM: {name}"));
+            }
+            Action::GroupDmUpdated(dm) => {
+                let name = dm.name(self.use_display_name);
+                if let CurrentScreen::Chat(ref mut state) = self.screen {
+                    state.upsert_dm(dm);
+                }
+                self.show_notification(format!("Updated group DM: {name}"));
+            }
+            Action::GroupDmError(error) => {
+                error!(error = %error, "Failed to update group DM");
+                self.show_notification(format!("Group DM failed: {error}"));
+            }
             Action::TypingIndicatorSent(_) => {}
             #[cfg(feature = "image")]
             Action::ImageLoaderReady(loader) => {
@@ -1899,13 +2913,18 @@ impl App {
             }
             Action::PasteImageLoaded(path) => {
                 if let CurrentScreen::Chat(state) = &mut self.screen {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
                     state.add_attachment(path);
-                    self.show_notification("Image pasted as attachment".to_string());
+                    self.show_notification(format!("Image pasted as attachment: {name}"));
                 }
             }
             Action::PasteTextLoaded(text) => {
                 let _ = self.handle_terminal_event(&Event::Paste(text));
             }
+            Action::RemoteAuth(event) => self.handle_remote_auth_event(event),
             Action::ShowNotification(message) => {
                 self.show_notification(message);
             }
@@ -1949,6 +2968,45 @@ impl App {
             state.mark_image_downloading(&id);
             loader.load_async(id, url);
         }
+
+        let prefetch = state.collect_image_prefetches();
+        if !prefetch
+            .iter()
+            .map(|(id, _)| id)
+            .eq(&self.last_image_prefetch)
+        {
+            self.last_image_prefetch = prefetch.iter().map(|(id, _)| id.clone()).collect();
+            loader.prefetch_batch(prefetch);
+        }
+    }
+
+    /// Deletes saved state, cached images and temporary files, leaving
+    /// nothing of the session on disk.
+    fn clear_local_data(&self) {
+        let _ = self.state_save_tx.send(StateStoreCommand::Clear);
+        #[cfg(feature = "image")]
+        let image_loader = self.image_loader.clone();
+
+        tokio::spawn(async move {
+            #[cfg(feature = "image")]
+            match image_loader {
+                Some(loader) => loader.clear_all().await,
+                None => {
+                    if let Ok(cache) = DiskImageCache::default_location().await
+                        && let Err(e) = cache.clear().await
+                    {
+                        warn!(error = %e, "Failed to clear disk image cache");
+                    }
+                }
+            }
+            let temp_dir = crate::infrastructure::temp_dir();
+            match tokio::fs::remove_dir_all(&temp_dir).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    warn!(error = %e, path = %temp_dir.display(), "Failed to remove temp files");
+                }
+                _ => info!("Cleared local data"),
+            }
+        });
     }
 
     fn transition_to_login(&mut self) {
@@ -1963,6 +3021,8 @@ impl App {
         self.pending_chat_state = None;
         self.typing_manager = TypingIndicatorManager::new();
         self.user_cache.clear();
+        self.outbound_queue.clear();
+        self.pending_sends.clear();
 
         if let Some((mut token, _)) = self.pending_token.take() {
             token.zeroize();
@@ -2007,43 +3067,164 @@ impl App {
         }
     }
 
+    fn handle_purge_messages(&mut self, message_ids: Vec<MessageId>) {
+        if let Some(ref token) = self.current_token
+            && let CurrentScreen::Chat(state) = &self.screen
+            && let Some(channel_id) = state.message_pane_data().channel_id()
+        {
+            let _ = self.command_tx.send(BackendCommand::PurgeMessages {
+                token: token.clone(),
+                channel_id,
+                message_ids,
+            });
+        }
+    }
+
+    fn handle_publish_message(&mut self, message_id: MessageId) {
+        if let Some(ref token) = self.current_token
+            && let CurrentScreen::Chat(state) = &self.screen
+            && let Some(channel_id) = state.message_pane_data().channel_id()
+        {
+            let _ = self.command_tx.send(BackendCommand::CrosspostMessage {
+                token: token.clone(),
+                channel_id,
+                message_id,
+            });
+        }
+    }
+
     fn handle_send_message(
         &mut self,
         content: String,
         reply_to: Option<MessageId>,
-        attachments: Vec<std::path::PathBuf>,
+        mention_reply: bool,
+        mass_mention_confirmed: bool,
+        attachments: Vec<OutgoingAttachment>,
     ) {
         if let Some(ref token) = self.current_token
             && let CurrentScreen::Chat(state) = &self.screen
             && let Some(channel_id) = state.message_pane_data().channel_id()
         {
-            let request = SendMessageRequest {
-                channel_id,
-                content,
+            let token = token.clone();
+            self.typing_throttle.reset(channel_id);
+            let request = self.send_request(
+                SendMessageRequest::new(channel_id, content)
+                    .with_reply_mention(mention_reply)
+                    .with_attachments(attachments),
                 reply_to,
-                attachments,
-            };
-            let _ = self.command_tx.send(BackendCommand::SendMessage {
-                token: token.clone(),
-                request,
-            });
+                mass_mention_confirmed,
+            );
+
+            self.submit_message(token, request);
+        }
+    }
+
+    /// Finishes a composed send: the reply target and which mentions in the
+    /// content may ping. Mass mentions are dropped when suppressed by
+    /// default, unless the user confirmed them at the `@everyone` prompt.
+    fn send_request(
+        &self,
+        mut request: SendMessageRequest,
+        reply_to: Option<MessageId>,
+        mass_mention_confirmed: bool,
+    ) -> SendMessageRequest {
+        if let Some(message_id) = reply_to {
+            request = request.with_reply(message_id);
+        }
+        let mut allowed_mentions = AllowedMentions {
+            parse: mention_types(&request.content),
+        };
+        if self.default_suppress_mentions && !mass_mention_confirmed {
+            allowed_mentions = allowed_mentions.without_mass_mentions();
+        }
+        request.with_allowed_mentions(allowed_mentions)
+    }
+
+    /// Sends a message right away, or queues it until the connection is back.
+    ///
+    /// Either way a local echo shows the message as pending until Discord
+    /// confirms it.
+    fn submit_message(&mut self, token: AuthToken, mut request: SendMessageRequest) {
+        // Local echo ids count down from the top of the range, far above any
+        // real snowflake. The id doubles as the nonce, so a queued request
+        // still finds its echo once flushed.
+        let local_id = MessageId(u64::MAX - self.next_local_id);
+        self.next_local_id += 1;
+        request.nonce = Some(local_id.to_string());
+        if let CurrentScreen::Chat(state) = &mut self.screen {
+            state.add_local_echo(local_id, &request);
+        }
+        self.pending_sends.insert(
+            local_id,
+            PendingSend {
+                request: request.clone(),
+                failed: false,
+            },
+        );
+
+        if self.connection_status != ConnectionStatus::Connected {
+            self.outbound_queue.push(request);
+            let pending = self.outbound_queue.len();
+            info!(pending, "Offline, queued message for later");
+            self.show_notification(format!("Offline: message queued ({pending} pending)"));
+            return;
+        }
+
+        let _ = self.command_tx.send(BackendCommand::SendMessage {
+            token,
+            request,
+            local_id,
+        });
+    }
+
+    /// Marks a send as failed so it can be retried, or forgets it when its
+    /// echo is no longer shown and there is nothing left to retry from.
+    fn fail_pending_send(&mut self, local_id: MessageId) {
+        let shown = match &mut self.screen {
+            CurrentScreen::Chat(state) => state.set_send_state(local_id, SendState::Failed),
+            _ => true,
+        };
+        if !shown {
+            self.pending_sends.remove(&local_id);
+        } else if let Some(pending) = self.pending_sends.get_mut(&local_id) {
+            pending.failed = true;
+        }
+    }
+
+    /// Sends a failed message again under its existing local echo.
+    fn retry_send(&mut self, local_id: MessageId) {
+        let Some(pending) = self.pending_sends.get_mut(&local_id) else {
+            return;
+        };
+        let Some(token) = self.current_token.clone() else {
+            return;
+        };
+        pending.failed = false;
+        let request = pending.request.clone();
+
+        if let CurrentScreen::Chat(state) = &mut self.screen {
+            state.set_send_state(local_id, SendState::Pending);
         }
+        let _ = self.command_tx.send(BackendCommand::SendMessage {
+            token,
+            request,
+            local_id,
+        });
     }
 
     fn handle_start_typing(&mut self) {
-        let now = Instant::now();
         if let CurrentScreen::Chat(state) = &self.screen
             && let Some(channel_id) = state.message_pane_data().channel_id()
             && let Some(ref token) = self.current_token
         {
-            if let Some((last_cid, last_time)) = self.last_typing_sent
-                && last_cid == channel_id
-                && now.duration_since(last_time) < TYPING_THROTTLE_DURATION
-            {
+            if state.message_input_state().is_empty() {
+                self.typing_throttle.reset(channel_id);
+                return;
+            }
+            if !self.typing_throttle.should_send(channel_id, Instant::now()) {
                 return;
             }
 
-            self.last_typing_sent = Some((channel_id, now));
             let _ = self.command_tx.send(BackendCommand::SendTypingIndicator {
                 channel_id,
                 token: token.clone(),
@@ -2053,7 +3234,7 @@ impl App {
 
     fn handle_login_error(&mut self, error: &AuthError) {
         if let CurrentScreen::Login(screen) = &mut self.screen {
-            screen.set_error(error.to_string());
+            screen.set_error(error.user_message());
         }
     }
 
@@ -2064,14 +3245,15 @@ impl App {
         target_message_id: Option<MessageId>,
     ) -> color_eyre::Result<()> {
         use std::io::Write;
-        use tempfile::NamedTempFile;
 
-        let mut temp_file = NamedTempFile::new()?;
+        let suffix = self.editor.suffix().unwrap_or_default();
+        let mut temp_file = tempfile::Builder::new().suffix(&suffix).tempfile()?;
         write!(temp_file, "{initial_content}")?;
         let temp_path = temp_file.path().to_owned();
 
         let editor = self
             .editor
+            .command
             .clone()
             .or_else(|| std::env::var("EDITOR").ok())
             .or_else(|| std::env::var("VISUAL").ok())
@@ -2098,13 +3280,11 @@ impl App {
             crossterm::cursor::Show
         )?;
 
-        let parts = crate::presentation::ui::utils::split_command(&editor);
+        let parts =
+            crate::presentation::ui::utils::editor_command(&editor, &temp_path.to_string_lossy());
         let mut parts_iter = parts.into_iter();
         let status = if let Some(cmd) = parts_iter.next() {
-            std::process::Command::new(cmd)
-                .args(parts_iter)
-                .arg(&temp_path)
-                .status()
+            std::process::Command::new(cmd).args(parts_iter).status()
         } else {
             Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -2159,8 +3339,9 @@ impl App {
             Ok(exit_status) => {
                 warn!(
                     exit_code = ?exit_status.code(),
-                    "Editor exited with non-zero status"
+                    "Editor exited with non-zero status, discarding edit"
                 );
+                self.show_notification("Editor exited with an error; edit discarded".to_string());
             }
             Err(e) => {
                 error!(error = %e, editor = %editor, "Failed to spawn editor");
@@ -2177,131 +3358,14 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::Guild;
     use crate::domain::ports::{
-        DirectMessageChannel, FetchMessagesOptions, SendMessageRequest,
-        mocks::{MockAuthPort, MockTokenStorage},
+        MentionType, SendMessageRequest,
+        mocks::{MockAuthPort, MockDiscordData, MockTokenStorage},
     };
 
-    struct MockDiscordData;
-
-    #[async_trait::async_trait]
-    impl DiscordDataPort for MockDiscordData {
-        async fn delete_message(
-            &self,
-            _token: &AuthToken,
-            _channel_id: ChannelId,
-            _message_id: MessageId,
-        ) -> Result<(), AuthError> {
-            Ok(())
-        }
-
-        async fn fetch_guilds(&self, _token: &AuthToken) -> Result<Vec<Guild>, AuthError> {
-            Ok(vec![Guild::new(1_u64, "Test Guild")])
-        }
-
-        async fn fetch_channels(
-            &self,
-            _token: &AuthToken,
-            _guild_id: u64,
-        ) -> Result<Vec<crate::domain::entities::Channel>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn fetch_dm_channels(
-            &self,
-            _token: &AuthToken,
-        ) -> Result<Vec<DirectMessageChannel>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn fetch_read_states(
-            &self,
-            _token: &AuthToken,
-        ) -> Result<Vec<crate::domain::entities::ReadState>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn fetch_messages(
-            &self,
-            _token: &AuthToken,
-            _channel_id: u64,
-            _options: FetchMessagesOptions,
-        ) -> Result<Vec<crate::domain::entities::Message>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn load_more_before_id(
-            &self,
-            _token: &AuthToken,
-            _channel_id: u64,
-            _message_id: u64,
-            _limit: u8,
-        ) -> Result<Vec<crate::domain::entities::Message>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn send_message(
-            &self,
-            _token: &AuthToken,
-            _request: SendMessageRequest,
-        ) -> Result<crate::domain::entities::Message, AuthError> {
-            Err(AuthError::unexpected("mock not implemented"))
-        }
-
-        async fn edit_message(
-            &self,
-            _token: &AuthToken,
-            _request: EditMessageRequest,
-        ) -> Result<crate::domain::entities::Message, AuthError> {
-            Err(AuthError::unexpected("mock not implemented"))
-        }
-
-        async fn send_typing_indicator(
-            &self,
-            _token: &AuthToken,
-            _channel_id: ChannelId,
-        ) -> Result<(), AuthError> {
-            Ok(())
-        }
-
-        async fn acknowledge_message(
-            &self,
-            _token: &AuthToken,
-            _channel_id: ChannelId,
-            _message_id: MessageId,
-        ) -> Result<(), AuthError> {
-            Ok(())
-        }
-
-        async fn fetch_forum_threads(
-            &self,
-            _token: &AuthToken,
-            _channel_id: ChannelId,
-            _guild_id: Option<GuildId>,
-            _offset: u32,
-            _limit: Option<u8>,
-        ) -> Result<Vec<crate::domain::entities::ForumThread>, AuthError> {
-            Ok(vec![])
-        }
-
-        async fn fetch_channel(
-            &self,
-            _token: &AuthToken,
-            _channel_id: ChannelId,
-        ) -> Result<crate::domain::entities::Channel, AuthError> {
-            Ok(crate::domain::entities::Channel::new(
-                123,
-                "mock-channel".to_string(),
-                crate::domain::entities::ChannelKind::Text,
-            ))
-        }
-    }
-
-    #[tokio::test]
-    async fn test_app_creation() {
+    fn create_test_app() -> App {
         let auth = Arc::new(MockAuthPort::new(true));
-        let data = Arc::new(MockDiscordData);
+        let data = Arc::new(MockDiscordData::new());
         let storage = Arc::new(MockTokenStorage::new());
         let theme = Theme::new("Orange", None, false);
         let identity = Arc::new(ClientIdentity::new());
@@ -2313,17 +3377,337 @@ mod tests {
             image_preview: true,
             timestamp_format: "%H:%M".to_string(),
             show_typing: true,
+            enable_typing_events: true,
+            enable_presence_updates: true,
+            idle_after: None,
             internal_notifications: true,
+            notification_batch_window: Duration::ZERO,
+            max_internal_notifications: 3,
             enable_animations: true,
-            editor: None,
+            cdn_max_dimension: 400,
+            prefer_webp: true,
+            disk_cache_max_mb: 200,
+            image_prefetch_distance: 10,
+            editor: EditorConfig::default(),
+            file_explorer: FileExplorerConfig::default(),
+            keymap: KeymapPreset::default(),
+            sequence_timeout: Duration::from_millis(1500),
             keybindings: std::collections::HashMap::new(),
             notification_duration: 5,
             theme,
             hide_blocked_completely: false,
             quick_switcher_order: QuickSwitcherSortMode::default(),
+            quick_switcher_sections: QuickSwitcherSection::ALL.to_vec(),
+            confirm_logout_with_drafts: true,
+            default_suppress_mentions: false,
+            max_message_length: 2000,
+            large_paste_threshold: 2000,
+            message_density: MessageDensity::Cozy,
+            record_recents: true,
+            max_recents: 50,
+            splash_min_duration_ms: 0,
+            show_splash: true,
+            require_double_escape_to_quit: false,
+            timestamp_position: TimestampPosition::Left,
+            show_embeds: true,
+            ellipsis: "…".to_string(),
+            purge_message_limit: 25,
+            group_start_timestamps_only: true,
+            confirm_everyone_send: true,
+            show_mention_count: true,
+            wrap_indent: 0,
+            confirm_masked_links: true,
+            startup_focus: StartupFocus::default(),
+            suppress_everyone_highlight: false,
+            mark_read_on_focus: false,
+            compact_guilds_tree: false,
+            restore_focus_after_overlay: false,
+            double_esc_to_close: false,
+            group_replies: false,
+            collapse_message_lines: 0,
+            eager_guild_count: 0,
+            flatten_single_channel_categories: false,
+            accent_overrides: std::collections::HashMap::new(),
+            background_poll_interval: None,
         };
-        let app = App::new(auth, data, storage, config, identity);
+        App::new(auth, data, storage, config, identity)
+    }
+
+    #[tokio::test]
+    async fn test_app_creation() {
+        let app = create_test_app();
 
         assert_eq!(app.state, AppState::Login);
     }
+
+    #[tokio::test]
+    async fn test_offline_messages_flush_in_order_on_reconnect() {
+        let mut app = create_test_app();
+        let token = AuthToken::new_unchecked("test-token");
+        app.current_token = Some(token.clone());
+
+        for content in ["first", "second", "third"] {
+            app.submit_message(
+                token.clone(),
+                SendMessageRequest::new(ChannelId(7), content),
+            );
+        }
+        assert_eq!(app.outbound_queue.len(), 3);
+        assert_eq!(app.pending_sends.len(), 3, "queued sends get a local echo");
+
+        app.set_connection_status(ConnectionStatus::Connected);
+        assert!(app.outbound_queue.is_empty());
+
+        let mut sent = Vec::new();
+        while sent.len() < 3 {
+            let action = tokio::time::timeout(Duration::from_secs(1), app.action_rx.recv())
+                .await
+                .expect("timed out waiting for queued sends")
+                .expect("action channel closed");
+            if let Action::MessageSent { message, local_id } = &action {
+                assert!(local_id.is_some(), "queued sends confirm their echo");
+                sent.push(message.content().to_string());
+                app.handle_action(action);
+            }
+        }
+        assert_eq!(sent, vec!["first", "second", "third"]);
+        assert!(app.pending_sends.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_request_honors_reply_ping_and_mass_mention_filter() {
+        let mut app = create_test_app();
+        let compose = || {
+            SendMessageRequest::new(ChannelId(7), "<@1> <@&2> @everyone").with_reply_mention(false)
+        };
+
+        let request = app.send_request(compose(), Some(MessageId(5)), false);
+        assert_eq!(request.reply_to, Some(MessageId(5)));
+        assert!(!request.mention_reply);
+        assert_eq!(
+            request.allowed_mentions.unwrap().parse,
+            vec![
+                MentionType::Users,
+                MentionType::Roles,
+                MentionType::Everyone
+            ]
+        );
+
+        app.default_suppress_mentions = true;
+        let request = app.send_request(compose(), None, false);
+        assert_eq!(request.reply_to, None);
+        assert_eq!(
+            request.allowed_mentions.unwrap().parse,
+            vec![MentionType::Users]
+        );
+
+        let request = app.send_request(compose(), None, true);
+        assert_eq!(
+            request.allowed_mentions.unwrap().parse,
+            vec![
+                MentionType::Users,
+                MentionType::Roles,
+                MentionType::Everyone,
+            ],
+            "a confirmed @everyone send keeps its pings"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_sends_are_dropped_when_channel_reloads() {
+        let mut app = create_test_app();
+        let token = AuthToken::new_unchecked("test-token");
+        app.current_token = Some(token.clone());
+        app.set_connection_status(ConnectionStatus::Connected);
+
+        app.submit_message(token.clone(), SendMessageRequest::new(ChannelId(7), "lost"));
+        let failed = *app
+            .pending_sends
+            .keys()
+            .next()
+            .expect("send should be tracked");
+        app.handle_action(Action::MessageSendError {
+            local_id: failed,
+            error: "server error".to_string(),
+        });
+        app.submit_message(token, SendMessageRequest::new(ChannelId(7), "in flight"));
+
+        app.load_channel_messages(ChannelId(8));
+        assert!(!app.pending_sends.contains_key(&failed));
+        assert_eq!(app.pending_sends.len(), 1, "in-flight sends are kept");
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_is_kept_for_retry() {
+        let mut app = create_test_app();
+        let token = AuthToken::new_unchecked("test-token");
+        app.current_token = Some(token.clone());
+        app.set_connection_status(ConnectionStatus::Connected);
+
+        app.submit_message(token, SendMessageRequest::new(ChannelId(7), "hello"));
+        let local_id = *app
+            .pending_sends
+            .keys()
+            .next()
+            .expect("send should be tracked");
+        app.handle_action(Action::MessageSendError {
+            local_id,
+            error: "server error".to_string(),
+        });
+        assert!(app.pending_sends.contains_key(&local_id));
+
+        app.retry_send(local_id);
+        loop {
+            let action = tokio::time::timeout(Duration::from_secs(1), app.action_rx.recv())
+                .await
+                .expect("timed out waiting for send")
+                .expect("action channel closed");
+            if let Action::MessageSent {
+                local_id: Some(confirmed),
+                ..
+            } = action
+            {
+                assert_eq!(confirmed, local_id);
+                app.handle_action(action);
+                break;
+            }
+        }
+        assert!(app.pending_sends.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_queued_message_is_requeued_while_offline() {
+        let mut app = create_test_app();
+        app.handle_action(Action::QueuedMessageFailed {
+            request: SendMessageRequest::new(ChannelId(7), "retry me"),
+            local_id: None,
+            error: "network down".to_string(),
+        });
+
+        assert_eq!(app.outbound_queue.pending_in(ChannelId(7)), 1);
+    }
+
+    #[tokio::test]
+    async fn test_terminal_background_flip_switches_theme() {
+        let mut app = create_test_app();
+        assert!(!app.theme.is_light);
+
+        app.handle_action(Action::TerminalBackgroundDetected(Some(true)));
+        assert!(!app.theme.is_light, "a single reading should be debounced");
+
+        app.handle_action(Action::TerminalBackgroundDetected(Some(true)));
+        assert!(app.theme.is_light);
+    }
+
+    fn create_idle_test_app() -> App {
+        let mut app = create_test_app();
+        app.gateway_ready = true;
+        app.idle_after = Some(Duration::from_secs(30));
+        app
+    }
+
+    fn quiet_since() -> Instant {
+        Instant::now().checked_sub(Duration::from_secs(31)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_idle_presence_after_inactivity() {
+        let mut app = create_idle_test_app();
+
+        app.poll_idle_presence();
+        assert!(!app.auto_idle, "recent input keeps us online");
+
+        app.last_input = quiet_since();
+        app.poll_idle_presence();
+        assert!(app.auto_idle);
+
+        app.auto_idle = false;
+        app.idle_after = None;
+        app.poll_idle_presence();
+        assert!(!app.auto_idle, "disabled when unset");
+    }
+
+    #[tokio::test]
+    async fn test_background_requery_waits_for_quiet_input() {
+        let mut app = create_test_app();
+        app.background_poll_interval = Some(Duration::from_secs(30));
+        app.last_background_check = quiet_since();
+
+        assert!(
+            !app.background_check_due(),
+            "recent input holds the query back"
+        );
+        app.last_input = quiet_since();
+        assert!(app.background_check_due());
+        assert!(!app.background_check_due(), "the interval restarts");
+    }
+
+    #[tokio::test]
+    async fn test_input_restores_presence_after_auto_idle() {
+        let mut app = create_idle_test_app();
+        app.last_input = quiet_since();
+        app.poll_idle_presence();
+        assert!(app.auto_idle);
+
+        app.handle_terminal_event(&Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        assert!(!app.auto_idle, "input restores the chosen status");
+        assert_eq!(app.chosen_status, PresenceStatus::Online);
+    }
+
+    #[tokio::test]
+    async fn test_manual_status_is_not_auto_idled() {
+        for status in [PresenceStatus::DoNotDisturb, PresenceStatus::Invisible] {
+            let mut app = create_idle_test_app();
+            app.chosen_status = status;
+            app.last_input = quiet_since();
+            app.poll_idle_presence();
+            assert!(!app.auto_idle, "a manual {status:?} is left alone");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_double_escape_to_quit() {
+        let mut app = create_test_app();
+        let esc = KeyEvent::from(KeyCode::Esc);
+        assert_eq!(app.handle_key(esc), EventResult::Exit);
+
+        app.require_double_escape_to_quit = true;
+        assert_eq!(app.handle_key(esc), EventResult::Continue);
+        assert_eq!(app.handle_key(esc), EventResult::Exit);
+
+        assert_eq!(app.handle_key(esc), EventResult::Continue);
+        app.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        assert_eq!(
+            app.handle_key(esc),
+            EventResult::Continue,
+            "other keys reset"
+        );
+
+        let late = Instant::now() + QUIT_ESCAPE_TIMEOUT;
+        app.expire_quit_escape(late);
+        assert!(app.quit_escape_at.is_none());
+        assert!(!app.confirm_quit_escape(late));
+    }
+
+    #[tokio::test]
+    async fn test_escape_backs_out_of_qr_login() {
+        let mut app = create_test_app();
+        if let CurrentScreen::Login(screen) = &mut app.screen {
+            screen.start_qr();
+        }
+        app.remote_auth_task = Some(tokio::spawn(std::future::pending()));
+        app.handle_remote_auth_event(RemoteAuthEvent::Scanned {
+            username: "ferris".to_string(),
+        });
+
+        assert_eq!(
+            app.handle_key(KeyEvent::from(KeyCode::Esc)),
+            EventResult::Continue
+        );
+        assert!(app.remote_auth_task.is_none());
+        let CurrentScreen::Login(screen) = &app.screen else {
+            panic!("expected login screen");
+        };
+        assert!(!screen.is_qr_active());
+    }
 }
@@ -1,8 +1,9 @@
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
-use crate::domain::entities::{AuthToken, ChannelId, GuildId, Message, MessageId};
+use crate::domain::entities::{AuthToken, ChannelId, GuildId, Member, Message, MessageId};
 use crate::domain::ports::{
     DirectMessageChannel, DiscordDataPort, EditMessageRequest, FetchMessagesOptions,
     SendMessageRequest,
@@ -13,6 +14,10 @@ use crate::infrastructure::image::ImageLoader;
 #[derive(Debug)]
 pub enum Action {
     HistoryLoaded(Vec<Message>),
+    MessagesBackfilled {
+        channel_id: ChannelId,
+        messages: Vec<Message>,
+    },
     LoadError(String),
     DataLoaded {
         user: crate::domain::entities::User,
@@ -25,6 +30,8 @@ pub enum Action {
         initial_messages: Option<Vec<Message>>,
         recents: Vec<crate::domain::search::RecentItem>,
         sort_mode: crate::infrastructure::config::app_config::QuickSwitcherSortMode,
+        expanded_nodes: Vec<String>,
+        pinned: Vec<crate::domain::search::RecentItem>,
     },
     GuildChannelsLoaded {
         guild_id: GuildId,
@@ -56,12 +63,39 @@ pub enum Action {
         channel_id: ChannelId,
         error: String,
     },
-    MessageSent(Message),
-    MessageSendError(String),
+    /// A message was sent; `local_id` names the local echo it confirms.
+    MessageSent {
+        message: Message,
+        local_id: Option<MessageId>,
+    },
+    MessageSendError {
+        local_id: MessageId,
+        error: String,
+    },
+    /// A message flushed from the offline queue could not be sent.
+    QueuedMessageFailed {
+        request: SendMessageRequest,
+        local_id: Option<MessageId>,
+        error: String,
+    },
     MessageEdited(Message),
     MessageEditError(String),
+    /// Result of a periodic terminal background query (`Some(true)` = light).
+    TerminalBackgroundDetected(Option<bool>),
     MessageDeleted(MessageId),
     MessageDeleteError(String),
+    MessagePublished(Message),
+    MessagePublishError(String),
+    /// REST requests are paused for this long by a Discord rate limit.
+    RateLimited(Duration),
+    NicknameChanged {
+        guild_id: GuildId,
+        member: Member,
+    },
+    NicknameError(String),
+    GroupDmCreated(DirectMessageChannel),
+    GroupDmUpdated(DirectMessageChannel),
+    GroupDmError(String),
     TypingIndicatorSent(ChannelId),
     LoginSuccess {
         user: crate::domain::entities::User,
@@ -74,6 +108,7 @@ pub enum Action {
     ImageLoaderReady(Arc<ImageLoader>),
     PasteImageLoaded(std::path::PathBuf),
     PasteTextLoaded(String),
+    RemoteAuth(crate::infrastructure::discord::remote_auth::RemoteAuthEvent),
     ShowNotification(String),
     SearchResults(Vec<crate::domain::search::SearchResult>),
 }
@@ -102,9 +137,20 @@ pub enum BackendCommand {
         before_message_id: MessageId,
         token: AuthToken,
     },
+    BackfillMessages {
+        channel_id: ChannelId,
+        after: MessageId,
+        token: AuthToken,
+    },
     SendMessage {
         token: AuthToken,
         request: SendMessageRequest,
+        local_id: MessageId,
+    },
+    SendQueuedMessage {
+        token: AuthToken,
+        request: SendMessageRequest,
+        local_id: Option<MessageId>,
     },
     EditMessage {
         token: AuthToken,
@@ -115,6 +161,31 @@ pub enum BackendCommand {
         channel_id: ChannelId,
         message_id: MessageId,
     },
+    /// Deletes messages one by one, spaced out to stay under the rate limit.
+    PurgeMessages {
+        token: AuthToken,
+        channel_id: ChannelId,
+        message_ids: Vec<MessageId>,
+    },
+    CrosspostMessage {
+        token: AuthToken,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
+    SetNickname {
+        token: AuthToken,
+        guild_id: GuildId,
+        nick: Option<String>,
+    },
+    CreateGroupDm {
+        token: AuthToken,
+        user_ids: Vec<String>,
+    },
+    AddGroupRecipients {
+        token: AuthToken,
+        channel_id: ChannelId,
+        user_ids: Vec<String>,
+    },
     SendTypingIndicator {
         channel_id: ChannelId,
         token: AuthToken,
@@ -131,9 +202,77 @@ pub enum BackendCommand {
         initial_channel_id: Option<ChannelId>,
         recents: Vec<crate::domain::search::RecentItem>,
         sort_mode: crate::infrastructure::config::app_config::QuickSwitcherSortMode,
+        expanded_nodes: Vec<String>,
+        pinned: Vec<crate::domain::search::RecentItem>,
     },
 }
 
+/// Gap between DELETEs in a purge; Discord allows about five per channel
+/// every five seconds.
+const PURGE_DELETE_INTERVAL: Duration = Duration::from_millis(1100);
+
+/// Most pages of 100 a reconnect backfill fetches; past this the channel
+/// reloads its latest page instead and the gap is dropped.
+const MAX_BACKFILL_PAGES: usize = 5;
+
+/// Pages forward from `after` until Discord has nothing newer, so a short
+/// disconnect leaves no gap. Each page is merged as it arrives.
+async fn backfill_messages(
+    discord_data: Arc<dyn DiscordDataPort>,
+    action_tx: mpsc::UnboundedSender<Action>,
+    token: AuthToken,
+    channel_id: ChannelId,
+    after: MessageId,
+) {
+    let mut cursor = after;
+    for _ in 0..MAX_BACKFILL_PAGES {
+        let options = FetchMessagesOptions::default()
+            .with_limit(100)
+            .after_message(cursor.as_u64());
+        let messages = match discord_data
+            .fetch_messages(&token, channel_id.as_u64(), options)
+            .await
+        {
+            Ok(messages) => messages,
+            Err(e) => {
+                warn!(channel_id = %channel_id, error = %e, "Failed to backfill messages");
+                return;
+            }
+        };
+        // Paging stops on an empty page rather than a short one, since
+        // messages that fail to parse are dropped from the page.
+        let Some(newest) = messages
+            .iter()
+            .map(Message::id)
+            .max_by_key(|id| id.as_u64())
+            .filter(|id| id.as_u64() > cursor.as_u64())
+        else {
+            return;
+        };
+        debug!(channel_id = %channel_id, count = messages.len(), "Backfilled messages");
+        cursor = newest;
+        let _ = action_tx.send(Action::MessagesBackfilled {
+            channel_id,
+            messages,
+        });
+    }
+
+    info!(channel_id = %channel_id, "Backfill too long, reloading latest messages");
+    let options = FetchMessagesOptions::default().with_limit(50);
+    match discord_data
+        .fetch_messages(&token, channel_id.as_u64(), options)
+        .await
+    {
+        Ok(messages) => {
+            let _ = action_tx.send(Action::ChannelMessagesLoaded {
+                channel_id,
+                messages,
+            });
+        }
+        Err(e) => warn!(channel_id = %channel_id, error = %e, "Failed to reload messages"),
+    }
+}
+
 pub struct Backend {
     discord_data: Arc<dyn DiscordDataPort>,
     command_rx: mpsc::UnboundedReceiver<BackendCommand>,
@@ -278,15 +417,62 @@ impl Backend {
                     }
                 }
             }
-            BackendCommand::SendMessage { token, request } => {
-                match self.discord_data.send_message(&token, request).await {
+            BackendCommand::BackfillMessages {
+                channel_id,
+                after,
+                token,
+            } => {
+                tokio::spawn(backfill_messages(
+                    Arc::clone(&self.discord_data),
+                    self.action_tx.clone(),
+                    token,
+                    channel_id,
+                    after,
+                ));
+            }
+            BackendCommand::SendMessage {
+                token,
+                request,
+                local_id,
+            } => match self.discord_data.send_message(&token, request).await {
+                Ok(message) => {
+                    info!(message_id = %message.id(), "Message sent successfully");
+                    let _ = self.action_tx.send(Action::MessageSent {
+                        message,
+                        local_id: Some(local_id),
+                    });
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to send message");
+                    let _ = self.action_tx.send(Action::MessageSendError {
+                        local_id,
+                        error: e.to_string(),
+                    });
+                }
+            },
+            BackendCommand::SendQueuedMessage {
+                token,
+                request,
+                local_id,
+            } => {
+                match self
+                    .discord_data
+                    .send_message(&token, request.clone())
+                    .await
+                {
                     Ok(message) => {
-                        info!(message_id = %message.id(), "Message sent successfully");
-                        let _ = self.action_tx.send(Action::MessageSent(message));
+                        info!(message_id = %message.id(), "Queued message sent successfully");
+                        let _ = self
+                            .action_tx
+                            .send(Action::MessageSent { message, local_id });
                     }
                     Err(e) => {
-                        error!(error = %e, "Failed to send message");
-                        let _ = self.action_tx.send(Action::MessageSendError(e.to_string()));
+                        warn!(channel_id = %request.channel_id, error = %e, "Failed to send queued message");
+                        let _ = self.action_tx.send(Action::QueuedMessageFailed {
+                            request,
+                            local_id,
+                            error: e.to_string(),
+                        });
                     }
                 }
             }
@@ -324,29 +510,135 @@ impl Backend {
                     }
                 }
             }
-            BackendCommand::SendTypingIndicator { channel_id, token } => {
-                if let Err(e) = self
+            BackendCommand::PurgeMessages {
+                token,
+                channel_id,
+                message_ids,
+            } => {
+                let discord_data = Arc::clone(&self.discord_data);
+                let action_tx = self.action_tx.clone();
+                tokio::spawn(async move {
+                    let total = message_ids.len();
+                    let mut deleted = 0;
+                    for (index, message_id) in message_ids.into_iter().enumerate() {
+                        if index > 0 {
+                            tokio::time::sleep(PURGE_DELETE_INTERVAL).await;
+                        }
+                        match discord_data
+                            .delete_message(&token, channel_id, message_id)
+                            .await
+                        {
+                            Ok(()) => {
+                                deleted += 1;
+                                let _ = action_tx.send(Action::MessageDeleted(message_id));
+                            }
+                            Err(e) => {
+                                error!(error = %e, "Failed to delete message during purge");
+                                let _ = action_tx.send(Action::MessageDeleteError(e.to_string()));
+                                break;
+                            }
+                        }
+                    }
+                    info!(deleted, total, "Purge finished");
+                    let _ = action_tx.send(Action::ShowNotification(format!(
+                        "Deleted {deleted} of {total} messages"
+                    )));
+                });
+            }
+            BackendCommand::CrosspostMessage {
+                token,
+                channel_id,
+                message_id,
+            } => {
+                match self
                     .discord_data
-                    .send_typing_indicator(&token, channel_id)
+                    .crosspost_message(&token, channel_id, message_id)
                     .await
                 {
-                    debug!(error = %e, "Failed to send typing indicator");
-                } else {
-                    let _ = self.action_tx.send(Action::TypingIndicatorSent(channel_id));
+                    Ok(message) => {
+                        info!(message_id = %message_id, "Message published successfully");
+                        let _ = self.action_tx.send(Action::MessagePublished(message));
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Failed to publish message");
+                        let _ = self
+                            .action_tx
+                            .send(Action::MessagePublishError(e.to_string()));
+                    }
+                }
+            }
+            BackendCommand::SetNickname {
+                token,
+                guild_id,
+                nick,
+            } => match self.discord_data.set_nickname(&token, guild_id, nick).await {
+                Ok(member) => {
+                    info!(guild_id = %guild_id, "Nickname updated");
+                    let _ = self
+                        .action_tx
+                        .send(Action::NicknameChanged { guild_id, member });
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to set nickname");
+                    let _ = self.action_tx.send(Action::NicknameError(e.to_string()));
+                }
+            },
+            BackendCommand::CreateGroupDm { token, user_ids } => {
+                match self.discord_data.create_group_dm(&token, &user_ids).await {
+                    Ok(dm) => {
+                        info!(channel_id = %dm.channel_id, "Group DM created");
+                        let _ = self.action_tx.send(Action::GroupDmCreated(dm));
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Failed to create group DM");
+                        let _ = self.action_tx.send(Action::GroupDmError(e.to_string()));
+                    }
                 }
             }
+            BackendCommand::AddGroupRecipients {
+                token,
+                channel_id,
+                user_ids,
+            } => {
+                let action = match self
+                    .add_group_recipients(&token, channel_id, &user_ids)
+                    .await
+                {
+                    Ok(dm) => Action::GroupDmUpdated(dm),
+                    Err(e) => {
+                        error!(error = %e, "Failed to add group DM recipients");
+                        Action::GroupDmError(e.to_string())
+                    }
+                };
+                let _ = self.action_tx.send(action);
+            }
+            // Typing and ack requests run on their own tasks so a rate limit
+            // on them never holds up sends and history loads behind them.
+            BackendCommand::SendTypingIndicator { channel_id, token } => {
+                let discord_data = Arc::clone(&self.discord_data);
+                let action_tx = self.action_tx.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = discord_data.send_typing_indicator(&token, channel_id).await {
+                        debug!(error = %e, "Failed to send typing indicator");
+                    } else {
+                        let _ = action_tx.send(Action::TypingIndicatorSent(channel_id));
+                    }
+                });
+            }
             BackendCommand::AcknowledgeMessage {
                 channel_id,
                 message_id,
                 token,
             } => {
-                if let Err(e) = self
-                    .discord_data
-                    .acknowledge_message(&token, channel_id, message_id)
-                    .await
-                {
-                    warn!(error = %e, "Failed to ack message");
-                }
+                let discord_data = Arc::clone(&self.discord_data);
+                tokio::spawn(async move {
+                    if let Err(e) = discord_data
+                        .acknowledge_message(&token, channel_id, message_id)
+                        .await
+                    {
+                        warn!(error = %e, "Failed to ack message");
+                    }
+                });
             }
             BackendCommand::LoadInitialData {
                 token,
@@ -355,6 +647,8 @@ impl Backend {
                 initial_channel_id,
                 recents,
                 sort_mode,
+                expanded_nodes,
+                pinned,
             } => {
                 let guilds_future = self.discord_data.fetch_guilds(&token);
                 let dms_future = self.discord_data.fetch_dm_channels(&token);
@@ -432,8 +726,124 @@ impl Backend {
                     initial_messages,
                     recents,
                     sort_mode,
+                    expanded_nodes,
+                    pinned,
                 });
             }
         }
     }
+
+    /// Adds each user to a group DM, then refetches it so the new
+    /// participants show up.
+    async fn add_group_recipients(
+        &self,
+        token: &AuthToken,
+        channel_id: ChannelId,
+        user_ids: &[String],
+    ) -> Result<DirectMessageChannel, crate::domain::errors::AuthError> {
+        for user_id in user_ids {
+            self.discord_data
+                .add_group_recipient(token, channel_id, user_id)
+                .await?;
+        }
+        info!(channel_id = %channel_id, count = user_ids.len(), "Group DM recipients added");
+        let channel_id = channel_id.to_string();
+        self.discord_data
+            .fetch_dm_channels(token)
+            .await?
+            .into_iter()
+            .find(|dm| dm.channel_id == channel_id)
+            .ok_or_else(|| crate::domain::errors::AuthError::unexpected("group DM not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::DmKind;
+    use crate::domain::ports::mocks::MockDiscordData;
+
+    fn create_test_backend(
+        data: Arc<MockDiscordData>,
+    ) -> (Backend, mpsc::UnboundedReceiver<Action>) {
+        let (_command_tx, command_rx) = mpsc::unbounded_channel();
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        (Backend::new(data, command_rx, action_tx), action_rx)
+    }
+
+    fn recipient_ids(dm: &DirectMessageChannel) -> Vec<&str> {
+        match &dm.kind {
+            DmKind::Group { recipients, .. } => recipients.iter().map(|r| r.id.as_str()).collect(),
+            DmKind::Direct => vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_group_dm_reports_new_channel() {
+        let data = Arc::new(MockDiscordData::new());
+        let (backend, mut action_rx) = create_test_backend(data.clone());
+
+        backend
+            .handle_command(BackendCommand::CreateGroupDm {
+                token: AuthToken::new_unchecked("test-token"),
+                user_ids: vec!["1".to_string(), "2".to_string()],
+            })
+            .await;
+
+        let Ok(Action::GroupDmCreated(dm)) = action_rx.try_recv() else {
+            panic!("expected GroupDmCreated");
+        };
+        assert!(dm.is_group());
+        assert_eq!(recipient_ids(&dm), ["1", "2"]);
+        assert_eq!(
+            *data.created_groups.lock().unwrap(),
+            [vec!["1".to_string(), "2".to_string()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_group_recipients_reports_updated_channel() {
+        let data = Arc::new(MockDiscordData::new());
+        let (backend, mut action_rx) = create_test_backend(data.clone());
+        let token = AuthToken::new_unchecked("test-token");
+        let group = data
+            .create_group_dm(&token, &["1".to_string()])
+            .await
+            .unwrap();
+        let channel_id = ChannelId(group.channel_id.parse().unwrap());
+
+        backend
+            .handle_command(BackendCommand::AddGroupRecipients {
+                token,
+                channel_id,
+                user_ids: vec!["2".to_string(), "3".to_string()],
+            })
+            .await;
+
+        let Ok(Action::GroupDmUpdated(dm)) = action_rx.try_recv() else {
+            panic!("expected GroupDmUpdated");
+        };
+        assert_eq!(dm.channel_id, group.channel_id);
+        assert_eq!(recipient_ids(&dm), ["1", "2", "3"]);
+        assert_eq!(
+            *data.added_recipients.lock().unwrap(),
+            [(channel_id, "2".to_string()), (channel_id, "3".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_group_recipients_reports_failure() {
+        let data = Arc::new(MockDiscordData::new());
+        let (backend, mut action_rx) = create_test_backend(data);
+
+        backend
+            .handle_command(BackendCommand::AddGroupRecipients {
+                token: AuthToken::new_unchecked("test-token"),
+                channel_id: ChannelId(404),
+                user_ids: vec!["2".to_string()],
+            })
+            .await;
+
+        assert!(matches!(action_rx.try_recv(), Ok(Action::GroupDmError(_))));
+    }
 }
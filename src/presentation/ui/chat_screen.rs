@@ -4,36 +4,49 @@ use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 use tachyonfx::{Effect, Interpolation, fx};
 
-use crate::application::services::autocomplete_service::AutocompleteService;
+use crate::application::services::autocomplete_service::{
+    AutocompleteService, MentionCandidate, MentionKind,
+};
 use crate::application::services::identity_resolver::IdentityResolver;
+use crate::application::services::markdown_parser::{Link, mentions_everyone};
 use crate::application::services::message_content_service::{
     MessageContentAction, MessageContentService,
 };
 use crate::domain::ConnectionStatus;
 use crate::domain::entities::{
     CachedUser, Channel, ChannelId, ChannelKind, Guild, GuildFolder, GuildId, Member, Message,
-    MessageId, Permissions, RelationshipState, Role, User, UserCache,
+    MessageAuthor, MessageId, MessageKind, Permissions, RelationshipState, Role, User, UserCache,
+    UserId, VoiceState,
 };
 use crate::domain::keybinding::{Action, Keybind};
-use crate::domain::ports::DirectMessageChannel;
+use crate::domain::ports::{DirectMessageChannel, DmKind, OutgoingAttachment, SendMessageRequest};
 use crate::domain::search::{SearchKind, SearchPrefix, SearchResult, parse_search_query};
 use crate::domain::services::permission_calculator::PermissionCalculator;
-use crate::infrastructure::config::app_config::QuickSwitcherSortMode;
-use crate::infrastructure::search::{ChannelSearchProvider, DmSearchProvider, GuildSearchProvider};
-use crate::presentation::commands::{CommandRegistry, HasCommands};
+use crate::infrastructure::config::app_config::{
+    FileExplorerConfig, MessageDensity, QuickSwitcherSection, QuickSwitcherSortMode, StartupFocus,
+    TimestampPosition,
+};
+use crate::infrastructure::search::{
+    ChannelSearchProvider, DmSearchProvider, GuildSearchProvider, channel_subtitle,
+};
+use crate::presentation::commands::{CommandRegistry, HasCommands, SequenceMatch};
 use crate::presentation::services::markdown_renderer::MarkdownRenderer;
 
 use crate::presentation::theme::Theme;
 use crate::presentation::ui::quick_switcher::{
     QuickSwitcher, QuickSwitcherAction, QuickSwitcherWidget,
 };
-use crate::presentation::ui::utils::sanitize_channel_name;
+use crate::presentation::ui::utils::{
+    DEFAULT_ELLIPSIS, RELATIVE_TIMESTAMP_FORMAT, sanitize_channel_name, truncate_to_width,
+};
 use crate::presentation::widgets::{
-    ConfirmationModal, FileExplorerAction, FileExplorerComponent, FocusContext, FooterBar,
-    ForumState, GuildsTree, GuildsTreeAction, GuildsTreeData, GuildsTreeState, HeaderBar,
+    CodeBlockChooser, CodeBlockChooserState, ConfirmationModal, FileExplorerAction,
+    FileExplorerComponent, FocusContext, FooterBar, FooterBarStyle, ForumState, GuildsTree,
+    GuildsTreeAction, GuildsTreeData, GuildsTreeState, GuildsTreeStyle, HeaderBar, HeaderBarStyle,
     ImageManager, MentionPopup, MessageInput, MessageInputAction, MessageInputMode,
-    MessageInputState, MessagePane, MessagePaneAction, MessagePaneData, MessagePaneState,
-    TreeNodeId, ViewMode,
+    MessageInputState, MessageInputStyle, MessagePane, MessagePaneAction, MessagePaneData,
+    MessagePaneState, MessagePaneStyle, SendState, SortedGuildChannels, TreeNodeId, ViewMode,
+    VoiceParticipant,
 };
 use ratatui::{
     buffer::Buffer,
@@ -45,6 +58,18 @@ use crate::{NAME, VERSION};
 
 const GUILDS_TREE_WIDTH_PERCENT: u16 = 25;
 const GUILDS_TREE_MIN_WIDTH: u16 = 20;
+/// Width of the guilds tree when it shows initials only.
+const COMPACT_GUILDS_TREE_WIDTH: u16 = 12;
+/// How often relative timestamps are redrawn while idle.
+const RELATIVE_TIMESTAMP_REFRESH: Duration = Duration::from_secs(30);
+/// Shortest gap between read acks sent for one channel as messages arrive.
+const READ_ACK_INTERVAL: Duration = Duration::from_secs(3);
+/// Discord's limit on guild nickname length.
+const MAX_NICKNAME_LENGTH: usize = 32;
+
+const READ_ONLY_NOTICE: &str = "You do not have permission to send messages here";
+
+const UNSENT_NOTICE: &str = "This message has not been sent yet";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChatFocus {
@@ -100,6 +125,124 @@ impl ChatFocus {
     }
 }
 
+impl From<StartupFocus> for ChatFocus {
+    fn from(focus: StartupFocus) -> Self {
+        match focus {
+            StartupFocus::Guilds => Self::GuildsTree,
+            StartupFocus::Messages => Self::MessagesList,
+            StartupFocus::Input => Self::MessageInput,
+        }
+    }
+}
+
+/// Action waiting on the confirmation modal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PendingConfirmation {
+    DeleteMessage(MessageId),
+    /// Your own recent messages in the open channel, newest first.
+    PurgeMessages(Vec<MessageId>),
+    OpenLink(Link),
+    /// A send held back because it pings `@everyone` or `@here`; `mode` is
+    /// the input mode to restore if the send is cancelled.
+    SendEveryone {
+        content: String,
+        reply_to: Option<MessageId>,
+        attachments: Vec<OutgoingAttachment>,
+        mode: MessageInputMode,
+    },
+    Logout {
+        secure: bool,
+        draft_count: usize,
+        return_focus: ChatFocus,
+    },
+    /// Pasted text longer than the large paste threshold.
+    LargePaste(String),
+}
+
+impl PendingConfirmation {
+    const fn title(&self) -> &'static str {
+        match self {
+            Self::DeleteMessage(_) => "Delete Message",
+            Self::PurgeMessages(_) => "Purge Messages",
+            Self::OpenLink(_) => "Open Link",
+            Self::SendEveryone { .. } => "Mention Everyone",
+            Self::Logout { .. } => "Unsent Drafts",
+            Self::LargePaste(_) => "Large Paste",
+        }
+    }
+
+    const fn confirm_label(&self) -> &'static str {
+        match self {
+            Self::LargePaste(_) => "Send as File",
+            _ => "Confirm",
+        }
+    }
+
+    const fn alternative(&self) -> Option<&'static str> {
+        match self {
+            Self::LargePaste(_) => Some("Keep as Text (t)"),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Self::DeleteMessage(_) => "Are you sure you want to delete this message?".to_string(),
+            Self::PurgeMessages(ids) => {
+                let messages = if ids.len() == 1 {
+                    "your last message".to_string()
+                } else {
+                    format!("your last {} messages", ids.len())
+                };
+                format!("Delete {messages} in this channel? This cannot be undone.")
+            }
+            Self::OpenLink(link) => format!(
+                "\"{}\" links to:\n{}\n\nOpen it in your browser?",
+                link.text, link.url
+            ),
+            Self::SendEveryone { .. } => {
+                "This message pings @everyone or @here. Send it anyway?".to_string()
+            }
+            Self::Logout { draft_count, .. } => {
+                let channels = if *draft_count == 1 {
+                    "1 channel".to_string()
+                } else {
+                    format!("{draft_count} channels")
+                };
+                format!("You have unsent drafts in {channels}. Log out and discard them?")
+            }
+            Self::LargePaste(text) => format!(
+                "This paste is {} characters long. Send it as a text file instead?",
+                text.chars().count()
+            ),
+        }
+    }
+}
+
+/// Widget styles derived from the active theme.
+///
+/// Rebuilt only when the active accent changes, instead of on every render.
+#[derive(Clone)]
+struct ThemeStyles {
+    header: HeaderBarStyle,
+    footer: FooterBarStyle,
+    guilds_tree: GuildsTreeStyle,
+    message_pane: MessagePaneStyle,
+    message_input: MessageInputStyle,
+}
+
+impl ThemeStyles {
+    fn from_theme(theme: &Theme) -> Self {
+        Self {
+            header: HeaderBarStyle::from_theme(theme),
+            footer: FooterBarStyle::from_theme(theme),
+            guilds_tree: GuildsTreeStyle::from_theme(theme),
+            message_pane: MessagePaneStyle::from_theme(theme),
+            message_input: MessageInputStyle::from_theme(theme),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChatKeyResult {
     Consumed,
@@ -108,6 +251,8 @@ pub enum ChatKeyResult {
     Logout,
     SecureLogout,
     CopyToClipboard(String),
+    /// Copy the contents of a single code block.
+    CopyCodeBlock(String),
     CopyImageToClipboard(crate::domain::entities::ImageId),
     LoadGuildChannels(GuildId),
     LoadChannelMessages {
@@ -135,15 +280,46 @@ pub enum ChatKeyResult {
         channel_id: ChannelId,
         before_message_id: MessageId,
     },
+    /// Fetch messages the open channel received while we were offline.
+    BackfillChannel {
+        channel_id: ChannelId,
+        after: MessageId,
+    },
     EditMessage(crate::domain::entities::MessageId),
     DeleteMessage(crate::domain::entities::MessageId),
+    /// Delete these messages one at a time, newest first.
+    PurgeMessages(Vec<MessageId>),
     OpenAttachments(crate::domain::entities::MessageId),
     OpenLink(String),
     JumpToMessage(crate::domain::entities::MessageId),
+    RetrySend(MessageId),
+    /// Crosspost a message from an announcement channel to its followers.
+    PublishMessage(MessageId),
+    SetNickname {
+        guild_id: GuildId,
+        nick: Option<String>,
+    },
+    CreateGroupDm {
+        user_ids: Vec<String>,
+    },
+    AddGroupRecipients {
+        channel_id: ChannelId,
+        user_ids: Vec<String>,
+    },
+    /// Sync a channel's read position to Discord.
+    AcknowledgeMessage {
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
     SendMessage {
         content: String,
         reply_to: Option<MessageId>,
-        attachments: Vec<std::path::PathBuf>,
+        /// Ping the author of the replied-to message.
+        mention_reply: bool,
+        /// Sent from the `@everyone` prompt, so mass mentions ping even
+        /// when they are suppressed by default.
+        mass_mention_confirmed: bool,
+        attachments: Vec<OutgoingAttachment>,
     },
     StartTyping,
     OpenEditor {
@@ -153,6 +329,14 @@ pub enum ChatKeyResult {
     Paste,
     ToggleHelp,
     ToggleDisplayName,
+    ToggleImagePreview,
+    ToggleNotificationHistory,
+    /// Write a large paste to a text file and attach it.
+    PasteAsFile(String),
+    /// Recents were cleared and should be saved.
+    RecentsCleared,
+    /// A channel was pinned or unpinned and the pins should be saved.
+    PinsChanged,
     JumpToChannel(ChannelId),
     RequestChannelFetch(Vec<ChannelId>),
     ShowNotification(String),
@@ -197,15 +381,19 @@ impl StatefulWidget for ChatScreen {
             widget.render(area, buf);
         }
 
-        if state.focus == ChatFocus::ConfirmationModal {
-            let modal = ConfirmationModal::new(
-                "Delete Message",
-                "Are you sure you want to delete this message?",
-                state.theme,
-            );
+        if state.focus == ChatFocus::ConfirmationModal
+            && let Some(pending) = &state.pending_confirmation
+        {
+            let modal = ConfirmationModal::new(pending.title(), pending.message(), state.theme)
+                .with_confirm_label(pending.confirm_label())
+                .with_alternative(pending.alternative());
             modal.render(area, buf);
         }
 
+        if let Some(chooser) = &state.code_block_chooser {
+            CodeBlockChooser::new(chooser, &state.theme).render(area, buf);
+        }
+
         if state.show_help {
             render_help_popup(state, area, buf);
         }
@@ -283,6 +471,9 @@ fn render_help_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer)
             (Action::FocusInput, "Focus Input"),
             (Action::ToggleGuildsTree, "Toggle Guilds Tree"),
             (Action::ToggleQuickSwitcher, "Quick Switcher"),
+            (Action::CopyUnreadChannels, "Copy Unread Channels"),
+            (Action::ToggleNotificationHistory, "Notification History"),
+            (Action::ClearRecents, "Clear Recents"),
         ],
     )];
 
@@ -308,9 +499,21 @@ fn render_help_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer)
             (Action::CopyContent, "Copy Content"),
             (Action::CopyImage, "Copy Image"),
             (Action::YankId, "Copy Message ID"),
+            (Action::CopyMessageLink, "Copy Message Link"),
+            (Action::CopyCodeBlock, "Copy Code Block"),
             (Action::OpenAttachments, "Open Image"),
             (Action::JumpToReply, "Jump to Reply"),
+            (Action::ToggleThreadList, "Thread List"),
+            (Action::RetrySend, "Retry Failed Send"),
+            (Action::ToggleMessageExpand, "Expand/Collapse Message"),
+            (Action::PublishMessage, "Publish Announcement"),
+            (Action::PurgeOwnMessages, "Purge My Messages"),
+            (Action::NextMention, "Next Mention"),
+            (Action::PreviousMention, "Previous Mention"),
+            (Action::JumpToLatest, "Jump to Latest"),
             (Action::ToggleDisplayName, "Toggle Display Name"),
+            (Action::ToggleImagePreview, "Toggle Image Preview"),
+            (Action::ChangeNickname, "Change Nickname"),
         ],
     )];
 
@@ -322,6 +525,7 @@ fn render_help_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer)
             (Action::Paste, "Paste (Text/Image)"),
             (Action::OpenEditor, "Open External Editor"),
             (Action::ClearInput, "Clear Input"),
+            (Action::ToggleReplyMention, "Toggle Reply Ping"),
             (Action::Cancel, "Cancel Reply / Exit"),
         ],
     )];
@@ -433,13 +637,10 @@ fn render_help_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer)
 }
 
 fn render_explorer_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer) {
+    let tree_constraint = guilds_tree_constraint(state);
     if let Some(explorer) = &mut state.file_explorer {
         let content_area = if state.guilds_tree_visible {
-            let chunks = Layout::horizontal([
-                Constraint::Percentage(GUILDS_TREE_WIDTH_PERCENT),
-                Constraint::Min(0),
-            ])
-            .split(area);
+            let chunks = Layout::horizontal([tree_constraint, Constraint::Min(0)]).split(area);
             chunks[1]
         } else {
             area
@@ -463,19 +664,26 @@ fn render_explorer_popup(state: &mut ChatScreenState, area: Rect, buf: &mut Buff
     }
 }
 
-fn render_header_bar(state: &ChatScreenState, area: Rect, buf: &mut Buffer) {
-    use crate::presentation::widgets::HeaderBarStyle;
+/// Whether the key would insert text when typed into the message input.
+fn is_text_key(key: KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char(_)) && (key.modifiers - KeyModifiers::SHIFT).is_empty()
+}
 
-    let style = HeaderBarStyle::from_theme(&state.theme);
+fn render_header_bar(state: &ChatScreenState, area: Rect, buf: &mut Buffer) {
+    let style = state.styles.header.clone();
     let header = HeaderBar::new(NAME, VERSION)
         .style(style)
-        .connection_status(state.connection_status());
+        .connection_status(state.connection_status())
+        .latency_ms(state.latency_ms)
+        .mention_count(if state.show_mention_count {
+            state.total_mentions
+        } else {
+            0
+        });
     Widget::render(header, area, buf);
 }
 
 fn render_footer_bar(state: &ChatScreenState, area: Rect, buf: &mut Buffer) {
-    use crate::presentation::widgets::FooterBarStyle;
-
     let focus_context = state.focus().to_focus_context();
     let message_count = state.message_pane_data().message_count();
 
@@ -487,10 +695,12 @@ fn render_footer_bar(state: &ChatScreenState, area: Rect, buf: &mut Buffer) {
 
     let commands = state.get_commands(&state.registry);
 
-    let style = FooterBarStyle::from_theme(&state.theme);
+    let style = state.styles.footer.clone();
     let footer = FooterBar::new(&commands)
         .style(style)
         .focus_context(focus_context)
+        .pending_sequence(state.registry.pending_prefix())
+        .ellipsis(&state.ellipsis)
         .right_info(if right_info.is_empty() {
             None
         } else {
@@ -499,15 +709,24 @@ fn render_footer_bar(state: &ChatScreenState, area: Rect, buf: &mut Buffer) {
     Widget::render(footer, area, buf);
 }
 
+/// The compact tree keeps a fixed narrow width; otherwise it takes a share
+/// of the screen.
+const fn guilds_tree_constraint(state: &ChatScreenState) -> Constraint {
+    if state.compact_guilds_tree {
+        Constraint::Length(COMPACT_GUILDS_TREE_WIDTH)
+    } else {
+        Constraint::Percentage(GUILDS_TREE_WIDTH_PERCENT)
+    }
+}
+
 fn render_content_area(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer) {
     if state.guilds_tree_visible {
-        let content_layout = Layout::horizontal([
-            Constraint::Percentage(GUILDS_TREE_WIDTH_PERCENT),
-            Constraint::Min(0),
-        ]);
+        let content_layout =
+            Layout::horizontal([guilds_tree_constraint(state), Constraint::Min(0)]);
         let [guilds_area, messages_area] = content_layout.areas(area);
 
-        let guilds_area = if guilds_area.width < GUILDS_TREE_MIN_WIDTH {
+        let guilds_area = if !state.compact_guilds_tree && guilds_area.width < GUILDS_TREE_MIN_WIDTH
+        {
             Rect {
                 width: GUILDS_TREE_MIN_WIDTH,
                 ..guilds_area
@@ -524,23 +743,24 @@ fn render_content_area(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer
 }
 
 fn render_guilds_tree(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer) {
-    use crate::presentation::widgets::GuildsTreeStyle;
-
-    let style = GuildsTreeStyle::from_theme(&state.theme);
+    let style = state.styles.guilds_tree.clone();
     let use_display_name = state.use_display_name;
+    let compact = state.compact_guilds_tree;
+    let ellipsis = state.ellipsis.clone();
     let (data, tree_state) = state.guilds_tree_parts_mut();
     let tree = GuildsTree::new(data)
         .style(style)
-        .use_display_name(use_display_name);
+        .use_display_name(use_display_name)
+        .compact(compact)
+        .ellipsis(&ellipsis);
     StatefulWidget::render(tree, area, buf, tree_state);
 }
 
 fn render_message_pane(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer) {
-    use crate::presentation::widgets::MessagePaneStyle;
-
     let service = state.markdown_service.clone();
     let disable_user_colors = state.disable_user_colors;
-    let image_preview = state.image_preview;
+    let image_preview = state.inline_images();
+    let image_placeholders = !image_preview;
     let timestamp_format = state.timestamp_format.clone();
     let relationship_state = state.relationship_state.clone();
     let hide_blocked_completely = state.hide_blocked_completely;
@@ -552,11 +772,12 @@ fn render_message_pane(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer
         state.theme.accent,
         state.message_pane_state.show_spoilers,
         image_preview,
+        state.message_pane_state.expanded_messages(),
     );
 
     state.update_visible_image_protocols(inner_width);
 
-    let style = MessagePaneStyle::from_theme(&state.theme);
+    let style = state.styles.message_pane.clone();
     let current_user_id = state.user().id().to_string();
     let (data, pane_state) = state.message_pane_parts_mut();
 
@@ -564,6 +785,7 @@ fn render_message_pane(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer
         .style(style)
         .with_disable_user_colors(disable_user_colors)
         .with_image_preview(image_preview)
+        .with_image_placeholders(image_placeholders)
         .with_timestamp_format(&timestamp_format)
         .with_current_user_id(current_user_id)
         .with_relationship_state(&relationship_state)
@@ -572,9 +794,7 @@ fn render_message_pane(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer
 }
 
 fn render_message_input(state: &mut ChatScreenState, area: Rect, buf: &mut Buffer) {
-    use crate::presentation::widgets::MessageInputStyle;
-
-    let style = MessageInputStyle::from_theme(&state.theme);
+    let style = state.styles.message_input.clone();
     MessageInput::new()
         .style(style)
         .render(state.message_input_parts_mut(), area, buf);
@@ -598,11 +818,11 @@ fn render_messages_area(state: &mut ChatScreenState, area: Rect, buf: &mut Buffe
             popup_height,
         );
         let mut autocomplete_state = state.autocomplete_service.state().clone();
-        MentionPopup::new(IdentityResolver::with_preference(state.use_display_name)).render(
-            popup_area,
-            buf,
-            &mut autocomplete_state,
-        );
+        MentionPopup::new(
+            IdentityResolver::with_preference(state.use_display_name),
+            &state.theme,
+        )
+        .render(popup_area, buf, &mut autocomplete_state);
     }
 }
 
@@ -610,6 +830,7 @@ fn render_messages_area(state: &mut ChatScreenState, area: Rect, buf: &mut Buffe
 pub struct DmChannelInfo {
     channel_id: ChannelId,
     recipient_name: String,
+    is_group: bool,
 }
 
 impl DmChannelInfo {
@@ -618,9 +839,21 @@ impl DmChannelInfo {
         Self {
             channel_id,
             recipient_name,
+            is_group: false,
         }
     }
 
+    #[must_use]
+    pub const fn with_group(mut self, is_group: bool) -> Self {
+        self.is_group = is_group;
+        self
+    }
+
+    #[must_use]
+    pub const fn is_group(&self) -> bool {
+        self.is_group
+    }
+
     #[must_use]
     pub const fn channel_id(&self) -> ChannelId {
         self.channel_id
@@ -632,6 +865,13 @@ impl DmChannelInfo {
     }
 }
 
+/// Unsent input kept for a channel while another one is open.
+#[derive(Debug, Clone)]
+struct MessageDraft {
+    content: String,
+    attachments: Vec<OutgoingAttachment>,
+}
+
 #[allow(clippy::struct_excessive_bools)]
 pub struct ChatScreenState {
     user: User,
@@ -649,34 +889,100 @@ pub struct ChatScreenState {
     dm_channels: std::collections::HashMap<String, DmChannelInfo>,
     read_states: std::collections::HashMap<ChannelId, crate::domain::entities::ReadState>,
     connection_status: ConnectionStatus,
+    /// Set once the first connection succeeds; later ones are reconnects.
+    has_connected: bool,
+    /// Show toasts when the connection drops and comes back.
+    internal_notifications: bool,
+    /// Last gateway heartbeat round trip.
+    latency_ms: Option<u64>,
+    /// Sum of unread mentions over all read states.
+    total_mentions: u32,
+    show_mention_count: bool,
     markdown_service: Arc<MarkdownRenderer>,
     file_explorer: Option<FileExplorerComponent>,
     show_file_explorer: bool,
+    file_explorer_config: FileExplorerConfig,
+    /// Where the file explorer was last left this session.
+    file_explorer_last_dir: Option<std::path::PathBuf>,
     show_help: bool,
     registry: CommandRegistry,
     entrance_effect: Effect,
     pending_duration: Duration,
     has_entered: bool,
+    /// Time since relative timestamps were last redrawn.
+    timestamp_clock: Duration,
+    /// Time left before each recently acked channel may be acked again.
+    read_ack_cooldowns: std::collections::HashMap<ChannelId, Duration>,
+    /// Newest message to ack for channels still cooling down.
+    pending_read_acks: std::collections::HashMap<ChannelId, MessageId>,
     /// Image manager for rendering image attachments.
     image_manager: ImageManager,
     disable_user_colors: bool,
     use_display_name: bool,
     image_preview: bool,
     timestamp_format: String,
+    /// Active theme, with any accent override for the selection applied.
     theme: Theme,
+    /// Global theme that accent overrides fall back to.
+    base_theme: Theme,
+    /// Themes keyed by guild or channel id, built from `accent_overrides`.
+    accent_overrides: std::collections::HashMap<u64, Theme>,
+    styles: ThemeStyles,
     forum_states: std::collections::HashMap<ChannelId, crate::presentation::widgets::ForumState>,
-    pending_deletion_id: Option<MessageId>,
+    pending_confirmation: Option<PendingConfirmation>,
+    confirm_logout_with_drafts: bool,
+    large_paste_threshold: usize,
+    max_recents: usize,
+    record_recents: bool,
+    double_esc_to_close: bool,
+    /// Set by a first Esc on an overlay when a second one is required to close it.
+    overlay_esc_armed: bool,
+    restore_focus_after_overlay: bool,
+    compact_guilds_tree: bool,
+    mark_read_on_focus: bool,
+    suppress_everyone_highlight: bool,
+    confirm_masked_links: bool,
+    confirm_everyone_send: bool,
+    /// Message a jump is waiting on while older history pages in.
+    pending_jump: Option<(ChannelId, MessageId)>,
+    /// Most of your own messages one purge deletes.
+    purge_message_limit: usize,
+    /// Messages beyond the visible ones whose images are prefetched.
+    image_prefetch_distance: usize,
+    /// Marker ending names and hints cut short to fit.
+    ellipsis: String,
+    /// Sections listed, in order, by an empty mixed-mode Quick Switcher query.
+    quick_switcher_sections: Vec<QuickSwitcherSection>,
+    /// Set when a folder or guild expansion changed and should be persisted.
+    tree_expansion_changed: bool,
+    /// Focus applied on construction and again once a channel is restored.
+    startup_focus: ChatFocus,
+    /// Focus held when the quick switcher was opened.
+    pre_overlay_focus: Option<ChatFocus>,
+    /// Unsent input stashed per channel while another channel is open.
+    drafts: std::collections::HashMap<ChannelId, MessageDraft>,
+    /// Last message seen in each DM, shown in the quick switcher.
+    dm_snippets: std::collections::HashMap<String, String>,
+    /// Users connected to each voice channel.
+    voice_states: std::collections::HashMap<ChannelId, Vec<VoiceState>>,
     quick_switcher: QuickSwitcher,
     show_quick_switcher: bool,
+    /// Open when copying from a message with several code blocks.
+    code_block_chooser: Option<CodeBlockChooserState>,
     relationship_state: RelationshipState,
     hide_blocked_completely: bool,
     last_scroll_state: Option<(usize, u16)>,
     pub recents: Vec<crate::domain::search::RecentItem>,
+    /// Channels pinned to the top of the quick switcher.
+    pub pinned: Vec<crate::domain::search::RecentItem>,
 
     // Permission related state
     guild_roles: std::collections::HashMap<GuildId, Vec<Role>>,
     guild_members: std::collections::HashMap<GuildId, Member>,
     raw_channels: std::collections::HashMap<GuildId, Vec<Channel>>,
+    /// Guilds whose raw channels arrived but haven't been permission-filtered.
+    deferred_guilds: std::collections::HashSet<GuildId>,
+    eager_guild_count: usize,
 }
 
 impl ChatScreenState {
@@ -690,7 +996,7 @@ impl ChatScreenState {
     }
 
     #[must_use]
-    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
     #[allow(clippy::fn_params_excessive_bools)]
     pub fn new(
         user: User,
@@ -708,8 +1014,7 @@ impl ChatScreenState {
         quick_switcher_order: QuickSwitcherSortMode,
         recents: Vec<crate::domain::search::RecentItem>,
     ) -> Self {
-        let mut guilds_tree_state = GuildsTreeState::new();
-        guilds_tree_state.set_focused(true);
+        let guilds_tree_state = GuildsTreeState::new();
 
         let entrance_effect = if enable_animations {
             fx::coalesce((800, Interpolation::SineOut))
@@ -747,31 +1052,83 @@ impl ChatScreenState {
             image_preview,
             timestamp_format,
             theme,
+            base_theme: theme,
+            accent_overrides: std::collections::HashMap::new(),
+            styles: ThemeStyles::from_theme(&theme),
             forum_states: std::collections::HashMap::new(),
-            pending_deletion_id: None,
+            pending_confirmation: None,
+            confirm_logout_with_drafts: true,
+            large_paste_threshold: 2000,
+            max_recents: 50,
+            record_recents: true,
+            double_esc_to_close: false,
+            overlay_esc_armed: false,
+            restore_focus_after_overlay: false,
+            compact_guilds_tree: false,
+            mark_read_on_focus: false,
+            suppress_everyone_highlight: false,
+            confirm_masked_links: true,
+            confirm_everyone_send: true,
+            pending_jump: None,
+            purge_message_limit: 25,
+            image_prefetch_distance: 10,
+            ellipsis: DEFAULT_ELLIPSIS.to_string(),
+            quick_switcher_sections: QuickSwitcherSection::ALL.to_vec(),
+            tree_expansion_changed: false,
+            startup_focus: ChatFocus::GuildsTree,
+            pre_overlay_focus: None,
+            drafts: std::collections::HashMap::new(),
+            dm_snippets: std::collections::HashMap::new(),
+            voice_states: std::collections::HashMap::new(),
             quick_switcher: QuickSwitcher::new(quick_switcher_order),
             show_quick_switcher: false,
+            code_block_chooser: None,
             relationship_state,
             hide_blocked_completely,
             last_scroll_state: None,
             recents: valid_recents.clone(),
+            pinned: Vec::new(),
             guilds_tree_visible: true,
             autocomplete_service:
                 crate::application::services::autocomplete_service::AutocompleteService::new(),
             connection_status: crate::domain::ConnectionStatus::Disconnected,
+            has_connected: false,
+            internal_notifications: true,
+            latency_ms: None,
+            total_mentions: 0,
+            show_mention_count: true,
             file_explorer: Some(crate::presentation::widgets::FileExplorerComponent::new()),
             show_file_explorer: false,
+            file_explorer_config: FileExplorerConfig::default(),
+            file_explorer_last_dir: None,
             entrance_effect,
             pending_duration: std::time::Duration::ZERO,
             has_entered: !enable_animations,
+            timestamp_clock: Duration::ZERO,
+            read_ack_cooldowns: std::collections::HashMap::new(),
+            pending_read_acks: std::collections::HashMap::new(),
             image_manager: crate::presentation::widgets::ImageManager::new(),
             guild_roles: std::collections::HashMap::new(),
             guild_members: std::collections::HashMap::new(),
             raw_channels: std::collections::HashMap::new(),
+            deferred_guilds: std::collections::HashSet::new(),
+            eager_guild_count: 0,
         };
 
         state.quick_switcher.set_recents(valid_recents);
         state
+            .message_pane_state
+            .set_animate_spinner(enable_animations);
+        state.set_focus(state.startup_focus);
+        state
+    }
+
+    /// Starts the screen focused on `focus` instead of the guilds tree.
+    #[must_use]
+    pub fn with_startup_focus(mut self, focus: StartupFocus) -> Self {
+        self.startup_focus = focus.into();
+        self.set_focus(self.startup_focus);
+        self
     }
 
     pub fn quick_switcher_sort_mode(&self) -> QuickSwitcherSortMode {
@@ -789,6 +1146,9 @@ impl ChatScreenState {
     }
 
     fn add_recent_item(&mut self, mut item: crate::domain::search::RecentItem) {
+        if !self.record_recents {
+            return;
+        }
         if !Self::is_valid_recent_item(&item) {
             tracing::warn!(
                 "Ignored invalid recent item: {} ({:?}) id={}",
@@ -805,10 +1165,45 @@ impl ChatScreenState {
         self.recents
             .retain(|r| !(r.id == item.id && r.kind == item.kind));
         self.recents.insert(0, item);
-        self.recents.truncate(50);
+        self.recents.truncate(self.max_recents);
         self.quick_switcher.set_recents(self.recents.clone());
     }
 
+    /// Pins `result` to the top of the quick switcher, or unpins it.
+    pub fn toggle_pin(&mut self, result: &SearchResult) {
+        if let Some(i) = self
+            .pinned
+            .iter()
+            .position(|p| p.id == result.id && p.kind == result.kind)
+        {
+            self.pinned.remove(i);
+        } else {
+            self.pinned
+                .push(crate::domain::search::RecentItem::new(result));
+        }
+        self.quick_switcher.set_pinned(self.pinned.clone());
+        if self.show_quick_switcher {
+            self.perform_search(&self.quick_switcher.input.clone());
+            self.quick_switcher.select_matching(result);
+        }
+    }
+
+    /// Replaces the pinned channels, e.g. with those saved last session.
+    pub fn set_pinned(&mut self, pinned: Vec<crate::domain::search::RecentItem>) {
+        self.pinned = pinned;
+        self.quick_switcher.set_pinned(self.pinned.clone());
+    }
+
+    /// Forgets every recent channel, refreshing the quick switcher if open.
+    pub fn clear_recents(&mut self) -> ChatKeyResult {
+        self.recents.clear();
+        self.quick_switcher.set_recents(Vec::new());
+        if self.show_quick_switcher {
+            self.perform_search(&self.quick_switcher.input.clone());
+        }
+        ChatKeyResult::RecentsCleared
+    }
+
     pub fn restore_state(
         &mut self,
         guild_id: Option<GuildId>,
@@ -825,6 +1220,7 @@ impl ChatScreenState {
         }
 
         let mut restored = false;
+        let mut ack = None;
         let recents_backup = self.recents.clone();
 
         if let Some(cid) = channel_id
@@ -832,12 +1228,19 @@ impl ChatScreenState {
         {
             if let Some(msgs) = messages {
                 self.set_messages(msgs);
-                if let Some(last_msg) = self.message_pane_data.messages().back().map(|m| &m.message)
+                if let Some(last_id) = self
+                    .message_pane_data
+                    .messages()
+                    .back()
+                    .map(|m| m.message.id())
                 {
-                    self.mark_channel_read(cid, last_msg.id());
+                    ack = self.mark_channel_read(cid, last_id);
                 }
             }
             restored = true;
+            if self.startup_focus != ChatFocus::GuildsTree {
+                self.set_focus(self.startup_focus);
+            }
         }
 
         if !restored && let Some(first_guild) = self.guilds_tree_data.guilds().first() {
@@ -848,13 +1251,47 @@ impl ChatScreenState {
         self.recents = recents_backup;
         self.quick_switcher.set_recents(self.recents.clone());
 
-        None
+        ack
     }
 
-    pub fn tick(&mut self, duration: Duration) {
+    /// Advances time-based state; returns whether the screen needs a redraw.
+    pub fn tick(&mut self, duration: Duration) -> bool {
         if !self.has_entered {
             self.pending_duration = self.pending_duration.saturating_add(duration);
         }
+        self.registry.expire_sequence();
+        if self.timestamp_format == RELATIVE_TIMESTAMP_FORMAT {
+            self.timestamp_clock = self.timestamp_clock.saturating_add(duration);
+        }
+        self.read_ack_cooldowns.retain(|_, left| {
+            *left = left.saturating_sub(duration);
+            !left.is_zero()
+        });
+
+        let spinner_advanced = self
+            .message_pane_state
+            .tick_spinner(duration, self.message_pane_data.is_loading());
+
+        !self.has_entered
+            || spinner_advanced
+            || self.has_pending_sequence()
+            || self.take_timestamp_refresh()
+            || self.message_pane_data.has_pending_sends()
+    }
+
+    /// Whether relative timestamps are due for a redraw; resets the clock.
+    fn take_timestamp_refresh(&mut self) -> bool {
+        if self.timestamp_clock < RELATIVE_TIMESTAMP_REFRESH {
+            return false;
+        }
+        self.timestamp_clock = Duration::ZERO;
+        true
+    }
+
+    /// Whether a sequence prefix is waiting for its next key.
+    #[must_use]
+    pub fn has_pending_sequence(&self) -> bool {
+        self.registry.pending_prefix().is_some()
     }
 
     #[must_use]
@@ -892,2121 +1329,5238 @@ impl ChatScreenState {
         self.connection_status
     }
 
-    pub const fn set_connection_status(&mut self, status: ConnectionStatus) {
+    pub const fn set_latency_ms(&mut self, latency_ms: u64) {
+        self.latency_ms = Some(latency_ms);
+    }
+
+    pub const fn set_internal_notifications(&mut self, enabled: bool) {
+        self.internal_notifications = enabled;
+    }
+
+    /// Updates the connection status.
+    ///
+    /// When the connection drops or comes back this returns a toast, and on
+    /// coming back a backfill of the open channel so messages missed offline
+    /// show up. Repeating the current status returns nothing.
+    pub fn set_connection_status(&mut self, status: ConnectionStatus) -> Vec<ChatKeyResult> {
+        let previous = self.connection_status;
         self.connection_status = status;
+        if !matches!(status, ConnectionStatus::Connected) {
+            self.latency_ms = None;
+        }
+        if status == previous {
+            return Vec::new();
+        }
+
+        let mut results = Vec::new();
+        let toast = match status {
+            ConnectionStatus::Reconnecting => Some("Reconnecting…"),
+            ConnectionStatus::Connected if self.has_connected => Some("Reconnected"),
+            _ => None,
+        };
+        if let Some(toast) = toast
+            && self.internal_notifications
+        {
+            results.push(ChatKeyResult::ShowNotification(toast.to_string()));
+        }
+        if status == ConnectionStatus::Connected {
+            self.has_connected = true;
+            if let Some(channel_id) = self.message_pane_data.channel_id()
+                && let Some(after) = self.message_pane_data.last_sent_message_id()
+            {
+                results.push(ChatKeyResult::BackfillChannel { channel_id, after });
+            }
+        }
+        results
     }
 
     pub fn set_guilds(&mut self, guilds: Vec<Guild>) {
         self.guilds_tree_data.set_guilds(guilds);
     }
 
+    /// Shows guilds announced by the gateway before their channels arrive.
+    pub fn add_guilds(&mut self, guilds: Vec<Guild>) {
+        self.guilds_tree_data.add_guilds(guilds);
+    }
+
     pub fn set_guild_folders(&mut self, folders: Vec<GuildFolder>) {
         self.guilds_tree_data.set_folders(folders);
     }
 
-    pub fn set_group_guilds(&mut self, group: bool) {
-        self.guilds_tree_data.set_group_guilds(group);
+    /// Persist keys of the expanded folders and guilds in the tree.
+    #[must_use]
+    pub fn tree_expansion(&self) -> Vec<String> {
+        self.guilds_tree_state.persisted_expansion()
     }
 
-    pub fn set_guild_data(
-        &mut self,
-        guild_id: GuildId,
-        roles: Vec<Role>,
-        mut members: Vec<Member>,
-    ) {
-        self.guild_roles.insert(guild_id, roles);
+    /// Re-expands folders and guilds saved by a previous run.
+    pub fn restore_tree_expansion(&mut self, keys: &[String]) {
+        self.guilds_tree_state.restore_expansion(keys);
+    }
 
-        // Find the member corresponding to self.user.id
-        if let Some(member) = members
-            .iter_mut()
-            .find(|m| m.user_id() == Some(self.user.id()))
-        {
-            self.guild_members.insert(guild_id, member.clone());
+    /// Whether a folder or guild was expanded or collapsed since the last call.
+    pub const fn take_tree_expansion_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.tree_expansion_changed, false)
+    }
+
+    pub const fn set_confirm_logout_with_drafts(&mut self, confirm: bool) {
+        self.confirm_logout_with_drafts = confirm;
+    }
+
+    pub const fn set_max_message_length(&mut self, max_length: usize) {
+        self.message_input_state.set_max_length(max_length);
+    }
+
+    /// Pastes longer than `threshold` characters ask to be sent as a file;
+    /// 0 never asks.
+    pub const fn set_large_paste_threshold(&mut self, threshold: usize) {
+        self.large_paste_threshold = threshold;
+    }
+
+    /// Caps the recents list, dropping the oldest beyond `max`.
+    pub fn set_max_recents(&mut self, max: usize) {
+        self.max_recents = max;
+        if self.recents.len() > max {
+            self.recents.truncate(max);
+            self.quick_switcher.set_recents(self.recents.clone());
         }
     }
 
-    pub fn set_channels(&mut self, guild_id: GuildId, channels: Vec<Channel>) {
-        self.raw_channels.insert(guild_id, channels);
-        let Some(channels_ref) = self.raw_channels.get(&guild_id) else {
-            return;
-        };
+    /// Stops or resumes adding opened channels to the recents.
+    pub const fn set_record_recents(&mut self, record: bool) {
+        self.record_recents = record;
+    }
 
-        let channel_map: std::collections::HashMap<ChannelId, &Channel> =
-            channels_ref.iter().map(|c| (c.id(), c)).collect();
+    pub const fn set_double_esc_to_close(&mut self, double_esc: bool) {
+        self.double_esc_to_close = double_esc;
+    }
 
-        let cached_member = self.guild_members.get(&guild_id);
-        let guild_roles = self.guild_roles.get(&guild_id).map(std::vec::Vec::as_slice);
+    pub const fn set_restore_focus_after_overlay(&mut self, restore: bool) {
+        self.restore_focus_after_overlay = restore;
+    }
 
-        let member = cached_member;
+    pub const fn set_compact_guilds_tree(&mut self, compact: bool) {
+        self.compact_guilds_tree = compact;
+    }
 
-        let mut visible_ids = std::collections::HashSet::new();
-        for c in channels_ref {
-            let perms = if let (Some(member), Some(roles)) = (member, guild_roles) {
-                let mut perms =
-                    PermissionCalculator::compute_permissions(guild_id.as_u64(), c, member, roles);
+    pub const fn set_mark_read_on_focus(&mut self, enabled: bool) {
+        self.mark_read_on_focus = enabled;
+    }
 
-                if c.kind().is_thread()
-                    && let Some(parent_id) = c.parent_id()
-                    && let Some(parent) = channel_map.get(&parent_id)
-                {
-                    perms = PermissionCalculator::compute_permissions(
-                        guild_id.as_u64(),
-                        parent,
-                        member,
-                        roles,
-                    );
-                }
-                perms
-            } else {
-                Permissions::empty()
-            };
+    pub const fn set_suppress_everyone_highlight(&mut self, suppress: bool) {
+        self.suppress_everyone_highlight = suppress;
+    }
 
-            if perms.contains(Permissions::VIEW_CHANNEL) {
-                visible_ids.insert(c.id());
-            }
-        }
+    pub const fn set_confirm_masked_links(&mut self, confirm: bool) {
+        self.confirm_masked_links = confirm;
+    }
 
-        let visible_channels: Vec<Channel> = channels_ref
-            .iter()
-            .filter(|c| {
-                if !visible_ids.contains(&c.id()) {
-                    return false;
-                }
+    pub const fn set_confirm_everyone_send(&mut self, confirm: bool) {
+        self.confirm_everyone_send = confirm;
+    }
 
-                if let Some(parent_id) = c.parent_id()
-                    && let Some(parent) = channel_map.get(&parent_id)
-                    && parent.kind().is_category()
-                    && !visible_ids.contains(&parent_id)
-                {
-                    return false;
-                }
-                true
-            })
-            .cloned()
-            .collect();
+    pub const fn set_purge_message_limit(&mut self, limit: usize) {
+        self.purge_message_limit = limit;
+    }
 
-        self.guilds_tree_data
-            .set_channels(guild_id, visible_channels);
-        self.recalculate_all_unread();
+    pub const fn set_image_prefetch_distance(&mut self, distance: usize) {
+        self.image_prefetch_distance = distance;
     }
 
-    pub fn set_dm_users(&mut self, users: Vec<DirectMessageChannel>) {
-        self.dm_channels.clear();
-        for dm in &users {
-            if let Ok(channel_id) = dm.channel_id.parse::<u64>() {
-                let user = User::new(
-                    dm.recipient_id.clone(),
-                    dm.recipient_username.clone(),
-                    &dm.recipient_discriminator,
-                    None,
-                    false,
-                    None,
-                )
-                .with_global_name(dm.recipient_global_name.clone().unwrap_or_default());
+    pub fn set_show_embeds(&mut self, show: bool) {
+        self.message_pane_data.set_show_embeds(show);
+    }
 
-                let name = IdentityResolver::with_preference(self.use_display_name).resolve(&user);
-                let info = DmChannelInfo::new(ChannelId(channel_id), name);
-                self.dm_channels.insert(dm.channel_id.clone(), info);
-            }
-        }
-        self.guilds_tree_data.set_dm_users(users);
+    pub fn set_ellipsis(&mut self, ellipsis: String) {
+        self.message_pane_data.set_ellipsis(ellipsis.clone());
+        self.ellipsis = ellipsis;
     }
 
-    pub fn set_read_states(
-        &mut self,
-        read_states: std::collections::HashMap<ChannelId, crate::domain::entities::ReadState>,
-    ) {
-        self.read_states = read_states;
-        self.recalculate_all_unread();
+    pub fn set_quick_switcher_sections(&mut self, sections: Vec<QuickSwitcherSection>) {
+        self.quick_switcher_sections = sections;
     }
 
-    fn recalculate_all_unread(&mut self) {
-        self.guilds_tree_data
-            .update_unread_status(&self.read_states);
+    pub const fn set_show_mention_count(&mut self, show: bool) {
+        self.show_mention_count = show;
     }
 
-    pub fn mark_channel_read(&mut self, channel_id: ChannelId, message_id: MessageId) {
-        if let Some(read_state) = self.read_states.get_mut(&channel_id) {
-            read_state.last_read_message_id = Some(message_id);
-        } else {
-            self.read_states.insert(
-                channel_id,
-                crate::domain::entities::ReadState::new(channel_id, Some(message_id)),
-            );
+    /// Whether an Esc aimed at an overlay should close it.
+    ///
+    /// With `double_esc_to_close` the first Esc only arms the close; the
+    /// next key closes the overlay if it is another Esc.
+    const fn confirm_overlay_esc(&mut self, armed: bool) -> bool {
+        if !self.double_esc_to_close || armed {
+            return true;
         }
-        self.recalculate_all_unread();
+        self.overlay_esc_armed = true;
+        false
     }
 
-    pub fn on_message_received(&mut self, message: &Message) {
-        if let Some(channel) = self.guilds_tree_data.get_channel_mut(message.channel_id()) {
-            channel.set_last_message_id(Some(message.id()));
+    pub fn set_accent_overrides(&mut self, overrides: std::collections::HashMap<u64, Theme>) {
+        self.accent_overrides = overrides;
+        self.apply_accent_override();
+    }
 
-            let is_own_message = message.author().id() == self.user.id_str();
-            let is_active_channel = self
-                .selected_channel
-                .as_ref()
-                .map(crate::domain::entities::Channel::id)
-                == Some(message.channel_id());
+    /// Rebuilds the global and override themes on the light or dark palette.
+    pub fn set_light_mode(&mut self, is_light_mode: bool) {
+        self.base_theme = self.base_theme.with_light_mode(is_light_mode);
+        for theme in self.accent_overrides.values_mut() {
+            *theme = theme.with_light_mode(is_light_mode);
+        }
+        self.apply_accent_override();
+    }
+
+    /// Switches the active theme to the override for the selected channel or
+    /// guild, falling back to the global theme when neither has an entry.
+    fn apply_accent_override(&mut self) {
+        let theme = self
+            .selected_channel
+            .as_ref()
+            .and_then(|channel| self.accent_overrides.get(&channel.id().as_u64()))
+            .or_else(|| {
+                self.selected_guild
+                    .and_then(|guild_id| self.accent_overrides.get(&guild_id.as_u64()))
+            })
+            .copied()
+            .unwrap_or(self.base_theme);
 
-            if is_own_message || is_active_channel {
-                self.mark_channel_read(message.channel_id(), message.id());
-            } else {
-                self.recalculate_all_unread();
-            }
+        if theme.accent != self.theme.accent || theme.is_light != self.theme.is_light {
+            self.theme = theme;
+            self.styles = ThemeStyles::from_theme(&theme);
+            self.message_pane_data.mark_dirty();
         }
+    }
 
-        if let Some(dm_info) = self
-            .dm_channels
-            .get(message.channel_id().to_string().as_str())
-        {
-            let mut current_dms = self.guilds_tree_data.dm_users().to_vec();
-            if let Some(dm) = current_dms
-                .iter_mut()
-                .find(|d| d.channel_id == dm_info.channel_id().to_string())
-            {
-                dm.last_message_id = Some(message.id());
-            }
-            self.guilds_tree_data.set_dm_users(current_dms);
+    /// Guild of the open channel, if it belongs to one.
+    #[must_use]
+    pub fn active_guild_id(&self) -> Option<GuildId> {
+        self.selected_channel
+            .as_ref()
+            .and_then(Channel::guild_id)
+            .or(self.selected_guild)
+    }
+
+    /// Hands the active guild's roles to the message pane for `<@&id>`
+    /// mentions, and shows the current user under their nickname there.
+    fn sync_pane_guild(&mut self) {
+        let guild_id = self.active_guild_id();
+        let roles = guild_id
+            .and_then(|guild_id| self.guild_roles.get(&guild_id))
+            .map_or(&[][..], Vec::as_slice);
+        self.message_pane_data.set_roles(roles);
+        let nick = guild_id
+            .and_then(|guild_id| self.guild_members.get(&guild_id))
+            .and_then(|member| member.nick.clone());
+        self.message_pane_data
+            .set_author_nick(&self.user.id().to_string(), nick);
+    }
 
-            let is_own_message = message.author().id() == self.user.id_str();
-            let is_active_channel = self
-                .selected_channel
-                .as_ref()
-                .map(crate::domain::entities::Channel::id)
-                == Some(message.channel_id());
+    /// Returns the number of channels holding unsent input, including the open one.
+    #[must_use]
+    pub fn draft_channel_count(&self) -> usize {
+        let current_has_draft = !self.message_input_state.is_editing()
+            && (!self.message_input_state.is_empty()
+                || !self.message_input_state.attachments().is_empty());
+        self.drafts.len() + usize::from(current_has_draft)
+    }
 
-            if is_own_message || is_active_channel {
-                self.mark_channel_read(message.channel_id(), message.id());
-            } else {
-                self.recalculate_all_unread();
-            }
+    /// Moves the open channel's input, attachments included, into `drafts`.
+    fn stash_draft(&mut self) {
+        let Some(channel_id) = self.message_pane_data.channel_id() else {
+            return;
+        };
+        let attachments = self.message_input_state.attachments().to_vec();
+        self.message_input_state.clear_attachments();
+        if self.message_input_state.is_editing()
+            || (self.message_input_state.is_empty() && attachments.is_empty())
+        {
+            self.drafts.remove(&channel_id);
+        } else {
+            self.drafts.insert(
+                channel_id,
+                MessageDraft {
+                    content: self.message_input_state.value(),
+                    attachments,
+                },
+            );
         }
     }
 
-    pub fn toggle_guilds_tree(&mut self) {
-        self.guilds_tree_visible = !self.guilds_tree_visible;
-        if !self.guilds_tree_visible && self.focus == ChatFocus::GuildsTree {
-            self.focus_next();
+    fn restore_draft(&mut self, channel_id: ChannelId) {
+        if let Some(draft) = self.drafts.remove(&channel_id) {
+            self.message_input_state.set_content(&draft.content);
+            for attachment in draft.attachments {
+                self.message_input_state.add_attachment(attachment);
+            }
         }
     }
 
-    pub fn focus_guilds_tree(&mut self) {
-        if self.guilds_tree_visible {
-            self.set_focus(ChatFocus::GuildsTree);
-        }
+    /// Whether `message` is one of the user's own, already-sent posts in an
+    /// announcement channel that has not been published yet.
+    fn can_publish(&self, message: &Message) -> bool {
+        self.selected_channel
+            .as_ref()
+            .is_some_and(|c| c.kind() == ChannelKind::Announcement)
+            && message.can_be_edited_by(&self.user)
+            && message.is_publishable()
+            && self.message_pane_data.send_state(message.id()) == Some(SendState::Sent)
     }
 
-    pub fn focus_messages_list(&mut self) {
-        self.set_focus(ChatFocus::MessagesList);
+    /// Whether `message_id` is a local echo that Discord has not confirmed,
+    /// so its id is not one the API knows.
+    fn is_unsent(&self, message_id: MessageId) -> bool {
+        self.message_pane_data
+            .send_state(message_id)
+            .is_some_and(|state| state != SendState::Sent)
     }
 
-    pub fn focus_message_input(&mut self) {
-        self.set_focus(ChatFocus::MessageInput);
+    /// Your most recent sent messages in the open channel, newest first,
+    /// capped at the purge limit.
+    fn own_recent_message_ids(&self) -> Vec<MessageId> {
+        self.message_pane_data
+            .messages()
+            .iter()
+            .rev()
+            .filter(|m| m.send_state == SendState::Sent && m.message.can_be_edited_by(&self.user))
+            .map(|m| m.message.id())
+            .take(self.purge_message_limit)
+            .collect()
+    }
+
+    /// Selects the next (or previous) loaded message that mentions you,
+    /// wrapping around at either end.
+    ///
+    /// Returns `false` if no loaded message mentions you.
+    fn jump_to_mention(&mut self, forward: bool) -> bool {
+        let user_id = self.user.id();
+        let mentions: Vec<usize> = self
+            .message_pane_data
+            .messages()
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.message.mentions().iter().any(|u| u.id() == user_id))
+            .map(|(idx, _)| idx)
+            .collect();
+        let (Some(&first), Some(&last)) = (mentions.first(), mentions.last()) else {
+            return false;
+        };
+
+        let current = self.message_pane_state.selected_index();
+        let target = if forward {
+            current
+                .and_then(|cur| mentions.iter().copied().find(|&idx| idx > cur))
+                .unwrap_or(first)
+        } else {
+            current
+                .and_then(|cur| mentions.iter().rev().copied().find(|&idx| idx < cur))
+                .unwrap_or(last)
+        };
+        self.message_pane_state.jump_to_index(target);
+        true
     }
 
-    pub fn focus_next(&mut self) {
-        let new_focus = self.focus.next(self.guilds_tree_visible);
-        self.set_focus(new_focus);
+    /// Opens `link`, first showing its real URL when the text disguises it.
+    fn open_link(&mut self, link: Link) -> ChatKeyResult {
+        if !self.confirm_masked_links || !link.hides_destination() {
+            return ChatKeyResult::OpenLink(link.url);
+        }
+
+        self.pending_confirmation = Some(PendingConfirmation::OpenLink(link));
+        self.set_focus(ChatFocus::ConfirmationModal);
+        ChatKeyResult::Consumed
     }
 
-    pub fn focus_previous(&mut self) {
-        let new_focus = self.focus.previous(self.guilds_tree_visible);
-        self.set_focus(new_focus);
+    fn request_logout(&mut self, secure: bool) -> ChatKeyResult {
+        let draft_count = self.draft_channel_count();
+        if !self.confirm_logout_with_drafts || draft_count == 0 {
+            return if secure {
+                ChatKeyResult::SecureLogout
+            } else {
+                ChatKeyResult::Logout
+            };
+        }
+
+        self.pending_confirmation = Some(PendingConfirmation::Logout {
+            secure,
+            draft_count,
+            return_focus: self.focus,
+        });
+        self.set_focus(ChatFocus::ConfirmationModal);
+        ChatKeyResult::Consumed
     }
 
-    fn set_focus(&mut self, focus: ChatFocus) {
-        self.focus = focus;
-        self.guilds_tree_state
-            .set_focused(focus == ChatFocus::GuildsTree);
-        self.message_pane_state
-            .set_focused(focus == ChatFocus::MessagesList);
-        self.message_input_state
-            .set_focused(focus == ChatFocus::MessageInput);
+    pub fn set_group_guilds(&mut self, group: bool) {
+        self.guilds_tree_data.set_group_guilds(group);
     }
 
-    pub fn handle_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        if self.show_help {
-            if let Some(action) = self.registry.find_action(key)
-                && matches!(action, Action::ToggleHelp | Action::Quit | Action::Cancel)
-            {
-                self.toggle_help();
-                return ChatKeyResult::Consumed;
-            }
-            return ChatKeyResult::Consumed;
-        }
+    pub fn set_flatten_single_channel_categories(&mut self, flatten: bool) {
+        self.guilds_tree_data
+            .set_flatten_single_channel_categories(flatten);
+    }
 
-        if self.focus == ChatFocus::ConfirmationModal {
-            match key.code {
-                KeyCode::Enter => {
-                    if let Some(id) = self.pending_deletion_id.take() {
-                        self.set_focus(ChatFocus::MessagesList);
-                        return ChatKeyResult::DeleteMessage(id);
-                    }
-                    self.set_focus(ChatFocus::MessagesList);
-                    return ChatKeyResult::Consumed;
-                }
-                KeyCode::Esc | KeyCode::Char('n') => {
-                    self.pending_deletion_id = None;
-                    self.set_focus(ChatFocus::MessagesList);
-                    return ChatKeyResult::Consumed;
-                }
-                _ => return ChatKeyResult::Consumed,
-            }
+    pub fn set_guild_data(
+        &mut self,
+        guild_id: GuildId,
+        roles: Vec<Role>,
+        mut members: Vec<Member>,
+    ) {
+        self.guild_roles.insert(guild_id, roles);
+        self.resolve_voice_members(&members);
+
+        // Find the member corresponding to self.user.id
+        if let Some(member) = members
+            .iter_mut()
+            .find(|m| m.user_id() == Some(self.user.id()))
+        {
+            self.guild_members.insert(guild_id, member.clone());
+        }
+        if self.active_guild_id() == Some(guild_id) {
+            self.sync_pane_guild();
+            self.sync_send_permission();
         }
+    }
 
-        if self.show_file_explorer {
-            if let Some(action) = self.registry.find_action(key)
-                && (action == Action::ToggleFileExplorer || action == Action::Cancel)
-            {
-                self.toggle_file_explorer();
-                return ChatKeyResult::Consumed;
-            }
-            return self.handle_file_explorer_key(key);
+    /// Replaces the cached member of the current user after a profile change.
+    pub fn set_own_member(&mut self, guild_id: GuildId, member: Member) {
+        self.guild_members.insert(guild_id, member);
+        if self.active_guild_id() == Some(guild_id) {
+            self.sync_pane_guild();
+            self.sync_send_permission();
         }
+    }
 
-        if let Some(action) = self.registry.find_action(key)
-            && action == Action::ToggleQuickSwitcher
-            && !self.show_quick_switcher
-        {
-            self.toggle_quick_switcher();
-            return ChatKeyResult::Consumed;
+    /// Puts `/nick` with the current nickname into the input to edit.
+    fn start_nick_command(&mut self) -> ChatKeyResult {
+        let Some(guild_id) = self.selected_guild else {
+            return ChatKeyResult::ShowNotification(
+                "Nicknames can only be set in a server".to_string(),
+            );
+        };
+        if self.message_input_state.is_read_only() {
+            return ChatKeyResult::ShowNotification(READ_ONLY_NOTICE.to_string());
         }
+        let nick = self
+            .guild_members
+            .get(&guild_id)
+            .and_then(|member| member.nick.as_deref())
+            .unwrap_or_default();
+        self.message_input_state
+            .set_content(&format!("/nick {nick}"));
+        self.focus_message_input();
+        ChatKeyResult::Consumed
+    }
 
-        if self.show_quick_switcher {
-            if let Some(action) = self.registry.find_action(key)
-                && action == Action::Cancel
-            {
-                self.toggle_quick_switcher();
-                return ChatKeyResult::Consumed;
-            }
-            return self.handle_quick_switcher_key(key);
+    fn nick_command(&self, nick: Result<Option<String>, String>) -> ChatKeyResult {
+        let Some(guild_id) = self.selected_guild else {
+            return ChatKeyResult::ShowNotification(
+                "Nicknames can only be set in a server".to_string(),
+            );
+        };
+        match nick {
+            Ok(nick) => ChatKeyResult::SetNickname { guild_id, nick },
+            Err(error) => ChatKeyResult::ShowNotification(error),
         }
+    }
 
-        if self.focus == ChatFocus::MessageInput {
-            let result = self.handle_message_input_key(key);
-            if result != ChatKeyResult::Ignored {
-                return result;
+    /// Runs `/group` or `/add`. `/add` only means something in a group DM;
+    /// anywhere else it is `None` and the input goes out as an ordinary
+    /// message. A mistyped command is put back in the input to be fixed.
+    fn group_command(&mut self, content: &str) -> Option<ChatKeyResult> {
+        let result = match parse_group_command(content)? {
+            GroupCommand::Create(Ok(user_ids)) => ChatKeyResult::CreateGroupDm { user_ids },
+            GroupCommand::Add(Ok(user_ids)) => ChatKeyResult::AddGroupRecipients {
+                channel_id: self.selected_group_dm()?,
+                user_ids,
+            },
+            GroupCommand::Add(Err(_)) if self.selected_group_dm().is_none() => return None,
+            GroupCommand::Create(Err(error)) => {
+                ChatKeyResult::ShowNotification(format!("{error}. Usage: /group @user [@user…]"))
             }
-        } else {
-            match self.focus {
-                ChatFocus::GuildsTree => {
-                    let result = self.handle_guilds_tree_key(key);
-                    if result != ChatKeyResult::Ignored {
-                        return result;
-                    }
-                }
-                ChatFocus::MessagesList => {
-                    let result = self.handle_messages_list_key(key);
-                    if result != ChatKeyResult::Ignored {
-                        return result;
-                    }
-                }
-                ChatFocus::ConfirmationModal => return ChatKeyResult::Consumed,
-                ChatFocus::MessageInput => unreachable!(),
+            GroupCommand::Add(Err(error)) => {
+                ChatKeyResult::ShowNotification(format!("{error}. Usage: /add @user [@user…]"))
             }
+        };
+        if matches!(result, ChatKeyResult::ShowNotification(_)) {
+            self.message_input_state.set_content(content);
         }
+        Some(result)
+    }
 
-        if let Some(result) = self.handle_global_key(key) {
-            return result;
+    /// Whether the input is a `/group` or `/add` command, so `@` picks from
+    /// every user a group could include rather than this channel's authors.
+    fn is_group_command_input(&self) -> bool {
+        match parse_group_command(&self.message_input_state.value()) {
+            Some(GroupCommand::Create(_)) => true,
+            Some(GroupCommand::Add(_)) => self.selected_group_dm().is_some(),
+            None => false,
         }
+    }
 
-        ChatKeyResult::Consumed
+    pub fn set_eager_guild_count(&mut self, count: usize) {
+        self.eager_guild_count = count;
     }
 
-    fn handle_global_key(&mut self, key: KeyEvent) -> Option<ChatKeyResult> {
-        match self.registry.find_action(key) {
-            Some(Action::Quit) => Some(ChatKeyResult::Quit),
-            Some(Action::Logout) => Some(ChatKeyResult::Logout),
-            Some(Action::SecureLogout) => Some(ChatKeyResult::SecureLogout),
-            Some(Action::FocusGuilds) => {
-                self.focus_guilds_tree();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::FocusMessages) => {
-                self.focus_messages_list();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::FocusInput) => {
-                if self.focus == ChatFocus::MessageInput {
-                    return None;
-                }
-                self.focus_message_input();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::FocusPrevious) => {
-                self.focus_previous();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::FocusNext | Action::NextTab) => {
-                self.focus_next();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::ToggleGuildsTree) => {
-                self.toggle_guilds_tree();
-                Some(ChatKeyResult::Consumed)
-            }
-            Some(Action::ToggleHelp) => {
-                if self.focus == ChatFocus::MessageInput && matches!(key.code, KeyCode::Char(_)) {
-                    return None;
-                }
-                self.toggle_help();
-                Some(ChatKeyResult::ToggleHelp)
-            }
-            Some(Action::ToggleDisplayName) => Some(ChatKeyResult::ToggleDisplayName),
+    pub fn set_group_replies(&mut self, group_replies: bool) {
+        self.message_pane_data.set_group_replies(group_replies);
+    }
 
-            Some(Action::ToggleQuickSwitcher) => {
-                self.toggle_quick_switcher();
-                Some(ChatKeyResult::Consumed)
-            }
-            _ => None,
-        }
+    pub fn set_group_start_timestamps_only(&mut self, only: bool) {
+        self.message_pane_data.set_group_start_timestamps_only(only);
     }
 
-    fn handle_guilds_tree_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        use crate::presentation::widgets::GuildsTreeStyle;
+    pub fn set_timestamp_position(&mut self, position: TimestampPosition) {
+        self.message_pane_data.set_timestamp_position(position);
+    }
 
-        let style = GuildsTreeStyle::from_theme(&self.theme);
+    pub fn set_message_density(&mut self, density: MessageDensity) {
+        self.message_pane_data.set_message_density(density);
+    }
 
-        if let Some(action) = self.guilds_tree_state.handle_key(
-            key,
-            &self.guilds_tree_data,
-            &self.registry,
-            &style,
-            self.use_display_name,
-        ) {
-            match action {
-                GuildsTreeAction::SelectChannel(channel_id) => {
-                    if let Some(result) = self.on_channel_selected(channel_id) {
-                        return result;
-                    }
-                }
-                GuildsTreeAction::SelectGuild(guild_id) => {
-                    if let Some(result) = self.on_guild_selected(guild_id) {
-                        return result;
-                    }
+    pub fn set_collapse_message_lines(&mut self, lines: usize) {
+        self.message_pane_data.set_collapse_message_lines(lines);
+    }
+
+    pub fn set_wrap_indent(&mut self, indent: u16) {
+        self.message_pane_data.set_wrap_indent(indent);
+    }
+
+    pub fn set_channels(&mut self, guild_id: GuildId, channels: Vec<Channel>) {
+        self.raw_channels.insert(guild_id, channels);
+        if self.is_eager_guild(guild_id) {
+            self.deferred_guilds.remove(&guild_id);
+            self.compute_visible_channels(guild_id);
+            self.show_empty_guild_if_inaccessible(guild_id);
+        } else {
+            self.deferred_guilds.insert(guild_id);
+        }
+    }
+
+    /// Whether a guild's channels should be filtered as soon as they arrive.
+    fn is_eager_guild(&self, guild_id: GuildId) -> bool {
+        self.eager_guild_count == 0
+            || self.selected_guild == Some(guild_id)
+            || self.guilds_tree_data.channels(guild_id).is_some()
+            || self
+                .guilds_tree_state
+                .is_expanded(&TreeNodeId::Guild(guild_id))
+            || self
+                .guilds_tree_data
+                .guilds()
+                .iter()
+                .take(self.eager_guild_count)
+                .any(|g| g.id() == guild_id)
+    }
+
+    /// Computes a deferred guild's visible channels; returns `false` if there
+    /// was nothing pending for it.
+    fn ensure_guild_channels(&mut self, guild_id: GuildId) -> bool {
+        if !self.deferred_guilds.remove(&guild_id) {
+            return false;
+        }
+        self.compute_visible_channels(guild_id);
+        true
+    }
+
+    /// Prepares the deferred guild a channel belongs to, if any.
+    fn ensure_channel_guild(&mut self, channel_id: ChannelId) {
+        if self.guilds_tree_data.get_channel(channel_id).is_some() {
+            return;
+        }
+        let deferred = self.deferred_guilds.iter().copied().find(|id| {
+            self.raw_channels
+                .get(id)
+                .is_some_and(|channels| channels.iter().any(|c| c.id() == channel_id))
+        });
+        if let Some(guild_id) = deferred {
+            self.ensure_guild_channels(guild_id);
+        }
+    }
+
+    fn compute_visible_channels(&mut self, guild_id: GuildId) {
+        let Some(visible_channels) = self.visible_channels(guild_id) else {
+            return;
+        };
+        self.guilds_tree_data
+            .set_channels(guild_id, visible_channels);
+        self.recalculate_all_unread();
+    }
+
+    /// A deferred guild's visible channels, sorted as the tree would show
+    /// them, without storing them in the tree.
+    fn deferred_channels(&self, guild_id: GuildId) -> Option<SortedGuildChannels> {
+        if !self.deferred_guilds.contains(&guild_id) {
+            return None;
+        }
+        let mut channels = SortedGuildChannels::new(self.visible_channels(guild_id)?);
+        channels.update_unread_status(&self.read_states);
+        Some(channels)
+    }
+
+    /// The guild's channels the user may view, filtered by permissions.
+    fn visible_channels(&self, guild_id: GuildId) -> Option<Vec<Channel>> {
+        let channels_ref = self.raw_channels.get(&guild_id)?;
+
+        let channel_map: std::collections::HashMap<ChannelId, &Channel> =
+            channels_ref.iter().map(|c| (c.id(), c)).collect();
+
+        let cached_member = self.guild_members.get(&guild_id);
+        let guild_roles = self.guild_roles.get(&guild_id).map(std::vec::Vec::as_slice);
+
+        let member = cached_member;
+
+        let mut visible_ids = std::collections::HashSet::new();
+        for c in channels_ref {
+            let perms = if let (Some(member), Some(roles)) = (member, guild_roles) {
+                let mut perms =
+                    PermissionCalculator::compute_permissions(guild_id.as_u64(), c, member, roles);
+
+                if c.kind().is_thread()
+                    && let Some(parent_id) = c.parent_id()
+                    && let Some(parent) = channel_map.get(&parent_id)
+                {
+                    perms = PermissionCalculator::compute_permissions(
+                        guild_id.as_u64(),
+                        parent,
+                        member,
+                        roles,
+                    );
                 }
-                GuildsTreeAction::SelectDirectMessage(dm_channel_id) => {
-                    if let Some(result) = self.on_dm_selected(&dm_channel_id) {
-                        return result;
-                    }
+                perms
+            } else {
+                Permissions::empty()
+            };
+
+            if perms.contains(Permissions::VIEW_CHANNEL) {
+                visible_ids.insert(c.id());
+            }
+        }
+
+        let visible_channels: Vec<Channel> = channels_ref
+            .iter()
+            .filter(|c| {
+                if !visible_ids.contains(&c.id()) {
+                    return false;
                 }
-                GuildsTreeAction::YankId(id) => {
-                    return ChatKeyResult::CopyToClipboard(id);
+
+                if let Some(parent_id) = c.parent_id()
+                    && let Some(parent) = channel_map.get(&parent_id)
+                    && parent.kind().is_category()
+                    && !visible_ids.contains(&parent_id)
+                {
+                    return false;
                 }
-                GuildsTreeAction::LoadGuildChannels(guild_id) => {
-                    return ChatKeyResult::LoadGuildChannels(guild_id);
+                true
+            })
+            .cloned()
+            .collect();
+        Some(visible_channels)
+    }
+
+    pub fn set_dm_users(&mut self, users: Vec<DirectMessageChannel>) {
+        self.dm_channels.clear();
+        for dm in &users {
+            if let Ok(channel_id) = dm.channel_id.parse::<u64>() {
+                if dm.is_group() {
+                    let info =
+                        DmChannelInfo::new(ChannelId(channel_id), dm.name(self.use_display_name))
+                            .with_group(true);
+                    self.dm_channels.insert(dm.channel_id.clone(), info);
+                    continue;
                 }
+                let user = User::new(
+                    dm.recipient_id.clone(),
+                    dm.recipient_username.clone(),
+                    &dm.recipient_discriminator,
+                    None,
+                    false,
+                    None,
+                )
+                .with_global_name(dm.recipient_global_name.clone().unwrap_or_default());
+
+                let name = IdentityResolver::with_preference(self.use_display_name).resolve(&user);
+                let info = DmChannelInfo::new(ChannelId(channel_id), name);
+                self.dm_channels.insert(dm.channel_id.clone(), info);
             }
         }
-        ChatKeyResult::Ignored
+        self.guilds_tree_data.set_dm_users(users);
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn handle_messages_list_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        if let Some(action) = self.message_pane_state.handle_key(
-            key,
-            &self.message_pane_data,
-            &self.registry,
-            Some(&self.relationship_state),
-            self.hide_blocked_completely,
-        ) {
-            match action {
-                MessagePaneAction::ClearSelection | MessagePaneAction::SelectMessage(_) => {}
-                MessagePaneAction::Reply {
-                    message_id,
-                    mention,
-                } => {
-                    return ChatKeyResult::ReplyToMessage {
-                        message_id,
-                        mention,
-                    };
-                }
-                MessagePaneAction::Edit(message_id) => {
-                    if let Some(message) = self
-                        .message_pane_data
-                        .messages()
-                        .iter()
-                        .find(|m| m.message.id() == message_id)
-                    {
-                        if message.message.can_be_edited_by(&self.user) {
-                            self.message_input_state
-                                .start_edit(message_id, message.message.content());
-                            self.focus_message_input();
-                        } else {
-                            return ChatKeyResult::ShowNotification(
-                                "You can only edit your own messages".to_string(),
-                            );
-                        }
-                    }
-                }
-                MessagePaneAction::EditExternal(message_id) => {
-                    if let Some(message) = self
-                        .message_pane_data
-                        .messages()
-                        .iter()
-                        .find(|m| m.message.id() == message_id)
-                    {
-                        if message.message.can_be_edited_by(&self.user) {
-                            return ChatKeyResult::OpenEditor {
-                                initial_content: message.message.content().to_string(),
-                                message_id: Some(message_id),
-                            };
-                        }
-                        return ChatKeyResult::ShowNotification(
-                            "You can only edit your own messages".to_string(),
-                        );
-                    }
-                }
-                MessagePaneAction::Delete(message_id) => {
-                    if let Some(message) = self
-                        .message_pane_data
-                        .messages()
-                        .iter()
-                        .find(|m| m.message.id() == message_id)
-                    {
-                        if message.message.can_be_edited_by(&self.user) {
-                            self.pending_deletion_id = Some(message_id);
-                            self.set_focus(ChatFocus::ConfirmationModal);
-                            return ChatKeyResult::Consumed;
+    /// Replaces every known voice state, e.g. from the READY payload.
+    pub fn set_voice_states(&mut self, states: Vec<VoiceState>) {
+        self.voice_states.clear();
+        for state in states {
+            self.insert_voice_state(state);
+        }
+        self.sync_voice_participants();
+    }
+
+    /// Replaces the voice states of one guild, e.g. from `GUILD_CREATE`.
+    pub fn set_guild_voice_states(&mut self, guild_id: GuildId, states: Vec<VoiceState>) {
+        if let Some(channels) = self.raw_channels.get(&guild_id) {
+            for channel in channels {
+                self.voice_states.remove(&channel.id());
+            }
+        }
+        for state in states {
+            self.insert_voice_state(state);
+        }
+        self.sync_voice_participants();
+    }
+
+    /// Puts a user in the channel of `state`, leaving any other channel.
+    fn insert_voice_state(&mut self, state: VoiceState) {
+        self.remove_voice_state(&state.user_id);
+        self.voice_states
+            .entry(state.channel_id)
+            .or_default()
+            .push(state);
+    }
+
+    fn remove_voice_state(&mut self, user_id: &str) {
+        for states in self.voice_states.values_mut() {
+            states.retain(|s| s.user_id != user_id);
+        }
+        self.voice_states.retain(|_, states| !states.is_empty());
+    }
+
+    /// Fills in the names of connected users from a guild's member list,
+    /// which READY and `GUILD_CREATE` voice states do not carry.
+    fn resolve_voice_members(&mut self, members: &[Member]) {
+        let mut changed = false;
+        for state in self.voice_states.values_mut().flatten() {
+            let Some(member) = members
+                .iter()
+                .find(|m| m.user_id() == Some(UserId::from(state.user_id.as_str())))
+            else {
+                continue;
+            };
+            if let Some(user) = &member.user {
+                self.user_cache.insert_from_user(user);
+            }
+            if member.nick.is_some() {
+                state.nick.clone_from(&member.nick);
+            }
+            changed = true;
+        }
+        if changed {
+            self.sync_voice_participants();
+        }
+    }
+
+    /// Re-resolves participant names after cached users changed.
+    pub fn refresh_voice_participants(&mut self) {
+        if !self.voice_states.is_empty() {
+            self.sync_voice_participants();
+        }
+    }
+
+    /// Moves a user to the channel in `state`, or out of voice when `None`.
+    pub fn update_voice_state(&mut self, user_id: &str, state: Option<VoiceState>) {
+        self.remove_voice_state(user_id);
+        if let Some(state) = state {
+            self.insert_voice_state(state);
+        }
+        self.sync_voice_participants();
+    }
+
+    fn sync_voice_participants(&mut self) {
+        let participants = self
+            .voice_states
+            .iter()
+            .map(|(channel_id, states)| {
+                let users = states
+                    .iter()
+                    .map(|state| {
+                        let cached = self.user_cache.get(&state.user_id);
+                        let username = cached
+                            .as_ref()
+                            .map_or_else(|| state.user_id.clone(), |u| u.username().to_string());
+                        let display_name = state.nick.clone().or_else(|| {
+                            cached
+                                .as_ref()
+                                .and_then(|u| u.global_name().map(str::to_string))
+                        });
+                        VoiceParticipant {
+                            user_id: state.user_id.clone(),
+                            username,
+                            display_name,
+                            muted: state.muted,
+                            deafened: state.deafened,
                         }
+                    })
+                    .collect();
+                (*channel_id, users)
+            })
+            .collect();
+        self.guilds_tree_data.set_voice_participants(participants);
+    }
 
-                        return ChatKeyResult::ShowNotification(
-                            "You can only delete your own messages".to_string(),
-                        );
-                    }
-                }
-                MessagePaneAction::YankContent(content) | MessagePaneAction::YankUrl(content) => {
-                    return ChatKeyResult::CopyToClipboard(content);
-                }
-                MessagePaneAction::CopyImage(image_id) => {
-                    return ChatKeyResult::CopyImageToClipboard(image_id);
-                }
-                MessagePaneAction::YankId(id) => {
-                    return ChatKeyResult::CopyToClipboard(id);
-                }
-                MessagePaneAction::OpenAttachments(message_id) => {
-                    if let Some(message) = self
-                        .message_pane_data
-                        .messages()
-                        .iter()
-                        .find(|m| m.message.id() == message_id)
-                        .map(|m| &m.message)
-                    {
-                        return match MessageContentService::resolve(message) {
-                            MessageContentAction::OpenImages => {
-                                ChatKeyResult::OpenAttachments(message_id)
-                            }
-                            MessageContentAction::OpenLink(url) => ChatKeyResult::OpenLink(url),
-                            MessageContentAction::None => ChatKeyResult::Ignored,
-                        };
-                    }
-                }
-                MessagePaneAction::JumpToReply(message_id) => {
-                    return ChatKeyResult::JumpToMessage(message_id);
-                }
-                MessagePaneAction::OpenThread(channel_id) => {
-                    if let Some(result) = self.on_channel_selected(channel_id) {
-                        return result;
+    /// Adds a DM to the list, or replaces it if already known, e.g. after a
+    /// group DM is created or gains recipients.
+    pub fn upsert_dm(&mut self, dm: DirectMessageChannel) {
+        let mut users = self.guilds_tree_data.dm_users().to_vec();
+        if let Some(existing) = users.iter_mut().find(|d| d.channel_id == dm.channel_id) {
+            *existing = dm;
+        } else {
+            users.insert(0, dm);
+        }
+        self.set_dm_users(users);
+    }
+
+    /// Opens the DM with the given channel id, as if picked from the tree.
+    pub fn open_dm(&mut self, dm_channel_id: &str) -> Option<ChatKeyResult> {
+        self.on_dm_selected(dm_channel_id)
+    }
+
+    /// Opens a guild channel or DM by id, as if picked from the tree.
+    pub fn open_channel(&mut self, channel_id: ChannelId) -> Option<ChatKeyResult> {
+        let id = channel_id.to_string();
+        if self.dm_channels.contains_key(&id) {
+            return self.on_dm_selected(&id);
+        }
+        self.on_channel_selected(channel_id)
+    }
+
+    /// The current DM channel, if it is a group DM.
+    fn selected_group_dm(&self) -> Option<ChannelId> {
+        self.selected_channel
+            .as_ref()
+            .filter(|channel| channel.kind() == ChannelKind::GroupDm)
+            .map(Channel::id)
+    }
+
+    pub fn set_read_states(
+        &mut self,
+        read_states: std::collections::HashMap<ChannelId, crate::domain::entities::ReadState>,
+    ) {
+        self.read_states = read_states;
+        self.recalculate_all_unread();
+    }
+
+    fn recalculate_all_unread(&mut self) {
+        self.guilds_tree_data
+            .update_unread_status(&self.read_states);
+        self.total_mentions = self
+            .read_states
+            .values()
+            .fold(0, |total, rs| total.saturating_add(rs.mention_count));
+    }
+
+    /// Every unread guild channel, one per line, including guilds whose
+    /// channels were deferred and not yet shown.
+    fn unread_channel_list(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for guild in self.guilds_tree_data.guilds() {
+            if let Some(channels) = self.guilds_tree_data.channels(guild.id()) {
+                names.extend(channels.unread_channel_names(guild.name()));
+            } else if let Some(channels) = self.deferred_channels(guild.id()) {
+                names.extend(channels.unread_channel_names(guild.name()));
+            }
+        }
+        names
+    }
+
+    fn copy_unread_channels(&self) -> ChatKeyResult {
+        let unread = self.unread_channel_list();
+        if unread.is_empty() {
+            return ChatKeyResult::ShowNotification("No unread channels".to_string());
+        }
+        ChatKeyResult::CopyToClipboard(unread.join("\n"))
+    }
+
+    /// Unread mentions summed over every channel.
+    #[must_use]
+    pub const fn total_mentions(&self) -> u32 {
+        self.total_mentions
+    }
+
+    /// Marks a channel read up to `message_id`.
+    ///
+    /// Returns the ACK to send to Discord when the read position moved.
+    pub fn mark_channel_read(
+        &mut self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Option<ChatKeyResult> {
+        let advanced = if let Some(read_state) = self.read_states.get_mut(&channel_id) {
+            let advanced = read_state.last_read_message_id != Some(message_id);
+            read_state.last_read_message_id = Some(message_id);
+            advanced
+        } else {
+            self.read_states.insert(
+                channel_id,
+                crate::domain::entities::ReadState::new(channel_id, Some(message_id)),
+            );
+            true
+        };
+        self.recalculate_all_unread();
+        advanced.then_some(ChatKeyResult::AcknowledgeMessage {
+            channel_id,
+            message_id,
+        })
+    }
+
+    /// Marks the open channel read up to its newest loaded message when the
+    /// terminal regains focus and `mark_read_on_focus` is enabled.
+    pub fn on_focus_gained(&mut self) -> Option<ChatKeyResult> {
+        if !self.mark_read_on_focus {
+            return None;
+        }
+        let channel_id = self.selected_channel.as_ref()?.id();
+        // Local echoes carry placeholder IDs above every real one.
+        let message_id = self.message_pane_data.last_sent_message_id()?;
+        self.mark_channel_read(channel_id, message_id)
+    }
+
+    /// Keeps the first line of a DM's latest message for search previews.
+    fn remember_dm_snippet(&mut self, message: &Message) {
+        let channel_id = message.channel_id().to_string();
+        if !self.dm_channels.contains_key(&channel_id) {
+            return;
+        }
+        let Some(line) = message
+            .content()
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+        else {
+            return;
+        };
+        let snippet = truncate_to_width(line, 60, DEFAULT_ELLIPSIS);
+        self.dm_snippets.insert(channel_id, snippet.into_owned());
+    }
+
+    /// Returns an ACK when the message was read on arrival in the open
+    /// channel. Own messages are marked read without one.
+    pub fn on_message_received(&mut self, message: &Message) -> Option<ChatKeyResult> {
+        self.remember_dm_snippet(message);
+        let mut ack = None;
+        if let Some(channel) = self.guilds_tree_data.get_channel_mut(message.channel_id()) {
+            channel.set_last_message_id(Some(message.id()));
+
+            let is_own_message = message.author().id() == self.user.id_str();
+            let is_active_channel = self
+                .selected_channel
+                .as_ref()
+                .map(crate::domain::entities::Channel::id)
+                == Some(message.channel_id());
+
+            if is_own_message || is_active_channel {
+                let result = self.mark_channel_read(message.channel_id(), message.id());
+                if !is_own_message {
+                    ack = result;
+                }
+            } else {
+                self.recalculate_all_unread();
+            }
+        }
+
+        if let Some(dm_info) = self
+            .dm_channels
+            .get(message.channel_id().to_string().as_str())
+        {
+            let mut current_dms = self.guilds_tree_data.dm_users().to_vec();
+            if let Some(dm) = current_dms
+                .iter_mut()
+                .find(|d| d.channel_id == dm_info.channel_id().to_string())
+            {
+                dm.last_message_id = Some(message.id());
+            }
+            self.guilds_tree_data.set_dm_users(current_dms);
+
+            let is_own_message = message.author().id() == self.user.id_str();
+            let is_active_channel = self
+                .selected_channel
+                .as_ref()
+                .map(crate::domain::entities::Channel::id)
+                == Some(message.channel_id());
+
+            if is_own_message || is_active_channel {
+                let result = self.mark_channel_read(message.channel_id(), message.id());
+                if !is_own_message {
+                    ack = ack.or(result);
+                }
+            } else {
+                self.recalculate_all_unread();
+            }
+        }
+
+        ack.and_then(|ack| self.throttle_ack(ack))
+    }
+
+    /// Holds back the ack for a channel acked within the last
+    /// [`READ_ACK_INTERVAL`], so a busy channel is acked once per interval
+    /// rather than once per message.
+    fn throttle_ack(&mut self, ack: ChatKeyResult) -> Option<ChatKeyResult> {
+        let ChatKeyResult::AcknowledgeMessage {
+            channel_id,
+            message_id,
+        } = ack
+        else {
+            return Some(ack);
+        };
+        if self.read_ack_cooldowns.contains_key(&channel_id) {
+            self.pending_read_acks.insert(channel_id, message_id);
+            return None;
+        }
+        self.read_ack_cooldowns
+            .insert(channel_id, READ_ACK_INTERVAL);
+        Some(ack)
+    }
+
+    /// Acks held back by [`Self::throttle_ack`] whose channel may be acked
+    /// again. Ones overtaken by a newer read position are dropped.
+    pub fn take_due_acks(&mut self) -> Vec<ChatKeyResult> {
+        let due: Vec<ChannelId> = self
+            .pending_read_acks
+            .keys()
+            .filter(|channel_id| !self.read_ack_cooldowns.contains_key(channel_id))
+            .copied()
+            .collect();
+        due.into_iter()
+            .filter_map(|channel_id| {
+                let message_id = self.pending_read_acks.remove(&channel_id)?;
+                let current = self
+                    .read_states
+                    .get(&channel_id)
+                    .and_then(|read_state| read_state.last_read_message_id);
+                if current != Some(message_id) {
+                    return None;
+                }
+                self.read_ack_cooldowns
+                    .insert(channel_id, READ_ACK_INTERVAL);
+                Some(ChatKeyResult::AcknowledgeMessage {
+                    channel_id,
+                    message_id,
+                })
+            })
+            .collect()
+    }
+
+    pub fn toggle_guilds_tree(&mut self) {
+        self.guilds_tree_visible = !self.guilds_tree_visible;
+        if !self.guilds_tree_visible && self.focus == ChatFocus::GuildsTree {
+            self.focus_next();
+        }
+    }
+
+    pub fn focus_guilds_tree(&mut self) {
+        if self.guilds_tree_visible {
+            self.set_focus(ChatFocus::GuildsTree);
+        }
+    }
+
+    pub fn focus_messages_list(&mut self) {
+        self.set_focus(ChatFocus::MessagesList);
+    }
+
+    pub fn focus_message_input(&mut self) {
+        self.set_focus(ChatFocus::MessageInput);
+    }
+
+    pub fn focus_next(&mut self) {
+        let new_focus = self.focus.next(self.guilds_tree_visible);
+        self.set_focus(new_focus);
+    }
+
+    pub fn focus_previous(&mut self) {
+        let new_focus = self.focus.previous(self.guilds_tree_visible);
+        self.set_focus(new_focus);
+    }
+
+    fn set_focus(&mut self, focus: ChatFocus) {
+        self.focus = focus;
+        self.guilds_tree_state
+            .set_focused(focus == ChatFocus::GuildsTree);
+        self.message_pane_state
+            .set_focused(focus == ChatFocus::MessagesList);
+        self.message_input_state
+            .set_focused(focus == ChatFocus::MessageInput);
+    }
+
+    /// Copies the selected message's code block, asking which one when
+    /// there are several.
+    fn copy_code_block(&mut self, message_id: MessageId) -> ChatKeyResult {
+        let Some(message) = self
+            .message_pane_data
+            .messages()
+            .iter()
+            .find(|m| m.message.id() == message_id)
+        else {
+            return ChatKeyResult::Consumed;
+        };
+        let blocks = message.code_blocks();
+        match blocks.as_slice() {
+            [] => ChatKeyResult::ShowNotification("No code blocks in this message".to_string()),
+            [block] => ChatKeyResult::CopyCodeBlock(block.code.to_string()),
+            blocks => {
+                self.code_block_chooser = Some(CodeBlockChooserState::new(message_id, blocks));
+                ChatKeyResult::Consumed
+            }
+        }
+    }
+
+    /// Picks a block from the chooser by number, or the highlighted one on Enter.
+    fn handle_code_block_chooser_key(&mut self, key: KeyEvent, esc_armed: bool) -> ChatKeyResult {
+        let Some(chooser) = &mut self.code_block_chooser else {
+            return ChatKeyResult::Ignored;
+        };
+        let index = match key.code {
+            KeyCode::Enter => chooser.selected(),
+            KeyCode::Char(c @ '1'..='9') => c as usize - '1' as usize,
+            KeyCode::Esc => {
+                if self.confirm_overlay_esc(esc_armed) {
+                    self.code_block_chooser = None;
+                }
+                return ChatKeyResult::Consumed;
+            }
+            _ => {
+                match self.registry.find_action(key) {
+                    Some(Action::NavigateDown) => chooser.select_next(),
+                    Some(Action::NavigateUp) => chooser.select_previous(),
+                    Some(Action::Cancel) => self.code_block_chooser = None,
+                    _ => {}
+                }
+                return ChatKeyResult::Consumed;
+            }
+        };
+        if index >= chooser.len() {
+            return ChatKeyResult::Consumed;
+        }
+        let message_id = chooser.message_id();
+        self.code_block_chooser = None;
+        self.message_pane_data
+            .messages()
+            .iter()
+            .find(|m| m.message.id() == message_id)
+            .and_then(|m| m.code_block(index))
+            .map_or(ChatKeyResult::Consumed, |code| {
+                ChatKeyResult::CopyCodeBlock(code.to_string())
+            })
+    }
+
+    /// Resolves the open confirmation modal: Enter accepts, Esc or `n` backs out.
+    fn handle_confirmation_key(&mut self, key: KeyEvent, esc_armed: bool) -> ChatKeyResult {
+        let pending = self.pending_confirmation.clone();
+        let return_focus = match pending {
+            Some(PendingConfirmation::Logout { return_focus, .. }) => return_focus,
+            Some(PendingConfirmation::SendEveryone { .. } | PendingConfirmation::LargePaste(_)) => {
+                ChatFocus::MessageInput
+            }
+            _ => ChatFocus::MessagesList,
+        };
+        match key.code {
+            KeyCode::Enter => {
+                self.pending_confirmation = None;
+                self.set_focus(return_focus);
+                match pending {
+                    Some(PendingConfirmation::DeleteMessage(id)) => {
+                        ChatKeyResult::DeleteMessage(id)
+                    }
+                    Some(PendingConfirmation::PurgeMessages(ids)) => {
+                        ChatKeyResult::PurgeMessages(ids)
+                    }
+                    Some(PendingConfirmation::OpenLink(link)) => ChatKeyResult::OpenLink(link.url),
+                    Some(PendingConfirmation::SendEveryone {
+                        content,
+                        reply_to,
+                        attachments,
+                        mode,
+                    }) => {
+                        self.message_pane_state.clear_selection();
+                        ChatKeyResult::SendMessage {
+                            content,
+                            reply_to,
+                            mention_reply: mode.mentions_reply(),
+                            mass_mention_confirmed: true,
+                            attachments,
+                        }
+                    }
+                    Some(PendingConfirmation::Logout { secure: true, .. }) => {
+                        ChatKeyResult::SecureLogout
+                    }
+                    Some(PendingConfirmation::Logout { secure: false, .. }) => {
+                        ChatKeyResult::Logout
+                    }
+                    Some(PendingConfirmation::LargePaste(text)) => ChatKeyResult::PasteAsFile(text),
+                    None => ChatKeyResult::Consumed,
+                }
+            }
+            KeyCode::Char('t') if matches!(pending, Some(PendingConfirmation::LargePaste(_))) => {
+                self.pending_confirmation = None;
+                if let Some(PendingConfirmation::LargePaste(text)) = pending {
+                    self.insert_text(&text);
+                }
+                ChatKeyResult::Consumed
+            }
+            KeyCode::Esc if !self.confirm_overlay_esc(esc_armed) => ChatKeyResult::Consumed,
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.pending_confirmation = None;
+                if let Some(PendingConfirmation::SendEveryone {
+                    content,
+                    attachments,
+                    mode,
+                    ..
+                }) = pending
+                {
+                    self.restore_unsent_draft(&content, attachments, mode);
+                }
+                self.set_focus(return_focus);
+                ChatKeyResult::Consumed
+            }
+            _ => ChatKeyResult::Consumed,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        let esc_armed = std::mem::take(&mut self.overlay_esc_armed);
+
+        if self.show_help {
+            if let Some(action) = self.registry.find_action(key)
+                && matches!(action, Action::ToggleHelp | Action::Quit | Action::Cancel)
+                && (action != Action::Cancel || self.confirm_overlay_esc(esc_armed))
+            {
+                self.toggle_help();
+                return ChatKeyResult::Consumed;
+            }
+            return ChatKeyResult::Consumed;
+        }
+
+        if self.focus == ChatFocus::ConfirmationModal {
+            return self.handle_confirmation_key(key, esc_armed);
+        }
+
+        if self.code_block_chooser.is_some() {
+            return self.handle_code_block_chooser_key(key, esc_armed);
+        }
+
+        if self.show_file_explorer {
+            if let Some(action) = self.registry.find_action(key)
+                && (action == Action::ToggleFileExplorer || action == Action::Cancel)
+            {
+                if action != Action::Cancel || self.confirm_overlay_esc(esc_armed) {
+                    self.toggle_file_explorer();
+                }
+                return ChatKeyResult::Consumed;
+            }
+            return self.handle_file_explorer_key(key);
+        }
+
+        if let Some(action) = self.registry.find_action(key)
+            && action == Action::ToggleQuickSwitcher
+            && !self.show_quick_switcher
+            && !(self.focus == ChatFocus::MessageInput && is_text_key(key))
+        {
+            self.toggle_quick_switcher();
+            return ChatKeyResult::Consumed;
+        }
+
+        if self.show_quick_switcher {
+            match self.registry.find_action(key) {
+                Some(Action::Cancel) => {
+                    if self.confirm_overlay_esc(esc_armed) {
+                        self.toggle_quick_switcher();
                     }
+                    return ChatKeyResult::Consumed;
+                }
+                Some(Action::ClearRecents) => return self.clear_recents(),
+                _ => {}
+            }
+            return self.handle_quick_switcher_key(key);
+        }
+
+        if self.focus != ChatFocus::MessageInput {
+            match self.registry.feed_sequence(key) {
+                SequenceMatch::Pending => return ChatKeyResult::Consumed,
+                SequenceMatch::Complete(action) => {
+                    self.registry.set_sequence_action(Some(action));
+                    let result = self.handle_focused_key(key);
+                    self.registry.set_sequence_action(None);
+                    return result;
+                }
+                SequenceMatch::None => {}
+            }
+        }
+
+        self.handle_focused_key(key)
+    }
+
+    fn handle_focused_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        if self.focus == ChatFocus::MessageInput {
+            let result = self.handle_message_input_key(key);
+            if result != ChatKeyResult::Ignored {
+                return result;
+            }
+        } else {
+            match self.focus {
+                ChatFocus::GuildsTree => {
+                    let result = self.handle_guilds_tree_key(key);
+                    if result != ChatKeyResult::Ignored {
+                        return result;
+                    }
+                }
+                ChatFocus::MessagesList => {
+                    let result = self.handle_messages_list_key(key);
+                    if result != ChatKeyResult::Ignored {
+                        return result;
+                    }
+                }
+                ChatFocus::ConfirmationModal => return ChatKeyResult::Consumed,
+                ChatFocus::MessageInput => unreachable!(),
+            }
+        }
+
+        if let Some(result) = self.handle_global_key(key) {
+            return result;
+        }
+
+        ChatKeyResult::Consumed
+    }
+
+    fn handle_global_key(&mut self, key: KeyEvent) -> Option<ChatKeyResult> {
+        match self.registry.find_action(key) {
+            Some(Action::Quit) => Some(ChatKeyResult::Quit),
+            Some(Action::Logout) => Some(self.request_logout(false)),
+            Some(Action::SecureLogout) => Some(self.request_logout(true)),
+            Some(Action::FocusGuilds) => {
+                self.focus_guilds_tree();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::FocusMessages) => {
+                self.focus_messages_list();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::FocusInput) => {
+                if self.focus == ChatFocus::MessageInput {
+                    return None;
+                }
+                self.focus_message_input();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::FocusPrevious) => {
+                self.focus_previous();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::FocusNext | Action::NextTab) => {
+                self.focus_next();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::ToggleGuildsTree) => {
+                self.toggle_guilds_tree();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::ToggleHelp) => {
+                if self.focus == ChatFocus::MessageInput && matches!(key.code, KeyCode::Char(_)) {
+                    return None;
+                }
+                self.toggle_help();
+                Some(ChatKeyResult::ToggleHelp)
+            }
+            Some(Action::ToggleDisplayName) => Some(ChatKeyResult::ToggleDisplayName),
+            Some(Action::ToggleImagePreview) => Some(ChatKeyResult::ToggleImagePreview),
+            Some(Action::ChangeNickname) => Some(self.start_nick_command()),
+
+            Some(Action::ToggleQuickSwitcher) => {
+                self.toggle_quick_switcher();
+                Some(ChatKeyResult::Consumed)
+            }
+            Some(Action::CopyUnreadChannels) => Some(self.copy_unread_channels()),
+            Some(Action::ToggleNotificationHistory) => {
+                Some(ChatKeyResult::ToggleNotificationHistory)
+            }
+            Some(Action::ClearRecents) => Some(self.clear_recents()),
+            _ => None,
+        }
+    }
+
+    fn handle_guilds_tree_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        let style = self.styles.guilds_tree.clone();
+        let expansion = self.guilds_tree_state.persisted_expansion();
+
+        let action = self.guilds_tree_state.handle_key(
+            key,
+            &self.guilds_tree_data,
+            &self.registry,
+            &style,
+            self.use_display_name,
+        );
+        if self.guilds_tree_state.persisted_expansion() != expansion {
+            self.tree_expansion_changed = true;
+        }
+
+        if let Some(action) = action {
+            match action {
+                GuildsTreeAction::SelectChannel(channel_id) => {
+                    if let Some(result) = self.on_channel_selected(channel_id) {
+                        return result;
+                    }
+                }
+                GuildsTreeAction::SelectGuild(guild_id) => {
+                    if let Some(result) = self.on_guild_selected(guild_id) {
+                        return result;
+                    }
+                }
+                GuildsTreeAction::SelectDirectMessage(dm_channel_id) => {
+                    if let Some(result) = self.on_dm_selected(&dm_channel_id) {
+                        return result;
+                    }
+                }
+                GuildsTreeAction::YankId(id) => {
+                    return ChatKeyResult::CopyToClipboard(id);
+                }
+                GuildsTreeAction::LoadGuildChannels(guild_id) => {
+                    if self.ensure_guild_channels(guild_id) {
+                        return ChatKeyResult::Consumed;
+                    }
+                    return ChatKeyResult::LoadGuildChannels(guild_id);
+                }
+            }
+        }
+        ChatKeyResult::Ignored
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn handle_messages_list_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        if self.registry.find_action(key) == Some(Action::ToggleThreadList) {
+            return self.toggle_thread_list();
+        }
+
+        if let Some(action) = self.message_pane_state.handle_key(
+            key,
+            &self.message_pane_data,
+            &self.registry,
+            Some(&self.relationship_state),
+            self.hide_blocked_completely,
+        ) {
+            match action {
+                MessagePaneAction::ClearSelection | MessagePaneAction::SelectMessage(_) => {}
+                MessagePaneAction::Reply { .. }
+                | MessagePaneAction::Edit(_)
+                | MessagePaneAction::EditExternal(_)
+                    if self.message_input_state.is_read_only() =>
+                {
+                    return ChatKeyResult::ShowNotification(READ_ONLY_NOTICE.to_string());
+                }
+                MessagePaneAction::Reply { message_id, .. }
+                | MessagePaneAction::Edit(message_id)
+                | MessagePaneAction::EditExternal(message_id)
+                | MessagePaneAction::Delete(message_id)
+                | MessagePaneAction::CopyLink(message_id)
+                    if self.is_unsent(message_id) =>
+                {
+                    return ChatKeyResult::ShowNotification(UNSENT_NOTICE.to_string());
+                }
+                MessagePaneAction::Reply {
+                    message_id,
+                    mention,
+                } => {
+                    return ChatKeyResult::ReplyToMessage {
+                        message_id,
+                        mention,
+                    };
+                }
+                MessagePaneAction::Edit(message_id) => {
+                    if let Some(message) = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                    {
+                        if message.message.can_be_edited_by(&self.user) {
+                            self.message_input_state
+                                .start_edit(message_id, message.message.content());
+                            self.focus_message_input();
+                        } else {
+                            return ChatKeyResult::ShowNotification(
+                                "You can only edit your own messages".to_string(),
+                            );
+                        }
+                    }
+                }
+                MessagePaneAction::EditExternal(message_id) => {
+                    if let Some(message) = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                    {
+                        if message.message.can_be_edited_by(&self.user) {
+                            return ChatKeyResult::OpenEditor {
+                                initial_content: message.message.content().to_string(),
+                                message_id: Some(message_id),
+                            };
+                        }
+                        return ChatKeyResult::ShowNotification(
+                            "You can only edit your own messages".to_string(),
+                        );
+                    }
+                }
+                MessagePaneAction::Delete(message_id) => {
+                    if let Some(message) = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                    {
+                        if message.message.can_be_edited_by(&self.user) {
+                            self.pending_confirmation =
+                                Some(PendingConfirmation::DeleteMessage(message_id));
+                            self.set_focus(ChatFocus::ConfirmationModal);
+                            return ChatKeyResult::Consumed;
+                        }
+
+                        return ChatKeyResult::ShowNotification(
+                            "You can only delete your own messages".to_string(),
+                        );
+                    }
+                }
+                MessagePaneAction::YankContent(content) | MessagePaneAction::YankUrl(content) => {
+                    return ChatKeyResult::CopyToClipboard(content);
+                }
+                MessagePaneAction::CopyImage(image_id) => {
+                    return ChatKeyResult::CopyImageToClipboard(image_id);
+                }
+                MessagePaneAction::CopyCodeBlock(message_id) => {
+                    return self.copy_code_block(message_id);
+                }
+                MessagePaneAction::YankId(id) => {
+                    return ChatKeyResult::CopyToClipboard(id);
+                }
+                MessagePaneAction::CopyLink(message_id) => {
+                    if let Some(message) = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                        .map(|m| &m.message)
+                    {
+                        // The tree has no entries for threads and forum posts,
+                        // so take the guild from the open channel instead.
+                        let guild_id = self.active_guild_id();
+                        return ChatKeyResult::CopyToClipboard(message.link(guild_id));
+                    }
+                }
+                MessagePaneAction::OpenAttachments(message_id) => {
+                    if let Some(message) = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                        .map(|m| &m.message)
+                    {
+                        return match MessageContentService::resolve(message) {
+                            MessageContentAction::OpenImages => {
+                                ChatKeyResult::OpenAttachments(message_id)
+                            }
+                            MessageContentAction::OpenLink(link) => self.open_link(link),
+                            MessageContentAction::None => ChatKeyResult::Ignored,
+                        };
+                    }
+                }
+                MessagePaneAction::JumpToReply(message_id) => {
+                    return ChatKeyResult::JumpToMessage(message_id);
+                }
+                MessagePaneAction::RetrySend(message_id) => {
+                    return ChatKeyResult::RetrySend(message_id);
+                }
+                MessagePaneAction::Publish(message_id) => {
+                    let publishable = self
+                        .message_pane_data
+                        .messages()
+                        .iter()
+                        .find(|m| m.message.id() == message_id)
+                        .is_some_and(|m| self.can_publish(&m.message));
+                    if publishable {
+                        return ChatKeyResult::PublishMessage(message_id);
+                    }
+                    return ChatKeyResult::ShowNotification(
+                        "Only your own announcements can be published".to_string(),
+                    );
+                }
+                MessagePaneAction::JumpToMention { forward } => {
+                    if !self.jump_to_mention(forward) {
+                        return ChatKeyResult::ShowNotification(
+                            "No mentions in this channel".to_string(),
+                        );
+                    }
+                }
+                MessagePaneAction::PurgeOwn => {
+                    let ids = self.own_recent_message_ids();
+                    if ids.is_empty() {
+                        return ChatKeyResult::ShowNotification(
+                            "You have no messages to purge here".to_string(),
+                        );
+                    }
+                    self.pending_confirmation = Some(PendingConfirmation::PurgeMessages(ids));
+                    self.set_focus(ChatFocus::ConfirmationModal);
+                    return ChatKeyResult::Consumed;
+                }
+                MessagePaneAction::OpenThread(channel_id) => {
+                    if let Some(result) = self.on_channel_selected(channel_id) {
+                        return result;
+                    }
+
+                    if let ViewMode::Forum(state) = &self.message_pane_state.view_mode
+                        && let Some(thread) = state.threads.iter().find(|t| t.id == channel_id)
+                    {
+                        let parent_id = self.selected_channel.as_ref().map(Channel::id);
+
+                        let mut channel =
+                            Channel::new(thread.id, thread.name.clone(), ChannelKind::PublicThread)
+                                .with_guild(thread.guild_id.unwrap_or(GuildId(0)).as_u64());
+
+                        if let Some(pid) = parent_id {
+                            channel = channel.with_parent(pid);
+                        }
+
+                        if let Some(guild_id) = thread.guild_id {
+                            self.selected_channel = Some(channel.clone());
+                            self.apply_accent_override();
+                            self.sync_pane_guild();
+                            self.sync_send_permission();
+                            self.message_pane_data
+                                .set_channel(channel_id, channel.display_name());
+                            self.message_pane_state.on_channel_change();
+                            self.message_input_state.set_has_channel(true);
+                            self.message_input_state.clear();
+                            self.focus_messages_list();
+
+                            return ChatKeyResult::LoadChannelMessages {
+                                channel_id,
+                                guild_id: Some(guild_id),
+                            };
+                        }
+                    }
+
+                    return ChatKeyResult::JumpToChannel(channel_id);
+                }
+                MessagePaneAction::CloseThread => {
+                    if let ViewMode::Forum(_) = &self.message_pane_state.view_mode {
+                        if self.can_list_threads() {
+                            return self.toggle_thread_list();
+                        }
+                        self.focus_guilds_tree();
+                        return ChatKeyResult::Consumed;
+                    }
+                    if let Some(current_channel) = &self.selected_channel
+                        && let Some(parent_id) = current_channel.parent_id()
+                        && let Some(result) = self.on_channel_selected(parent_id)
+                    {
+                        return result;
+                    }
+                    self.focus_guilds_tree();
+                }
+                MessagePaneAction::LoadHistory => {
+                    if let ViewMode::Forum(forum_state) = &self.message_pane_state.view_mode
+                        && let Some(channel_id) = self.message_pane_data.channel_id()
+                    {
+                        let offset = u32::try_from(forum_state.threads.len()).unwrap_or(0);
+                        let guild_id = self.selected_guild;
+
+                        return ChatKeyResult::LoadForumThreads {
+                            channel_id,
+                            guild_id,
+                            offset,
+                        };
+                    }
+
+                    if let Some(channel_id) = self.message_pane_data.channel_id()
+                        && let Some(first_msg) = self.message_pane_data.messages().iter().next()
+                    {
+                        return ChatKeyResult::LoadHistory {
+                            channel_id,
+                            before_message_id: first_msg.message.id(),
+                        };
+                    }
+                }
+            }
+        }
+        ChatKeyResult::Ignored
+    }
+
+    /// Whether an `@everyone` sent to the selected channel would actually
+    /// ping anyone. Assumed true while the member's roles are still unknown.
+    fn can_mention_everyone(&self) -> bool {
+        if self
+            .selected_channel
+            .as_ref()
+            .and_then(Channel::guild_id)
+            .is_none()
+        {
+            return false;
+        }
+        self.channel_permissions()
+            .is_none_or(|perms| perms.contains(Permissions::MENTION_EVERYONE))
+    }
+
+    /// Permissions the user holds in the selected guild channel, taken from
+    /// the parent for threads. `None` in DMs and while roles are loading.
+    fn channel_permissions(&self) -> Option<Permissions> {
+        let channel = self.selected_channel.as_ref()?;
+        let guild_id = channel.guild_id()?;
+        let member = self.guild_members.get(&guild_id)?;
+        let roles = self.guild_roles.get(&guild_id)?;
+        let source = channel
+            .parent_id()
+            .filter(|_| channel.kind().is_thread())
+            .and_then(|parent_id| self.guilds_tree_data.get_channel(parent_id))
+            .unwrap_or(channel);
+        Some(PermissionCalculator::compute_permissions(
+            guild_id.as_u64(),
+            source,
+            member,
+            roles,
+        ))
+    }
+
+    /// Whether the user may post in the selected channel. Archived or locked
+    /// threads additionally need Manage Threads.
+    fn can_send_messages(&self) -> bool {
+        let Some(channel) = &self.selected_channel else {
+            return true;
+        };
+        let mut required = if channel.kind().is_thread() {
+            Permissions::SEND_MESSAGES_IN_THREADS
+        } else {
+            Permissions::SEND_MESSAGES
+        };
+        if channel
+            .thread_metadata()
+            .is_some_and(|meta| meta.archived || meta.locked)
+        {
+            required |= Permissions::MANAGE_THREADS;
+        }
+        self.channel_permissions()
+            .is_none_or(|perms| perms.contains(required))
+    }
+
+    fn can_attach_files(&self) -> bool {
+        self.can_send_messages()
+            && self
+                .channel_permissions()
+                .is_none_or(|perms| perms.contains(Permissions::ATTACH_FILES))
+    }
+
+    /// Puts the input in read-only mode when the user cannot send here.
+    fn sync_send_permission(&mut self) {
+        let read_only = !self.can_send_messages();
+        self.message_input_state.set_read_only(read_only);
+    }
+
+    /// Whether the selected channel is a text channel whose active threads
+    /// can be listed in place of its messages.
+    fn can_list_threads(&self) -> bool {
+        self.selected_channel.as_ref().is_some_and(|channel| {
+            matches!(
+                channel.kind(),
+                ChannelKind::Text | ChannelKind::Announcement
+            )
+        })
+    }
+
+    /// Switches a text channel between its messages and its active threads.
+    fn toggle_thread_list(&mut self) -> ChatKeyResult {
+        if !self.can_list_threads() {
+            return ChatKeyResult::Ignored;
+        }
+        let Some(channel_id) = self.message_pane_data.channel_id() else {
+            return ChatKeyResult::Ignored;
+        };
+
+        if let ViewMode::Forum(_) = &self.message_pane_state.view_mode {
+            self.message_pane_state.view_mode = ViewMode::Messages;
+            self.message_pane_data.mark_dirty();
+            return ChatKeyResult::Consumed;
+        }
+
+        self.message_pane_state.view_mode = ViewMode::Forum(ForumState::default());
+        ChatKeyResult::LoadForumThreads {
+            channel_id,
+            guild_id: self.selected_guild,
+            offset: 0,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn handle_message_input_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        let is_text_editing = matches!(
+            key.code,
+            KeyCode::Backspace | KeyCode::Delete | KeyCode::Char('w' | 'h')
+        ) && (key.modifiers.contains(KeyModifiers::CONTROL)
+            || key.modifiers.contains(KeyModifiers::ALT));
+
+        if !is_text_editing
+            && matches!(
+                self.registry.find_action(key),
+                Some(
+                    Action::FocusMessages
+                        | Action::FocusGuilds
+                        | Action::FocusNext
+                        | Action::FocusPrevious
+                        | Action::NextTab
+                        | Action::ToggleGuildsTree
+                        | Action::ToggleQuickSwitcher
+                )
+            )
+        {
+            return ChatKeyResult::Ignored;
+        }
+
+        if self.registry.find_action(key) == Some(Action::ToggleFileExplorer) {
+            if !self.can_attach_files() {
+                return ChatKeyResult::ShowNotification(
+                    "You cannot attach files in this channel".to_string(),
+                );
+            }
+            self.toggle_file_explorer();
+            return ChatKeyResult::Consumed;
+        }
+
+        if self.handle_autocomplete_navigation(key) {
+            return ChatKeyResult::Consumed;
+        }
+
+        let autocomplete_changed;
+        let input_mode = self.message_input_state.mode().clone();
+
+        if let Some(action) = self.message_input_state.handle_key(key, &self.registry) {
+            let value = self.message_input_state.value();
+            let cursor_idx = self.message_input_state.get_cursor_index();
+            autocomplete_changed = self.autocomplete_service.process_input(&value, cursor_idx);
+
+            match action {
+                MessageInputAction::SendMessage {
+                    content,
+                    reply_to,
+                    attachments,
+                } => {
+                    if attachments.is_empty()
+                        && let Some(nick) = parse_nick_command(&content)
+                    {
+                        return self.nick_command(nick);
+                    }
+                    if attachments.is_empty()
+                        && let Some(result) = self.group_command(&content)
+                    {
+                        return result;
+                    }
+                    return self.submit_message(content, reply_to, attachments, input_mode);
+                }
+                MessageInputAction::EditMessage {
+                    message_id,
+                    content,
+                } => {
+                    return ChatKeyResult::SubmitEdit {
+                        message_id,
+                        content,
+                    };
+                }
+                MessageInputAction::ExitInput => {
+                    self.focus_messages_list();
+                }
+                MessageInputAction::OpenEditor => {
+                    let initial_content = self.message_input_state.value();
+                    let message_id = match self.message_input_state.mode() {
+                        MessageInputMode::Editing { message_id } => Some(*message_id),
+                        _ => None,
+                    };
+                    return ChatKeyResult::OpenEditor {
+                        initial_content,
+                        message_id,
+                    };
+                }
+                MessageInputAction::Paste => {
+                    return ChatKeyResult::Paste;
+                }
+                MessageInputAction::TooLong { length, max } => {
+                    return ChatKeyResult::ShowNotification(format!(
+                        "Message is {length} characters, over the {max} limit"
+                    ));
+                }
+                MessageInputAction::StartTyping | MessageInputAction::CancelReply => {
+                    if autocomplete_changed {
+                        self.update_autocomplete_suggestions();
+                    }
+                    return ChatKeyResult::StartTyping;
+                }
+            }
+        } else {
+            let value = self.message_input_state.value();
+            let cursor_idx = self.message_input_state.get_cursor_index();
+            autocomplete_changed = self.autocomplete_service.process_input(&value, cursor_idx);
+        }
+
+        if autocomplete_changed {
+            self.update_autocomplete_suggestions();
+            return ChatKeyResult::StartTyping;
+        }
+
+        ChatKeyResult::Ignored
+    }
+
+    fn handle_autocomplete_navigation(&mut self, key: KeyEvent) -> bool {
+        if !self.autocomplete_service.state().active {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Up => {
+                self.autocomplete_service.select_previous();
+                true
+            }
+            KeyCode::Down => {
+                self.autocomplete_service.select_next();
+                true
+            }
+            KeyCode::Enter | KeyCode::Tab => {
+                if let Some(candidate) = self.autocomplete_service.state().selected() {
+                    let trigger_idx = self.autocomplete_service.state().trigger_index;
+                    let label = match candidate {
+                        MentionCandidate::User(user) => format!(
+                            "@{}",
+                            IdentityResolver::with_preference(self.use_display_name).resolve(user)
+                        ),
+                        MentionCandidate::Role(role) => format!("@{}", role.name),
+                        MentionCandidate::Channel(channel) => format!("#{}", channel.name()),
+                    };
+                    self.message_input_state.insert_reference(
+                        trigger_idx,
+                        &label,
+                        candidate.markup(),
+                    );
+                }
+                self.autocomplete_service.reset();
+                true
+            }
+            KeyCode::Esc => {
+                self.autocomplete_service.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update_autocomplete_suggestions(&mut self) {
+        if !self.autocomplete_service.state().active {
+            return;
+        }
+
+        let guild_id = self.active_guild_id();
+        let candidates = match self.autocomplete_service.state().kind {
+            MentionKind::User if self.is_group_command_input() => self.group_member_candidates(),
+            MentionKind::User => self.user_mention_candidates(),
+            // `@everyone` shares the guild's ID and has its own syntax.
+            MentionKind::Role => guild_id
+                .and_then(|guild_id| self.guild_roles.get(&guild_id))
+                .into_iter()
+                .flatten()
+                .filter(|role| guild_id.is_some_and(|g| role.id.as_u64() != g.as_u64()))
+                .cloned()
+                .map(MentionCandidate::Role)
+                .collect(),
+            MentionKind::Channel => guild_id
+                .and_then(|guild_id| self.raw_channels.get(&guild_id))
+                .into_iter()
+                .flatten()
+                .filter(|channel| !channel.kind().is_category())
+                .cloned()
+                .map(MentionCandidate::Channel)
+                .collect(),
+        };
+
+        self.autocomplete_service.update_results(
+            candidates,
+            &IdentityResolver::with_preference(self.use_display_name),
+        );
+    }
+
+    /// Users seen in the open channel, starting with ourselves.
+    fn user_mention_candidates(&self) -> Vec<MentionCandidate> {
+        let mut candidates = Vec::new();
+        candidates.push(CachedUser::from_user(&self.user));
+
+        let mut seen_ids = std::collections::HashSet::new();
+        seen_ids.insert(self.user.id().to_string());
+
+        for msg in self.message_pane_data.messages() {
+            let author = msg.message.author();
+            if seen_ids.insert(author.id().to_string()) {
+                if let Some(cached) = self.user_cache.get(author.id()) {
+                    candidates.push(cached);
+                } else {
+                    let cached = CachedUser::new(
+                        author.id(),
+                        author.username(),
+                        author.discriminator(),
+                        author.avatar().map(String::from),
+                        author.global_name.clone(),
+                        author.is_bot(),
+                    );
+                    candidates.push(cached);
+                }
+            }
+
+            for mention in msg.message.mentions() {
+                if seen_ids.insert(mention.id().to_string()) {
+                    if let Some(cached) = self.user_cache.get(&mention.id_str()) {
+                        candidates.push(cached);
+                    } else {
+                        candidates.push(CachedUser::from_user(mention));
+                    }
+                }
+            }
+        }
+
+        candidates.into_iter().map(MentionCandidate::User).collect()
+    }
+
+    /// Users a group DM can be created with or extended by: DM recipients
+    /// first, then everyone else we know of. Leaves out the current user and
+    /// anyone already in the selected group.
+    fn group_member_candidates(&self) -> Vec<MentionCandidate> {
+        let selected = self.selected_group_dm().map(|id| id.to_string());
+        let mut seen_ids = std::collections::HashSet::new();
+        seen_ids.insert(self.user.id().to_string());
+
+        let mut recipients = Vec::new();
+        for dm in self.guilds_tree_data.dm_users() {
+            match &dm.kind {
+                DmKind::Direct => recipients.push(CachedUser::new(
+                    dm.recipient_id.clone(),
+                    dm.recipient_username.clone(),
+                    dm.recipient_discriminator.clone(),
+                    None,
+                    dm.recipient_global_name.clone(),
+                    false,
+                )),
+                DmKind::Group {
+                    recipients: members,
+                    ..
+                } => {
+                    let in_selected = selected.as_deref() == Some(dm.channel_id.as_str());
+                    for member in members {
+                        if in_selected {
+                            seen_ids.insert(member.id.clone());
+                        } else {
+                            recipients.push(CachedUser::new(
+                                member.id.clone(),
+                                member.username.clone(),
+                                member.discriminator.clone(),
+                                None,
+                                member.global_name.clone(),
+                                false,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut known = self.user_cache.users();
+        known.sort_by(|a, b| a.username().cmp(b.username()));
+
+        recipients
+            .into_iter()
+            .map(|user| self.user_cache.get(user.id()).unwrap_or(user))
+            .chain(known.into_iter().filter(|user| !user.is_bot()))
+            .filter(|user| seen_ids.insert(user.id().to_string()))
+            .map(MentionCandidate::User)
+            .collect()
+    }
+
+    fn register_channel_mentions(&mut self, messages: &[Message]) -> Vec<ChannelId> {
+        static MENTION_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<#(\d+)>").unwrap());
+        static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"https?://(?:ptb\.|canary\.)?discord(?:app)?\.com/channels/\d+/(\d+)")
+                .unwrap()
+        });
+
+        let mut unknown_ids = Vec::new();
+
+        for msg in messages {
+            let content = msg.content();
+            let channel_ids = MENTION_RE
+                .captures_iter(content)
+                .filter_map(|c| c.get(1))
+                .chain(URL_RE.captures_iter(content).filter_map(|c| c.get(1)));
+
+            for id_match in channel_ids {
+                if let Ok(id) = id_match.as_str().parse::<u64>() {
+                    let channel_id = ChannelId(id);
+                    if let Some(channel) = self.guilds_tree_data.get_channel(channel_id) {
+                        let icon = if channel.kind().is_thread() {
+                            "󰙯 "
+                        } else if channel.kind() == ChannelKind::Forum {
+                            "󰠄 "
+                        } else {
+                            "#"
+                        };
+                        let name = format!("{}{}", icon, channel.name());
+                        self.message_pane_data
+                            .register_channel(id.to_string(), name);
+                    } else if !self.message_pane_data.is_channel_known(&id.to_string()) {
+                        unknown_ids.push(channel_id);
+                    }
+                }
+            }
+        }
+        unknown_ids
+    }
+
+    fn on_channel_selected(&mut self, channel_id: ChannelId) -> Option<ChatKeyResult> {
+        self.ensure_channel_guild(channel_id);
+
+        let mut guild_id = self.selected_guild;
+
+        if let Some(id) = guild_id {
+            let channel_in_guild = self
+                .guilds_tree_data
+                .channels(id)
+                .is_some_and(|channels| channels.iter().any(|c| c.id() == channel_id));
+            if !channel_in_guild {
+                guild_id = None;
+            }
+        }
+
+        if guild_id.is_none() {
+            guild_id = self.guilds_tree_data.find_guild_for_channel(channel_id);
+        }
+
+        let channel_info = if let Some(guild_id) = guild_id
+            && let Some(channels) = self.guilds_tree_data.channels(guild_id)
+            && let Some(channel) = channels.iter().find(|c| c.id() == channel_id)
+        {
+            Some((guild_id, channel.clone(), channel.topic().map(String::from)))
+        } else {
+            None
+        };
+
+        if let Some((guild_id, mut channel, topic)) = channel_info {
+            if let Some(rs) = self.read_states.get_mut(&channel.id()) {
+                rs.mention_count = 0;
+            }
+            self.recalculate_all_unread();
+
+            if let Some(ch) = self.guilds_tree_data.get_channel(channel.id()) {
+                channel = ch.clone();
+            }
+
+            self.selected_guild = Some(guild_id);
+            self.selected_channel = Some(channel.clone());
+            self.apply_accent_override();
+            self.sync_pane_guild();
+            self.sync_send_permission();
+
+            let search_kind = match channel.kind() {
+                ChannelKind::Voice | ChannelKind::StageVoice => SearchKind::Voice,
+                ChannelKind::Forum => SearchKind::Forum,
+                ChannelKind::PublicThread
+                | ChannelKind::PrivateThread
+                | ChannelKind::AnnouncementThread => SearchKind::Thread,
+                ChannelKind::Category => {
+                    return None;
+                }
+                _ => SearchKind::Channel,
+            };
+
+            self.add_recent_item(crate::domain::search::RecentItem {
+                id: channel_id.to_string(),
+                name: channel.name().to_string(),
+                kind: search_kind,
+                guild_id: Some(guild_id.to_string()),
+                timestamp: chrono::Utc::now().timestamp(),
+            });
+
+            self.guilds_tree_data.set_active_guild(Some(guild_id));
+            self.guilds_tree_data.set_active_channel(Some(channel_id));
+            self.guilds_tree_data.set_active_dm_user(None);
+
+            let channel_name = channel.display_name();
+
+            if let Some(current_channel_id) = self.message_pane_data.channel_id()
+                && let ViewMode::Forum(state) = &self.message_pane_state.view_mode
+            {
+                self.forum_states.insert(current_channel_id, state.clone());
+            }
+
+            self.stash_draft();
+            self.message_pane_data.set_channel(channel_id, channel_name);
+            self.message_pane_state.on_channel_change();
+
+            if channel.kind() == ChannelKind::Forum {
+                if let Some(saved_state) = self.forum_states.get(&channel_id) {
+                    self.message_pane_state.view_mode = ViewMode::Forum(saved_state.clone());
+                } else {
+                    self.message_pane_state.view_mode = ViewMode::Forum(ForumState::default());
+                }
+            }
+
+            self.message_pane_data.set_channel_topic(topic);
+            self.message_input_state.set_has_channel(true);
+            self.message_input_state.clear();
+            self.restore_draft(channel_id);
+            self.focus_messages_list();
+
+            if channel.kind() == ChannelKind::Forum {
+                if let ViewMode::Forum(ref state) = self.message_pane_state.view_mode
+                    && !state.threads.is_empty()
+                {
+                    return None;
+                }
+
+                return Some(ChatKeyResult::LoadForumThreads {
+                    channel_id,
+                    guild_id: Some(guild_id),
+                    offset: 0,
+                });
+            }
+            return Some(ChatKeyResult::LoadChannelMessages {
+                channel_id,
+                guild_id: Some(guild_id),
+            });
+        }
+        None
+    }
+
+    fn on_guild_selected(&mut self, guild_id: GuildId) -> Option<ChatKeyResult> {
+        if self.selected_guild == Some(guild_id) {
+            return None;
+        }
+
+        if let Some(guild) = self
+            .guilds_tree_data
+            .guilds()
+            .iter()
+            .find(|g| g.id() == guild_id)
+        {
+            self.add_recent_item(crate::domain::search::RecentItem {
+                id: guild_id.to_string(),
+                name: guild.name().to_string(),
+                kind: SearchKind::Guild,
+                guild_id: None,
+                timestamp: chrono::Utc::now().timestamp(),
+            });
+        }
+
+        self.selected_guild = Some(guild_id);
+        self.selected_channel = None;
+        self.apply_accent_override();
+        self.guilds_tree_data.set_active_channel(None);
+        self.guilds_tree_data.set_active_guild(Some(guild_id));
+        self.ensure_guild_channels(guild_id);
+
+        if self.guilds_tree_data.channels(guild_id).is_none() {
+            return Some(ChatKeyResult::LoadGuildChannels(guild_id));
+        }
+
+        self.show_empty_guild_if_inaccessible(guild_id);
+        None
+    }
+
+    /// Whether permission filtering hid every channel the guild has.
+    fn guild_has_no_accessible_channels(&self, guild_id: GuildId) -> bool {
+        self.raw_channels
+            .get(&guild_id)
+            .is_some_and(|raw| !raw.is_empty())
+            && self
+                .guilds_tree_data
+                .channels(guild_id)
+                .is_some_and(|visible| visible.iter().next().is_none())
+    }
+
+    /// Shows the empty-guild notice when the selected guild has nothing the
+    /// user can open, rather than leaving the last channel on screen.
+    fn show_empty_guild_if_inaccessible(&mut self, guild_id: GuildId) {
+        if self.selected_guild != Some(guild_id)
+            || self.selected_channel.is_some()
+            || !self.guild_has_no_accessible_channels(guild_id)
+        {
+            return;
+        }
+        self.stash_draft();
+        self.message_pane_data.set_no_accessible_channels();
+        self.message_pane_state.on_channel_change();
+        self.message_input_state.set_has_channel(false);
+    }
+
+    fn on_dm_selected(&mut self, dm_channel_id: &str) -> Option<ChatKeyResult> {
+        let (channel_id, recipient_name, is_group) =
+            if let Some(dm_info) = self.dm_channels.get(dm_channel_id) {
+                (
+                    dm_info.channel_id(),
+                    dm_info.recipient_name().to_string(),
+                    dm_info.is_group(),
+                )
+            } else {
+                return None;
+            };
+
+        self.add_recent_item(crate::domain::search::RecentItem {
+            id: dm_channel_id.to_string(),
+            name: recipient_name.clone(),
+            kind: SearchKind::DM,
+            guild_id: None,
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+
+        if let Some(rs) = self.read_states.get_mut(&channel_id) {
+            rs.mention_count = 0;
+        }
+        self.recalculate_all_unread();
+
+        let kind = if is_group {
+            ChannelKind::GroupDm
+        } else {
+            ChannelKind::Dm
+        };
+        let dm_channel = Channel::new(channel_id, recipient_name.clone(), kind);
+        self.selected_channel = Some(dm_channel);
+        self.selected_guild = None;
+        self.apply_accent_override();
+        self.sync_pane_guild();
+        self.sync_send_permission();
+        self.guilds_tree_data.set_active_guild(None);
+        self.guilds_tree_data.set_active_channel(None);
+        self.guilds_tree_data
+            .set_active_dm_user(Some(dm_channel_id.to_string()));
+
+        let display_name = if is_group {
+            recipient_name.clone()
+        } else {
+            format!("@{recipient_name}")
+        };
+        self.stash_draft();
+        self.message_pane_data.set_channel(channel_id, display_name);
+        self.message_pane_state.on_channel_change();
+        self.message_input_state.set_has_channel(true);
+        self.message_input_state.clear();
+        self.restore_draft(channel_id);
+
+        self.focus_messages_list();
+
+        Some(ChatKeyResult::LoadDmMessages {
+            channel_id,
+            recipient_name,
+        })
+    }
+
+    #[must_use]
+    pub const fn guilds_tree_data(&self) -> &GuildsTreeData {
+        &self.guilds_tree_data
+    }
+
+    pub const fn guilds_tree_state_mut(&mut self) -> &mut GuildsTreeState {
+        &mut self.guilds_tree_state
+    }
+
+    pub const fn guilds_tree_parts_mut(&mut self) -> (&GuildsTreeData, &mut GuildsTreeState) {
+        (&self.guilds_tree_data, &mut self.guilds_tree_state)
+    }
+
+    pub fn set_messages(&mut self, messages: Vec<Message>) -> Option<ChatKeyResult> {
+        let unknown = self.register_channel_mentions(&messages);
+        if let Some(last) = messages.last() {
+            self.remember_dm_snippet(last);
+        }
+        self.message_pane_data.set_messages(messages);
+        if unknown.is_empty() {
+            None
+        } else {
+            Some(ChatKeyResult::RequestChannelFetch(unknown))
+        }
+    }
+
+    pub fn set_forum_threads(
+        &mut self,
+        mut threads: Vec<crate::domain::entities::ForumThread>,
+        offset: u32,
+    ) {
+        self.message_pane_data.set_loading_more(false);
+        for thread in &mut threads {
+            let read_state = self.read_states.get(&thread.id);
+            thread.new = match (read_state, thread.last_message_id) {
+                (Some(rs), Some(last_msg_id)) => rs.last_read_message_id != Some(last_msg_id),
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+        }
+
+        if let ViewMode::Forum(state) = &mut self.message_pane_state.view_mode {
+            if offset > 0 {
+                let old_selection_id = state.threads.get(state.selected_idx).map(|t| t.id);
+                let added_count = threads.len();
+
+                let mut new_list = threads;
+                new_list.append(&mut state.threads);
+                state.threads = new_list;
+
+                if let Some(id) = old_selection_id
+                    && let Some(new_idx) = state.threads.iter().position(|t| t.id == id)
+                {
+                    state.selected_idx = new_idx;
+                    state.scroll_offset = state
+                        .scroll_offset
+                        .saturating_add(u16::try_from(added_count).unwrap_or(0));
+                }
+                return;
+            }
+
+            let was_empty = state.threads.is_empty();
+            let was_at_bottom = state.selected_idx + 1 >= state.threads.len();
+            let invalid_selection = state.selected_idx >= state.threads.len();
+
+            state.threads = threads;
+
+            if (was_empty || was_at_bottom || invalid_selection) && !state.threads.is_empty() {
+                state.selected_idx = state.threads.len().saturating_sub(1);
+                state.needs_scroll_to_selection = true;
+            }
+        } else if !self.can_list_threads() {
+            let mut state = crate::presentation::widgets::ForumState {
+                threads,
+                ..Default::default()
+            };
+            if !state.threads.is_empty() {
+                state.selected_idx = state.threads.len().saturating_sub(1);
+                state.needs_scroll_to_selection = true;
+            }
+            self.message_pane_state.view_mode = ViewMode::Forum(state);
+        }
+    }
+
+    /// Shows a message that is still being sent, under the temporary `local_id`.
+    pub fn add_local_echo(&mut self, local_id: MessageId, request: &SendMessageRequest) {
+        let author = MessageAuthor {
+            id: self.user.id().to_string(),
+            username: self.user.username().to_string(),
+            discriminator: self.user.discriminator().to_string(),
+            avatar: self.user.avatar().map(String::from),
+            bot: self.user.is_bot(),
+            global_name: self.user.global_name().map(String::from),
+        };
+        let message = Message::new(
+            local_id,
+            request.channel_id,
+            author,
+            request.content.clone(),
+            chrono::Local::now(),
+            MessageKind::Default,
+        )
+        .with_nonce(request.nonce.clone());
+        self.message_pane_data.add_pending_message(message);
+        self.message_pane_state.on_new_message();
+    }
+
+    /// Replaces the local echo `local_id` with the confirmed message.
+    pub fn confirm_local_echo(
+        &mut self,
+        local_id: MessageId,
+        message: Message,
+    ) -> Option<ChatKeyResult> {
+        let unknown = self.register_channel_mentions(std::slice::from_ref(&message));
+        self.message_pane_data
+            .confirm_pending_message(local_id, message);
+        if unknown.is_empty() {
+            None
+        } else {
+            Some(ChatKeyResult::RequestChannelFetch(unknown))
+        }
+    }
+
+    pub fn set_send_state(&mut self, message_id: MessageId, send_state: SendState) -> bool {
+        self.message_pane_data
+            .set_send_state(message_id, send_state)
+    }
+
+    pub fn add_message(&mut self, message: Message) -> Option<ChatKeyResult> {
+        let unknown = self.register_channel_mentions(std::slice::from_ref(&message));
+        self.message_pane_data.add_message(message);
+        self.message_pane_state.on_new_message();
+        if unknown.is_empty() {
+            None
+        } else {
+            Some(ChatKeyResult::RequestChannelFetch(unknown))
+        }
+    }
+
+    /// Prepends a page of older history, then continues a pending jump:
+    /// selecting the target once it has loaded, or asking for the next page.
+    pub fn prepend_messages(&mut self, new_messages: Vec<Message>) -> Vec<ChatKeyResult> {
+        self.message_pane_data.set_loading_more(false);
+        if new_messages.is_empty() {
+            return self.end_pending_jump().into_iter().collect();
+        }
+        let unknown = self.register_channel_mentions(&new_messages);
+
+        let width = self.message_pane_state.last_width();
+        let inline_images = self.inline_images();
+        let pane = MessagePane::new(&mut self.message_pane_data, &self.markdown_service)
+            .with_image_preview(inline_images)
+            .with_timestamp_format(&self.timestamp_format)
+            .with_current_user_id(self.user.id().to_string());
+        let added_height: u16 = new_messages
+            .iter()
+            .map(|m| {
+                pane.calculate_message_height(m, width, &self.markdown_service, self.theme.accent)
+            })
+            .sum();
+
+        let added_count = self.message_pane_data.prepend_messages(new_messages);
+
+        if added_count > 0 {
+            self.message_pane_state
+                .adjust_for_prepend(added_count, added_height.into());
+        }
+        let fetch = (!unknown.is_empty()).then_some(ChatKeyResult::RequestChannelFetch(unknown));
+        fetch
+            .into_iter()
+            .chain(self.resume_pending_jump())
+            .collect()
+    }
+
+    fn resume_pending_jump(&mut self) -> Option<ChatKeyResult> {
+        let (channel_id, message_id) = self.pending_jump.take()?;
+        if self.message_pane_data.channel_id() != Some(channel_id) {
+            return None;
+        }
+        self.scroll_to_message_id(message_id)
+    }
+
+    /// Drops a pending jump once history has run out without reaching it.
+    fn end_pending_jump(&mut self) -> Option<ChatKeyResult> {
+        let (channel_id, _) = self.pending_jump.take()?;
+        (self.message_pane_data.channel_id() == Some(channel_id))
+            .then(|| ChatKeyResult::ShowNotification("Message not found in history".to_string()))
+    }
+
+    /// Forgets a pending jump, e.g. after a history page failed to load.
+    pub const fn cancel_pending_jump(&mut self) {
+        self.pending_jump = None;
+    }
+
+    /// Merges messages fetched by [`ChatKeyResult::BackfillChannel`] if the
+    /// channel is still open.
+    pub fn merge_backfilled_messages(
+        &mut self,
+        channel_id: ChannelId,
+        messages: Vec<Message>,
+    ) -> Option<ChatKeyResult> {
+        if self.message_pane_data.channel_id() != Some(channel_id) || messages.is_empty() {
+            return None;
+        }
+        let unknown = self.register_channel_mentions(&messages);
+        self.message_pane_data.merge_messages(messages);
+        if unknown.is_empty() {
+            None
+        } else {
+            Some(ChatKeyResult::RequestChannelFetch(unknown))
+        }
+    }
+
+    pub fn update_message(&mut self, message: Message) -> Option<ChatKeyResult> {
+        let unknown = self.register_channel_mentions(std::slice::from_ref(&message));
+        self.message_pane_data.update_message(message);
+        if unknown.is_empty() {
+            None
+        } else {
+            Some(ChatKeyResult::RequestChannelFetch(unknown))
+        }
+    }
+
+    pub fn remove_message(&mut self, message_id: crate::domain::entities::MessageId) {
+        self.message_pane_data.remove_message(message_id);
+    }
+
+    pub fn set_message_error(&mut self, error: String) {
+        self.message_pane_data.set_error(error);
+    }
+
+    pub fn set_typing_indicator(&mut self, indicator: Option<String>) {
+        self.message_pane_data.set_typing_indicator(indicator);
+    }
+
+    #[must_use]
+    pub const fn message_pane_data(&self) -> &MessagePaneData {
+        &self.message_pane_data
+    }
+
+    pub const fn message_pane_data_mut(&mut self) -> &mut MessagePaneData {
+        &mut self.message_pane_data
+    }
+
+    /// Marks message data as dirty, forcing a re-render.
+    /// Called when blocked user state changes to update message visibility.
+    pub fn mark_messages_dirty(&mut self) {
+        self.message_pane_data.mark_dirty();
+    }
+
+    pub const fn message_pane_parts_mut(
+        &mut self,
+    ) -> (&mut MessagePaneData, &mut MessagePaneState) {
+        (&mut self.message_pane_data, &mut self.message_pane_state)
+    }
+
+    pub fn start_reply(&mut self, message_id: MessageId, author_name: String, mention: bool) {
+        self.message_input_state
+            .start_reply(message_id, author_name, mention);
+        self.focus_message_input();
+    }
+
+    /// Sends composed input, holding it for confirmation first when it would
+    /// ping `@everyone` or `@here`.
+    fn submit_message(
+        &mut self,
+        content: String,
+        reply_to: Option<MessageId>,
+        attachments: Vec<OutgoingAttachment>,
+        mode: MessageInputMode,
+    ) -> ChatKeyResult {
+        if self.confirm_everyone_send && mentions_everyone(&content) && self.can_mention_everyone()
+        {
+            self.pending_confirmation = Some(PendingConfirmation::SendEveryone {
+                content,
+                reply_to,
+                attachments,
+                mode,
+            });
+            self.set_focus(ChatFocus::ConfirmationModal);
+            return ChatKeyResult::Consumed;
+        }
+        self.message_pane_state.clear_selection();
+        ChatKeyResult::SendMessage {
+            content,
+            reply_to,
+            mention_reply: mode.mentions_reply(),
+            mass_mention_confirmed: false,
+            attachments,
+        }
+    }
+
+    /// Puts a send the user backed out of back into the input.
+    fn restore_unsent_draft(
+        &mut self,
+        content: &str,
+        attachments: Vec<OutgoingAttachment>,
+        mode: MessageInputMode,
+    ) {
+        self.message_input_state.set_content(content);
+        for attachment in attachments {
+            self.message_input_state.add_attachment(attachment);
+        }
+        if let MessageInputMode::Reply {
+            message_id,
+            author,
+            mention,
+        } = mode
+        {
+            self.message_input_state
+                .start_reply(message_id, author, mention);
+        }
+    }
+
+    pub fn cancel_reply(&mut self) {
+        self.message_input_state.reset_mode();
+    }
+
+    pub fn clear_input(&mut self) {
+        self.message_input_state.clear();
+    }
+
+    pub fn get_reply_author(&self, message_id: MessageId) -> Option<String> {
+        self.message_pane_data
+            .messages()
+            .iter()
+            .find(|m| m.message.id() == message_id)
+            .map(|m| {
+                IdentityResolver::with_preference(self.use_display_name).resolve(m.message.author())
+            })
+    }
+
+    pub fn message_input_parts_mut(&mut self) -> &mut MessageInputState<'static> {
+        &mut self.message_input_state
+    }
+
+    pub fn message_input_state(&self) -> &MessageInputState<'static> {
+        &self.message_input_state
+    }
+
+    /// Get the current message input value.
+    pub fn message_input_value(&self) -> String {
+        self.message_input_state.value()
+    }
+
+    /// Get the current reply info (`message_id`, author) if in reply mode.
+    pub fn message_input_reply_info(&self) -> Option<(MessageId, String, bool)> {
+        match self.message_input_state.mode() {
+            MessageInputMode::Reply {
+                message_id,
+                author,
+                mention,
+            } => Some((*message_id, author.clone(), *mention)),
+            MessageInputMode::Normal | MessageInputMode::Editing { .. } => None,
+        }
+    }
+
+    /// Set the message input content.
+    pub fn set_message_input_content(&mut self, content: &str) {
+        self.message_input_state.set_content(content);
+    }
+
+    /// Returns a reference to the image manager.
+    #[must_use]
+    pub const fn image_manager(&self) -> &ImageManager {
+        &self.image_manager
+    }
+
+    /// Returns a mutable reference to the image manager.
+    pub fn image_manager_mut(&mut self) -> &mut ImageManager {
+        &mut self.image_manager
+    }
+
+    /// Turns inline image previews on or off. Turning them off frees the
+    /// decoded images; they are reloaded from the cache when turned back on.
+    pub fn set_image_preview(&mut self, enabled: bool) {
+        if self.image_preview == enabled {
+            return;
+        }
+        self.image_preview = enabled;
+
+        if !enabled {
+            for ui_msg in self.message_pane_data.ui_messages_mut() {
+                for attachment in &mut ui_msg.image_attachments {
+                    attachment.unload();
+                }
+            }
+        }
+        self.last_scroll_state = None;
+        self.message_pane_data.mark_dirty();
+    }
+
+    /// Returns true when images are previewed and the terminal can draw them.
+    #[must_use]
+    pub const fn inline_images(&self) -> bool {
+        self.image_preview && self.image_manager.supports_graphics()
+    }
+
+    /// Collects all image attachments that need loading within the visible range.
+    /// Returns a list of (`ImageId`, URL) pairs.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn collect_needed_image_loads(&self) -> Vec<(crate::domain::entities::ImageId, String)> {
+        let mut needed = Vec::new();
+        if !self.inline_images() {
+            return needed;
+        }
+
+        let visible_range = self.calculate_visible_range();
+        let buffer = super::super::widgets::LOAD_BUFFER;
+        let start = visible_range.0.saturating_sub(buffer);
+        let end = (visible_range.1 + buffer).min(self.message_pane_data.message_count());
+
+        for idx in start..end {
+            if let Some(ui_msg) = self.message_pane_data.messages().get(idx) {
+                for load in ui_msg.collect_image_loads() {
+                    needed.push(load);
+                }
+            }
+        }
+
+        needed
+    }
+
+    /// Collects images to prefetch just outside the load buffer.
+    /// Returns a list of (`ImageId`, URL) pairs, nearest first.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn collect_image_prefetches(&self) -> Vec<(crate::domain::entities::ImageId, String)> {
+        if !self.inline_images() {
+            return Vec::new();
+        }
+
+        let (visible_start, visible_end) = self.calculate_visible_range();
+        let messages = self.message_pane_data.messages();
+        super::super::widgets::ImageManager::prefetch_indices(
+            visible_start,
+            visible_end,
+            self.image_prefetch_distance,
+            self.message_pane_data.message_count(),
+        )
+        .into_iter()
+        .filter_map(|idx| messages.get(idx))
+        .flat_map(super::super::widgets::UiMessage::collect_image_loads)
+        .collect()
+    }
+
+    /// Calculates the visible message range based on scroll position.
+    fn calculate_visible_range(&self) -> (usize, usize) {
+        let offset = self.message_pane_state.vertical_scroll;
+        let viewport_height = self.message_pane_state.viewport_height() as usize;
+
+        let mut y = 0;
+        let mut start_idx = 0;
+        let mut end_idx = 0;
+        let mut found_start = false;
+
+        for (idx, msg) in self.message_pane_data.messages().iter().enumerate() {
+            let h = msg.estimated_height as usize;
+
+            if !found_start && y + h > offset {
+                start_idx = idx;
+                found_start = true;
+            }
+
+            if y >= offset + viewport_height {
+                end_idx = idx;
+                break;
+            }
+
+            y += h;
+            end_idx = idx + 1;
+        }
+
+        (start_idx, end_idx)
+    }
+
+    /// Updates an image attachment when it finishes loading.
+    #[cfg(feature = "image")]
+    pub fn on_image_loaded(
+        &mut self,
+        id: &crate::domain::entities::ImageId,
+        image: &std::sync::Arc<image::DynamicImage>,
+    ) {
+        if !self.inline_images() {
+            return;
+        }
+
+        for ui_msg in self.message_pane_data.ui_messages_mut() {
+            for attachment in &mut ui_msg.image_attachments {
+                if &attachment.id == id {
+                    attachment.set_loaded(image.clone());
+                }
+            }
+        }
+        self.message_pane_data.mark_dirty();
+    }
+
+    /// Updates image protocols for visible messages.
+    /// Should be called before rendering.
+    /// Only processes images that need protocol updates.
+    #[cfg(feature = "image")]
+    pub fn update_visible_image_protocols(&mut self, terminal_width: u16) {
+        if self.message_pane_data.is_empty() || !self.inline_images() {
+            return;
+        }
 
-                    if let ViewMode::Forum(state) = &self.message_pane_state.view_mode
-                        && let Some(thread) = state.threads.iter().find(|t| t.id == channel_id)
-                    {
-                        let parent_id = self.selected_channel.as_ref().map(Channel::id);
+        self.image_manager.set_width(terminal_width);
 
-                        let mut channel =
-                            Channel::new(thread.id, thread.name.clone(), ChannelKind::PublicThread)
-                                .with_guild(thread.guild_id.unwrap_or(GuildId(0)).as_u64());
+        let current_scroll = self.message_pane_state.vertical_scroll;
+        let current_height = self.message_pane_state.viewport_height();
 
-                        if let Some(pid) = parent_id {
-                            channel = channel.with_parent(pid);
-                        }
+        let scroll_changed = self.last_scroll_state != Some((current_scroll, current_height));
 
-                        if let Some(guild_id) = thread.guild_id {
-                            self.selected_channel = Some(channel.clone());
-                            self.message_pane_data
-                                .set_channel(channel_id, channel.display_name());
-                            self.message_pane_state.on_channel_change();
-                            self.message_input_state.set_has_channel(true);
-                            self.message_input_state.clear();
-                            self.focus_messages_list();
+        let (visible_start, visible_end) = self.calculate_visible_range();
+        let buffer = super::super::widgets::LOAD_BUFFER;
 
-                            return ChatKeyResult::LoadChannelMessages {
-                                channel_id,
-                                guild_id: Some(guild_id),
-                            };
+        let protocol_start = visible_start.saturating_sub(buffer);
+        let protocol_end = visible_end + buffer;
+        let picker = self.image_manager.picker();
+        let mut dirty = false;
+
+        if scroll_changed {
+            let memory_buffer = buffer * 3;
+            let keep_start = visible_start.saturating_sub(memory_buffer);
+            let keep_end = visible_end + memory_buffer;
+
+            for (idx, ui_msg) in self
+                .message_pane_data
+                .ui_messages_mut()
+                .iter_mut()
+                .enumerate()
+            {
+                let is_in_protocol_range = idx >= protocol_start && idx <= protocol_end;
+                let is_in_memory_range = idx >= keep_start && idx <= keep_end;
+
+                for attachment in &mut ui_msg.image_attachments {
+                    if is_in_protocol_range {
+                        if attachment.is_ready() && attachment.update_protocol_if_needed(picker) {
+                            dirty = true;
                         }
+                    } else if attachment.protocol.is_some() {
+                        attachment.clear_protocol();
                     }
 
-                    return ChatKeyResult::JumpToChannel(channel_id);
-                }
-                MessagePaneAction::CloseThread => {
-                    if let ViewMode::Forum(_) = &self.message_pane_state.view_mode {
-                        self.focus_guilds_tree();
-                        return ChatKeyResult::Consumed;
-                    }
-                    if let Some(current_channel) = &self.selected_channel
-                        && let Some(parent_id) = current_channel.parent_id()
-                        && let Some(result) = self.on_channel_selected(parent_id)
-                    {
-                        return result;
+                    if !is_in_memory_range && attachment.image.is_some() {
+                        attachment.unload();
                     }
-                    self.focus_guilds_tree();
                 }
-                MessagePaneAction::LoadHistory => {
-                    if let ViewMode::Forum(forum_state) = &self.message_pane_state.view_mode
-                        && let Some(channel_id) = self.message_pane_data.channel_id()
-                    {
-                        let offset = u32::try_from(forum_state.threads.len()).unwrap_or(0);
-                        let guild_id = self.selected_guild;
+            }
+            self.last_scroll_state = Some((current_scroll, current_height));
+        } else {
+            let max_idx = self.message_pane_data.message_count();
+            let effective_end = protocol_end.min(max_idx);
+            let effective_start = protocol_start.min(max_idx);
 
-                        return ChatKeyResult::LoadForumThreads {
-                            channel_id,
-                            guild_id,
-                            offset,
-                        };
+            if effective_start < max_idx {
+                let messages = self.message_pane_data.ui_messages_mut();
+                for idx in effective_start..effective_end {
+                    if let Some(ui_msg) = messages.get_mut(idx) {
+                        for attachment in &mut ui_msg.image_attachments {
+                            if attachment.is_ready() && attachment.update_protocol_if_needed(picker)
+                            {
+                                dirty = true;
+                            }
+                        }
                     }
+                }
+            }
+        }
 
-                    if let Some(channel_id) = self.message_pane_data.channel_id()
-                        && let Some(first_msg) = self.message_pane_data.messages().iter().next()
-                    {
-                        return ChatKeyResult::LoadHistory {
-                            channel_id,
-                            before_message_id: first_msg.message.id(),
-                        };
-                    }
+        if dirty {
+            self.message_pane_data.mark_dirty();
+        }
+    }
+
+    /// Marks an image as downloading.
+    #[cfg(feature = "image")]
+    pub fn mark_image_downloading(&mut self, id: &crate::domain::entities::ImageId) {
+        for ui_msg in self.message_pane_data.ui_messages_mut() {
+            for attachment in &mut ui_msg.image_attachments {
+                if &attachment.id == id {
+                    attachment.set_downloading();
                 }
             }
         }
-        ChatKeyResult::Ignored
     }
 
-    fn handle_message_input_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        let is_text_editing = matches!(
-            key.code,
-            KeyCode::Backspace | KeyCode::Delete | KeyCode::Char('w' | 'h')
-        ) && (key.modifiers.contains(KeyModifiers::CONTROL)
-            || key.modifiers.contains(KeyModifiers::ALT));
+    /// Marks an image as failed.
+    #[cfg(feature = "image")]
+    pub fn mark_image_failed(&mut self, id: &crate::domain::entities::ImageId, error: &str) {
+        for ui_msg in self.message_pane_data.ui_messages_mut() {
+            for attachment in &mut ui_msg.image_attachments {
+                if &attachment.id == id {
+                    attachment.set_failed(error.to_owned());
+                }
+            }
+        }
+    }
 
-        if !is_text_editing
-            && matches!(
-                self.registry.find_action(key),
-                Some(
-                    Action::FocusMessages
-                        | Action::FocusGuilds
-                        | Action::FocusNext
-                        | Action::FocusPrevious
-                        | Action::NextTab
-                        | Action::ToggleGuildsTree
-                        | Action::ToggleQuickSwitcher
-                )
-            )
+    pub fn add_attachment(&mut self, path: std::path::PathBuf) {
+        self.message_input_state
+            .add_attachment(OutgoingAttachment::new(path));
+        self.focus_message_input();
+    }
+
+    pub fn insert_text(&mut self, text: &str) {
+        self.message_input_state.insert_text_at_cursor(text);
+        self.focus_message_input();
+    }
+
+    /// Inserts pasted text, first asking whether to send it as a file
+    /// instead when it is longer than the large paste threshold.
+    pub fn paste_text(&mut self, text: &str) {
+        if self.large_paste_threshold > 0 && text.chars().count() > self.large_paste_threshold {
+            self.pending_confirmation = Some(PendingConfirmation::LargePaste(text.to_string()));
+            self.set_focus(ChatFocus::ConfirmationModal);
+            return;
+        }
+        self.insert_text(text);
+    }
+
+    /// Selects and scrolls to a message if it is loaded.
+    ///
+    /// Returns a history request when the message is older than anything
+    /// loaded in the open channel, so the caller can fetch and try again.
+    pub fn scroll_to_message_id(&mut self, message_id: MessageId) -> Option<ChatKeyResult> {
+        self.pending_jump = None;
+        let messages = self.message_pane_data.messages();
+        if let Some(index) = messages.iter().position(|m| m.message.id() == message_id) {
+            self.message_pane_state.jump_to_index(index);
+            return None;
+        }
+
+        let channel_id = self.message_pane_data.channel_id()?;
+        let oldest = messages.front()?.message.id();
+        if message_id.as_u64() >= oldest.as_u64() {
+            return None;
+        }
+        self.pending_jump = Some((channel_id, message_id));
+        Some(ChatKeyResult::LoadHistory {
+            channel_id,
+            before_message_id: oldest,
+        })
+    }
+
+    /// Bumps the channel's mention badge. `everyone_only` marks a mention
+    /// that came solely from `@everyone`/`@here`, which may be suppressed.
+    pub fn increment_mention_count(&mut self, channel_id: ChannelId, everyone_only: bool) {
+        if everyone_only && self.suppress_everyone_highlight {
+            return;
+        }
+        if let Some(active_channel) = &self.selected_channel
+            && active_channel.id() == channel_id
         {
-            return ChatKeyResult::Ignored;
+            return;
         }
 
-        if self.registry.find_action(key) == Some(Action::ToggleFileExplorer) {
-            self.toggle_file_explorer();
-            return ChatKeyResult::Consumed;
+        if let Some(read_state) = self.read_states.get_mut(&channel_id) {
+            read_state.mention_count += 1;
+        } else {
+            let mut rs = crate::domain::entities::ReadState::new(channel_id, None);
+            rs.mention_count = 1;
+            self.read_states.insert(channel_id, rs);
         }
+        self.recalculate_all_unread();
+    }
 
-        if self.handle_autocomplete_navigation(key) {
-            return ChatKeyResult::Consumed;
+    #[must_use]
+    pub fn get_channel(&self, channel_id: ChannelId) -> Option<&Channel> {
+        self.guilds_tree_data.get_channel(channel_id)
+    }
+
+    pub fn set_file_explorer_config(&mut self, config: FileExplorerConfig) {
+        self.file_explorer_config = config;
+    }
+
+    pub fn toggle_file_explorer(&mut self) {
+        if self.show_file_explorer {
+            self.close_file_explorer();
+            return;
         }
+        self.show_file_explorer = true;
+        // The configured directory may start with `~`; the explorer expands
+        // and checks it, falling back to home.
+        let start_dir = self
+            .file_explorer_last_dir
+            .as_ref()
+            .filter(|dir| dir.is_dir())
+            .or(self.file_explorer_config.start_dir.as_ref());
+        let remaining = OutgoingAttachment::MAX_PER_MESSAGE
+            .saturating_sub(self.message_input_state.attachments().len());
+        self.file_explorer = Some(
+            FileExplorerComponent::open(
+                start_dir.map(std::path::PathBuf::as_path),
+                &self.file_explorer_config.bookmarks,
+            )
+            .with_max_files(remaining),
+        );
+    }
 
-        let autocomplete_changed;
+    /// Hides the file explorer, remembering its directory for the next open.
+    fn close_file_explorer(&mut self) {
+        self.show_file_explorer = false;
+        if let Some(explorer) = self.file_explorer.take() {
+            self.file_explorer_last_dir = Some(explorer.current_dir().to_path_buf());
+        }
+    }
 
-        if let Some(action) = self.message_input_state.handle_key(key, &self.registry) {
-            let value = self.message_input_state.value();
-            let cursor_idx = self.message_input_state.get_cursor_index();
-            autocomplete_changed = self.autocomplete_service.process_input(&value, cursor_idx);
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
 
-            match action {
-                MessageInputAction::SendMessage {
-                    content,
-                    reply_to,
-                    attachments,
-                } => {
-                    self.message_pane_state.clear_selection();
-                    return ChatKeyResult::SendMessage {
-                        content,
-                        reply_to,
-                        attachments,
-                    };
-                }
-                MessageInputAction::EditMessage {
-                    message_id,
-                    content,
-                } => {
-                    return ChatKeyResult::SubmitEdit {
-                        message_id,
-                        content,
-                    };
-                }
-                MessageInputAction::ExitInput => {
-                    self.focus_messages_list();
-                }
-                MessageInputAction::OpenEditor => {
-                    let initial_content = self.message_input_state.value();
-                    let message_id = match self.message_input_state.mode() {
-                        MessageInputMode::Editing { message_id } => Some(*message_id),
-                        _ => None,
-                    };
-                    return ChatKeyResult::OpenEditor {
-                        initial_content,
-                        message_id,
-                    };
+    fn handle_file_explorer_key(&mut self, key: KeyEvent) -> ChatKeyResult {
+        if self.registry.find_action(key) == Some(Action::ToggleHiddenFiles)
+            && let Some(explorer) = &mut self.file_explorer
+        {
+            explorer.toggle_hidden();
+            return ChatKeyResult::Consumed;
+        }
+
+        if self.registry.find_action(key) == Some(Action::ToggleAttachmentSpoiler)
+            && let Some(explorer) = &mut self.file_explorer
+        {
+            explorer.toggle_spoiler();
+            return ChatKeyResult::Consumed;
+        }
+
+        if let Some(explorer) = &mut self.file_explorer {
+            let spoiler = explorer.is_spoiler();
+            match explorer.handle_key(key) {
+                FileExplorerAction::SelectFiles(paths) => {
+                    for path in paths {
+                        self.message_input_state
+                            .add_attachment(OutgoingAttachment::new(path).with_spoiler(spoiler));
+                    }
+                    self.close_file_explorer();
+                    ChatKeyResult::Consumed
                 }
-                MessageInputAction::Paste => {
-                    return ChatKeyResult::Paste;
+                FileExplorerAction::TooManyFiles(remaining) => {
+                    let max = OutgoingAttachment::MAX_PER_MESSAGE;
+                    ChatKeyResult::ShowNotification(if remaining == 0 {
+                        format!("Messages can have at most {max} attachments")
+                    } else {
+                        format!("Only {remaining} more files fit in this message (max {max})")
+                    })
                 }
-                MessageInputAction::StartTyping | MessageInputAction::CancelReply => {
-                    if autocomplete_changed {
-                        self.update_autocomplete_suggestions();
-                    }
-                    return ChatKeyResult::StartTyping;
+                FileExplorerAction::BookmarkMissing(path) => ChatKeyResult::ShowNotification(
+                    format!("Bookmarked folder not found: {}", path.display()),
+                ),
+                FileExplorerAction::Close => {
+                    self.close_file_explorer();
+                    ChatKeyResult::Consumed
                 }
+                FileExplorerAction::None => ChatKeyResult::Consumed,
             }
         } else {
-            let value = self.message_input_state.value();
-            let cursor_idx = self.message_input_state.get_cursor_index();
-            autocomplete_changed = self.autocomplete_service.process_input(&value, cursor_idx);
+            ChatKeyResult::Consumed
         }
+    }
 
-        if autocomplete_changed {
-            self.update_autocomplete_suggestions();
-            return ChatKeyResult::StartTyping;
+    pub fn toggle_quick_switcher(&mut self) {
+        self.show_quick_switcher = !self.show_quick_switcher;
+        if self.show_quick_switcher {
+            self.pre_overlay_focus = Some(self.focus);
+            self.quick_switcher.reset();
+            self.perform_search("");
+        } else if let Some(focus) = self.pre_overlay_focus.take()
+            && self.restore_focus_after_overlay
+        {
+            self.set_focus(focus);
         }
-
-        ChatKeyResult::Ignored
     }
 
-    fn handle_autocomplete_navigation(&mut self, key: KeyEvent) -> bool {
-        if !self.autocomplete_service.state().active {
-            return false;
+    fn close_quick_switcher(&mut self) {
+        self.show_quick_switcher = false;
+        self.quick_switcher.reset();
+        let previous = self.pre_overlay_focus.take();
+        match previous {
+            Some(focus) if self.restore_focus_after_overlay => self.set_focus(focus),
+            _ => self.focus = ChatFocus::GuildsTree,
         }
+    }
+
+    pub fn set_quick_switcher_results(
+        &mut self,
+        results: Vec<crate::domain::search::SearchResult>,
+    ) {
+        self.quick_switcher.set_results(results);
+    }
 
+    fn handle_quick_switcher_key(&mut self, key: KeyEvent) -> ChatKeyResult {
         match key.code {
-            KeyCode::Up => {
-                self.autocomplete_service.select_previous();
-                true
+            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.quick_switcher.select_previous();
+                return ChatKeyResult::Consumed;
             }
-            KeyCode::Down => {
-                self.autocomplete_service.select_next();
-                true
+            KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.quick_switcher.select_next();
+                return ChatKeyResult::Consumed;
             }
-            KeyCode::Enter | KeyCode::Tab => {
-                if let Some(user) = self.autocomplete_service.state().selected_user() {
-                    let trigger_idx = self.autocomplete_service.state().trigger_index;
-                    let name =
-                        IdentityResolver::with_preference(self.use_display_name).resolve(user);
-                    self.message_input_state
-                        .insert_mention(trigger_idx, &name, user.id());
-                }
-                self.autocomplete_service.reset();
-                true
+            _ => {}
+        }
+
+        match self.quick_switcher.handle_key(key) {
+            QuickSwitcherAction::Close => {
+                self.close_quick_switcher();
+                ChatKeyResult::Consumed
             }
-            KeyCode::Esc => {
-                self.autocomplete_service.reset();
-                true
+            QuickSwitcherAction::ToggleSortMode => {
+                self.quick_switcher.toggle_sort_mode();
+                self.perform_search(&self.quick_switcher.input.clone());
+                ChatKeyResult::Consumed
             }
-            _ => false,
+            QuickSwitcherAction::Select(result) => {
+                self.close_quick_switcher();
+                self.jump_to_result(&result)
+            }
+            QuickSwitcherAction::UpdateSearch(query) => {
+                self.perform_search(&query);
+                ChatKeyResult::Consumed
+            }
+            QuickSwitcherAction::TogglePin(result) => {
+                self.toggle_pin(&result);
+                ChatKeyResult::PinsChanged
+            }
+            QuickSwitcherAction::None => ChatKeyResult::Consumed,
         }
     }
 
-    fn update_autocomplete_suggestions(&mut self) {
-        if !self.autocomplete_service.state().active {
+    /// Search result for a remembered channel, with its server's name.
+    fn recent_result(&self, item: &crate::domain::search::RecentItem, score: i64) -> SearchResult {
+        let mut res = SearchResult::new(item.id.clone(), item.name.clone(), item.kind.clone())
+            .with_score(score);
+        if let Some(gid) = &item.guild_id
+            && let Ok(id) = gid.parse::<u64>()
+            && let Some(guild) = self
+                .guilds_tree_data
+                .guilds()
+                .iter()
+                .find(|g| g.id() == GuildId(id))
+        {
+            res = res.with_guild(gid, guild.name());
+        }
+        res
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn perform_search(&mut self, query: &str) {
+        let (prefix, query_text) = parse_search_query(query);
+        let query_text = query_text.to_string();
+
+        if query_text.is_empty() {
+            let mut results = Vec::new();
+
+            if self.quick_switcher.sort_mode == QuickSwitcherSortMode::Recents {
+                results = self
+                    .recents
+                    .iter()
+                    .map(|r| self.recent_result(r, 100))
+                    .collect();
+            } else {
+                for section in &self.quick_switcher_sections {
+                    match section {
+                        QuickSwitcherSection::Recents => {
+                            results.extend(self.recents.iter().map(|r| self.recent_result(r, 0)));
+                        }
+                        QuickSwitcherSection::Dms => {
+                            if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
+                                let dms = self.guilds_tree_data.dm_users();
+                                tracing::debug!("Mixed Mode: Adding {} DMs", dms.len());
+                                for dm in dms {
+                                    let name = &dm.name(self.use_display_name);
+                                    results.push(
+                                        SearchResult::new(
+                                            dm.channel_id.clone(),
+                                            name,
+                                            SearchKind::DM,
+                                        )
+                                        .with_subtitle(
+                                            self.dm_snippets.get(&dm.channel_id).cloned(),
+                                        )
+                                        .with_score(0),
+                                    );
+                                }
+                            }
+                        }
+                        QuickSwitcherSection::Guilds => {
+                            if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
+                                let guilds = self.guilds_tree_data.guilds();
+                                tracing::debug!("Mixed Mode: Adding {} Guilds", guilds.len());
+                                for guild in guilds {
+                                    results.push(
+                                        SearchResult::new(
+                                            guild.id().to_string(),
+                                            guild.name(),
+                                            SearchKind::Guild,
+                                        )
+                                        .with_score(0),
+                                    );
+                                }
+                            }
+                        }
+                        QuickSwitcherSection::Channels => {
+                            if matches!(
+                                prefix,
+                                SearchPrefix::None
+                                    | SearchPrefix::Text
+                                    | SearchPrefix::Voice
+                                    | SearchPrefix::Thread
+                            ) {
+                                let channels = self.collect_searchable_channels(prefix);
+                                tracing::debug!("Mixed Mode: Adding {} Channels", channels.len());
+                                for (guild_name, channel, parent_name) in channels {
+                                    let mut kind = SearchKind::Channel;
+                                    if channel.kind().is_voice() {
+                                        kind = SearchKind::Voice;
+                                    } else if channel.kind().is_thread() {
+                                        kind = SearchKind::Thread;
+                                    } else if channel.kind()
+                                        == crate::domain::entities::ChannelKind::Forum
+                                    {
+                                        kind = SearchKind::Forum;
+                                    }
+
+                                    let mut res = SearchResult::new(
+                                        channel.id().to_string(),
+                                        channel.name(),
+                                        kind,
+                                    )
+                                    .with_subtitle(channel_subtitle(
+                                        &guild_name,
+                                        parent_name.as_deref(),
+                                    ))
+                                    .with_score(0);
+
+                                    if let Some(gid) = channel.guild_id() {
+                                        res = res.with_guild(gid.to_string(), guild_name);
+                                    }
+                                    if let Some(pname) = parent_name {
+                                        res = res.with_parent_name(pname);
+                                    }
+                                    results.push(res);
+                                }
+                            }
+                        }
+                    }
+                }
+                // Whichever section lists a channel or guild first keeps it.
+                let mut seen = std::collections::HashSet::new();
+                results.retain(|r| seen.insert(r.id.clone()));
+            }
+
+            if prefix == SearchPrefix::None && !self.pinned.is_empty() {
+                let pinned: Vec<SearchResult> = self
+                    .pinned
+                    .iter()
+                    .map(|p| self.recent_result(p, 100))
+                    .collect();
+                results.retain(|r| !pinned.iter().any(|p| p.id == r.id && p.kind == r.kind));
+                results.splice(0..0, pinned);
+            }
+
+            tracing::debug!(
+                "Empty query search results: {} (Mode: {})",
+                results.len(),
+                self.quick_switcher.sort_mode
+            );
+            self.quick_switcher.set_results(results);
             return;
         }
 
-        let mut candidates = Vec::new();
-        candidates.push(CachedUser::from_user(&self.user));
+        let channels = self.collect_searchable_channels(prefix);
 
-        let mut seen_ids = std::collections::HashSet::new();
-        seen_ids.insert(self.user.id().to_string());
+        let dms = if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
+            self.guilds_tree_data.dm_users().to_vec()
+        } else {
+            Vec::new()
+        };
 
-        for msg in self.message_pane_data.messages() {
-            let author = msg.message.author();
-            if seen_ids.insert(author.id().to_string()) {
-                if let Some(cached) = self.user_cache.get(author.id()) {
-                    candidates.push(cached);
-                } else {
-                    let cached = CachedUser::new(
-                        author.id(),
-                        author.username(),
-                        author.discriminator(),
-                        author.avatar().map(String::from),
-                        author.global_name.clone(),
-                        author.is_bot(),
-                    );
-                    candidates.push(cached);
+        let guilds = if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
+            self.guilds_tree_data.guilds().to_vec()
+        } else {
+            Vec::new()
+        };
+
+        let channel_provider = ChannelSearchProvider::new(channels);
+        let dm_provider = DmSearchProvider::new(dms, self.use_display_name)
+            .with_snippets(self.dm_snippets.clone());
+        let guild_provider = GuildSearchProvider::new(guilds);
+
+        let mut results = Vec::new();
+        if matches!(
+            prefix,
+            SearchPrefix::None | SearchPrefix::Text | SearchPrefix::Voice | SearchPrefix::Thread
+        ) {
+            results.extend(channel_provider.search_sync(&query_text));
+        }
+        if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
+            results.extend(dm_provider.search_sync(&query_text));
+        }
+        if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
+            results.extend(guild_provider.search_sync(&query_text));
+        }
+        results.sort_by(|a, b| b.score.cmp(&a.score));
+
+        self.quick_switcher.set_results(results);
+    }
+
+    fn collect_searchable_channels(
+        &self,
+        prefix: SearchPrefix,
+    ) -> Vec<(String, Channel, Option<String>)> {
+        let mut channels = Vec::new();
+        let mut added_ids = std::collections::HashSet::new();
+
+        if matches!(
+            prefix,
+            SearchPrefix::None | SearchPrefix::Text | SearchPrefix::Voice | SearchPrefix::Thread
+        ) {
+            for guild in self.guilds_tree_data.guilds() {
+                let deferred;
+                let guild_channels =
+                    if let Some(channels) = self.guilds_tree_data.channels(guild.id()) {
+                        Some(channels)
+                    } else {
+                        deferred = self.deferred_channels(guild.id());
+                        deferred.as_ref()
+                    };
+                if let Some(guild_channels) = guild_channels {
+                    for channel in guild_channels.iter() {
+                        if channel.kind() == crate::domain::entities::ChannelKind::Category {
+                            continue;
+                        }
+
+                        let include = match prefix {
+                            SearchPrefix::Text => {
+                                !channel.kind().is_voice() && !channel.kind().is_thread()
+                            }
+                            SearchPrefix::Voice => channel.kind().is_voice(),
+                            SearchPrefix::Thread => channel.kind().is_thread(),
+                            _ => true,
+                        };
+
+                        if include {
+                            let mut parent_name = None;
+                            if let Some(pid) = channel.parent_id()
+                                && let Some(parent) = guild_channels.iter().find(|c| c.id() == pid)
+                            {
+                                parent_name = Some(parent.name().to_string());
+                            }
+                            channels.push((guild.name().to_string(), channel.clone(), parent_name));
+                            added_ids.insert(channel.id());
+                        }
+                    }
+                }
+            }
+
+            if matches!(prefix, SearchPrefix::None | SearchPrefix::Thread) {
+                for (channel_id, state) in &self.forum_states {
+                    if let Some(parent_channel) = self.guilds_tree_data.get_channel(*channel_id) {
+                        let guild_name = parent_channel
+                            .guild_id()
+                            .and_then(|gid| {
+                                self.guilds_tree_data
+                                    .guilds()
+                                    .iter()
+                                    .find(|g| g.id() == gid)
+                            })
+                            .map_or(String::new(), |g| g.name().to_string());
+
+                        let parent_name = parent_channel.name().to_string();
+
+                        for thread in &state.threads {
+                            if !added_ids.contains(&thread.id) {
+                                let channel = Channel::new(
+                                    thread.id,
+                                    &thread.name,
+                                    ChannelKind::PublicThread,
+                                )
+                                .with_guild(parent_channel.guild_id().unwrap_or(GuildId(0)))
+                                .with_parent(parent_channel.id());
+
+                                channels.push((
+                                    guild_name.clone(),
+                                    channel,
+                                    Some(parent_name.clone()),
+                                ));
+                                added_ids.insert(thread.id);
+                            }
+                        }
+                    }
                 }
             }
+        }
+        channels
+    }
 
-            for mention in msg.message.mentions() {
-                if seen_ids.insert(mention.id().to_string()) {
-                    if let Some(cached) = self.user_cache.get(&mention.id_str()) {
-                        candidates.push(cached);
-                    } else {
-                        candidates.push(CachedUser::from_user(mention));
-                    }
+    fn jump_to_result(&mut self, result: &crate::domain::search::SearchResult) -> ChatKeyResult {
+        match result.kind {
+            SearchKind::DM => {
+                if let Some(result) = self.on_dm_selected(&result.id) {
+                    return result;
                 }
             }
-        }
+            SearchKind::Thread => {
+                let thread_id_val = result.id.parse::<u64>().unwrap_or(0);
+                let thread_channel_id = ChannelId(thread_id_val);
 
-        self.autocomplete_service.update_results(candidates);
-    }
+                if let Some(result) = self.on_channel_selected(thread_channel_id) {
+                    return result;
+                }
 
-    fn register_channel_mentions(&mut self, messages: &[Message]) -> Vec<ChannelId> {
-        static MENTION_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<#(\d+)>").unwrap());
-        static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
-            Regex::new(r"https?://(?:ptb\.|canary\.)?discord(?:app)?\.com/channels/\d+/(\d+)")
-                .unwrap()
-        });
+                let found_thread = self.forum_states.iter().find_map(|(parent_id, state)| {
+                    state
+                        .threads
+                        .iter()
+                        .find(|t| t.id == thread_channel_id)
+                        .map(|t| (*parent_id, t.clone()))
+                });
 
-        let mut unknown_ids = Vec::new();
+                if let Some((parent_id, thread)) = found_thread {
+                    let _ = self.on_channel_selected(parent_id);
 
-        for msg in messages {
-            let content = msg.content();
-            let channel_ids = MENTION_RE
-                .captures_iter(content)
-                .filter_map(|c| c.get(1))
-                .chain(URL_RE.captures_iter(content).filter_map(|c| c.get(1)));
+                    let channel =
+                        Channel::new(thread.id, thread.name.clone(), ChannelKind::PublicThread)
+                            .with_guild(thread.guild_id.unwrap_or(GuildId(0)).as_u64())
+                            .with_parent(parent_id);
 
-            for id_match in channel_ids {
-                if let Ok(id) = id_match.as_str().parse::<u64>() {
+                    self.selected_channel = Some(channel.clone());
+                    self.apply_accent_override();
+                    self.sync_pane_guild();
+                    self.sync_send_permission();
+                    self.message_pane_data
+                        .set_channel(thread_channel_id, channel.display_name());
+                    self.message_pane_state.on_channel_change();
+                    self.message_input_state.set_has_channel(true);
+                    self.message_input_state.clear();
+                    self.focus_messages_list();
+
+                    return ChatKeyResult::LoadChannelMessages {
+                        channel_id: thread_channel_id,
+                        guild_id: thread.guild_id,
+                    };
+                }
+            }
+            SearchKind::Channel | SearchKind::Forum | SearchKind::Voice => {
+                if let Ok(id) = result.id.parse::<u64>() {
                     let channel_id = ChannelId(id);
-                    if let Some(channel) = self.guilds_tree_data.get_channel(channel_id) {
-                        let icon = if channel.kind().is_thread() {
-                            "󰙯 "
-                        } else if channel.kind() == ChannelKind::Forum {
-                            "󰠄 "
-                        } else {
-                            "#"
-                        };
-                        let name = format!("{}{}", icon, channel.name());
-                        self.message_pane_data
-                            .register_channel(id.to_string(), name);
-                    } else if !self.message_pane_data.is_channel_known(&id.to_string()) {
-                        unknown_ids.push(channel_id);
+                    if let Some(result) = self.on_channel_selected(channel_id) {
+                        return result;
+                    }
+                }
+            }
+            SearchKind::Guild => {
+                if let Ok(id) = result.id.parse::<u64>() {
+                    let guild_id = GuildId(id);
+                    if let Some(result) = self.on_guild_selected(guild_id) {
+                        return result;
                     }
                 }
             }
         }
-        unknown_ids
+        ChatKeyResult::Consumed
     }
+}
 
-    fn on_channel_selected(&mut self, channel_id: ChannelId) -> Option<ChatKeyResult> {
-        let mut guild_id = self.selected_guild;
+impl HasCommands for ChatScreenState {
+    fn get_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
+        let mut commands = Vec::new();
 
-        if let Some(id) = guild_id {
-            let channel_in_guild = self
-                .guilds_tree_data
-                .channels(id)
-                .is_some_and(|channels| channels.iter().any(|c| c.id() == channel_id));
-            if !channel_in_guild {
-                guild_id = None;
-            }
+        if self.show_help {
+            commands.push(Keybind::new(
+                KeyEvent::from(KeyCode::Esc),
+                Action::ToggleHelp,
+                "Close",
+            ));
+            return commands;
         }
 
-        if guild_id.is_none() {
-            guild_id = self.guilds_tree_data.find_guild_for_channel(channel_id);
+        if self.show_quick_switcher {
+            commands.push(
+                Keybind::new(KeyEvent::from(KeyCode::Up), Action::NavigateUp, "Nav")
+                    .with_display("Ctrl+k/j"),
+            );
+            commands.push(Keybind::new(
+                KeyEvent::from(KeyCode::Enter),
+                Action::Select,
+                "Select",
+            ));
+            commands.push(Keybind::new(
+                KeyEvent::from(KeyCode::Tab),
+                Action::None,
+                format!("Sort ({})", self.quick_switcher.sort_mode),
+            ));
+            commands.push(Keybind::new(
+                KeyEvent::from(KeyCode::Esc),
+                Action::Cancel,
+                "Close",
+            ));
+            return commands;
         }
 
-        let channel_info = if let Some(guild_id) = guild_id
-            && let Some(channels) = self.guilds_tree_data.channels(guild_id)
-            && let Some(channel) = channels.iter().find(|c| c.id() == channel_id)
-        {
-            Some((guild_id, channel.clone(), channel.topic().map(String::from)))
+        if self.show_file_explorer {
+            if let Some(key) = registry.get_first(Action::Select) {
+                commands.push(Keybind::new(key, Action::Select, "Select"));
+            }
+            if let Some(key) = registry.get_first(Action::ToggleFileExplorer) {
+                commands.push(Keybind::new(key, Action::ToggleFileExplorer, "Close"));
+            }
+            commands.push(Keybind::new(
+                KeyEvent::from(KeyCode::Char('.')),
+                Action::ToggleHiddenFiles,
+                "Hidden",
+            ));
+            if let Some(key) = registry.get_first(Action::ToggleAttachmentSpoiler) {
+                commands.push(Keybind::new(
+                    key,
+                    Action::ToggleAttachmentSpoiler,
+                    "Spoiler",
+                ));
+            }
         } else {
-            None
-        };
-
-        if let Some((guild_id, mut channel, topic)) = channel_info {
-            if let Some(rs) = self.read_states.get_mut(&channel.id()) {
-                rs.mention_count = 0;
+            match self.focus {
+                ChatFocus::GuildsTree => {
+                    commands.extend(self.get_guilds_tree_commands(registry));
+                }
+                ChatFocus::MessagesList => {
+                    commands.extend(self.get_messages_list_commands(registry));
+                }
+                ChatFocus::MessageInput => {
+                    commands.extend(self.get_message_input_commands(registry));
+                }
+                ChatFocus::ConfirmationModal => {
+                    commands.push(Keybind::new(
+                        KeyEvent::from(KeyCode::Enter),
+                        Action::Select,
+                        "Confirm",
+                    ));
+                    commands.push(Keybind::new(
+                        KeyEvent::from(KeyCode::Esc),
+                        Action::Cancel,
+                        "Cancel",
+                    ));
+                    if let Some(key) = registry.get_first(Action::ToggleHelp) {
+                        commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
+                    }
+                }
             }
-            self.recalculate_all_unread();
+        }
 
-            if let Some(ch) = self.guilds_tree_data.get_channel(channel.id()) {
-                channel = ch.clone();
+        commands
+    }
+}
+
+impl ChatScreenState {
+    #[allow(clippy::unused_self)]
+    fn get_guilds_tree_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
+        let mut commands = Vec::new();
+        if let Some(key) = registry.get_first(Action::NavigateDown) {
+            let mut bind = Keybind::new(key, Action::NavigateDown, "Nav");
+            if let KeyCode::Char('j') = key.code {
+                bind = bind.with_display("j/k");
             }
+            commands.push(bind);
+        }
 
-            self.selected_guild = Some(guild_id);
-            self.selected_channel = Some(channel.clone());
+        if let Some(selected_id) = self.guilds_tree_state.selected() {
+            let is_expanded = self.guilds_tree_state.is_expanded(selected_id);
+            let can_expand = matches!(
+                selected_id,
+                TreeNodeId::Guild(_)
+                    | TreeNodeId::Category(_)
+                    | TreeNodeId::DirectMessages
+                    | TreeNodeId::Folder(_)
+            );
 
-            let search_kind = match channel.kind() {
-                ChannelKind::Voice | ChannelKind::StageVoice => SearchKind::Voice,
-                ChannelKind::Forum => SearchKind::Forum,
-                ChannelKind::PublicThread
-                | ChannelKind::PrivateThread
-                | ChannelKind::AnnouncementThread => SearchKind::Thread,
-                ChannelKind::Category => {
-                    return None;
+            if can_expand {
+                if is_expanded {
+                    if let Some(key) = registry.get_first(Action::NavigateLeft) {
+                        commands.push(Keybind::new(key, Action::NavigateLeft, "Collapse"));
+                    }
+                } else if let Some(key) = registry.get_first(Action::NavigateRight) {
+                    commands.push(Keybind::new(key, Action::NavigateRight, "Expand"));
                 }
-                _ => SearchKind::Channel,
-            };
+            } else if let Some(key) = registry.get_first(Action::NavigateLeft) {
+                commands.push(Keybind::new(key, Action::NavigateLeft, "Back"));
+            }
+        } else if let Some(key) = registry.get_first(Action::NavigateRight) {
+            commands.push(Keybind::new(key, Action::NavigateRight, "Expand"));
+        }
 
-            self.add_recent_item(crate::domain::search::RecentItem {
-                id: channel_id.to_string(),
-                name: channel.name().to_string(),
-                kind: search_kind,
-                guild_id: Some(guild_id.to_string()),
-                timestamp: chrono::Utc::now().timestamp(),
-            });
+        if let Some(key) = registry.get_first(Action::Select) {
+            commands.push(Keybind::new(key, Action::Select, "Select"));
+        }
+        if let Some(key) = registry.get_first(Action::ToggleGuildsTree) {
+            commands.push(Keybind::new(key, Action::ToggleGuildsTree, "Toggle Tree"));
+        }
+        if let Some(key) = registry.get_first(Action::FocusMessages) {
+            commands.push(Keybind::new(key, Action::FocusMessages, "Msgs"));
+        }
+        if let Some(key) = registry.get_first(Action::NextTab) {
+            commands.push(Keybind::new(key, Action::NextTab, "Next"));
+        }
+        if let Some(key) = registry.get_first(Action::ToggleHelp) {
+            commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
+        }
+        commands
+    }
 
-            self.guilds_tree_data.set_active_guild(Some(guild_id));
-            self.guilds_tree_data.set_active_channel(Some(channel_id));
-            self.guilds_tree_data.set_active_dm_user(None);
+    fn get_messages_list_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
+        let mut commands = Vec::new();
+        if let Some(key) = registry.get_first(Action::NavigateDown) {
+            let mut bind = Keybind::new(key, Action::NavigateDown, "Nav");
+            if let KeyCode::Char('j') = key.code {
+                bind = bind.with_display("j/k");
+            }
+            commands.push(bind);
+        }
+        if self.can_send_messages()
+            && let Some(key) = registry.get_first(Action::Reply)
+        {
+            commands.push(Keybind::new(key, Action::Reply, "Reply"));
+        }
+        if let Some(key) = registry.get_first(Action::EditMessage) {
+            let can_edit = self
+                .message_pane_state
+                .selected_index()
+                .and_then(|idx| self.message_pane_data.get_message(idx))
+                .is_some_and(|m| m.can_be_edited_by(&self.user));
 
-            let channel_name = channel.display_name();
+            if can_edit && !self.message_input_state.is_read_only() {
+                commands.push(Keybind::new(key, Action::EditMessage, "Edit"));
+            }
+        }
+        if let Some(key) = registry.get_first(Action::DeleteMessage) {
+            let can_delete = self
+                .message_pane_state
+                .selected_index()
+                .and_then(|idx| self.message_pane_data.get_message(idx))
+                .is_some_and(|m| m.can_be_edited_by(&self.user));
 
-            if let Some(current_channel_id) = self.message_pane_data.channel_id()
-                && let ViewMode::Forum(state) = &self.message_pane_state.view_mode
-            {
-                self.forum_states.insert(current_channel_id, state.clone());
+            if can_delete {
+                commands.push(Keybind::new(key, Action::DeleteMessage, "Delete"));
             }
+        }
+        if let Some(key) = registry.get_first(Action::OpenAttachments) {
+            let label = self
+                .message_pane_state
+                .selected_index()
+                .and_then(|idx| self.message_pane_data.get_message(idx))
+                .and_then(|m| MessageContentService::resolve(m).label());
 
-            self.message_pane_data.set_channel(channel_id, channel_name);
-            self.message_pane_state.on_channel_change();
+            if let Some(label) = label {
+                commands.push(Keybind::new(key, Action::OpenAttachments, label));
+            } else {
+                commands.push(Keybind::new(key, Action::OpenAttachments, "Open"));
+            }
+        }
+        if let Some(key) = registry.get_first(Action::CopyContent) {
+            commands.push(Keybind::new(key, Action::CopyContent, "Copy"));
+        }
+        if let Some(key) = registry.get_first(Action::JumpToReply) {
+            commands.push(Keybind::new(key, Action::JumpToReply, "Jump"));
+        }
+        if let Some(key) = registry.get_first(Action::RetrySend) {
+            let failed = self
+                .message_pane_state
+                .get_selected_message_id(&self.message_pane_data)
+                .and_then(|id| self.message_pane_data.send_state(id))
+                == Some(SendState::Failed);
 
-            if channel.kind() == ChannelKind::Forum {
-                if let Some(saved_state) = self.forum_states.get(&channel_id) {
-                    self.message_pane_state.view_mode = ViewMode::Forum(saved_state.clone());
-                } else {
-                    self.message_pane_state.view_mode = ViewMode::Forum(ForumState::default());
-                }
+            if failed {
+                commands.push(Keybind::new(key, Action::RetrySend, "Retry"));
             }
+        }
+        if let Some(key) = registry.get_first(Action::PublishMessage) {
+            let publishable = self
+                .message_pane_state
+                .selected_index()
+                .and_then(|idx| self.message_pane_data.get_message(idx))
+                .is_some_and(|m| self.can_publish(m));
 
-            self.message_pane_data.set_channel_topic(topic);
-            self.message_input_state.set_has_channel(true);
-            self.message_input_state.clear();
-            self.focus_messages_list();
+            if publishable {
+                commands.push(Keybind::new(key, Action::PublishMessage, "Publish"));
+            }
+        }
+        if let Some(key) = registry.get_first(Action::ToggleMessageExpand) {
+            let collapsed = self
+                .message_pane_state
+                .selected_index()
+                .and_then(|idx| self.message_pane_data.ui_messages().get(idx))
+                .filter(|m| m.collapsible)
+                .map(|m| m.collapsed);
 
-            if channel.kind() == ChannelKind::Forum {
-                if let ViewMode::Forum(ref state) = self.message_pane_state.view_mode
-                    && !state.threads.is_empty()
-                {
-                    return None;
+            match collapsed {
+                Some(true) => {
+                    commands.push(Keybind::new(key, Action::ToggleMessageExpand, "Expand"));
                 }
-
-                return Some(ChatKeyResult::LoadForumThreads {
-                    channel_id,
-                    guild_id: Some(guild_id),
-                    offset: 0,
-                });
+                Some(false) => {
+                    commands.push(Keybind::new(key, Action::ToggleMessageExpand, "Collapse"));
+                }
+                None => {}
             }
-            return Some(ChatKeyResult::LoadChannelMessages {
-                channel_id,
-                guild_id: Some(guild_id),
-            });
         }
-        None
+        if self.can_list_threads()
+            && let Some(key) = registry.get_first(Action::ToggleThreadList)
+        {
+            commands.push(Keybind::new(key, Action::ToggleThreadList, "Threads"));
+        }
+        if let Some(key) = registry.get_first(Action::FocusInput) {
+            commands.push(Keybind::new(key, Action::FocusInput, "Focus Input"));
+        }
+        if let Some(key) = registry.get_first(Action::ToggleHelp) {
+            commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
+        }
+        commands
     }
 
-    fn on_guild_selected(&mut self, guild_id: GuildId) -> Option<ChatKeyResult> {
-        if self.selected_guild == Some(guild_id) {
-            return None;
+    fn get_message_input_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
+        let mut commands = Vec::new();
+        let can_send = self.message_input_state.is_editing() || self.can_send_messages();
+        if can_send && let Some(key) = registry.get_first(Action::SendMessage) {
+            commands.push(Keybind::new(key, Action::SendMessage, "Send"));
         }
-
-        if let Some(guild) = self
-            .guilds_tree_data
-            .guilds()
-            .iter()
-            .find(|g| g.id() == guild_id)
+        if let Some(key) = registry.get_first(Action::FocusMessages) {
+            commands.push(Keybind::new(key, Action::FocusMessages, "Msgs"));
+        }
+        if can_send && let Some(key) = registry.get_first(Action::OpenEditor) {
+            commands.push(Keybind::new(key, Action::OpenEditor, "Editor"));
+        }
+        if self.can_attach_files()
+            && let Some(key) = registry.get_first(Action::ToggleFileExplorer)
         {
-            self.add_recent_item(crate::domain::search::RecentItem {
-                id: guild_id.to_string(),
-                name: guild.name().to_string(),
-                kind: SearchKind::Guild,
-                guild_id: None,
-                timestamp: chrono::Utc::now().timestamp(),
-            });
+            commands.push(Keybind::new(key, Action::ToggleFileExplorer, "Attach"));
+        }
+        if let Some(key) = registry.get_first(Action::Paste) {
+            commands.push(Keybind::new(key, Action::Paste, "Paste"));
+        }
+        if self.message_input_state.is_replying()
+            && let Some(key) = registry.get_first(Action::ToggleReplyMention)
+        {
+            commands.push(Keybind::new(key, Action::ToggleReplyMention, "Ping"));
+        }
+        if let Some(key) = registry.get_first(Action::Cancel) {
+            commands.push(Keybind::new(key, Action::Cancel, "Cancel"));
         }
 
-        self.selected_guild = Some(guild_id);
-        self.selected_channel = None;
-        self.guilds_tree_data.set_active_channel(None);
-        self.guilds_tree_data.set_active_guild(Some(guild_id));
+        commands.push(Keybind::new(
+            KeyEvent::from(KeyCode::F(1)),
+            Action::ToggleHelp,
+            "Help",
+        ));
 
-        if self.guilds_tree_data.channels(guild_id).is_none() {
-            return Some(ChatKeyResult::LoadGuildChannels(guild_id));
-        }
+        commands
+    }
+}
 
-        None
+/// Parses a `/nick [name]` command; an empty name resets the nickname.
+fn parse_nick_command(content: &str) -> Option<Result<Option<String>, String>> {
+    let rest = content.trim().strip_prefix("/nick")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let nick = rest.trim();
+    if nick.is_empty() {
+        return Some(Ok(None));
+    }
+    if nick.chars().count() > MAX_NICKNAME_LENGTH {
+        return Some(Err(format!(
+            "Nickname must be at most {MAX_NICKNAME_LENGTH} characters"
+        )));
     }
+    Some(Ok(Some(nick.to_string())))
+}
 
-    fn on_dm_selected(&mut self, dm_channel_id: &str) -> Option<ChatKeyResult> {
-        let (channel_id, recipient_name) =
-            if let Some(dm_info) = self.dm_channels.get(dm_channel_id) {
-                (dm_info.channel_id(), dm_info.recipient_name().to_string())
-            } else {
-                return None;
-            };
+/// `/group` or `/add` followed by the users to include.
+#[derive(Debug, PartialEq, Eq)]
+enum GroupCommand {
+    Create(Result<Vec<String>, String>),
+    Add(Result<Vec<String>, String>),
+}
 
-        self.add_recent_item(crate::domain::search::RecentItem {
-            id: dm_channel_id.to_string(),
-            name: recipient_name.clone(),
-            kind: SearchKind::DM,
-            guild_id: None,
-            timestamp: chrono::Utc::now().timestamp(),
-        });
+/// Parses `/group @a @b` (create a group DM) or `/add @c` (add to the current
+/// one). Users are mentions picked from the `@` popup or raw ids.
+fn parse_group_command(content: &str) -> Option<GroupCommand> {
+    let content = content.trim();
+    if let Some(rest) = content.strip_prefix("/group") {
+        parse_user_ids(rest).map(GroupCommand::Create)
+    } else {
+        parse_user_ids(content.strip_prefix("/add")?).map(GroupCommand::Add)
+    }
+}
 
-        if let Some(rs) = self.read_states.get_mut(&channel_id) {
-            rs.mention_count = 0;
+/// Reads whitespace-separated `<@id>`, `<@!id>` or bare ids, dropping
+/// duplicates. `None` when `rest` does not start a new word.
+fn parse_user_ids(rest: &str) -> Option<Result<Vec<String>, String>> {
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut user_ids: Vec<String> = Vec::new();
+    for token in rest.split_whitespace() {
+        let id = token
+            .strip_prefix("<@")
+            .and_then(|id| id.strip_suffix('>'))
+            .map_or(token, |id| id.trim_start_matches('!'));
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return Some(Err(format!("Unknown user: {token}")));
         }
-        self.recalculate_all_unread();
+        if !user_ids.iter().any(|existing| existing == id) {
+            user_ids.push(id.to_string());
+        }
+    }
+    if user_ids.is_empty() {
+        return Some(Err("Mention at least one user".to_string()));
+    }
+    Some(Ok(user_ids))
+}
 
-        let dm_channel = Channel::new(channel_id, recipient_name.clone(), ChannelKind::Dm);
-        self.selected_channel = Some(dm_channel);
-        self.selected_guild = None;
-        self.guilds_tree_data.set_active_guild(None);
-        self.guilds_tree_data.set_active_channel(None);
-        self.guilds_tree_data
-            .set_active_dm_user(Some(dm_channel_id.to_string()));
+#[cfg(test)]
+#[cfg(not(windows))]
+mod tests {
+    use super::*;
+    use crate::domain::entities::RoleId;
+    use crate::domain::ports::DmRecipient;
+    use test_case::test_case;
 
-        let display_name = format!("@{recipient_name}");
-        self.message_pane_data.set_channel(channel_id, display_name);
-        self.message_pane_state.on_channel_change();
-        self.message_input_state.set_has_channel(true);
-        self.message_input_state.clear();
+    fn setup_permissive_guild_data(state: &mut ChatScreenState, guild_id: GuildId) {
+        let user = state.user().clone();
+        let role = Role {
+            id: RoleId(guild_id.as_u64()), // @everyone has same ID as guild
+            name: "@everyone".to_string(),
+            permissions: Permissions::all(), // Allow everything
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            managed: false,
+            mentionable: false,
+        };
+        let member = Member {
+            user: Some(user),
+            roles: vec![],
+            nick: None,
+            avatar: None,
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
+        };
+        state.set_guild_data(guild_id, vec![role], vec![member]);
+    }
+
+    fn create_test_user() -> User {
+        User::new("123", "testuser", "0", None, false, None)
+    }
+
+    /// A plain message from user `author_id` in `channel_id`.
+    fn create_test_message(
+        id: u64,
+        channel_id: ChannelId,
+        author_id: &str,
+        content: &str,
+    ) -> Message {
+        Message::new(
+            MessageId(id),
+            channel_id,
+            MessageAuthor {
+                id: author_id.to_string(),
+                username: "user".to_string(),
+                discriminator: "0".to_string(),
+                avatar: None,
+                bot: false,
+                global_name: None,
+            },
+            content.to_string(),
+            chrono::Local::now(),
+            MessageKind::Default,
+        )
+    }
+
+    /// The buffer's text, one string per row.
+    fn buffer_rows(buf: &Buffer) -> Vec<String> {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reselecting_same_guild_preserves_channel() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        self.focus_messages_list();
+        let guild_a = Guild::new(1_u64, "Guild A");
+        let channel_a1 = Channel::new(ChannelId(10), "Channel A1", ChannelKind::Text);
 
-        Some(ChatKeyResult::LoadDmMessages {
-            channel_id,
-            recipient_name,
-        })
-    }
+        state.set_guilds(vec![guild_a.clone()]);
+        setup_permissive_guild_data(&mut state, guild_a.id());
+        state.set_channels(guild_a.id(), vec![channel_a1.clone()]);
 
-    #[must_use]
-    pub const fn guilds_tree_data(&self) -> &GuildsTreeData {
-        &self.guilds_tree_data
-    }
+        state.on_guild_selected(guild_a.id());
+        state.on_channel_selected(channel_a1.id());
 
-    pub const fn guilds_tree_state_mut(&mut self) -> &mut GuildsTreeState {
-        &mut self.guilds_tree_state
-    }
+        state.on_guild_selected(guild_a.id());
 
-    pub const fn guilds_tree_parts_mut(&mut self) -> (&GuildsTreeData, &mut GuildsTreeState) {
-        (&self.guilds_tree_data, &mut self.guilds_tree_state)
+        assert_eq!(
+            state
+                .selected_channel()
+                .map(crate::domain::entities::Channel::id),
+            Some(channel_a1.id()),
+            "Channel selection should be preserved when reselecting same guild"
+        );
     }
 
-    pub fn set_messages(&mut self, messages: Vec<Message>) -> Option<ChatKeyResult> {
-        let unknown = self.register_channel_mentions(&messages);
-        self.message_pane_data.set_messages(messages);
-        if unknown.is_empty() {
-            None
-        } else {
-            Some(ChatKeyResult::RequestChannelFetch(unknown))
-        }
-    }
+    #[test]
+    fn test_external_edit_restriction() {
+        use crate::domain::entities::{ChannelId, Message, MessageAuthor, MessageId, MessageKind};
+        use chrono::Local;
 
-    pub fn set_forum_threads(
-        &mut self,
-        mut threads: Vec<crate::domain::entities::ForumThread>,
-        offset: u32,
-    ) {
-        for thread in &mut threads {
-            let read_state = self.read_states.get(&thread.id);
-            thread.new = match (read_state, thread.last_message_id) {
-                (Some(rs), Some(last_msg_id)) => rs.last_read_message_id != Some(last_msg_id),
-                (Some(_), None) => false,
-                (None, _) => true,
-            };
-        }
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        if let ViewMode::Forum(state) = &mut self.message_pane_state.view_mode {
-            if offset > 0 {
-                let old_selection_id = state.threads.get(state.selected_idx).map(|t| t.id);
-                let added_count = threads.len();
+        let channel_id = ChannelId(1);
+        let timestamp = Local::now();
 
-                let mut new_list = threads;
-                new_list.append(&mut state.threads);
-                state.threads = new_list;
+        let other_message = Message::new(
+            MessageId(2),
+            channel_id,
+            MessageAuthor {
+                id: "456".to_string(),
+                username: "other".to_string(),
+                discriminator: "0".to_string(),
+                avatar: None,
+                bot: false,
+                global_name: None,
+            },
+            "Other message".to_string(),
+            timestamp,
+            MessageKind::Default,
+        );
 
-                if let Some(id) = old_selection_id
-                    && let Some(new_idx) = state.threads.iter().position(|t| t.id == id)
-                {
-                    state.selected_idx = new_idx;
-                    state.scroll_offset = state
-                        .scroll_offset
-                        .saturating_add(u16::try_from(added_count).unwrap_or(0));
-                }
-                return;
-            }
+        state.message_pane_data.set_messages(vec![other_message]);
+        state.focus_messages_list();
+        state.message_pane_state.jump_to_index(0);
 
-            let was_empty = state.threads.is_empty();
-            let was_at_bottom = state.selected_idx + 1 >= state.threads.len();
-            let invalid_selection = state.selected_idx >= state.threads.len();
+        let edit_key = KeyEvent::new(KeyCode::Char('e'), crossterm::event::KeyModifiers::CONTROL);
 
-            state.threads = threads;
+        let result = state.handle_key(edit_key);
 
-            if (was_empty || was_at_bottom || invalid_selection) && !state.threads.is_empty() {
-                state.selected_idx = state.threads.len().saturating_sub(1);
-                state.needs_scroll_to_selection = true;
-            }
-        } else {
-            let mut state = crate::presentation::widgets::ForumState {
-                threads,
-                ..Default::default()
-            };
-            if !state.threads.is_empty() {
-                state.selected_idx = state.threads.len().saturating_sub(1);
-                state.needs_scroll_to_selection = true;
+        match result {
+            ChatKeyResult::ShowNotification(msg) => {
+                assert_eq!(msg, "You can only edit your own messages");
             }
-            self.message_pane_state.view_mode = ViewMode::Forum(state);
-        }
-    }
-
-    pub fn add_message(&mut self, message: Message) -> Option<ChatKeyResult> {
-        let unknown = self.register_channel_mentions(std::slice::from_ref(&message));
-        self.message_pane_data.add_message(message);
-        self.message_pane_state.on_new_message();
-        if unknown.is_empty() {
-            None
-        } else {
-            Some(ChatKeyResult::RequestChannelFetch(unknown))
+            _ => panic!("Expected ShowNotification, got {result:?}"),
         }
     }
 
-    pub fn prepend_messages(&mut self, new_messages: Vec<Message>) -> Option<ChatKeyResult> {
-        if new_messages.is_empty() {
-            return None;
-        }
-        let unknown = self.register_channel_mentions(&new_messages);
-
-        let width = self.message_pane_state.last_width();
-        let pane = MessagePane::new(&mut self.message_pane_data, &self.markdown_service)
-            .with_image_preview(self.image_preview)
-            .with_timestamp_format(&self.timestamp_format)
-            .with_current_user_id(self.user.id().to_string());
-        let added_height: u16 = new_messages
-            .iter()
-            .map(|m| {
-                pane.calculate_message_height(m, width, &self.markdown_service, self.theme.accent)
-            })
-            .sum();
+    #[test]
+    fn test_increment_mention_count_on_active_channel_should_not_increment() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        let added_count = self.message_pane_data.prepend_messages(new_messages);
+        let guild = crate::domain::entities::Guild::new(1_u64, "Guild A");
+        let channel = crate::domain::entities::Channel::new(
+            crate::domain::entities::ChannelId(10),
+            "Channel A",
+            crate::domain::entities::ChannelKind::Text,
+        );
 
-        if added_count > 0 {
-            self.message_pane_state
-                .adjust_for_prepend(added_count, added_height.into());
-        }
-        if unknown.is_empty() {
-            None
-        } else {
-            Some(ChatKeyResult::RequestChannelFetch(unknown))
-        }
-    }
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
 
-    pub fn update_message(&mut self, message: Message) -> Option<ChatKeyResult> {
-        let unknown = self.register_channel_mentions(std::slice::from_ref(&message));
-        self.message_pane_data.update_message(message);
-        if unknown.is_empty() {
-            None
-        } else {
-            Some(ChatKeyResult::RequestChannelFetch(unknown))
-        }
-    }
+        state.on_channel_selected(channel.id());
+        assert_eq!(
+            state
+                .selected_channel()
+                .map(crate::domain::entities::Channel::id),
+            Some(channel.id())
+        );
 
-    pub fn remove_message(&mut self, message_id: crate::domain::entities::MessageId) {
-        self.message_pane_data.remove_message(message_id);
-    }
+        state.increment_mention_count(channel.id(), false);
 
-    pub fn set_message_error(&mut self, error: String) {
-        self.message_pane_data.set_error(error);
+        let count = state
+            .read_states
+            .get(&channel.id())
+            .map_or(0, |rs| rs.mention_count);
+        assert_eq!(count, 0, "Mention count should be 0 for active channel");
     }
 
-    pub fn set_typing_indicator(&mut self, indicator: Option<String>) {
-        self.message_pane_data.set_typing_indicator(indicator);
-    }
+    #[test]
+    fn test_focus_cycling() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-    #[must_use]
-    pub const fn message_pane_data(&self) -> &MessagePaneData {
-        &self.message_pane_data
-    }
+        assert_eq!(state.focus(), ChatFocus::GuildsTree);
 
-    pub const fn message_pane_data_mut(&mut self) -> &mut MessagePaneData {
-        &mut self.message_pane_data
-    }
+        state.focus_next();
+        assert_eq!(state.focus(), ChatFocus::MessagesList);
 
-    /// Marks message data as dirty, forcing a re-render.
-    /// Called when blocked user state changes to update message visibility.
-    pub fn mark_messages_dirty(&mut self) {
-        self.message_pane_data.mark_dirty();
-    }
+        state.focus_next();
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
 
-    pub const fn message_pane_parts_mut(
-        &mut self,
-    ) -> (&mut MessagePaneData, &mut MessagePaneState) {
-        (&mut self.message_pane_data, &mut self.message_pane_state)
+        state.focus_next();
+        assert_eq!(state.focus(), ChatFocus::GuildsTree);
     }
 
-    pub fn start_reply(&mut self, message_id: MessageId, author_name: String, mention: bool) {
-        self.message_input_state
-            .start_reply(message_id, author_name, mention);
-        self.focus_message_input();
-    }
+    #[test]
+    fn test_focus_skip_when_guilds_hidden() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
+        state.toggle_guilds_tree();
+        state.set_focus(ChatFocus::MessagesList);
 
-    pub fn cancel_reply(&mut self) {
-        self.message_input_state.reset_mode();
-    }
+        state.focus_next();
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
 
-    pub fn clear_input(&mut self) {
-        self.message_input_state.clear();
+        state.focus_next();
+        assert_eq!(state.focus(), ChatFocus::MessagesList);
     }
 
-    pub fn get_reply_author(&self, message_id: MessageId) -> Option<String> {
-        self.message_pane_data
-            .messages()
-            .iter()
-            .find(|m| m.message.id() == message_id)
-            .map(|m| {
-                IdentityResolver::with_preference(self.use_display_name).resolve(m.message.author())
-            })
-    }
+    #[test]
+    #[cfg(not(windows))]
+    fn test_cross_guild_channel_selection() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-    pub fn message_input_parts_mut(&mut self) -> &mut MessageInputState<'static> {
-        &mut self.message_input_state
-    }
+        let guild_a = Guild::new(1_u64, "Guild A");
+        let guild_b = Guild::new(2_u64, "Guild B");
+        let channel_a = Channel::new(ChannelId(10), "Channel A1", ChannelKind::Text);
+        let channel_b = Channel::new(ChannelId(20), "Channel B1", ChannelKind::Text);
 
-    pub fn message_input_state(&self) -> &MessageInputState<'static> {
-        &self.message_input_state
-    }
+        state.set_guilds(vec![guild_a.clone(), guild_b.clone()]);
+        setup_permissive_guild_data(&mut state, guild_a.id());
+        setup_permissive_guild_data(&mut state, guild_b.id());
+        state.set_channels(guild_a.id(), vec![channel_a.clone()]);
+        state.set_channels(guild_b.id(), vec![channel_b.clone()]);
 
-    /// Get the current message input value.
-    pub fn message_input_value(&self) -> String {
-        self.message_input_state.value()
-    }
+        state.on_guild_selected(guild_a.id());
+        state.on_channel_selected(channel_a.id());
 
-    /// Get the current reply info (`message_id`, author) if in reply mode.
-    pub fn message_input_reply_info(&self) -> Option<(MessageId, String, bool)> {
-        match self.message_input_state.mode() {
-            MessageInputMode::Reply {
-                message_id,
-                author,
-                mention,
-            } => Some((*message_id, author.clone(), *mention)),
-            MessageInputMode::Normal | MessageInputMode::Editing { .. } => None,
-        }
-    }
+        assert_eq!(state.selected_guild(), Some(guild_a.id()));
+        assert_eq!(
+            state
+                .selected_channel()
+                .map(crate::domain::entities::Channel::id),
+            Some(channel_a.id())
+        );
 
-    /// Set the message input content.
-    pub fn set_message_input_content(&mut self, content: &str) {
-        self.message_input_state.set_content(content);
-    }
+        let result = state.on_channel_selected(channel_b.id());
 
-    /// Returns a reference to the image manager.
-    #[must_use]
-    pub const fn image_manager(&self) -> &ImageManager {
-        &self.image_manager
+        assert!(
+            result.is_some(),
+            "Should return a result when selecting Channel B1"
+        );
+        assert_eq!(
+            state.selected_guild(),
+            Some(guild_b.id()),
+            "Should switch to Guild B"
+        );
+        assert_eq!(
+            state
+                .selected_channel()
+                .map(crate::domain::entities::Channel::id),
+            Some(channel_b.id()),
+            "Should switch to Channel B1"
+        );
     }
 
-    /// Returns a mutable reference to the image manager.
-    pub fn image_manager_mut(&mut self) -> &mut ImageManager {
-        &mut self.image_manager
-    }
+    #[test]
+    fn test_permission_filtering_public_channel() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild_id = GuildId(1);
+        let channel = Channel::new(ChannelId(10), "public", ChannelKind::Text).with_guild(guild_id);
 
-    /// Collects all image attachments that need loading within the visible range.
-    /// Returns a list of (`ImageId`, URL) pairs.
-    #[cfg(feature = "image")]
-    #[must_use]
-    pub fn collect_needed_image_loads(&self) -> Vec<(crate::domain::entities::ImageId, String)> {
-        let mut needed = Vec::new();
+        // Setup @everyone role with VIEW_CHANNEL (default)
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            permissions: Permissions::VIEW_CHANNEL,
+            managed: false,
+            mentionable: false,
+        };
 
-        let visible_range = self.calculate_visible_range();
-        let buffer = super::super::widgets::LOAD_BUFFER;
-        let start = visible_range.0.saturating_sub(buffer);
-        let end = (visible_range.1 + buffer).min(self.message_pane_data.message_count());
+        let member = Member {
+            user: Some(user.clone()),
+            nick: None,
+            avatar: None,
+            roles: vec![],
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
+        };
 
-        for idx in start..end {
-            if let Some(ui_msg) = self.message_pane_data.messages().get(idx) {
-                for load in ui_msg.collect_image_loads() {
-                    needed.push(load);
-                }
-            }
-        }
+        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
+        state.set_channels(guild_id, vec![channel.clone()]);
 
-        needed
+        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
     }
 
-    /// Calculates the visible message range based on scroll position.
-    fn calculate_visible_range(&self) -> (usize, usize) {
-        let offset = self.message_pane_state.vertical_scroll;
-        let viewport_height = self.message_pane_state.viewport_height() as usize;
+    #[test]
+    fn test_permission_filtering_private_channel_denied() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild_id = GuildId(1);
+        let channel =
+            Channel::new(ChannelId(10), "private", ChannelKind::Text).with_guild(guild_id);
 
-        let mut y = 0;
-        let mut start_idx = 0;
-        let mut end_idx = 0;
-        let mut found_start = false;
+        // Setup @everyone role with VIEW_CHANNEL DENIED
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            permissions: Permissions::empty(), // No VIEW_CHANNEL
+            managed: false,
+            mentionable: false,
+        };
 
-        for (idx, msg) in self.message_pane_data.messages().iter().enumerate() {
-            let h = msg.estimated_height as usize;
+        let member = Member {
+            user: Some(user.clone()),
+            nick: None,
+            avatar: None,
+            roles: vec![],
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
+        };
+
+        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
+        state.set_channels(guild_id, vec![channel.clone()]);
+
+        assert!(state.guilds_tree_data.get_channel(channel.id()).is_none());
+    }
 
-            if !found_start && y + h > offset {
-                start_idx = idx;
-                found_start = true;
-            }
+    #[test]
+    fn test_permission_filtering_role_access() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild_id = GuildId(1);
+        let channel =
+            Channel::new(ChannelId(10), "restricted", ChannelKind::Text).with_guild(guild_id);
 
-            if y >= offset + viewport_height {
-                end_idx = idx;
-                break;
-            }
+        // @everyone denied
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            permissions: Permissions::empty(),
+            ..create_dummy_role(1)
+        };
 
-            y += h;
-            end_idx = idx + 1;
-        }
+        // Member role allowed
+        let member_role = Role {
+            id: RoleId(2),
+            name: "Member".to_string(),
+            permissions: Permissions::VIEW_CHANNEL,
+            ..create_dummy_role(2)
+        };
 
-        (start_idx, end_idx)
-    }
+        let member = Member {
+            user: Some(user.clone()),
+            roles: vec![RoleId(2)],
+            ..create_dummy_member()
+        };
 
-    /// Updates an image attachment when it finishes loading.
-    #[cfg(feature = "image")]
-    pub fn on_image_loaded(
-        &mut self,
-        id: &crate::domain::entities::ImageId,
-        image: &std::sync::Arc<image::DynamicImage>,
-    ) {
-        if !self.image_preview {
-            return;
-        }
+        state.set_guild_data(guild_id, vec![everyone_role, member_role], vec![member]);
+        state.set_channels(guild_id, vec![channel.clone()]);
 
-        for ui_msg in self.message_pane_data.ui_messages_mut() {
-            for attachment in &mut ui_msg.image_attachments {
-                if &attachment.id == id {
-                    attachment.set_loaded(image.clone());
-                }
-            }
-        }
-        self.message_pane_data.mark_dirty();
+        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
     }
 
-    /// Updates image protocols for visible messages.
-    /// Should be called before rendering.
-    /// Only processes images that need protocol updates.
-    #[cfg(feature = "image")]
-    pub fn update_visible_image_protocols(&mut self, terminal_width: u16) {
-        if self.message_pane_data.is_empty() || !self.image_preview {
-            return;
-        }
-
-        self.image_manager.set_width(terminal_width);
+    #[test]
+    fn test_permission_filtering_strict_category_pruning() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild_id = GuildId(1);
 
-        let current_scroll = self.message_pane_state.vertical_scroll;
-        let current_height = self.message_pane_state.viewport_height();
+        let category = Channel::new(ChannelId(100), "Hidden Category", ChannelKind::Category)
+            .with_guild(guild_id);
 
-        let scroll_changed = self.last_scroll_state != Some((current_scroll, current_height));
+        let child = Channel::new(ChannelId(101), "Visible Child", ChannelKind::Text)
+            .with_guild(guild_id)
+            .with_parent(ChannelId(100));
 
-        let (visible_start, visible_end) = self.calculate_visible_range();
-        let buffer = super::super::widgets::LOAD_BUFFER;
+        // @everyone denied VIEW_CHANNEL base
+        let everyone_role = Role {
+            id: RoleId(1),
+            permissions: Permissions::empty(),
+            ..create_dummy_role(1)
+        };
 
-        let protocol_start = visible_start.saturating_sub(buffer);
-        let protocol_end = visible_end + buffer;
-        let picker = self.image_manager.picker();
-        let mut dirty = false;
+        // Child has overwrite allowing VIEW_CHANNEL for @everyone
+        // But Category does NOT.
+        let child =
+            child.with_permission_overwrites(vec![crate::domain::entities::PermissionOverwrite {
+                id: "1".to_string(), // @everyone
+                overwrite_type: crate::domain::entities::OverwriteType::Role,
+                allow: Permissions::VIEW_CHANNEL.bits().to_string(),
+                deny: "0".to_string(),
+            }]);
 
-        if scroll_changed {
-            let memory_buffer = buffer * 3;
-            let keep_start = visible_start.saturating_sub(memory_buffer);
-            let keep_end = visible_end + memory_buffer;
+        let member = create_dummy_member_with_user(user);
 
-            for (idx, ui_msg) in self
-                .message_pane_data
-                .ui_messages_mut()
-                .iter_mut()
-                .enumerate()
-            {
-                let is_in_protocol_range = idx >= protocol_start && idx <= protocol_end;
-                let is_in_memory_range = idx >= keep_start && idx <= keep_end;
+        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
+        state.set_channels(guild_id, vec![category.clone(), child.clone()]);
 
-                for attachment in &mut ui_msg.image_attachments {
-                    if is_in_protocol_range {
-                        if attachment.is_ready() && attachment.update_protocol_if_needed(picker) {
-                            dirty = true;
-                        }
-                    } else if attachment.protocol.is_some() {
-                        attachment.clear_protocol();
-                    }
+        // Category is hidden (base permissions empty, no overwrite).
+        // Child is technically visible (overwrite allows).
+        // BUT strict pruning means if category is hidden, child is hidden.
 
-                    if !is_in_memory_range && attachment.image.is_some() {
-                        attachment.image = None;
-                        if matches!(
-                            attachment.status,
-                            crate::domain::entities::ImageStatus::Ready
-                        ) {
-                            attachment.status = crate::domain::entities::ImageStatus::NotStarted;
-                        }
-                    }
-                }
-            }
-            self.last_scroll_state = Some((current_scroll, current_height));
-        } else {
-            let max_idx = self.message_pane_data.message_count();
-            let effective_end = protocol_end.min(max_idx);
-            let effective_start = protocol_start.min(max_idx);
+        assert!(
+            state.guilds_tree_data.get_channel(category.id()).is_none(),
+            "Category should be hidden"
+        );
+        assert!(
+            state.guilds_tree_data.get_channel(child.id()).is_none(),
+            "Child should be hidden because parent is hidden"
+        );
+    }
 
-            if effective_start < max_idx {
-                let messages = self.message_pane_data.ui_messages_mut();
-                for idx in effective_start..effective_end {
-                    if let Some(ui_msg) = messages.get_mut(idx) {
-                        for attachment in &mut ui_msg.image_attachments {
-                            if attachment.is_ready() && attachment.update_protocol_if_needed(picker)
-                            {
-                                dirty = true;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    fn create_test_state(user: User) -> ChatScreenState {
+        ChatScreenState::new(
+            user,
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        )
+    }
 
-        if dirty {
-            self.message_pane_data.mark_dirty();
+    fn create_dummy_role(id: u64) -> Role {
+        Role {
+            id: RoleId(id),
+            name: format!("Role {id}"),
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            permissions: Permissions::empty(),
+            managed: false,
+            mentionable: false,
         }
     }
 
-    /// Marks an image as downloading.
-    #[cfg(feature = "image")]
-    pub fn mark_image_downloading(&mut self, id: &crate::domain::entities::ImageId) {
-        for ui_msg in self.message_pane_data.ui_messages_mut() {
-            for attachment in &mut ui_msg.image_attachments {
-                if &attachment.id == id {
-                    attachment.set_downloading();
-                }
-            }
+    fn create_dummy_member() -> Member {
+        Member {
+            user: None,
+            nick: None,
+            avatar: None,
+            roles: vec![],
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
         }
     }
 
-    /// Marks an image as failed.
-    #[cfg(feature = "image")]
-    pub fn mark_image_failed(&mut self, id: &crate::domain::entities::ImageId, error: &str) {
-        for ui_msg in self.message_pane_data.ui_messages_mut() {
-            for attachment in &mut ui_msg.image_attachments {
-                if &attachment.id == id {
-                    attachment.set_failed(error.to_owned());
-                }
-            }
-        }
+    fn create_dummy_member_with_user(user: User) -> Member {
+        let mut m = create_dummy_member();
+        m.user = Some(user);
+        m
     }
 
-    pub fn add_attachment(&mut self, path: std::path::PathBuf) {
-        self.message_input_state.add_attachment(path);
-        self.focus_message_input();
-    }
+    #[test]
+    #[cfg(not(windows))]
+    fn test_chat_screen_state_creation_initial_focus() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
+
+        assert_eq!(state.focus(), ChatFocus::GuildsTree);
+
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+
+        state.on_channel_selected(channel.id());
 
-    pub fn insert_text(&mut self, text: &str) {
-        self.message_input_state.insert_text_at_cursor(text);
-        self.focus_message_input();
+        assert_eq!(
+            state.focus(),
+            ChatFocus::MessagesList,
+            "Focus should switch to MessagesList on channel selection"
+        );
     }
 
-    pub fn jump_to_message(&mut self, message_id: crate::domain::entities::MessageId) {
-        if let Some(index) = self
-            .message_pane_data
-            .messages()
-            .iter()
-            .position(|m| m.message.id() == message_id)
-        {
-            self.message_pane_state.jump_to_index(index);
-        }
-    }
+    #[test]
+    fn test_escape_from_empty_message_list_focuses_tree() {
+        use crossterm::event::KeyModifiers;
 
-    pub fn increment_mention_count(&mut self, channel_id: ChannelId) {
-        if let Some(active_channel) = &self.selected_channel
-            && active_channel.id() == channel_id
-        {
-            return;
-        }
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        if let Some(read_state) = self.read_states.get_mut(&channel_id) {
-            read_state.mention_count += 1;
-        } else {
-            let mut rs = crate::domain::entities::ReadState::new(channel_id, None);
-            rs.mention_count = 1;
-            self.read_states.insert(channel_id, rs);
-        }
-        self.recalculate_all_unread();
-    }
+        state.focus_messages_list();
+        assert_eq!(state.focus(), ChatFocus::MessagesList);
 
-    #[must_use]
-    pub fn get_channel(&self, channel_id: ChannelId) -> Option<&Channel> {
-        self.guilds_tree_data.get_channel(channel_id)
-    }
+        state.message_pane_state.clear_selection();
 
-    pub fn toggle_file_explorer(&mut self) {
-        self.show_file_explorer = !self.show_file_explorer;
-        if self.show_file_explorer {
-            self.file_explorer = Some(FileExplorerComponent::new());
-        } else {
-            self.file_explorer = None;
-        }
-    }
+        let esc_event = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
 
-    pub fn toggle_help(&mut self) {
-        self.show_help = !self.show_help;
+        let result = state.handle_key(esc_event);
+
+        assert_eq!(result, ChatKeyResult::Consumed);
+        assert_eq!(
+            state.focus(),
+            ChatFocus::GuildsTree,
+            "Should return focus to Guilds Tree on Cancel from empty selection"
+        );
     }
 
-    fn handle_file_explorer_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        if self.registry.find_action(key) == Some(Action::ToggleHiddenFiles)
-            && let Some(explorer) = &mut self.file_explorer
-        {
-            explorer.toggle_hidden();
-            return ChatKeyResult::Consumed;
-        }
+    #[test]
+    fn test_message_input_shows_focus_messages_command() {
+        let state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        if let Some(explorer) = &mut self.file_explorer {
-            match explorer.handle_key(key) {
-                FileExplorerAction::SelectFile(path) => {
-                    self.message_input_state.add_attachment(path);
-                    self.show_file_explorer = false;
-                    self.file_explorer = None;
-                    ChatKeyResult::Consumed
-                }
-                FileExplorerAction::Close => {
-                    self.show_file_explorer = false;
-                    self.file_explorer = None;
-                    ChatKeyResult::Consumed
-                }
-                FileExplorerAction::None => ChatKeyResult::Consumed,
-            }
-        } else {
-            ChatKeyResult::Consumed
-        }
-    }
+        let commands = state.get_message_input_commands(&state.registry);
 
-    pub fn toggle_quick_switcher(&mut self) {
-        self.show_quick_switcher = !self.show_quick_switcher;
-        if self.show_quick_switcher {
-            self.quick_switcher.reset();
-            self.perform_search("");
-        }
+        assert!(
+            commands.iter().any(|k| k.action == Action::FocusMessages),
+            "Message input commands should include FocusMessages (Ctrl+T)"
+        );
     }
 
-    pub fn set_quick_switcher_results(
-        &mut self,
-        results: Vec<crate::domain::search::SearchResult>,
-    ) {
-        self.quick_switcher.set_results(results);
-    }
+    #[test]
+    fn test_channel_without_send_permission_hides_send_hint() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "rules", ChannelKind::Text).with_guild(1_u64);
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            permissions: Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY,
+            managed: false,
+            mentionable: false,
+        };
+        let member = Member {
+            user: Some(user),
+            nick: None,
+            avatar: None,
+            roles: vec![],
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
+        };
+        state.set_guilds(vec![guild.clone()]);
+        state.set_guild_data(guild.id(), vec![everyone_role], vec![member]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
 
-    fn handle_quick_switcher_key(&mut self, key: KeyEvent) -> ChatKeyResult {
-        match key.code {
-            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.quick_switcher.select_previous();
-                return ChatKeyResult::Consumed;
-            }
-            KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.quick_switcher.select_next();
-                return ChatKeyResult::Consumed;
-            }
-            _ => {}
-        }
+        let commands = state.get_message_input_commands(&state.registry);
+        assert!(!commands.iter().any(|k| k.action == Action::SendMessage));
+        assert!(
+            !commands
+                .iter()
+                .any(|k| k.action == Action::ToggleFileExplorer)
+        );
+        assert!(state.message_input_state.is_read_only());
 
-        match self.quick_switcher.handle_key(key) {
-            QuickSwitcherAction::Close => {
-                self.show_quick_switcher = false;
-                self.quick_switcher.reset();
-                self.focus = ChatFocus::GuildsTree;
-                ChatKeyResult::Consumed
-            }
-            QuickSwitcherAction::ToggleSortMode => {
-                self.quick_switcher.toggle_sort_mode();
-                self.perform_search(&self.quick_switcher.input.clone());
-                ChatKeyResult::Consumed
-            }
-            QuickSwitcherAction::Select(result) => {
-                self.show_quick_switcher = false;
-                self.quick_switcher.reset();
-                self.focus = ChatFocus::GuildsTree;
-                self.jump_to_result(&result)
-            }
-            QuickSwitcherAction::UpdateSearch(query) => {
-                self.perform_search(&query);
-                ChatKeyResult::Consumed
-            }
-            QuickSwitcherAction::None => ChatKeyResult::Consumed,
-        }
+        state.message_input_state.set_content("hello");
+        assert!(!matches!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            ChatKeyResult::SendMessage { .. }
+        ));
+
+        setup_permissive_guild_data(&mut state, guild.id());
+        assert!(!state.message_input_state.is_read_only());
+        let commands = state.get_message_input_commands(&state.registry);
+        assert!(commands.iter().any(|k| k.action == Action::SendMessage));
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn perform_search(&mut self, query: &str) {
-        let (prefix, query_text) = parse_search_query(query);
-        let query_text = query_text.to_string();
+    #[test]
+    fn test_unsent_echo_blocks_actions_on_its_local_id() {
+        let mut state = create_test_state(create_test_user());
+        state
+            .message_pane_data
+            .set_channel(ChannelId(7), "general".to_string());
+        state.add_local_echo(
+            MessageId(u64::MAX),
+            &SendMessageRequest::new(ChannelId(7), "on its way"),
+        );
+        state.focus_messages_list();
+        state.message_pane_state.jump_to_index(0);
 
-        if query_text.is_empty() {
-            let mut results = Vec::new();
+        for action in [Action::Reply, Action::EditMessage, Action::DeleteMessage] {
+            let key = state.registry.get_first(action).expect("action is bound");
+            let result = state.handle_key(key);
+            assert!(
+                matches!(result, ChatKeyResult::ShowNotification(ref msg) if msg == UNSENT_NOTICE),
+                "{action:?} should be refused"
+            );
+        }
+        assert!(!state.message_input_state.is_editing());
+        assert!(state.pending_confirmation.is_none());
+    }
 
-            if self.quick_switcher.sort_mode == QuickSwitcherSortMode::Recents {
-                results = self
-                    .recents
-                    .iter()
-                    .map(|r| {
-                        let mut res =
-                            SearchResult::new(r.id.clone(), r.name.clone(), r.kind.clone())
-                                .with_score(100);
-
-                        if let Some(gid) = &r.guild_id
-                            && let Ok(id) = gid.parse::<u64>()
-                            && let Some(guild) = self
-                                .guilds_tree_data
-                                .guilds()
-                                .iter()
-                                .find(|g| g.id() == GuildId(id))
-                        {
-                            res = res.with_guild(gid, guild.name());
-                        }
-                        res
-                    })
-                    .collect();
-            } else {
-                for r in &self.recents {
-                    let mut res = SearchResult::new(r.id.clone(), r.name.clone(), r.kind.clone())
-                        .with_score(0);
-
-                    if let Some(gid) = &r.guild_id
-                        && let Ok(id) = gid.parse::<u64>()
-                        && let Some(guild) = self
-                            .guilds_tree_data
-                            .guilds()
-                            .iter()
-                            .find(|g| g.id() == GuildId(id))
-                    {
-                        res = res.with_guild(gid, guild.name());
-                    }
-                    results.push(res);
-                }
+    #[test]
+    fn test_read_only_channel_blocks_edit_until_writable_channel_selected() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild = Guild::new(1_u64, "Guild A");
+        let announcements =
+            Channel::new(ChannelId(10), "news", ChannelKind::Announcement).with_guild(1_u64);
+        let general = Channel::new(ChannelId(11), "general", ChannelKind::Text)
+            .with_guild(1_u64)
+            .with_permission_overwrites(vec![crate::domain::entities::PermissionOverwrite {
+                id: "1".to_string(),
+                overwrite_type: crate::domain::entities::OverwriteType::Role,
+                allow: Permissions::SEND_MESSAGES.bits().to_string(),
+                deny: "0".to_string(),
+            }]);
+        let everyone_role = Role {
+            permissions: Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY,
+            ..create_dummy_role(1)
+        };
+        state.set_guilds(vec![guild.clone()]);
+        state.set_guild_data(
+            guild.id(),
+            vec![everyone_role],
+            vec![create_dummy_member_with_user(user.clone())],
+        );
+        state.set_channels(guild.id(), vec![announcements.clone(), general.clone()]);
+        state.on_channel_selected(announcements.id());
+        assert!(state.message_input_state.is_read_only());
 
-                if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
-                    let dms = self.guilds_tree_data.dm_users();
-                    tracing::debug!("Mixed Mode: Adding {} DMs", dms.len());
-                    for dm in dms {
-                        let name = if self.use_display_name {
-                            dm.recipient_global_name
-                                .as_ref()
-                                .unwrap_or(&dm.recipient_username)
-                        } else {
-                            &dm.recipient_username
-                        };
-                        if !results
-                            .iter()
-                            .any(|r| r.kind == SearchKind::DM && r.id == dm.channel_id)
-                        {
-                            results.push(
-                                SearchResult::new(dm.channel_id.clone(), name, SearchKind::DM)
-                                    .with_score(0),
-                            );
-                        }
-                    }
-                }
+        let own_message = Message::new(
+            MessageId(2),
+            announcements.id(),
+            MessageAuthor {
+                id: user.id_str(),
+                username: user.username().to_string(),
+                discriminator: "0".to_string(),
+                avatar: None,
+                bot: false,
+                global_name: None,
+            },
+            "Own message".to_string(),
+            chrono::Local::now(),
+            MessageKind::Default,
+        );
+        state.message_pane_data.set_messages(vec![own_message]);
+        state.focus_messages_list();
+        state.message_pane_state.jump_to_index(0);
 
-                if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
-                    let guilds = self.guilds_tree_data.guilds();
-                    tracing::debug!("Mixed Mode: Adding {} Guilds", guilds.len());
-                    for guild in guilds {
-                        if !results
-                            .iter()
-                            .any(|r| r.kind == SearchKind::Guild && r.id == guild.id().to_string())
-                        {
-                            results.push(
-                                SearchResult::new(
-                                    guild.id().to_string(),
-                                    guild.name(),
-                                    SearchKind::Guild,
-                                )
-                                .with_score(0),
-                            );
-                        }
-                    }
-                }
+        let result = state.handle_key(KeyEvent::new(
+            KeyCode::Char('e'),
+            crossterm::event::KeyModifiers::CONTROL,
+        ));
+        assert!(matches!(
+            result,
+            ChatKeyResult::ShowNotification(ref msg) if msg == READ_ONLY_NOTICE
+        ));
+        assert!(!state.message_input_state.is_editing());
 
-                if matches!(
-                    prefix,
-                    SearchPrefix::None
-                        | SearchPrefix::Text
-                        | SearchPrefix::Voice
-                        | SearchPrefix::Thread
-                ) {
-                    let channels = self.collect_searchable_channels(prefix);
-                    tracing::debug!("Mixed Mode: Adding {} Channels", channels.len());
-                    for (guild_name, channel, parent_name) in channels {
-                        let mut kind = SearchKind::Channel;
-                        if channel.kind().is_voice() {
-                            kind = SearchKind::Voice;
-                        } else if channel.kind().is_thread() {
-                            kind = SearchKind::Thread;
-                        } else if channel.kind() == crate::domain::entities::ChannelKind::Forum {
-                            kind = SearchKind::Forum;
-                        }
+        state.on_channel_selected(general.id());
+        assert!(!state.message_input_state.is_read_only());
+    }
 
-                        let channel_id_str = channel.id().to_string();
-                        if !results
-                            .iter()
-                            .any(|r| r.kind == kind && r.id == channel_id_str)
-                        {
-                            let mut res = SearchResult::new(channel_id_str, channel.name(), kind)
-                                .with_score(0);
+    #[test]
+    fn test_ctrl_t_focuses_messages_from_input() {
+        use crossterm::event::KeyModifiers;
 
-                            if let Some(gid) = channel.guild_id() {
-                                res = res.with_guild(gid.to_string(), guild_name);
-                            }
-                            if let Some(pname) = parent_name {
-                                res = res.with_parent_name(pname);
-                            }
-                            results.push(res);
-                        }
-                    }
-                }
-            }
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-            tracing::debug!(
-                "Empty query search results: {} (Mode: {})",
-                results.len(),
-                self.quick_switcher.sort_mode
-            );
-            self.quick_switcher.set_results(results);
-            return;
-        }
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
 
-        let channels = self.collect_searchable_channels(prefix);
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        let result = state.handle_key(ctrl_t);
 
-        let dms = if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
-            self.guilds_tree_data.dm_users().to_vec()
-        } else {
-            Vec::new()
-        };
+        assert_eq!(result, ChatKeyResult::Consumed, "Ctrl+t should be consumed");
+        assert_eq!(
+            state.focus(),
+            ChatFocus::MessagesList,
+            "Ctrl+t should focus the messages list"
+        );
+    }
 
-        let guilds = if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
-            self.guilds_tree_data.guilds().to_vec()
-        } else {
-            Vec::new()
-        };
+    #[test_case('a', KeyModifiers::NONE ; "lowercase char")]
+    #[test_case('Z', KeyModifiers::SHIFT ; "uppercase char")]
+    #[test_case('1', KeyModifiers::NONE ; "digit")]
+    #[test_case('i', KeyModifiers::NONE ; "i key")]
+    #[test_case('t', KeyModifiers::NONE ; "t key")]
+    #[test_case('?', KeyModifiers::SHIFT ; "question mark")]
+    fn test_input_character_handling(c: char, modifiers: KeyModifiers) {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        let channel_provider = ChannelSearchProvider::new(channels);
-        let dm_provider = DmSearchProvider::new(dms, self.use_display_name);
-        let guild_provider = GuildSearchProvider::new(guilds);
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
+        state.message_input_state.clear();
 
-        let mut results = Vec::new();
-        if matches!(
-            prefix,
-            SearchPrefix::None | SearchPrefix::Text | SearchPrefix::Voice | SearchPrefix::Thread
-        ) {
-            results.extend(channel_provider.search_sync(&query_text));
-        }
-        if matches!(prefix, SearchPrefix::None | SearchPrefix::User) {
-            results.extend(dm_provider.search_sync(&query_text));
-        }
-        if matches!(prefix, SearchPrefix::None | SearchPrefix::Guild) {
-            results.extend(guild_provider.search_sync(&query_text));
-        }
-        results.sort_by(|a, b| b.score.cmp(&a.score));
+        let result = state.handle_key(KeyEvent::new(KeyCode::Char(c), modifiers));
 
-        self.quick_switcher.set_results(results);
+        assert!(
+            matches!(result, ChatKeyResult::StartTyping | ChatKeyResult::Ignored),
+            "Character '{}' should be typed in input mode, not trigger actions",
+            c
+        );
+        assert_eq!(
+            state.focus(),
+            ChatFocus::MessageInput,
+            "Focus should remain on MessageInput when typing '{}'",
+            c
+        );
     }
 
-    fn collect_searchable_channels(
-        &self,
-        prefix: SearchPrefix,
-    ) -> Vec<(String, Channel, Option<String>)> {
-        let mut channels = Vec::new();
-        let mut added_ids = std::collections::HashSet::new();
+    #[test_case(KeyCode::Char('t'), KeyModifiers::CONTROL, ChatFocus::MessagesList ; "ctrl+t focus messages")]
+    #[test_case(KeyCode::Char('g'), KeyModifiers::CONTROL, ChatFocus::GuildsTree ; "ctrl+g focus guilds")]
+    fn test_global_focus_shortcuts_preserve_input(
+        key: KeyCode,
+        modifiers: KeyModifiers,
+        target: ChatFocus,
+    ) {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        if matches!(
-            prefix,
-            SearchPrefix::None | SearchPrefix::Text | SearchPrefix::Voice | SearchPrefix::Thread
-        ) {
-            for guild in self.guilds_tree_data.guilds() {
-                if let Some(guild_channels) = self.guilds_tree_data.channels(guild.id()) {
-                    for channel in guild_channels.iter() {
-                        if channel.kind() == crate::domain::entities::ChannelKind::Category {
-                            continue;
-                        }
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
 
-                        let include = match prefix {
-                            SearchPrefix::Text => {
-                                !channel.kind().is_voice() && !channel.kind().is_thread()
-                            }
-                            SearchPrefix::Voice => channel.kind().is_voice(),
-                            SearchPrefix::Thread => channel.kind().is_thread(),
-                            _ => true,
-                        };
+        state.focus_message_input();
+        let text = "Important draft";
+        state.message_input_state.set_content(text);
 
-                        if include {
-                            let mut parent_name = None;
-                            if let Some(pid) = channel.parent_id()
-                                && let Some(parent) = self.guilds_tree_data.get_channel(pid)
-                            {
-                                parent_name = Some(parent.name().to_string());
-                            }
-                            channels.push((guild.name().to_string(), channel.clone(), parent_name));
-                            added_ids.insert(channel.id());
-                        }
-                    }
-                }
-            }
+        let result = state.handle_key(KeyEvent::new(key, modifiers));
 
-            if matches!(prefix, SearchPrefix::None | SearchPrefix::Thread) {
-                for (channel_id, state) in &self.forum_states {
-                    if let Some(parent_channel) = self.guilds_tree_data.get_channel(*channel_id) {
-                        let guild_name = parent_channel
-                            .guild_id()
-                            .and_then(|gid| {
-                                self.guilds_tree_data
-                                    .guilds()
-                                    .iter()
-                                    .find(|g| g.id() == gid)
-                            })
-                            .map_or(String::new(), |g| g.name().to_string());
+        assert_eq!(result, ChatKeyResult::Consumed);
+        assert_eq!(state.focus(), target);
+        assert_eq!(
+            state.message_input_state.value(),
+            text,
+            "Input buffer should be preserved"
+        );
+    }
 
-                        let parent_name = parent_channel.name().to_string();
+    #[test_case(ChatFocus::MessagesList, KeyCode::Char('i'), KeyModifiers::NONE ; "i from messages list")]
+    #[test_case(ChatFocus::GuildsTree, KeyCode::Char('i'), KeyModifiers::NONE ; "i from guilds tree")]
+    #[test_case(ChatFocus::MessagesList, KeyCode::Char('i'), KeyModifiers::CONTROL ; "ctrl+i from messages list")]
+    fn test_focus_input_shortcuts(initial_focus: ChatFocus, key: KeyCode, modifiers: KeyModifiers) {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-                        for thread in &state.threads {
-                            if !added_ids.contains(&thread.id) {
-                                let channel = Channel::new(
-                                    thread.id,
-                                    &thread.name,
-                                    ChannelKind::PublicThread,
-                                )
-                                .with_guild(parent_channel.guild_id().unwrap_or(GuildId(0)))
-                                .with_parent(parent_channel.id());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+
+        state.set_focus(initial_focus);
 
-                                channels.push((
-                                    guild_name.clone(),
-                                    channel,
-                                    Some(parent_name.clone()),
-                                ));
-                                added_ids.insert(thread.id);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        channels
+        let result = state.handle_key(KeyEvent::new(key, modifiers));
+
+        assert_eq!(result, ChatKeyResult::Consumed);
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
     }
 
-    fn jump_to_result(&mut self, result: &crate::domain::search::SearchResult) -> ChatKeyResult {
-        match result.kind {
-            SearchKind::DM => {
-                if let Some(result) = self.on_dm_selected(&result.id) {
-                    return result;
-                }
-            }
-            SearchKind::Thread => {
-                let thread_id_val = result.id.parse::<u64>().unwrap_or(0);
-                let thread_channel_id = ChannelId(thread_id_val);
+    #[test]
+    fn test_double_esc_closes_overlays() {
+        let mut state = create_test_state(create_test_user());
+        state.set_double_esc_to_close(true);
+        let esc = KeyEvent::from(KeyCode::Esc);
 
-                if let Some(result) = self.on_channel_selected(thread_channel_id) {
-                    return result;
-                }
+        state.toggle_help();
+        assert_eq!(state.handle_key(esc), ChatKeyResult::Consumed);
+        assert!(state.show_help);
+        state.handle_key(esc);
+        assert!(!state.show_help);
 
-                let found_thread = self.forum_states.iter().find_map(|(parent_id, state)| {
-                    state
-                        .threads
-                        .iter()
-                        .find(|t| t.id == thread_channel_id)
-                        .map(|t| (*parent_id, t.clone()))
-                });
+        state.toggle_quick_switcher();
+        state.handle_key(esc);
+        assert!(state.show_quick_switcher);
+        state.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        state.handle_key(esc);
+        assert!(state.show_quick_switcher, "other keys disarm the close");
+        state.handle_key(esc);
+        assert!(!state.show_quick_switcher);
+
+        state.set_double_esc_to_close(false);
+        state.toggle_help();
+        state.handle_key(esc);
+        assert!(!state.show_help);
+    }
 
-                if let Some((parent_id, thread)) = found_thread {
-                    let _ = self.on_channel_selected(parent_id);
+    #[test]
+    fn test_mark_channel_read_emits_ack() {
+        let mut state = create_test_state(create_test_user());
 
-                    let channel =
-                        Channel::new(thread.id, thread.name.clone(), ChannelKind::PublicThread)
-                            .with_guild(thread.guild_id.unwrap_or(GuildId(0)).as_u64())
-                            .with_parent(parent_id);
+        assert_eq!(
+            state.mark_channel_read(ChannelId(7), MessageId(70)),
+            Some(ChatKeyResult::AcknowledgeMessage {
+                channel_id: ChannelId(7),
+                message_id: MessageId(70),
+            })
+        );
+        assert_eq!(state.mark_channel_read(ChannelId(7), MessageId(70)), None);
+        assert_eq!(
+            state.mark_channel_read(ChannelId(7), MessageId(71)),
+            Some(ChatKeyResult::AcknowledgeMessage {
+                channel_id: ChannelId(7),
+                message_id: MessageId(71),
+            })
+        );
+    }
 
-                    self.selected_channel = Some(channel.clone());
-                    self.message_pane_data
-                        .set_channel(thread_channel_id, channel.display_name());
-                    self.message_pane_state.on_channel_change();
-                    self.message_input_state.set_has_channel(true);
-                    self.message_input_state.clear();
-                    self.focus_messages_list();
+    #[test]
+    fn test_suppressed_everyone_mentions_skip_badge() {
+        let mut state = create_test_state(create_test_user());
+        let mentions = |state: &ChatScreenState| {
+            state
+                .read_states
+                .get(&ChannelId(7))
+                .map_or(0, |rs| rs.mention_count)
+        };
 
-                    return ChatKeyResult::LoadChannelMessages {
-                        channel_id: thread_channel_id,
-                        guild_id: thread.guild_id,
-                    };
-                }
-            }
-            SearchKind::Channel | SearchKind::Forum | SearchKind::Voice => {
-                if let Ok(id) = result.id.parse::<u64>() {
-                    let channel_id = ChannelId(id);
-                    if let Some(result) = self.on_channel_selected(channel_id) {
-                        return result;
-                    }
-                }
-            }
-            SearchKind::Guild => {
-                if let Ok(id) = result.id.parse::<u64>() {
-                    let guild_id = GuildId(id);
-                    if let Some(result) = self.on_guild_selected(guild_id) {
-                        return result;
-                    }
-                }
-            }
-        }
-        ChatKeyResult::Consumed
+        state.increment_mention_count(ChannelId(7), true);
+        assert_eq!(mentions(&state), 1);
+
+        state.set_suppress_everyone_highlight(true);
+        state.increment_mention_count(ChannelId(7), true);
+        assert_eq!(mentions(&state), 1);
+        state.increment_mention_count(ChannelId(7), false);
+        assert_eq!(mentions(&state), 2);
     }
-}
 
-impl HasCommands for ChatScreenState {
-    fn get_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
-        let mut commands = Vec::new();
+    #[test]
+    fn test_total_mentions_tracks_read_states() {
+        use crate::domain::entities::ReadState;
 
-        if self.show_help {
-            commands.push(Keybind::new(
-                KeyEvent::from(KeyCode::Esc),
-                Action::ToggleHelp,
-                "Close",
-            ));
-            return commands;
-        }
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(7), "pinged", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.set_read_states(
+            [
+                (
+                    ChannelId(7),
+                    ReadState::new(ChannelId(7), None).with_mention_count(2),
+                ),
+                (
+                    ChannelId(8),
+                    ReadState::new(ChannelId(8), None).with_mention_count(3),
+                ),
+            ]
+            .into(),
+        );
+        let sum = |state: &ChatScreenState| -> u32 {
+            state.read_states.values().map(|rs| rs.mention_count).sum()
+        };
+        assert_eq!(state.total_mentions(), 5);
 
-        if self.show_quick_switcher {
-            commands.push(
-                Keybind::new(KeyEvent::from(KeyCode::Up), Action::NavigateUp, "Nav")
-                    .with_display("Ctrl+k/j"),
-            );
-            commands.push(Keybind::new(
-                KeyEvent::from(KeyCode::Enter),
-                Action::Select,
-                "Select",
-            ));
-            commands.push(Keybind::new(
-                KeyEvent::from(KeyCode::Tab),
-                Action::None,
-                format!("Sort ({})", self.quick_switcher.sort_mode),
-            ));
-            commands.push(Keybind::new(
-                KeyEvent::from(KeyCode::Esc),
-                Action::Cancel,
-                "Close",
-            ));
-            return commands;
-        }
+        state.increment_mention_count(ChannelId(9), false);
+        assert_eq!(state.total_mentions(), 6);
+        assert_eq!(state.total_mentions(), sum(&state));
 
-        if self.show_file_explorer {
-            if let Some(key) = registry.get_first(Action::Select) {
-                commands.push(Keybind::new(key, Action::Select, "Select"));
-            }
-            if let Some(key) = registry.get_first(Action::ToggleFileExplorer) {
-                commands.push(Keybind::new(key, Action::ToggleFileExplorer, "Close"));
-            }
-            commands.push(Keybind::new(
-                KeyEvent::from(KeyCode::Char('.')),
-                Action::ToggleHiddenFiles,
-                "Hidden",
-            ));
-        } else {
-            match self.focus {
-                ChatFocus::GuildsTree => {
-                    commands.extend(self.get_guilds_tree_commands(registry));
-                }
-                ChatFocus::MessagesList => {
-                    commands.extend(self.get_messages_list_commands(registry));
-                }
-                ChatFocus::MessageInput => {
-                    commands.extend(self.get_message_input_commands(registry));
-                }
-                ChatFocus::ConfirmationModal => {
-                    commands.push(Keybind::new(
-                        KeyEvent::from(KeyCode::Enter),
-                        Action::Select,
-                        "Confirm",
-                    ));
-                    commands.push(Keybind::new(
-                        KeyEvent::from(KeyCode::Esc),
-                        Action::Cancel,
-                        "Cancel",
-                    ));
-                    if let Some(key) = registry.get_first(Action::ToggleHelp) {
-                        commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
-                    }
-                }
-            }
-        }
+        state.on_channel_selected(channel.id());
+        assert_eq!(state.total_mentions(), 4);
+        assert_eq!(state.total_mentions(), sum(&state));
+    }
 
-        commands
+    #[test]
+    fn test_folder_collapse_survives_state_reload() {
+        let folder_node = TreeNodeId::Folder(Some(9));
+        let folders = || {
+            vec![GuildFolder {
+                id: Some(9),
+                name: Some("Work".to_string()),
+                color: None,
+                guild_ids: vec![GuildId(1), GuildId(2)],
+            }]
+        };
+        let mut state = create_test_state(create_test_user());
+        state.set_guilds(vec![Guild::new(1_u64, "A"), Guild::new(2_u64, "B")]);
+        state.set_guild_folders(folders());
+        state.restore_tree_expansion(&["folder:9".to_string()]);
+        state.guilds_tree_state.select(folder_node.clone());
+
+        state.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(state.take_tree_expansion_changed());
+        assert!(!state.take_tree_expansion_changed());
+        let saved = state.tree_expansion();
+        assert!(saved.is_empty());
+
+        state.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(state.take_tree_expansion_changed());
+        let saved = state.tree_expansion();
+        assert_eq!(saved, vec!["folder:9".to_string()]);
+
+        let mut reloaded = create_test_state(create_test_user());
+        reloaded.set_guild_folders(folders());
+        reloaded.restore_tree_expansion(&saved);
+        assert!(reloaded.guilds_tree_state.is_expanded(&folder_node));
+        assert_eq!(reloaded.guilds_tree_state.selected(), None);
+
+        let mut collapsed = create_test_state(create_test_user());
+        collapsed.restore_tree_expansion(&[]);
+        assert!(!collapsed.guilds_tree_state.is_expanded(&folder_node));
     }
-}
 
-impl ChatScreenState {
-    #[allow(clippy::unused_self)]
-    fn get_guilds_tree_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
-        let mut commands = Vec::new();
-        if let Some(key) = registry.get_first(Action::NavigateDown) {
-            let mut bind = Keybind::new(key, Action::NavigateDown, "Nav");
-            if let KeyCode::Char('j') = key.code {
-                bind = bind.with_display("j/k");
-            }
-            commands.push(bind);
-        }
+    #[test]
+    fn test_streamed_guilds_show_before_their_channels() {
+        let mut state = create_test_state(create_test_user());
+        let alpha = Guild::new(1_u64, "Alpha");
+        state.set_guilds(vec![alpha.clone()]);
+
+        state.add_guilds(vec![
+            Guild::new(2_u64, "Beta"),
+            Guild::new(1_u64, "Renamed"),
+        ]);
+        let names: Vec<&str> = state
+            .guilds_tree_data
+            .guilds()
+            .iter()
+            .map(Guild::name)
+            .collect();
+        assert_eq!(names, vec!["Alpha", "Beta"]);
+        assert!(state.guilds_tree_data.channels(GuildId(2)).is_none());
 
-        if let Some(selected_id) = self.guilds_tree_state.selected() {
-            let is_expanded = self.guilds_tree_state.is_expanded(selected_id);
-            let can_expand = matches!(
-                selected_id,
-                TreeNodeId::Guild(_)
-                    | TreeNodeId::Category(_)
-                    | TreeNodeId::DirectMessages
-                    | TreeNodeId::Folder(_)
-            );
+        setup_permissive_guild_data(&mut state, GuildId(2));
+        state.set_channels(
+            GuildId(2),
+            vec![Channel::new(ChannelId(20), "news", ChannelKind::Text).with_guild(2_u64)],
+        );
+        let channels = state
+            .guilds_tree_data
+            .channels(GuildId(2))
+            .expect("channel payload populates the guild");
+        assert_eq!(
+            channels.iter().map(Channel::id).collect::<Vec<_>>(),
+            vec![ChannelId(20)]
+        );
+        assert!(state.guilds_tree_data.channels(alpha.id()).is_none());
+    }
 
-            if can_expand {
-                if is_expanded {
-                    if let Some(key) = registry.get_first(Action::NavigateLeft) {
-                        commands.push(Keybind::new(key, Action::NavigateLeft, "Collapse"));
-                    }
-                } else if let Some(key) = registry.get_first(Action::NavigateRight) {
-                    commands.push(Keybind::new(key, Action::NavigateRight, "Expand"));
-                }
-            } else if let Some(key) = registry.get_first(Action::NavigateLeft) {
-                commands.push(Keybind::new(key, Action::NavigateLeft, "Back"));
-            }
-        } else if let Some(key) = registry.get_first(Action::NavigateRight) {
-            commands.push(Keybind::new(key, Action::NavigateRight, "Expand"));
-        }
+    #[test]
+    fn test_copy_unread_channels_lists_only_unread() {
+        use crate::domain::entities::ReadState;
+
+        let mut state = create_test_state(create_test_user());
+        let alpha = Guild::new(1_u64, "Alpha");
+        let beta = Guild::new(2_u64, "Beta");
+        let channel = |id: u64, name: &str, guild: u64| {
+            Channel::new(ChannelId(id), name, ChannelKind::Text)
+                .with_guild(guild)
+                .with_last_message_id(Some(MessageId(100)))
+        };
+        state.set_eager_guild_count(1);
+        state.set_guilds(vec![alpha.clone(), beta.clone()]);
+        setup_permissive_guild_data(&mut state, alpha.id());
+        setup_permissive_guild_data(&mut state, beta.id());
+        state.set_channels(
+            alpha.id(),
+            vec![channel(10, "general", 1), channel(11, "random", 1)],
+        );
+        state.set_channels(beta.id(), vec![channel(20, "news", 2)]);
+        state.set_read_states(
+            [
+                (
+                    ChannelId(10),
+                    ReadState::new(ChannelId(10), Some(MessageId(50))),
+                ),
+                (
+                    ChannelId(11),
+                    ReadState::new(ChannelId(11), Some(MessageId(100))),
+                ),
+                (ChannelId(20), ReadState::new(ChannelId(20), None)),
+            ]
+            .into(),
+        );
 
-        if let Some(key) = registry.get_first(Action::Select) {
-            commands.push(Keybind::new(key, Action::Select, "Select"));
-        }
-        if let Some(key) = registry.get_first(Action::ToggleGuildsTree) {
-            commands.push(Keybind::new(key, Action::ToggleGuildsTree, "Toggle Tree"));
-        }
-        if let Some(key) = registry.get_first(Action::FocusMessages) {
-            commands.push(Keybind::new(key, Action::FocusMessages, "Msgs"));
-        }
-        if let Some(key) = registry.get_first(Action::NextTab) {
-            commands.push(Keybind::new(key, Action::NextTab, "Next"));
-        }
-        if let Some(key) = registry.get_first(Action::ToggleHelp) {
-            commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
+        let copy = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::ALT);
+        assert_eq!(
+            state.handle_key(copy),
+            ChatKeyResult::CopyToClipboard("Alpha › #general\nBeta › #news".to_string())
+        );
+        assert!(state.guilds_tree_data.channels(beta.id()).is_none());
+
+        state.mark_channel_read(ChannelId(10), MessageId(100));
+        state.mark_channel_read(ChannelId(20), MessageId(100));
+        assert_eq!(
+            state.handle_key(copy),
+            ChatKeyResult::ShowNotification("No unread channels".to_string())
+        );
+    }
+
+    #[test]
+    fn test_publish_only_own_unpublished_announcements() {
+        use crate::domain::entities::MessageFlags;
+
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel =
+            Channel::new(ChannelId(10), "news", ChannelKind::Announcement).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        let message =
+            |id: u64, author_id: &str| create_test_message(id, channel.id(), author_id, "update");
+        state.set_messages(vec![
+            message(1, "123"),
+            message(2, "123").with_flags(MessageFlags::CROSSPOSTED),
+            message(3, "456"),
+        ]);
+        state.focus_messages_list();
+
+        let publish = KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT);
+        state.message_pane_state.jump_to_index(0);
+        assert_eq!(
+            state.handle_key(publish),
+            ChatKeyResult::PublishMessage(MessageId(1))
+        );
+        for index in [1, 2] {
+            state.message_pane_state.jump_to_index(index);
+            assert_eq!(
+                state.handle_key(publish),
+                ChatKeyResult::ShowNotification(
+                    "Only your own announcements can be published".to_string()
+                )
+            );
         }
-        commands
     }
 
-    fn get_messages_list_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
-        let mut commands = Vec::new();
-        if let Some(key) = registry.get_first(Action::NavigateDown) {
-            let mut bind = Keybind::new(key, Action::NavigateDown, "Nav");
-            if let KeyCode::Char('j') = key.code {
-                bind = bind.with_display("j/k");
-            }
-            commands.push(bind);
-        }
-        if let Some(key) = registry.get_first(Action::Reply) {
-            commands.push(Keybind::new(key, Action::Reply, "Reply"));
-        }
-        if let Some(key) = registry.get_first(Action::EditMessage) {
-            let can_edit = self
-                .message_pane_state
-                .selected_index()
-                .and_then(|idx| self.message_pane_data.get_message(idx))
-                .is_some_and(|m| m.can_be_edited_by(&self.user));
+    #[test]
+    fn test_purge_confirms_own_recent_messages_up_to_limit() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.set_purge_message_limit(2);
+        let message =
+            |id: u64, author_id: &str| create_test_message(id, channel.id(), author_id, "hello");
+        let purge = KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT);
 
-            if can_edit {
-                commands.push(Keybind::new(key, Action::EditMessage, "Edit"));
-            }
-        }
-        if let Some(key) = registry.get_first(Action::DeleteMessage) {
-            let can_delete = self
-                .message_pane_state
-                .selected_index()
-                .and_then(|idx| self.message_pane_data.get_message(idx))
-                .is_some_and(|m| m.can_be_edited_by(&self.user));
+        state.set_messages(vec![message(1, "456")]);
+        state.focus_messages_list();
+        assert_eq!(
+            state.handle_key(purge),
+            ChatKeyResult::ShowNotification("You have no messages to purge here".to_string())
+        );
 
-            if can_delete {
-                commands.push(Keybind::new(key, Action::DeleteMessage, "Delete"));
-            }
-        }
-        if let Some(key) = registry.get_first(Action::OpenAttachments) {
-            let label = self
-                .message_pane_state
-                .selected_index()
-                .and_then(|idx| self.message_pane_data.get_message(idx))
-                .and_then(|m| MessageContentService::resolve(m).label());
+        state.set_messages(vec![
+            message(1, "123"),
+            message(2, "123"),
+            message(3, "456"),
+            message(4, "123"),
+        ]);
+        assert_eq!(state.handle_key(purge), ChatKeyResult::Consumed);
+        assert_eq!(state.focus(), ChatFocus::ConfirmationModal);
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            ChatKeyResult::PurgeMessages(vec![MessageId(4), MessageId(2)])
+        );
+    }
 
-            if let Some(label) = label {
-                commands.push(Keybind::new(key, Action::OpenAttachments, label));
-            } else {
-                commands.push(Keybind::new(key, Action::OpenAttachments, "Open"));
-            }
-        }
-        if let Some(key) = registry.get_first(Action::CopyContent) {
-            commands.push(Keybind::new(key, Action::CopyContent, "Copy"));
-        }
-        if let Some(key) = registry.get_first(Action::JumpToReply) {
-            commands.push(Keybind::new(key, Action::JumpToReply, "Jump"));
-        }
-        if let Some(key) = registry.get_first(Action::FocusInput) {
-            commands.push(Keybind::new(key, Action::FocusInput, "Focus Input"));
+    #[test]
+    fn test_mention_jump_cycles_through_mentions_and_wraps() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        let message = |id: u64, mentions: Vec<User>| {
+            create_test_message(id, channel.id(), "456", "hello").with_mentions(mentions)
+        };
+        let other = User::new("789", "someone", "0", None, false, None);
+        let next = KeyEvent::new(KeyCode::Char(']'), KeyModifiers::NONE);
+        let previous = KeyEvent::new(KeyCode::Char('['), KeyModifiers::NONE);
+
+        state.set_messages(vec![message(1, vec![other.clone()])]);
+        state.focus_messages_list();
+        assert_eq!(
+            state.handle_key(next),
+            ChatKeyResult::ShowNotification("No mentions in this channel".to_string())
+        );
+
+        state.set_messages(vec![
+            message(1, vec![create_test_user()]),
+            message(2, vec![other.clone()]),
+            message(3, Vec::new()),
+            message(4, vec![other, create_test_user()]),
+            message(5, Vec::new()),
+            message(6, vec![create_test_user()]),
+        ]);
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            state.handle_key(next);
+            visited.push(state.message_pane_state.selected_index());
         }
-        if let Some(key) = registry.get_first(Action::ToggleHelp) {
-            commands.push(Keybind::new(key, Action::ToggleHelp, "Help"));
+        assert_eq!(visited, vec![Some(0), Some(3), Some(5), Some(0)]);
+
+        visited.clear();
+        for _ in 0..3 {
+            state.handle_key(previous);
+            visited.push(state.message_pane_state.selected_index());
         }
-        commands
+        assert_eq!(visited, vec![Some(5), Some(3), Some(0)]);
     }
 
-    #[allow(clippy::unused_self)]
-    fn get_message_input_commands(&self, registry: &CommandRegistry) -> Vec<Keybind> {
-        let mut commands = Vec::new();
-        if let Some(key) = registry.get_first(Action::SendMessage) {
-            commands.push(Keybind::new(key, Action::SendMessage, "Send"));
-        }
-        if let Some(key) = registry.get_first(Action::FocusMessages) {
-            commands.push(Keybind::new(key, Action::FocusMessages, "Msgs"));
-        }
-        if let Some(key) = registry.get_first(Action::OpenEditor) {
-            commands.push(Keybind::new(key, Action::OpenEditor, "Editor"));
-        }
-        if let Some(key) = registry.get_first(Action::ToggleFileExplorer) {
-            commands.push(Keybind::new(key, Action::ToggleFileExplorer, "Attach"));
-        }
-        if let Some(key) = registry.get_first(Action::Paste) {
-            commands.push(Keybind::new(key, Action::Paste, "Paste"));
-        }
-        if let Some(key) = registry.get_first(Action::Cancel) {
-            commands.push(Keybind::new(key, Action::Cancel, "Cancel"));
-        }
+    #[test]
+    fn test_everyone_send_waits_for_confirmation() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
 
-        commands.push(Keybind::new(
-            KeyEvent::from(KeyCode::F(1)),
-            Action::ToggleHelp,
-            "Help",
-        ));
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        state
+            .message_input_state
+            .set_content("@everyone standup now");
+        assert_eq!(state.handle_key(enter), ChatKeyResult::Consumed);
+        assert_eq!(state.focus(), ChatFocus::ConfirmationModal);
 
-        commands
-    }
-}
+        state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
+        assert_eq!(
+            state.message_input_state.value(),
+            "@everyone standup now",
+            "cancelled send goes back into the input"
+        );
 
-#[cfg(test)]
-#[cfg(not(windows))]
-mod tests {
-    use super::*;
-    use crate::domain::entities::RoleId;
-    use test_case::test_case;
+        state.handle_key(enter);
+        assert_eq!(
+            state.handle_key(enter),
+            ChatKeyResult::SendMessage {
+                content: "@everyone standup now".to_string(),
+                reply_to: None,
+                mention_reply: true,
+                mass_mention_confirmed: true,
+                attachments: vec![],
+            }
+        );
 
-    fn setup_permissive_guild_data(state: &mut ChatScreenState, guild_id: GuildId) {
-        let user = state.user().clone();
-        let role = Role {
-            id: RoleId(guild_id.as_u64()), // @everyone has same ID as guild
+        state.set_confirm_everyone_send(false);
+        state.message_input_state.set_content("@here lunch");
+        assert!(matches!(
+            state.handle_key(enter),
+            ChatKeyResult::SendMessage { .. }
+        ));
+    }
+
+    #[test]
+    fn test_everyone_send_skips_prompt_without_permission() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        let everyone_role = Role {
+            id: RoleId(1),
             name: "@everyone".to_string(),
-            permissions: Permissions::all(), // Allow everything
             color: 0,
             hoist: false,
             icon: None,
             unicode_emoji: None,
             position: 0,
+            permissions: Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
             managed: false,
             mentionable: false,
         };
         let member = Member {
             user: Some(user),
-            roles: vec![],
             nick: None,
             avatar: None,
+            roles: vec![],
             joined_at: String::new(),
             premium_since: None,
             deaf: false,
@@ -3015,190 +6569,274 @@ mod tests {
             permissions: None,
             communication_disabled_until: None,
         };
-        state.set_guild_data(guild_id, vec![role], vec![member]);
-    }
+        state.set_guilds(vec![guild.clone()]);
+        state.set_guild_data(guild.id(), vec![everyone_role], vec![member]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
 
-    fn create_test_user() -> User {
-        User::new("123", "testuser", "0", None, false, None)
+        state.message_input_state.set_content("@everyone hello");
+        assert!(matches!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            ChatKeyResult::SendMessage { .. }
+        ));
     }
 
     #[test]
-    fn test_reselecting_same_guild_preserves_channel() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
-        );
-
-        let guild_a = Guild::new(1_u64, "Guild A");
-        let channel_a1 = Channel::new(ChannelId(10), "Channel A1", ChannelKind::Text);
-
-        state.set_guilds(vec![guild_a.clone()]);
-        setup_permissive_guild_data(&mut state, guild_a.id());
-        state.set_channels(guild_a.id(), vec![channel_a1.clone()]);
-
-        state.on_guild_selected(guild_a.id());
-        state.on_channel_selected(channel_a1.id());
-
-        state.on_guild_selected(guild_a.id());
+    fn test_guild_without_accessible_channels_shows_empty_state() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Locked");
+        let channel = Channel::new(ChannelId(10), "secret", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel]);
 
+        assert_eq!(state.on_guild_selected(guild.id()), None);
+        assert_eq!(state.selected_channel(), None);
         assert_eq!(
-            state
-                .selected_channel()
-                .map(crate::domain::entities::Channel::id),
-            Some(channel_a1.id()),
-            "Channel selection should be preserved when reselecting same guild"
+            state.message_pane_data.loading_state(),
+            crate::presentation::widgets::LoadingState::NoAccessibleChannels
+        );
+
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        MessagePane::new(&mut state.message_pane_data, &MarkdownRenderer::new()).render(
+            area,
+            &mut buf,
+            &mut state.message_pane_state,
+        );
+        assert!(
+            buffer_rows(&buf)
+                .concat()
+                .contains("No accessible channels in this guild")
         );
     }
 
     #[test]
-    fn test_external_edit_restriction() {
-        use crate::domain::entities::{ChannelId, Message, MessageAuthor, MessageId, MessageKind};
-        use chrono::Local;
+    fn test_compact_guilds_tree_keeps_fixed_width() {
+        let mut state = create_test_state(create_test_user());
+        let area = Rect::new(0, 0, 100, 10);
+        let tree_right_edge = |state: &mut ChatScreenState| {
+            let mut buf = Buffer::empty(area);
+            render_content_area(state, area, &mut buf);
+            (0..area.width).find(|&x| buf[(x, 0)].symbol() == "┐")
+        };
 
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+        assert_eq!(tree_right_edge(&mut state), Some(24));
+        state.set_compact_guilds_tree(true);
+        assert_eq!(
+            tree_right_edge(&mut state),
+            Some(COMPACT_GUILDS_TREE_WIDTH - 1)
         );
+    }
 
-        let channel_id = ChannelId(1);
-        let timestamp = Local::now();
-
-        let other_message = Message::new(
-            MessageId(2),
-            channel_id,
+    #[test]
+    fn test_nickname_change_shows_in_message_pane() {
+        let user = create_test_user();
+        let mut state = create_test_state(user.clone());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.set_messages(vec![Message::new(
+            MessageId(1),
+            channel.id(),
             MessageAuthor {
-                id: "456".to_string(),
-                username: "other".to_string(),
+                id: user.id_str(),
+                username: user.username().to_string(),
                 discriminator: "0".to_string(),
                 avatar: None,
                 bot: false,
                 global_name: None,
             },
-            "Other message".to_string(),
-            timestamp,
+            "hello".to_string(),
+            chrono::Local::now(),
             MessageKind::Default,
-        );
+        )]);
+
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        let rendered = |state: &mut ChatScreenState, buf: &mut Buffer| {
+            render_message_pane(state, area, buf);
+            buffer_rows(buf).concat()
+        };
+        assert!(rendered(&mut state, &mut buf).contains("testuser"));
+
+        let mut member = create_dummy_member_with_user(user);
+        member.nick = Some("Ferris".to_string());
+        state.set_own_member(guild.id(), member);
+        let text = rendered(&mut state, &mut buf);
+        assert!(text.contains("Ferris"));
+        assert!(!text.contains("testuser"));
 
-        state.message_pane_data.set_messages(vec![other_message]);
         state.focus_messages_list();
-        state.message_pane_state.jump_to_index(0);
+        let key = state
+            .registry
+            .get_first(Action::ChangeNickname)
+            .expect("action is bound");
+        assert_eq!(state.handle_key(key), ChatKeyResult::Consumed);
+        assert_eq!(state.message_input_state.value(), "/nick Ferris");
+    }
+
+    #[test]
+    fn test_active_guild_roles_resolve_in_message_pane() {
+        use crate::application::services::markdown_parser::MentionResolver;
+
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+
+        assert_eq!(state.message_pane_data.resolve_role("1"), None);
+        state.on_channel_selected(channel.id());
+        assert_eq!(
+            state.message_pane_data.resolve_role("1"),
+            Some(("@everyone".to_string(), 0))
+        );
+    }
 
-        let edit_key = KeyEvent::new(KeyCode::Char('e'), crossterm::event::KeyModifiers::CONTROL);
+    #[test]
+    fn test_startup_focus_applied_at_construction() {
+        let state = create_test_state(create_test_user());
+        assert_eq!(state.focus(), ChatFocus::GuildsTree);
+        assert!(state.guilds_tree_state.is_focused());
+        assert!(!state.message_input_state.is_focused());
 
-        let result = state.handle_key(edit_key);
+        let state = create_test_state(create_test_user()).with_startup_focus(StartupFocus::Input);
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
+        assert!(state.message_input_state.is_focused());
+        assert!(!state.guilds_tree_state.is_focused());
+        assert!(!state.message_pane_state.is_focused());
 
-        match result {
-            ChatKeyResult::ShowNotification(msg) => {
-                assert_eq!(msg, "You can only edit your own messages");
-            }
-            _ => panic!("Expected ShowNotification, got {result:?}"),
-        }
+        let state =
+            create_test_state(create_test_user()).with_startup_focus(StartupFocus::Messages);
+        assert_eq!(state.focus(), ChatFocus::MessagesList);
+        assert!(state.message_pane_state.is_focused());
     }
 
     #[test]
-    fn test_increment_mention_count_on_active_channel_should_not_increment() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    fn test_startup_focus_survives_channel_restore() {
+        let mut state =
+            create_test_state(create_test_user()).with_startup_focus(StartupFocus::Input);
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.restore_state(
+            Some(guild.id()),
+            Some(channel.id()),
+            Some(vec![channel]),
+            None,
         );
+        assert!(state.selected_channel.is_some());
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
+    }
 
-        let guild = crate::domain::entities::Guild::new(1_u64, "Guild A");
-        let channel = crate::domain::entities::Channel::new(
-            crate::domain::entities::ChannelId(10),
-            "Channel A",
-            crate::domain::entities::ChannelKind::Text,
+    #[test]
+    fn test_focus_gained_marks_active_channel_read() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        let author = MessageAuthor {
+            id: "456".to_string(),
+            username: "other".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        state.set_messages(vec![Message::new(
+            MessageId(100),
+            channel.id(),
+            author,
+            "hello".to_string(),
+            chrono::Local::now(),
+            MessageKind::Default,
+        )]);
+        state
+            .message_pane_data
+            .add_pending_message(create_test_message(u64::MAX - 1, channel.id(), "123", "hi"));
+
+        assert_eq!(state.on_focus_gained(), None, "disabled by default");
+        assert!(!state.read_states.contains_key(&channel.id()));
+
+        state.set_mark_read_on_focus(true);
+        assert_eq!(
+            state.on_focus_gained(),
+            Some(ChatKeyResult::AcknowledgeMessage {
+                channel_id: channel.id(),
+                message_id: MessageId(100),
+            })
+        );
+        assert_eq!(
+            state.read_states[&channel.id()].last_read_message_id,
+            Some(MessageId(100))
         );
+    }
 
+    #[test]
+    fn test_incoming_messages_ack_once_per_interval() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text).with_guild(1_u64);
         state.set_guilds(vec![guild.clone()]);
         setup_permissive_guild_data(&mut state, guild.id());
         state.set_channels(guild.id(), vec![channel.clone()]);
-
         state.on_channel_selected(channel.id());
+        let ack = |id: u64| ChatKeyResult::AcknowledgeMessage {
+            channel_id: channel.id(),
+            message_id: MessageId(id),
+        };
+
+        let received = |state: &mut ChatScreenState, id: u64| {
+            state.on_message_received(&create_test_message(id, channel.id(), "456", "hi"))
+        };
+        assert_eq!(received(&mut state, 100), Some(ack(100)));
+        assert_eq!(received(&mut state, 101), None);
+        assert_eq!(received(&mut state, 102), None);
         assert_eq!(
-            state
-                .selected_channel()
-                .map(crate::domain::entities::Channel::id),
-            Some(channel.id())
+            state.read_states[&channel.id()].last_read_message_id,
+            Some(MessageId(102))
         );
+        assert!(state.take_due_acks().is_empty());
 
-        state.increment_mention_count(channel.id());
-
-        let count = state
-            .read_states
-            .get(&channel.id())
-            .map_or(0, |rs| rs.mention_count);
-        assert_eq!(count, 0, "Mention count should be 0 for active channel");
+        state.tick(READ_ACK_INTERVAL);
+        assert_eq!(state.take_due_acks(), vec![ack(102)]);
+        assert!(state.take_due_acks().is_empty());
+        assert_eq!(received(&mut state, 103), None);
     }
 
     #[test]
-    fn test_focus_cycling() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
-        );
+    fn test_quick_switcher_restores_focus() {
+        let mut state = create_test_state(create_test_user());
+        state.set_focus(ChatFocus::MessageInput);
 
+        state.toggle_quick_switcher();
+        state.close_quick_switcher();
         assert_eq!(state.focus(), ChatFocus::GuildsTree);
 
-        state.focus_next();
-        assert_eq!(state.focus(), ChatFocus::MessagesList);
-
-        state.focus_next();
+        state.set_restore_focus_after_overlay(true);
+        state.set_focus(ChatFocus::MessageInput);
+        state.toggle_quick_switcher();
+        state.close_quick_switcher();
         assert_eq!(state.focus(), ChatFocus::MessageInput);
 
-        state.focus_next();
-        assert_eq!(state.focus(), ChatFocus::GuildsTree);
+        state.toggle_quick_switcher();
+        state.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(!state.show_quick_switcher);
+        assert_eq!(state.focus(), ChatFocus::MessageInput);
     }
 
-    #[test]
-    fn test_focus_skip_when_guilds_hidden() {
+    #[test_case(ChatFocus::MessagesList ; "messages list")]
+    #[test_case(ChatFocus::GuildsTree ; "guilds tree")]
+    fn test_help_toggle(initial_focus: ChatFocus) {
         let mut state = ChatScreenState::new(
             create_test_user(),
             Arc::new(MarkdownRenderer::new()),
@@ -3215,19 +6853,24 @@ mod tests {
             QuickSwitcherSortMode::default(),
             vec![],
         );
-        state.toggle_guilds_tree();
-        state.set_focus(ChatFocus::MessagesList);
 
-        state.focus_next();
-        assert_eq!(state.focus(), ChatFocus::MessageInput);
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        state.set_guilds(vec![guild.clone()]);
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
 
-        state.focus_next();
-        assert_eq!(state.focus(), ChatFocus::MessagesList);
+        state.set_focus(initial_focus);
+        assert!(!state.show_help);
+
+        let result = state.handle_key(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT));
+
+        assert!(matches!(result, ChatKeyResult::ToggleHelp));
+        assert!(state.show_help);
     }
 
-    #[test]
-    #[cfg(not(windows))]
-    fn test_cross_guild_channel_selection() {
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_quick_switcher_input_vs_navigation() {
         let mut state = ChatScreenState::new(
             create_test_user(),
             Arc::new(MarkdownRenderer::new()),
@@ -3241,223 +6884,260 @@ mod tests {
             CommandRegistry::default(),
             RelationshipState::new(),
             false,
-            QuickSwitcherSortMode::default(),
+            QuickSwitcherSortMode::Mixed,
             vec![],
         );
 
-        let guild_a = Guild::new(1_u64, "Guild A");
-        let guild_b = Guild::new(2_u64, "Guild B");
-        let channel_a = Channel::new(ChannelId(10), "Channel A1", ChannelKind::Text);
-        let channel_b = Channel::new(ChannelId(20), "Channel B1", ChannelKind::Text);
-
-        state.set_guilds(vec![guild_a.clone(), guild_b.clone()]);
-        setup_permissive_guild_data(&mut state, guild_a.id());
-        setup_permissive_guild_data(&mut state, guild_b.id());
-        state.set_channels(guild_a.id(), vec![channel_a.clone()]);
-        state.set_channels(guild_b.id(), vec![channel_b.clone()]);
-
-        state.on_guild_selected(guild_a.id());
-        state.on_channel_selected(channel_a.id());
-
-        assert_eq!(state.selected_guild(), Some(guild_a.id()));
-        assert_eq!(
-            state
-                .selected_channel()
-                .map(crate::domain::entities::Channel::id),
-            Some(channel_a.id())
+        let guild = crate::domain::entities::Guild::new(1_u64, "Guild A");
+        let channel1 = crate::domain::entities::Channel::new(
+            crate::domain::entities::ChannelId(10),
+            "juice",
+            crate::domain::entities::ChannelKind::Text,
+        );
+        let channel2 = crate::domain::entities::Channel::new(
+            crate::domain::entities::ChannelId(11),
+            "jam",
+            crate::domain::entities::ChannelKind::Text,
         );
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel1.clone(), channel2.clone()]);
 
-        let result = state.on_channel_selected(channel_b.id());
+        state.toggle_quick_switcher();
+        assert!(state.show_quick_switcher);
 
-        assert!(
-            result.is_some(),
-            "Should return a result when selecting Channel B1"
-        );
-        assert_eq!(
-            state.selected_guild(),
-            Some(guild_b.id()),
-            "Should switch to Guild B"
-        );
-        assert_eq!(
-            state
-                .selected_channel()
-                .map(crate::domain::entities::Channel::id),
-            Some(channel_b.id()),
-            "Should switch to Channel B1"
-        );
-    }
+        let key_j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        state.handle_key(key_j);
 
-    #[test]
-    fn test_permission_filtering_public_channel() {
-        let user = create_test_user();
-        let mut state = create_test_state(user.clone());
-        let guild_id = GuildId(1);
-        let channel = Channel::new(ChannelId(10), "public", ChannelKind::Text).with_guild(guild_id);
+        assert_eq!(state.quick_switcher.input, "j");
+        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
 
-        // Setup @everyone role with VIEW_CHANNEL (default)
-        let everyone_role = Role {
-            id: RoleId(1),
-            name: "@everyone".to_string(),
-            color: 0,
-            hoist: false,
-            icon: None,
-            unicode_emoji: None,
-            position: 0,
-            permissions: Permissions::VIEW_CHANNEL,
-            managed: false,
-            mentionable: false,
-        };
+        let key_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        state.handle_key(key_u);
 
-        let member = Member {
-            user: Some(user.clone()),
-            nick: None,
-            avatar: None,
-            roles: vec![],
-            joined_at: String::new(),
-            premium_since: None,
-            deaf: false,
-            mute: false,
-            pending: false,
-            permissions: None,
-            communication_disabled_until: None,
-        };
+        assert_eq!(state.quick_switcher.input, "ju");
+        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
 
-        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
-        state.set_channels(guild_id, vec![channel.clone()]);
+        state.quick_switcher.input.clear();
+        state.perform_search("");
 
-        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
-    }
+        assert!(state.quick_switcher.results.len() >= 2);
+        state.quick_switcher.list_state.select(Some(0));
 
-    #[test]
-    fn test_permission_filtering_private_channel_denied() {
-        let user = create_test_user();
-        let mut state = create_test_state(user.clone());
-        let guild_id = GuildId(1);
-        let channel =
-            Channel::new(ChannelId(10), "private", ChannelKind::Text).with_guild(guild_id);
+        let key_ctrl_j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL);
+        state.handle_key(key_ctrl_j);
 
-        // Setup @everyone role with VIEW_CHANNEL DENIED
-        let everyone_role = Role {
-            id: RoleId(1),
-            name: "@everyone".to_string(),
-            color: 0,
-            hoist: false,
-            icon: None,
-            unicode_emoji: None,
-            position: 0,
-            permissions: Permissions::empty(), // No VIEW_CHANNEL
-            managed: false,
-            mentionable: false,
-        };
+        assert_eq!(state.quick_switcher.list_state.selected(), Some(1));
 
-        let member = Member {
-            user: Some(user.clone()),
-            nick: None,
-            avatar: None,
-            roles: vec![],
-            joined_at: String::new(),
-            premium_since: None,
-            deaf: false,
-            mute: false,
-            pending: false,
-            permissions: None,
-            communication_disabled_until: None,
+        let key_ctrl_k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        state.handle_key(key_ctrl_k);
+
+        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_empty_query_sections_limit_results() {
+        use crate::domain::search::RecentItem;
+
+        let recent = RecentItem::new(&SearchResult::new("11", "jam", SearchKind::Channel));
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::Mixed,
+            vec![recent],
+        );
+        let guild = Guild::new(1_u64, "Guild A");
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(
+            guild.id(),
+            vec![
+                Channel::new(ChannelId(10), "juice", ChannelKind::Text).with_guild(1_u64),
+                Channel::new(ChannelId(11), "jam", ChannelKind::Text).with_guild(1_u64),
+            ],
+        );
+        state.set_dm_users(vec![DirectMessageChannel {
+            channel_id: "50".to_string(),
+            recipient_id: "456".to_string(),
+            recipient_username: "friend".to_string(),
+            recipient_discriminator: "0".to_string(),
+            recipient_global_name: None,
+            last_message_id: None,
+            has_unread: false,
+            mention_count: 0,
+            kind: DmKind::Direct,
+        }]);
+
+        let kinds = |state: &ChatScreenState| -> Vec<(SearchKind, String)> {
+            state
+                .quick_switcher
+                .results
+                .iter()
+                .map(|r| (r.kind.clone(), r.id.clone()))
+                .collect()
         };
 
-        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
-        state.set_channels(guild_id, vec![channel.clone()]);
+        state.perform_search("");
+        let all = kinds(&state);
+        assert!(all.contains(&(SearchKind::Guild, "1".to_string())));
+        assert!(all.contains(&(SearchKind::Channel, "10".to_string())));
 
-        assert!(state.guilds_tree_data.get_channel(channel.id()).is_none());
+        state.set_quick_switcher_sections(vec![
+            QuickSwitcherSection::Recents,
+            QuickSwitcherSection::Dms,
+        ]);
+        state.perform_search("");
+        assert_eq!(
+            kinds(&state),
+            vec![
+                (SearchKind::Channel, "11".to_string()),
+                (SearchKind::DM, "50".to_string()),
+            ]
+        );
+
+        state.add_recent_item(RecentItem::new(&SearchResult::new(
+            "50",
+            "friend",
+            SearchKind::DM,
+        )));
+        state.set_quick_switcher_sections(vec![
+            QuickSwitcherSection::Dms,
+            QuickSwitcherSection::Recents,
+        ]);
+        state.perform_search("");
+        assert_eq!(
+            kinds(&state),
+            vec![
+                (SearchKind::DM, "50".to_string()),
+                (SearchKind::Channel, "11".to_string()),
+            ],
+            "a recent DM is not listed twice"
+        );
     }
 
-    #[test]
-    fn test_permission_filtering_role_access() {
-        let user = create_test_user();
-        let mut state = create_test_state(user.clone());
-        let guild_id = GuildId(1);
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_search_excludes_categories() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
+
+        let guild = Guild::new(1_u64, "Guild A");
+        let category = Channel::new(ChannelId(100), "Category 1", ChannelKind::Category);
         let channel =
-            Channel::new(ChannelId(10), "restricted", ChannelKind::Text).with_guild(guild_id);
+            Channel::new(ChannelId(101), "general", ChannelKind::Text).with_parent(100_u64);
 
-        // @everyone denied
-        let everyone_role = Role {
-            id: RoleId(1),
-            name: "@everyone".to_string(),
-            permissions: Permissions::empty(),
-            ..create_dummy_role(1)
-        };
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![category.clone(), channel.clone()]);
 
-        // Member role allowed
-        let member_role = Role {
-            id: RoleId(2),
-            name: "Member".to_string(),
-            permissions: Permissions::VIEW_CHANNEL,
-            ..create_dummy_role(2)
-        };
+        state.toggle_quick_switcher();
 
-        let member = Member {
-            user: Some(user.clone()),
-            roles: vec![RoleId(2)],
-            ..create_dummy_member()
-        };
+        state.perform_search("Category");
 
-        state.set_guild_data(guild_id, vec![everyone_role, member_role], vec![member]);
-        state.set_channels(guild_id, vec![channel.clone()]);
+        assert!(
+            state.quick_switcher.results.is_empty(),
+            "Categories should be excluded from search results. Found: {:?}",
+            state.quick_switcher.results
+        );
 
-        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
+        state.perform_search("general");
+        assert_eq!(state.quick_switcher.results.len(), 1);
+        assert_eq!(state.quick_switcher.results[0].id, "101");
     }
 
     #[test]
-    fn test_permission_filtering_strict_category_pruning() {
-        let user = create_test_user();
-        let mut state = create_test_state(user.clone());
-        let guild_id = GuildId(1);
-
-        let category = Channel::new(ChannelId(100), "Hidden Category", ChannelKind::Category)
-            .with_guild(guild_id);
-
-        let child = Channel::new(ChannelId(101), "Visible Child", ChannelKind::Text)
-            .with_guild(guild_id)
-            .with_parent(ChannelId(100));
+    fn test_esc_navigation_void_state() {
+        let mut state = ChatScreenState::new(
+            create_test_user(),
+            Arc::new(MarkdownRenderer::new()),
+            UserCache::new(),
+            false,
+            true,
+            true,
+            "%H:%M".to_string(),
+            Theme::new("Orange", None, false),
+            true,
+            CommandRegistry::default(),
+            RelationshipState::new(),
+            false,
+            QuickSwitcherSortMode::default(),
+            vec![],
+        );
 
-        // @everyone denied VIEW_CHANNEL base
-        let everyone_role = Role {
-            id: RoleId(1),
-            permissions: Permissions::empty(),
-            ..create_dummy_role(1)
-        };
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
 
-        // Child has overwrite allowing VIEW_CHANNEL for @everyone
-        // But Category does NOT.
-        let child =
-            child.with_permission_overwrites(vec![crate::domain::entities::PermissionOverwrite {
-                id: "1".to_string(), // @everyone
-                overwrite_type: crate::domain::entities::OverwriteType::Role,
-                allow: Permissions::VIEW_CHANNEL.bits().to_string(),
-                deny: "0".to_string(),
-            }]);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
 
-        let member = create_dummy_member_with_user(user);
+        state.on_channel_selected(channel.id());
+        assert!(state.selected_channel().is_some());
 
-        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
-        state.set_channels(guild_id, vec![category.clone(), child.clone()]);
+        let key_esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        state.handle_key(key_esc);
 
-        // Category is hidden (base permissions empty, no overwrite).
-        // Child is technically visible (overwrite allows).
-        // BUT strict pruning means if category is hidden, child is hidden.
+        let recents = &state.recents;
+        let null_recent = recents
+            .iter()
+            .find(|r| r.name.contains("Text Channel") && r.id == "0");
 
         assert!(
-            state.guilds_tree_data.get_channel(category.id()).is_none(),
-            "Category should be hidden"
-        );
-        assert!(
-            state.guilds_tree_data.get_channel(child.id()).is_none(),
-            "Child should be hidden because parent is hidden"
+            null_recent.is_none(),
+            "Should not add null channel to recents. Found: {:?}",
+            null_recent
         );
+
+        if state.selected_channel().is_none() {
+            assert_eq!(state.focus(), ChatFocus::GuildsTree);
+        }
     }
 
-    fn create_test_state(user: User) -> ChatScreenState {
-        ChatScreenState::new(
-            user,
+    #[test]
+    fn test_initial_recents_filtering() {
+        use crate::domain::search::{RecentItem, SearchKind};
+
+        let invalid_item = RecentItem {
+            id: "0".to_string(),
+            name: "Text Channels".to_string(),
+            kind: SearchKind::Channel,
+            guild_id: None,
+            timestamp: 0,
+        };
+
+        let valid_item = RecentItem {
+            id: "123".to_string(),
+            name: "General".to_string(),
+            kind: SearchKind::Channel,
+            guild_id: None,
+            timestamp: 0,
+        };
+
+        let state = ChatScreenState::new(
+            create_test_user(),
             Arc::new(MarkdownRenderer::new()),
             UserCache::new(),
             false,
@@ -3470,50 +7150,83 @@ mod tests {
             RelationshipState::new(),
             false,
             QuickSwitcherSortMode::default(),
-            vec![],
-        )
+            vec![invalid_item, valid_item],
+        );
+
+        assert_eq!(
+            state.recents.len(),
+            1,
+            "Recents should be filtered on initialization"
+        );
+        assert_eq!(state.recents[0].id, "123");
     }
 
-    fn create_dummy_role(id: u64) -> Role {
-        Role {
-            id: RoleId(id),
-            name: format!("Role {id}"),
-            color: 0,
-            hoist: false,
-            icon: None,
-            unicode_emoji: None,
-            position: 0,
-            permissions: Permissions::empty(),
-            managed: false,
-            mentionable: false,
+    fn recent(id: u64) -> crate::domain::search::RecentItem {
+        crate::domain::search::RecentItem {
+            id: id.to_string(),
+            name: format!("channel-{id}"),
+            kind: crate::domain::search::SearchKind::Channel,
+            guild_id: None,
+            timestamp: 0,
         }
     }
 
-    fn create_dummy_member() -> Member {
-        Member {
-            user: None,
-            nick: None,
-            avatar: None,
-            roles: vec![],
-            joined_at: String::new(),
-            premium_since: None,
-            deaf: false,
-            mute: false,
-            pending: false,
-            permissions: None,
-            communication_disabled_until: None,
+    #[test]
+    fn test_recents_capped_and_cleared() {
+        let mut state = create_test_state(create_test_user());
+        state.set_max_recents(3);
+        for id in 1..=5 {
+            state.add_recent_item(recent(id));
         }
+        let ids: Vec<&str> = state.recents.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["5", "4", "3"]);
+
+        state.set_max_recents(2);
+        assert_eq!(state.recents.len(), 2);
+
+        state.toggle_quick_switcher();
+        assert!(!state.quick_switcher.results.is_empty());
+        assert_eq!(state.clear_recents(), ChatKeyResult::RecentsCleared);
+        assert!(state.recents.is_empty());
+        assert!(state.quick_switcher.results.is_empty());
     }
 
-    fn create_dummy_member_with_user(user: User) -> Member {
-        let mut m = create_dummy_member();
-        m.user = Some(user);
-        m
+    #[test]
+    fn test_pinned_channel_listed_first_after_reopening() {
+        let mut state = create_test_state(create_test_user());
+        for id in 1..=3 {
+            state.add_recent_item(recent(id));
+        }
+        state.toggle_quick_switcher();
+        assert_eq!(state.quick_switcher.results[0].id, "3");
+        state.quick_switcher.list_state.select(Some(2));
+
+        let result = state.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT));
+        assert_eq!(result, ChatKeyResult::PinsChanged);
+        assert_eq!(state.pinned.len(), 1);
+
+        state.toggle_quick_switcher();
+        state.add_recent_item(recent(4));
+        state.toggle_quick_switcher();
+        let first = &state.quick_switcher.results[0];
+        assert_eq!(first.id, "1");
+        assert!(state.quick_switcher.is_pinned(first));
+        assert_eq!(state.quick_switcher.results[1].id, "4");
     }
 
     #[test]
-    #[cfg(not(windows))]
-    fn test_chat_screen_state_creation_initial_focus() {
+    fn test_disabled_recents_are_not_recorded() {
+        let mut state = create_test_state(create_test_user());
+        state.add_recent_item(recent(1));
+        state.set_record_recents(false);
+        state.add_recent_item(recent(2));
+
+        assert_eq!(state.recents.len(), 1);
+        assert_eq!(state.recents[0].id, "1");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_perform_search_finds_general() {
         let mut state = ChatScreenState::new(
             create_test_user(),
             Arc::new(MarkdownRenderer::new()),
@@ -3531,27 +7244,29 @@ mod tests {
             vec![],
         );
 
-        assert_eq!(state.focus(), ChatFocus::GuildsTree);
-
         let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        let channel = Channel::new(ChannelId(101), "general", ChannelKind::Text).with_guild(1_u64);
+
         state.set_guilds(vec![guild.clone()]);
         setup_permissive_guild_data(&mut state, guild.id());
         state.set_channels(guild.id(), vec![channel.clone()]);
 
-        state.on_channel_selected(channel.id());
+        assert!(state.guilds_tree_data.get_channel(ChannelId(101)).is_some());
+
+        state.toggle_quick_switcher();
+        state.perform_search("general");
 
         assert_eq!(
-            state.focus(),
-            ChatFocus::MessagesList,
-            "Focus should switch to MessagesList on channel selection"
+            state.quick_switcher.results.len(),
+            1,
+            "Search for 'general' should return 1 result. Actual: {:?}",
+            state.quick_switcher.results
         );
+        assert_eq!(state.quick_switcher.results[0].id, "101");
     }
 
     #[test]
-    fn test_escape_from_empty_message_list_focuses_tree() {
-        use crossterm::event::KeyModifiers;
-
+    fn test_channels_hidden_if_member_missing() {
         let mut state = ChatScreenState::new(
             create_test_user(),
             Arc::new(MarkdownRenderer::new()),
@@ -3568,27 +7283,32 @@ mod tests {
             QuickSwitcherSortMode::default(),
             vec![],
         );
+        let guild_id = GuildId(1);
+        let channel =
+            Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(guild_id);
 
-        state.focus_messages_list();
-        assert_eq!(state.focus(), ChatFocus::MessagesList);
-
-        state.message_pane_state.clear_selection();
-
-        let esc_event = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            permissions: Permissions::VIEW_CHANNEL,
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            managed: false,
+            mentionable: false,
+        };
 
-        let result = state.handle_key(esc_event);
+        state.set_guild_data(guild_id, vec![everyone_role], vec![]);
+        state.set_channels(guild_id, vec![channel.clone()]);
 
-        assert_eq!(result, ChatKeyResult::Consumed);
-        assert_eq!(
-            state.focus(),
-            ChatFocus::GuildsTree,
-            "Should return focus to Guilds Tree on Cancel from empty selection"
-        );
+        assert!(state.guilds_tree_data.get_channel(channel.id()).is_none());
     }
 
     #[test]
-    fn test_message_input_shows_focus_messages_command() {
-        let state = ChatScreenState::new(
+    fn test_channels_visible_if_member_present() {
+        let mut state = ChatScreenState::new(
             create_test_user(),
             Arc::new(MarkdownRenderer::new()),
             UserCache::new(),
@@ -3604,551 +7324,951 @@ mod tests {
             QuickSwitcherSortMode::default(),
             vec![],
         );
+        let guild_id = GuildId(1);
+        let channel =
+            Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(guild_id);
 
-        let commands = state.get_message_input_commands(&state.registry);
+        let everyone_role = Role {
+            id: RoleId(1),
+            name: "@everyone".to_string(),
+            permissions: Permissions::VIEW_CHANNEL,
+            color: 0,
+            hoist: false,
+            icon: None,
+            unicode_emoji: None,
+            position: 0,
+            managed: false,
+            mentionable: false,
+        };
 
-        assert!(
-            commands.iter().any(|k| k.action == Action::FocusMessages),
-            "Message input commands should include FocusMessages (Ctrl+T)"
-        );
+        let member = Member {
+            user: Some(create_test_user()),
+            roles: vec![],
+            nick: None,
+            avatar: None,
+            joined_at: String::new(),
+            premium_since: None,
+            deaf: false,
+            mute: false,
+            pending: false,
+            permissions: None,
+            communication_disabled_until: None,
+        };
+
+        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
+        state.set_channels(guild_id, vec![channel.clone()]);
+
+        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
     }
 
     #[test]
-    fn test_ctrl_t_focuses_messages_from_input() {
-        use crossterm::event::KeyModifiers;
-
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    fn test_logout_with_drafts_requires_confirmation() {
+        let mut state = create_test_state(create_test_user());
+        state.drafts.insert(
+            ChannelId(5),
+            MessageDraft {
+                content: "unsent".to_string(),
+                attachments: vec![],
+            },
         );
+        state.message_input_state.set_content("also unsent");
+        assert_eq!(state.draft_channel_count(), 2);
+
+        let logout = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(state.handle_key(logout), ChatKeyResult::Consumed);
+        assert_eq!(state.focus, ChatFocus::ConfirmationModal);
 
+        let result = state.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(result, ChatKeyResult::Logout);
+        assert_eq!(state.focus, ChatFocus::GuildsTree);
+    }
+
+    #[test]
+    fn test_attachment_only_draft_follows_its_channel() {
+        let mut state = create_test_state(create_test_user());
         let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        let first = Channel::new(ChannelId(10), "first", ChannelKind::Text).with_guild(1_u64);
+        let second = Channel::new(ChannelId(11), "second", ChannelKind::Text).with_guild(1_u64);
         state.set_guilds(vec![guild.clone()]);
-        state.set_channels(guild.id(), vec![channel.clone()]);
-        state.on_channel_selected(channel.id());
-        state.focus_message_input();
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![first.clone(), second.clone()]);
 
-        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
-        let result = state.handle_key(ctrl_t);
+        state.on_channel_selected(first.id());
+        let attachment = OutgoingAttachment::new("/tmp/cat.png".into());
+        state.message_input_state.add_attachment(attachment.clone());
+        assert_eq!(state.draft_channel_count(), 1);
 
-        assert_eq!(result, ChatKeyResult::Consumed, "Ctrl+t should be consumed");
+        state.on_channel_selected(second.id());
+        assert!(state.message_input_state.attachments().is_empty());
+        assert_eq!(state.draft_channel_count(), 1);
+
+        state.on_channel_selected(first.id());
+        assert_eq!(state.message_input_state.attachments(), [attachment]);
+        assert_eq!(state.draft_channel_count(), 1);
+    }
+
+    #[test]
+    fn test_masked_link_requires_confirmation() {
+        let mut state = create_test_state(create_test_user());
+        let masked = Link {
+            text: "free nitro".to_string(),
+            url: "https://evil.example".to_string(),
+        };
+
+        assert_eq!(state.open_link(masked.clone()), ChatKeyResult::Consumed);
+        assert_eq!(state.focus, ChatFocus::ConfirmationModal);
         assert_eq!(
-            state.focus(),
-            ChatFocus::MessagesList,
-            "Ctrl+t should focus the messages list"
+            state.handle_key(KeyEvent::from(KeyCode::Enter)),
+            ChatKeyResult::OpenLink("https://evil.example".to_string())
+        );
+        assert_eq!(state.focus, ChatFocus::MessagesList);
+
+        assert_eq!(
+            state.open_link(Link::plain("https://example.com")),
+            ChatKeyResult::OpenLink("https://example.com".to_string())
+        );
+
+        state.set_confirm_masked_links(false);
+        assert_eq!(
+            state.open_link(masked),
+            ChatKeyResult::OpenLink("https://evil.example".to_string())
         );
     }
 
-    #[test_case('a', KeyModifiers::NONE ; "lowercase char")]
-    #[test_case('Z', KeyModifiers::SHIFT ; "uppercase char")]
-    #[test_case('1', KeyModifiers::NONE ; "digit")]
-    #[test_case('i', KeyModifiers::NONE ; "i key")]
-    #[test_case('t', KeyModifiers::NONE ; "t key")]
-    #[test_case('?', KeyModifiers::SHIFT ; "question mark")]
-    fn test_input_character_handling(c: char, modifiers: KeyModifiers) {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    #[test]
+    fn test_logout_confirmation_cancel_keeps_session() {
+        let mut state = create_test_state(create_test_user());
+        state.drafts.insert(
+            ChannelId(5),
+            MessageDraft {
+                content: "unsent".to_string(),
+                attachments: vec![],
+            },
         );
 
-        let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
-        state.set_guilds(vec![guild.clone()]);
-        state.set_channels(guild.id(), vec![channel.clone()]);
-        state.on_channel_selected(channel.id());
-        state.focus_message_input();
-        state.message_input_state.clear();
+        let secure_logout = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT);
+        assert_eq!(state.handle_key(secure_logout), ChatKeyResult::Consumed);
+        assert_eq!(
+            state.handle_key(KeyEvent::from(KeyCode::Esc)),
+            ChatKeyResult::Consumed
+        );
+        assert_eq!(state.focus, ChatFocus::GuildsTree);
+        assert!(state.pending_confirmation.is_none());
+    }
+
+    #[test_case(true, false ; "no drafts")]
+    #[test_case(false, true ; "confirmation disabled")]
+    fn test_logout_proceeds_directly(confirm: bool, with_draft: bool) {
+        let mut state = create_test_state(create_test_user());
+        state.set_confirm_logout_with_drafts(confirm);
+        if with_draft {
+            state.drafts.insert(
+                ChannelId(5),
+                MessageDraft {
+                    content: "unsent".to_string(),
+                    attachments: vec![],
+                },
+            );
+        }
 
-        let result = state.handle_key(KeyEvent::new(KeyCode::Char(c), modifiers));
+        let logout = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(state.handle_key(logout), ChatKeyResult::Logout);
+        assert_eq!(state.focus, ChatFocus::GuildsTree);
+    }
 
-        assert!(
-            matches!(result, ChatKeyResult::StartTyping | ChatKeyResult::Ignored),
-            "Character '{}' should be typed in input mode, not trigger actions",
-            c
+    #[test]
+    fn test_vim_sequence_dispatches_to_focused_pane() {
+        use crate::infrastructure::config::KeymapPreset;
+
+        let mut state = create_test_state(create_test_user());
+        state.registry = CommandRegistry::with_preset(KeymapPreset::Vim);
+        state.set_guilds(vec![Guild::new(1_u64, "A"), Guild::new(2_u64, "B")]);
+
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let last = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        state.handle_key(last);
+        assert_eq!(
+            state.guilds_tree_state.selected(),
+            Some(&TreeNodeId::Guild(GuildId(2)))
         );
+
+        assert_eq!(state.handle_key(g), ChatKeyResult::Consumed);
         assert_eq!(
-            state.focus(),
-            ChatFocus::MessageInput,
-            "Focus should remain on MessageInput when typing '{}'",
-            c
+            state.guilds_tree_state.selected(),
+            Some(&TreeNodeId::Guild(GuildId(2)))
+        );
+        state.handle_key(g);
+        assert_eq!(
+            state.guilds_tree_state.selected(),
+            Some(&TreeNodeId::DirectMessages)
         );
+        assert_eq!(state.registry.find_action(g), None);
+    }
+
+    fn lazy_guilds_fixture() -> ChatScreenState {
+        let mut state = create_test_state(create_test_user());
+        state.set_eager_guild_count(1);
+        state.set_guilds(vec![Guild::new(1_u64, "Top"), Guild::new(2_u64, "Other")]);
+        for guild_id in [GuildId(1), GuildId(2)] {
+            setup_permissive_guild_data(&mut state, guild_id);
+            let channel = Channel::new(
+                ChannelId(guild_id.as_u64() * 10),
+                "general",
+                ChannelKind::Text,
+            )
+            .with_guild(guild_id.as_u64());
+            state.set_channels(guild_id, vec![channel]);
+        }
+        state
     }
 
-    #[test_case(KeyCode::Char('t'), KeyModifiers::CONTROL, ChatFocus::MessagesList ; "ctrl+t focus messages")]
-    #[test_case(KeyCode::Char('g'), KeyModifiers::CONTROL, ChatFocus::GuildsTree ; "ctrl+g focus guilds")]
-    fn test_global_focus_shortcuts_preserve_input(
-        key: KeyCode,
-        modifiers: KeyModifiers,
-        target: ChatFocus,
-    ) {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    #[test]
+    fn test_lazy_guild_channels_computed_on_selection() {
+        let mut state = lazy_guilds_fixture();
+        assert!(state.guilds_tree_data.channels(GuildId(1)).is_some());
+        assert!(state.guilds_tree_data.channels(GuildId(2)).is_none());
+
+        assert_eq!(state.on_guild_selected(GuildId(2)), None);
+        assert!(state.guilds_tree_data.channels(GuildId(2)).is_some());
+    }
+
+    #[test]
+    fn test_lazy_guild_channels_computed_on_expansion() {
+        let mut state = lazy_guilds_fixture();
+        state
+            .guilds_tree_state
+            .select(TreeNodeId::Guild(GuildId(2)));
+
+        let expand = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE);
+        assert_eq!(state.handle_key(expand), ChatKeyResult::Consumed);
+        assert!(state.guilds_tree_data.channels(GuildId(2)).is_some());
+    }
+
+    #[test]
+    fn test_lazy_guild_channels_computed_for_channel_jump() {
+        let mut state = lazy_guilds_fixture();
+
+        assert_eq!(
+            state.on_channel_selected(ChannelId(20)),
+            Some(ChatKeyResult::LoadChannelMessages {
+                channel_id: ChannelId(20),
+                guild_id: Some(GuildId(2)),
+            })
         );
+    }
+
+    #[test]
+    fn test_lazy_guild_channels_searchable_without_building_tree() {
+        let state = lazy_guilds_fixture();
 
-        let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
+        let ids: Vec<ChannelId> = state
+            .collect_searchable_channels(SearchPrefix::None)
+            .iter()
+            .map(|(_, channel, _)| channel.id())
+            .collect();
+        assert_eq!(ids, vec![ChannelId(10), ChannelId(20)]);
+        assert!(state.guilds_tree_data.channels(GuildId(2)).is_none());
+    }
+
+    fn thread_list_fixture() -> ChatScreenState {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text);
         state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
         state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_guild_selected(guild.id());
         state.on_channel_selected(channel.id());
+        state
+    }
 
-        state.focus_message_input();
-        let text = "Important draft";
-        state.message_input_state.set_content(text);
-
-        let result = state.handle_key(KeyEvent::new(key, modifiers));
+    #[test]
+    fn test_thread_list_loads_for_text_channel() {
+        let mut state = thread_list_fixture();
+        let t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE);
 
-        assert_eq!(result, ChatKeyResult::Consumed);
-        assert_eq!(state.focus(), target);
         assert_eq!(
-            state.message_input_state.value(),
-            text,
-            "Input buffer should be preserved"
+            state.handle_key(t),
+            ChatKeyResult::LoadForumThreads {
+                channel_id: ChannelId(10),
+                guild_id: Some(GuildId(1)),
+                offset: 0,
+            }
         );
+        assert!(matches!(
+            state.message_pane_state.view_mode,
+            ViewMode::Forum(_)
+        ));
+
+        assert_eq!(state.handle_key(t), ChatKeyResult::Consumed);
+        assert!(matches!(
+            state.message_pane_state.view_mode,
+            ViewMode::Messages
+        ));
     }
 
-    #[test_case(ChatFocus::MessagesList, KeyCode::Char('i'), KeyModifiers::NONE ; "i from messages list")]
-    #[test_case(ChatFocus::GuildsTree, KeyCode::Char('i'), KeyModifiers::NONE ; "i from guilds tree")]
-    #[test_case(ChatFocus::MessagesList, KeyCode::Char('i'), KeyModifiers::CONTROL ; "ctrl+i from messages list")]
-    fn test_focus_input_shortcuts(initial_focus: ChatFocus, key: KeyCode, modifiers: KeyModifiers) {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    #[test]
+    fn test_selecting_listed_thread_opens_it() {
+        let mut state = thread_list_fixture();
+        state.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+
+        let mut thread =
+            crate::domain::entities::ForumThread::new(ChannelId(50), "design notes", "123");
+        thread.guild_id = Some(GuildId(1));
+        thread.parent_id = Some(ChannelId(10));
+        state.set_forum_threads(vec![thread], 0);
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            state.handle_key(enter),
+            ChatKeyResult::LoadChannelMessages {
+                channel_id: ChannelId(50),
+                guild_id: Some(GuildId(1)),
+            }
         );
+        let opened = state.selected_channel().expect("thread should be selected");
+        assert_eq!(opened.id(), ChannelId(50));
+        assert_eq!(opened.parent_id(), Some(ChannelId(10)));
+        assert!(matches!(
+            state.message_pane_state.view_mode,
+            ViewMode::Messages
+        ));
+    }
 
-        let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
-        state.set_guilds(vec![guild.clone()]);
-        state.set_channels(guild.id(), vec![channel.clone()]);
-        state.on_channel_selected(channel.id());
+    #[test]
+    fn test_accent_override_follows_selected_guild() {
+        use ratatui::style::Color;
 
-        state.set_focus(initial_focus);
+        let mut state = create_test_state(create_test_user());
+        let guild_a = Guild::new(1_u64, "Guild A");
+        let guild_b = Guild::new(2_u64, "Guild B");
+        let channel_a1 = Channel::new(ChannelId(10), "Channel A1", ChannelKind::Text);
+        let channel_a2 = Channel::new(ChannelId(11), "Channel A2", ChannelKind::Text);
 
-        let result = state.handle_key(KeyEvent::new(key, modifiers));
+        state.set_guilds(vec![guild_a.clone(), guild_b.clone()]);
+        setup_permissive_guild_data(&mut state, guild_a.id());
+        state.set_channels(guild_a.id(), vec![channel_a1.clone(), channel_a2.clone()]);
+        state.set_accent_overrides(std::collections::HashMap::from([
+            (guild_a.id().as_u64(), Theme::new("Blue", None, false)),
+            (channel_a2.id().as_u64(), Theme::new("Green", None, false)),
+        ]));
+        let base_accent = state.theme.accent;
 
-        assert_eq!(result, ChatKeyResult::Consumed);
-        assert_eq!(state.focus(), ChatFocus::MessageInput);
-    }
+        state.on_guild_selected(guild_a.id());
+        assert_eq!(state.theme.accent, Color::Blue);
+        assert_eq!(
+            state.styles.message_input.border_style_focused.fg,
+            Some(Color::Blue)
+        );
 
-    #[test_case(ChatFocus::MessagesList ; "messages list")]
-    #[test_case(ChatFocus::GuildsTree ; "guilds tree")]
-    fn test_help_toggle(initial_focus: ChatFocus) {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+        state.on_channel_selected(channel_a1.id());
+        assert_eq!(state.theme.accent, Color::Blue);
+
+        state.on_channel_selected(channel_a2.id());
+        assert_eq!(state.theme.accent, Color::Green);
+
+        state.on_guild_selected(guild_b.id());
+        assert_eq!(state.theme.accent, base_accent);
+        assert_eq!(
+            state.styles.message_input.border_style_focused.fg,
+            Some(base_accent)
         );
+    }
 
-        let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
-        state.set_guilds(vec![guild.clone()]);
-        state.set_channels(guild.id(), vec![channel.clone()]);
-        state.on_channel_selected(channel.id());
+    #[test]
+    fn test_scroll_to_message_id() {
+        let mut state = create_test_state(create_test_user());
+        state
+            .message_pane_data
+            .set_channel(ChannelId(1), "general".to_string());
+        let author = MessageAuthor {
+            id: "456".to_string(),
+            username: "other".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        let page = |ids: std::ops::Range<u64>| -> Vec<Message> {
+            ids.map(|id| {
+                Message::new(
+                    MessageId(id),
+                    ChannelId(1),
+                    author.clone(),
+                    format!("message {id}"),
+                    chrono::Local::now(),
+                    MessageKind::Default,
+                )
+            })
+            .collect()
+        };
+        state.message_pane_data.set_messages(page(10..13));
 
-        state.set_focus(initial_focus);
-        assert!(!state.show_help);
+        assert!(state.scroll_to_message_id(MessageId(11)).is_none());
+        assert_eq!(state.message_pane_state.selected_index(), Some(1));
 
-        let result = state.handle_key(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT));
+        assert!(matches!(
+            state.scroll_to_message_id(MessageId(5)),
+            Some(ChatKeyResult::LoadHistory {
+                channel_id: ChannelId(1),
+                before_message_id: MessageId(10),
+            })
+        ));
+        assert_eq!(state.message_pane_state.selected_index(), Some(1));
+
+        let results = state.prepend_messages(page(7..10));
+        assert!(matches!(
+            results.as_slice(),
+            [ChatKeyResult::LoadHistory {
+                channel_id: ChannelId(1),
+                before_message_id: MessageId(7),
+            }]
+        ));
 
-        assert!(matches!(result, ChatKeyResult::ToggleHelp));
-        assert!(state.show_help);
+        assert!(state.prepend_messages(page(4..7)).is_empty());
+        assert_eq!(state.message_pane_state.selected_index(), Some(1));
+
+        assert!(state.scroll_to_message_id(MessageId(2)).is_some());
+        let results = state.prepend_messages(Vec::new());
+        assert!(matches!(
+            results.as_slice(),
+            [ChatKeyResult::ShowNotification(_)]
+        ));
+        assert!(state.prepend_messages(Vec::new()).is_empty());
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_quick_switcher_input_vs_navigation() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::Mixed,
-            vec![],
+    #[test]
+    fn test_parse_nick_command() {
+        assert_eq!(
+            parse_nick_command("/nick  Ferris "),
+            Some(Ok(Some("Ferris".to_string())))
         );
+        assert_eq!(parse_nick_command("/nick"), Some(Ok(None)));
+        assert_eq!(parse_nick_command("/nickname"), None);
+        assert_eq!(parse_nick_command("hello /nick x"), None);
+        assert!(matches!(
+            parse_nick_command(&format!("/nick {}", "x".repeat(33))),
+            Some(Err(_))
+        ));
+    }
 
-        let guild = crate::domain::entities::Guild::new(1_u64, "Guild A");
-        let channel1 = crate::domain::entities::Channel::new(
-            crate::domain::entities::ChannelId(10),
-            "juice",
-            crate::domain::entities::ChannelKind::Text,
+    #[test]
+    fn test_parse_group_command() {
+        assert_eq!(
+            parse_group_command("/group <@1> <@!2> 3 <@1>"),
+            Some(GroupCommand::Create(Ok(vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string()
+            ])))
         );
-        let channel2 = crate::domain::entities::Channel::new(
-            crate::domain::entities::ChannelId(11),
-            "jam",
-            crate::domain::entities::ChannelKind::Text,
+        assert_eq!(
+            parse_group_command("/add <@4>"),
+            Some(GroupCommand::Add(Ok(vec!["4".to_string()])))
         );
-        state.set_guilds(vec![guild.clone()]);
-        setup_permissive_guild_data(&mut state, guild.id());
-        state.set_channels(guild.id(), vec![channel1.clone(), channel2.clone()]);
-
-        state.toggle_quick_switcher();
-        assert!(state.show_quick_switcher);
+        assert_eq!(parse_group_command("/groups"), None);
+        assert_eq!(parse_group_command("/address"), None);
+        assert!(matches!(
+            parse_group_command("/group"),
+            Some(GroupCommand::Create(Err(_)))
+        ));
+        assert!(matches!(
+            parse_group_command("/add @someone"),
+            Some(GroupCommand::Add(Err(_)))
+        ));
+    }
 
-        let key_j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
-        state.handle_key(key_j);
+    fn group_dm_fixture(state: &mut ChatScreenState) {
+        let recipient = |id: &str, username: &str| DmRecipient {
+            id: id.to_string(),
+            username: username.to_string(),
+            discriminator: "0".to_string(),
+            global_name: None,
+        };
+        let dm = |channel_id: &str, recipient_id: &str, kind: DmKind| DirectMessageChannel {
+            channel_id: channel_id.to_string(),
+            recipient_id: recipient_id.to_string(),
+            recipient_username: "friend".to_string(),
+            recipient_discriminator: "0".to_string(),
+            recipient_global_name: None,
+            last_message_id: None,
+            has_unread: false,
+            mention_count: 0,
+            kind,
+        };
+        state.set_dm_users(vec![
+            dm("50", "456", DmKind::Direct),
+            dm(
+                "60",
+                "457",
+                DmKind::Group {
+                    name: None,
+                    recipients: vec![recipient("457", "pal"), recipient("458", "mate")],
+                },
+            ),
+        ]);
+        state
+            .user_cache
+            .insert(CachedUser::new("459", "known", "0", None, None, false));
+        state
+            .user_cache
+            .insert(CachedUser::new("460", "robot", "0", None, None, true));
+    }
 
-        assert_eq!(state.quick_switcher.input, "j");
-        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
+    fn picker_ids(state: &mut ChatScreenState, input: &str) -> Vec<String> {
+        state.focus_message_input();
+        state.autocomplete_service.reset();
+        state.message_input_state.set_content(input);
+        state.handle_key(KeyEvent::new(KeyCode::Char('@'), KeyModifiers::NONE));
+        state
+            .autocomplete_service
+            .state()
+            .results
+            .iter()
+            .filter_map(|candidate| match candidate {
+                MentionCandidate::User(user) => Some(user.id().to_string()),
+                _ => None,
+            })
+            .collect()
+    }
 
-        let key_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
-        state.handle_key(key_u);
+    #[test]
+    fn test_group_command_picks_from_dm_recipients_and_known_users() {
+        let mut state = create_test_state(create_test_user());
+        group_dm_fixture(&mut state);
 
-        assert_eq!(state.quick_switcher.input, "ju");
-        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
+        assert_eq!(
+            picker_ids(&mut state, "/group "),
+            ["456", "457", "458", "459"]
+        );
+        assert_eq!(picker_ids(&mut state, "hello "), ["123"]);
 
-        state.quick_switcher.input.clear();
-        state.perform_search("");
+        state.selected_channel = Some(Channel::new(ChannelId(60), "group", ChannelKind::GroupDm));
+        assert_eq!(
+            picker_ids(&mut state, "/add "),
+            ["456", "459"],
+            "members of the open group are left out"
+        );
+    }
 
-        assert!(state.quick_switcher.results.len() >= 2);
-        state.quick_switcher.list_state.select(Some(0));
+    #[test]
+    fn test_add_command_outside_group_dm_is_sent_as_text() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        group_dm_fixture(&mut state);
+        state.focus_message_input();
 
-        let key_ctrl_j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL);
-        state.handle_key(key_ctrl_j);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        state.message_input_state.set_content("/add <@4>");
+        assert!(matches!(
+            state.handle_key(enter),
+            ChatKeyResult::SendMessage { content, .. } if content == "/add <@4>"
+        ));
+        state.message_input_state.set_content("/add me to the list");
+        assert!(matches!(
+            state.handle_key(enter),
+            ChatKeyResult::SendMessage { content, .. } if content == "/add me to the list"
+        ));
 
-        assert_eq!(state.quick_switcher.list_state.selected(), Some(1));
+        state.selected_channel = Some(Channel::new(ChannelId(60), "group", ChannelKind::GroupDm));
+        state.message_input_state.set_content("/add <@4>");
+        assert_eq!(
+            state.handle_key(enter),
+            ChatKeyResult::AddGroupRecipients {
+                channel_id: ChannelId(60),
+                user_ids: vec!["4".to_string()],
+            }
+        );
+    }
 
-        let key_ctrl_k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
-        state.handle_key(key_ctrl_k);
+    #[test]
+    fn test_group_command_with_unknown_users_shows_usage() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        state.focus_message_input();
 
-        assert_eq!(state.quick_switcher.list_state.selected(), Some(0));
+        state.message_input_state.set_content("/group chat tonight");
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            ChatKeyResult::ShowNotification(
+                "Unknown user: chat. Usage: /group @user [@user…]".to_string()
+            )
+        );
+        assert_eq!(state.message_input_state.value(), "/group chat tonight");
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_search_excludes_categories() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
+    #[test]
+    fn test_voice_state_moves_user_between_channels() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild");
+        let lounge = Channel::new(ChannelId(20), "Lounge", ChannelKind::Voice);
+        let gaming = Channel::new(ChannelId(21), "Gaming", ChannelKind::Voice);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![lounge.clone(), gaming.clone()]);
+        state.user_cache.insert(CachedUser::new(
+            "7",
+            "ferris",
+            "0",
+            None,
+            Some("Ferris".to_string()),
             false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+        ));
+
+        state.set_voice_states(vec![VoiceState::new("7", lounge.id())]);
+        state.update_voice_state(
+            "7",
+            Some(VoiceState::new("7", gaming.id()).with_flags(true, false)),
         );
 
-        let guild = Guild::new(1_u64, "Guild A");
-        let category = Channel::new(ChannelId(100), "Category 1", ChannelKind::Category);
-        let channel =
-            Channel::new(ChannelId(101), "general", ChannelKind::Text).with_parent(100_u64);
+        let data = &state.guilds_tree_data;
+        assert!(data.voice_participants(lounge.id()).is_none());
+        let participants = data.voice_participants(gaming.id()).unwrap();
+        assert_eq!(participants.len(), 1);
+        assert_eq!(participants[0].name(true), "Ferris");
+        assert_eq!(participants[0].name(false), "ferris");
 
+        state
+            .guilds_tree_state
+            .expand(TreeNodeId::Guild(guild.id()));
+        state
+            .guilds_tree_state
+            .expand(TreeNodeId::Channel(gaming.id()));
+        let labels: Vec<String> = state
+            .guilds_tree_data
+            .flatten(
+                &state.guilds_tree_state,
+                u16::MAX,
+                &GuildsTreeStyle::default(),
+                true,
+            )
+            .iter()
+            .map(|node| node.label.to_string())
+            .collect();
+        assert!(labels.iter().any(|l| l.ends_with("Gaming (1)")));
+        assert_eq!(labels.last().unwrap(), "    └── 🔇 Ferris");
+
+        state.update_voice_state("7", None);
+        assert!(
+            state
+                .guilds_tree_data
+                .voice_participants(gaming.id())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_guild_voice_states_take_member_names() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild");
+        let lounge = Channel::new(ChannelId(20), "Lounge", ChannelKind::Voice);
         state.set_guilds(vec![guild.clone()]);
         setup_permissive_guild_data(&mut state, guild.id());
-        state.set_channels(guild.id(), vec![category.clone(), channel.clone()]);
+        state.set_channels(guild.id(), vec![lounge.clone()]);
+        state.set_voice_states(vec![
+            VoiceState::new("7", lounge.id()),
+            VoiceState::new("8", lounge.id()),
+        ]);
 
-        state.toggle_quick_switcher();
+        state.set_guild_voice_states(guild.id(), vec![VoiceState::new("7", lounge.id())]);
+        let mut member =
+            create_dummy_member_with_user(User::new("7", "ferris", "0", None, false, None));
+        member.nick = Some("Crab".to_string());
+        state.set_guild_data(guild.id(), Vec::new(), vec![member]);
 
-        state.perform_search("Category");
+        let participants = state
+            .guilds_tree_data
+            .voice_participants(lounge.id())
+            .unwrap();
+        assert_eq!(participants.len(), 1);
+        assert_eq!(participants[0].name(false), "ferris");
+        assert_eq!(participants[0].name(true), "Crab");
+    }
 
-        assert!(
-            state.quick_switcher.results.is_empty(),
-            "Categories should be excluded from search results. Found: {:?}",
-            state.quick_switcher.results
+    #[test]
+    fn test_connection_toasts_only_on_transitions() {
+        let mut state = create_test_state(create_test_user());
+        let toasts = |results: Vec<ChatKeyResult>| -> Vec<String> {
+            results
+                .into_iter()
+                .filter_map(|result| match result {
+                    ChatKeyResult::ShowNotification(message) => Some(message),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Connecting)).is_empty());
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Connected)).is_empty());
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Disconnected)).is_empty());
+        assert_eq!(
+            toasts(state.set_connection_status(ConnectionStatus::Reconnecting)),
+            vec!["Reconnecting…"]
         );
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Reconnecting)).is_empty());
+        assert_eq!(
+            toasts(state.set_connection_status(ConnectionStatus::Connected)),
+            vec!["Reconnected"]
+        );
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Connected)).is_empty());
 
-        state.perform_search("general");
-        assert_eq!(state.quick_switcher.results.len(), 1);
-        assert_eq!(state.quick_switcher.results[0].id, "101");
+        state.set_internal_notifications(false);
+        let _ = state.set_connection_status(ConnectionStatus::Reconnecting);
+        assert!(toasts(state.set_connection_status(ConnectionStatus::Connected)).is_empty());
     }
 
     #[test]
-    fn test_esc_navigation_void_state() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
-        );
-
+    fn test_reconnect_backfills_open_channel() {
+        let mut state = create_test_state(create_test_user());
         let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(10), "Channel A", ChannelKind::Text);
-
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
         state.set_guilds(vec![guild.clone()]);
         setup_permissive_guild_data(&mut state, guild.id());
         state.set_channels(guild.id(), vec![channel.clone()]);
+        assert!(
+            state
+                .set_connection_status(ConnectionStatus::Connected)
+                .is_empty()
+        );
 
         state.on_channel_selected(channel.id());
-        assert!(state.selected_channel().is_some());
+        let message =
+            |id: u64| create_test_message(id, channel.id(), "456", &format!("message {id}"));
+        state.set_messages(vec![message(1), message(2)]);
 
-        let key_esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
-        state.handle_key(key_esc);
+        let _ = state.set_connection_status(ConnectionStatus::Reconnecting);
+        assert!(matches!(
+            state
+                .set_connection_status(ConnectionStatus::Connected)
+                .as_slice(),
+            [
+                ChatKeyResult::ShowNotification(_),
+                ChatKeyResult::BackfillChannel {
+                    channel_id: ChannelId(10),
+                    after: MessageId(2),
+                },
+            ]
+        ));
 
-        let recents = &state.recents;
-        let null_recent = recents
+        state.message_pane_data_mut().add_message(message(5));
+        state.add_local_echo(
+            MessageId(u64::MAX),
+            &SendMessageRequest::new(channel.id(), "still sending"),
+        );
+        let _ = state.merge_backfilled_messages(
+            channel.id(),
+            vec![message(3), message(4), message(5), message(6)],
+        );
+        let ids: Vec<u64> = state
+            .message_pane_data()
+            .messages()
             .iter()
-            .find(|r| r.name.contains("Text Channel") && r.id == "0");
-
-        assert!(
-            null_recent.is_none(),
-            "Should not add null channel to recents. Found: {:?}",
-            null_recent
+            .map(|m| m.message.id().as_u64())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![1, 2, 3, 4, 5, 6, u64::MAX],
+            "echoes stay below merged messages"
         );
-
-        if state.selected_channel().is_none() {
-            assert_eq!(state.focus(), ChatFocus::GuildsTree);
-        }
     }
 
     #[test]
-    fn test_initial_recents_filtering() {
-        use crate::domain::search::{RecentItem, SearchKind};
-
-        let invalid_item = RecentItem {
-            id: "0".to_string(),
-            name: "Text Channels".to_string(),
-            kind: SearchKind::Channel,
-            guild_id: None,
-            timestamp: 0,
-        };
-
-        let valid_item = RecentItem {
-            id: "123".to_string(),
-            name: "General".to_string(),
-            kind: SearchKind::Channel,
-            guild_id: None,
-            timestamp: 0,
-        };
+    fn test_copy_link_in_thread_uses_thread_guild() {
+        let mut state = create_test_state(create_test_user());
+        let guild = Guild::new(1_u64, "Guild A");
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
+        state.set_guilds(vec![guild.clone()]);
+        setup_permissive_guild_data(&mut state, guild.id());
+        state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
 
-        let state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![invalid_item, valid_item],
-        );
+        let thread = Channel::new(ChannelId(20), "thread", ChannelKind::PublicThread)
+            .with_guild(1_u64)
+            .with_parent(channel.id());
+        state.selected_channel = Some(thread);
+        state
+            .message_pane_data
+            .set_channel(ChannelId(20), "thread".to_string());
+        state.set_messages(vec![create_test_message(30, ChannelId(20), "456", "hi")]);
+        state.focus_messages_list();
+        state.message_pane_state.jump_to_index(0);
 
         assert_eq!(
-            state.recents.len(),
-            1,
-            "Recents should be filtered on initialization"
+            state.handle_key(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT)),
+            ChatKeyResult::CopyToClipboard("https://discord.com/channels/1/20/30".to_string())
         );
-        assert_eq!(state.recents[0].id, "123");
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_perform_search_finds_general() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
-        );
-
+    #[test]
+    fn test_copy_code_block_offers_chooser_for_several_blocks() {
+        let mut state = create_test_state(create_test_user());
         let guild = Guild::new(1_u64, "Guild A");
-        let channel = Channel::new(ChannelId(101), "general", ChannelKind::Text).with_guild(1_u64);
-
+        let channel = Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(1_u64);
         state.set_guilds(vec![guild.clone()]);
         setup_permissive_guild_data(&mut state, guild.id());
         state.set_channels(guild.id(), vec![channel.clone()]);
+        state.on_channel_selected(channel.id());
+        let message =
+            |id: u64, content: &str| create_test_message(id, channel.id(), "456", content);
+        state.set_messages(vec![
+            message(1, "no code here"),
+            message(2, "```rust\nlet a = 1;\n```"),
+            message(3, "```\nfirst\n```\nthen\n```sh\nsecond\n```"),
+        ]);
+        state.focus_messages_list();
+        let copy = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
 
-        assert!(state.guilds_tree_data.get_channel(ChannelId(101)).is_some());
+        state.message_pane_state.jump_to_index(0);
+        assert_eq!(
+            state.handle_key(copy),
+            ChatKeyResult::ShowNotification("No code blocks in this message".to_string())
+        );
 
-        state.toggle_quick_switcher();
-        state.perform_search("general");
+        state.message_pane_state.jump_to_index(1);
+        assert_eq!(
+            state.handle_key(copy),
+            ChatKeyResult::CopyCodeBlock("let a = 1;".to_string())
+        );
 
+        state.message_pane_state.jump_to_index(2);
+        assert_eq!(state.handle_key(copy), ChatKeyResult::Consumed);
         assert_eq!(
-            state.quick_switcher.results.len(),
-            1,
-            "Search for 'general' should return 1 result. Actual: {:?}",
-            state.quick_switcher.results
+            state
+                .code_block_chooser
+                .as_ref()
+                .map(CodeBlockChooserState::len),
+            Some(2)
         );
-        assert_eq!(state.quick_switcher.results[0].id, "101");
+        assert_eq!(
+            state.handle_key(KeyEvent::from(KeyCode::Char('2'))),
+            ChatKeyResult::CopyCodeBlock("second".to_string())
+        );
+        assert!(state.code_block_chooser.is_none());
     }
 
     #[test]
-    fn test_channels_hidden_if_member_missing() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
-        );
-        let guild_id = GuildId(1);
-        let channel =
-            Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(guild_id);
+    #[cfg(feature = "image")]
+    fn test_toggle_image_preview_relayouts_messages() {
+        use crate::domain::entities::Attachment;
 
-        let everyone_role = Role {
-            id: RoleId(1),
-            name: "@everyone".to_string(),
-            permissions: Permissions::VIEW_CHANNEL,
-            color: 0,
-            hoist: false,
-            icon: None,
-            unicode_emoji: None,
-            position: 0,
-            managed: false,
-            mentionable: false,
-        };
+        let mut state = create_test_state(create_test_user());
+        *state.image_manager_mut() = crate::presentation::widgets::ImageManager::halfblocks();
+        state
+            .message_pane_data
+            .set_channel(ChannelId(100), "general".to_string());
+        let mut attachment = Attachment::new("1", "cat.png", 10, "https://x.test/cat.png");
+        attachment.width = Some(400);
+        attachment.height = Some(300);
+        let message = create_test_message(1, ChannelId(100), "456", "look")
+            .with_attachments(vec![attachment]);
+        state.set_messages(vec![message]);
 
-        state.set_guild_data(guild_id, vec![everyone_role], vec![]);
-        state.set_channels(guild_id, vec![channel.clone()]);
+        let area = Rect::new(0, 0, 80, 30);
+        let mut buf = Buffer::empty(area);
+        let height =
+            |state: &ChatScreenState| state.message_pane_data.ui_messages()[0].estimated_height;
 
-        assert!(state.guilds_tree_data.get_channel(channel.id()).is_none());
+        render_message_pane(&mut state, area, &mut buf);
+        let with_images = height(&state);
+
+        state.set_image_preview(false);
+        render_message_pane(&mut state, area, &mut buf);
+        let without_images = height(&state);
+        assert!(without_images < with_images);
+
+        state.set_image_preview(true);
+        render_message_pane(&mut state, area, &mut buf);
+        assert_eq!(height(&state), with_images);
     }
 
     #[test]
-    fn test_channels_visible_if_member_present() {
-        let mut state = ChatScreenState::new(
-            create_test_user(),
-            Arc::new(MarkdownRenderer::new()),
-            UserCache::new(),
-            false,
-            true,
-            true,
-            "%H:%M".to_string(),
-            Theme::new("Orange", None, false),
-            true,
-            CommandRegistry::default(),
-            RelationshipState::new(),
-            false,
-            QuickSwitcherSortMode::default(),
-            vec![],
+    fn test_large_paste_asks_before_inserting() {
+        let mut state = create_test_state(create_test_user());
+        state.set_large_paste_threshold(5);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        state.paste_text("short");
+        assert_eq!(state.message_input_state.value(), "short");
+        state.message_input_state.clear();
+
+        state.paste_text("much longer");
+        assert_eq!(state.focus, ChatFocus::ConfirmationModal);
+        assert_eq!(state.message_input_state.value(), "");
+        assert_eq!(
+            state.handle_key(enter),
+            ChatKeyResult::PasteAsFile("much longer".to_string())
         );
-        let guild_id = GuildId(1);
-        let channel =
-            Channel::new(ChannelId(10), "general", ChannelKind::Text).with_guild(guild_id);
+        assert_eq!(state.focus, ChatFocus::MessageInput);
 
-        let everyone_role = Role {
-            id: RoleId(1),
-            name: "@everyone".to_string(),
-            permissions: Permissions::VIEW_CHANNEL,
-            color: 0,
-            hoist: false,
-            icon: None,
-            unicode_emoji: None,
-            position: 0,
-            managed: false,
-            mentionable: false,
-        };
+        state.paste_text("much longer");
+        state.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        assert_eq!(state.message_input_state.value(), "much longer");
+        assert_eq!(state.focus, ChatFocus::MessageInput);
+        state.message_input_state.clear();
 
-        let member = Member {
-            user: Some(create_test_user()),
-            roles: vec![],
-            nick: None,
-            avatar: None,
-            joined_at: String::new(),
-            premium_since: None,
-            deaf: false,
-            mute: false,
-            pending: false,
-            permissions: None,
-            communication_disabled_until: None,
-        };
+        state.paste_text("much longer");
+        state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert_eq!(state.message_input_state.value(), "");
+        assert!(state.pending_confirmation.is_none());
+    }
 
-        state.set_guild_data(guild_id, vec![everyone_role], vec![member]);
-        state.set_channels(guild_id, vec![channel.clone()]);
+    #[test]
+    fn test_reply_ping_toggle_reaches_send() {
+        let mut state = create_test_state(create_test_user());
+        state.message_input_state.set_has_channel(true);
+        state.start_reply(MessageId(5), "alice".to_string(), true);
+        state.message_input_state.set_content("hi");
 
-        assert!(state.guilds_tree_data.get_channel(channel.id()).is_some());
+        let toggle = KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT);
+        assert_eq!(state.handle_key(toggle), ChatKeyResult::Consumed);
+        assert!(!state.message_input_state.mode().mentions_reply());
+
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            ChatKeyResult::SendMessage {
+                content: "hi".to_string(),
+                reply_to: Some(MessageId(5)),
+                mention_reply: false,
+                mass_mention_confirmed: false,
+                attachments: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_file_explorer_reopens_in_last_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let start = root.path().join("start");
+        let later = root.path().join("later");
+        std::fs::create_dir(&start).unwrap();
+        std::fs::create_dir(&later).unwrap();
+
+        let mut state = create_test_state(create_test_user());
+        state.set_file_explorer_config(FileExplorerConfig {
+            start_dir: Some(start.clone()),
+            bookmarks: vec![later.clone()],
+        });
+
+        state.toggle_file_explorer();
+        let explorer = state.file_explorer.as_ref().unwrap();
+        assert_eq!(explorer.current_dir(), start);
+
+        state.handle_key(KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE));
+        state.toggle_file_explorer();
+        assert!(state.file_explorer.is_none());
+
+        state.toggle_file_explorer();
+        let explorer = state.file_explorer.as_ref().unwrap();
+        assert_eq!(explorer.current_dir(), later);
     }
 }
@@ -4,7 +4,7 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
@@ -20,12 +20,26 @@ pub enum LoginState {
     Success,
 }
 
+/// Progress of a QR code login, as shown on the login screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrLoginStatus {
+    Connecting,
+    /// Waiting for this URL to be scanned.
+    Code(String),
+    /// Scanned by this user; waiting for approval on their phone.
+    Scanned(String),
+}
+
 /// Login screen UI.
 pub struct LoginScreen {
     token_input: TextInput,
     state: LoginState,
     error_message: Option<String>,
     persist_token: bool,
+    /// Esc was pressed once and a second press will quit.
+    quit_pending: bool,
+    /// Set while a QR code login replaces the token input.
+    qr: Option<QrLoginStatus>,
     theme: Theme,
 }
 
@@ -35,6 +49,7 @@ pub enum LoginAction {
     Submit,
     DeleteToken,
     Paste,
+    QrLogin,
 }
 
 impl LoginScreen {
@@ -51,6 +66,8 @@ impl LoginScreen {
             state: LoginState::Input,
             error_message: None,
             persist_token: true,
+            quit_pending: false,
+            qr: None,
             theme: Theme::default(),
         }
     }
@@ -61,6 +78,10 @@ impl LoginScreen {
         self
     }
 
+    pub const fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     /// Returns current state.
     #[must_use]
     pub const fn state(&self) -> LoginState {
@@ -98,6 +119,34 @@ impl LoginScreen {
         self.error_message = Some(message.into());
     }
 
+    /// Shows or hides the reminder that another Esc quits.
+    pub const fn set_quit_pending(&mut self, pending: bool) {
+        self.quit_pending = pending;
+    }
+
+    /// Switches to the QR code view until [`Self::cancel_qr`].
+    pub fn start_qr(&mut self) {
+        self.reset();
+        self.qr = Some(QrLoginStatus::Connecting);
+    }
+
+    /// Updates the QR code view, if it is showing.
+    pub fn set_qr_status(&mut self, status: QrLoginStatus) {
+        if self.qr.is_some() {
+            self.qr = Some(status);
+        }
+    }
+
+    /// Returns to the token input.
+    pub fn cancel_qr(&mut self) {
+        self.qr = None;
+    }
+
+    #[must_use]
+    pub const fn is_qr_active(&self) -> bool {
+        self.qr.is_some()
+    }
+
     /// Resets to input state.
     pub fn reset(&mut self) {
         self.state = LoginState::Input;
@@ -122,6 +171,12 @@ impl LoginScreen {
 
         if self.state == LoginState::Error {
             self.reset();
+        }
+
+        if self.qr.is_some() {
+            if key.code == KeyCode::Tab {
+                self.persist_token = !self.persist_token;
+            }
             return LoginAction::None;
         }
 
@@ -134,6 +189,9 @@ impl LoginScreen {
             KeyCode::Char('d') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
                 return LoginAction::DeleteToken;
             }
+            KeyCode::Char('q') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
+                return LoginAction::QrLogin;
+            }
             KeyCode::Char('u')
                 if key
                     .modifiers
@@ -179,6 +237,11 @@ impl LoginScreen {
     }
 
     fn render_inner(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(status) = &self.qr {
+            self.render_qr(status, area, buf);
+            return;
+        }
+
         let vertical = Layout::vertical([
             Constraint::Fill(1),
             Constraint::Min(15),
@@ -209,12 +272,14 @@ impl LoginScreen {
             Constraint::Length(3),
         ];
 
-        let token_val = self.token_input.value();
-        let has_quote = token_val.contains('"') || token_val.contains('\'');
-        let too_long = token_val.len() > 72;
-        let show_tip = has_quote || too_long;
+        let tips = token_tips(self.token_input.value());
+        let show_tip = !tips.is_empty();
+        let error = self
+            .error_message
+            .as_deref()
+            .filter(|_| self.state == LoginState::Error);
 
-        if show_tip {
+        if show_tip || error.is_some() {
             constraints.push(Constraint::Length(2));
         } else {
             constraints.push(Constraint::Length(1));
@@ -233,14 +298,12 @@ impl LoginScreen {
 
         (&self.token_input).render(areas[2], buf);
 
-        if show_tip {
-            let mut tips = Vec::new();
-            if has_quote {
-                tips.push("Token should not contain quotes");
-            }
-            if too_long {
-                tips.push("Token seems too long");
-            }
+        if let Some(error) = error {
+            Paragraph::new(error)
+                .style(self.theme.error_style)
+                .wrap(ratatui::widgets::Wrap { trim: true })
+                .render(areas[3], buf);
+        } else if show_tip {
             let tip_text = format!("Tip! {}", tips.join(", "));
             let tip_para = Paragraph::new(tip_text)
                 .style(self.theme.warning_style.add_modifier(Modifier::BOLD));
@@ -256,12 +319,18 @@ impl LoginScreen {
         persist_para.render(areas[4], buf);
 
         let status = match self.state {
-            LoginState::Input => Line::from(vec![
+            LoginState::Input if self.quit_pending => Line::from(Span::styled(
+                "Press Esc again to quit",
+                Style::default().fg(self.theme.accent),
+            )),
+            LoginState::Input | LoginState::Error => Line::from(vec![
                 Span::styled("Enter: Login", self.theme.dimmed_style),
                 Span::raw(" | "),
                 Span::styled("Esc: Quit", self.theme.dimmed_style),
                 Span::raw(" | "),
                 Span::styled("Alt+D: Clear Saved", self.theme.dimmed_style),
+                Span::raw(" | "),
+                Span::styled("Alt+Q: QR Login", self.theme.dimmed_style),
             ]),
             LoginState::Validating => Line::from(Span::styled(
                 "Validating token...",
@@ -269,13 +338,6 @@ impl LoginScreen {
                     .fg(self.theme.accent)
                     .add_modifier(Modifier::ITALIC),
             )),
-            LoginState::Error => {
-                let msg = self.error_message.as_deref().unwrap_or("Unknown error");
-                Line::from(Span::styled(
-                    format!("Error: {msg}"),
-                    self.theme.error_style,
-                ))
-            }
             LoginState::Success => {
                 Line::from(Span::styled("Login successful!", self.theme.success_style))
             }
@@ -284,6 +346,114 @@ impl LoginScreen {
         let status_para = Paragraph::new(status).wrap(ratatui::widgets::Wrap { trim: true });
         status_para.render(areas[6], buf);
     }
+
+    fn render_qr(&self, status: &QrLoginStatus, area: Rect, buf: &mut Buffer) {
+        let code = match status {
+            QrLoginStatus::Code(url) => qrcode::QrCode::new(url.as_bytes())
+                .map(|code| {
+                    code.render::<qrcode::render::unicode::Dense1x2>()
+                        .quiet_zone(true)
+                        .build()
+                })
+                .ok(),
+            _ => None,
+        };
+        let code_lines: Vec<&str> = code
+            .as_deref()
+            .map_or_else(Vec::new, |c| c.lines().collect());
+        let code_width = code_lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let height = u16::try_from(code_lines.len()).unwrap_or(u16::MAX).max(1);
+        let width = u16::try_from(code_width).unwrap_or(u16::MAX).max(56);
+        let popup_area = Rect::new(
+            area.x + area.width.saturating_sub(width + 4) / 2,
+            area.y + area.height.saturating_sub(height + 6) / 2,
+            (width + 4).min(area.width),
+            (height + 6).min(area.height),
+        );
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.accent))
+            .title(" Oxicord Login ");
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let [title_area, code_area, persist_area, status_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        Paragraph::new("Scan with the Discord mobile app")
+            .style(self.theme.base_style)
+            .alignment(ratatui::layout::Alignment::Center)
+            .render(title_area, buf);
+
+        match status {
+            QrLoginStatus::Code(_) if !code_lines.is_empty() => {
+                let lines: Vec<Line> = code_lines.into_iter().map(Line::raw).collect();
+                Paragraph::new(lines)
+                    .style(Style::default().fg(Color::Black).bg(Color::White))
+                    .render(
+                        Layout::horizontal([Constraint::Length(
+                            u16::try_from(code_width).unwrap_or(u16::MAX),
+                        )])
+                        .flex(ratatui::layout::Flex::Center)
+                        .areas::<1>(code_area)[0],
+                        buf,
+                    );
+            }
+            QrLoginStatus::Code(_) => {
+                Paragraph::new("Could not draw the QR code")
+                    .style(self.theme.error_style)
+                    .render(code_area, buf);
+            }
+            QrLoginStatus::Connecting => {
+                Paragraph::new("Requesting a code...")
+                    .style(self.theme.dimmed_style)
+                    .render(code_area, buf);
+            }
+            QrLoginStatus::Scanned(username) => {
+                Paragraph::new(format!("Scanned by {username}. Confirm on your phone."))
+                    .style(self.theme.success_style)
+                    .render(code_area, buf);
+            }
+        }
+
+        let checkbox = if self.persist_token { "[x]" } else { "[ ]" };
+        Paragraph::new(Line::from(vec![
+            Span::styled(checkbox, Style::default().fg(self.theme.accent)),
+            Span::raw(" Remember token (Tab to toggle)"),
+        ]))
+        .render(persist_area, buf);
+
+        Paragraph::new(Span::styled(
+            "Esc: Back to token login",
+            self.theme.dimmed_style,
+        ))
+        .render(status_area, buf);
+    }
+}
+
+/// Hints about likely copy-paste mistakes in `token`.
+fn token_tips(token: &str) -> Vec<&'static str> {
+    let mut tips = Vec::new();
+    if token.contains('"') || token.contains('\'') {
+        tips.push("Token should not contain quotes");
+    }
+    if token.len() > 72 {
+        tips.push("Token seems too long");
+    }
+    tips
 }
 
 impl Default for LoginScreen {
@@ -375,4 +545,55 @@ mod tests {
         let action = screen.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL));
         assert_eq!(action, LoginAction::Paste);
     }
+
+    #[test]
+    fn test_error_is_shown_inline_and_token_kept() {
+        let mut screen = LoginScreen::new();
+        screen.set_token("abc".to_string());
+        screen.set_validating();
+        screen.set_error("Invalid token: invalid or expired token");
+
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buf = Buffer::empty(area);
+        (&screen).render(area, &mut buf);
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("Invalid token: invalid or expired token"))
+        );
+
+        screen.handle_key(key(KeyCode::Char('d')));
+        assert_eq!(screen.state(), LoginState::Input);
+        assert_eq!(screen.token(), Some("abcd"));
+    }
+
+    #[test]
+    fn test_qr_login_shows_code_and_ignores_typing() {
+        let mut screen = LoginScreen::new();
+        let action = screen.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT));
+        assert_eq!(action, LoginAction::QrLogin);
+
+        screen.start_qr();
+        screen.handle_key(key(KeyCode::Char('x')));
+        assert!(screen.token().is_none());
+
+        screen.set_qr_status(QrLoginStatus::Code(
+            "https://discord.com/ra/abcdefghijklmnopqrstuvwxyz0123456789ABCDEFG".to_string(),
+        ));
+        let area = Rect::new(0, 0, 80, 40);
+        let mut buf = Buffer::empty(area);
+        (&screen).render(area, &mut buf);
+        let drawn: String = (0..area.height)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .map(|pos| buf[pos].symbol().to_string())
+            .collect();
+        assert!(drawn.contains("Scan with the Discord mobile app"));
+        assert!(drawn.contains('▀'));
+
+        screen.cancel_qr();
+        screen.set_qr_status(QrLoginStatus::Connecting);
+        assert!(!screen.is_qr_active());
+    }
 }
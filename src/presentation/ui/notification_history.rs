@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+use crate::domain::{Notification, NotificationLevel};
+use crate::presentation::theme::Theme;
+
+/// Modal listing past notifications, newest first.
+pub struct NotificationHistory<'a> {
+    notifications: &'a VecDeque<Notification>,
+    selected: usize,
+    unseen: usize,
+    theme: &'a Theme,
+}
+
+impl<'a> NotificationHistory<'a> {
+    #[must_use]
+    pub fn new(
+        notifications: &'a VecDeque<Notification>,
+        selected: usize,
+        unseen: usize,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            notifications,
+            selected,
+            unseen,
+            theme,
+        }
+    }
+
+    fn level_span(&self, level: &NotificationLevel) -> Span<'static> {
+        let (label, style) = match level {
+            NotificationLevel::Info => ("INFO ", Style::default().fg(self.theme.accent)),
+            NotificationLevel::Warn => ("WARN ", self.theme.warning_style),
+            NotificationLevel::Error => ("ERROR", self.theme.error_style),
+        };
+        Span::styled(label, style)
+    }
+
+    fn item(&self, notification: &Notification, unseen: bool) -> ListItem<'static> {
+        let text_style = if unseen {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![
+            Span::styled(
+                notification.timestamp.format("%H:%M:%S ").to_string(),
+                self.theme.timestamp_style,
+            ),
+            self.level_span(&notification.level),
+            Span::styled(
+                format!(" {}: {}", notification.title, notification.message),
+                text_style,
+            ),
+        ];
+        if let Some(source) = &notification.source {
+            spans.push(Span::styled(
+                format!("  {}", source.name),
+                self.theme.dimmed_style,
+            ));
+        }
+        ListItem::new(Line::from(spans))
+    }
+}
+
+impl Widget for NotificationHistory<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = 90.min(area.width);
+        let height = 24.min(area.height);
+        let popup_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        Clear.render(popup_area, buf);
+
+        let title = if self.unseen > 0 {
+            format!(" Notifications ({} new) ", self.unseen)
+        } else {
+            " Notifications ".to_string()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .border_style(Style::default().fg(self.theme.accent));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+        Paragraph::new("Enter: jump to channel  c: clear  Esc: close")
+            .style(self.theme.dimmed_style)
+            .alignment(Alignment::Center)
+            .render(hint_area, buf);
+
+        if self.notifications.is_empty() {
+            Paragraph::new("No notifications")
+                .style(self.theme.dimmed_style)
+                .alignment(Alignment::Center)
+                .render(list_area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .notifications
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, notification)| self.item(notification, i < self.unseen))
+            .collect();
+        let list = List::new(items).highlight_style(self.theme.selection_style);
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        StatefulWidget::render(list, list_area, buf, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::ChannelId;
+
+    #[test]
+    fn test_history_lists_newest_first_with_source() {
+        let theme = Theme::default();
+        let notifications: VecDeque<Notification> = [
+            Notification::new(NotificationLevel::Info, "Info", "first"),
+            Notification::new(NotificationLevel::Error, "@ferris", "second")
+                .with_source(ChannelId(1), "#general"),
+        ]
+        .into();
+        let area = Rect::new(0, 0, 90, 10);
+        let mut buf = Buffer::empty(area);
+        NotificationHistory::new(&notifications, 1, 1, &theme).render(area, &mut buf);
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert!(rows[0].contains("Notifications (1 new)"));
+        assert!(rows[1].contains("ERROR @ferris: second  #general"));
+        assert!(rows[2].contains("INFO  Info: first"));
+        let error_at = rows[1].find("ERROR").unwrap();
+        let error_x = u16::try_from(rows[1][..error_at].chars().count()).unwrap();
+        assert_eq!(buf[(error_x, 1)].fg, theme.error_style.fg.unwrap());
+    }
+}
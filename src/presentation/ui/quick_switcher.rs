@@ -1,12 +1,12 @@
 use crate::domain::search::{RecentItem, SearchKind, SearchResult};
 use crate::infrastructure::config::app_config::QuickSwitcherSortMode;
 use crate::presentation::theme::Theme;
-use crate::presentation::ui::utils::sanitize_channel_name;
+use crate::presentation::ui::utils::{highlight_matches, sanitize_channel_name};
 use crate::presentation::widgets::FooterBarStyle;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{
         Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
@@ -19,6 +19,8 @@ pub struct QuickSwitcher {
     pub list_state: ListState,
     pub sort_mode: QuickSwitcherSortMode,
     pub recents: Vec<RecentItem>,
+    /// Listed first, in pin order, when the query is empty.
+    pub pinned: Vec<RecentItem>,
 }
 
 impl Default for QuickSwitcher {
@@ -36,6 +38,7 @@ impl QuickSwitcher {
             list_state: ListState::default(),
             sort_mode,
             recents: Vec::new(),
+            pinned: Vec::new(),
         }
     }
 
@@ -57,6 +60,27 @@ impl QuickSwitcher {
         self.apply_sort();
     }
 
+    pub fn set_pinned(&mut self, pinned: Vec<RecentItem>) {
+        self.pinned = pinned;
+        self.apply_sort();
+    }
+
+    #[must_use]
+    pub fn is_pinned(&self, result: &SearchResult) -> bool {
+        pin_index(&self.pinned, result).is_some()
+    }
+
+    /// Moves the selection to `result`, if it is listed.
+    pub fn select_matching(&mut self, result: &SearchResult) {
+        if let Some(i) = self
+            .results
+            .iter()
+            .position(|r| r.id == result.id && r.kind == result.kind)
+        {
+            self.list_state.select(Some(i));
+        }
+    }
+
     pub fn reset(&mut self) {
         self.input.clear();
         self.results.clear();
@@ -153,6 +177,12 @@ impl QuickSwitcher {
                 });
             }
         }
+
+        if self.input.trim().is_empty() && !self.pinned.is_empty() {
+            let pinned = &self.pinned;
+            self.results
+                .sort_by_key(|r| pin_index(pinned, r).unwrap_or(usize::MAX));
+        }
     }
 
     #[must_use]
@@ -179,6 +209,11 @@ impl QuickSwitcher {
                 QuickSwitcherAction::None
             }
             KeyCode::Tab => QuickSwitcherAction::ToggleSortMode,
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::ALT) => self
+                .selected_result()
+                .map_or(QuickSwitcherAction::None, |result| {
+                    QuickSwitcherAction::TogglePin(result.clone())
+                }),
             KeyCode::Char('h' | 'w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if let Some(last_space_idx) = self.input.trim_end().rfind(' ') {
                     self.input.truncate(last_space_idx + 1);
@@ -249,6 +284,26 @@ pub enum QuickSwitcherAction {
     Select(SearchResult),
     UpdateSearch(String),
     ToggleSortMode,
+    TogglePin(SearchResult),
+}
+
+/// Position of `result` among the pinned items.
+fn pin_index(pinned: &[RecentItem], result: &SearchResult) -> Option<usize> {
+    pinned
+        .iter()
+        .position(|p| p.id == result.id && p.kind == result.kind)
+}
+
+/// Maps match positions in `raw` onto the sanitized `name` shown for it.
+/// Highlighting is skipped if sanitizing altered more than the edges.
+fn sanitized_indices(raw: &str, name: &str, indices: &[usize]) -> Vec<usize> {
+    let Some(offset) = raw.find(name).map(|byte| raw[..byte].chars().count()) else {
+        return Vec::new();
+    };
+    indices
+        .iter()
+        .filter_map(|&i| i.checked_sub(offset))
+        .collect()
 }
 
 pub struct QuickSwitcherWidget<'a> {
@@ -282,19 +337,36 @@ impl<'a> QuickSwitcherWidget<'a> {
                 };
 
                 let name = sanitize_channel_name(&res.name);
+                let star = if self.switcher.is_pinned(res) {
+                    "★ "
+                } else {
+                    ""
+                };
 
                 let left_part_1 = format!(" {type_label:<9} ");
                 let left_part_2 = format!(" {icon} ");
-                let left_part_3 = format!(" {name} ");
+                let left_part_3 = format!(" {star}");
 
-                let mut left_len =
-                    left_part_1.len() + left_part_2.chars().count() + left_part_3.len();
+                let mut left_len = left_part_1.len()
+                    + left_part_2.chars().count()
+                    + left_part_3.chars().count()
+                    + name.chars().count()
+                    + 1;
 
                 let mut spans = vec![
                     Span::styled(left_part_1, self.theme.dimmed_style),
                     Span::styled(left_part_2, Style::default().fg(self.theme.accent)),
                     Span::styled(left_part_3, self.theme.base_style),
                 ];
+                spans.extend(highlight_matches(
+                    &name,
+                    &sanitized_indices(&res.name, &name, &res.match_indices),
+                    self.theme.base_style,
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ));
+                spans.push(Span::styled(" ", self.theme.base_style));
 
                 if let Some(parent) = &res.parent_name {
                     let parent_text = format!("({}) ", sanitize_channel_name(parent));
@@ -322,7 +394,15 @@ impl<'a> QuickSwitcherWidget<'a> {
                     }
                 }
 
-                ListItem::new(Line::from(spans))
+                // Every result takes two rows so the list scrolls evenly.
+                let subtitle = res.subtitle.as_deref().map_or_else(Line::default, |text| {
+                    Line::from(Span::styled(
+                        format!("{:15}{text}", ""),
+                        self.theme.dimmed_style,
+                    ))
+                });
+
+                ListItem::new(vec![Line::from(spans), subtitle])
             })
             .collect();
 
@@ -396,6 +476,9 @@ impl Widget for QuickSwitcherWidget<'_> {
             ));
             footer_spans.push(Span::styled(format!(" {label} "), footer_style.key_style));
         }
+        footer_spans.push(Span::raw(" "));
+        footer_spans.push(Span::styled(" Alt+P ", footer_style.label_style));
+        footer_spans.push(Span::styled(" Pin ", footer_style.key_style));
 
         let footer = Paragraph::new(Line::from(footer_spans));
         for x in layout[3].left()..layout[3].right() {
@@ -608,4 +691,25 @@ mod tests {
         assert_eq!(switcher.results[3].id, "5");
         assert_eq!(switcher.results[4].id, "4");
     }
+
+    #[test]
+    fn test_results_render_subtitle_below_name() {
+        let theme = Theme::default();
+        let mut switcher = QuickSwitcher::new(QuickSwitcherSortMode::default());
+        switcher.set_results(vec![
+            SearchResult::new("1", "cool-thread", SearchKind::Thread)
+                .with_subtitle(Some("Guild A › general".to_string())),
+            SearchResult::new("2", "random", SearchKind::Channel),
+        ]);
+        let area = Rect::new(0, 0, 60, 6);
+        let mut buf = ratatui::buffer::Buffer::empty(area);
+        QuickSwitcherWidget::new(&switcher, &theme).render_results_list(area, &mut buf);
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert!(rows[0].contains("cool-thread"));
+        assert!(rows[1].contains("Guild A › general"));
+        assert!(rows[2].contains("random"));
+    }
 }
@@ -29,6 +29,12 @@ pub struct SplashScreen {
     outro_effect: Effect,
     pub state: LoadingState,
     pending_duration: Duration,
+    /// Time shown so far.
+    elapsed: Duration,
+    /// The outro waits until the splash has been up this long.
+    min_duration: Duration,
+    /// When off, nothing is drawn and loading completes as soon as data is ready.
+    visible: bool,
 }
 
 impl Default for SplashScreen {
@@ -48,20 +54,52 @@ impl SplashScreen {
             outro_effect,
             state: LoadingState::default(),
             pending_duration: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            min_duration: Duration::ZERO,
+            visible: true,
         }
     }
 
+    /// Skips the animation entirely when `visible` is false.
+    #[must_use]
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self.state.intro_finished |= !visible;
+        self
+    }
+
+    /// Holds the outro back until the splash has been shown for `duration`.
+    #[must_use]
+    pub const fn with_min_duration(mut self, duration: Duration) -> Self {
+        self.min_duration = duration;
+        self
+    }
+
     pub fn tick(&mut self, duration: Duration) {
         self.pending_duration = self.pending_duration.saturating_add(duration);
+        self.elapsed = self.elapsed.saturating_add(duration);
+        self.complete_if_hidden();
     }
 
     pub fn set_data_ready(&mut self) {
         self.state.data_ready = true;
+        self.complete_if_hidden();
+    }
+
+    /// A hidden splash has no outro to wait for.
+    const fn complete_if_hidden(&mut self) {
+        if !self.visible && self.state.data_ready {
+            self.state.animation_complete = true;
+        }
     }
 }
 
 impl Widget for &mut SplashScreen {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.visible {
+            return;
+        }
+
         let text_content = LOGO_TEXT.trim_matches('\n');
         let text = Text::from(text_content).centered();
 
@@ -94,7 +132,7 @@ impl Widget for &mut SplashScreen {
             if overflow.is_some() {
                 self.state.intro_finished = true;
             }
-        } else if self.state.data_ready {
+        } else if self.state.data_ready && self.elapsed >= self.min_duration {
             let overflow = self.outro_effect.process(duration.into(), buf, center_area);
             if overflow.is_some() {
                 self.state.animation_complete = true;
@@ -102,3 +140,39 @@ impl Widget for &mut SplashScreen {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_splash_completes_once_data_is_ready() {
+        let mut splash = SplashScreen::new().with_visible(false);
+        assert!(splash.state.intro_finished);
+
+        splash.tick(Duration::from_millis(16));
+        assert!(!splash.state.animation_complete);
+
+        splash.set_data_ready();
+        assert!(splash.state.animation_complete);
+    }
+
+    #[test]
+    fn test_outro_waits_for_min_duration() {
+        let area = Rect::new(0, 0, 80, 10);
+        let mut buf = Buffer::empty(area);
+        let mut splash = SplashScreen::new().with_min_duration(Duration::from_secs(5));
+        splash.state.intro_finished = true;
+        splash.set_data_ready();
+
+        splash.tick(Duration::from_secs(2));
+        splash.render(area, &mut buf);
+        splash.tick(Duration::from_secs(1));
+        splash.render(area, &mut buf);
+        assert!(!splash.state.animation_complete);
+
+        splash.tick(Duration::from_secs(3));
+        splash.render(area, &mut buf);
+        assert!(splash.state.animation_complete);
+    }
+}
@@ -1,11 +1,15 @@
+use std::borrow::Cow;
 use std::sync::OnceLock;
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Days, Local, Utc};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Color;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
 use regex::Regex;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::domain::entities::{MessageAuthor, User};
+use crate::infrastructure::config::app_config::{AuthorColorMode, AuthorColorSeed, AuthorPalette};
 
 /// Removes emojis and symbols from the given string.
 ///
@@ -68,23 +72,66 @@ pub fn u32_to_color(color: u32) -> Color {
     )
 }
 
+const PASTEL_PALETTE: &[Color] = &[
+    Color::Rgb(255, 179, 186),
+    Color::Rgb(255, 223, 186),
+    Color::Rgb(255, 255, 186),
+    Color::Rgb(186, 255, 201),
+    Color::Rgb(186, 225, 255),
+    Color::Rgb(218, 198, 255),
+    Color::Rgb(255, 198, 236),
+    Color::Rgb(198, 240, 232),
+];
+
+/// Lightness steps for shades of the accent color.
+const ACCENT_SHADES: &[f32] = &[0.45, 0.55, 0.65, 0.75, 0.85];
+
+/// Stable FNV-1a hash, so usernames map to the same color on every run.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Picks a name color for a user without a role color.
+#[must_use]
+pub fn author_color(id: u64, username: &str, mode: AuthorColorMode, accent: Color) -> Color {
+    use crate::presentation::theme::adapter::ColorConverter;
+
+    let seed = match mode.seed {
+        AuthorColorSeed::Id => id,
+        AuthorColorSeed::Username => fnv1a(&username.to_lowercase()),
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let pick = |len: usize| (seed % len as u64) as usize;
+
+    match mode.palette {
+        AuthorPalette::Vibrant => USER_PALETTE[pick(USER_PALETTE.len())],
+        AuthorPalette::Pastel => PASTEL_PALETTE[pick(PASTEL_PALETTE.len())],
+        AuthorPalette::Accent => {
+            let mut hsl = ColorConverter::to_hsl(accent);
+            hsl.l = ACCENT_SHADES[pick(ACCENT_SHADES.len())];
+            ColorConverter::to_ratatui(hsl)
+        }
+    }
+}
+
 #[must_use]
-pub fn get_author_color(author: &MessageAuthor) -> Color {
+pub fn get_author_color(author: &MessageAuthor, mode: AuthorColorMode, accent: Color) -> Color {
     if let Some(c) = author.color() {
         u32_to_color(c)
     } else {
         let id = author.id().parse::<u64>().unwrap_or(0);
-        hash_id_to_color(id)
+        author_color(id, author.username(), mode, accent)
     }
 }
 
 #[must_use]
-pub fn get_user_color(user: &User) -> Color {
+pub fn get_user_color(user: &User, mode: AuthorColorMode, accent: Color) -> Color {
     if let Some(c) = user.color() {
         u32_to_color(c)
     } else {
-        let id = user.id().as_u64();
-        hash_id_to_color(id)
+        author_color(user.id().as_u64(), user.username(), mode, accent)
     }
 }
 
@@ -147,6 +194,21 @@ pub fn split_command(s: &str) -> Vec<String> {
     args
 }
 
+/// Builds an editor invocation from a command template. Every `{file}` in
+/// the template is replaced with `path`; without one, `path` is appended.
+#[must_use]
+pub fn editor_command(template: &str, path: &str) -> Vec<String> {
+    let mut args = split_command(template);
+    if args.iter().any(|arg| arg.contains("{file}")) {
+        for arg in &mut args {
+            *arg = arg.replace("{file}", path);
+        }
+    } else if !args.is_empty() {
+        args.push(path.to_string());
+    }
+    args
+}
+
 /// Formats an ISO 8601 timestamp string to local time (HH:MM format).
 /// Falls back to the original string if parsing fails.
 #[must_use]
@@ -172,6 +234,129 @@ pub fn format_iso_timestamp(iso_str: &str) -> String {
     iso_str.chars().take(10).collect()
 }
 
+/// Timestamp format value that selects [`format_relative_timestamp`].
+pub const RELATIVE_TIMESTAMP_FORMAT: &str = "relative";
+
+/// Formats a timestamp relative to `now`, e.g. "now", "2m", "3h", "yesterday".
+///
+/// Timestamps older than a week fall back to the calendar date.
+#[must_use]
+pub fn format_relative_timestamp(timestamp: DateTime<Local>, now: DateTime<Local>) -> String {
+    let elapsed = now.signed_duration_since(timestamp);
+    if elapsed.num_minutes() < 1 {
+        return "now".to_string();
+    }
+    if elapsed.num_hours() < 1 {
+        return format!("{}m", elapsed.num_minutes());
+    }
+
+    let today = now.date_naive();
+    let date = timestamp.date_naive();
+    if date == today {
+        return format!("{}h", elapsed.num_hours());
+    }
+    if today.checked_sub_days(Days::new(1)) == Some(date) {
+        return "yesterday".to_string();
+    }
+
+    let days = (today - date).num_days();
+    if days < 7 {
+        format!("{days}d")
+    } else {
+        timestamp.format("%b %d").to_string()
+    }
+}
+
+/// Marker used by [`truncate_to_width`] unless configured otherwise.
+pub const DEFAULT_ELLIPSIS: &str = "…";
+
+/// Shortens `text` to at most `max_width` columns, ending with `ellipsis`.
+///
+/// Cuts only between characters, so a wide character that would straddle
+/// the limit is dropped whole. When even the ellipsis does not fit, the text
+/// is cut without it.
+#[must_use]
+pub fn truncate_to_width<'a>(text: &'a str, max_width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if text.width() <= max_width {
+        return Cow::Borrowed(text);
+    }
+
+    let ellipsis = if ellipsis.width() <= max_width {
+        ellipsis
+    } else {
+        ""
+    };
+    let budget = max_width - ellipsis.width();
+    let mut width = 0;
+    let mut end = 0;
+    for (index, c) in text.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > budget {
+            break;
+        }
+        width += char_width;
+        end = index + c.len_utf8();
+    }
+    Cow::Owned(format!("{}{ellipsis}", &text[..end]))
+}
+
+/// Shortens a styled line to `max_width` columns, like [`truncate_to_width`].
+///
+/// The ellipsis takes the style of the span it cuts into.
+#[must_use]
+pub fn truncate_line<'a>(line: Line<'a>, max_width: usize, ellipsis: &str) -> Line<'a> {
+    if line.width() <= max_width {
+        return line;
+    }
+
+    let ellipsis = if ellipsis.width() <= max_width {
+        ellipsis
+    } else {
+        ""
+    };
+    let mut remaining = max_width - ellipsis.width();
+    let mut spans = Vec::with_capacity(line.spans.len());
+    for span in line.spans {
+        let width = span.width();
+        if width <= remaining {
+            remaining -= width;
+            spans.push(span);
+            continue;
+        }
+        let cut = truncate_to_width(&span.content, remaining, "");
+        spans.push(Span::styled(format!("{cut}{ellipsis}"), span.style));
+        break;
+    }
+    Line { spans, ..line }
+}
+
+/// Splits `text` into spans, giving the characters at `indices` (as
+/// returned by fuzzy matching) the `matched` style.
+#[must_use]
+pub fn highlight_matches(
+    text: &str,
+    indices: &[usize],
+    style: Style,
+    matched: Style,
+) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_match = indices.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            let style = if run_matched { matched } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { matched } else { style }));
+    }
+    spans
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,4 +398,117 @@ mod tests {
         assert_eq!(split_command(""), Vec::<String>::new());
         assert_eq!(split_command("   "), Vec::<String>::new());
     }
+
+    #[test]
+    fn test_editor_command() {
+        assert_eq!(
+            editor_command("nvim", "/tmp/a b.md"),
+            vec!["nvim", "/tmp/a b.md"]
+        );
+        assert_eq!(
+            editor_command("code --wait {file}", "/tmp/a.md"),
+            vec!["code", "--wait", "/tmp/a.md"]
+        );
+        assert_eq!(
+            editor_command("emacsclient --eval '(find-file \"{file}\")'", "/tmp/a.md"),
+            vec!["emacsclient", "--eval", "(find-file \"/tmp/a.md\")"]
+        );
+        assert_eq!(editor_command("", "/tmp/a.md"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("general", 10, "…"), "general");
+        assert_eq!(truncate_to_width("announcements", 8, "…"), "announc…");
+        assert_eq!(truncate_to_width("announcements", 8, "..."), "annou...");
+        assert_eq!(truncate_to_width("announcements", 2, "..."), "an");
+
+        // Wide characters are two columns and are never split.
+        assert_eq!(truncate_to_width("日本語チャンネル", 7, "…"), "日本語…");
+        assert_eq!(truncate_to_width("日本語チャンネル", 6, "…"), "日本…");
+        assert!(truncate_to_width("日本語チャンネル", 6, "…").width() <= 6);
+        assert_eq!(truncate_to_width("héllo wörld", 6, "…"), "héllo…");
+    }
+
+    #[test]
+    fn test_author_color_modes_are_stable_and_distinct() {
+        let id = 80_351_110_224_678_912;
+        let vibrant = AuthorColorMode::default();
+        let pastel = AuthorColorMode {
+            palette: AuthorPalette::Pastel,
+            ..vibrant
+        };
+        let by_name = AuthorColorMode {
+            seed: AuthorColorSeed::Username,
+            palette: AuthorPalette::Accent,
+        };
+
+        let colors: Vec<Color> = [vibrant, pastel, by_name]
+            .into_iter()
+            .map(|mode| author_color(id, "Nelly", mode, Color::Rgb(88, 101, 242)))
+            .collect();
+        assert_eq!(colors[0], hash_id_to_color(id));
+        assert_ne!(colors[0], colors[1]);
+        assert_ne!(colors[1], colors[2]);
+
+        // Same input, same color; username seeding ignores the id and case.
+        assert_eq!(author_color(id, "Nelly", pastel, Color::Red), colors[1]);
+        assert_eq!(
+            author_color(1, "nelly", by_name, Color::Rgb(88, 101, 242)),
+            colors[2]
+        );
+    }
+
+    #[test]
+    fn test_highlight_matches_groups_runs() {
+        let base = Style::default();
+        let hit = Style::default().fg(Color::Yellow);
+        let spans = highlight_matches("crash-course", &[0, 1, 3], base, hit);
+        let parts: Vec<(&str, Style)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("cr", hit), ("a", base), ("s", hit), ("h-course", base)]
+        );
+        assert_eq!(highlight_matches("", &[0], base, hit), vec![]);
+    }
+
+    #[test]
+    fn test_truncate_line_keeps_styles() {
+        use ratatui::style::Style;
+
+        let bold = Style::default().fg(Color::Yellow);
+        let line = Line::from(vec![
+            Span::raw("▸ "),
+            Span::styled("Rust Programming", bold),
+        ]);
+        let truncated = truncate_line(line, 8, "…");
+
+        assert_eq!(truncated.width(), 8);
+        assert_eq!(truncated.spans[1].content, "Rust …");
+        assert_eq!(truncated.spans[1].style, bold);
+    }
+
+    #[test]
+    fn test_format_relative_timestamp() {
+        use chrono::{Duration, TimeZone};
+
+        let at = |d, h, m| Local.with_ymd_and_hms(2024, 1, d, h, m, 0).unwrap();
+        let now = at(20, 12, 0);
+
+        let cases = [
+            (now - Duration::seconds(30), "now"),
+            (now - Duration::minutes(2), "2m"),
+            (now - Duration::hours(3), "3h"),
+            (at(19, 23, 0), "yesterday"),
+            (at(17, 8, 0), "3d"),
+            (at(2, 8, 0), "Jan 02"),
+            (now + Duration::minutes(5), "now"),
+        ];
+        for (timestamp, expected) in cases {
+            assert_eq!(format_relative_timestamp(timestamp, now), expected);
+        }
+    }
 }
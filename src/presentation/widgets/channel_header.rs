@@ -8,6 +8,8 @@ use ratatui::{
     widgets::{Paragraph, Widget},
 };
 
+use crate::presentation::ui::utils::{DEFAULT_ELLIPSIS, truncate_to_width};
+
 pub struct ChannelHeaderStyle {
     pub channel_name: Style,
     pub channel_icon: Style,
@@ -35,6 +37,7 @@ pub struct ChannelHeader<'a> {
     channel_icon: Option<&'a str>,
     online_count: Option<u32>,
     topic: Option<&'a str>,
+    ellipsis: &'a str,
     style: ChannelHeaderStyle,
 }
 
@@ -46,6 +49,7 @@ impl<'a> ChannelHeader<'a> {
             channel_icon: None,
             online_count: None,
             topic: None,
+            ellipsis: DEFAULT_ELLIPSIS,
             style: ChannelHeaderStyle::default(),
         }
     }
@@ -74,6 +78,13 @@ impl<'a> ChannelHeader<'a> {
         self
     }
 
+    /// Sets the marker ending a topic too long for the header.
+    #[must_use]
+    pub const fn ellipsis(mut self, ellipsis: &'a str) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
     #[must_use]
     pub const fn style(mut self, style: ChannelHeaderStyle) -> Self {
         self.style = style;
@@ -130,11 +141,7 @@ impl Widget for ChannelHeader<'_> {
             right_spans.push(Span::styled("TOPIC: ", self.style.topic));
 
             let available_width = area.width.saturating_sub(left_width + 20) as usize;
-            let truncated_topic = if topic.len() > available_width {
-                format!("{}...", &topic[..available_width.saturating_sub(3)])
-            } else {
-                topic.to_string()
-            };
+            let truncated_topic = truncate_to_width(topic, available_width, self.ellipsis);
             right_spans.push(Span::styled(
                 truncated_topic.to_uppercase(),
                 self.style.topic,
@@ -0,0 +1,143 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+use crate::application::services::markdown_parser::CodeBlockRef;
+use crate::domain::entities::MessageId;
+use crate::presentation::theme::Theme;
+
+/// Code blocks of one message, offered for copying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlockChooserState {
+    message_id: MessageId,
+    labels: Vec<String>,
+    selected: usize,
+}
+
+impl CodeBlockChooserState {
+    #[must_use]
+    pub fn new(message_id: MessageId, blocks: &[CodeBlockRef<'_>]) -> Self {
+        let labels = blocks
+            .iter()
+            .map(|block| {
+                let first_line = block
+                    .code
+                    .lines()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or_default()
+                    .trim();
+                match block.lang {
+                    Some(lang) => format!("{lang}: {first_line}"),
+                    None => first_line.to_string(),
+                }
+            })
+            .collect();
+        Self {
+            message_id,
+            labels,
+            selected: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn message_id(&self) -> MessageId {
+        self.message_id
+    }
+
+    #[must_use]
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.labels.is_empty() {
+            self.selected = (self.selected + 1) % self.labels.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.labels.is_empty() {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or(self.labels.len() - 1);
+        }
+    }
+}
+
+/// Small popup listing a message's code blocks by number.
+pub struct CodeBlockChooser<'a> {
+    state: &'a CodeBlockChooserState,
+    theme: &'a Theme,
+}
+
+impl<'a> CodeBlockChooser<'a> {
+    #[must_use]
+    pub const fn new(state: &'a CodeBlockChooserState, theme: &'a Theme) -> Self {
+        Self { state, theme }
+    }
+}
+
+impl Widget for CodeBlockChooser<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = u16::try_from(self.state.labels.len()).unwrap_or(u16::MAX);
+        let width = 60.min(area.width);
+        let height = rows.saturating_add(3).min(area.height);
+        let popup_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Copy Code Block ")
+            .title_alignment(Alignment::Center)
+            .border_style(Style::default().fg(self.theme.accent));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+        Paragraph::new("1-9/Enter: copy  Esc: cancel")
+            .style(self.theme.dimmed_style)
+            .alignment(Alignment::Center)
+            .render(hint_area, buf);
+
+        let items: Vec<ListItem> = self
+            .state
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{}. ", i + 1), self.theme.dimmed_style),
+                    Span::raw(label.clone()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(self.theme.selection_style);
+        let mut state = ListState::default().with_selected(Some(self.state.selected));
+        StatefulWidget::render(list, list_area, buf, &mut state);
+    }
+}
@@ -11,6 +11,8 @@ use crate::presentation::theme::Theme;
 pub struct ConfirmationModal {
     title: String,
     message: String,
+    confirm_label: String,
+    alternative: Option<String>,
     theme: Theme,
 }
 
@@ -20,10 +22,27 @@ impl ConfirmationModal {
         Self {
             title: title.into(),
             message: message.into(),
+            confirm_label: "Confirm".to_string(),
+            alternative: None,
             theme,
         }
     }
 
+    /// Replaces the "Confirm" label on the Enter button.
+    #[must_use]
+    pub fn with_confirm_label(mut self, label: impl Into<String>) -> Self {
+        self.confirm_label = label.into();
+        self
+    }
+
+    /// Adds a third choice between confirm and cancel. The label should
+    /// name its own key, since the modal does not handle input.
+    #[must_use]
+    pub fn with_alternative(mut self, label: Option<impl Into<String>>) -> Self {
+        self.alternative = label.map(Into::into);
+        self
+    }
+
     fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         let popup_layout = Layout::vertical([
             Constraint::Percentage((100 - percent_y) / 2),
@@ -66,14 +85,23 @@ impl Widget for ConfirmationModal {
 
         message.render(layout[0], buf);
 
-        let buttons = Line::from(vec![
-            Span::styled(
-                "Confirm (Enter)",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("   "),
-            Span::styled("Cancel (Esc)", Style::default().fg(Color::Gray)),
-        ]);
+        let mut buttons = vec![Span::styled(
+            format!("{} (Enter)", self.confirm_label),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )];
+        if let Some(alternative) = self.alternative {
+            buttons.push(Span::raw("   "));
+            buttons.push(Span::styled(
+                alternative,
+                Style::default().fg(self.theme.accent),
+            ));
+        }
+        buttons.push(Span::raw("   "));
+        buttons.push(Span::styled(
+            "Cancel (Esc)",
+            Style::default().fg(Color::Gray),
+        ));
+        let buttons = Line::from(buttons);
 
         let buttons_para = Paragraph::new(buttons).alignment(Alignment::Center);
         buttons_para.render(layout[1], buf);
@@ -1,3 +1,4 @@
+use crate::domain::ports::OutgoingAttachment;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     buffer::Buffer,
@@ -14,7 +15,11 @@ use std::{
 
 #[derive(Debug, Clone)]
 pub enum FileExplorerAction {
-    SelectFile(PathBuf),
+    SelectFiles(Vec<PathBuf>),
+    /// More files were picked than the `usize` that can still be attached.
+    TooManyFiles(usize),
+    /// A bookmark was picked whose directory no longer exists.
+    BookmarkMissing(PathBuf),
     Close,
     None,
 }
@@ -31,21 +36,109 @@ pub struct FileExplorerComponent {
     entries: Vec<FileEntry>,
     state: ListState,
     show_hidden: bool,
+    /// Attach the chosen file behind a spoiler.
+    spoiler: bool,
+    /// Directories opened with the number keys, in order.
+    bookmarks: Vec<PathBuf>,
+    /// Files checked with Space, in the order they were checked.
+    checked: Vec<PathBuf>,
+    /// Most files that may be picked at once.
+    max_files: usize,
 }
 
 impl FileExplorerComponent {
+    #[must_use]
     pub fn new() -> Self {
-        let root = env::var("HOME").map_or_else(|_| PathBuf::from("/"), PathBuf::from);
+        Self::open(None, &[])
+    }
+
+    /// Opens in `start_dir`, or the home directory when it is missing.
+    /// Bookmarks keep their configured number even when their directory is
+    /// missing. A leading `~` in any path stands for the home directory.
+    #[must_use]
+    pub fn open(start_dir: Option<&Path>, bookmarks: &[PathBuf]) -> Self {
+        let root = start_dir
+            .map(expand_home)
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(home_dir);
         let mut component = Self {
             current_dir: root.clone(),
             entries: Vec::new(),
             state: ListState::default(),
             show_hidden: false,
+            spoiler: false,
+            bookmarks: bookmarks
+                .iter()
+                .map(|dir| expand_home(dir))
+                .take(9)
+                .collect(),
+            checked: Vec::new(),
+            max_files: OutgoingAttachment::MAX_PER_MESSAGE,
         };
         component.load_entries(&root);
         component
     }
 
+    /// Caps how many files may be picked, e.g. to what is left of the
+    /// per-message attachment limit.
+    #[must_use]
+    pub const fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    #[must_use]
+    pub fn current_dir(&self) -> &Path {
+        &self.current_dir
+    }
+
+    /// Checks or unchecks the highlighted file.
+    fn toggle_checked(&mut self) -> FileExplorerAction {
+        let Some(entry) = self.selected_entry().filter(|entry| !entry.is_dir) else {
+            return FileExplorerAction::None;
+        };
+        let path = entry.path.clone();
+        if let Some(index) = self.checked.iter().position(|p| *p == path) {
+            self.checked.remove(index);
+        } else if self.checked.len() >= self.max_files {
+            return FileExplorerAction::TooManyFiles(self.max_files);
+        } else {
+            self.checked.push(path);
+        }
+        FileExplorerAction::None
+    }
+
+    /// Picks the checked files, or just `path` when none are checked.
+    fn pick(&mut self, path: PathBuf) -> FileExplorerAction {
+        let paths = if self.checked.is_empty() {
+            vec![path]
+        } else {
+            self.checked.clone()
+        };
+        if paths.len() > self.max_files {
+            return FileExplorerAction::TooManyFiles(self.max_files);
+        }
+        FileExplorerAction::SelectFiles(paths)
+    }
+
+    /// Moves to the bookmark under `digit` (1-9).
+    fn open_bookmark(&mut self, digit: usize) -> FileExplorerAction {
+        let Some(dir) = digit
+            .checked_sub(1)
+            .and_then(|index| self.bookmarks.get(index))
+            .cloned()
+        else {
+            return FileExplorerAction::None;
+        };
+        if !dir.is_dir() {
+            return FileExplorerAction::BookmarkMissing(dir);
+        }
+        self.state.select(None);
+        self.load_entries(&dir);
+        self.current_dir = dir;
+        FileExplorerAction::None
+    }
+
     pub fn load_entries(&mut self, path: &Path) {
         let selected_name = self.selected_entry().map(|e| e.name.clone());
 
@@ -111,6 +204,15 @@ impl FileExplorerComponent {
         self.load_entries(&path);
     }
 
+    pub const fn toggle_spoiler(&mut self) {
+        self.spoiler = !self.spoiler;
+    }
+
+    #[must_use]
+    pub const fn is_spoiler(&self) -> bool {
+        self.spoiler
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) -> FileExplorerAction {
         match key.code {
             KeyCode::Esc => FileExplorerAction::Close,
@@ -143,7 +245,8 @@ impl FileExplorerComponent {
                         self.load_entries(&path);
                         FileExplorerAction::None
                     } else {
-                        FileExplorerAction::SelectFile(selected.path.clone())
+                        let path = selected.path.clone();
+                        self.pick(path)
                     }
                 } else {
                     FileExplorerAction::None
@@ -167,12 +270,15 @@ impl FileExplorerComponent {
                         self.load_entries(&path);
                         FileExplorerAction::None
                     } else {
-                        FileExplorerAction::SelectFile(selected.path.clone())
+                        let path = selected.path.clone();
+                        self.pick(path)
                     }
                 } else {
                     FileExplorerAction::None
                 }
             }
+            KeyCode::Char(' ') => self.toggle_checked(),
+            KeyCode::Char(c @ '1'..='9') => self.open_bookmark(c as usize - '0' as usize),
             _ => FileExplorerAction::None,
         }
     }
@@ -212,16 +318,28 @@ impl FileExplorerComponent {
     pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
         Clear.render(area, buf);
 
-        let title = if self.show_hidden {
-            " File Explorer (H) - Select Attachment "
-        } else {
-            " File Explorer - Select Attachment "
+        let title = match (self.show_hidden, self.spoiler) {
+            (false, false) => " File Explorer - Select Attachment ",
+            (true, false) => " File Explorer (H) - Select Attachment ",
+            (false, true) => " File Explorer - Select Spoiler Attachment ",
+            (true, true) => " File Explorer (H) - Select Spoiler Attachment ",
         };
 
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .title(title)
             .title_style(Style::default().add_modifier(Modifier::BOLD));
+        if !self.checked.is_empty() {
+            block = block.title(
+                Line::from(format!(
+                    " {}/{} selected ",
+                    self.checked.len(),
+                    self.max_files
+                ))
+                .right_aligned()
+                .style(Style::default().fg(Color::Green)),
+            );
+        }
 
         let inner_area = block.inner(area);
 
@@ -236,10 +354,14 @@ impl FileExplorerComponent {
             .entries
             .iter()
             .map(|entry| {
+                let checked = self.checked.contains(&entry.path);
+                let mark = if checked { "✓ " } else { "  " };
                 let icon = if entry.is_dir { "  " } else { "  " };
-                let content = format!("{}{}", icon, entry.name);
+                let content = format!("{mark}{icon}{}", entry.name);
                 ListItem::new(content).style(Style::default().fg(if entry.is_dir {
                     Color::Blue
+                } else if checked {
+                    Color::Green
                 } else {
                     Color::White
                 }))
@@ -256,11 +378,24 @@ impl FileExplorerComponent {
 
         StatefulWidget::render(list, list_area, buf, &mut self.state);
 
-        let footer = Line::from(vec![
+        let mut footer = vec![
             Span::raw(" [Esc] Cancel | "),
+            Span::raw("[Space] Mark | "),
             Span::raw("[Enter] Select/Enter "),
-        ])
-        .style(Style::default().fg(Color::Gray));
+        ];
+        for (index, dir) in self.bookmarks.iter().enumerate() {
+            let name = dir
+                .file_name()
+                .map_or_else(|| dir.to_string_lossy(), |name| name.to_string_lossy());
+            let style = if dir.is_dir() {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            footer.push(Span::raw("| "));
+            footer.push(Span::styled(format!("[{}] {name} ", index + 1), style));
+        }
+        let footer = Line::from(footer).style(Style::default().fg(Color::Gray));
 
         Widget::render(footer, footer_area, buf);
     }
@@ -271,3 +406,169 @@ impl Default for FileExplorerComponent {
         Self::new()
     }
 }
+
+fn home_dir() -> PathBuf {
+    env::var("HOME").map_or_else(|_| PathBuf::from("/"), PathBuf::from)
+}
+
+/// Replaces a leading `~` with the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    expand_home_in(path, &home_dir())
+}
+
+/// Replaces a leading `~` with `home`.
+fn expand_home_in(path: &Path, home: &Path) -> PathBuf {
+    path.strip_prefix("~")
+        .map_or_else(|_| path.to_path_buf(), |rest| home.join(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_bookmarks_keep_their_numbers_and_open_with_number_keys() {
+        let root = tempfile::tempdir().unwrap();
+        let docs = root.path().join("docs");
+        let pics = root.path().join("pics");
+        fs::create_dir(&docs).unwrap();
+        fs::create_dir(&pics).unwrap();
+        fs::write(docs.join("notes.txt"), "hi").unwrap();
+
+        let mut explorer = FileExplorerComponent::open(
+            Some(root.path()),
+            &[docs.clone(), root.path().join("gone"), pics.clone()],
+        );
+        assert_eq!(explorer.current_dir(), root.path());
+
+        assert!(matches!(
+            explorer.handle_key(key('3')),
+            FileExplorerAction::None
+        ));
+        assert_eq!(explorer.current_dir(), pics);
+        assert!(matches!(
+            explorer.handle_key(key('2')),
+            FileExplorerAction::BookmarkMissing(path) if path == root.path().join("gone")
+        ));
+        assert_eq!(explorer.current_dir(), pics);
+        explorer.handle_key(key('1'));
+        assert_eq!(explorer.current_dir(), docs);
+        assert_eq!(explorer.selected_entry().unwrap().name, "..");
+        explorer.handle_key(key('9'));
+        assert_eq!(explorer.current_dir(), docs);
+
+        fs::remove_dir(&pics).unwrap();
+        assert!(matches!(
+            explorer.handle_key(key('3')),
+            FileExplorerAction::BookmarkMissing(path) if path == pics
+        ));
+        assert_eq!(explorer.current_dir(), docs);
+    }
+
+    /// Highlights the entry called `name`.
+    fn highlight(explorer: &mut FileExplorerComponent, name: &str) {
+        let index = explorer.entries.iter().position(|e| e.name == name);
+        explorer.state.select(index);
+    }
+
+    #[test]
+    fn test_space_toggles_files_and_enter_picks_them_all() {
+        let root = tempfile::tempdir().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(root.path().join(name), name).unwrap();
+        }
+        let mut explorer = FileExplorerComponent::open(Some(root.path()), &[]);
+        let space = key(' ');
+
+        for name in ["c.txt", "a.txt", "b.txt"] {
+            highlight(&mut explorer, name);
+            explorer.handle_key(space);
+        }
+        explorer.handle_key(space);
+        assert_eq!(
+            explorer.checked,
+            vec![root.path().join("c.txt"), root.path().join("a.txt")]
+        );
+
+        highlight(&mut explorer, "..");
+        explorer.handle_key(space);
+        assert_eq!(explorer.checked.len(), 2);
+
+        highlight(&mut explorer, "b.txt");
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(matches!(
+            explorer.handle_key(enter),
+            FileExplorerAction::SelectFiles(paths)
+                if paths == vec![root.path().join("c.txt"), root.path().join("a.txt")]
+        ));
+    }
+
+    #[test]
+    fn test_enter_without_marks_picks_highlighted_file() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.txt"), "a").unwrap();
+        let mut explorer = FileExplorerComponent::open(Some(root.path()), &[]);
+
+        highlight(&mut explorer, "a.txt");
+        assert!(matches!(
+            explorer.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            FileExplorerAction::SelectFiles(paths) if paths == vec![root.path().join("a.txt")]
+        ));
+    }
+
+    #[test]
+    fn test_marking_stops_at_file_limit() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.txt"), "a").unwrap();
+        fs::write(root.path().join("b.txt"), "b").unwrap();
+        let mut explorer = FileExplorerComponent::open(Some(root.path()), &[]).with_max_files(1);
+
+        highlight(&mut explorer, "a.txt");
+        assert!(matches!(
+            explorer.handle_key(key(' ')),
+            FileExplorerAction::None
+        ));
+        highlight(&mut explorer, "b.txt");
+        assert!(matches!(
+            explorer.handle_key(key(' ')),
+            FileExplorerAction::TooManyFiles(1)
+        ));
+        assert_eq!(explorer.checked, vec![root.path().join("a.txt")]);
+
+        let mut full = FileExplorerComponent::open(Some(root.path()), &[]).with_max_files(0);
+        highlight(&mut full, "a.txt");
+        assert!(matches!(
+            full.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            FileExplorerAction::TooManyFiles(0)
+        ));
+    }
+
+    #[test]
+    fn test_expand_home_replaces_leading_tilde() {
+        let home = Path::new("/home/someone");
+        assert_eq!(
+            expand_home_in(Path::new("~/Downloads"), home),
+            home.join("Downloads")
+        );
+        assert_eq!(expand_home_in(Path::new("~"), home), home);
+        assert_eq!(
+            expand_home_in(Path::new("/tmp/~"), home),
+            Path::new("/tmp/~")
+        );
+        assert_eq!(
+            expand_home_in(Path::new("~user/files"), home),
+            Path::new("~user/files")
+        );
+    }
+
+    #[test]
+    fn test_missing_start_dir_falls_back_to_home() {
+        let explorer = FileExplorerComponent::open(Some(Path::new("/no/such/dir")), &[]);
+        assert_eq!(explorer.current_dir(), home_dir());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::keybinding::Keybind;
 use crate::presentation::theme::Theme;
+use crate::presentation::ui::utils::{DEFAULT_ELLIPSIS, truncate_line};
 use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
@@ -8,6 +9,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Paragraph, Widget},
 };
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FocusContext {
@@ -30,6 +32,7 @@ impl FocusContext {
     }
 }
 
+#[derive(Clone)]
 pub struct FooterBarStyle {
     pub background: Style,
     pub label_style: Style,
@@ -72,6 +75,8 @@ pub struct FooterBar<'a> {
     keybindings: &'a [Keybind],
     focus_context: Option<FocusContext>,
     right_info: Option<&'a str>,
+    pending_sequence: Option<&'a [crossterm::event::KeyEvent]>,
+    ellipsis: &'a str,
     style: FooterBarStyle,
 }
 
@@ -82,6 +87,8 @@ impl<'a> FooterBar<'a> {
             keybindings,
             focus_context: None,
             right_info: None,
+            pending_sequence: None,
+            ellipsis: DEFAULT_ELLIPSIS,
             style: FooterBarStyle::default(),
         }
     }
@@ -98,6 +105,23 @@ impl<'a> FooterBar<'a> {
         self
     }
 
+    /// Shows the keys typed so far of an unfinished sequence.
+    #[must_use]
+    pub const fn pending_sequence(
+        mut self,
+        keys: Option<&'a [crossterm::event::KeyEvent]>,
+    ) -> Self {
+        self.pending_sequence = keys;
+        self
+    }
+
+    /// Sets the marker ending hints cut off by a narrow terminal.
+    #[must_use]
+    pub const fn ellipsis(mut self, ellipsis: &'a str) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
     #[must_use]
     pub const fn style(mut self, style: FooterBarStyle) -> Self {
         self.style = style;
@@ -148,6 +172,12 @@ impl<'a> FooterBar<'a> {
             spans.push(Span::raw(" "));
         }
 
+        if let Some(keys) = self.pending_sequence {
+            let prefix: String = keys.iter().map(Self::format_key).collect();
+            spans.push(Span::styled(format!(" {prefix}… "), self.style.key_style));
+            spans.push(Span::raw(" "));
+        }
+
         for (i, binding) in self
             .keybindings
             .iter()
@@ -189,10 +219,14 @@ impl Widget for FooterBar<'_> {
         }
 
         let left_spans = self.build_left_spans();
-        let left_line = Line::from(left_spans);
-        let left_para = Paragraph::new(left_line);
-        let right_width = self.right_info.map_or(0, |s| s.len() as u16);
+        let right_width = self.right_info.map_or(0, |s| s.width() as u16);
         let left_width = area.width.saturating_sub(right_width + 1);
+        let left_line = truncate_line(
+            Line::from(left_spans),
+            usize::from(left_width),
+            self.ellipsis,
+        );
+        let left_para = Paragraph::new(left_line);
 
         let left_area = Rect::new(area.x, area.y, left_width, 1);
         left_para.render(left_area, buf);
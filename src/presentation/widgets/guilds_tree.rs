@@ -1,5 +1,6 @@
 //! Guilds tree widget for server/channel navigation.
 
+use std::borrow::Cow;
 use std::collections::HashSet;
 
 use crossterm::event::KeyEvent;
@@ -19,7 +20,7 @@ use crate::domain::keybinding::Action;
 use crate::domain::ports::DirectMessageChannel;
 use crate::presentation::commands::CommandRegistry;
 use crate::presentation::theme::Theme;
-use crate::presentation::ui::utils::clean_text;
+use crate::presentation::ui::utils::{DEFAULT_ELLIPSIS, clean_text, truncate_line};
 
 /// Unique identifier for nodes in the guilds tree.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -37,6 +38,8 @@ pub enum TreeNodeId {
     Channel(ChannelId),
 
     Placeholder(GuildId),
+
+    VoiceMember(ChannelId, String),
 }
 
 impl std::fmt::Display for TreeNodeId {
@@ -49,6 +52,33 @@ impl std::fmt::Display for TreeNodeId {
             Self::Category(id) => write!(f, "cat:{id}"),
             Self::Channel(id) => write!(f, "ch:{id}"),
             Self::Placeholder(id) => write!(f, "placeholder:{id}"),
+            Self::VoiceMember(channel_id, user_id) => write!(f, "voice:{channel_id}:{user_id}"),
+        }
+    }
+}
+
+impl TreeNodeId {
+    /// Key under which the node's expansion is persisted between runs.
+    ///
+    /// Only folders and guilds are persisted; other nodes return `None`.
+    #[must_use]
+    pub fn persist_key(&self) -> Option<String> {
+        match self {
+            Self::Folder(Some(id)) => Some(format!("folder:{id}")),
+            Self::Guild(id) => Some(format!("guild:{id}")),
+            _ => None,
+        }
+    }
+
+    /// Parses a key produced by [`Self::persist_key`].
+    #[must_use]
+    pub fn from_persist_key(key: &str) -> Option<Self> {
+        let (kind, id) = key.split_once(':')?;
+        let id = id.parse::<u64>().ok()?;
+        match kind {
+            "folder" => Some(Self::Folder(Some(id))),
+            "guild" => Some(Self::Guild(GuildId(id))),
+            _ => None,
         }
     }
 }
@@ -128,6 +158,26 @@ impl GuildsTreeState {
         self.expanded.contains(node_id)
     }
 
+    /// Sorted persist keys of the expanded folders and guilds.
+    #[must_use]
+    pub fn persisted_expansion(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .expanded
+            .iter()
+            .filter_map(TreeNodeId::persist_key)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Expands the nodes named by persisted keys, ignoring unknown ones.
+    pub fn restore_expansion(&mut self, keys: &[String]) {
+        self.expanded.extend(
+            keys.iter()
+                .filter_map(|key| TreeNodeId::from_persist_key(key)),
+        );
+    }
+
     #[allow(clippy::too_many_lines)]
     pub fn handle_key(
         &mut self,
@@ -139,10 +189,7 @@ impl GuildsTreeState {
     ) -> Option<GuildsTreeAction> {
         let flattened = data.flatten(self, u16::MAX, style, use_display_name);
 
-        let current_index = self
-            .selected
-            .as_ref()
-            .and_then(|sel| flattened.iter().position(|node| &node.id == sel));
+        let current_index = self.resolve_selection(&flattened, data);
 
         match registry.find_action(key) {
             Some(Action::NavigateDown) => {
@@ -187,13 +234,14 @@ impl GuildsTreeState {
             }
             Some(Action::NavigateRight) => {
                 if let Some(selected) = &self.selected {
-                    let can_expand = matches!(
-                        selected,
+                    let can_expand = match selected {
                         TreeNodeId::Guild(_)
-                            | TreeNodeId::Category(_)
-                            | TreeNodeId::DirectMessages
-                            | TreeNodeId::Folder(_)
-                    );
+                        | TreeNodeId::Category(_)
+                        | TreeNodeId::DirectMessages
+                        | TreeNodeId::Folder(_) => true,
+                        TreeNodeId::Channel(id) => data.voice_participants(*id).is_some(),
+                        _ => false,
+                    };
 
                     if can_expand && !self.expanded.contains(selected) {
                         self.expanded.insert(selected.clone());
@@ -248,7 +296,9 @@ impl GuildsTreeState {
             Some(Action::YankId) => self.selected.as_ref().map(|node| {
                 let id = match node {
                     TreeNodeId::DirectMessages => "direct_messages".to_string(),
-                    TreeNodeId::DirectMessageUser(id) => id.clone(),
+                    TreeNodeId::DirectMessageUser(id) | TreeNodeId::VoiceMember(_, id) => {
+                        id.clone()
+                    }
                     TreeNodeId::Folder(id) => format!("{id:?}"),
                     TreeNodeId::Guild(id) | TreeNodeId::Placeholder(id) => id.to_string(),
                     TreeNodeId::Category(id) | TreeNodeId::Channel(id) => id.to_string(),
@@ -259,6 +309,25 @@ impl GuildsTreeState {
         }
     }
 
+    /// Returns the index of the selection among the visible nodes.
+    ///
+    /// A selection hidden inside a collapsed node is moved up to its nearest
+    /// visible ancestor, so navigation continues past the collapsed children.
+    fn resolve_selection(
+        &mut self,
+        flattened: &[FlattenedNode<'_>],
+        data: &GuildsTreeData,
+    ) -> Option<usize> {
+        let mut node = self.selected.clone()?;
+        loop {
+            if let Some(index) = flattened.iter().position(|n| n.id == node) {
+                self.selected = Some(node);
+                return Some(index);
+            }
+            node = data.parent_node(&node)?;
+        }
+    }
+
     fn get_selection_action(&self) -> Option<GuildsTreeAction> {
         self.selected.as_ref().and_then(|node| match node {
             TreeNodeId::Channel(id) => Some(GuildsTreeAction::SelectChannel(*id)),
@@ -279,6 +348,7 @@ impl Default for GuildsTreeState {
 
 /// Style configuration for the guilds tree.
 #[allow(missing_docs)]
+#[derive(Clone)]
 pub struct GuildsTreeStyle {
     pub border_style: Style,
     pub border_style_focused: Style,
@@ -376,6 +446,108 @@ pub struct SortedGuildChannels {
 }
 
 impl SortedGuildChannels {
+    /// Groups a guild's channels under their categories in Discord order,
+    /// dropping categories left without children.
+    #[must_use]
+    pub fn new(channels: Vec<Channel>) -> Self {
+        let mut orphans = Vec::new();
+        let mut threads = Vec::new();
+        let mut category_map: std::collections::HashMap<ChannelId, (Channel, Vec<Channel>)> =
+            std::collections::HashMap::new();
+
+        let mut potential_children = Vec::new();
+
+        for channel in channels {
+            if channel.kind().is_thread() {
+                threads.push(channel);
+            } else if channel.kind().is_category() {
+                category_map.insert(channel.id(), (channel, Vec::new()));
+            } else {
+                potential_children.push(channel);
+            }
+        }
+
+        for channel in potential_children {
+            if let Some(parent_id) = channel.parent_id() {
+                if let Some((_, children)) = category_map.get_mut(&parent_id) {
+                    children.push(channel);
+                } else {
+                    orphans.push(channel);
+                }
+            } else {
+                orphans.push(channel);
+            }
+        }
+
+        orphans.sort_by(discord_channel_order);
+        threads.sort_by(discord_channel_order);
+
+        let mut categories: Vec<CategoryNode> = category_map
+            .into_values()
+            .map(|(category, mut children)| {
+                children.sort_by(discord_channel_order);
+                CategoryNode { category, children }
+            })
+            .filter(|node| !node.children.is_empty())
+            .collect();
+
+        categories.sort_by(|a, b| discord_channel_order(&a.category, &b.category));
+
+        Self {
+            orphans,
+            categories,
+            threads,
+        }
+    }
+
+    /// Refreshes each channel's unread flag and mention count.
+    pub fn update_unread_status(
+        &mut self,
+        read_states: &std::collections::HashMap<ChannelId, ReadState>,
+    ) {
+        let update_channel = |channel: &mut Channel| {
+            if let Some(read_state) = read_states.get(&channel.id()) {
+                if let Some(last_msg_id) = channel.last_message_id() {
+                    let is_unread = if let Some(last_read_id) = read_state.last_read_message_id {
+                        last_msg_id.as_u64() > last_read_id.as_u64()
+                    } else {
+                        true
+                    };
+                    channel.set_unread(is_unread);
+                } else {
+                    channel.set_unread(false);
+                }
+                channel.set_mention_count(read_state.mention_count);
+            } else {
+                channel.set_unread(false);
+                channel.set_mention_count(0);
+            }
+        };
+
+        for channel in &mut self.orphans {
+            update_channel(channel);
+        }
+        for channel in &mut self.threads {
+            update_channel(channel);
+        }
+        for cat in &mut self.categories {
+            update_channel(&mut cat.category);
+            for channel in &mut cat.children {
+                update_channel(channel);
+            }
+        }
+    }
+
+    /// "Guild › #channel" for every unread channel, in tree order.
+    pub fn unread_channel_names<'a>(
+        &'a self,
+        guild_name: &'a str,
+    ) -> impl Iterator<Item = String> + 'a {
+        self.iter()
+            .filter(|c| c.has_unread() && !c.kind().is_category())
+            .map(move |c| format!("{guild_name} › #{}", c.name()))
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Channel> {
         self.orphans
             .iter()
@@ -388,15 +560,64 @@ impl SortedGuildChannels {
     }
 }
 
+/// Orders sibling channels the way the Discord client does.
+///
+/// Text-like channels always sort above voice channels, then by `position`,
+/// with the channel id breaking ties between equal positions.
+fn discord_channel_order(a: &Channel, b: &Channel) -> std::cmp::Ordering {
+    let key = |channel: &Channel| {
+        (
+            channel.kind().is_voice(),
+            channel.position(),
+            channel.id().as_u64(),
+        )
+    };
+    key(a).cmp(&key(b))
+}
+
+/// A user connected to a voice channel, listed under it when expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceParticipant {
+    pub user_id: String,
+    pub username: String,
+    /// Guild nickname or global display name.
+    pub display_name: Option<String>,
+    pub muted: bool,
+    pub deafened: bool,
+}
+
+impl VoiceParticipant {
+    #[must_use]
+    pub fn name(&self, use_display_name: bool) -> &str {
+        match &self.display_name {
+            Some(name) if use_display_name && !name.is_empty() => name,
+            _ => &self.username,
+        }
+    }
+
+    const fn glyph(&self) -> &'static str {
+        if self.deafened {
+            "🔕 "
+        } else if self.muted {
+            "🔇 "
+        } else {
+            ""
+        }
+    }
+}
+
 pub struct GuildsTreeData {
     guilds: Vec<Guild>,
     folders: Vec<GuildFolder>,
     group_guilds: bool,
+    /// Show the only channel of a category in place of the category.
+    flatten_single_channel_categories: bool,
     channels_by_guild: std::collections::HashMap<GuildId, SortedGuildChannels>,
     dm_users: Vec<DirectMessageChannel>,
     active_guild_id: Option<GuildId>,
     active_channel_id: Option<ChannelId>,
     active_dm_user_id: Option<String>,
+    voice_participants: std::collections::HashMap<ChannelId, Vec<VoiceParticipant>>,
 }
 
 impl GuildsTreeData {
@@ -406,11 +627,13 @@ impl GuildsTreeData {
             guilds: Vec::new(),
             folders: Vec::new(),
             group_guilds: false,
+            flatten_single_channel_categories: false,
             channels_by_guild: std::collections::HashMap::new(),
             dm_users: Vec::new(),
             active_guild_id: None,
             active_channel_id: None,
             active_dm_user_id: None,
+            voice_participants: std::collections::HashMap::new(),
         }
     }
 
@@ -419,6 +642,19 @@ impl GuildsTreeData {
         self.sort_guilds();
     }
 
+    /// Adds guilds not yet in the tree, leaving known ones untouched.
+    pub fn add_guilds(&mut self, guilds: Vec<Guild>) {
+        let before = self.guilds.len();
+        for guild in guilds {
+            if !self.guilds.iter().any(|g| g.id() == guild.id()) {
+                self.guilds.push(guild);
+            }
+        }
+        if self.guilds.len() != before {
+            self.sort_guilds();
+        }
+    }
+
     pub fn set_folders(&mut self, folders: Vec<GuildFolder>) {
         self.folders = folders;
         self.sort_guilds();
@@ -453,6 +689,14 @@ impl GuildsTreeData {
         self.group_guilds = group;
     }
 
+    pub fn set_flatten_single_channel_categories(&mut self, flatten: bool) {
+        self.flatten_single_channel_categories = flatten;
+    }
+
+    fn is_flattened(&self, category: &CategoryNode) -> bool {
+        self.flatten_single_channel_categories && category.children.len() == 1
+    }
+
     pub fn set_channels(&mut self, guild_id: GuildId, channels: Vec<Channel>) {
         tracing::debug!(
             guild_id = %guild_id,
@@ -461,75 +705,8 @@ impl GuildsTreeData {
             "Storing channels for guild"
         );
 
-        let mut orphans = Vec::new();
-        let mut threads = Vec::new();
-        let mut category_map: std::collections::HashMap<ChannelId, (Channel, Vec<Channel>)> =
-            std::collections::HashMap::new();
-
-        let temp_channels = channels;
-        let mut potential_children = Vec::new();
-
-        for channel in temp_channels {
-            if channel.kind().is_thread() {
-                threads.push(channel);
-            } else if channel.kind().is_category() {
-                category_map.insert(channel.id(), (channel, Vec::new()));
-            } else {
-                potential_children.push(channel);
-            }
-        }
-
-        for channel in potential_children {
-            if let Some(parent_id) = channel.parent_id() {
-                if let Some((_, children)) = category_map.get_mut(&parent_id) {
-                    children.push(channel);
-                } else {
-                    orphans.push(channel);
-                }
-            } else {
-                orphans.push(channel);
-            }
-        }
-
-        let sort_fn = |a: &Channel, b: &Channel| match a.position().cmp(&b.position()) {
-            std::cmp::Ordering::Equal => {
-                let a_is_text = a.kind().is_text_based() || a.kind() == ChannelKind::Forum;
-                let b_is_text = b.kind().is_text_based() || b.kind() == ChannelKind::Forum;
-
-                match (a_is_text, b_is_text) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => match (a.kind() as u8).cmp(&(b.kind() as u8)) {
-                        std::cmp::Ordering::Equal => a.id().as_u64().cmp(&b.id().as_u64()),
-                        other => other,
-                    },
-                }
-            }
-            other => other,
-        };
-
-        orphans.sort_by(sort_fn);
-        threads.sort_by(sort_fn);
-
-        let mut categories: Vec<CategoryNode> = category_map
-            .into_values()
-            .map(|(category, mut children)| {
-                children.sort_by(sort_fn);
-                CategoryNode { category, children }
-            })
-            .filter(|node| !node.children.is_empty())
-            .collect();
-
-        categories.sort_by(|a, b| sort_fn(&a.category, &b.category));
-
-        self.channels_by_guild.insert(
-            guild_id,
-            SortedGuildChannels {
-                orphans,
-                categories,
-                threads,
-            },
-        );
+        self.channels_by_guild
+            .insert(guild_id, SortedGuildChannels::new(channels));
     }
 
     pub fn set_dm_users(&mut self, mut users: Vec<DirectMessageChannel>) {
@@ -554,6 +731,21 @@ impl GuildsTreeData {
         self.channels_by_guild.get(&guild_id)
     }
 
+    /// Replaces the users shown under each voice channel.
+    pub fn set_voice_participants(
+        &mut self,
+        participants: std::collections::HashMap<ChannelId, Vec<VoiceParticipant>>,
+    ) {
+        self.voice_participants = participants;
+        self.voice_participants.retain(|_, users| !users.is_empty());
+    }
+
+    /// Users connected to a voice channel, or `None` when it is empty.
+    #[must_use]
+    pub fn voice_participants(&self, channel_id: ChannelId) -> Option<&[VoiceParticipant]> {
+        self.voice_participants.get(&channel_id).map(Vec::as_slice)
+    }
+
     #[must_use]
     pub fn dm_users(&self) -> &[DirectMessageChannel] {
         &self.dm_users
@@ -627,6 +819,39 @@ impl GuildsTreeData {
         None
     }
 
+    /// Returns the node that directly contains `node` in the tree.
+    #[must_use]
+    pub fn parent_node(&self, node: &TreeNodeId) -> Option<TreeNodeId> {
+        match node {
+            TreeNodeId::DirectMessages | TreeNodeId::Folder(_) => None,
+            TreeNodeId::DirectMessageUser(_) => Some(TreeNodeId::DirectMessages),
+            TreeNodeId::Placeholder(guild_id) => Some(TreeNodeId::Guild(*guild_id)),
+            TreeNodeId::VoiceMember(channel_id, _) => Some(TreeNodeId::Channel(*channel_id)),
+            TreeNodeId::Guild(guild_id) => {
+                if !self.group_guilds {
+                    return None;
+                }
+                self.folders
+                    .iter()
+                    .find(|folder| folder.id.is_some() && folder.guild_ids.contains(guild_id))
+                    .map(|folder| TreeNodeId::Folder(folder.id))
+            }
+            TreeNodeId::Category(channel_id) | TreeNodeId::Channel(channel_id) => {
+                let guild_id = self.find_guild_for_channel(*channel_id)?;
+                let category = self.channels_by_guild.get(&guild_id).and_then(|sorted| {
+                    sorted
+                        .categories
+                        .iter()
+                        .find(|cat| cat.children.iter().any(|c| c.id() == *channel_id))
+                        .filter(|cat| !self.is_flattened(cat))
+                });
+                Some(category.map_or(TreeNodeId::Guild(guild_id), |cat| {
+                    TreeNodeId::Category(cat.category.id())
+                }))
+            }
+        }
+    }
+
     #[must_use]
     pub fn get_channel(&self, channel_id: ChannelId) -> Option<&Channel> {
         for sorted in self.channels_by_guild.values() {
@@ -719,6 +944,7 @@ impl GuildsTreeData {
                         width,
                         style,
                         children_base_indent,
+                        use_display_name,
                     );
                 }
                 RootItem::Guild(guild) => {
@@ -728,8 +954,8 @@ impl GuildsTreeData {
                         state,
                         width,
                         style,
-                        "",
                         children_base_indent,
+                        use_display_name,
                     );
                 }
             }
@@ -743,38 +969,7 @@ impl GuildsTreeData {
         read_states: &std::collections::HashMap<ChannelId, ReadState>,
     ) {
         for sorted in self.channels_by_guild.values_mut() {
-            let update_channel = |channel: &mut Channel| {
-                if let Some(read_state) = read_states.get(&channel.id()) {
-                    if let Some(last_msg_id) = channel.last_message_id() {
-                        let is_unread = if let Some(last_read_id) = read_state.last_read_message_id
-                        {
-                            last_msg_id.as_u64() > last_read_id.as_u64()
-                        } else {
-                            true
-                        };
-                        channel.set_unread(is_unread);
-                    } else {
-                        channel.set_unread(false);
-                    }
-                    channel.set_mention_count(read_state.mention_count);
-                } else {
-                    channel.set_unread(false);
-                    channel.set_mention_count(0);
-                }
-            };
-
-            for channel in &mut sorted.orphans {
-                update_channel(channel);
-            }
-            for channel in &mut sorted.threads {
-                update_channel(channel);
-            }
-            for cat in &mut sorted.categories {
-                update_channel(&mut cat.category);
-                for channel in &mut cat.children {
-                    update_channel(channel);
-                }
-            }
+            sorted.update_unread_status(read_states);
         }
 
         for guild in &mut self.guilds {
@@ -890,7 +1085,9 @@ impl GuildsTreeData {
 
                 let mut clean_name = clean_text(&dm.recipient_username);
 
-                if let Ok(_channel_id) = dm.channel_id.parse::<u64>() {
+                if dm.is_group() {
+                    clean_name = clean_text(&dm.name(use_display_name));
+                } else if let Ok(_channel_id) = dm.channel_id.parse::<u64>() {
                     let user = User::new(
                         dm.recipient_id.clone(),
                         dm.recipient_username.clone(),
@@ -909,7 +1106,7 @@ impl GuildsTreeData {
                 let mut spans = vec![
                     Span::styled(children_base_indent, style.tree_guide_style),
                     Span::styled(prefix, style.tree_guide_style),
-                    Span::styled("@ ", current_style),
+                    Span::styled(if dm.is_group() { "# " } else { "@ " }, current_style),
                     Span::styled(clean_name.clone(), current_style),
                 ];
 
@@ -949,6 +1146,7 @@ impl GuildsTreeData {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_folder_node<'a>(
         &'a self,
         nodes: &mut Vec<FlattenedNode<'a>>,
@@ -957,18 +1155,13 @@ impl GuildsTreeData {
         width: u16,
         style: &GuildsTreeStyle,
         children_base_indent: &'a str,
+        use_display_name: bool,
     ) {
         let expanded = state.expanded.contains(&TreeNodeId::Folder(folder.id));
-        let folder_name = folder.name.as_deref().unwrap_or("Folder");
+        let folder_name = self.folder_name(folder);
         let folder_icon = " ";
 
-        let mut folder_style = style.folder_style;
-        if let Some(color) = folder.color {
-            let r = ((color >> 16) & 0xFF) as u8;
-            let g = ((color >> 8) & 0xFF) as u8;
-            let b = (color & 0xFF) as u8;
-            folder_style = folder_style.fg(Color::Rgb(r, g, b));
-        }
+        let folder_style = folder_style(folder, style);
 
         let arrow = if expanded { "▾ " } else { "▸ " };
         nodes.push(FlattenedNode {
@@ -1003,12 +1196,114 @@ impl GuildsTreeData {
                         children_base_indent,
                         prefix,
                         guild_sibling_indent,
+                        use_display_name,
                     );
                 }
             }
         }
     }
 
+    fn guild_style(&self, guild: &Guild, style: &GuildsTreeStyle) -> Style {
+        if self.active_guild_id() == Some(guild.id()) {
+            style.active_guild_style
+        } else if guild.has_unread() {
+            style.guild_unread_style
+        } else {
+            style.guild_style
+        }
+    }
+
+    /// Whether a guild is listed inside a folder rather than at the root.
+    fn is_in_folder(&self, guild_id: GuildId) -> bool {
+        self.group_guilds
+            && self
+                .folders
+                .iter()
+                .any(|f| f.id.is_some() && f.guild_ids.contains(&guild_id))
+    }
+
+    /// A root node labelled with the initials of its name, keeping the
+    /// arrow, folder icon and mention marker; `None` for other nodes.
+    fn compact_label(
+        &self,
+        id: &TreeNodeId,
+        state: &GuildsTreeState,
+        style: &GuildsTreeStyle,
+    ) -> Option<Line<'static>> {
+        let (icon, name, name_style, marker) = match id {
+            TreeNodeId::DirectMessages => {
+                let mentions: u32 = self.dm_users.iter().map(|dm| dm.mention_count).sum();
+                let marker = if mentions > 0 {
+                    Some(style.mention_style)
+                } else if self.dm_users.iter().any(|dm| dm.has_unread) {
+                    Some(style.channel_unread_style)
+                } else {
+                    None
+                };
+                (
+                    None,
+                    "Direct Messages".to_string(),
+                    Style::default(),
+                    marker,
+                )
+            }
+            TreeNodeId::Guild(guild_id) if !self.is_in_folder(*guild_id) => {
+                let guild = self.guilds.iter().find(|g| g.id() == *guild_id)?;
+                let marker = (guild.mention_count() > 0).then_some(style.mention_style);
+                (
+                    None,
+                    guild.name().to_string(),
+                    self.guild_style(guild, style),
+                    marker,
+                )
+            }
+            TreeNodeId::Folder(folder_id) => {
+                let folder = self.folders.iter().find(|f| f.id == *folder_id)?;
+                let folder_style = folder_style(folder, style);
+                (
+                    Some(Span::styled(" ", folder_style)),
+                    self.folder_name(folder).into_owned(),
+                    folder_style,
+                    None,
+                )
+            }
+            _ => return None,
+        };
+
+        let arrow = if state.expanded.contains(id) {
+            "▾ "
+        } else {
+            "▸ "
+        };
+        let mut spans = vec![Span::styled(arrow, style.tree_guide_style)];
+        spans.extend(icon);
+        spans.push(Span::styled(initials(&name), name_style));
+        if let Some(marker) = marker {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled("⦁", marker));
+        }
+        Some(Line::from(spans))
+    }
+
+    /// A folder's own name, or the initials of its guilds when it has none,
+    /// as the Discord client shows them.
+    fn folder_name<'a>(&self, folder: &'a GuildFolder) -> Cow<'a, str> {
+        if let Some(name) = folder.name.as_deref().filter(|n| !n.trim().is_empty()) {
+            return Cow::Borrowed(name);
+        }
+        let members: Vec<String> = folder
+            .guild_ids
+            .iter()
+            .filter_map(|id| self.guilds.iter().find(|g| g.id() == *id))
+            .map(|guild| initials(guild.name()))
+            .collect();
+        if members.is_empty() {
+            Cow::Borrowed("Folder")
+        } else {
+            Cow::Owned(members.join(", "))
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn render_guild_node_nested<'a>(
         &'a self,
@@ -1020,18 +1315,12 @@ impl GuildsTreeData {
         base_indent_1: &'a str,
         connector: &'a str,
         base_indent_2: &'a str,
+        use_display_name: bool,
     ) {
         let guild_id = guild.id();
         let expanded = state.expanded.contains(&TreeNodeId::Guild(guild_id));
 
-        let is_active = self.active_guild_id() == Some(guild_id);
-        let guild_style = if is_active {
-            style.active_guild_style
-        } else if guild.has_unread() {
-            style.guild_unread_style
-        } else {
-            style.guild_style
-        };
+        let guild_style = self.guild_style(guild, style);
 
         let clean_name = clean_text(guild.name());
         let arrow = if expanded { "▾ " } else { "▸ " };
@@ -1084,6 +1373,7 @@ impl GuildsTreeData {
                     style,
                     base_indent_1,
                     base_indent_2,
+                    use_display_name,
                 );
             } else {
                 nodes.push(FlattenedNode {
@@ -1108,20 +1398,13 @@ impl GuildsTreeData {
         state: &GuildsTreeState,
         width: u16,
         style: &GuildsTreeStyle,
-        _prefix_unused: &'a str,
         children_base_indent: &'a str,
+        use_display_name: bool,
     ) {
         let guild_id = guild.id();
         let expanded = state.expanded.contains(&TreeNodeId::Guild(guild_id));
 
-        let is_active = self.active_guild_id() == Some(guild_id);
-        let guild_style = if is_active {
-            style.active_guild_style
-        } else if guild.has_unread() {
-            style.guild_unread_style
-        } else {
-            style.guild_style
-        };
+        let guild_style = self.guild_style(guild, style);
 
         let clean_name = clean_text(guild.name());
         let arrow = if expanded { "▾ " } else { "▸ " };
@@ -1156,7 +1439,15 @@ impl GuildsTreeData {
 
         if expanded {
             if let Some(channels) = self.channels(guild_id) {
-                self.flatten_channels(nodes, channels, state, width, style, children_base_indent);
+                self.flatten_channels(
+                    nodes,
+                    channels,
+                    state,
+                    width,
+                    style,
+                    children_base_indent,
+                    use_display_name,
+                );
             } else {
                 nodes.push(FlattenedNode {
                     id: TreeNodeId::Placeholder(guild_id),
@@ -1171,6 +1462,7 @@ impl GuildsTreeData {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn flatten_channels<'a>(
         &'a self,
         nodes: &mut Vec<FlattenedNode<'a>>,
@@ -1179,8 +1471,18 @@ impl GuildsTreeData {
         width: u16,
         style: &GuildsTreeStyle,
         base_indent: &'a str,
+        use_display_name: bool,
     ) {
-        self.flatten_channels_nested(nodes, channels, state, width, style, base_indent, "");
+        self.flatten_channels_nested(
+            nodes,
+            channels,
+            state,
+            width,
+            style,
+            base_indent,
+            "",
+            use_display_name,
+        );
     }
 
     #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
@@ -1193,21 +1495,22 @@ impl GuildsTreeData {
         style: &GuildsTreeStyle,
         base_indent_1: &'a str,
         base_indent_2: &'a str,
+        use_display_name: bool,
     ) {
         for (i, channel) in channels.orphans.iter().enumerate() {
             let is_last = i == channels.orphans.len() - 1 && channels.categories.is_empty();
             let prefix = if is_last { "└── " } else { "├── " };
-            if let Some(node) = self.create_channel_node(
+            self.push_channel_node(
+                nodes,
                 channel,
                 1,
                 prefix,
+                state,
                 width,
                 style,
-                base_indent_1,
-                base_indent_2,
-            ) {
-                nodes.push(node);
-            }
+                (base_indent_1, base_indent_2),
+                use_display_name,
+            );
         }
 
         for (i, cat_node) in channels.categories.iter().enumerate() {
@@ -1219,6 +1522,21 @@ impl GuildsTreeData {
             };
             let child_indent_comp = if is_last_category { "    " } else { "│   " };
 
+            if self.is_flattened(cat_node) {
+                self.push_channel_node(
+                    nodes,
+                    &cat_node.children[0],
+                    1,
+                    cat_prefix,
+                    state,
+                    width,
+                    style,
+                    (base_indent_1, base_indent_2),
+                    use_display_name,
+                );
+                continue;
+            }
+
             let category = &cat_node.category;
             let expanded = state
                 .expanded
@@ -1287,22 +1605,80 @@ impl GuildsTreeData {
                         "├── "
                     });
 
-                    if let Some(node) = self.create_channel_node(
+                    self.push_channel_node(
+                        nodes,
                         child,
                         2,
                         &prefix,
+                        state,
                         width,
                         style,
-                        base_indent_1,
-                        base_indent_2,
-                    ) {
-                        nodes.push(node);
-                    }
+                        (base_indent_1, base_indent_2),
+                        use_display_name,
+                    );
                 }
             }
         }
     }
 
+    /// Pushes a channel's node and, when it is an expanded voice channel, a
+    /// node for each user connected to it.
+    #[allow(clippy::too_many_arguments)]
+    fn push_channel_node<'a>(
+        &'a self,
+        nodes: &mut Vec<FlattenedNode<'a>>,
+        channel: &'a Channel,
+        depth: usize,
+        prefix: &str,
+        state: &GuildsTreeState,
+        width: u16,
+        style: &GuildsTreeStyle,
+        (base_indent_1, base_indent_2): (&'a str, &'a str),
+        use_display_name: bool,
+    ) {
+        let Some(node) = self.create_channel_node(
+            channel,
+            depth,
+            prefix,
+            width,
+            style,
+            base_indent_1,
+            base_indent_2,
+        ) else {
+            return;
+        };
+        let expanded = state.expanded.contains(&node.id);
+        nodes.push(node);
+
+        let Some(participants) = self.voice_participants(channel.id()).filter(|_| expanded) else {
+            return;
+        };
+        // The channel's own branch continues down past its participants.
+        let guide = format!(
+            "{base_indent_1}{base_indent_2}{}",
+            prefix.replace("├── ", "│   ").replace("└── ", "    ")
+        );
+        for (i, participant) in participants.iter().enumerate() {
+            let branch = if i == participants.len() - 1 {
+                "└── "
+            } else {
+                "├── "
+            };
+            nodes.push(FlattenedNode {
+                id: TreeNodeId::VoiceMember(channel.id(), participant.user_id.clone()),
+                label: Line::from(vec![
+                    Span::styled(format!("{guide}{branch}"), style.tree_guide_style),
+                    Span::styled(participant.glyph(), style.channel_style),
+                    Span::styled(
+                        clean_text(participant.name(use_display_name)),
+                        style.channel_style,
+                    ),
+                ]),
+                depth: depth + 1,
+            });
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn create_channel_node<'a>(
         &'a self,
@@ -1339,9 +1715,15 @@ impl GuildsTreeData {
         let dot_width = 1;
         let padding_right = 1;
 
+        let occupants = self
+            .voice_participants(channel.id())
+            .map(|users| format!(" ({})", users.len()))
+            .unwrap_or_default();
+
         let max_name_width = width
             .saturating_sub(prefix_width)
             .saturating_sub(channel_icon_width)
+            .saturating_sub(u16::try_from(occupants.len()).unwrap_or(0))
             .saturating_sub(dot_width)
             .saturating_sub(padding_right);
 
@@ -1371,6 +1753,9 @@ impl GuildsTreeData {
         spans.push(Span::styled(prefix.to_string(), style.tree_guide_style));
         spans.push(Span::styled(channel_icon.to_string(), channel_style));
         spans.push(Span::styled(clean_name.clone(), channel_style));
+        if !occupants.is_empty() {
+            spans.push(Span::styled(occupants.clone(), style.tree_guide_style));
+        }
 
         if channel.mention_count() > 0 || channel.has_unread() {
             let used_width = u16::try_from(unicode_width::UnicodeWidthStr::width(base_indent_1))
@@ -1384,7 +1769,8 @@ impl GuildsTreeData {
                 .saturating_add(
                     u16::try_from(unicode_width::UnicodeWidthStr::width(clean_name.as_str()))
                         .unwrap_or(0),
-                );
+                )
+                .saturating_add(u16::try_from(occupants.len()).unwrap_or(0));
             let total_available = width
                 .saturating_sub(dot_width)
                 .saturating_sub(padding_right);
@@ -1423,6 +1809,8 @@ pub struct GuildsTree<'a> {
     style: GuildsTreeStyle,
     title: &'a str,
     use_display_name: bool,
+    compact: bool,
+    ellipsis: &'a str,
 }
 
 impl<'a> GuildsTree<'a> {
@@ -1433,6 +1821,8 @@ impl<'a> GuildsTree<'a> {
             style: GuildsTreeStyle::default(),
             title: "Guilds",
             use_display_name: true,
+            compact: false,
+            ellipsis: DEFAULT_ELLIPSIS,
         }
     }
 
@@ -1453,6 +1843,45 @@ impl<'a> GuildsTree<'a> {
         self.use_display_name = use_display_name;
         self
     }
+
+    /// Shows top-level nodes by their initials, except for the selected one.
+    #[must_use]
+    pub const fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Sets the marker ending names too long for the tree's width.
+    #[must_use]
+    pub const fn ellipsis(mut self, ellipsis: &'a str) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+}
+
+/// Initials of each word in a name, e.g. "Rust Programming" -> "RP".
+fn initials(name: &str) -> String {
+    let initials: String = clean_text(name)
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(3)
+        .collect();
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// A folder's style, tinted with its color when it has one.
+fn folder_style(folder: &GuildFolder, style: &GuildsTreeStyle) -> Style {
+    let Some(color) = folder.color else {
+        return style.folder_style;
+    };
+    let r = ((color >> 16) & 0xFF) as u8;
+    let g = ((color >> 8) & 0xFF) as u8;
+    let b = (color & 0xFF) as u8;
+    style.folder_style.fg(Color::Rgb(r, g, b))
 }
 
 impl StatefulWidget for GuildsTree<'_> {
@@ -1496,7 +1925,13 @@ impl StatefulWidget for GuildsTree<'_> {
             .map(|node| {
                 let is_selected = state.selected.as_ref() == Some(&node.id);
 
-                let mut label = node.label.clone();
+                let mut label = if self.compact && !is_selected {
+                    self.data
+                        .compact_label(&node.id, state, &self.style)
+                        .unwrap_or_else(|| node.label.clone())
+                } else {
+                    node.label.clone()
+                };
 
                 if is_selected {
                     let selected_style = self.style.selected_style;
@@ -1521,7 +1956,11 @@ impl StatefulWidget for GuildsTree<'_> {
                     }
                 }
 
-                ListItem::new(label)
+                ListItem::new(truncate_line(
+                    label,
+                    usize::from(inner_area.width),
+                    self.ellipsis,
+                ))
             })
             .collect();
 
@@ -1537,6 +1976,28 @@ impl StatefulWidget for GuildsTree<'_> {
 mod tests {
     use super::*;
     use crossterm::event::{KeyCode, KeyModifiers};
+    use test_case::test_case;
+
+    #[test]
+    fn test_persisted_expansion_skips_transient_nodes() {
+        let mut state = GuildsTreeState::new();
+        state.expand(TreeNodeId::Folder(Some(3)));
+        state.expand(TreeNodeId::Guild(GuildId(7)));
+        state.expand(TreeNodeId::Category(ChannelId(11)));
+        state.expand(TreeNodeId::DirectMessages);
+        state.select(TreeNodeId::Guild(GuildId(7)));
+
+        let keys = state.persisted_expansion();
+        assert_eq!(keys, vec!["folder:3".to_string(), "guild:7".to_string()]);
+
+        let mut restored = GuildsTreeState::new();
+        restored.restore_expansion(&keys);
+        restored.restore_expansion(&["bogus".to_string(), "guild:x".to_string()]);
+        assert!(restored.is_expanded(&TreeNodeId::Folder(Some(3))));
+        assert!(restored.is_expanded(&TreeNodeId::Guild(GuildId(7))));
+        assert!(!restored.is_expanded(&TreeNodeId::Category(ChannelId(11))));
+        assert_eq!(restored.selected(), None);
+    }
 
     #[test]
     fn test_guilds_tree_state_creation() {
@@ -1687,4 +2148,254 @@ mod tests {
             "Thread should not appear in the flattened tree"
         );
     }
+
+    #[test]
+    fn test_channels_follow_discord_order() {
+        let mut data = GuildsTreeData::new();
+        let guild_id = GuildId(1);
+        data.set_guilds(vec![Guild::new(guild_id, "Test Guild")]);
+
+        // Fetch order deliberately differs from display order.
+        let channels = vec![
+            Channel::new(40_u64, "Voice", ChannelKind::Category)
+                .with_guild(guild_id)
+                .with_position(1),
+            Channel::new(41_u64, "lounge", ChannelKind::Voice)
+                .with_guild(guild_id)
+                .with_parent(40_u64)
+                .with_position(0),
+            Channel::new(42_u64, "voice-text", ChannelKind::Text)
+                .with_guild(guild_id)
+                .with_parent(40_u64)
+                .with_position(5),
+            Channel::new(30_u64, "Text", ChannelKind::Category)
+                .with_guild(guild_id)
+                .with_position(0),
+            Channel::new(32_u64, "off-topic", ChannelKind::Text)
+                .with_guild(guild_id)
+                .with_parent(30_u64)
+                .with_position(1),
+            Channel::new(31_u64, "general", ChannelKind::Text)
+                .with_guild(guild_id)
+                .with_parent(30_u64)
+                .with_position(1),
+            Channel::new(33_u64, "forum", ChannelKind::Forum)
+                .with_guild(guild_id)
+                .with_parent(30_u64)
+                .with_position(0),
+            Channel::new(11_u64, "afk", ChannelKind::Voice)
+                .with_guild(guild_id)
+                .with_position(0),
+            Channel::new(10_u64, "rules", ChannelKind::Text)
+                .with_guild(guild_id)
+                .with_position(3),
+        ];
+        data.set_channels(guild_id, channels);
+
+        let mut state = GuildsTreeState::new();
+        state.expand(TreeNodeId::Guild(guild_id));
+        state.expand(TreeNodeId::Category(ChannelId(30)));
+        state.expand(TreeNodeId::Category(ChannelId(40)));
+
+        let style = GuildsTreeStyle::default();
+        let order: Vec<u64> = data
+            .flatten(&state, 100, &style, true)
+            .iter()
+            .filter_map(|node| match node.id {
+                TreeNodeId::Channel(id) | TreeNodeId::Category(id) => Some(id.as_u64()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(order, vec![10, 11, 30, 33, 31, 32, 40, 42, 41]);
+    }
+
+    fn collapsed_category_fixture() -> (GuildsTreeData, GuildsTreeState) {
+        let mut data = GuildsTreeData::new();
+        let guild_id = GuildId(1);
+        data.set_guilds(vec![Guild::new(guild_id, "Test Guild")]);
+        data.set_channels(
+            guild_id,
+            vec![
+                Channel::new(30_u64, "Text", ChannelKind::Category)
+                    .with_guild(guild_id)
+                    .with_position(0),
+                Channel::new(31_u64, "general", ChannelKind::Text)
+                    .with_guild(guild_id)
+                    .with_parent(30_u64),
+                Channel::new(32_u64, "off-topic", ChannelKind::Text)
+                    .with_guild(guild_id)
+                    .with_parent(30_u64)
+                    .with_position(1),
+                Channel::new(40_u64, "More", ChannelKind::Category)
+                    .with_guild(guild_id)
+                    .with_position(1),
+                Channel::new(41_u64, "memes", ChannelKind::Text)
+                    .with_guild(guild_id)
+                    .with_parent(40_u64),
+            ],
+        );
+
+        let mut state = GuildsTreeState::new();
+        state.expand(TreeNodeId::Guild(guild_id));
+        (data, state)
+    }
+
+    #[test]
+    fn test_navigation_skips_collapsed_category_children() {
+        let (data, mut state) = collapsed_category_fixture();
+        let registry = CommandRegistry::default();
+        let style = GuildsTreeStyle::default();
+        let down = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+
+        state.select(TreeNodeId::Category(ChannelId(30)));
+        state.handle_key(down, &data, &registry, &style, true);
+        assert_eq!(state.selected(), Some(&TreeNodeId::Category(ChannelId(40))));
+
+        // A selection left inside a collapsed category resumes from the category.
+        state.select(TreeNodeId::Channel(ChannelId(32)));
+        state.handle_key(down, &data, &registry, &style, true);
+        assert_eq!(state.selected(), Some(&TreeNodeId::Category(ChannelId(40))));
+    }
+
+    #[test]
+    fn test_expanding_category_reveals_children_for_selection() {
+        let (data, mut state) = collapsed_category_fixture();
+        let registry = CommandRegistry::default();
+        let style = GuildsTreeStyle::default();
+        let down = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        let right = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE);
+
+        state.select(TreeNodeId::Category(ChannelId(30)));
+        state.handle_key(right, &data, &registry, &style, true);
+        assert!(state.is_expanded(&TreeNodeId::Category(ChannelId(30))));
+
+        state.handle_key(down, &data, &registry, &style, true);
+        assert_eq!(state.selected(), Some(&TreeNodeId::Channel(ChannelId(31))));
+        state.handle_key(down, &data, &registry, &style, true);
+        assert_eq!(state.selected(), Some(&TreeNodeId::Channel(ChannelId(32))));
+    }
+
+    #[test_case(true ; "enabled")]
+    #[test_case(false ; "disabled")]
+    fn test_flatten_single_channel_categories(enabled: bool) {
+        let (mut data, mut state) = collapsed_category_fixture();
+        data.set_flatten_single_channel_categories(enabled);
+        state.expand(TreeNodeId::Category(ChannelId(30)));
+        state.expand(TreeNodeId::Category(ChannelId(40)));
+
+        let style = GuildsTreeStyle::default();
+        let nodes = data.flatten(&state, 100, &style, true);
+        let ids: Vec<&TreeNodeId> = nodes.iter().skip(2).map(|node| &node.id).collect();
+
+        let mut expected = vec![
+            TreeNodeId::Category(ChannelId(30)),
+            TreeNodeId::Channel(ChannelId(31)),
+            TreeNodeId::Channel(ChannelId(32)),
+        ];
+        if enabled {
+            expected.push(TreeNodeId::Channel(ChannelId(41)));
+        } else {
+            expected.push(TreeNodeId::Category(ChannelId(40)));
+            expected.push(TreeNodeId::Channel(ChannelId(41)));
+        }
+        assert_eq!(ids, expected.iter().collect::<Vec<_>>());
+
+        let memes = nodes
+            .iter()
+            .find(|node| node.id == TreeNodeId::Channel(ChannelId(41)))
+            .expect("memes should be visible");
+        assert_eq!(memes.depth, if enabled { 1 } else { 2 });
+    }
+
+    #[test]
+    fn test_compact_mode_shows_initials_except_selected() {
+        let mut data = GuildsTreeData::new();
+        data.set_guilds(vec![
+            Guild::new(1_u64, "Rust Programming"),
+            Guild::new(2_u64, "Gaming Lounge"),
+            Guild::new(3_u64, "Book Club"),
+        ]);
+        data.set_group_guilds(true);
+        data.set_folders(vec![
+            GuildFolder {
+                id: None,
+                name: None,
+                color: None,
+                guild_ids: vec![GuildId(1), GuildId(2)],
+            },
+            GuildFolder {
+                id: Some(10),
+                name: Some("Work Stuff".to_string()),
+                color: None,
+                guild_ids: vec![GuildId(3)],
+            },
+        ]);
+        let mut state = GuildsTreeState::new();
+        state.select(TreeNodeId::Guild(GuildId(2)));
+        state.expand(TreeNodeId::Folder(Some(10)));
+
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        GuildsTree::new(&data)
+            .compact(true)
+            .render(area, &mut buf, &mut state);
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        let rendered = rows.join("\n");
+        assert!(rows.iter().any(|row| row.contains("▸ DM ")));
+        assert!(rows.iter().any(|row| row.contains("▸ RP ")));
+        assert!(rows.iter().any(|row| row.contains("▾  WS ")));
+        assert!(rendered.contains("└── ▸ Book Club"));
+        assert!(!rendered.contains("Rust Programming"));
+        assert!(!rendered.contains("Direct Messages"));
+        assert!(rendered.contains("Gaming Lounge"));
+    }
+
+    #[test]
+    fn test_folder_headers_show_name_color_or_guild_initials() {
+        let mut data = GuildsTreeData::new();
+        data.set_guilds(vec![
+            Guild::new(1_u64, "Rust Programming"),
+            Guild::new(2_u64, "Gaming Lounge"),
+            Guild::new(3_u64, "Book Club"),
+        ]);
+        data.set_group_guilds(true);
+        data.set_folders(vec![
+            GuildFolder {
+                id: Some(10),
+                name: Some("Work".to_string()),
+                color: Some(0x00FF_8800),
+                guild_ids: vec![GuildId(3)],
+            },
+            GuildFolder {
+                id: Some(11),
+                name: None,
+                color: None,
+                guild_ids: vec![GuildId(1), GuildId(2)],
+            },
+        ]);
+        let mut state = GuildsTreeState::new();
+
+        let area = Rect::new(0, 0, 30, 6);
+        let mut buf = Buffer::empty(area);
+        GuildsTree::new(&data).render(area, &mut buf, &mut state);
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        let (y, row) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.contains("Work"))
+            .expect("named folder header");
+        let x = row[..row.find("Work").unwrap()].chars().count();
+        assert_eq!(
+            buf[(u16::try_from(x).unwrap(), u16::try_from(y).unwrap())].fg,
+            Color::Rgb(0xFF, 0x88, 0x00)
+        );
+        assert!(rows.iter().any(|row| row.contains("RP, GL")));
+    }
 }
@@ -8,6 +8,11 @@ use ratatui::{
     widgets::{Paragraph, Widget},
 };
 
+/// Latency at or below this is shown as good.
+const LATENCY_GOOD_MS: u64 = 150;
+/// Latency at or below this (and above good) is shown as fair.
+const LATENCY_FAIR_MS: u64 = 400;
+
 trait ConnectionStatusExt {
     fn display_text(self) -> &'static str;
     fn indicator(self) -> &'static str;
@@ -33,6 +38,7 @@ impl ConnectionStatusExt for ConnectionStatus {
     }
 }
 
+#[derive(Clone)]
 pub struct HeaderBarStyle {
     pub background: Style,
     pub app_name: Style,
@@ -41,6 +47,10 @@ pub struct HeaderBarStyle {
     pub status_disconnected: Style,
     pub status_connecting: Style,
     pub status_error: Style,
+    pub latency_good: Style,
+    pub latency_fair: Style,
+    pub latency_poor: Style,
+    pub mentions: Style,
 }
 
 impl HeaderBarStyle {
@@ -56,6 +66,10 @@ impl HeaderBarStyle {
             status_disconnected: theme.error_style.add_modifier(Modifier::BOLD),
             status_connecting: theme.warning_style.add_modifier(Modifier::BOLD),
             status_error: theme.error_style.add_modifier(Modifier::BOLD),
+            latency_good: theme.success_style,
+            latency_fair: theme.warning_style,
+            latency_poor: theme.error_style,
+            mentions: theme.error_style.add_modifier(Modifier::BOLD),
             ..Self::default()
         }
     }
@@ -75,6 +89,10 @@ impl Default for HeaderBarStyle {
             status_disconnected: Style::default().fg(Color::Red),
             status_connecting: Style::default().fg(Color::Yellow),
             status_error: Style::default().fg(Color::Red),
+            latency_good: Style::default().fg(Color::Green),
+            latency_fair: Style::default().fg(Color::Yellow),
+            latency_poor: Style::default().fg(Color::Red),
+            mentions: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         }
     }
 }
@@ -83,6 +101,8 @@ pub struct HeaderBar<'a> {
     app_name: &'a str,
     version: &'a str,
     connection_status: ConnectionStatus,
+    latency_ms: Option<u64>,
+    mention_count: u32,
     style: HeaderBarStyle,
 }
 
@@ -93,6 +113,8 @@ impl<'a> HeaderBar<'a> {
             app_name,
             version,
             connection_status: ConnectionStatus::default(),
+            latency_ms: None,
+            mention_count: 0,
             style: HeaderBarStyle::default(),
         }
     }
@@ -103,6 +125,20 @@ impl<'a> HeaderBar<'a> {
         self
     }
 
+    /// Gateway heartbeat round trip, shown while connected.
+    #[must_use]
+    pub const fn latency_ms(mut self, latency_ms: Option<u64>) -> Self {
+        self.latency_ms = latency_ms;
+        self
+    }
+
+    /// Unread mentions across all channels, shown when non-zero.
+    #[must_use]
+    pub const fn mention_count(mut self, count: u32) -> Self {
+        self.mention_count = count;
+        self
+    }
+
     #[must_use]
     pub const fn style(mut self, style: HeaderBarStyle) -> Self {
         self.style = style;
@@ -132,6 +168,32 @@ impl<'a> HeaderBar<'a> {
 
         (spans, width)
     }
+
+    /// Latency readout, or "—" until connected with a measured ACK.
+    #[allow(clippy::cast_possible_truncation)]
+    fn build_latency_span(&self) -> (Span<'static>, u16) {
+        let latency = self
+            .latency_ms
+            .filter(|_| self.connection_status == ConnectionStatus::Connected);
+        let (text, style) = match latency {
+            Some(ms) if ms <= LATENCY_GOOD_MS => (format!("{ms}ms "), self.style.latency_good),
+            Some(ms) if ms <= LATENCY_FAIR_MS => (format!("{ms}ms "), self.style.latency_fair),
+            Some(ms) => (format!("{ms}ms "), self.style.latency_poor),
+            None => ("— ".to_string(), self.style.version),
+        };
+        let width = text.chars().count() as u16;
+        (Span::styled(text, style), width)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn build_mentions_span(&self) -> Option<(Span<'static>, u16)> {
+        if self.mention_count == 0 {
+            return None;
+        }
+        let text = format!(" @{} ", self.mention_count);
+        let width = text.chars().count() as u16;
+        Some((Span::styled(text, self.style.mentions), width))
+    }
 }
 
 impl Widget for HeaderBar<'_> {
@@ -162,9 +224,23 @@ impl Widget for HeaderBar<'_> {
         let left_area = Rect::new(area.x, area.y, left_width.min(area.width), 1);
         Paragraph::new(left_line).render(left_area, buf);
 
-        let (status_spans, status_width) = self.build_status_spans();
+        let (mut status_spans, mut status_width) = self.build_status_spans();
+        let (latency_span, latency_width) = self.build_latency_span();
+        let available = area.width.saturating_sub(left_width);
+
+        if status_width + latency_width < available {
+            status_spans.push(latency_span);
+            status_width += latency_width;
+        }
+
+        if let Some((mentions_span, mentions_width)) = self.build_mentions_span()
+            && status_width + mentions_width < available
+        {
+            status_spans.insert(0, mentions_span);
+            status_width += mentions_width;
+        }
 
-        if status_width < area.width.saturating_sub(left_width) {
+        if status_width < available {
             let right_x = area.right().saturating_sub(status_width);
             let right_area = Rect::new(right_x, area.y, status_width, 1);
             let right_line = Line::from(status_spans);
@@ -201,4 +277,66 @@ mod tests {
         assert_eq!(header.app_name, "oxicord");
         assert_eq!(header.version, "0.0.1");
     }
+
+    #[test]
+    fn test_latency_span_colors_and_placeholder() {
+        let style = HeaderBarStyle::default();
+        let span = |status, latency| {
+            HeaderBar::new("oxicord", "0.0.1")
+                .connection_status(status)
+                .latency_ms(latency)
+                .build_latency_span()
+                .0
+        };
+
+        let good = span(ConnectionStatus::Connected, Some(42));
+        assert_eq!(good.content, "42ms ");
+        assert_eq!(good.style, style.latency_good);
+        assert_eq!(
+            span(ConnectionStatus::Connected, Some(300)).style,
+            style.latency_fair
+        );
+        assert_eq!(
+            span(ConnectionStatus::Connected, Some(900)).style,
+            style.latency_poor
+        );
+        assert_eq!(span(ConnectionStatus::Disconnected, Some(42)).content, "— ");
+        assert_eq!(span(ConnectionStatus::Connected, None).content, "— ");
+    }
+
+    #[test]
+    fn test_latency_hidden_when_header_is_narrow() {
+        let render = |width| {
+            let area = Rect::new(0, 0, width, 1);
+            let mut buf = Buffer::empty(area);
+            HeaderBar::new("oxicord", "0.0.1")
+                .connection_status(ConnectionStatus::Connected)
+                .latency_ms(Some(42))
+                .render(area, &mut buf);
+            (0..width).map(|x| buf[(x, 0)].symbol()).collect::<String>()
+        };
+
+        assert!(render(60).contains("CONNECTED 42ms"));
+        let narrow = render(32);
+        assert!(narrow.contains("CONNECTED"));
+        assert!(!narrow.contains("42ms"));
+    }
+
+    #[test]
+    fn test_mention_count_shown_only_when_nonzero() {
+        let render = |count| {
+            let area = Rect::new(0, 0, 60, 1);
+            let mut buf = Buffer::empty(area);
+            HeaderBar::new("oxicord", "0.0.1")
+                .connection_status(ConnectionStatus::Connected)
+                .mention_count(count)
+                .render(area, &mut buf);
+            (0..area.width)
+                .map(|x| buf[(x, 0)].symbol())
+                .collect::<String>()
+        };
+
+        assert!(render(3).contains("@3  ● CONNECTED"));
+        assert!(!render(0).contains('@'));
+    }
 }
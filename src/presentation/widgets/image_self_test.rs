@@ -0,0 +1,41 @@
+//! Diagnostic self-test for the image pipeline.
+
+use std::fmt;
+
+/// Small checkerboard PNG the self-test decodes and renders.
+pub const SELF_TEST_IMAGE: &[u8] = include_bytes!("assets/self_test.png");
+
+/// Outcome of [`ImageManager::self_test`](super::ImageManager::self_test).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageSelfTestReport {
+    /// Graphics protocol the picker settled on.
+    pub protocol: String,
+    /// Whether the terminal answered the query with a real graphics protocol.
+    pub graphics_supported: bool,
+    /// Decoded dimensions, or why decoding failed.
+    pub decode: Result<(u32, u32), String>,
+    /// Whether the decoded image encoded and rendered through the protocol.
+    pub render: Result<(), String>,
+}
+
+impl ImageSelfTestReport {
+    #[must_use]
+    pub const fn passed(&self) -> bool {
+        self.decode.is_ok() && self.render.is_ok()
+    }
+}
+
+impl fmt::Display for ImageSelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let graphics = if self.graphics_supported { "yes" } else { "no" };
+        writeln!(f, "Protocol: {} (graphics: {graphics})", self.protocol)?;
+        match &self.decode {
+            Ok((width, height)) => writeln!(f, "Decode:   ok ({width}x{height})")?,
+            Err(e) => writeln!(f, "Decode:   failed ({e})")?,
+        }
+        match &self.render {
+            Ok(()) => write!(f, "Render:   ok"),
+            Err(e) => write!(f, "Render:   failed ({e})"),
+        }
+    }
+}
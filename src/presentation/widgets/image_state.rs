@@ -8,6 +8,7 @@ use ratatui_image::picker::{Picker, ProtocolType};
 use ratatui_image::protocol::StatefulProtocol;
 use tokio::sync::oneshot;
 
+use super::ImageSelfTestReport;
 use crate::domain::entities::{ImageId, ImageStatus};
 
 pub const MAX_IMAGE_HEIGHT: u16 = 20;
@@ -22,6 +23,8 @@ pub struct ImageAttachment {
     pub status: ImageStatus,
     pub width: Option<u32>,
     pub height: Option<u32>,
+    /// Hidden behind a spoiler until revealed.
+    pub spoiler: bool,
 }
 
 impl ImageAttachment {
@@ -36,6 +39,7 @@ impl ImageAttachment {
             status: ImageStatus::NotStarted,
             width,
             height,
+            spoiler: false,
         }
     }
 
@@ -46,12 +50,14 @@ impl ImageAttachment {
         }
 
         let id = ImageId::from_url(&attachment.url);
-        Some(Self::new(
+        let mut image = Self::new(
             id,
             attachment.url.clone(),
             attachment.width,
             attachment.height,
-        ))
+        );
+        image.spoiler = attachment.is_spoiler();
+        Some(image)
     }
 
     pub fn set_loaded(&mut self, image: Arc<image::DynamicImage>) {
@@ -124,6 +130,16 @@ impl ImageAttachment {
         self.protocol_receiver = None;
     }
 
+    /// Drops the decoded image and its protocol so the attachment is
+    /// loaded again from the cache the next time it is needed.
+    pub fn unload(&mut self) {
+        self.clear_protocol();
+        self.image = None;
+        if matches!(self.status, ImageStatus::Ready) {
+            self.status = ImageStatus::NotStarted;
+        }
+    }
+
     #[must_use]
     pub fn height(&self, width: u16) -> u16 {
         if let (Some(w), Some(h)) = (self.width, self.height) {
@@ -164,12 +180,18 @@ impl std::fmt::Debug for ImageAttachment {
 
 pub struct ImageManager {
     picker: Picker,
+    /// Whether the terminal answered the query with a real graphics protocol.
+    graphics_supported: bool,
 }
 
 impl ImageManager {
     #[must_use]
     pub fn new() -> Self {
-        let mut picker = Picker::from_query_stdio().unwrap_or_else(|_| Picker::halfblocks());
+        let queried = Picker::from_query_stdio().ok();
+        let graphics_supported = queried
+            .as_ref()
+            .is_some_and(|p| p.protocol_type() != ProtocolType::Halfblocks);
+        let mut picker = queried.unwrap_or_else(Picker::halfblocks);
 
         let caps = picker.capabilities();
         let has_sixel = caps
@@ -187,7 +209,16 @@ impl ImageManager {
             picker.set_protocol_type(ProtocolType::Halfblocks);
         }
 
-        Self { picker }
+        tracing::info!(
+            protocol = ?picker.protocol_type(),
+            graphics_supported,
+            "Detected terminal image protocol"
+        );
+
+        Self {
+            picker,
+            graphics_supported,
+        }
     }
 
     fn is_inside_multiplexer() -> bool {
@@ -202,6 +233,7 @@ impl ImageManager {
     pub fn halfblocks() -> Self {
         Self {
             picker: Picker::halfblocks(),
+            graphics_supported: true,
         }
     }
 
@@ -221,6 +253,51 @@ impl ImageManager {
         &self.picker
     }
 
+    /// Returns false when images should fall back to textual placeholders.
+    #[must_use]
+    pub const fn supports_graphics(&self) -> bool {
+        self.graphics_supported
+    }
+
+    /// Decodes `bytes` and renders them through the detected protocol,
+    /// reporting each step for debugging missing images.
+    #[must_use]
+    pub fn self_test(&self, bytes: &[u8]) -> ImageSelfTestReport {
+        use ratatui::buffer::Buffer;
+        use ratatui::widgets::StatefulWidget;
+        use ratatui_image::StatefulImage;
+
+        let decoded = image::load_from_memory(bytes).map_err(|e| e.to_string());
+        let decode = decoded
+            .as_ref()
+            .map(|img| (img.width(), img.height()))
+            .map_err(Clone::clone);
+
+        let render = decoded
+            .map_err(|_| "skipped, nothing decoded".to_string())
+            .and_then(|img| {
+                let mut protocol = self.picker.new_resize_protocol(img);
+                let area = Rect::new(0, 0, 16, 8);
+                let mut buf = Buffer::empty(area);
+                StatefulImage::default().resize(Resize::Fit(None)).render(
+                    area,
+                    &mut buf,
+                    &mut protocol,
+                );
+                protocol
+                    .last_encoding_result()
+                    .unwrap_or(Ok(()))
+                    .map_err(|e| e.to_string())
+            });
+
+        ImageSelfTestReport {
+            protocol: format!("{:?}", self.picker.protocol_type()),
+            graphics_supported: self.graphics_supported,
+            decode,
+            render,
+        }
+    }
+
     pub const fn set_width(&mut self, _width: u16) {}
 
     #[must_use]
@@ -261,6 +338,25 @@ impl ImageManager {
         }
     }
 
+    /// Message indices whose images are prefetched: past the load buffer
+    /// out to `distance` messages on each side of the visible range, nearest
+    /// first and older messages before newer ones.
+    #[must_use]
+    pub fn prefetch_indices(
+        visible_start: usize,
+        visible_end: usize,
+        distance: usize,
+        count: usize,
+    ) -> Vec<usize> {
+        if distance <= LOAD_BUFFER {
+            return Vec::new();
+        }
+        let above =
+            visible_start.saturating_sub(distance)..visible_start.saturating_sub(LOAD_BUFFER);
+        let below = (visible_end + LOAD_BUFFER).min(count)..(visible_end + distance).min(count);
+        above.rev().chain(below).collect()
+    }
+
     #[must_use]
     pub fn collect_needed_loads(
         attachments: &[ImageAttachment],
@@ -344,6 +440,23 @@ mod tests {
         assert_eq!(manager.width(), 0);
     }
 
+    #[test]
+    fn test_self_test_reports_protocol_and_decode() {
+        let manager = ImageManager::halfblocks();
+
+        let report = manager.self_test(crate::presentation::widgets::SELF_TEST_IMAGE);
+        assert_eq!(report.protocol, "Halfblocks");
+        assert_eq!(report.decode, Ok((16, 16)));
+        assert!(report.passed(), "{report}");
+
+        let report = manager.self_test(b"not an image");
+        assert_eq!(report.protocol, "Halfblocks");
+        assert!(report.decode.is_err());
+        assert!(report.render.is_err());
+        assert!(!report.passed());
+        assert!(report.to_string().contains("Decode:   failed"));
+    }
+
     #[test]
     fn test_collect_needed_loads() {
         let attachments = vec![
@@ -358,6 +471,16 @@ mod tests {
         assert!(needed.len() >= 2);
     }
 
+    #[test]
+    fn test_prefetch_indices_skip_load_buffer() {
+        assert_eq!(
+            ImageManager::prefetch_indices(20, 30, 8, 100),
+            vec![14, 13, 12, 35, 36, 37]
+        );
+        assert_eq!(ImageManager::prefetch_indices(3, 30, 8, 36), vec![35]);
+        assert!(ImageManager::prefetch_indices(20, 30, LOAD_BUFFER, 100).is_empty());
+    }
+
     #[tokio::test]
     async fn test_image_protocol_async_loading() {
         let id = ImageId::new("test");
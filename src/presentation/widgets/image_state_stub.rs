@@ -12,6 +12,8 @@ pub struct ImageAttachment {
     pub status: ImageStatus,
     pub width: Option<u32>,
     pub height: Option<u32>,
+    /// Hidden behind a spoiler until revealed.
+    pub spoiler: bool,
     pub protocol: Option<()>,
 }
 
@@ -25,6 +27,7 @@ impl ImageAttachment {
             width,
             height,
             protocol: None,
+            spoiler: false,
         }
     }
 
@@ -35,12 +38,14 @@ impl ImageAttachment {
         }
 
         let id = ImageId::from_url(&attachment.url);
-        Some(Self::new(
+        let mut image = Self::new(
             id,
             attachment.url.clone(),
             attachment.width,
             attachment.height,
-        ))
+        );
+        image.spoiler = attachment.is_spoiler();
+        Some(image)
     }
 
     pub fn set_downloading(&mut self) {
@@ -68,6 +73,8 @@ impl ImageAttachment {
 
     pub fn clear_protocol(&mut self) {}
 
+    pub fn unload(&mut self) {}
+
     #[must_use]
     pub fn height(&self, _width: u16) -> u16 {
         0
@@ -114,6 +121,23 @@ impl ImageManager {
         0
     }
 
+    #[must_use]
+    pub const fn supports_graphics(&self) -> bool {
+        false
+    }
+
+    /// Always fails: images are not compiled in.
+    #[must_use]
+    pub fn self_test(&self, _bytes: &[u8]) -> super::ImageSelfTestReport {
+        let disabled = "image feature disabled".to_string();
+        super::ImageSelfTestReport {
+            protocol: "none".to_string(),
+            graphics_supported: false,
+            decode: Err(disabled.clone()),
+            render: Err(disabled),
+        }
+    }
+
     pub const fn set_width(&mut self, _width: u16) {}
 
     pub fn update_visible_protocols(&self, _attachments: &mut [&mut ImageAttachment]) {}
@@ -2,31 +2,30 @@ use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
-    text::Span,
+    text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
 };
 
-use crate::application::services::autocomplete_service::AutocompleteState;
+use crate::application::services::autocomplete_service::{
+    AutocompleteState, MentionCandidate, MentionKind,
+};
 use crate::application::services::identity_resolver::IdentityResolver;
+use crate::presentation::theme::Theme;
+use crate::presentation::ui::utils::highlight_matches;
 
-pub struct MentionPopup {
+pub struct MentionPopup<'a> {
     resolver: IdentityResolver,
+    theme: &'a Theme,
 }
 
-impl Default for MentionPopup {
-    fn default() -> Self {
-        Self::new(IdentityResolver::with_preference(false))
-    }
-}
-
-impl MentionPopup {
+impl<'a> MentionPopup<'a> {
     #[must_use]
-    pub fn new(resolver: IdentityResolver) -> Self {
-        Self { resolver }
+    pub fn new(resolver: IdentityResolver, theme: &'a Theme) -> Self {
+        Self { resolver, theme }
     }
 }
 
-impl StatefulWidget for MentionPopup {
+impl StatefulWidget for MentionPopup<'_> {
     type State = AutocompleteState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
@@ -36,15 +35,37 @@ impl StatefulWidget for MentionPopup {
 
         Widget::render(Clear, area, buf);
 
+        let title = match state.kind {
+            MentionKind::User => " Mention User ",
+            MentionKind::Role => " Mention Role ",
+            MentionKind::Channel => " Mention Channel ",
+        };
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(" Mention User ")
+            .title(title)
             .title_style(Style::default().add_modifier(Modifier::BOLD));
 
+        let matched = Style::default()
+            .fg(self.theme.accent)
+            .add_modifier(Modifier::BOLD);
         let items: Vec<ListItem> = state
             .results
             .iter()
-            .map(|user| ListItem::new(Span::raw(self.resolver.resolve(user))))
+            .zip(&state.match_indices)
+            .map(|(candidate, indices)| {
+                let icon = match candidate {
+                    MentionCandidate::User(_) => "@",
+                    MentionCandidate::Role(_) => "󰓹",
+                    MentionCandidate::Channel(channel) => channel.kind().prefix().trim_end(),
+                };
+                let name = candidate.label(&self.resolver);
+                let mut spans = vec![Span::styled(
+                    format!("{icon} "),
+                    Style::default().fg(Color::DarkGray),
+                )];
+                spans.extend(highlight_matches(&name, indices, Style::default(), matched));
+                ListItem::new(Line::from(spans))
+            })
             .collect();
 
         let list = List::new(items)
@@ -1,23 +1,28 @@
-use std::path::PathBuf;
-
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Widget},
 };
 use tui_textarea::TextArea;
 
 use crate::domain::entities::MessageId;
 use crate::domain::keybinding::Action;
+use crate::domain::ports::OutgoingAttachment;
 use crate::presentation::commands::CommandRegistry;
 use crate::presentation::theme::Theme;
 use unicode_width::UnicodeWidthChar;
 
-const MAX_MESSAGE_LENGTH: usize = 2000;
+/// Discord's message length limit without Nitro.
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 2000;
 const PLACEHOLDER_TEXT: &str = "Type a message...";
 const PLACEHOLDER_NO_CHANNEL: &str = "Select a channel first";
+const PLACEHOLDER_READ_ONLY: &str = "You do not have permission to send messages here";
+
+/// Slash commands handled locally instead of being sent as text.
+const LOCAL_COMMANDS: &[&str] = &["nick", "group", "add"];
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum MessageInputMode {
@@ -33,17 +38,33 @@ pub enum MessageInputMode {
     },
 }
 
+impl MessageInputMode {
+    /// Whether a message sent in this mode pings the author it replies to.
+    #[must_use]
+    pub const fn mentions_reply(&self) -> bool {
+        match self {
+            Self::Reply { mention, .. } => *mention,
+            Self::Normal | Self::Editing { .. } => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MessageInputAction {
     SendMessage {
         content: String,
         reply_to: Option<MessageId>,
-        attachments: Vec<PathBuf>,
+        attachments: Vec<OutgoingAttachment>,
     },
     EditMessage {
         message_id: MessageId,
         content: String,
     },
+    /// A send or edit was refused because the content is over the limit.
+    TooLong {
+        length: usize,
+        max: usize,
+    },
     StartTyping,
     CancelReply,
     ExitInput,
@@ -56,10 +77,13 @@ pub struct MessageInputState<'a> {
     focused: bool,
     mode: MessageInputMode,
     has_channel: bool,
-    attachments: Vec<PathBuf>,
+    /// The user cannot send messages in the open channel.
+    read_only: bool,
+    attachments: Vec<OutgoingAttachment>,
     scroll_offset: usize,
     last_width: usize,
     mentions: std::collections::HashMap<String, String>,
+    max_length: usize,
 }
 
 impl MessageInputState<'_> {
@@ -72,22 +96,54 @@ impl MessageInputState<'_> {
             focused: false,
             mode: MessageInputMode::Normal,
             has_channel: false,
+            read_only: false,
             attachments: Vec::new(),
             scroll_offset: 0,
             last_width: 0,
             mentions: std::collections::HashMap::new(),
+            max_length: DEFAULT_MAX_MESSAGE_LENGTH,
         }
     }
 
-    pub fn add_attachment(&mut self, path: PathBuf) {
-        self.attachments.push(path);
+    /// Sets the longest message that may be sent, in characters; 0 removes
+    /// the limit.
+    pub const fn set_max_length(&mut self, max_length: usize) {
+        self.max_length = max_length;
+    }
+
+    /// Length of the content as it will be sent, with mentions expanded.
+    #[must_use]
+    pub fn char_count(&self) -> usize {
+        self.message_content().chars().count()
+    }
+
+    /// The length counter shown once the content nears the limit, styled as
+    /// a warning and as an error past it.
+    fn length_counter(&self) -> Option<Span<'static>> {
+        let count = self.char_count();
+        if self.max_length == 0 || count < self.max_length - self.max_length / 10 {
+            return None;
+        }
+        let style = if count > self.max_length {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        Some(Span::styled(
+            format!(" {count}/{} ", self.max_length),
+            style,
+        ))
+    }
+
+    pub fn add_attachment(&mut self, attachment: OutgoingAttachment) {
+        self.attachments.push(attachment);
     }
 
     pub fn clear_attachments(&mut self) {
         self.attachments.clear();
     }
 
-    pub fn attachments(&self) -> &[PathBuf] {
+    pub fn attachments(&self) -> &[OutgoingAttachment] {
         &self.attachments
     }
 
@@ -110,14 +166,41 @@ impl MessageInputState<'_> {
         self.has_channel
     }
 
+    /// Disables typing, sending and editing, e.g. where the user lacks
+    /// permission to post. Any reply or edit in progress is dropped.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only && self.is_editing() {
+            self.clear();
+        } else if read_only {
+            self.reset_mode();
+        }
+        self.update_placeholder();
+    }
+
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    const fn placeholder(&self) -> &'static str {
+        if !self.has_channel {
+            PLACEHOLDER_NO_CHANNEL
+        } else if self.read_only {
+            PLACEHOLDER_READ_ONLY
+        } else {
+            PLACEHOLDER_TEXT
+        }
+    }
+
     #[must_use]
     pub fn message_content(&self) -> String {
         let mut content = self.value();
         let mut sorted_mentions: Vec<_> = self.mentions.iter().collect();
         sorted_mentions.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
 
-        for (name, id) in sorted_mentions {
-            content = content.replace(name, &format!("<@{id}>"));
+        for (label, markup) in sorted_mentions {
+            content = content.replace(label, markup);
         }
         content
     }
@@ -160,6 +243,13 @@ impl MessageInputState<'_> {
         self.set_content(content);
     }
 
+    /// Flips whether the reply pings its author. Does nothing outside reply mode.
+    pub const fn toggle_reply_mention(&mut self) {
+        if let MessageInputMode::Reply { mention, .. } = &mut self.mode {
+            *mention = !*mention;
+        }
+    }
+
     pub fn reset_mode(&mut self) {
         self.mode = MessageInputMode::Normal;
     }
@@ -178,16 +268,14 @@ impl MessageInputState<'_> {
     }
 
     pub fn insert_text_at_cursor(&mut self, content: &str) {
+        if self.read_only {
+            return;
+        }
         self.textarea.insert_str(content);
     }
 
     fn update_placeholder(&mut self) {
-        let placeholder = if self.has_channel {
-            PLACEHOLDER_TEXT
-        } else {
-            PLACEHOLDER_NO_CHANNEL
-        };
-        self.textarea.set_placeholder_text(placeholder);
+        self.textarea.set_placeholder_text(self.placeholder());
     }
 
     pub fn get_cursor_index(&self) -> usize {
@@ -334,10 +422,19 @@ impl MessageInputState<'_> {
     }
 
     pub fn insert_mention(&mut self, trigger_index: usize, resolved_name: &str, user_id: &str) {
+        self.insert_reference(
+            trigger_index,
+            &format!("@{resolved_name}"),
+            format!("<@{user_id}>"),
+        );
+    }
+
+    /// Replaces the trigger at `trigger_index` with `label`, which is sent as
+    /// `markup` (e.g. `#general` as `<#id>`).
+    pub fn insert_reference(&mut self, trigger_index: usize, label: &str, markup: String) {
         let content = self.value();
-        let mention_text = format!("@{resolved_name} ");
-        self.mentions
-            .insert(format!("@{resolved_name}"), user_id.to_string());
+        let mention_text = format!("{label} ");
+        self.mentions.insert(label.to_string(), markup);
 
         if trigger_index >= content.len() {
             return;
@@ -435,20 +532,17 @@ impl MessageInputState<'_> {
         }
     }
 
-    fn enforce_message_limit(&mut self) {
-        let content = self.value();
-        if content.len() > MAX_MESSAGE_LENGTH {
-            let truncated: String = content.chars().take(MAX_MESSAGE_LENGTH).collect();
-            self.set_content(&truncated);
-        }
-    }
-
     #[allow(clippy::too_many_lines)]
     pub fn handle_key(
         &mut self,
         key: KeyEvent,
         registry: &CommandRegistry,
     ) -> Option<MessageInputAction> {
+        if self.read_only {
+            return (registry.find_action(key) == Some(Action::Cancel))
+                .then_some(MessageInputAction::ExitInput);
+        }
+
         match key.code {
             KeyCode::Home => {
                 self.move_cursor_start();
@@ -488,6 +582,14 @@ impl MessageInputState<'_> {
                     return None;
                 }
 
+                let length = content.chars().count();
+                if self.max_length > 0 && length > self.max_length {
+                    return Some(MessageInputAction::TooLong {
+                        length,
+                        max: self.max_length,
+                    });
+                }
+
                 if let MessageInputMode::Editing { message_id } = &self.mode {
                     let message_id = *message_id;
                     self.clear();
@@ -511,6 +613,10 @@ impl MessageInputState<'_> {
                 })
             }
             Some(Action::OpenEditor) => Some(MessageInputAction::OpenEditor),
+            Some(Action::ToggleReplyMention) => {
+                self.toggle_reply_mention();
+                None
+            }
             Some(Action::ClearInput) => {
                 self.clear();
                 None
@@ -610,7 +716,6 @@ impl MessageInputState<'_> {
                 }
 
                 let was_empty = self.is_empty();
-                self.enforce_message_limit();
 
                 if !was_empty || !self.is_empty() {
                     Some(MessageInputAction::StartTyping)
@@ -633,8 +738,11 @@ impl MessageInputState<'_> {
             .border_style(border_style);
 
         match &self.mode {
-            MessageInputMode::Reply { author, .. } => {
-                let reply_title = format!(" Replying to @{author} ");
+            MessageInputMode::Reply {
+                author, mention, ..
+            } => {
+                let ping = if *mention { "on" } else { "off" };
+                let reply_title = format!(" ↩ Replying to @{author} [ping: {ping}] ");
                 block = block
                     .title(reply_title)
                     .title_style(style.reply_indicator_style);
@@ -647,11 +755,28 @@ impl MessageInputState<'_> {
                         .add_modifier(Modifier::BOLD),
                 );
             }
+            MessageInputMode::Normal if self.read_only && self.has_channel => {
+                block = block
+                    .title(" Read-only ")
+                    .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+            }
             MessageInputMode::Normal => {}
         }
 
+        if let Some(counter) = self.length_counter() {
+            block = block.title_bottom(Line::from(counter).right_aligned());
+        }
+
         if !self.attachments.is_empty() {
-            let attachments_title = format!(" {} Attachments ", self.attachments.len());
+            let spoilers = self.attachments.iter().filter(|a| a.spoiler).count();
+            let attachments_title = if spoilers == 0 {
+                format!(" {} Attachments ", self.attachments.len())
+            } else {
+                format!(
+                    " {} Attachments ({spoilers} spoiler) ",
+                    self.attachments.len()
+                )
+            };
             block = block.title(attachments_title).title_style(
                 Style::default()
                     .fg(Color::Magenta)
@@ -662,6 +787,21 @@ impl MessageInputState<'_> {
         block
     }
 
+    /// How many leading chars of each visual line belong to a leading
+    /// `/command`, and whether that command is known.
+    fn command_highlight(&self, visual_lines: &[String]) -> Option<(Vec<usize>, bool)> {
+        let (mut remaining, known) = command_token(self.textarea.lines().first()?)?;
+        let counts = visual_lines
+            .iter()
+            .map(|line| {
+                let count = line.chars().count().min(remaining);
+                remaining -= count;
+                count
+            })
+            .collect();
+        Some((counts, known))
+    }
+
     /// Render using manual rendering instead of tui-textarea's widget
     /// to avoid ratatui version incompatibility (project: 0.30, tui-textarea: 0.29)
     pub fn render_with_style(&mut self, area: Rect, buf: &mut Buffer, style: &MessageInputStyle) {
@@ -691,6 +831,8 @@ impl MessageInputState<'_> {
             style.text_style
         };
 
+        let command = self.command_highlight(&visual_lines);
+
         for (i, line) in visual_lines
             .iter()
             .enumerate()
@@ -700,11 +842,7 @@ impl MessageInputState<'_> {
             let y = inner.y + u16::try_from(i - self.scroll_offset).unwrap_or(0);
 
             if self.value().is_empty() && visual_lines.len() == 1 && line.is_empty() {
-                let placeholder = if self.has_channel {
-                    PLACEHOLDER_TEXT
-                } else {
-                    PLACEHOLDER_NO_CHANNEL
-                };
+                let placeholder = self.placeholder();
                 let placeholder_chars: Vec<char> = placeholder.chars().collect();
 
                 for j in 0..width {
@@ -723,16 +861,20 @@ impl MessageInputState<'_> {
                 let line_chars: Vec<char> = line.chars().collect();
                 let mut current_width = 0;
 
-                for ch in &line_chars {
+                for (k, ch) in line_chars.iter().enumerate() {
                     let ch_width = ch.width().unwrap_or(0);
                     if current_width + ch_width > width {
                         break;
                     }
 
+                    let ch_style = match &command {
+                        Some((counts, known)) if k < counts[i] => style.command(*known),
+                        _ => text_style,
+                    };
                     let x = inner.x + u16::try_from(current_width).unwrap_or(0);
                     if let Some(cell) = buf.cell_mut((x, y)) {
                         cell.set_symbol(&ch.to_string());
-                        cell.set_style(text_style);
+                        cell.set_style(ch_style);
                     }
                     current_width += ch_width;
                 }
@@ -776,12 +918,21 @@ impl MessageInputState<'_> {
     }
 }
 
+/// Length in chars of the `/command` starting `line`, and whether it is one
+/// of [`LOCAL_COMMANDS`]. Unknown commands are still sent as plain text.
+fn command_token(line: &str) -> Option<(usize, bool)> {
+    let name = line.strip_prefix('/')?;
+    let name = name.split(char::is_whitespace).next().unwrap_or_default();
+    Some((name.chars().count() + 1, LOCAL_COMMANDS.contains(&name)))
+}
+
 impl Default for MessageInputState<'_> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[derive(Clone)]
 pub struct MessageInputStyle {
     pub border_style: Style,
     pub border_style_focused: Style,
@@ -789,9 +940,19 @@ pub struct MessageInputStyle {
     pub placeholder_style: Style,
     pub cursor_style: Style,
     pub reply_indicator_style: Style,
+    pub command_style: Style,
+    pub unknown_command_style: Style,
 }
 
 impl MessageInputStyle {
+    const fn command(&self, known: bool) -> Style {
+        if known {
+            self.command_style
+        } else {
+            self.unknown_command_style
+        }
+    }
+
     #[must_use]
     pub fn from_theme(theme: &Theme) -> Self {
         Self {
@@ -803,6 +964,8 @@ impl MessageInputStyle {
             reply_indicator_style: Style::default()
                 .fg(theme.accent)
                 .add_modifier(Modifier::ITALIC),
+            command_style: Style::default().fg(theme.accent),
+            unknown_command_style: theme.warning_style,
         }
     }
 }
@@ -818,6 +981,8 @@ impl Default for MessageInputStyle {
             reply_indicator_style: Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::ITALIC),
+            command_style: Style::default().fg(Color::Cyan),
+            unknown_command_style: Style::default().fg(Color::Yellow),
         }
     }
 }
@@ -838,6 +1003,8 @@ impl MessageInput {
                 placeholder_style: Style::new(),
                 cursor_style: Style::new(),
                 reply_indicator_style: Style::new(),
+                command_style: Style::new(),
+                unknown_command_style: Style::new(),
             },
         }
     }
@@ -878,6 +1045,109 @@ mod tests {
         assert_eq!(state.value(), "hi");
     }
 
+    #[test]
+    fn test_read_only_ignores_typing_and_drops_edit() {
+        let mut state = MessageInputState::new();
+        let registry = CommandRegistry::default();
+        state.set_has_channel(true);
+        state.start_edit(MessageId(1), "original");
+        assert!(state.is_editing());
+
+        state.set_read_only(true);
+        assert!(!state.is_editing());
+        assert!(state.is_empty());
+
+        state.handle_key(
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+            &registry,
+        );
+        assert!(state.is_empty());
+
+        state.set_read_only(false);
+        state.handle_key(
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+            &registry,
+        );
+        assert_eq!(state.value(), "h");
+    }
+
+    #[test]
+    fn test_send_refused_over_length_limit() {
+        let mut state = MessageInputState::new();
+        let registry = CommandRegistry::default();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        state.set_has_channel(true);
+        state.set_max_length(10);
+
+        state.set_content("hello world!");
+        assert_eq!(
+            state.handle_key(enter, &registry),
+            Some(MessageInputAction::TooLong {
+                length: 12,
+                max: 10
+            })
+        );
+        assert_eq!(state.value(), "hello world!");
+
+        state.set_max_length(DEFAULT_MAX_MESSAGE_LENGTH);
+        assert!(matches!(
+            state.handle_key(enter, &registry),
+            Some(MessageInputAction::SendMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_length_counter_shown_near_limit() {
+        let mut state = MessageInputState::new();
+        state.set_max_length(10);
+
+        state.set_content("12345678");
+        assert!(state.length_counter().is_none());
+
+        state.set_content("123456789");
+        let counter = state.length_counter().unwrap();
+        assert_eq!(counter.content, " 9/10 ");
+        assert_eq!(counter.style.fg, Some(Color::Yellow));
+
+        state.set_content("12345678901");
+        assert_eq!(state.length_counter().unwrap().style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn test_zero_max_length_disables_limit() {
+        let mut state = MessageInputState::new();
+        let registry = CommandRegistry::default();
+        state.set_has_channel(true);
+        state.set_max_length(0);
+
+        state.set_content("hello world!");
+        assert!(state.length_counter().is_none());
+        assert!(matches!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &registry),
+            Some(MessageInputAction::SendMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_toggle_reply_mention_updates_banner() {
+        let mut state = MessageInputState::new();
+        let registry = CommandRegistry::default();
+        let toggle = KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT);
+        state.start_reply(MessageId(1), "alice".to_string(), true);
+        let title = |state: &MessageInputState<'_>| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 50, 3));
+            state
+                .setup_block(&MessageInputStyle::default())
+                .render(buf.area, &mut buf);
+            (0..50).map(|x| buf[(x, 0)].symbol()).collect::<String>()
+        };
+        assert!(title(&state).contains("Replying to @alice [ping: on]"));
+
+        assert_eq!(state.handle_key(toggle, &registry), None);
+        assert!(!state.mode().mentions_reply());
+        assert!(title(&state).contains("Replying to @alice [ping: off]"));
+    }
+
     #[test]
     fn test_clear() {
         let mut state = MessageInputState::new();
@@ -985,6 +1255,35 @@ mod tests {
         assert_eq!(buf[(5, 2)].symbol(), "0");
     }
 
+    #[test]
+    fn test_slash_command_token_highlight() {
+        use ratatui::buffer::Buffer;
+        use ratatui::layout::Rect;
+
+        let style = MessageInputStyle::default();
+        let area = Rect::new(0, 0, 20, 3);
+        let render = |content: &str| {
+            let mut state = MessageInputState::new();
+            state.set_content(content);
+            let mut buf = Buffer::empty(area);
+            state.render_with_style(area, &mut buf, &style);
+            buf
+        };
+
+        let buf = render("/nick bob");
+        assert_eq!(buf[(1, 1)].fg, Color::Cyan);
+        assert_eq!(buf[(5, 1)].fg, Color::Cyan);
+        assert_eq!(buf[(7, 1)].fg, Color::White);
+
+        let buf = render("/shrug hi");
+        assert_eq!(buf[(1, 1)].fg, Color::Yellow);
+        assert_eq!(buf[(6, 1)].fg, Color::Yellow);
+        assert_eq!(buf[(8, 1)].fg, Color::White);
+
+        let buf = render("a /nick");
+        assert_eq!(buf[(3, 1)].fg, Color::White);
+    }
+
     #[test]
     fn test_ctrl_left_right_move_word() {
         use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -1039,6 +1338,22 @@ mod tests {
         assert!(!state.mentions.contains_key("@Antonio"));
     }
 
+    #[test]
+    fn test_channel_and_role_references_sent_as_markup() {
+        use tui_textarea::CursorMove;
+
+        let mut state = MessageInputState::new();
+        state.set_content("see #gen");
+        state.textarea.move_cursor(CursorMove::End);
+        state.insert_reference(4, "#general", "<#7>".to_string());
+        assert_eq!(state.value(), "see #general ");
+
+        state.set_content(&format!("{}@&mo", state.value()));
+        state.textarea.move_cursor(CursorMove::End);
+        state.insert_reference(13, "@Moderators", "<@&9>".to_string());
+        assert_eq!(state.message_content(), "see <#7> <@&9> ");
+    }
+
     #[test_case(KeyCode::Delete, KeyModifiers::CONTROL, "hello world", true, " world" ; "ctrl+delete from head")]
     #[test_case(KeyCode::Backspace, KeyModifiers::CONTROL, "hello world", false, "hello " ; "ctrl+backspace from end")]
     #[test_case(KeyCode::Char('w'), KeyModifiers::CONTROL, "hello world", false, "hello " ; "ctrl+w from end")]
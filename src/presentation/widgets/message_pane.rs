@@ -1,19 +1,21 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use crate::application::services::identity_resolver::IdentityResolver;
 use crate::application::services::markdown_parser::{
-    MdBlock, MdInline, MentionResolver, parse_markdown,
+    CodeBlockRef, MdBlock, MdInline, MentionResolver, code_blocks, parse_markdown,
 };
 use crate::application::services::url_extractor::UrlExtractor;
 use crate::domain::entities::{
-    ChannelId, Embed, ForumThread, ImageId, Message, MessageId, RelationshipState,
+    ChannelId, Embed, ForumThread, ImageId, Message, MessageFlags, MessageId, MessageKind,
+    RelationshipState, Role,
 };
 use crate::domain::keybinding::Action;
 
 use crate::presentation::commands::CommandRegistry;
-use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+use crate::presentation::services::markdown_renderer::{MarkdownRenderer, QUOTE_BORDER};
 
 use crossterm::event::KeyEvent;
 use ratatui::{
@@ -31,8 +33,16 @@ use unicode_width::UnicodeWidthStr;
 use super::image_state::{ImageAttachment, MAX_IMAGE_HEIGHT};
 #[cfg(not(feature = "image"))]
 use super::image_state_stub::{ImageAttachment, MAX_IMAGE_HEIGHT};
+use crate::infrastructure::config::app_config::{
+    AuthorColorMode, MessageDensity, TimestampPosition,
+};
+#[cfg(feature = "image")]
+use crate::infrastructure::image::{ANIMATED_AVATAR_INDICATOR, avatar_image};
 use crate::presentation::theme::Theme;
-use crate::presentation::ui::utils::{clean_text, get_author_color};
+use crate::presentation::ui::utils::{
+    DEFAULT_ELLIPSIS, RELATIVE_TIMESTAMP_FORMAT, clean_text, format_relative_timestamp,
+    get_author_color, truncate_to_width,
+};
 
 const SCROLL_AMOUNT: u16 = 3;
 const SCROLLBAR_MARGIN: u16 = 2;
@@ -40,10 +50,19 @@ const CHANNEL_NAME_PREFIX: &str = "[ ";
 const CHANNEL_NAME_SUFFIX: &str = " ]";
 const DM_CHANNEL_PREFIX: &str = "[ ";
 const TIMESTAMP_WIDTH: usize = 6;
+/// Column width for relative timestamps, sized to fit "yesterday".
+const RELATIVE_TIMESTAMP_WIDTH: usize = 10;
 const CONTENT_INDENT: usize = 6;
-const EMBED_INDENT: usize = 6;
+/// Indent for message content when no timestamp takes the left gutter.
+const NARROW_CONTENT_INDENT: usize = 2;
 const THREAD_CARD_HEIGHT: u16 = 6;
+/// Wrapped lines kept for one message body or embed description, leaving
+/// headroom so a message's total height still fits in `u16`.
+const MAX_CONTENT_LINES: usize = (u16::MAX / 2) as usize;
 const GROUPING_WINDOW_SECONDS: i64 = 7 * 60;
+const SEND_SPINNER: [&str; 4] = ["◐", "◓", "◑", "◒"];
+const LOADING_SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const LOADING_SPINNER_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Pre-calculated layout data for an embed.
 pub struct RenderedEmbed {
@@ -54,6 +73,8 @@ pub struct RenderedEmbed {
     pub height: u16,
     pub color: Color,
     pub url: Option<String>,
+    /// Whether a border-only row is drawn above and below the contents.
+    pub padded: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -63,6 +84,17 @@ pub enum MessageGroup {
     Compact,
 }
 
+/// Delivery state of a message composed in this client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SendState {
+    #[default]
+    Sent,
+    /// Shown as a local echo while the send request is in flight.
+    Pending,
+    /// The send request failed and can be retried.
+    Failed,
+}
+
 /// Represents a renderable item in the message list.
 /// Either a single visible message or a collapsed run of blocked messages.
 enum RenderItem {
@@ -74,6 +106,8 @@ enum RenderItem {
         /// Index range [start, end) of blocked messages.
         start_idx: usize,
     },
+    /// A message from an expanded blocked run, shown as a one-line placeholder.
+    BlockedMessage { idx: usize, run_start: usize },
 }
 
 /// UI wrapper for a message with rendering state.
@@ -88,7 +122,14 @@ pub struct UiMessage {
     pub rendered_embeds: Vec<RenderedEmbed>,
     /// Cached reply preview line
     pub reply_preview: Option<Line<'static>>,
+    /// Cached quote block for a forwarded message.
+    pub forward_preview: Vec<Line<'static>>,
     pub group: MessageGroup,
+    pub send_state: SendState,
+    /// Whether the content is longer than the collapse threshold.
+    pub collapsible: bool,
+    /// Whether the content is currently cut down to the collapse threshold.
+    pub collapsed: bool,
 }
 
 impl UiMessage {
@@ -117,19 +158,41 @@ impl UiMessage {
             image_attachments,
             rendered_embeds: Vec::new(),
             reply_preview: None,
+            forward_preview: Vec::new(),
             group: MessageGroup::Start,
+            send_state: SendState::Sent,
+            collapsible: false,
+            collapsed: false,
         }
     }
 
     #[must_use]
     #[allow(clippy::cast_possible_truncation)]
-    pub fn total_image_height(&self, width: u16) -> u16 {
+    pub fn total_image_height(&self, width: u16, show_spoilers: bool) -> u16 {
         self.image_attachments
             .iter()
-            .map(|img| img.height(width))
+            .map(|img| {
+                if img.spoiler && !show_spoilers {
+                    1
+                } else {
+                    img.height(width)
+                }
+            })
             .fold(0u16, u16::saturating_add)
     }
 
+    /// Fenced code blocks in the message, without fences or language tag.
+    #[must_use]
+    pub fn code_blocks(&self) -> Vec<CodeBlockRef<'_>> {
+        code_blocks(&self.parsed_content)
+    }
+
+    /// Raw contents of the code block at `index`.
+    #[must_use]
+    pub fn code_block(&self, index: usize) -> Option<&str> {
+        self.code_blocks().get(index).map(|block| block.code)
+    }
+
     /// Returns true if this message has any image attachments.
     #[must_use]
     pub fn has_images(&self) -> bool {
@@ -158,6 +221,7 @@ impl UiMessage {
 struct HashMapResolver<'a> {
     authors: &'a HashMap<String, String>,
     channels: &'a HashMap<String, String>,
+    roles: &'a HashMap<String, (String, u32)>,
 }
 
 impl MentionResolver for HashMapResolver<'_> {
@@ -168,6 +232,24 @@ impl MentionResolver for HashMapResolver<'_> {
     fn resolve_channel(&self, channel_id: &str) -> Option<String> {
         self.channels.get(channel_id).cloned()
     }
+
+    fn resolve_role(&self, role_id: &str) -> Option<(String, u32)> {
+        self.roles.get(role_id).cloned()
+    }
+}
+
+/// Converts a line count to rows, saturating at `u16::MAX`.
+fn line_height(lines: usize) -> u16 {
+    u16::try_from(lines).unwrap_or(u16::MAX)
+}
+
+/// Rows taken by `count` file attachments: one each, or a single shared
+/// row when compact.
+fn file_attachment_rows(count: usize, density: MessageDensity) -> u16 {
+    match density {
+        MessageDensity::Cozy => line_height(count),
+        MessageDensity::Compact => u16::from(count > 0),
+    }
 }
 
 fn calculate_embed_layout(
@@ -175,8 +257,10 @@ fn calculate_embed_layout(
     width: u16,
     markdown_service: &MarkdownRenderer,
     default_color: Color,
+    density: MessageDensity,
 ) -> RenderedEmbed {
-    let mut height = 0;
+    let padded = density == MessageDensity::Cozy;
+    let mut height: u16 = 0;
     let width = width.saturating_sub(2);
 
     let mut title_lines = Vec::new();
@@ -194,22 +278,22 @@ fn calculate_embed_layout(
 
     if let Some(title) = &embed.title {
         title_lines = wrap_text(title, width as usize);
-        height += u16::try_from(title_lines.len()).unwrap_or(u16::MAX);
+        height = height.saturating_add(line_height(title_lines.len()));
     }
 
     if let Some(description) = &embed.description {
         let text = markdown_service.render_markdown(description, None, false);
 
-        let wrapped_text = wrap_styled_text(text, width);
-        let content_lines = u16::try_from(wrapped_text.lines.len()).unwrap_or(0);
+        let mut wrapped_text = wrap_styled_text(text, width, 0);
+        wrapped_text.lines.truncate(MAX_CONTENT_LINES);
 
-        description_height = content_lines;
-        height += description_height;
+        description_height = line_height(wrapped_text.lines.len());
+        height = height.saturating_add(description_height);
         description_text = Some(wrapped_text);
     }
 
-    if height > 0 {
-        height += 2;
+    if padded && height > 0 {
+        height = height.saturating_add(2);
     }
 
     let color = if let Some(c) = embed.color {
@@ -230,6 +314,7 @@ fn calculate_embed_layout(
         height,
         color,
         url: embed.url.clone(),
+        padded,
     }
 }
 
@@ -248,11 +333,19 @@ pub enum MessagePaneAction {
     YankContent(String),
     YankUrl(String),
     YankId(String),
+    CopyLink(MessageId),
+    CopyCodeBlock(MessageId),
     OpenAttachments(MessageId),
     JumpToReply(MessageId),
     LoadHistory,
     OpenThread(ChannelId),
     CloseThread,
+    RetrySend(MessageId),
+    Publish(MessageId),
+    PurgeOwn,
+    JumpToMention {
+        forward: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -261,8 +354,11 @@ pub enum LoadingState {
     Loading,
     Loaded,
     Error,
+    /// The selected guild has channels, but none the user may view.
+    NoAccessibleChannels,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct MessagePaneData {
     channel_id: Option<ChannelId>,
     channel_name: Option<String>,
@@ -276,11 +372,29 @@ pub struct MessagePaneData {
     typing_indicator: Option<String>,
     authors: HashMap<String, String>,
     channels: HashMap<String, String>,
+    /// Role names and colors by role id, for `<@&id>` mentions.
+    roles: HashMap<String, (String, u32)>,
+    /// Guild nicknames by user id, shown in place of the resolved name.
+    nicknames: HashMap<String, String>,
     last_layout_width: Option<u16>,
     last_show_spoilers: Option<bool>,
     last_image_preview: Option<bool>,
     is_dirty: bool,
     use_display_name: bool,
+    collapse_message_lines: usize,
+    /// Extra indent for wrapped continuation lines.
+    wrap_indent: u16,
+    group_replies: bool,
+    /// When false, grouped follow-up messages show their time in the gutter.
+    group_start_timestamps_only: bool,
+    timestamp_position: TimestampPosition,
+    message_density: MessageDensity,
+    /// Marker ending reply previews and topics that were cut short.
+    ellipsis: String,
+    /// When false, link preview embeds are left out of the layout.
+    show_embeds: bool,
+    /// Older messages or forum threads have been requested but not arrived.
+    loading_more: bool,
 }
 
 impl MessagePaneData {
@@ -299,11 +413,22 @@ impl MessagePaneData {
             typing_indicator: None,
             authors: HashMap::new(),
             channels: HashMap::new(),
+            roles: HashMap::new(),
+            nicknames: HashMap::new(),
             last_layout_width: None,
             last_show_spoilers: None,
             last_image_preview: None,
             is_dirty: true,
             use_display_name,
+            collapse_message_lines: 0,
+            wrap_indent: 0,
+            group_replies: false,
+            group_start_timestamps_only: true,
+            timestamp_position: TimestampPosition::Left,
+            message_density: MessageDensity::Cozy,
+            ellipsis: DEFAULT_ELLIPSIS.to_string(),
+            show_embeds: true,
+            loading_more: false,
         }
     }
 
@@ -314,6 +439,7 @@ impl MessagePaneData {
         self.channel_topic = None;
         self.messages.clear();
         self.loading_state = LoadingState::Loading;
+        self.loading_more = false;
         self.error_message = None;
         self.is_dirty = true;
     }
@@ -332,39 +458,146 @@ impl MessagePaneData {
 
     pub fn set_messages(&mut self, messages: Vec<Message>) {
         for msg in &messages {
-            self.authors.insert(
-                msg.author().id().to_string(),
-                IdentityResolver::with_preference(self.use_display_name).resolve(msg.author()),
-            );
-            for mention in msg.mentions() {
-                self.authors.insert(
-                    mention.id().to_string(),
-                    IdentityResolver::with_preference(self.use_display_name).resolve(mention),
-                );
-            }
+            self.register_authors(msg);
         }
         self.messages = messages.into_iter().map(UiMessage::new).collect();
         self.update_grouping();
         self.loading_state = LoadingState::Loaded;
+        self.loading_more = false;
         self.error_message = None;
         self.is_dirty = true;
     }
 
+    /// Appends a message, replacing the local echo it confirms if there is one.
     pub fn add_message(&mut self, message: Message) {
+        if let Some(idx) = self.pending_echo_index(&message) {
+            self.messages[idx] = UiMessage::new(message);
+            self.update_grouping();
+            self.is_dirty = true;
+            return;
+        }
+        self.push_message(message, SendState::Sent);
+    }
+
+    /// Appends a local echo of a message that is still being sent.
+    pub fn add_pending_message(&mut self, message: Message) {
+        self.push_message(message, SendState::Pending);
+    }
+
+    /// Swaps the local echo `local_id` for the message the API returned.
+    ///
+    /// The echo is dropped instead if the gateway already delivered it.
+    pub fn confirm_pending_message(&mut self, local_id: MessageId, message: Message) {
+        let Some(idx) = self
+            .messages
+            .iter()
+            .position(|m| m.message.id() == local_id)
+        else {
+            self.add_message(message);
+            return;
+        };
+
+        if self.messages.iter().any(|m| m.message.id() == message.id()) {
+            self.messages.remove(idx);
+        } else {
+            self.messages[idx] = UiMessage::new(message);
+        }
+        self.update_grouping();
+        self.is_dirty = true;
+    }
+
+    /// Updates the send state of a message; returns `false` if it isn't loaded.
+    pub fn set_send_state(&mut self, message_id: MessageId, send_state: SendState) -> bool {
+        let Some(ui_msg) = self
+            .messages
+            .iter_mut()
+            .find(|m| m.message.id() == message_id)
+        else {
+            return false;
+        };
+        ui_msg.send_state = send_state;
+        self.update_grouping();
+        self.is_dirty = true;
+        true
+    }
+
+    #[must_use]
+    pub fn send_state(&self, message_id: MessageId) -> Option<SendState> {
+        self.messages
+            .iter()
+            .find(|m| m.message.id() == message_id)
+            .map(|m| m.send_state)
+    }
+
+    /// Marks a page of older messages or forum threads as in flight.
+    pub fn set_loading_more(&mut self, loading_more: bool) {
+        self.loading_more = loading_more;
+    }
+
+    #[must_use]
+    pub const fn is_loading_more(&self) -> bool {
+        self.loading_more
+    }
+
+    /// Whether anything the pane shows is still being fetched.
+    #[must_use]
+    pub const fn is_loading(&self) -> bool {
+        self.loading_more || matches!(self.loading_state, LoadingState::Loading)
+    }
+
+    #[must_use]
+    pub fn has_pending_sends(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|m| m.send_state == SendState::Pending)
+    }
+
+    /// Local echo that `message` confirms: matched by nonce when Discord
+    /// echoed one back, otherwise by author and content.
+    fn pending_echo_index(&self, message: &Message) -> Option<usize> {
+        self.messages.iter().position(|m| {
+            m.send_state == SendState::Pending
+                && m.message.channel_id() == message.channel_id()
+                && match (m.message.nonce(), message.nonce()) {
+                    (Some(ours), Some(theirs)) => ours == theirs,
+                    _ => {
+                        m.message.author().id() == message.author().id()
+                            && m.message.content() == message.content()
+                    }
+                }
+        })
+    }
+
+    /// Records display names for a message's author and the users it
+    /// mentions.
+    fn register_authors(&mut self, message: &Message) {
+        let resolver = IdentityResolver::with_preference(self.use_display_name);
+        let author_id = message.author().id().to_string();
+        let author = self
+            .nicknames
+            .get(&author_id)
+            .cloned()
+            .unwrap_or_else(|| resolver.resolve(message.author()));
+        self.authors.insert(author_id, author);
+        for mention in message.mentions() {
+            let mention_id = mention.id().to_string();
+            let name = self
+                .nicknames
+                .get(&mention_id)
+                .cloned()
+                .unwrap_or_else(|| resolver.resolve(mention));
+            self.authors.insert(mention_id, name);
+        }
+    }
+
+    fn push_message(&mut self, message: Message, send_state: SendState) {
         if self.channel_id == Some(message.channel_id())
             && !self.messages.iter().any(|m| m.message.id() == message.id())
         {
-            self.authors.insert(
-                message.author().id().to_string(),
-                IdentityResolver::with_preference(self.use_display_name).resolve(message.author()),
-            );
-            for mention in message.mentions() {
-                self.authors.insert(
-                    mention.id().to_string(),
-                    IdentityResolver::with_preference(self.use_display_name).resolve(mention),
-                );
-            }
-            self.messages.push_back(UiMessage::new(message));
+            self.register_authors(&message);
+            let mut ui_msg = UiMessage::new(message);
+            ui_msg.send_state = send_state;
+            self.messages.push_back(ui_msg);
             self.update_grouping();
             self.is_dirty = true;
         }
@@ -375,16 +608,7 @@ impl MessagePaneData {
         let mut added = 0;
         for msg in new_messages.into_iter().rev() {
             if !existing_ids.contains(&msg.id()) {
-                self.authors.insert(
-                    msg.author().id().to_string(),
-                    IdentityResolver::with_preference(self.use_display_name).resolve(msg.author()),
-                );
-                for mention in msg.mentions() {
-                    self.authors.insert(
-                        mention.id().to_string(),
-                        IdentityResolver::with_preference(self.use_display_name).resolve(mention),
-                    );
-                }
+                self.register_authors(&msg);
                 self.messages.push_front(UiMessage::new(msg));
                 added += 1;
             }
@@ -396,6 +620,45 @@ impl MessagePaneData {
         added
     }
 
+    /// Adds messages fetched after a reconnect, in id order among the
+    /// messages already shown. Ones already present are skipped.
+    pub fn merge_messages(&mut self, new_messages: Vec<Message>) -> usize {
+        let mut added = 0;
+        for msg in new_messages {
+            if self.channel_id != Some(msg.channel_id())
+                || self.messages.iter().any(|m| m.message.id() == msg.id())
+            {
+                continue;
+            }
+            self.register_authors(&msg);
+            // Local echoes stay at the bottom until Discord confirms them.
+            let idx = self
+                .messages
+                .iter()
+                .position(|m| {
+                    m.send_state != SendState::Sent || m.message.id().as_u64() > msg.id().as_u64()
+                })
+                .unwrap_or(self.messages.len());
+            self.messages.insert(idx, UiMessage::new(msg));
+            added += 1;
+        }
+        if added > 0 {
+            self.update_grouping();
+            self.is_dirty = true;
+        }
+        added
+    }
+
+    /// Newest message confirmed by Discord, skipping local echoes.
+    #[must_use]
+    pub fn last_sent_message_id(&self) -> Option<MessageId> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.send_state == SendState::Sent)
+            .map(|m| m.message.id())
+    }
+
     pub fn update_message(&mut self, updated: Message) {
         if let Some(pos) = self
             .messages
@@ -420,37 +683,51 @@ impl MessagePaneData {
             return;
         }
 
-        let mut previous_author_id: Option<String> = None;
-        let mut previous_timestamp: Option<i64> = None;
+        let mut previous: Option<Arc<Message>> = None;
 
         for ui_msg in &mut self.messages {
             let msg = &ui_msg.message;
-            let current_author_id = msg.author().id().to_string();
-            let current_timestamp = msg.timestamp().timestamp();
-
             ui_msg.group = MessageGroup::Start;
 
-            if !msg.is_reply()
-                && let (Some(prev_id), Some(prev_ts)) = (&previous_author_id, previous_timestamp)
-                && prev_id == &current_author_id
+            if let Some(prev) = &previous
+                && ui_msg.send_state == SendState::Sent
+                && prev.author().id() == msg.author().id()
+                && (!msg.is_reply() || (self.group_replies && same_reply_target(prev, msg)))
             {
-                let diff = current_timestamp.saturating_sub(prev_ts);
+                let diff = msg
+                    .timestamp()
+                    .timestamp()
+                    .saturating_sub(prev.timestamp().timestamp());
                 if diff < GROUPING_WINDOW_SECONDS {
                     ui_msg.group = MessageGroup::Compact;
                 }
             }
 
-            previous_author_id = Some(current_author_id);
-            previous_timestamp = Some(current_timestamp);
+            previous = Some(Arc::clone(msg));
         }
     }
 
     pub fn set_error(&mut self, error: String) {
         self.loading_state = LoadingState::Error;
+        self.loading_more = false;
         self.error_message = Some(error);
         self.is_dirty = true;
     }
 
+    /// Replaces the open channel with the empty-guild notice.
+    pub fn set_no_accessible_channels(&mut self) {
+        self.channel_id = None;
+        self.channel_name = None;
+        self.channel_topic = None;
+        self.channel_icon = None;
+        self.messages.clear();
+        self.loading_state = LoadingState::NoAccessibleChannels;
+        self.loading_more = false;
+        self.error_message = None;
+        self.typing_indicator = None;
+        self.is_dirty = true;
+    }
+
     pub fn clear(&mut self) {
         self.channel_id = None;
         self.channel_name = None;
@@ -459,6 +736,7 @@ impl MessagePaneData {
         self.online_count = None;
         self.messages.clear();
         self.loading_state = LoadingState::Idle;
+        self.loading_more = false;
         self.error_message = None;
         self.is_dm = false;
         self.typing_indicator = None;
@@ -472,21 +750,90 @@ impl MessagePaneData {
         self.refresh_authors();
     }
 
+    /// Sets whether consecutive replies to the same message or author group.
+    pub fn set_group_replies(&mut self, group_replies: bool) {
+        if self.group_replies != group_replies {
+            self.group_replies = group_replies;
+            self.update_grouping();
+            self.is_dirty = true;
+        }
+    }
+
+    /// Sets whether only the first message of a group shows its timestamp.
+    pub fn set_group_start_timestamps_only(&mut self, only: bool) {
+        if self.group_start_timestamps_only != only {
+            self.group_start_timestamps_only = only;
+            self.is_dirty = true;
+        }
+    }
+
+    pub fn set_timestamp_position(&mut self, position: TimestampPosition) {
+        if self.timestamp_position != position {
+            self.timestamp_position = position;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Switching density changes every message's height, so the layout is redone.
+    pub fn set_message_density(&mut self, density: MessageDensity) {
+        if self.message_density != density {
+            self.message_density = density;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Sets whether link preview embeds are shown; rich embeds always are.
+    pub fn set_show_embeds(&mut self, show: bool) {
+        if self.show_embeds != show {
+            self.show_embeds = show;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Sets the marker ending reply previews and topics that were cut short.
+    pub fn set_ellipsis(&mut self, ellipsis: String) {
+        if self.ellipsis != ellipsis {
+            self.ellipsis = ellipsis;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Sets the content line count above which messages collapse (0 disables).
+    pub fn set_collapse_message_lines(&mut self, lines: usize) {
+        if self.collapse_message_lines != lines {
+            self.collapse_message_lines = lines;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Sets the hanging indent applied to wrapped continuation lines.
+    pub fn set_wrap_indent(&mut self, indent: u16) {
+        if self.wrap_indent != indent {
+            self.wrap_indent = indent;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Shows `user_id` under a guild nickname, or their resolved name again
+    /// when `nick` is `None`.
+    pub fn set_author_nick(&mut self, user_id: &str, nick: Option<String>) {
+        if self.nicknames.get(user_id) == nick.as_ref() {
+            return;
+        }
+        match nick {
+            Some(nick) => self.nicknames.insert(user_id.to_string(), nick),
+            None => self.nicknames.remove(user_id),
+        };
+        self.refresh_authors();
+    }
+
     pub fn refresh_authors(&mut self) {
         self.authors.clear();
-        for ui_msg in &self.messages {
-            self.authors.insert(
-                ui_msg.message.author().id().to_string(),
-                IdentityResolver::with_preference(self.use_display_name)
-                    .resolve(ui_msg.message.author()),
-            );
-            for mention in ui_msg.message.mentions() {
-                self.authors.insert(
-                    mention.id().to_string(),
-                    IdentityResolver::with_preference(self.use_display_name).resolve(mention),
-                );
-            }
+        let messages = std::mem::take(&mut self.messages);
+        for ui_msg in &messages {
+            self.register_authors(&ui_msg.message);
         }
+        self.messages = messages;
         self.is_dirty = true;
     }
 
@@ -498,6 +845,15 @@ impl MessagePaneData {
         self.channels.insert(id, name);
     }
 
+    /// Sets the active guild's roles so their mentions render by name and color.
+    pub fn set_roles(&mut self, roles: &[Role]) {
+        self.roles = roles
+            .iter()
+            .map(|role| (role.id.to_string(), (role.name.clone(), role.color)))
+            .collect();
+        self.is_dirty = true;
+    }
+
     #[must_use]
     pub fn is_channel_known(&self, id: &str) -> bool {
         self.channels.contains_key(id)
@@ -575,17 +931,22 @@ impl MessagePaneData {
         default_color: Color,
         show_spoilers: bool,
         image_preview: bool,
+        expanded_messages: &HashSet<MessageId>,
     ) {
         if !self.is_dirty
             && self.last_layout_width == Some(width)
             && self.last_show_spoilers == Some(show_spoilers)
             && self.last_image_preview == Some(image_preview)
-            && !self.messages.iter().any(|m| m.rendered_content.is_none())
+            && !self.messages.iter().any(|m| {
+                m.rendered_content.is_none()
+                    || (m.collapsible && m.collapsed == expanded_messages.contains(&m.message.id()))
+            })
         {
             return;
         }
 
-        let indent_width = u16::try_from(CONTENT_INDENT).unwrap_or(0);
+        let indent = content_indent(self.timestamp_position);
+        let indent_width = u16::try_from(indent).unwrap_or(0);
         let content_width = width
             .saturating_sub(indent_width)
             .saturating_sub(SCROLLBAR_MARGIN);
@@ -596,12 +957,19 @@ impl MessagePaneData {
 
         let authors = &self.authors;
         let channels = &self.channels;
-        let resolver = HashMapResolver { authors, channels };
+        let roles = &self.roles;
+        let resolver = HashMapResolver {
+            authors,
+            channels,
+            roles,
+        };
 
         for ui_msg in &mut self.messages {
+            let expanded = expanded_messages.contains(&ui_msg.message.id());
             Self::layout_message(
                 ui_msg,
                 content_width,
+                indent,
                 markdown_service,
                 default_color,
                 show_spoilers,
@@ -609,6 +977,12 @@ impl MessagePaneData {
                 &resolver,
                 authors,
                 self.use_display_name,
+                self.collapse_message_lines,
+                self.wrap_indent,
+                expanded,
+                &self.ellipsis,
+                self.show_embeds,
+                self.message_density,
             );
         }
 
@@ -618,10 +992,15 @@ impl MessagePaneData {
         self.is_dirty = false;
     }
 
-    #[allow(clippy::too_many_lines, clippy::too_many_arguments)]
+    #[allow(
+        clippy::too_many_lines,
+        clippy::too_many_arguments,
+        clippy::fn_params_excessive_bools
+    )]
     fn layout_message(
         ui_msg: &mut UiMessage,
         content_width: u16,
+        indent: usize,
         markdown_service: &MarkdownRenderer,
         default_color: Color,
         show_spoilers: bool,
@@ -629,45 +1008,77 @@ impl MessagePaneData {
         resolver: &HashMapResolver<'_>,
         authors: &HashMap<String, String>,
         use_display_name: bool,
+        collapse_message_lines: usize,
+        wrap_indent: u16,
+        expanded: bool,
+        ellipsis: &str,
+        show_embeds: bool,
+        density: MessageDensity,
     ) {
         let message = &ui_msg.message;
 
-        let text =
-            markdown_service.render(ui_msg.parsed_content.clone(), Some(resolver), show_spoilers);
+        let text = system_message_text(message, use_display_name).map_or_else(
+            || {
+                markdown_service.render(
+                    ui_msg.parsed_content.clone(),
+                    Some(resolver),
+                    show_spoilers,
+                )
+            },
+            Text::from,
+        );
 
-        let wrapped_text = wrap_styled_text(text, content_width);
-        let content_lines = u16::try_from(wrapped_text.lines.len()).unwrap_or(0);
+        let mut wrapped_text = wrap_styled_text(text, content_width, wrap_indent);
+        wrapped_text.lines.truncate(MAX_CONTENT_LINES);
+        let total_lines = wrapped_text.lines.len();
+        ui_msg.collapsible = collapse_message_lines > 0 && total_lines > collapse_message_lines;
+        ui_msg.collapsed = ui_msg.collapsible && !expanded;
+        if ui_msg.collapsed {
+            wrapped_text.lines.truncate(collapse_message_lines);
+            wrapped_text.lines.push(Line::from(format!(
+                "… show more ({} lines)",
+                total_lines - collapse_message_lines
+            )));
+        }
+        let mut height = line_height(wrapped_text.lines.len());
 
         ui_msg.rendered_content = Some(wrapped_text);
 
-        let mut height = content_lines;
-
         if ui_msg.group == MessageGroup::Start {
-            height += 1;
+            height = height.saturating_add(1);
         }
 
         if message.is_reply() {
-            height += 1;
+            height = height.saturating_add(1);
         }
 
+        ui_msg.forward_preview =
+            forward_block_lines(message, content_width, indent, use_display_name);
+        height = height.saturating_add(line_height(ui_msg.forward_preview.len()));
+
         let non_image_attachments = message
             .attachments()
             .iter()
             .filter(|a| !a.is_image())
             .count();
-        height += u16::try_from(non_image_attachments).unwrap_or(0);
+        height = height.saturating_add(file_attachment_rows(non_image_attachments, density));
 
         if image_preview {
-            height += ui_msg.total_image_height(content_width);
+            height = height.saturating_add(ui_msg.total_image_height(content_width, show_spoilers));
         } else {
-            height += u16::try_from(ui_msg.image_attachments.len()).unwrap_or(0);
+            height = height.saturating_add(line_height(ui_msg.image_attachments.len()));
         }
 
         let mut rendered_embeds = Vec::new();
-        for embed in message.embeds() {
-            let layout =
-                calculate_embed_layout(embed, content_width, markdown_service, default_color);
-            height += layout.height;
+        for embed in shown_embeds(message, show_embeds) {
+            let layout = calculate_embed_layout(
+                embed,
+                content_width,
+                markdown_service,
+                default_color,
+                density,
+            );
+            height = height.saturating_add(layout.height);
             rendered_embeds.push(layout);
         }
         ui_msg.rendered_embeds = rendered_embeds;
@@ -685,7 +1096,7 @@ impl MessagePaneData {
                         .map_or_else(|| format!("@{id}"), |name| format!("@{name}"))
                 });
 
-                let snippet = truncate_string(&resolved_content, 50);
+                let snippet = truncate_to_width(&resolved_content, 50, ellipsis);
 
                 let reply_style = Style::default()
                     .fg(Color::DarkGray)
@@ -693,7 +1104,7 @@ impl MessagePaneData {
                 let username_style = Style::default().fg(Color::Cyan);
 
                 let spans = vec![
-                    Span::raw(" ".repeat(CONTENT_INDENT)),
+                    Span::raw(" ".repeat(indent)),
                     Span::styled("┌─ Replying to ", reply_style),
                     Span::styled(
                         IdentityResolver::with_preference(use_display_name)
@@ -708,7 +1119,7 @@ impl MessagePaneData {
                     .fg(Color::Red)
                     .add_modifier(Modifier::ITALIC);
                 let spans = vec![
-                    Span::raw(" ".repeat(CONTENT_INDENT)),
+                    Span::raw(" ".repeat(indent)),
                     Span::styled("┌─ Original message unavailable", error_style),
                 ];
                 ui_msg.reply_preview = Some(Line::from(spans));
@@ -774,6 +1185,10 @@ impl MentionResolver for MessagePaneData {
     fn resolve_channel(&self, channel_id: &str) -> Option<String> {
         self.channels.get(channel_id).cloned()
     }
+
+    fn resolve_role(&self, role_id: &str) -> Option<(String, u32)> {
+        self.roles.get(role_id).cloned()
+    }
 }
 
 impl Default for MessagePaneData {
@@ -797,6 +1212,17 @@ pub struct MessagePaneState {
     content_height: usize,
     viewport_height: u16,
     last_width: u16,
+    /// Blocked runs expanded into per-message rows, keyed by the run's first message.
+    expanded_blocked_runs: HashSet<MessageId>,
+    /// Collapsed long messages the user has expanded.
+    expanded_messages: HashSet<MessageId>,
+    /// Messages that arrived below the view since following stopped.
+    unseen_messages: usize,
+    /// Whether animations are on: loading indicators spin, and animated
+    /// avatars are not flagged as still frames.
+    animate_spinner: bool,
+    spinner_frame: usize,
+    spinner_elapsed: Duration,
 }
 
 impl MessagePaneState {
@@ -815,9 +1241,52 @@ impl MessagePaneState {
             content_height: 0,
             viewport_height: 0,
             last_width: 0,
+            expanded_blocked_runs: HashSet::new(),
+            expanded_messages: HashSet::new(),
+            unseen_messages: 0,
+            animate_spinner: false,
+            spinner_frame: 0,
+            spinner_elapsed: Duration::ZERO,
         }
     }
 
+    pub fn set_animate_spinner(&mut self, animate: bool) {
+        self.animate_spinner = animate;
+    }
+
+    /// Advances the loading spinner while `loading`, resetting it otherwise.
+    ///
+    /// Returns whether the spinner moved to a new frame.
+    pub fn tick_spinner(&mut self, duration: Duration, loading: bool) -> bool {
+        if !loading || !self.animate_spinner {
+            self.spinner_frame = 0;
+            self.spinner_elapsed = Duration::ZERO;
+            return false;
+        }
+
+        self.spinner_elapsed = self.spinner_elapsed.saturating_add(duration);
+        let mut advanced = false;
+        while self.spinner_elapsed >= LOADING_SPINNER_INTERVAL {
+            self.spinner_elapsed -= LOADING_SPINNER_INTERVAL;
+            self.spinner_frame = (self.spinner_frame + 1) % LOADING_SPINNER.len();
+            advanced = true;
+        }
+        advanced
+    }
+
+    /// The current spinner glyph, or `None` when animations are off.
+    #[must_use]
+    pub fn spinner(&self) -> Option<&'static str> {
+        self.animate_spinner
+            .then(|| LOADING_SPINNER[self.spinner_frame])
+    }
+
+    /// Prefixes `text` with the spinner glyph when animations are on.
+    fn loading_label(&self, text: &str) -> String {
+        self.spinner()
+            .map_or_else(|| text.to_string(), |frame| format!("{frame} {text}"))
+    }
+
     pub fn set_focused(&mut self, focused: bool) {
         self.flags.focused = focused;
     }
@@ -842,6 +1311,29 @@ impl MessagePaneState {
         self.last_width
     }
 
+    #[must_use]
+    pub const fn expanded_messages(&self) -> &HashSet<MessageId> {
+        &self.expanded_messages
+    }
+
+    /// Expands the selected long message, or collapses it again.
+    ///
+    /// Returns `false` if the selected message is too short to collapse.
+    fn toggle_selected_expanded(&mut self, data: &MessagePaneData) -> bool {
+        let Some(ui_msg) = self.selected_index.and_then(|idx| data.messages.get(idx)) else {
+            return false;
+        };
+        if !ui_msg.collapsible {
+            return false;
+        }
+        let id = ui_msg.message.id();
+        if !self.expanded_messages.remove(&id) {
+            self.expanded_messages.insert(id);
+        }
+        self.flags.scroll_to_selection = true;
+        true
+    }
+
     pub fn toggle_spoiler(&mut self) {
         self.show_spoilers = !self.show_spoilers;
     }
@@ -933,17 +1425,70 @@ impl MessagePaneState {
     fn find_current_render_item_index(&self, render_items: &[RenderItem]) -> Option<usize> {
         let selected = self.selected_index?;
         render_items.iter().position(|item| match item {
-            RenderItem::Message { idx } => *idx == selected,
+            RenderItem::Message { idx } | RenderItem::BlockedMessage { idx, .. } => {
+                *idx == selected
+            }
             RenderItem::BlockedRun { start_idx, count } => {
                 selected >= *start_idx && selected < *start_idx + *count
             }
         })
     }
 
+    /// Start index of the blocked run containing the selection, if any.
+    fn selected_blocked_run(&self, render_items: &[RenderItem]) -> Option<usize> {
+        let current = self.find_current_render_item_index(render_items)?;
+        match render_items[current] {
+            RenderItem::BlockedRun { start_idx, .. } => Some(start_idx),
+            RenderItem::BlockedMessage { run_start, .. } => Some(run_start),
+            RenderItem::Message { .. } => None,
+        }
+    }
+
+    /// Expands the selected blocked run into individual rows, or collapses it.
+    ///
+    /// Returns `false` if the selection isn't part of a blocked run.
+    fn toggle_selected_blocked_run(
+        &mut self,
+        render_items: &[RenderItem],
+        data: &MessagePaneData,
+    ) -> bool {
+        let Some(run_start) = self.selected_blocked_run(render_items) else {
+            return false;
+        };
+        let Some(run_id) = data.get_message(run_start).map(Message::id) else {
+            return false;
+        };
+        if !self.expanded_blocked_runs.remove(&run_id) {
+            self.expanded_blocked_runs.insert(run_id);
+        }
+        self.flags.scroll_to_selection = true;
+        true
+    }
+
+    /// Collapses expanded blocked runs once the selection has left them.
+    ///
+    /// Returns `true` if any run was collapsed.
+    fn collapse_blocked_runs_outside_selection(
+        &mut self,
+        render_items: &[RenderItem],
+        data: &MessagePaneData,
+    ) -> bool {
+        if self.expanded_blocked_runs.is_empty() {
+            return false;
+        }
+        let keep = self
+            .selected_blocked_run(render_items)
+            .and_then(|run_start| data.get_message(run_start))
+            .map(Message::id);
+        let before = self.expanded_blocked_runs.len();
+        self.expanded_blocked_runs.retain(|id| Some(*id) == keep);
+        self.expanded_blocked_runs.len() != before
+    }
+
     /// Get the representative message index for a render item.
     fn message_index_for_render_item(item: &RenderItem) -> usize {
         match item {
-            RenderItem::Message { idx } => *idx,
+            RenderItem::Message { idx } | RenderItem::BlockedMessage { idx, .. } => *idx,
             RenderItem::BlockedRun { start_idx, .. } => *start_idx,
         }
     }
@@ -969,25 +1514,41 @@ impl MessagePaneState {
     #[allow(clippy::missing_const_for_fn)]
     pub fn clear_selection(&mut self) {
         self.selected_index = None;
-        self.flags.is_following = true;
+        self.resume_following();
         self.scroll_to_bottom();
     }
 
     pub fn on_new_message(&mut self) {
         if self.flags.is_following {
             self.scroll_to_bottom();
+        } else {
+            self.unseen_messages += 1;
         }
     }
 
+    /// Number of messages that arrived while scrolled away from the bottom.
+    #[must_use]
+    pub const fn unseen_messages(&self) -> usize {
+        self.unseen_messages
+    }
+
+    /// Follows new messages again and forgets the unseen count.
+    const fn resume_following(&mut self) {
+        self.flags.is_following = true;
+        self.unseen_messages = 0;
+    }
+
     /// Resets the state for a new channel - clears selection, enables following, resets scroll.
     /// This ensures new channels start at the bottom (following mode).
     pub fn on_channel_change(&mut self) {
         self.selected_index = None;
-        self.flags.is_following = true;
+        self.resume_following();
         self.vertical_scroll = 0;
         self.content_height = 0;
         self.viewport_height = 0;
         self.view_mode = ViewMode::Messages;
+        self.expanded_blocked_runs.clear();
+        self.expanded_messages.clear();
     }
 
     #[allow(clippy::missing_const_for_fn)]
@@ -1094,6 +1655,7 @@ impl MessagePaneState {
             data.ui_messages(),
             relationship_state,
             hide_blocked_completely,
+            &self.expanded_blocked_runs,
         );
 
         match registry.find_action(key) {
@@ -1104,7 +1666,9 @@ impl MessagePaneState {
             Some(Action::NavigateUp) => {
                 let at_top = self.selected_index.is_some()
                     && render_items.first().is_some_and(|item| match item {
-                        RenderItem::Message { idx } => self.selected_index == Some(*idx),
+                        RenderItem::Message { idx } | RenderItem::BlockedMessage { idx, .. } => {
+                            self.selected_index == Some(*idx)
+                        }
                         RenderItem::BlockedRun { start_idx, count } => self
                             .selected_index
                             .is_some_and(|sel| sel >= *start_idx && sel < *start_idx + *count),
@@ -1121,7 +1685,7 @@ impl MessagePaneState {
                     .content_height
                     .saturating_sub(self.viewport_height as usize);
                 if self.vertical_scroll == max_scroll {
-                    self.flags.is_following = true;
+                    self.resume_following();
                 }
                 None
             }
@@ -1147,10 +1711,16 @@ impl MessagePaneState {
             Some(Action::ScrollToBottom) => {
                 self.scroll_to_bottom();
                 if self.selected_index.is_none() {
-                    self.flags.is_following = true;
+                    self.resume_following();
                 }
                 None
             }
+            Some(Action::JumpToLatest) => {
+                self.selected_index = None;
+                self.resume_following();
+                self.scroll_to_bottom();
+                Some(MessagePaneAction::ClearSelection)
+            }
             Some(Action::Cancel | Action::ClearSelection) => {
                 if self.selected_index.is_some() {
                     self.clear_selection();
@@ -1198,9 +1768,31 @@ impl MessagePaneState {
             Some(Action::YankId) => self
                 .get_selected_message_id(data)
                 .map(|id| MessagePaneAction::YankId(id.to_string())),
+            Some(Action::CopyMessageLink) => self
+                .get_selected_message_id(data)
+                .map(MessagePaneAction::CopyLink),
+            Some(Action::CopyCodeBlock) => self
+                .get_selected_message_id(data)
+                .map(MessagePaneAction::CopyCodeBlock),
             Some(Action::OpenAttachments) => self
                 .get_selected_message_id(data)
                 .map(MessagePaneAction::OpenAttachments),
+            Some(Action::RetrySend) => self
+                .get_selected_message_id(data)
+                .filter(|id| data.send_state(*id) == Some(SendState::Failed))
+                .map(MessagePaneAction::RetrySend),
+            Some(Action::PublishMessage) => self
+                .get_selected_message_id(data)
+                .map(MessagePaneAction::Publish),
+            Some(Action::PurgeOwnMessages) => Some(MessagePaneAction::PurgeOwn),
+            Some(Action::NextMention) => Some(MessagePaneAction::JumpToMention { forward: true }),
+            Some(Action::PreviousMention) => {
+                Some(MessagePaneAction::JumpToMention { forward: false })
+            }
+            Some(Action::ToggleMessageExpand) => {
+                self.toggle_selected_expanded(data);
+                None
+            }
             Some(Action::JumpToReply) => {
                 if let Some(msg) = self.get_selected_message(data)
                     && let Some(reference) = msg.reference()
@@ -1230,7 +1822,9 @@ impl MessagePaneState {
                 None
             }
             Some(Action::Select) => {
-                self.show_spoilers = !self.show_spoilers;
+                if !self.toggle_selected_blocked_run(&render_items, data) {
+                    self.show_spoilers = !self.show_spoilers;
+                }
                 None
             }
 
@@ -1256,6 +1850,7 @@ impl Default for MessagePaneState {
 }
 
 #[allow(missing_docs)]
+#[derive(Clone)]
 pub struct MessagePaneStyle {
     pub border_style: Style,
     pub border_style_focused: Style,
@@ -1277,6 +1872,8 @@ pub struct MessagePaneStyle {
     pub scrollbar_track_style: Style,
     pub scrollbar_thumb_style: Style,
     pub blocked_style: Style,
+    pub collapse_marker_style: Style,
+    pub author_color_mode: AuthorColorMode,
 }
 
 impl MessagePaneStyle {
@@ -1309,6 +1906,8 @@ impl MessagePaneStyle {
             blocked_style: Style::default()
                 .fg(blocked_fg)
                 .add_modifier(Modifier::ITALIC),
+            collapse_marker_style: theme.dimmed_style.add_modifier(Modifier::ITALIC),
+            author_color_mode: theme.author_color_mode,
             ..Self::default()
         }
     }
@@ -1351,16 +1950,23 @@ impl Default for MessagePaneStyle {
             blocked_style: Style::default()
                 .fg(Color::DarkGray)
                 .add_modifier(Modifier::ITALIC),
+            collapse_marker_style: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+            author_color_mode: AuthorColorMode::default(),
         }
     }
 }
 
-#[allow(missing_docs)]
+#[allow(missing_docs, clippy::struct_excessive_bools)]
 pub struct MessagePane<'a> {
     data: &'a mut MessagePaneData,
     style: MessagePaneStyle,
     disable_user_colors: bool,
     image_preview: bool,
+    /// Render images as descriptive text lines when previews are off or the
+    /// terminal cannot draw them.
+    image_placeholders: bool,
     timestamp_format: &'a str,
     current_user_id: Option<String>,
     markdown_service: &'a MarkdownRenderer,
@@ -1378,6 +1984,7 @@ impl<'a> MessagePane<'a> {
             style: MessagePaneStyle::default(),
             disable_user_colors: false,
             image_preview: true,
+            image_placeholders: false,
             timestamp_format: "%H:%M",
             current_user_id: None,
             markdown_service,
@@ -1404,6 +2011,12 @@ impl<'a> MessagePane<'a> {
         self
     }
 
+    #[must_use]
+    pub const fn with_image_placeholders(mut self, placeholders: bool) -> Self {
+        self.image_placeholders = placeholders;
+        self
+    }
+
     #[must_use]
     pub const fn with_timestamp_format(mut self, format: &'a str) -> Self {
         self.timestamp_format = format;
@@ -1438,29 +2051,44 @@ impl<'a> MessagePane<'a> {
         markdown_service: &MarkdownRenderer,
         default_color: Color,
     ) -> u16 {
-        let indent_width = u16::try_from(CONTENT_INDENT).unwrap_or(0);
+        let indent = content_indent(self.data.timestamp_position);
+        let indent_width = u16::try_from(indent).unwrap_or(0);
         let content_width = width
             .saturating_sub(indent_width)
             .saturating_sub(SCROLLBAR_MARGIN);
 
         let authors = &self.data.authors;
         let channels = &self.data.channels;
-        let resolver = HashMapResolver { authors, channels };
-        let text = markdown_service.render_markdown(message.content(), Some(&resolver), false);
+        let roles = &self.data.roles;
+        let resolver = HashMapResolver {
+            authors,
+            channels,
+            roles,
+        };
+        let text = system_message_text(message, self.data.use_display_name).map_or_else(
+            || markdown_service.render_markdown(message.content(), Some(&resolver), false),
+            Text::from,
+        );
 
-        let mut content_lines = 0;
-        for line in &text.lines {
-            let line_text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
-            content_lines +=
-                u16::try_from(wrap_text(&line_text, content_width as usize).len()).unwrap_or(0);
+        let mut content_lines = wrap_styled_text(text, content_width, self.data.wrap_indent)
+            .lines
+            .len()
+            .min(MAX_CONTENT_LINES);
+        let collapse_lines = self.data.collapse_message_lines;
+        if collapse_lines > 0 && content_lines > collapse_lines {
+            content_lines = collapse_lines + 1;
         }
 
-        let mut height = 1 + content_lines;
+        let mut height = line_height(content_lines).saturating_add(1);
 
         if message.is_reply() {
-            height += 1;
+            height = height.saturating_add(1);
         }
 
+        height = height.saturating_add(line_height(
+            forward_block_lines(message, content_width, indent, self.data.use_display_name).len(),
+        ));
+
         if message.has_attachments() {
             if self.image_preview {
                 for attachment in message.attachments().iter().filter(|a| a.is_image()) {
@@ -1468,9 +2096,9 @@ impl<'a> MessagePane<'a> {
                         let aspect = f64::from(h) / f64::from(w);
                         #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
                         let estimated = (f64::from(content_width) * 0.5 * aspect).ceil() as u16;
-                        height += estimated.clamp(1, MAX_IMAGE_HEIGHT);
+                        height = height.saturating_add(estimated.clamp(1, MAX_IMAGE_HEIGHT));
                     } else {
-                        height += 3;
+                        height = height.saturating_add(3);
                     }
                 }
 
@@ -1479,15 +2107,34 @@ impl<'a> MessagePane<'a> {
                     .iter()
                     .filter(|a| !a.is_image())
                     .count();
-                height += u16::try_from(non_image_count).unwrap_or(0);
+                height = height.saturating_add(file_attachment_rows(
+                    non_image_count,
+                    self.data.message_density,
+                ));
             } else {
-                height += u16::try_from(message.attachments().len()).unwrap_or(u16::MAX);
+                let image_count = message
+                    .attachments()
+                    .iter()
+                    .filter(|a| a.is_image())
+                    .count();
+                height = height
+                    .saturating_add(file_attachment_rows(
+                        message.attachments().len() - image_count,
+                        self.data.message_density,
+                    ))
+                    .saturating_add(line_height(image_count));
             }
         }
 
-        for embed in message.embeds() {
-            height += calculate_embed_layout(embed, content_width, markdown_service, default_color)
-                .height;
+        for embed in shown_embeds(message, self.data.show_embeds) {
+            let layout = calculate_embed_layout(
+                embed,
+                content_width,
+                markdown_service,
+                default_color,
+                self.data.message_density,
+            );
+            height = height.saturating_add(layout.height);
         }
 
         height
@@ -1509,7 +2156,7 @@ impl<'a> MessagePane<'a> {
         }
 
         if let Some(topic) = self.data.channel_topic() {
-            let truncated_topic = truncate_string(topic, 60);
+            let truncated_topic = truncate_to_width(topic, 60, &self.data.ellipsis);
             block = block.title(
                 Line::from(Span::styled(
                     format!(" {truncated_topic} "),
@@ -1519,6 +2166,16 @@ impl<'a> MessagePane<'a> {
             );
         }
 
+        if self.data.is_loading_more() {
+            block = block.title_bottom(
+                Line::from(Span::styled(
+                    format!(" {} ", state.loading_label("Loading more...")),
+                    self.style.loading_style,
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
+
         if let Some(typing) = self.data.typing_indicator() {
             let clean_typing = clean_text(typing);
             block = block.title_bottom(
@@ -1546,6 +2203,7 @@ impl<'a> MessagePane<'a> {
             style,
             disable_user_colors,
             image_preview,
+            image_placeholders,
             timestamp_format,
             current_user_id,
             markdown_service,
@@ -1555,7 +2213,8 @@ impl<'a> MessagePane<'a> {
 
         match data.loading_state() {
             LoadingState::Loading => {
-                let loading = Paragraph::new("Loading messages...").style(style.loading_style);
+                let loading = Paragraph::new(state.loading_label("Loading messages..."))
+                    .style(style.loading_style);
                 loading.render(inner_area, buf);
                 return;
             }
@@ -1571,6 +2230,12 @@ impl<'a> MessagePane<'a> {
                 empty.render(inner_area, buf);
                 return;
             }
+            LoadingState::NoAccessibleChannels => {
+                let empty =
+                    Paragraph::new("No accessible channels in this guild").style(style.empty_style);
+                empty.render(inner_area, buf);
+                return;
+            }
             LoadingState::Loaded => {}
         }
 
@@ -1586,6 +2251,7 @@ impl<'a> MessagePane<'a> {
             style.content_style.fg.unwrap_or(Color::White),
             state.show_spoilers,
             *image_preview,
+            &state.expanded_messages,
         );
 
         if let Some(error_msg) = &data.error_message
@@ -1605,18 +2271,24 @@ impl<'a> MessagePane<'a> {
             error_para.render(error_area, buf);
         }
 
-        let render_items = build_render_items(
+        let mut render_items = build_render_items(
             data.ui_messages(),
             *relationship_state,
             *hide_blocked_completely,
+            &state.expanded_blocked_runs,
         );
+        if state.collapse_blocked_runs_outside_selection(&render_items, data) {
+            render_items = build_render_items(
+                data.ui_messages(),
+                *relationship_state,
+                *hide_blocked_completely,
+                &state.expanded_blocked_runs,
+            );
+        }
 
         let content_height: usize = render_items
             .iter()
-            .map(|item| match item {
-                RenderItem::Message { idx } => data.messages[*idx].estimated_height as usize,
-                RenderItem::BlockedRun { .. } => 1,
-            })
+            .map(|item| render_item_height(item, &data.messages))
             .sum();
         state.update_dimensions(content_height, inner_area.height);
         state.last_width = inner_area.width;
@@ -1631,12 +2303,11 @@ impl<'a> MessagePane<'a> {
             let mut selection_height = 0;
 
             for item in &render_items {
-                let h = match item {
-                    RenderItem::Message { idx } => data.messages[*idx].estimated_height as usize,
-                    RenderItem::BlockedRun { .. } => 1,
-                };
+                let h = render_item_height(item, &data.messages);
                 let contains_selection = match item {
-                    RenderItem::Message { idx } => *idx == selected_idx,
+                    RenderItem::Message { idx } | RenderItem::BlockedMessage { idx, .. } => {
+                        *idx == selected_idx
+                    }
                     RenderItem::BlockedRun { start_idx, count } => {
                         selected_idx >= *start_idx && selected_idx < *start_idx + *count
                     }
@@ -1665,10 +2336,7 @@ impl<'a> MessagePane<'a> {
 
         let authors = &data.authors;
         for item in render_items {
-            let h = match &item {
-                RenderItem::Message { idx } => data.messages[*idx].estimated_height as usize,
-                RenderItem::BlockedRun { .. } => 1,
-            };
+            let h = render_item_height(&item, &data.messages);
             let current_y_usize = usize::try_from(current_y).unwrap_or(0);
 
             if current_y_usize + h > offset && current_y_usize < offset + inner_area.height as usize
@@ -1682,8 +2350,37 @@ impl<'a> MessagePane<'a> {
                             .is_some_and(|sel| sel >= start_idx && sel < start_idx + count);
                         render_blocked_run(style, render_y, inner_area, buf, is_selected, count);
                     }
+                    RenderItem::BlockedMessage { idx, .. } => {
+                        let ui_msg = &mut data.messages[idx];
+                        render_ui_message(
+                            ui_msg,
+                            style,
+                            authors,
+                            idx,
+                            render_y,
+                            inner_area,
+                            buf,
+                            state,
+                            *disable_user_colors,
+                            data.use_display_name,
+                            *image_preview,
+                            *image_placeholders,
+                            timestamp_format,
+                            !data.group_start_timestamps_only,
+                            data.timestamp_position,
+                            data.message_density,
+                            current_user_id.as_deref(),
+                            false,
+                        );
+                        dim_rows(style, render_y, h, inner_area, buf);
+                    }
                     RenderItem::Message { idx } => {
                         let ui_msg = &mut data.messages[idx];
+                        let mask_reply = *hide_blocked_completely
+                            && ui_msg.message.referenced().is_some_and(|referenced| {
+                                let author_id = referenced.author().id().to_string();
+                                relationship_state.is_some_and(|s| s.is_blocked_str(&author_id))
+                            });
                         render_ui_message(
                             ui_msg,
                             style,
@@ -1696,8 +2393,13 @@ impl<'a> MessagePane<'a> {
                             *disable_user_colors,
                             data.use_display_name,
                             *image_preview,
+                            *image_placeholders,
                             timestamp_format,
+                            !data.group_start_timestamps_only,
+                            data.timestamp_position,
+                            data.message_density,
                             current_user_id.as_deref(),
+                            mask_reply,
                         );
                     }
                 }
@@ -1723,6 +2425,10 @@ impl<'a> MessagePane<'a> {
             height: inner_area.height,
         };
         scrollbar.render(scrollbar_area, buf);
+
+        if !state.flags.is_following && state.unseen_messages > 0 {
+            render_unseen_indicator(inner_area, buf, state.unseen_messages, style.selected_style);
+        }
     }
 
     fn render_forum(&self, area: Rect, buf: &mut Buffer, state: &mut MessagePaneState) {
@@ -1732,13 +2438,18 @@ impl<'a> MessagePane<'a> {
         block.render(area, buf);
 
         state.update_dimensions(0, inner_area.height);
+        let loading_label = state.loading_label("Loading threads...");
 
         let ViewMode::Forum(forum_state) = &mut state.view_mode else {
             return;
         };
 
         if forum_state.threads.is_empty() {
-            let empty = Paragraph::new("No threads found").style(self.style.empty_style);
+            let empty = if self.data.is_loading_more() {
+                Paragraph::new(loading_label).style(self.style.loading_style)
+            } else {
+                Paragraph::new("No threads found").style(self.style.empty_style)
+            };
             empty.render(inner_area, buf);
             return;
         }
@@ -1949,6 +2660,22 @@ impl StatefulWidget for MessagePane<'_> {
     }
 }
 
+/// Floats "↓ N new messages" in the bottom-right corner, clear of the scrollbar.
+fn render_unseen_indicator(area: Rect, buf: &mut Buffer, count: usize, style: Style) {
+    let noun = if count == 1 { "message" } else { "messages" };
+    let label = format!(" ↓ {count} new {noun} ");
+    let width = u16::try_from(label.width())
+        .unwrap_or(u16::MAX)
+        .min(area.width.saturating_sub(SCROLLBAR_MARGIN));
+    if width == 0 || area.height == 0 {
+        return;
+    }
+    let x = area.right().saturating_sub(width + SCROLLBAR_MARGIN);
+    let y = area.bottom() - 1;
+    Clear.render(Rect::new(x, y, width, 1), buf);
+    buf.set_stringn(x, y, &label, usize::from(width), style);
+}
+
 fn draw_embed_border(
     buf: &mut Buffer,
     x: u16,
@@ -1966,9 +2693,15 @@ fn draw_embed_border(
 }
 
 #[allow(clippy::too_many_lines)]
-fn render_embed(embed: &RenderedEmbed, start_y: i32, area: Rect, buf: &mut Buffer) -> i32 {
+fn render_embed(
+    embed: &RenderedEmbed,
+    start_y: i32,
+    indent: usize,
+    area: Rect,
+    buf: &mut Buffer,
+) -> i32 {
     let mut current_y = start_y;
-    let indent = u16::try_from(EMBED_INDENT).unwrap_or(0);
+    let indent = u16::try_from(indent).unwrap_or(0);
     let border_color = embed.color;
     let content_x = area.x.saturating_add(indent);
     let content_width = area
@@ -1977,7 +2710,7 @@ fn render_embed(embed: &RenderedEmbed, start_y: i32, area: Rect, buf: &mut Buffe
         .saturating_sub(SCROLLBAR_MARGIN)
         .saturating_sub(2);
 
-    if embed.height > 0 {
+    if embed.padded && embed.height > 0 {
         draw_embed_border(buf, content_x, current_y, area.y, area.height, border_color);
         current_y += 1;
     }
@@ -2061,7 +2794,7 @@ fn render_embed(embed: &RenderedEmbed, start_y: i32, area: Rect, buf: &mut Buffe
         current_y += desc_height;
     }
 
-    if embed.height > 0 {
+    if embed.padded && embed.height > 0 {
         draw_embed_border(buf, content_x, current_y, area.y, area.height, border_color);
         current_y += 1;
     }
@@ -2069,10 +2802,80 @@ fn render_embed(embed: &RenderedEmbed, start_y: i32, area: Rect, buf: &mut Buffe
     current_y - start_y
 }
 
+/// Builds the quote block shown above a forwarded message's content.
+fn forward_block_lines(
+    message: &Message,
+    content_width: u16,
+    indent: usize,
+    use_display_name: bool,
+) -> Vec<Line<'static>> {
+    let Some(forwarded) = message.forwarded() else {
+        return Vec::new();
+    };
+
+    let quote_style = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let bar = || Span::styled(format!("{}│ ", " ".repeat(indent)), quote_style);
+    let text_width = usize::from(content_width).saturating_sub(2).max(1);
+
+    let mut header = vec![bar(), Span::styled("↪ Forwarded", quote_style)];
+    if let Some(author) = &forwarded.author {
+        header.push(Span::styled(" from ", quote_style));
+        header.push(Span::styled(
+            IdentityResolver::with_preference(use_display_name).resolve(author),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    let mut lines = vec![Line::from(header)];
+
+    for text_line in forwarded.content.lines() {
+        for wrapped in wrap_text(text_line, text_width) {
+            lines.push(Line::from(vec![bar(), Span::raw(wrapped)]));
+        }
+    }
+
+    for attachment in &forwarded.attachments {
+        let marker = if attachment.is_image() {
+            "[image]".to_string()
+        } else {
+            format!("[file: {}]", attachment.filename)
+        };
+        lines.push(Line::from(vec![bar(), Span::styled(marker, quote_style)]));
+    }
+
+    if let Some(link) = message.forward_link() {
+        lines.push(Line::from(vec![
+            bar(),
+            Span::styled(link, Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+
+    lines
+}
+
+/// Whether both messages are replies to the same message or the same author.
+fn same_reply_target(previous: &Message, current: &Message) -> bool {
+    if !previous.is_reply() || !current.is_reply() {
+        return false;
+    }
+    let parent_id = |m: &Message| m.reference().and_then(|r| r.message_id);
+    if let (Some(a), Some(b)) = (parent_id(previous), parent_id(current))
+        && a == b
+    {
+        return true;
+    }
+    match (previous.referenced(), current.referenced()) {
+        (Some(a), Some(b)) => a.author().id() == b.author().id(),
+        _ => false,
+    }
+}
+
 fn build_render_items(
     messages: &VecDeque<UiMessage>,
     relationship_state: Option<&RelationshipState>,
     hide_blocked_completely: bool,
+    expanded_blocked_runs: &HashSet<MessageId>,
 ) -> Vec<RenderItem> {
     let mut items = Vec::new();
     let mut i = 0;
@@ -2098,7 +2901,16 @@ fn build_render_items(
                 count += 1;
                 i += 1;
             }
-            items.push(RenderItem::BlockedRun { count, start_idx });
+            if expanded_blocked_runs.contains(&messages[start_idx].message.id()) {
+                items.extend((start_idx..start_idx + count).map(|idx| {
+                    RenderItem::BlockedMessage {
+                        idx,
+                        run_start: start_idx,
+                    }
+                }));
+            } else {
+                items.push(RenderItem::BlockedRun { count, start_idx });
+            }
         } else {
             items.push(RenderItem::Message { idx: i });
             i += 1;
@@ -2108,6 +2920,37 @@ fn build_render_items(
     items
 }
 
+fn render_item_height(item: &RenderItem, messages: &VecDeque<UiMessage>) -> usize {
+    match item {
+        RenderItem::Message { idx } | RenderItem::BlockedMessage { idx, .. } => {
+            messages[*idx].estimated_height as usize
+        }
+        RenderItem::BlockedRun { .. } => 1,
+    }
+}
+
+/// Restyles the visible rows of an item with the blocked style.
+fn dim_rows(style: &MessagePaneStyle, render_y: i32, height: usize, area: Rect, buf: &mut Buffer) {
+    let bottom = render_y
+        .saturating_add(i32::try_from(height).unwrap_or(i32::MAX))
+        .min(i32::from(area.height));
+    let top = render_y.max(0);
+    if bottom <= top {
+        return;
+    }
+    let y = area.y.saturating_add(u16::try_from(top).unwrap_or(0));
+    let rows = u16::try_from(bottom - top).unwrap_or(0);
+    buf.set_style(Rect::new(area.x, y, area.width, rows), style.blocked_style);
+}
+
+/// Stands in for the quote of a blocked author's message.
+fn masked_reply_preview(style: &MessagePaneStyle, indent: usize) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(" ".repeat(indent)),
+        Span::styled("┌─ Replying to a blocked message", style.blocked_style),
+    ])
+}
+
 fn render_blocked_run(
     style: &MessagePaneStyle,
     render_y: i32,
@@ -2120,18 +2963,18 @@ fn render_blocked_run(
         return;
     }
 
-    let blocked_style = if is_selected {
-        style.selected_style.add_modifier(Modifier::ITALIC)
-    } else {
-        style.blocked_style
-    };
-
     let text = if count == 1 {
         "─ 1 blocked ─".to_string()
     } else {
         format!("─ {count} blocked ─")
     };
 
+    let blocked_style = if is_selected {
+        style.selected_style.add_modifier(Modifier::ITALIC)
+    } else {
+        style.blocked_style
+    };
+
     let line = Line::from(Span::styled(text, blocked_style));
     let para = Paragraph::new(line).alignment(Alignment::Center);
 
@@ -2140,15 +2983,87 @@ fn render_blocked_run(
     para.render(placeholder_area, buf);
 }
 
+/// Formats a message timestamp padded to the header's timestamp column.
+///
+/// The column widens for relative timestamps and for formats longer than
+/// the default, so the author always starts after a gap.
+fn format_timestamp(message: &Message, timestamp_format: &str) -> String {
+    let timestamp = timestamp_text(message, timestamp_format);
+    let column = if timestamp_format == RELATIVE_TIMESTAMP_FORMAT {
+        RELATIVE_TIMESTAMP_WIDTH
+    } else {
+        TIMESTAMP_WIDTH
+    };
+    let width = column.max(timestamp.width() + 1);
+    format!("{timestamp:<width$}")
+}
+
+/// A message's timestamp without the column padding.
+fn timestamp_text(message: &Message, timestamp_format: &str) -> String {
+    if timestamp_format == RELATIVE_TIMESTAMP_FORMAT {
+        format_relative_timestamp(message.timestamp(), chrono::Local::now())
+    } else {
+        message.timestamp().format(timestamp_format).to_string()
+    }
+}
+
+/// Draws `timestamp` against the right edge of row `y`, clear of the
+/// scrollbar, unless the first `used` columns leave no room for it.
+fn render_right_timestamp(
+    area: Rect,
+    y: u16,
+    used: usize,
+    timestamp: &str,
+    style: Style,
+    buf: &mut Buffer,
+) {
+    let width = timestamp.width();
+    let available = usize::from(area.width.saturating_sub(SCROLLBAR_MARGIN));
+    if used + 1 + width > available {
+        return;
+    }
+    let x = area.x + u16::try_from(available - width).unwrap_or(0);
+    buf.set_stringn(x, y, timestamp, width, style);
+}
+
+/// A message's embeds, leaving out link previews unless `show_embeds`.
+fn shown_embeds(message: &Message, show_embeds: bool) -> impl Iterator<Item = &Embed> {
+    message
+        .embeds()
+        .iter()
+        .filter(move |embed| show_embeds || !embed.kind.is_link_preview())
+}
+
+/// Columns before message content: the timestamp gutter when timestamps
+/// sit on the left, a narrow indent otherwise.
+const fn content_indent(timestamp_position: TimestampPosition) -> usize {
+    match timestamp_position {
+        TimestampPosition::Left => CONTENT_INDENT,
+        TimestampPosition::Right | TimestampPosition::Hidden => NARROW_CONTENT_INDENT,
+    }
+}
+
+/// Formats a grouped message's time for the content gutter, if it fits.
+///
+/// Relative timestamps and long formats are skipped rather than truncated.
+fn gutter_timestamp(message: &Message, timestamp_format: &str) -> Option<String> {
+    if timestamp_format == RELATIVE_TIMESTAMP_FORMAT {
+        return None;
+    }
+    let timestamp = message.timestamp().format(timestamp_format).to_string();
+    (timestamp.width() < CONTENT_INDENT).then_some(timestamp)
+}
+
 #[allow(
     clippy::too_many_lines,
     clippy::items_after_statements,
-    clippy::too_many_arguments
+    clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools
 )]
 fn render_ui_message(
     ui_msg: &mut UiMessage,
     style: &MessagePaneStyle,
-    _authors: &HashMap<String, String>,
+    authors: &HashMap<String, String>,
     index: usize,
     render_y: i32,
     area: Rect,
@@ -2157,10 +3072,16 @@ fn render_ui_message(
     disable_user_colors: bool,
     use_display_name: bool,
     image_preview: bool,
+    image_placeholders: bool,
     timestamp_format: &str,
+    gutter_timestamps: bool,
+    timestamp_position: TimestampPosition,
+    density: MessageDensity,
     current_user_id: Option<&str>,
+    mask_reply: bool,
 ) {
     let message = &ui_msg.message;
+    let indent = content_indent(timestamp_position);
     let is_selected = state.selected_index == Some(index);
     let is_mentioned = if let Some(id) = current_user_id {
         message.mentions().iter().any(|u| u.id_str() == id)
@@ -2182,6 +3103,13 @@ fn render_ui_message(
             && current_msg_y < i32::from(area.height)
             && let Some(preview) = &ui_msg.reply_preview
         {
+            let masked;
+            let preview = if mask_reply {
+                masked = masked_reply_preview(style, indent);
+                &masked
+            } else {
+                preview
+            };
             let render_line = if is_selected || is_mentioned {
                 let mut spans = preview.clone().spans;
                 if spans.len() == 4 {
@@ -2222,51 +3150,103 @@ fn render_ui_message(
         current_msg_y += 1;
     }
 
+    let (timestamp_style, edited_style) = if is_selected || is_mentioned {
+        (
+            Style::default().fg(Color::White),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::ITALIC),
+        )
+    } else {
+        (style.timestamp_style, style.edited_style)
+    };
+
     if ui_msg.group == MessageGroup::Start {
         if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
-            let (timestamp_style, edited_style) = if is_selected || is_mentioned {
-                (
-                    Style::default().fg(Color::White),
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::ITALIC),
-                )
-            } else {
-                (style.timestamp_style, style.edited_style)
-            };
-
             let author_color = if disable_user_colors {
                 style.author_style.fg.unwrap_or(Color::Yellow)
             } else {
-                get_author_color(message.author())
+                get_author_color(
+                    message.author(),
+                    style.author_color_mode,
+                    style.author_style.fg.unwrap_or(Color::Yellow),
+                )
             };
 
-            let mut header_spans = vec![
-                Span::styled(
-                    format!(
-                        "{:<width$}",
-                        message.timestamp().format(timestamp_format).to_string(),
-                        width = TIMESTAMP_WIDTH
-                    ),
+            let mut header_spans = Vec::new();
+            if timestamp_position == TimestampPosition::Left {
+                header_spans.push(Span::styled(
+                    format_timestamp(message, timestamp_format),
                     timestamp_style,
-                ),
-                Span::styled(
-                    IdentityResolver::with_preference(use_display_name).resolve(message.author()),
-                    style.author_style.fg(author_color),
-                ),
-            ];
+                ));
+            }
+            let author_name = authors
+                .get(message.author().id())
+                .cloned()
+                .unwrap_or_else(|| {
+                    IdentityResolver::with_preference(use_display_name).resolve(message.author())
+                });
+            header_spans.push(Span::styled(
+                author_name,
+                style.author_style.fg(author_color),
+            ));
 
             if message.author().is_bot() {
                 header_spans.push(Span::raw(" "));
                 header_spans.push(Span::styled("[BOT]", style.bot_badge_style));
             }
 
+            #[cfg(feature = "image")]
+            {
+                let author = message.author();
+                let avatar = avatar_image(
+                    author.id(),
+                    author.avatar(),
+                    &author.discriminator,
+                    state.animate_spinner,
+                );
+                if avatar.animated {
+                    header_spans.push(Span::raw(" "));
+                    header_spans.push(Span::styled(
+                        format!("[{ANIMATED_AVATAR_INDICATOR}]"),
+                        style.bot_badge_style,
+                    ));
+                }
+            }
+
+            let flags = message.flags();
+            if flags.contains(MessageFlags::IS_CROSSPOST) {
+                header_spans.push(Span::raw(" "));
+                header_spans.push(Span::styled("[FOLLOWED]", style.bot_badge_style));
+            } else if flags.contains(MessageFlags::CROSSPOSTED) {
+                header_spans.push(Span::raw(" "));
+                header_spans.push(Span::styled("[PUBLISHED]", style.bot_badge_style));
+            }
+
             if message.is_edited() {
                 header_spans.push(Span::raw(" "));
                 header_spans.push(Span::styled("(edited)", edited_style));
             }
 
+            match ui_msg.send_state {
+                SendState::Sent => {}
+                SendState::Pending => {
+                    let elapsed = chrono::Local::now() - message.timestamp();
+                    let frame = usize::try_from(elapsed.num_milliseconds() / 100).unwrap_or(0);
+                    header_spans.push(Span::raw(" "));
+                    header_spans.push(Span::styled(
+                        SEND_SPINNER[frame % SEND_SPINNER.len()],
+                        timestamp_style,
+                    ));
+                }
+                SendState::Failed => {
+                    header_spans.push(Span::raw(" "));
+                    header_spans.push(Span::styled("✗ not sent", style.error_style));
+                }
+            }
+
             let header_line = Line::from(header_spans);
+            let header_width = header_line.width();
             let header_para = Paragraph::new(header_line).style(base_style);
 
             let header_area = Rect::new(
@@ -2277,6 +3257,32 @@ fn render_ui_message(
                 1,
             );
             header_para.render(header_area, buf);
+            if timestamp_position == TimestampPosition::Right {
+                render_right_timestamp(
+                    area,
+                    header_area.y,
+                    header_width,
+                    &timestamp_text(message, timestamp_format),
+                    timestamp_style,
+                    buf,
+                );
+            }
+        }
+        current_msg_y += 1;
+    }
+
+    for line in &ui_msg.forward_preview {
+        if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
+            let quote_area = Rect::new(
+                area.x,
+                area.y
+                    .saturating_add(u16::try_from(current_msg_y).unwrap_or(0)),
+                area.width,
+                1,
+            );
+            Paragraph::new(line.clone())
+                .style(base_style)
+                .render(quote_area, buf);
         }
         current_msg_y += 1;
     }
@@ -2291,15 +3297,25 @@ fn render_ui_message(
     } else {
         base_style.patch(content_style)
     };
+    let paragraph_style = if ui_msg.send_state == SendState::Sent {
+        paragraph_style
+    } else {
+        paragraph_style.add_modifier(Modifier::DIM)
+    };
 
-    let indent_width = u16::try_from(CONTENT_INDENT).unwrap_or(0);
+    let indent_width = u16::try_from(indent).unwrap_or(0);
     let max_image_width = area.width.saturating_sub(indent_width + SCROLLBAR_MARGIN);
 
-    let text = if let Some(t) = &ui_msg.rendered_content {
+    let mut text = if let Some(t) = &ui_msg.rendered_content {
         t.clone()
     } else {
         Text::raw(message.content())
     };
+    if ui_msg.collapsed
+        && let Some(marker) = text.lines.last_mut()
+    {
+        marker.style = style.collapse_marker_style;
+    }
 
     let mut para = Paragraph::new(text)
         .block(Block::default().padding(Padding::new(indent_width, SCROLLBAR_MARGIN, 0, 0)))
@@ -2334,19 +3350,63 @@ fn render_ui_message(
             para.render(para_area, buf);
         }
     }
-    current_msg_y += content_height;
 
-    for attachment in message.attachments() {
-        if attachment.is_image() {
-            continue;
+    if gutter_timestamps
+        && ui_msg.group == MessageGroup::Compact
+        && content_start_y >= 0
+        && content_start_y < i32::from(area.height)
+    {
+        let y = area
+            .y
+            .saturating_add(u16::try_from(content_start_y).unwrap_or(0));
+        match timestamp_position {
+            TimestampPosition::Left => {
+                if let Some(timestamp) = gutter_timestamp(message, timestamp_format) {
+                    buf.set_stringn(area.x, y, timestamp, CONTENT_INDENT, timestamp_style);
+                }
+            }
+            TimestampPosition::Right => {
+                let first_line_width = ui_msg
+                    .rendered_content
+                    .as_ref()
+                    .and_then(|text| text.lines.first())
+                    .map_or_else(|| message.content().width(), Line::width);
+                render_right_timestamp(
+                    area,
+                    y,
+                    indent + first_line_width,
+                    &timestamp_text(message, timestamp_format),
+                    timestamp_style,
+                    buf,
+                );
+            }
+            TimestampPosition::Hidden => {}
         }
+    }
+    current_msg_y += content_height;
+
+    let files: Vec<_> = message
+        .attachments()
+        .iter()
+        .filter(|a| !a.is_image())
+        .collect();
+    let files_per_row = match density {
+        MessageDensity::Cozy => 1,
+        MessageDensity::Compact => files.len().max(1),
+    };
+    for row in files.chunks(files_per_row) {
         if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
-            let indent_span = Span::raw(" ".repeat(CONTENT_INDENT));
-            let attachment_text = format!("\u{1F4CE} {}", attachment.filename);
-            let attachment_line = Line::from(vec![
-                indent_span.clone(),
-                Span::styled(attachment_text, style.attachment_style),
-            ]);
+            let mut spans = vec![Span::raw(" ".repeat(indent))];
+            for (i, attachment) in row.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw("  "));
+                }
+                spans.push(Span::styled(
+                    format!("\u{1F4CE} {}", attachment.filename),
+                    style.attachment_style,
+                ));
+            }
+            let attachment_line = Line::from(spans);
             let attachment_para = Paragraph::new(attachment_line).style(base_style);
             let att_area = Rect::new(
                 area.x,
@@ -2360,11 +3420,37 @@ fn render_ui_message(
         current_msg_y += 1;
     }
 
+    let show_spoilers = state.show_spoilers;
     for img_attachment in &mut ui_msg.image_attachments {
+        if img_attachment.spoiler && !show_spoilers {
+            if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
+                let spoiler_line = Line::from(vec![
+                    Span::raw(" ".repeat(indent)),
+                    Span::styled("[SPOILER image]", style.attachment_style),
+                ]);
+                let spoiler_area = Rect::new(
+                    area.x,
+                    area.y
+                        .saturating_add(u16::try_from(current_msg_y).unwrap_or(0)),
+                    area.width,
+                    1,
+                );
+                Paragraph::new(spoiler_line)
+                    .style(base_style)
+                    .render(spoiler_area, buf);
+            }
+            current_msg_y += 1;
+            continue;
+        }
+
         if !image_preview {
             if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
-                let indent_span = Span::raw(" ".repeat(CONTENT_INDENT));
-                let attachment_text = format!("\u{1F5BC} {}", img_attachment.url);
+                let indent_span = Span::raw(" ".repeat(indent));
+                let attachment_text = if image_placeholders {
+                    image_placeholder_text(img_attachment)
+                } else {
+                    format!("\u{1F5BC} {}", img_attachment.url)
+                };
                 let attachment_line = Line::from(vec![
                     indent_span,
                     Span::styled(attachment_text, style.attachment_style),
@@ -2388,7 +3474,7 @@ fn render_ui_message(
                 && current_msg_y >= 0
                 && current_msg_y < i32::from(area.height)
             {
-                let indent_span = Span::raw(" ".repeat(CONTENT_INDENT));
+                let indent_span = Span::raw(" ".repeat(indent));
                 let loading_text = "\u{1F5BC}  Loading image...";
                 let loading_line = Line::from(vec![
                     indent_span,
@@ -2441,7 +3527,7 @@ fn render_ui_message(
                     };
 
                     let img_area = Rect::new(
-                        area.x + u16::try_from(CONTENT_INDENT).unwrap_or(0),
+                        area.x + indent_width,
                         area.y + target_y,
                         effective_width,
                         effective_height,
@@ -2470,7 +3556,7 @@ fn render_ui_message(
             current_msg_y += img_height;
         } else {
             if current_msg_y >= 0 && current_msg_y < i32::from(area.height) {
-                let indent_span = Span::raw(" ".repeat(CONTENT_INDENT));
+                let indent_span = Span::raw(" ".repeat(indent));
                 let placeholder_text = "\u{1F5BC}  [Image]";
                 let placeholder_line = Line::from(vec![
                     indent_span,
@@ -2491,46 +3577,23 @@ fn render_ui_message(
     }
 
     for embed in &ui_msg.rendered_embeds {
-        let height = render_embed(embed, current_msg_y, area, buf);
+        let height = render_embed(embed, current_msg_y, indent, area, buf);
         current_msg_y += height;
     }
 }
 
-fn truncate_string(s: &str, max_len: usize) -> String {
-    if UnicodeWidthStr::width(s) <= max_len {
-        return s.to_string();
-    }
-
-    if max_len < 3 {
-        let mut current_width = 0;
-        let mut cut_off_index = 0;
-        for (i, c) in s.char_indices() {
-            let char_len = c.len_utf8();
-            let w = UnicodeWidthStr::width(&s[i..i + char_len]);
-            if current_width + w > max_len {
-                break;
-            }
-            current_width += w;
-            cut_off_index = i + char_len;
-        }
-        return s[..cut_off_index].to_string();
-    }
-
-    let mut current_width = 0;
-    let mut cut_off_index = 0;
-    let target_width = max_len.saturating_sub(3);
-
-    for (i, c) in s.char_indices() {
-        let char_len = c.len_utf8();
-        let w = UnicodeWidthStr::width(&s[i..i + char_len]);
-        if current_width + w > target_width {
-            break;
-        }
-        current_width += w;
-        cut_off_index = i + char_len;
-    }
-
-    format!("{}...", &s[..cut_off_index])
+/// Describes an image that cannot be drawn, e.g. `[image: cat.png — 800×600] <url>`.
+fn image_placeholder_text(img: &ImageAttachment) -> String {
+    let path = img.url.split(['?', '#']).next().unwrap_or(&img.url);
+    let filename = path
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or("image");
+    let label = match (img.width, img.height) {
+        (Some(w), Some(h)) => format!("{filename} \u{2014} {w}\u{d7}{h}"),
+        _ => filename.to_string(),
+    };
+    format!("\u{1F5BC} [image: {label}] {}", img.url)
 }
 
 fn wrap_text(text: &str, width: usize) -> Vec<String> {
@@ -2613,11 +3676,73 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
-fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
+/// Readable text for a system message such as a join or pin, or `None` when
+/// the content should be shown as sent.
+fn system_message_text(message: &Message, use_display_name: bool) -> Option<String> {
+    let resolver = IdentityResolver::with_preference(use_display_name);
+    let author = resolver.resolve(message.author());
+    let target = message
+        .mentions()
+        .first()
+        .map(|user| resolver.resolve(user));
+    let content = message.content();
+
+    let text = match message.kind() {
+        MessageKind::Default
+        | MessageKind::Reply
+        | MessageKind::ChatInputCommand
+        | MessageKind::ContextMenuCommand => return None,
+        MessageKind::RecipientAdd => format!(
+            "➕ {author} added {} to the group.",
+            target.as_deref().unwrap_or("someone")
+        ),
+        MessageKind::RecipientRemove => match target {
+            Some(target) if target != author => {
+                format!("➖ {author} removed {target} from the group.")
+            }
+            _ => format!("➖ {author} left the group."),
+        },
+        MessageKind::Call => format!("📞 {author} started a call."),
+        MessageKind::ChannelNameChange => {
+            format!("✏️ {author} changed the channel name: {content}")
+        }
+        MessageKind::ChannelIconChange => format!("✏️ {author} changed the channel icon."),
+        MessageKind::ChannelPinnedMessage => {
+            format!("📌 {author} pinned a message to this channel.")
+        }
+        MessageKind::UserJoin => format!("🎉 {author} joined the server."),
+        MessageKind::GuildBoost => format!("🚀 {author} boosted the server!"),
+        MessageKind::GuildBoostTier1 => {
+            format!("🚀 {author} boosted the server! It has reached Level 1!")
+        }
+        MessageKind::GuildBoostTier2 => {
+            format!("🚀 {author} boosted the server! It has reached Level 2!")
+        }
+        MessageKind::GuildBoostTier3 => {
+            format!("🚀 {author} boosted the server! It has reached Level 3!")
+        }
+        MessageKind::ChannelFollowAdd => {
+            format!("📣 {author} has added {content} to this channel.")
+        }
+        MessageKind::ThreadCreated => format!("🧵 {author} started a thread: {content}"),
+        MessageKind::StageStart => format!("🎙️ {author} started {content}"),
+        MessageKind::StageEnd => format!("🎙️ {author} ended {content}"),
+        MessageKind::StageSpeaker => format!("🎙️ {author} is now a speaker."),
+        MessageKind::StageTopic => format!("🎙️ {author} changed the Stage topic: {content}"),
+        _ if !content.is_empty() => return None,
+        kind => format!("ℹ️ System message ({kind:?})"),
+    };
+    Some(text)
+}
+
+/// Wraps `text` to `width`, indenting continuation lines by `hanging_indent`.
+fn wrap_styled_text(text: Text<'static>, width: u16, hanging_indent: u16) -> Text<'static> {
     if width == 0 {
         return text;
     }
 
+    let indent = usize::from(hanging_indent.min(width / 2));
+
     let mut new_lines = Vec::new();
 
     for line in text.lines {
@@ -2626,6 +3751,28 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
             continue;
         }
 
+        // Wrapped lines of a quote keep its borders ahead of the indent, as
+        // many as fit in half the width so deep nesting still leaves room.
+        let mut hang = indent;
+        let borders: Vec<Span<'static>> = line
+            .spans
+            .iter()
+            .take_while(|span| span.content == QUOTE_BORDER)
+            .take_while(|span| {
+                hang += span.width();
+                hang <= usize::from(width / 2)
+            })
+            .cloned()
+            .collect();
+        let hang = indent + borders.iter().map(Span::width).sum::<usize>();
+        let continuation = || -> Vec<Span<'static>> {
+            let mut spans = borders.clone();
+            if indent > 0 {
+                spans.push(Span::raw(" ".repeat(indent)));
+            }
+            spans
+        };
+
         let mut current_line_spans = Vec::new();
 
         let mut current_width = 0;
@@ -2639,7 +3786,7 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
             for word in words {
                 let word_width = UnicodeWidthStr::width(word);
 
-                if word_width > width as usize {
+                if word_width > (width as usize).saturating_sub(hang) {
                     let mut remaining_word = word;
                     while !remaining_word.is_empty() {
                         if current_width + UnicodeWidthStr::width(remaining_word) <= width as usize
@@ -2652,8 +3799,8 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
                             let available = (width as usize).saturating_sub(current_width);
                             if available == 0 {
                                 new_lines.push(Line::from(current_line_spans));
-                                current_line_spans = Vec::new();
-                                current_width = 0;
+                                current_line_spans = continuation();
+                                current_width = hang;
                                 continue;
                             }
 
@@ -2669,6 +3816,12 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
                                 split_idx = idx + c.len_utf8();
                             }
 
+                            // A character wider than the room left on a fresh
+                            // line goes on it anyway rather than never fitting.
+                            if split_idx == 0 && current_width <= hang {
+                                split_idx = remaining_word.chars().next().map_or(0, char::len_utf8);
+                            }
+
                             if split_idx > 0 {
                                 current_line_spans.push(Span::styled(
                                     remaining_word[..split_idx].to_string(),
@@ -2678,8 +3831,8 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
                             }
 
                             new_lines.push(Line::from(current_line_spans));
-                            current_line_spans = Vec::new();
-                            current_width = 0;
+                            current_line_spans = continuation();
+                            current_width = hang;
                         }
                     }
                     continue;
@@ -2687,8 +3840,8 @@ fn wrap_styled_text(text: Text<'static>, width: u16) -> Text<'static> {
 
                 if current_width + word_width > width as usize {
                     new_lines.push(Line::from(current_line_spans));
-                    current_line_spans = Vec::new();
-                    current_width = 0;
+                    current_line_spans = continuation();
+                    current_width = hang;
                 }
 
                 current_line_spans.push(Span::styled(word.to_string(), style));
@@ -2715,6 +3868,18 @@ mod tests {
     use chrono::Local;
     use test_case::test_case;
 
+    /// The buffer's text, one string per row.
+    fn buffer_rows(buf: &Buffer) -> Vec<String> {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
     fn create_test_message(id: u64, content: &str) -> Message {
         let author = MessageAuthor {
             id: "1".to_string(),
@@ -2794,7 +3959,7 @@ mod tests {
         data.set_messages(messages);
 
         let markdown = MarkdownRenderer::new();
-        data.update_layout(100, &markdown, Color::Yellow, false, true);
+        data.update_layout(100, &markdown, Color::Yellow, false, true, &HashSet::new());
 
         let mut state = MessagePaneState::new();
         state.flags.is_following = true;
@@ -2812,35 +3977,241 @@ mod tests {
     }
 
     #[test]
-    fn test_message_grouping_logic() {
-        use chrono::Duration;
+    fn test_oversized_message_height_saturates() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
 
-        let now = Local::now();
-        let author1 = MessageAuthor {
-            id: "1".to_string(),
-            username: "user1".to_string(),
-            discriminator: "0".to_string(),
-            avatar: None,
-            bot: false,
-            global_name: None,
-        };
-        let author2 = MessageAuthor {
-            id: "2".to_string(),
-            username: "user2".to_string(),
-            discriminator: "0".to_string(),
-            avatar: None,
-            bot: false,
-            global_name: None,
-        };
+        let huge = "line\n\n".repeat(70_000);
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages(vec![
+            create_test_message(1, &huge),
+            create_test_message(2, "after"),
+        ]);
 
-        let m1 = Message::new(
-            1u64.into(),
-            ChannelId(100),
-            author1.clone(),
-            "Base message".to_string(),
-            now,
-            crate::domain::entities::MessageKind::Default,
-        );
+        let markdown = MarkdownRenderer::new();
+        data.update_layout(100, &markdown, Color::Yellow, false, true, &HashSet::new());
+
+        let big = &data.ui_messages()[0];
+        let rendered_lines = big.rendered_content.as_ref().map_or(0, |t| t.lines.len());
+        assert_eq!(rendered_lines, MAX_CONTENT_LINES);
+        assert!(usize::from(big.estimated_height) > rendered_lines);
+
+        let content_height: usize = data
+            .ui_messages()
+            .iter()
+            .map(|m| m.estimated_height as usize)
+            .sum();
+        let mut state = MessagePaneState::new();
+        state.flags.is_following = true;
+        state.update_dimensions(content_height, 20);
+        assert_eq!(state.vertical_scroll, content_height - 20);
+
+        let area = Rect::new(0, 0, 100, 22);
+        let mut buf = Buffer::empty(area);
+        MessagePane::new(&mut data, &markdown).render(area, &mut buf, &mut state);
+        assert!(buffer_rows(&buf).concat().contains("after"));
+    }
+
+    #[test]
+    fn test_system_messages_get_readable_text() {
+        let message = |kind, content: &str| {
+            let base = create_test_message(1, content);
+            Message::new(
+                base.id(),
+                base.channel_id(),
+                base.author().clone(),
+                content.to_string(),
+                Local::now(),
+                kind,
+            )
+        };
+
+        assert_eq!(
+            system_message_text(&message(MessageKind::UserJoin, ""), false).as_deref(),
+            Some("🎉 testuser joined the server.")
+        );
+        assert_eq!(
+            system_message_text(&message(MessageKind::ChannelPinnedMessage, "1"), false).as_deref(),
+            Some("📌 testuser pinned a message to this channel.")
+        );
+        assert_eq!(
+            system_message_text(&message(MessageKind::GuildIncidentReportRaid, ""), false)
+                .as_deref(),
+            Some("ℹ️ System message (GuildIncidentReportRaid)")
+        );
+        assert!(system_message_text(&message(MessageKind::Default, "hi"), false).is_none());
+        assert!(
+            system_message_text(
+                &message(MessageKind::AutoModerationAction, "flagged"),
+                false
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_compact_density_fits_more_lines() {
+        use crate::domain::entities::{Attachment, Embed};
+
+        let markdown = MarkdownRenderer::new();
+        let mut embed = Embed::new();
+        embed.title = Some("Preview".to_string());
+        let first = create_test_message(1, "first")
+            .with_embeds(vec![embed])
+            .with_attachments(vec![
+                Attachment::new("1", "a.txt", 10, "https://x.test/a.txt"),
+                Attachment::new("2", "b.txt", 10, "https://x.test/b.txt"),
+            ]);
+        let base = create_test_message(2, "second");
+        let mut author = base.author().clone();
+        author.id = "2".to_string();
+        author.username = "other".to_string();
+        let second = Message::new(
+            base.id(),
+            base.channel_id(),
+            author,
+            "second".to_string(),
+            Local::now(),
+            MessageKind::Default,
+        );
+
+        let render = |density| {
+            let mut data = MessagePaneData::new(true);
+            data.set_channel(ChannelId(100), "general".to_string());
+            data.set_messages(vec![first.clone(), second.clone()]);
+            data.set_message_density(density);
+
+            let area = Rect::new(0, 0, 60, 20);
+            let mut buf = Buffer::empty(area);
+            let mut state = MessagePaneState::new();
+            MessagePane::new(&mut data, &markdown).render(area, &mut buf, &mut state);
+
+            let rows = buffer_rows(&buf);
+            let total: u16 = data.messages.iter().map(|m| m.estimated_height).sum();
+            let row_of = |text: &str| rows.iter().position(|row| row.contains(text)).unwrap();
+            (total, row_of("first"), row_of("b.txt"), row_of("second"))
+        };
+
+        let (cozy_total, cozy_first, cozy_files, cozy_second) = render(MessageDensity::Cozy);
+        let (compact_total, compact_first, compact_files, compact_second) =
+            render(MessageDensity::Compact);
+        // Cozy lays out like the plain list: one row per file, a padded
+        // embed, and the next author directly below.
+        assert_eq!(cozy_files, cozy_first + 2);
+        assert_eq!(cozy_second, cozy_first + 2 + 3 + 2);
+        // Compact shares one row between the files and drops the padding.
+        assert_eq!(compact_files, compact_first + 1);
+        assert_eq!(cozy_total, compact_total + 3);
+        assert_eq!(cozy_second, compact_second + 3);
+    }
+
+    #[test]
+    fn test_wrapped_quote_lines_keep_border() {
+        let markdown = MarkdownRenderer::new();
+        let text = markdown.render_markdown(
+            "> > one two three four five six seven\n> ```\n> code\n> ```",
+            None,
+            false,
+        );
+        let wrapped = wrap_styled_text(text, 16, 0);
+        let rendered: Vec<String> = wrapped.lines.iter().map(ToString::to_string).collect();
+
+        assert_eq!(rendered.len(), 5);
+        assert!(rendered[..4].iter().all(|line| line.starts_with("▎ ▎ ")));
+        assert_eq!(rendered[4], "▎ code");
+        assert!(wrapped.lines.iter().all(|line| line.width() <= 16));
+    }
+
+    #[test]
+    fn test_deeply_nested_quote_wraps_at_narrow_width() {
+        let markdown = MarkdownRenderer::new();
+        for depth in [5, 6, 12] {
+            let content = format!("{}{}", "> ".repeat(depth), "a".repeat(40));
+            let text = markdown.render_markdown(&content, None, false);
+            let wrapped = wrap_styled_text(text, 10, 2);
+            let rendered: String = wrapped.lines.iter().map(ToString::to_string).collect();
+
+            assert_eq!(rendered.matches('a').count(), 40, "depth {depth}");
+            assert!(wrapped.lines.len() < 40, "depth {depth}");
+        }
+
+        let text = Text::from(Line::from(vec![
+            Span::raw(QUOTE_BORDER),
+            Span::raw(QUOTE_BORDER),
+            Span::raw("🎉🎉🎉"),
+        ]));
+        let wrapped = wrap_styled_text(text, 3, 0);
+        let rendered: String = wrapped.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(rendered.matches('🎉').count(), 3);
+    }
+
+    #[test]
+    fn test_wrap_indent_offsets_continuation_lines() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+
+        let content = (1..=30)
+            .map(|n| format!("w{n:02}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let markdown = MarkdownRenderer::new();
+
+        let content_columns = |wrap_indent: u16| {
+            let mut data = MessagePaneData::new(true);
+            data.set_channel(ChannelId(100), "general".to_string());
+            data.set_wrap_indent(wrap_indent);
+            data.set_messages(vec![create_test_message(1, &content)]);
+
+            let area = Rect::new(0, 0, 40, 12);
+            let mut buf = Buffer::empty(area);
+            let mut state = MessagePaneState::new();
+            MessagePane::new(&mut data, &markdown).render(area, &mut buf, &mut state);
+
+            let rows = buffer_rows(&buf);
+            let first = rows.iter().position(|row| row.contains("w01")).unwrap();
+            let first_x = rows[first].find("w01").unwrap();
+            let next_x = rows[first + 1].find('w').unwrap();
+            (first_x, next_x)
+        };
+
+        let (base, continuation) = content_columns(0);
+        assert_eq!(continuation, base);
+
+        let (first, continuation) = content_columns(4);
+        assert_eq!(first, base);
+        assert_eq!(continuation, base + 4);
+    }
+
+    #[test]
+    fn test_message_grouping_logic() {
+        use chrono::Duration;
+
+        let now = Local::now();
+        let author1 = MessageAuthor {
+            id: "1".to_string(),
+            username: "user1".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        let author2 = MessageAuthor {
+            id: "2".to_string(),
+            username: "user2".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+
+        let m1 = Message::new(
+            1u64.into(),
+            ChannelId(100),
+            author1.clone(),
+            "Base message".to_string(),
+            now,
+            crate::domain::entities::MessageKind::Default,
+        );
 
         let m2 = Message::new(
             2u64.into(),
@@ -2890,6 +4261,30 @@ mod tests {
         assert_eq!(messages[4].group, MessageGroup::Start);
     }
 
+    #[test]
+    fn test_reply_grouping_option() {
+        use crate::domain::entities::MessageReference;
+
+        let reply = |id: u64, content: &str| {
+            create_test_message(id, content).with_reference(MessageReference::new(
+                Some(MessageId(1)),
+                Some(ChannelId(100)),
+                None,
+            ))
+        };
+        let mut data = MessagePaneData::new(true);
+        data.set_messages(vec![reply(2, "first"), reply(3, "second")]);
+
+        assert_eq!(data.messages[1].group, MessageGroup::Start);
+
+        data.set_group_replies(true);
+        assert_eq!(data.messages[0].group, MessageGroup::Start);
+        assert_eq!(data.messages[1].group, MessageGroup::Compact);
+
+        data.set_group_replies(false);
+        assert_eq!(data.messages[1].group, MessageGroup::Start);
+    }
+
     #[test]
     fn test_invalid_timestamp_format() {
         use crate::presentation::services::markdown_renderer::MarkdownRenderer;
@@ -2913,6 +4308,293 @@ mod tests {
         pane.render(area, &mut buf, &mut state);
     }
 
+    #[test]
+    fn test_gateway_echo_replaces_pending_message() {
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let local_id = MessageId(u64::MAX);
+
+        data.add_pending_message(create_test_message(u64::MAX, "hello"));
+        assert!(data.has_pending_sends());
+
+        data.add_message(create_test_message(5, "hello"));
+        assert_eq!(data.message_count(), 1);
+        assert_eq!(data.send_state(MessageId(5)), Some(SendState::Sent));
+
+        data.confirm_pending_message(local_id, create_test_message(5, "hello"));
+        assert_eq!(data.message_count(), 1);
+        assert!(!data.has_pending_sends());
+    }
+
+    #[test]
+    fn test_gateway_echo_matched_by_nonce() {
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let nonce = Some(u64::MAX.to_string());
+
+        data.add_pending_message(create_test_message(u64::MAX, "hi :)").with_nonce(nonce.clone()));
+        data.add_message(create_test_message(5, "hi 🙂").with_nonce(nonce));
+        assert_eq!(
+            data.message_count(),
+            1,
+            "echo replaced despite edited content"
+        );
+        assert_eq!(data.send_state(MessageId(5)), Some(SendState::Sent));
+
+        data.add_pending_message(
+            create_test_message(u64::MAX - 1, "same").with_nonce(Some("a".to_string())),
+        );
+        data.add_message(create_test_message(6, "same").with_nonce(Some("b".to_string())));
+        assert_eq!(data.message_count(), 3, "unrelated message inserted");
+        assert_eq!(
+            data.send_state(MessageId(u64::MAX - 1)),
+            Some(SendState::Pending)
+        );
+    }
+
+    #[test]
+    fn test_api_confirmation_replaces_pending_in_place() {
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let local_id = MessageId(u64::MAX);
+
+        data.add_pending_message(create_test_message(u64::MAX, "first"));
+        data.add_message(create_test_message(6, "someone else"));
+        data.confirm_pending_message(local_id, create_test_message(5, "first"));
+
+        let ids: Vec<_> = data.messages.iter().map(|m| m.message.id()).collect();
+        assert_eq!(ids, vec![MessageId(5), MessageId(6)]);
+
+        data.add_message(create_test_message(5, "first"));
+        assert_eq!(data.message_count(), 2);
+    }
+
+    #[test]
+    fn test_retry_only_offered_for_failed_sends() {
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let local_id = MessageId(u64::MAX);
+        data.add_pending_message(create_test_message(u64::MAX, "hello"));
+
+        let registry = CommandRegistry::default();
+        let retry = KeyEvent::new(
+            crossterm::event::KeyCode::Char('r'),
+            crossterm::event::KeyModifiers::CONTROL,
+        );
+        let mut state = MessagePaneState::new();
+        state.selected_index = Some(0);
+
+        assert!(
+            state
+                .handle_key(retry, &data, &registry, None, false)
+                .is_none()
+        );
+
+        data.set_send_state(local_id, SendState::Failed);
+        assert!(matches!(
+            state.handle_key(retry, &data, &registry, None, false),
+            Some(MessagePaneAction::RetrySend(id)) if id == local_id
+        ));
+    }
+
+    #[test]
+    fn test_relative_timestamp_column_keeps_alignment() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+
+        let first = create_test_message(1, "first");
+        let yesterday = Local::now() - chrono::Duration::days(1);
+        let at = |message: &Message, offset: i64| {
+            Message::new(
+                message.id(),
+                ChannelId(100),
+                message.author().clone(),
+                message.content().to_string(),
+                yesterday + chrono::Duration::minutes(offset),
+                message.kind(),
+            )
+        };
+        let second = create_test_message(2, "second");
+        data.set_messages(vec![at(&first, 0), at(&second, 1)]);
+
+        let markdown = MarkdownRenderer::new();
+        let pane =
+            MessagePane::new(&mut data, &markdown).with_timestamp_format(RELATIVE_TIMESTAMP_FORMAT);
+        let mut state = MessagePaneState::new();
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        pane.render(area, &mut buf, &mut state);
+
+        let rows = buffer_rows(&buf);
+        let column = |text: &str| {
+            rows.iter()
+                .find_map(|row| row.find(text).map(|byte| row[..byte].chars().count()))
+                .expect("text should render")
+        };
+
+        assert_eq!(
+            column("testuser") - column("yesterday"),
+            RELATIVE_TIMESTAMP_WIDTH
+        );
+        assert_eq!(column("first"), column("second"));
+    }
+
+    #[test]
+    fn test_grouped_messages_show_gutter_timestamp_when_enabled() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use chrono::TimeZone;
+        use ratatui::widgets::StatefulWidget;
+
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let at = |id: u64, content: &str, offset: i64| {
+            let message = create_test_message(id, content);
+            Message::new(
+                message.id(),
+                ChannelId(100),
+                message.author().clone(),
+                message.content().to_string(),
+                start + chrono::Duration::minutes(offset),
+                message.kind(),
+            )
+        };
+        let markdown = MarkdownRenderer::new();
+
+        for only in [true, false] {
+            let mut data = MessagePaneData::new(true);
+            data.set_channel(ChannelId(100), "general".to_string());
+            data.set_group_start_timestamps_only(only);
+            data.set_messages(vec![at(1, "first", 0), at(2, "second", 1)]);
+            assert_eq!(data.messages[1].group, MessageGroup::Compact);
+
+            let pane = MessagePane::new(&mut data, &markdown);
+            let mut state = MessagePaneState::new();
+            let area = Rect::new(0, 0, 60, 10);
+            let mut buf = Buffer::empty(area);
+            pane.render(area, &mut buf, &mut state);
+
+            let rows = buffer_rows(&buf);
+            let row = |text: &str| {
+                rows.iter()
+                    .find(|row| row.contains(text))
+                    .expect("text should render")
+                    .clone()
+            };
+
+            assert!(row("testuser").contains("10:00"));
+            assert_eq!(row("second").contains("10:01"), !only);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_position_in_header_and_group() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use chrono::TimeZone;
+        use ratatui::widgets::StatefulWidget;
+
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let at = |id: u64, content: &str, offset: i64| {
+            let message = create_test_message(id, content);
+            Message::new(
+                message.id(),
+                ChannelId(100),
+                message.author().clone(),
+                message.content().to_string(),
+                start + chrono::Duration::minutes(offset),
+                message.kind(),
+            )
+        };
+        let markdown = MarkdownRenderer::new();
+
+        for position in [
+            TimestampPosition::Left,
+            TimestampPosition::Right,
+            TimestampPosition::Hidden,
+        ] {
+            let mut data = MessagePaneData::new(true);
+            data.set_channel(ChannelId(100), "general".to_string());
+            data.set_group_start_timestamps_only(false);
+            data.set_timestamp_position(position);
+            data.set_messages(vec![at(1, "first", 0), at(2, "second", 1)]);
+
+            let pane = MessagePane::new(&mut data, &markdown);
+            let mut state = MessagePaneState::new();
+            let area = Rect::new(0, 0, 60, 10);
+            let mut buf = Buffer::empty(area);
+            pane.render(area, &mut buf, &mut state);
+
+            // Drop the borders and scrollbar so rows hold only message text.
+            let rows: Vec<String> = (1..area.height - 1)
+                .map(|y| {
+                    (1..area.width - 2)
+                        .map(|x| buf[(x, y)].symbol().to_string())
+                        .collect()
+                })
+                .collect();
+            let row = |text: &str| {
+                rows.iter()
+                    .find(|row| row.contains(text))
+                    .expect("text should render")
+                    .trim_end()
+                    .to_string()
+            };
+
+            let (header, first, grouped) = (row("testuser"), row("first"), row("second"));
+            let column = content_indent(position);
+            assert_eq!(first.find("first"), Some(column));
+            assert_eq!(grouped.find("second"), Some(column));
+            match position {
+                TimestampPosition::Left => {
+                    assert!(header.starts_with("10:00 testuser"));
+                    assert!(grouped.starts_with("10:01 second"));
+                }
+                TimestampPosition::Right => {
+                    assert_eq!(column, NARROW_CONTENT_INDENT);
+                    assert!(header.starts_with("testuser"));
+                    assert!(header.ends_with("10:00"));
+                    assert!(grouped.ends_with("10:01"));
+                    // Right-aligned against the scrollbar margin.
+                    assert_eq!(
+                        header.width(),
+                        usize::from(area.width - 2 - SCROLLBAR_MARGIN)
+                    );
+                }
+                TimestampPosition::Hidden => {
+                    assert_eq!(column, NARROW_CONTENT_INDENT);
+                    assert!(header.starts_with("testuser"));
+                    assert!(rows.iter().all(|row| !row.contains("10:0")));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_crosspost_badges_in_header() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let markdown = MarkdownRenderer::new();
+        for (flags, badge) in [
+            (MessageFlags::CROSSPOSTED, "[PUBLISHED]"),
+            (MessageFlags::IS_CROSSPOST, "[FOLLOWED]"),
+        ] {
+            let mut data = MessagePaneData::new(true);
+            data.set_channel(ChannelId(100), "news".to_string());
+            data.set_messages(vec![create_test_message(1, "update").with_flags(flags)]);
+
+            let pane = MessagePane::new(&mut data, &markdown);
+            let mut state = MessagePaneState::new();
+            let area = Rect::new(0, 0, 80, 10);
+            let mut buf = Buffer::empty(area);
+            pane.render(area, &mut buf, &mut state);
+
+            let text = buffer_rows(&buf).concat();
+            assert!(text.contains(badge), "missing {badge}");
+        }
+    }
+
     #[test]
     fn test_embed_height_calculation_padding() {
         use crate::domain::entities::Embed;
@@ -2932,6 +4614,51 @@ mod tests {
         assert_eq!(height, 6, "Height should be 6 with padding changes");
     }
 
+    #[test]
+    fn test_hidden_link_previews_keep_rich_embeds() {
+        use crate::domain::entities::{Embed, EmbedKind};
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+
+        let embed = |title: &str, kind| {
+            let mut embed = Embed::new().with_kind(kind);
+            embed.title = Some(title.to_string());
+            embed
+        };
+        let message = create_test_message(1, "https://example.com").with_embeds(vec![
+            embed("Example Domain", EmbedKind::Link),
+            embed("News story", EmbedKind::Article),
+            embed("Build passed", EmbedKind::Rich),
+        ]);
+        let markdown = MarkdownRenderer::new();
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages(vec![message.clone()]);
+        let shown_height = MessagePane::new(&mut data, &markdown).calculate_message_height(
+            &message,
+            100,
+            &markdown,
+            Color::Yellow,
+        );
+
+        data.set_show_embeds(false);
+        data.update_layout(100, &markdown, Color::Yellow, false, true, &HashSet::new());
+        let titles: Vec<_> = data.messages[0]
+            .rendered_embeds
+            .iter()
+            .map(|embed| embed.title.concat())
+            .collect();
+        assert_eq!(titles, vec!["Build passed"]);
+
+        let hidden_height = MessagePane::new(&mut data, &markdown).calculate_message_height(
+            &message,
+            100,
+            &markdown,
+            Color::Yellow,
+        );
+        assert!(hidden_height < shown_height);
+    }
+
     #[test_case(true, 2 ; "hide blocked completely")]
     #[test_case(false, 2 ; "show placeholder")]
     fn test_blocked_user_rendering(hide_completely: bool, expected_count: usize) {
@@ -2994,4 +4721,483 @@ mod tests {
 
         assert_eq!(data.message_count(), expected_count);
     }
+
+    #[test]
+    fn test_blocked_run_expands_and_collapses() {
+        use crate::domain::entities::{RelationshipState, UserId};
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages(vec![
+            create_test_message(1, "one"),
+            create_test_message(2, "two"),
+            create_test_message(3, "three"),
+        ]);
+        let relationship_state = RelationshipState::new();
+        relationship_state.block_user(UserId(1));
+
+        let registry = CommandRegistry::default();
+        let select = KeyEvent::new(
+            crossterm::event::KeyCode::Enter,
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let mut state = MessagePaneState::new();
+        state.selected_index = Some(0);
+        let items = |state: &MessagePaneState| {
+            build_render_items(
+                data.ui_messages(),
+                Some(&relationship_state),
+                false,
+                &state.expanded_blocked_runs,
+            )
+        };
+
+        assert!(matches!(
+            items(&state)[..],
+            [RenderItem::BlockedRun {
+                count: 3,
+                start_idx: 0
+            }]
+        ));
+
+        state.handle_key(select, &data, &registry, Some(&relationship_state), false);
+        let expanded = items(&state);
+        assert_eq!(expanded.len(), 3);
+        assert!(
+            expanded
+                .iter()
+                .all(|item| matches!(item, RenderItem::BlockedMessage { run_start: 0, .. }))
+        );
+        assert!(!state.show_spoilers);
+
+        state.selected_index = Some(2);
+        state.handle_key(select, &data, &registry, Some(&relationship_state), false);
+        assert!(matches!(
+            items(&state)[..],
+            [RenderItem::BlockedRun {
+                count: 3,
+                start_idx: 0
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_expanded_blocked_run_recollapses_when_selection_leaves() {
+        use crate::domain::entities::{RelationshipState, UserId};
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let other = MessageAuthor {
+            id: "2".to_string(),
+            username: "friend".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        data.set_messages(vec![
+            create_test_message(1, "hidden words"),
+            Message::new(
+                2.into(),
+                ChannelId(100),
+                other,
+                "visible".to_string(),
+                Local::now(),
+                crate::domain::entities::MessageKind::Default,
+            ),
+        ]);
+        let relationship_state = RelationshipState::new();
+        relationship_state.block_user(UserId(1));
+        let markdown = MarkdownRenderer::new();
+
+        let mut state = MessagePaneState::new();
+        state.selected_index = Some(0);
+        let run_items = build_render_items(
+            data.ui_messages(),
+            Some(&relationship_state),
+            false,
+            &state.expanded_blocked_runs,
+        );
+        assert!(state.toggle_selected_blocked_run(&run_items, &data));
+
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        MessagePane::new(&mut data, &markdown)
+            .with_relationship_state(&relationship_state)
+            .render(area, &mut buf, &mut state);
+        assert_eq!(state.expanded_blocked_runs.len(), 1);
+        let blocked_fg = MessagePaneStyle::default().blocked_style.fg;
+        let hidden_row = buffer_rows(&buf)
+            .iter()
+            .position(|row| row.contains("hidden words"))
+            .and_then(|y| u16::try_from(y).ok());
+        let hidden_row = hidden_row.expect("expanded message content is rendered");
+        assert_eq!(buf[(area.width / 2, hidden_row)].fg, blocked_fg.unwrap());
+
+        state.selected_index = Some(1);
+        MessagePane::new(&mut data, &markdown)
+            .with_relationship_state(&relationship_state)
+            .render(area, &mut buf, &mut state);
+        assert!(state.expanded_blocked_runs.is_empty());
+    }
+
+    #[test_case(true, false ; "hidden")]
+    #[test_case(false, true ; "shown")]
+    fn test_reply_to_blocked_author_preview(hide_completely: bool, shows_quote: bool) {
+        use crate::domain::entities::{MessageReference, RelationshipState, UserId};
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let replier = MessageAuthor {
+            id: "2".to_string(),
+            username: "friend".to_string(),
+            discriminator: "0".to_string(),
+            avatar: None,
+            bot: false,
+            global_name: None,
+        };
+        let reply = Message::new(
+            2.into(),
+            ChannelId(100),
+            replier,
+            "answer".to_string(),
+            Local::now(),
+            crate::domain::entities::MessageKind::Default,
+        )
+        .with_reference(MessageReference::new(
+            Some(MessageId(1)),
+            Some(ChannelId(100)),
+            None,
+        ))
+        .with_referenced_message(Some(create_test_message(1, "secret words")));
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages(vec![reply]);
+        let relationship_state = RelationshipState::new();
+        relationship_state.block_user(UserId(1));
+
+        let markdown = MarkdownRenderer::new();
+        let mut state = MessagePaneState::new();
+        let area = Rect::new(0, 0, 80, 10);
+        let mut buf = Buffer::empty(area);
+        MessagePane::new(&mut data, &markdown)
+            .with_relationship_state(&relationship_state)
+            .with_hide_blocked_completely(hide_completely)
+            .render(area, &mut buf, &mut state);
+
+        let rendered = buffer_rows(&buf).join("\n");
+        assert_eq!(rendered.contains("secret words"), shows_quote);
+        assert_eq!(
+            rendered.contains("Replying to a blocked message"),
+            !shows_quote
+        );
+        assert!(rendered.contains("answer"));
+    }
+
+    #[test]
+    fn test_long_message_collapses_and_expands() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+
+        let content = (1..=10)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_collapse_message_lines(3);
+        data.set_messages(vec![create_test_message(1, &content)]);
+
+        let markdown = MarkdownRenderer::new();
+        let registry = CommandRegistry::default();
+        let expand = KeyEvent::new(
+            crossterm::event::KeyCode::Char('z'),
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let mut state = MessagePaneState::new();
+        state.selected_index = Some(0);
+
+        let layout = |data: &mut MessagePaneData, state: &MessagePaneState| {
+            data.update_layout(
+                100,
+                &markdown,
+                Color::Yellow,
+                false,
+                false,
+                state.expanded_messages(),
+            );
+            data.messages[0].estimated_height
+        };
+
+        // Header, three lines of content and the "show more" marker.
+        assert_eq!(layout(&mut data, &state), 5);
+        assert!(data.messages[0].collapsed);
+        let pane = MessagePane::new(&mut data, &markdown);
+        let message = create_test_message(2, &content);
+        assert_eq!(
+            pane.calculate_message_height(&message, 100, &markdown, Color::Yellow),
+            5
+        );
+
+        state.handle_key(expand, &data, &registry, None, false);
+        assert_eq!(layout(&mut data, &state), 11);
+        assert!(!data.messages[0].collapsed);
+
+        state.handle_key(expand, &data, &registry, None, false);
+        assert_eq!(layout(&mut data, &state), 5);
+    }
+
+    #[test]
+    fn test_collapse_marker_uses_pane_style() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let content = (1..=10)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_collapse_message_lines(3);
+        data.set_messages(vec![create_test_message(1, &content)]);
+
+        let markdown = MarkdownRenderer::new();
+        let marker_style = Style::default().fg(Color::Magenta);
+        let pane = MessagePane::new(&mut data, &markdown).style(MessagePaneStyle {
+            collapse_marker_style: marker_style,
+            ..MessagePaneStyle::default()
+        });
+
+        let mut state = MessagePaneState::new();
+        let area = Rect::new(0, 0, 100, 10);
+        let mut buf = Buffer::empty(area);
+        pane.render(area, &mut buf, &mut state);
+
+        let (x, y) = buffer_rows(&buf)
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| {
+                let column = row.find("show more")?;
+                let x = row[..column].chars().count();
+                Some((u16::try_from(x).ok()?, u16::try_from(y).ok()?))
+            })
+            .expect("marker rendered");
+        assert_eq!(buf[(x, y)].fg, Color::Magenta);
+    }
+
+    #[test]
+    fn test_image_placeholder_text() {
+        let img = ImageAttachment::new(
+            ImageId::new("1"),
+            "https://cdn.discordapp.com/attachments/1/2/cat.png?ex=abc".to_string(),
+            Some(800),
+            Some(600),
+        );
+        assert_eq!(
+            image_placeholder_text(&img),
+            "\u{1F5BC} [image: cat.png \u{2014} 800\u{d7}600] https://cdn.discordapp.com/attachments/1/2/cat.png?ex=abc"
+        );
+
+        let no_dims = ImageAttachment::new(
+            ImageId::new("2"),
+            "https://x.test/a.gif".to_string(),
+            None,
+            None,
+        );
+        assert_eq!(
+            image_placeholder_text(&no_dims),
+            "\u{1F5BC} [image: a.gif] https://x.test/a.gif"
+        );
+    }
+
+    #[test]
+    fn test_image_placeholder_rendered_when_previews_disabled() {
+        use crate::domain::entities::Attachment;
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let attachment = Attachment::new("1", "cat.png", 10, "https://x.test/cat.png");
+        data.set_messages(vec![
+            create_test_message(1, "look").with_attachments(vec![attachment]),
+        ]);
+
+        let markdown = MarkdownRenderer::new();
+        let pane = MessagePane::new(&mut data, &markdown)
+            .with_image_preview(false)
+            .with_image_placeholders(true);
+
+        let mut state = MessagePaneState::new();
+        let area = Rect::new(0, 0, 100, 10);
+        let mut buf = Buffer::empty(area);
+        pane.render(area, &mut buf, &mut state);
+
+        let rendered = buffer_rows(&buf).join("\n");
+        assert!(rendered.contains("[image: cat.png]"));
+    }
+
+    #[test]
+    fn test_spoiler_image_hidden_until_revealed() {
+        use crate::domain::entities::Attachment;
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let attachment = Attachment::new("1", "SPOILER_cat.png", 10, "https://x.test/cat.png");
+        data.set_messages(vec![
+            create_test_message(1, "look").with_attachments(vec![attachment]),
+        ]);
+
+        let markdown = MarkdownRenderer::new();
+        let mut state = MessagePaneState::new();
+        let area = Rect::new(0, 0, 100, 10);
+        let render = |data: &mut MessagePaneData, state: &mut MessagePaneState| {
+            let mut buf = Buffer::empty(area);
+            MessagePane::new(data, &markdown)
+                .with_image_preview(false)
+                .with_image_placeholders(true)
+                .render(area, &mut buf, state);
+            buffer_rows(&buf).join("\n")
+        };
+
+        let rendered = render(&mut data, &mut state);
+        assert!(rendered.contains("[SPOILER image]"));
+        assert!(!rendered.contains("cat.png"));
+
+        state.toggle_spoiler();
+        let rendered = render(&mut data, &mut state);
+        assert!(!rendered.contains("[SPOILER image]"));
+        assert!(rendered.contains("[image: cat.png]"));
+    }
+
+    #[test]
+    fn test_loading_spinner_animates_until_loaded() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let render = |data: &mut MessagePaneData, state: &mut MessagePaneState| {
+            let markdown = MarkdownRenderer::new();
+            let area = Rect::new(0, 0, 60, 6);
+            let mut buf = Buffer::empty(area);
+            MessagePane::new(data, &markdown).render(area, &mut buf, state);
+            buffer_rows(&buf).join("\n")
+        };
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        let mut state = MessagePaneState::new();
+        assert!(!state.tick_spinner(Duration::from_millis(500), data.is_loading()));
+        assert!(render(&mut data, &mut state).contains("Loading messages..."));
+        assert_eq!(state.spinner(), None);
+
+        state.set_animate_spinner(true);
+        assert!(!state.tick_spinner(Duration::from_millis(50), data.is_loading()));
+        assert_eq!(state.spinner(), Some(LOADING_SPINNER[0]));
+        assert!(state.tick_spinner(Duration::from_millis(50), data.is_loading()));
+        assert_eq!(state.spinner(), Some(LOADING_SPINNER[1]));
+        assert!(render(&mut data, &mut state).contains("⠙ Loading messages..."));
+
+        data.set_messages(vec![create_test_message(1, "hello")]);
+        data.set_loading_more(true);
+        state.tick_spinner(Duration::from_millis(100), data.is_loading());
+        assert!(render(&mut data, &mut state).contains("⠹ Loading more..."));
+
+        data.set_loading_more(false);
+        assert!(!state.tick_spinner(Duration::from_millis(100), data.is_loading()));
+        assert_eq!(state.spinner(), Some(LOADING_SPINNER[0]));
+        assert!(!render(&mut data, &mut state).contains("Loading"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_animated_avatar_indicator_when_animations_off() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use ratatui::widgets::StatefulWidget;
+
+        let render = |data: &mut MessagePaneData, state: &mut MessagePaneState| {
+            let markdown = MarkdownRenderer::new();
+            let area = Rect::new(0, 0, 60, 10);
+            let mut buf = Buffer::empty(area);
+            MessagePane::new(data, &markdown).render(area, &mut buf, state);
+            buffer_rows(&buf).join("\n")
+        };
+
+        let message = |id: u64, username: &str, avatar: &str| {
+            let author = MessageAuthor {
+                id: id.to_string(),
+                username: username.to_string(),
+                discriminator: "0".to_string(),
+                avatar: Some(avatar.to_string()),
+                bot: false,
+                global_name: None,
+            };
+            Message::new(
+                id.into(),
+                ChannelId(100),
+                author,
+                "hello".to_string(),
+                Local::now(),
+                crate::domain::entities::MessageKind::Default,
+            )
+        };
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages(vec![
+            message(1, "animated", "a_abcdef"),
+            message(2, "still", "abcdef"),
+        ]);
+        let mut state = MessagePaneState::new();
+
+        let rows = render(&mut data, &mut state);
+        assert!(rows.contains("animated [GIF]"));
+        assert!(rows.contains("still"));
+        assert_eq!(rows.matches("[GIF]").count(), 1);
+
+        state.set_animate_spinner(true);
+        assert!(!render(&mut data, &mut state).contains("[GIF]"));
+    }
+
+    #[test]
+    fn test_unseen_messages_indicator_until_jump_to_latest() {
+        use crate::presentation::services::markdown_renderer::MarkdownRenderer;
+        use crossterm::event::{KeyCode, KeyModifiers};
+        use ratatui::widgets::StatefulWidget;
+
+        let render = |data: &mut MessagePaneData, state: &mut MessagePaneState| {
+            let markdown = MarkdownRenderer::new();
+            let area = Rect::new(0, 0, 60, 8);
+            let mut buf = Buffer::empty(area);
+            MessagePane::new(data, &markdown).render(area, &mut buf, state);
+            buffer_rows(&buf).join("\n")
+        };
+
+        let mut data = MessagePaneData::new(true);
+        data.set_channel(ChannelId(100), "general".to_string());
+        data.set_messages((1..=10).map(|i| create_test_message(i, "hello")).collect());
+        let mut state = MessagePaneState::new();
+        render(&mut data, &mut state);
+
+        data.add_message(create_test_message(11, "while following"));
+        state.on_new_message();
+        assert_eq!(state.unseen_messages(), 0);
+
+        state.scroll_up();
+        for id in 12..14 {
+            data.add_message(create_test_message(id, "missed"));
+            state.on_new_message();
+        }
+        assert_eq!(state.unseen_messages(), 2);
+        assert!(render(&mut data, &mut state).contains("↓ 2 new messages"));
+
+        let registry = CommandRegistry::default();
+        let jump = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE);
+        state.handle_key(jump, &data, &registry, None, false);
+        assert_eq!(state.unseen_messages(), 0);
+        assert!(state.flags.is_following);
+        assert!(!render(&mut data, &mut state).contains("new message"));
+    }
 }
@@ -1,9 +1,11 @@
 mod channel_header;
+mod code_block_chooser;
 mod confirmation_modal;
 mod file_explorer;
 mod footer_bar;
 mod guilds_tree;
 mod header_bar;
+mod image_self_test;
 #[cfg(feature = "image")]
 mod image_state;
 #[cfg(not(feature = "image"))]
@@ -15,14 +17,16 @@ mod message_pane;
 mod status_bar;
 
 pub use channel_header::{ChannelHeader, ChannelHeaderStyle};
+pub use code_block_chooser::{CodeBlockChooser, CodeBlockChooserState};
 pub use confirmation_modal::ConfirmationModal;
 pub use file_explorer::{FileExplorerAction, FileExplorerComponent};
 pub use footer_bar::{FocusContext, FooterBar, FooterBarStyle};
 pub use guilds_tree::{
     GuildsTree, GuildsTreeAction, GuildsTreeData, GuildsTreeState, GuildsTreeStyle,
-    SortedGuildChannels, TreeNodeId,
+    SortedGuildChannels, TreeNodeId, VoiceParticipant,
 };
 pub use header_bar::{HeaderBar, HeaderBarStyle};
+pub use image_self_test::{ImageSelfTestReport, SELF_TEST_IMAGE};
 #[cfg(feature = "image")]
 pub use image_state::{ImageAttachment, ImageManager, LOAD_BUFFER, MAX_IMAGE_HEIGHT};
 #[cfg(not(feature = "image"))]
@@ -34,6 +38,6 @@ pub use message_input::{
 };
 pub use message_pane::{
     ForumState, LoadingState, MessagePane, MessagePaneAction, MessagePaneData, MessagePaneState,
-    MessagePaneStyle, UiMessage, ViewMode,
+    MessagePaneStyle, SendState, UiMessage, ViewMode,
 };
 pub use status_bar::{StatusBar, StatusLevel};